@@ -0,0 +1,37 @@
+//! Generate a SeaC program from the fuzzer's bytes, and check that the frontend accepts it
+//!
+//! Programs come from `sea_c::grammar`, so every input is syntactically valid and the
+//! fuzzer spends its time past the parser: every program is also resolved and
+//! type-checked. Generated programs rarely declare the names they use or agree on types,
+//! so those passes report errors, but they must never panic. Lowering and optimization
+//! will join this target once `sea-ir` and `sea-opt` can take a SeaC program.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sea_lex::SourceDb;
+use sea_parse::{ByteChoices, Generator};
+use sea_type::{DataLayout, TypeInterner};
+use std::sync::OnceLock;
+
+/// The generator, built once per process
+fn generator() -> &'static Generator<sea_c::Token> {
+    static GENERATOR: OnceLock<Generator<sea_c::Token>> = OnceLock::new();
+    GENERATOR.get_or_init(|| {
+        Generator::new(sea_c::grammar())
+            .expect("SeaC's grammar is valid")
+            .max_depth(16)
+            .max_tokens(512)
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let source = generator().generate_source(&mut ByteChoices::new(data), sea_c::spell);
+    let mut db = SourceDb::new();
+    let file = db.add("fuzz.c", &source);
+    let program = sea_c::parse(file, &source)
+        .unwrap_or_else(|errors| panic!("generated program doesn't parse: {errors:?}\n{source}"));
+    let _ = sea_c::resolve(&program);
+    let mut types = TypeInterner::new();
+    let _ = sea_c::check(&mut types, &DataLayout::X86_64_SYSV, &program);
+});
@@ -0,0 +1,473 @@
+//! The SeaC abstract syntax tree
+//!
+//! Every node carries the [`Span`] it was parsed from. Types are kept exactly as
+//! written, for the type checker to resolve.
+
+use sea_lex::Span;
+use sea_parse::{Fold, Visit};
+use std::fmt;
+
+/// A whole source file
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Program {
+    /// The top-level items, in order
+    pub items: Vec<Item>,
+}
+
+/// A top-level item
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub enum Item {
+    /// A struct definition
+    Struct(StructDef),
+    /// A function definition or declaration
+    Function(Function),
+    /// Global variables, declared together: `int a = 1, *b;`
+    Global(Vec<VarDecl>),
+    /// An item that couldn't be parsed
+    Error(Span),
+}
+
+/// A name, with its location
+#[derive(Debug, Clone, PartialEq, Eq, Visit, Fold)]
+pub struct Ident {
+    /// The name
+    pub name: String,
+    /// The location of the name
+    pub span: Span,
+}
+
+/// A struct definition: `struct Name { fields };`
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct StructDef {
+    /// The struct's name
+    pub name: Ident,
+    /// The struct's fields, in order
+    pub fields: Vec<Field>,
+    /// The location of the definition
+    pub span: Span,
+}
+
+/// A struct field: `type name;`
+///
+/// Fields declared together, as in `int x, y;`, are separate fields.
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Field {
+    /// The field's type
+    pub ty: Type,
+    /// The field's name
+    pub name: Ident,
+    /// The location of the field, without the type for any but the first field declared
+    /// together
+    pub span: Span,
+}
+
+/// A function definition or, without a body, a declaration
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Function {
+    /// The return type
+    pub return_type: Type,
+    /// The function's name
+    pub name: Ident,
+    /// The parameters, in order (empty for `(void)`)
+    pub params: Vec<Param>,
+    /// The body, or `None` for a declaration
+    pub body: Option<Block>,
+    /// The location of the function
+    pub span: Span,
+}
+
+/// A function parameter
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Param {
+    /// The parameter's type
+    pub ty: Type,
+    /// The parameter's name, which declarations may omit
+    pub name: Option<Ident>,
+    /// The location of the parameter
+    pub span: Span,
+}
+
+/// A type, as written
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Type {
+    /// The kind of type
+    pub kind: TypeKind,
+    /// The location of the type
+    pub span: Span,
+}
+
+/// The kinds of types
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub enum TypeKind {
+    /// A built-in type
+    Primitive(Primitive),
+    /// `struct Name`
+    Struct(Ident),
+    /// `T*`
+    Pointer(Box<Type>),
+    /// `T name[n]`, or `T name[]` without a length
+    Array(Box<Type>, Option<u64>),
+}
+
+/// The built-in types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Visit, Fold)]
+pub enum Primitive {
+    /// `void`
+    Void,
+    /// `bool`
+    Bool,
+    /// `char`
+    Char,
+    /// `short`
+    Short,
+    /// `int`
+    Int,
+    /// `long`
+    Long,
+    /// `unsigned char`
+    UChar,
+    /// `unsigned short`
+    UShort,
+    /// `unsigned int`, or just `unsigned`
+    UInt,
+    /// `unsigned long`
+    ULong,
+    /// `float`
+    Float,
+    /// `double`
+    Double,
+}
+
+/// A variable declaration: `type name = init`
+///
+/// A declaration like `int a = 1, *b;` declares each of its variables with the base type
+/// it starts with, and the stars and array dimensions of the variable's own declarator.
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct VarDecl {
+    /// The variable's type, including any pointers and array dimensions
+    pub ty: Type,
+    /// The variable's name
+    pub name: Ident,
+    /// The initial value
+    pub init: Option<Expr>,
+    /// The location of the declaration, without the base type for any but the first
+    /// variable declared together
+    pub span: Span,
+}
+
+/// A block: `{ stmts }`
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Block {
+    /// The statements, in order
+    pub stmts: Vec<Stmt>,
+    /// The location of the block, including its braces
+    pub span: Span,
+}
+
+/// A statement
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Stmt {
+    /// The kind of statement
+    pub kind: StmtKind,
+    /// The location of the statement
+    pub span: Span,
+}
+
+/// The kinds of statements
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub enum StmtKind {
+    /// A nested block
+    Block(Block),
+    /// A local variable declaration, of one or more variables
+    Decl(Vec<VarDecl>),
+    /// An expression evaluated for its effects: `expr;`
+    Expr(Expr),
+    /// `if (cond) then else otherwise`
+    If {
+        /// The condition
+        cond: Expr,
+        /// The statement run if the condition holds
+        then: Box<Stmt>,
+        /// The statement run otherwise, if any
+        otherwise: Option<Box<Stmt>>,
+    },
+    /// `while (cond) body`
+    While {
+        /// The condition, checked before each iteration
+        cond: Expr,
+        /// The loop body
+        body: Box<Stmt>,
+    },
+    /// `do body while (cond);`
+    DoWhile {
+        /// The loop body
+        body: Box<Stmt>,
+        /// The condition, checked after each iteration
+        cond: Expr,
+    },
+    /// `for (init; cond; step) body`
+    For {
+        /// The initializer: a declaration or expression statement
+        init: Option<Box<Stmt>>,
+        /// The condition, if any
+        cond: Option<Expr>,
+        /// The expression evaluated after each iteration, if any
+        step: Option<Expr>,
+        /// The loop body
+        body: Box<Stmt>,
+    },
+    /// `return value;`
+    Return(Option<Expr>),
+    /// `break;`
+    Break,
+    /// `continue;`
+    Continue,
+    /// `;`
+    Empty,
+    /// A statement that couldn't be parsed
+    Error,
+}
+
+/// An expression
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Expr {
+    /// The kind of expression
+    pub kind: ExprKind,
+    /// The location of the expression
+    pub span: Span,
+}
+
+/// The kinds of expressions
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub enum ExprKind {
+    /// An integer literal
+    Int(u64),
+    /// A floating-point literal
+    Float(f64),
+    /// A character literal
+    Char(u8),
+    /// A string literal
+    String(String),
+    /// `true` or `false`
+    Bool(bool),
+    /// A variable or function name
+    Var(Ident),
+    /// A prefix or postfix operator
+    Unary(UnaryOp, Box<Expr>),
+    /// A binary operator
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// `lhs = rhs`, or a compound assignment like `lhs += rhs`
+    Assign(Option<BinaryOp>, Box<Expr>, Box<Expr>),
+    /// `cond ? then : otherwise`
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// `lhs, rhs`: evaluates `lhs` for its effects, then `rhs` for the value
+    Comma(Box<Expr>, Box<Expr>),
+    /// `callee(args)`
+    Call(Box<Expr>, Vec<Expr>),
+    /// `base[index]`
+    Index(Box<Expr>, Box<Expr>),
+    /// `base.field`
+    Member(Box<Expr>, Ident),
+    /// `base->field`
+    PtrMember(Box<Expr>, Ident),
+    /// `(type) expr`
+    Cast(Type, Box<Expr>),
+    /// `sizeof(type)`
+    SizeofType(Type),
+    /// `sizeof expr`
+    SizeofExpr(Box<Expr>),
+}
+
+/// Prefix and postfix operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Visit, Fold)]
+pub enum UnaryOp {
+    /// `-x`
+    Neg,
+    /// `+x`
+    Plus,
+    /// `!x`
+    Not,
+    /// `~x`
+    BitNot,
+    /// `*x`
+    Deref,
+    /// `&x`
+    AddrOf,
+    /// `++x`
+    PreInc,
+    /// `--x`
+    PreDec,
+    /// `x++`
+    PostInc,
+    /// `x--`
+    PostDec,
+}
+
+/// Binary operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Visit, Fold)]
+pub enum BinaryOp {
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `%`
+    Rem,
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `<<`
+    Shl,
+    /// `>>`
+    Shr,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEq,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEq,
+    /// `==`
+    Eq,
+    /// `!=`
+    NotEq,
+    /// `&`
+    BitAnd,
+    /// `^`
+    BitXor,
+    /// `|`
+    BitOr,
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+}
+
+impl BinaryOp {
+    /// The operator's symbol
+    #[must_use]
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Rem => "%",
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+            Self::Less => "<",
+            Self::LessEq => "<=",
+            Self::Greater => ">",
+            Self::GreaterEq => ">=",
+            Self::Eq => "==",
+            Self::NotEq => "!=",
+            Self::BitAnd => "&",
+            Self::BitXor => "^",
+            Self::BitOr => "|",
+            Self::And => "&&",
+            Self::Or => "||",
+        }
+    }
+}
+
+impl UnaryOp {
+    /// The operator's symbol
+    #[must_use]
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Neg => "-",
+            Self::Plus => "+",
+            Self::Not => "!",
+            Self::BitNot => "~",
+            Self::Deref => "*",
+            Self::AddrOf => "&",
+            Self::PreInc | Self::PostInc => "++",
+            Self::PreDec | Self::PostDec => "--",
+        }
+    }
+
+    /// Reports whether the operator is written after its operand
+    #[must_use]
+    pub const fn is_postfix(self) -> bool {
+        matches!(self, Self::PostInc | Self::PostDec)
+    }
+}
+
+impl Primitive {
+    /// The type's name, as written
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Void => "void",
+            Self::Bool => "bool",
+            Self::Char => "char",
+            Self::Short => "short",
+            Self::Int => "int",
+            Self::Long => "long",
+            Self::UChar => "unsigned char",
+            Self::UShort => "unsigned short",
+            Self::UInt => "unsigned int",
+            Self::ULong => "unsigned long",
+            Self::Float => "float",
+            Self::Double => "double",
+        }
+    }
+}
+
+/// Types are written in a C-like, but unambiguous, way: `int*[4]` is an array of four
+/// pointers to `int`
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TypeKind::Primitive(primitive) => f.write_str(primitive.name()),
+            TypeKind::Struct(name) => write!(f, "struct {}", name.name),
+            TypeKind::Pointer(pointee) => write!(f, "{pointee}*"),
+            TypeKind::Array(element, Some(length)) => write!(f, "{element}[{length}]"),
+            TypeKind::Array(element, None) => write!(f, "{element}[]"),
+        }
+    }
+}
+
+/// Expressions are written with every operation parenthesized, to show their structure
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ExprKind::Int(value) => write!(f, "{value}"),
+            ExprKind::Float(value) => write!(f, "{value:?}"),
+            ExprKind::Char(value) => write!(f, "{:?}", char::from(*value)),
+            ExprKind::String(value) => write!(f, "{value:?}"),
+            ExprKind::Bool(value) => write!(f, "{value}"),
+            ExprKind::Var(name) => f.write_str(&name.name),
+            ExprKind::Unary(op, operand) if op.is_postfix() => {
+                write!(f, "({operand}{})", op.symbol())
+            }
+            ExprKind::Unary(op, operand) => write!(f, "({}{operand})", op.symbol()),
+            ExprKind::Binary(op, lhs, rhs) => write!(f, "({lhs} {} {rhs})", op.symbol()),
+            ExprKind::Assign(op, lhs, rhs) => {
+                let op = op.map_or("", BinaryOp::symbol);
+                write!(f, "({lhs} {op}= {rhs})")
+            }
+            ExprKind::Conditional(cond, then, otherwise) => {
+                write!(f, "({cond} ? {then} : {otherwise})")
+            }
+            ExprKind::Comma(lhs, rhs) => write!(f, "({lhs}, {rhs})"),
+            ExprKind::Call(callee, args) => {
+                write!(f, "{callee}(")?;
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{arg}")?;
+                }
+                f.write_str(")")
+            }
+            ExprKind::Index(base, index) => write!(f, "{base}[{index}]"),
+            ExprKind::Member(base, field) => write!(f, "{base}.{}", field.name),
+            ExprKind::PtrMember(base, field) => write!(f, "{base}->{}", field.name),
+            ExprKind::Cast(ty, operand) => write!(f, "(({ty}) {operand})"),
+            ExprKind::SizeofType(ty) => write!(f, "sizeof({ty})"),
+            ExprKind::SizeofExpr(operand) => write!(f, "(sizeof {operand})"),
+        }
+    }
+}
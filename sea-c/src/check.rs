@@ -0,0 +1,1391 @@
+//! Type checking SeaC programs
+
+use crate::ast::{
+    BinaryOp, Block, Expr, ExprKind, Function, Ident, Item, Primitive, Program, Stmt, StmtKind,
+    StructDef, Type as CType, TypeKind, UnaryOp, VarDecl,
+};
+use crate::typed::{
+    Conversion, TypedBlock, TypedExpr, TypedExprKind, TypedFunction, TypedItem, TypedParam,
+    TypedProgram, TypedStmt, TypedStmtKind, TypedVar,
+};
+use sea_lex::Span;
+use sea_type::{DataLayout, FloatKind, Layout, Repr, StructLayout, Type, TypeId, TypeInterner};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Type-check a SeaC program for the `target`, interning its types in `types`
+///
+/// Returns the [typed program](TypedProgram), along with every type error found, in
+/// source order. Expressions with errors have the type [`TypeId::BOTTOM`], and don't
+/// cause more errors in the expressions around them. Names that don't resolve are also
+/// given that type, without an error of their own, since [`resolve`](crate::resolve) reports
+/// them.
+///
+/// `char`, `short`, and `int` have 8, 16, and 32 bits, and `long` has as many as the
+/// target says, as does `size_t`, the type of `sizeof`. Plain `char` is signed if the
+/// target says so, `bool` is a 1-bit unsigned integer, and `void` is the empty tuple.
+/// Pointers are nullable, and structs are laid out with [`Repr::C`].
+///
+/// ```
+/// # use sea_lex::SourceDb;
+/// # use sea_type::{DataLayout, TypeInterner};
+/// let source = "struct s { char c; long l; }; long f(struct s *p) { return p->c + 1; }";
+/// let file = SourceDb::new().add("example.c", source);
+/// let program = sea_c::parse(file, source).unwrap();
+/// let mut types = TypeInterner::new();
+/// let (typed, errors) = sea_c::check(&mut types, &DataLayout::X86_64_SYSV, &program);
+/// assert!(errors.is_empty());
+/// assert_eq!(typed.layouts["s"].offsets, [0, 8]);
+/// let (typed, _) = sea_c::check(&mut types, &DataLayout::WASM32, &program);
+/// assert_eq!(typed.layouts["s"].offsets, [0, 4]);
+/// ```
+pub fn check(
+    types: &mut TypeInterner,
+    target: &DataLayout,
+    program: &Program,
+) -> (TypedProgram, Vec<TypeError>) {
+    let void = types.tuple([]);
+    let mut checker = Checker {
+        types,
+        target,
+        void,
+        scopes: vec![HashMap::new()],
+        layouts: HashMap::new(),
+        return_type: void,
+        loops: 0,
+        errors: Vec::new(),
+    };
+    for item in &program.items {
+        match item {
+            Item::Struct(def) => checker.define_struct(def),
+            Item::Function(function) => checker.declare_function(function),
+            Item::Global(decls) => {
+                for decl in decls {
+                    let ty = checker.var_type(decl);
+                    checker.declare(&decl.name, ty);
+                }
+            }
+            Item::Error(_) => {}
+        }
+    }
+    let mut items = Vec::new();
+    for item in &program.items {
+        match item {
+            Item::Function(function) => items.push(TypedItem::Function(checker.function(function))),
+            Item::Global(decls) => {
+                let vars = decls
+                    .iter()
+                    .map(|decl| {
+                        let ty = checker.lookup(&decl.name.name);
+                        let init = decl.init.as_ref().map(|init| checker.init(init, ty));
+                        TypedVar {
+                            name: decl.name.clone(),
+                            ty,
+                            init,
+                            span: decl.span,
+                        }
+                    })
+                    .collect();
+                items.push(TypedItem::Global(vars));
+            }
+            Item::Struct(_) | Item::Error(_) => {}
+        }
+    }
+    let Checker {
+        layouts,
+        mut errors,
+        ..
+    } = checker;
+    errors.sort_by_key(|error| error.span);
+    (TypedProgram { items, layouts }, errors)
+}
+
+/// The state of type checking a program
+struct Checker<'a> {
+    /// The types, interned
+    types: &'a mut TypeInterner,
+    /// The target's sizes and alignments
+    target: &'a DataLayout,
+    /// The `void` type
+    void: TypeId,
+    /// The types of the variables and functions in scope, innermost last
+    scopes: Vec<HashMap<String, TypeId>>,
+    /// The layouts of the structs defined so far
+    layouts: HashMap<String, StructLayout>,
+    /// The return type of the function being checked
+    return_type: TypeId,
+    /// The number of loops around the statement being checked
+    loops: usize,
+    /// The errors found so far
+    errors: Vec<TypeError>,
+}
+
+impl Checker<'_> {
+    /// Record an error
+    fn error(&mut self, span: Span, kind: TypeErrorKind) {
+        self.errors.push(TypeError { span, kind });
+    }
+
+    /// Get a type's name, as it's written in SeaC
+    fn name(&self, ty: TypeId) -> String {
+        match self.types.get(ty) {
+            Type::Int {
+                bits: 1,
+                signed: false,
+            } => "bool".to_string(),
+            &Type::Int { bits, signed } => {
+                let name = match bits {
+                    8 => "char",
+                    16 => "short",
+                    32 => "int",
+                    64 => "long",
+                    _ => return format!("{}{bits}", if signed { 'i' } else { 'u' }),
+                };
+                if signed {
+                    name.to_string()
+                } else {
+                    format!("unsigned {name}")
+                }
+            }
+            Type::Float(FloatKind::F32) => "float".to_string(),
+            Type::Float(FloatKind::F64) => "double".to_string(),
+            Type::Tuple(elements) if elements.is_empty() => "void".to_string(),
+            Type::Pointer { pointee, .. } => format!("{}*", self.name(*pointee)),
+            Type::Array {
+                element,
+                length: Some(length),
+            } => format!("{}[{length}]", self.name(*element)),
+            Type::Array {
+                element,
+                length: None,
+            } => format!("{}[]", self.name(*element)),
+            Type::Struct(name) => format!("struct {name}"),
+            Type::Function { params, ret } => {
+                let params: Vec<_> = params.iter().map(|&param| self.name(param)).collect();
+                format!("{}({})", self.name(*ret), params.join(", "))
+            }
+            _ => self.types.display(ty).to_string(),
+        }
+    }
+
+    /// Get the interned type of a primitive
+    fn primitive(&mut self, primitive: Primitive) -> TypeId {
+        let (bits, signed) = match primitive {
+            Primitive::Void => return self.void,
+            Primitive::Float => return self.types.float(FloatKind::F32),
+            Primitive::Double => return self.types.float(FloatKind::F64),
+            Primitive::Bool => (1, false),
+            Primitive::Char => (8, self.target.char_signed),
+            Primitive::Short => (16, true),
+            Primitive::Int => (32, true),
+            Primitive::Long => (self.target.long_bits, true),
+            Primitive::UChar => (8, false),
+            Primitive::UShort => (16, false),
+            Primitive::UInt => (32, false),
+            Primitive::ULong => (self.target.long_bits, false),
+        };
+        self.types.int(bits, signed)
+    }
+
+    /// Get the interned type of a type as written
+    fn resolve_type(&mut self, ty: &CType) -> TypeId {
+        match &ty.kind {
+            TypeKind::Primitive(primitive) => self.primitive(*primitive),
+            TypeKind::Struct(name) => self.types.struct_type(&name.name),
+            TypeKind::Pointer(pointee) => {
+                let pointee = self.resolve_type(pointee);
+                self.types.pointer(pointee, true)
+            }
+            TypeKind::Array(element, length) => {
+                let element = self.resolve_type(element);
+                self.types.array(element, *length)
+            }
+        }
+    }
+
+    /// Get a type's size and alignment in bytes, or `None` if it's incomplete
+    fn size_align(&self, ty: TypeId) -> Option<(u64, u64)> {
+        match self.types.get(ty) {
+            Type::Array {
+                element,
+                length: Some(length),
+            } => {
+                let (size, align) = self.size_align(*element)?;
+                Some((size * length, align))
+            }
+            Type::Struct(name) => self
+                .layouts
+                .get(name)
+                .map(|layout| (layout.size, layout.align)),
+            Type::Int { .. } | Type::Float(_) | Type::Pointer { .. } => self
+                .target
+                .layout(self.types, ty)
+                .map(|layout| (layout.size, layout.align)),
+            _ => None,
+        }
+    }
+
+    /// Get the bits and signedness of an integer type
+    fn int_info(&self, ty: TypeId) -> Option<(u8, bool)> {
+        match *self.types.get(ty) {
+            Type::Int { bits, signed } => Some((bits, signed)),
+            _ => None,
+        }
+    }
+
+    /// Reports whether a type is an integer or floating-point type
+    fn is_arithmetic(&self, ty: TypeId) -> bool {
+        matches!(self.types.get(ty), Type::Int { .. } | Type::Float(_))
+    }
+
+    /// Reports whether a type is a floating-point type
+    fn is_float(&self, ty: TypeId) -> bool {
+        matches!(self.types.get(ty), Type::Float(_))
+    }
+
+    /// Get the type a pointer type points to
+    fn pointee(&self, ty: TypeId) -> Option<TypeId> {
+        match *self.types.get(ty) {
+            Type::Pointer { pointee, .. } => Some(pointee),
+            _ => None,
+        }
+    }
+
+    /// Reports whether a type is arithmetic or a pointer
+    fn is_scalar(&self, ty: TypeId) -> bool {
+        self.is_arithmetic(ty) || self.pointee(ty).is_some()
+    }
+
+    /// Reports whether a type is a function type
+    fn is_function(&self, ty: TypeId) -> bool {
+        matches!(self.types.get(ty), Type::Function { .. })
+    }
+
+    /// Declare a name in the innermost scope
+    fn declare(&mut self, name: &Ident, ty: TypeId) {
+        self.scopes
+            .last_mut()
+            .expect("there's always a scope")
+            .insert(name.name.clone(), ty);
+    }
+
+    /// Get the type of a name in scope, or [`TypeId::BOTTOM`] if it isn't declared
+    fn lookup(&self, name: &str) -> TypeId {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+            .unwrap_or(TypeId::BOTTOM)
+    }
+
+    /// Define a struct's fields, and lay it out
+    fn define_struct(&mut self, def: &StructDef) {
+        let mut fields = Vec::new();
+        let mut layouts = Vec::new();
+        for field in &def.fields {
+            let ty = self.resolve_type(&field.ty);
+            let (size, align) = self.size_align(ty).unwrap_or_else(|| {
+                let ty = self.name(ty);
+                self.error(field.ty.span, TypeErrorKind::Incomplete { ty });
+                (0, 1)
+            });
+            layouts.push(Layout { size, align });
+            fields.push((field.name.name.clone(), ty));
+        }
+        self.types.define_struct(&def.name.name, fields);
+        let layout = self.target.struct_layout(layouts, Repr::C);
+        self.layouts.insert(def.name.name.clone(), layout);
+    }
+
+    /// Get a function's type, adjusting array parameters to pointers
+    fn function_type(&mut self, function: &Function) -> (TypeId, Vec<TypedParam>) {
+        let ret = self.resolve_type(&function.return_type);
+        let params: Vec<_> = function
+            .params
+            .iter()
+            .map(|param| {
+                let mut ty = self.resolve_type(&param.ty);
+                if let Type::Array { element, .. } = *self.types.get(ty) {
+                    ty = self.types.pointer(element, true);
+                }
+                TypedParam {
+                    name: param.name.clone(),
+                    ty,
+                    span: param.span,
+                }
+            })
+            .collect();
+        let ty = self
+            .types
+            .function(params.iter().map(|param| param.ty), ret);
+        (ty, params)
+    }
+
+    /// Declare a function, checking that it matches any earlier declaration
+    ///
+    /// The parser reads a lone `(void)` as no parameters, so any `void` parameter left is
+    /// an error.
+    fn declare_function(&mut self, function: &Function) {
+        let (ty, params) = self.function_type(function);
+        for (param, typed) in function.params.iter().zip(&params) {
+            if typed.ty == self.void {
+                self.error(param.ty.span, TypeErrorKind::VoidParameter);
+            }
+        }
+        let previous = self.lookup(&function.name.name);
+        if previous != TypeId::BOTTOM && previous != ty {
+            let (expected, found) = (self.name(previous), self.name(ty));
+            self.error(
+                function.name.span,
+                TypeErrorKind::Mismatch { expected, found },
+            );
+        }
+        self.declare(&function.name, ty);
+    }
+
+    /// Check a function's body
+    fn function(&mut self, function: &Function) -> TypedFunction {
+        let (ty, params) = self.function_type(function);
+        let Type::Function { ret, .. } = *self.types.get(ty) else {
+            unreachable!("function types are functions");
+        };
+        self.return_type = ret;
+        let body = function.body.as_ref().map(|body| {
+            self.scopes.push(HashMap::new());
+            for param in &params {
+                if let Some(name) = &param.name {
+                    self.declare(name, param.ty);
+                }
+            }
+            let stmts = body.stmts.iter().map(|stmt| self.stmt(stmt)).collect();
+            self.scopes.pop();
+            TypedBlock {
+                stmts,
+                span: body.span,
+            }
+        });
+        TypedFunction {
+            name: function.name.clone(),
+            ty,
+            params,
+            body,
+            span: function.span,
+        }
+    }
+
+    /// Get a variable's type, reporting an error if it's incomplete
+    fn var_type(&mut self, decl: &VarDecl) -> TypeId {
+        let ty = self.resolve_type(&decl.ty);
+        if self.size_align(ty).is_none() {
+            let name = self.name(ty);
+            self.error(decl.ty.span, TypeErrorKind::Incomplete { ty: name });
+        }
+        ty
+    }
+
+    /// Check a variable's initial value
+    fn init(&mut self, init: &Expr, ty: TypeId) -> TypedExpr {
+        let init = self.value(init);
+        self.convert(init, ty)
+    }
+
+    /// Check a block, in a new scope
+    fn block(&mut self, block: &Block) -> TypedBlock {
+        self.scopes.push(HashMap::new());
+        let stmts = block.stmts.iter().map(|stmt| self.stmt(stmt)).collect();
+        self.scopes.pop();
+        TypedBlock {
+            stmts,
+            span: block.span,
+        }
+    }
+
+    /// Check a statement that may declare a variable, in a new scope
+    fn nested(&mut self, stmt: &Stmt) -> Box<TypedStmt> {
+        self.scopes.push(HashMap::new());
+        let stmt = self.stmt(stmt);
+        self.scopes.pop();
+        Box::new(stmt)
+    }
+
+    /// Check the body of a loop, where `break` and `continue` can be used
+    fn loop_body(&mut self, body: &Stmt) -> Box<TypedStmt> {
+        self.loops += 1;
+        let body = self.nested(body);
+        self.loops -= 1;
+        body
+    }
+
+    /// Check a statement
+    fn stmt(&mut self, stmt: &Stmt) -> TypedStmt {
+        let kind = match &stmt.kind {
+            StmtKind::Block(block) => TypedStmtKind::Block(self.block(block)),
+            StmtKind::Decl(decls) => TypedStmtKind::Decl(
+                decls
+                    .iter()
+                    .map(|decl| {
+                        let ty = self.var_type(decl);
+                        self.declare(&decl.name, ty);
+                        let init = decl.init.as_ref().map(|init| self.init(init, ty));
+                        TypedVar {
+                            name: decl.name.clone(),
+                            ty,
+                            init,
+                            span: decl.span,
+                        }
+                    })
+                    .collect(),
+            ),
+            StmtKind::Expr(expr) => TypedStmtKind::Expr(self.value(expr)),
+            StmtKind::If {
+                cond,
+                then,
+                otherwise,
+            } => TypedStmtKind::If {
+                cond: self.condition(cond),
+                then: self.nested(then),
+                otherwise: otherwise.as_ref().map(|otherwise| self.nested(otherwise)),
+            },
+            StmtKind::While { cond, body } => TypedStmtKind::While {
+                cond: self.condition(cond),
+                body: self.loop_body(body),
+            },
+            StmtKind::DoWhile { body, cond } => TypedStmtKind::DoWhile {
+                body: self.loop_body(body),
+                cond: self.condition(cond),
+            },
+            StmtKind::For {
+                init,
+                cond,
+                step,
+                body,
+            } => {
+                self.scopes.push(HashMap::new());
+                let kind = TypedStmtKind::For {
+                    init: init.as_ref().map(|init| Box::new(self.stmt(init))),
+                    cond: cond.as_ref().map(|cond| self.condition(cond)),
+                    step: step.as_ref().map(|step| self.value(step)),
+                    body: self.loop_body(body),
+                };
+                self.scopes.pop();
+                kind
+            }
+            StmtKind::Return(value) => TypedStmtKind::Return(self.ret(value.as_ref(), stmt.span)),
+            StmtKind::Break => {
+                self.in_loop("break", stmt.span);
+                TypedStmtKind::Break
+            }
+            StmtKind::Continue => {
+                self.in_loop("continue", stmt.span);
+                TypedStmtKind::Continue
+            }
+            StmtKind::Empty => TypedStmtKind::Empty,
+            StmtKind::Error => TypedStmtKind::Error,
+        };
+        TypedStmt {
+            kind,
+            span: stmt.span,
+        }
+    }
+
+    /// Check that the statement `keyword` at `span` is inside a loop
+    fn in_loop(&mut self, keyword: &'static str, span: Span) {
+        if self.loops == 0 {
+            self.error(span, TypeErrorKind::OutsideLoop { keyword });
+        }
+    }
+
+    /// Check a `return` statement's value against the function's return type
+    fn ret(&mut self, value: Option<&Expr>, span: Span) -> Option<TypedExpr> {
+        let return_type = self.return_type;
+        let Some(value) = value else {
+            if return_type != self.void {
+                let expected = self.name(return_type);
+                self.error(
+                    span,
+                    TypeErrorKind::Mismatch {
+                        expected,
+                        found: "void".to_string(),
+                    },
+                );
+            }
+            return None;
+        };
+        let value = self.value(value);
+        if return_type == self.void && value.ty != TypeId::BOTTOM {
+            let found = self.name(value.ty);
+            self.error(
+                value.span,
+                TypeErrorKind::Mismatch {
+                    expected: "void".to_string(),
+                    found,
+                },
+            );
+            return Some(value);
+        }
+        Some(self.convert(value, return_type))
+    }
+
+    /// Check a condition, which must be a scalar
+    fn condition(&mut self, cond: &Expr) -> TypedExpr {
+        let cond = self.value(cond);
+        if cond.ty != TypeId::BOTTOM && !self.is_scalar(cond.ty) {
+            let ty = self.name(cond.ty);
+            self.error(cond.span, TypeErrorKind::NotScalar { ty });
+        }
+        cond
+    }
+
+    /// Wrap an expression in an implicit conversion to `ty`, unless it already has that
+    /// type
+    fn wrap(conversion: Conversion, expr: TypedExpr, ty: TypeId) -> TypedExpr {
+        if expr.ty == ty {
+            return expr;
+        }
+        TypedExpr {
+            span: expr.span,
+            kind: TypedExprKind::Convert(conversion, Box::new(expr)),
+            ty,
+            lvalue: false,
+        }
+    }
+
+    /// Convert an array or function to a pointer, as in any context but `sizeof` and
+    /// `&`
+    fn decay(&mut self, expr: TypedExpr) -> TypedExpr {
+        let pointee = match *self.types.get(expr.ty) {
+            Type::Array { element, .. } => element,
+            Type::Function { .. } => expr.ty,
+            _ => return expr,
+        };
+        let ty = self.types.pointer(pointee, true);
+        Self::wrap(Conversion::Decay, expr, ty)
+    }
+
+    /// Check an expression whose value is used, decaying arrays and functions
+    fn value(&mut self, expr: &Expr) -> TypedExpr {
+        let expr = self.expr(expr);
+        self.decay(expr)
+    }
+
+    /// Apply the integer promotions: integers narrower than `int` become `int`
+    fn promote(&mut self, expr: TypedExpr) -> TypedExpr {
+        match self.int_info(expr.ty) {
+            Some((bits, _)) if bits < 32 => {
+                let int = self.types.int(32, true);
+                Self::wrap(Conversion::Arithmetic, expr, int)
+            }
+            _ => expr,
+        }
+    }
+
+    /// Get the type the usual arithmetic conversions convert two arithmetic types to
+    fn common_type(&mut self, a: TypeId, b: TypeId) -> TypeId {
+        let float = |ty| match self.types.get(ty) {
+            Type::Float(kind) => Some(*kind),
+            _ => None,
+        };
+        if let Some(kind) = float(a).max(float(b)) {
+            return self.types.float(kind);
+        }
+        let promote = |(bits, signed): (u8, bool)| {
+            if bits < 32 {
+                (32, true)
+            } else {
+                (bits, signed)
+            }
+        };
+        let a = promote(
+            self.int_info(a)
+                .expect("arithmetic types are integers or floats"),
+        );
+        let b = promote(
+            self.int_info(b)
+                .expect("arithmetic types are integers or floats"),
+        );
+        let (bits, signed) = if a.1 == b.1 {
+            (a.0.max(b.0), a.1)
+        } else {
+            let (signed, unsigned) = if a.1 { (a, b) } else { (b, a) };
+            // A wider signed type holds every unsigned value; otherwise both become
+            // unsigned
+            if signed.0 > unsigned.0 {
+                signed
+            } else {
+                (unsigned.0, false)
+            }
+        };
+        self.types.int(bits, signed)
+    }
+
+    /// Apply the usual arithmetic conversions to two arithmetic operands
+    fn arithmetic(&mut self, lhs: TypedExpr, rhs: TypedExpr) -> (TypedExpr, TypedExpr, TypeId) {
+        let ty = self.common_type(lhs.ty, rhs.ty);
+        (
+            Self::wrap(Conversion::Arithmetic, lhs, ty),
+            Self::wrap(Conversion::Arithmetic, rhs, ty),
+            ty,
+        )
+    }
+
+    /// Reports whether an expression is the null pointer constant: the integer `0`
+    fn is_null(&self, expr: &TypedExpr) -> bool {
+        matches!(expr.kind, TypedExprKind::Int(0)) && self.int_info(expr.ty).is_some()
+    }
+
+    /// Convert a value to `ty` as if by assignment, reporting an error if it can't be
+    fn convert(&mut self, expr: TypedExpr, ty: TypeId) -> TypedExpr {
+        if expr.ty == ty || expr.ty == TypeId::BOTTOM || ty == TypeId::BOTTOM {
+            return expr;
+        }
+        let bool = self.types.int(1, false);
+        if self.is_arithmetic(ty) && self.is_arithmetic(expr.ty) {
+            return Self::wrap(Conversion::Arithmetic, expr, ty);
+        }
+        if ty == bool && self.is_scalar(expr.ty) {
+            return Self::wrap(Conversion::ToBool, expr, ty);
+        }
+        if let Some(pointee) = self.pointee(ty) {
+            if self.is_null(&expr) {
+                return Self::wrap(Conversion::NullPointer, expr, ty);
+            }
+            if let Some(from) = self.pointee(expr.ty) {
+                if pointee == self.void || from == self.void {
+                    return Self::wrap(Conversion::Pointer, expr, ty);
+                }
+            }
+        }
+        let (expected, found) = (self.name(ty), self.name(expr.ty));
+        self.error(expr.span, TypeErrorKind::Mismatch { expected, found });
+        expr
+    }
+
+    /// Build an expression with an error, which doesn't cause more errors
+    const fn poisoned(kind: TypedExprKind, span: Span) -> TypedExpr {
+        TypedExpr {
+            kind,
+            ty: TypeId::BOTTOM,
+            lvalue: false,
+            span,
+        }
+    }
+
+    /// Check an expression, without decaying arrays and functions
+    fn expr(&mut self, expr: &Expr) -> TypedExpr {
+        let span = expr.span;
+        let rvalue = |kind, ty| TypedExpr {
+            kind,
+            ty,
+            lvalue: false,
+            span,
+        };
+        match &expr.kind {
+            &ExprKind::Int(value) => {
+                let ty = if i32::try_from(value).is_ok() {
+                    self.types.int(32, true)
+                } else if value < 1 << (self.target.long_bits - 1) {
+                    self.types.int(self.target.long_bits, true)
+                } else {
+                    self.types.int(64, false)
+                };
+                rvalue(TypedExprKind::Int(value), ty)
+            }
+            &ExprKind::Float(value) => rvalue(
+                TypedExprKind::Float(value),
+                self.types.float(FloatKind::F64),
+            ),
+            &ExprKind::Char(value) => {
+                rvalue(TypedExprKind::Int(value.into()), self.types.int(32, true))
+            }
+            &ExprKind::Bool(value) => {
+                rvalue(TypedExprKind::Int(value.into()), self.types.int(1, false))
+            }
+            ExprKind::String(value) => {
+                let char = self.primitive(Primitive::Char);
+                let ty = self.types.array(char, Some(value.len() as u64 + 1));
+                TypedExpr {
+                    kind: TypedExprKind::String(value.clone()),
+                    ty,
+                    lvalue: true,
+                    span,
+                }
+            }
+            ExprKind::Var(name) => {
+                let ty = self.lookup(&name.name);
+                TypedExpr {
+                    kind: TypedExprKind::Var(name.clone()),
+                    ty,
+                    lvalue: ty != TypeId::BOTTOM && !self.is_function(ty),
+                    span,
+                }
+            }
+            ExprKind::Unary(op, operand) => self.unary(*op, operand, span),
+            ExprKind::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.value(lhs), self.value(rhs));
+                self.binary(*op, lhs, rhs, span)
+            }
+            ExprKind::Assign(op, lhs, rhs) => self.assign(*op, lhs, rhs, span),
+            ExprKind::Conditional(cond, then, otherwise) => {
+                self.conditional(cond, then, otherwise, span)
+            }
+            ExprKind::Comma(lhs, rhs) => {
+                let (lhs, rhs) = (self.value(lhs), self.value(rhs));
+                let ty = rhs.ty;
+                rvalue(TypedExprKind::Comma(Box::new(lhs), Box::new(rhs)), ty)
+            }
+            ExprKind::Call(callee, args) => self.call(callee, args, span),
+            ExprKind::Index(base, index) => self.index(base, index, span),
+            ExprKind::Member(base, field) => {
+                let base = self.expr(base);
+                self.member(base, field, ".", span)
+            }
+            ExprKind::PtrMember(base, field) => self.ptr_member(base, field, span),
+            ExprKind::Cast(ty, operand) => self.cast(ty, operand, span),
+            ExprKind::SizeofType(ty) => {
+                let ty = self.resolve_type(ty);
+                self.sizeof(ty, span)
+            }
+            ExprKind::SizeofExpr(operand) => {
+                let operand = self.expr(operand);
+                if operand.ty == TypeId::BOTTOM {
+                    return Self::poisoned(TypedExprKind::Int(0), span);
+                }
+                self.sizeof(operand.ty, span)
+            }
+        }
+    }
+
+    /// Check `base[index]`, which is `*(base + index)`
+    fn index(&mut self, base: &Expr, index: &Expr, span: Span) -> TypedExpr {
+        let (base, index) = (self.value(base), self.value(index));
+        let address = self.binary(BinaryOp::Add, base, index, span);
+        if address.ty != TypeId::BOTTOM && self.pointee(address.ty).is_none() {
+            let TypedExprKind::Binary(_, base, index) = address.kind else {
+                unreachable!("additions are binary operators");
+            };
+            let (lhs, rhs) = (self.name(base.ty), self.name(index.ty));
+            self.error(span, TypeErrorKind::InvalidOperands { op: "[]", lhs, rhs });
+            return Self::poisoned(TypedExprKind::Binary(BinaryOp::Add, base, index), span);
+        }
+        self.deref(address, span)
+    }
+
+    /// Check `base->field`, which is `(*base).field`
+    fn ptr_member(&mut self, base: &Expr, field: &Ident, span: Span) -> TypedExpr {
+        let base = self.value(base);
+        if base.ty == TypeId::BOTTOM {
+            return self.member(base, field, "->", span);
+        }
+        let Some(pointee) = self.pointee(base.ty) else {
+            let operand = self.name(base.ty);
+            self.error(span, TypeErrorKind::InvalidOperand { op: "->", operand });
+            return Self::poisoned(TypedExprKind::Member(Box::new(base), field.clone()), span);
+        };
+        let base = TypedExpr {
+            kind: TypedExprKind::Unary(UnaryOp::Deref, Box::new(base)),
+            ty: pointee,
+            lvalue: true,
+            span,
+        };
+        self.member(base, field, "->", span)
+    }
+
+    /// Check an explicit cast, which converts between scalars, or to `void`
+    fn cast(&mut self, ty: &CType, operand: &Expr, span: Span) -> TypedExpr {
+        let ty = self.resolve_type(ty);
+        let operand = self.value(operand);
+        let valid = operand.ty == TypeId::BOTTOM
+            || ty == self.void
+            || ty == operand.ty
+            || (self.is_scalar(ty)
+                && self.is_scalar(operand.ty)
+                && !(self.pointee(ty).is_some() && self.is_float(operand.ty))
+                && !(self.pointee(operand.ty).is_some() && self.is_float(ty)));
+        if !valid {
+            let (from, to) = (self.name(operand.ty), self.name(ty));
+            self.error(span, TypeErrorKind::InvalidCast { from, to });
+            return Self::poisoned(TypedExprKind::Cast(Box::new(operand)), span);
+        }
+        TypedExpr {
+            kind: TypedExprKind::Cast(Box::new(operand)),
+            ty,
+            lvalue: false,
+            span,
+        }
+    }
+
+    /// Get the size of a type, as an `unsigned long` constant
+    fn sizeof(&mut self, ty: TypeId, span: Span) -> TypedExpr {
+        let Some((size, _)) = self.size_align(ty) else {
+            let ty = self.name(ty);
+            self.error(span, TypeErrorKind::Incomplete { ty });
+            return Self::poisoned(TypedExprKind::Int(0), span);
+        };
+        TypedExpr {
+            kind: TypedExprKind::Int(size),
+            ty: self.primitive(Primitive::ULong),
+            lvalue: false,
+            span,
+        }
+    }
+
+    /// Dereference a pointer
+    fn deref(&mut self, operand: TypedExpr, span: Span) -> TypedExpr {
+        if operand.ty == TypeId::BOTTOM {
+            return Self::poisoned(
+                TypedExprKind::Unary(UnaryOp::Deref, Box::new(operand)),
+                span,
+            );
+        }
+        match self.pointee(operand.ty) {
+            Some(pointee) if pointee != self.void => TypedExpr {
+                kind: TypedExprKind::Unary(UnaryOp::Deref, Box::new(operand)),
+                ty: pointee,
+                lvalue: !self.is_function(pointee),
+                span,
+            },
+            _ => {
+                let operand_name = self.name(operand.ty);
+                self.error(
+                    span,
+                    TypeErrorKind::InvalidOperand {
+                        op: "*",
+                        operand: operand_name,
+                    },
+                );
+                Self::poisoned(
+                    TypedExprKind::Unary(UnaryOp::Deref, Box::new(operand)),
+                    span,
+                )
+            }
+        }
+    }
+
+    /// Access a struct's field
+    fn member(
+        &mut self,
+        base: TypedExpr,
+        field: &Ident,
+        op: &'static str,
+        span: Span,
+    ) -> TypedExpr {
+        let member = |base, ty, lvalue| TypedExpr {
+            kind: TypedExprKind::Member(Box::new(base), field.clone()),
+            ty,
+            lvalue,
+            span,
+        };
+        if base.ty == TypeId::BOTTOM {
+            return member(base, TypeId::BOTTOM, false);
+        }
+        if !matches!(self.types.get(base.ty), Type::Struct(_)) {
+            let operand = self.name(base.ty);
+            self.error(span, TypeErrorKind::InvalidOperand { op, operand });
+            return member(base, TypeId::BOTTOM, false);
+        }
+        let Some(fields) = self.types.fields(base.ty) else {
+            let ty = self.name(base.ty);
+            self.error(span, TypeErrorKind::Incomplete { ty });
+            return member(base, TypeId::BOTTOM, false);
+        };
+        if let Some(&(_, ty)) = fields.iter().find(|(name, _)| *name == field.name) {
+            let lvalue = base.lvalue;
+            return member(base, ty, lvalue);
+        }
+        let ty = self.name(base.ty);
+        self.error(
+            field.span,
+            TypeErrorKind::NoField {
+                ty,
+                field: field.name.clone(),
+            },
+        );
+        member(base, TypeId::BOTTOM, false)
+    }
+
+    /// Check that an operand is a modifiable lvalue, reporting an error if it isn't
+    fn modifiable(&mut self, operand: &TypedExpr, op: &'static str) -> bool {
+        if operand.ty == TypeId::BOTTOM {
+            return false;
+        }
+        if !operand.lvalue {
+            self.error(operand.span, TypeErrorKind::NotAnLvalue { op });
+            return false;
+        }
+        if matches!(self.types.get(operand.ty), Type::Array { .. }) {
+            let operand_name = self.name(operand.ty);
+            self.error(
+                operand.span,
+                TypeErrorKind::InvalidOperand {
+                    op,
+                    operand: operand_name,
+                },
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Check a prefix or postfix operator
+    fn unary(&mut self, op: UnaryOp, operand: &Expr, span: Span) -> TypedExpr {
+        let operand = match op {
+            UnaryOp::AddrOf
+            | UnaryOp::PreInc
+            | UnaryOp::PreDec
+            | UnaryOp::PostInc
+            | UnaryOp::PostDec => self.expr(operand),
+            _ => self.value(operand),
+        };
+        let unary = |operand, ty| TypedExpr {
+            kind: TypedExprKind::Unary(op, Box::new(operand)),
+            ty,
+            lvalue: false,
+            span,
+        };
+        if operand.ty == TypeId::BOTTOM {
+            return unary(operand, TypeId::BOTTOM);
+        }
+        let valid = match op {
+            UnaryOp::Neg | UnaryOp::Plus if self.is_arithmetic(operand.ty) => {
+                let operand = self.promote(operand);
+                let ty = operand.ty;
+                return unary(operand, ty);
+            }
+            UnaryOp::BitNot if self.int_info(operand.ty).is_some() => {
+                let operand = self.promote(operand);
+                let ty = operand.ty;
+                return unary(operand, ty);
+            }
+            UnaryOp::Not if self.is_scalar(operand.ty) => {
+                let int = self.types.int(32, true);
+                return unary(operand, int);
+            }
+            UnaryOp::Deref => return self.deref(operand, span),
+            UnaryOp::AddrOf => {
+                if !operand.lvalue && !self.is_function(operand.ty) {
+                    self.error(operand.span, TypeErrorKind::NotAnLvalue { op: "&" });
+                    return unary(operand, TypeId::BOTTOM);
+                }
+                let ty = self.types.pointer(operand.ty, true);
+                return unary(operand, ty);
+            }
+            UnaryOp::PreInc | UnaryOp::PreDec | UnaryOp::PostInc | UnaryOp::PostDec => {
+                if !self.modifiable(&operand, op.symbol()) {
+                    return unary(operand, TypeId::BOTTOM);
+                }
+                self.is_arithmetic(operand.ty)
+                    || self
+                        .pointee(operand.ty)
+                        .is_some_and(|pointee| self.size_align(pointee).is_some())
+            }
+            _ => false,
+        };
+        if !valid {
+            let operand_name = self.name(operand.ty);
+            self.error(
+                span,
+                TypeErrorKind::InvalidOperand {
+                    op: op.symbol(),
+                    operand: operand_name,
+                },
+            );
+            return unary(operand, TypeId::BOTTOM);
+        }
+        let ty = operand.ty;
+        unary(operand, ty)
+    }
+
+    /// Check a binary operator on operands whose values are used
+    fn binary(&mut self, op: BinaryOp, lhs: TypedExpr, rhs: TypedExpr, span: Span) -> TypedExpr {
+        let binary = |lhs, rhs, ty| TypedExpr {
+            kind: TypedExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+            ty,
+            lvalue: false,
+            span,
+        };
+        if lhs.ty == TypeId::BOTTOM || rhs.ty == TypeId::BOTTOM {
+            return binary(lhs, rhs, TypeId::BOTTOM);
+        }
+        let int = self.types.int(32, true);
+        let arithmetic = self.is_arithmetic(lhs.ty) && self.is_arithmetic(rhs.ty);
+        let integers = self.int_info(lhs.ty).is_some() && self.int_info(rhs.ty).is_some();
+        let (lhs_pointee, rhs_pointee) = (self.pointee(lhs.ty), self.pointee(rhs.ty));
+        let usual = match op {
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Add | BinaryOp::Sub => arithmetic,
+            BinaryOp::Rem | BinaryOp::BitAnd | BinaryOp::BitXor | BinaryOp::BitOr => integers,
+            _ => false,
+        };
+        if usual {
+            let (lhs, rhs, ty) = self.arithmetic(lhs, rhs);
+            return binary(lhs, rhs, ty);
+        }
+        match op {
+            BinaryOp::Shl | BinaryOp::Shr if integers => {
+                let (lhs, rhs) = (self.promote(lhs), self.promote(rhs));
+                let ty = lhs.ty;
+                return binary(lhs, rhs, ty);
+            }
+            BinaryOp::Add | BinaryOp::Sub => return self.pointer_arithmetic(op, lhs, rhs, span),
+            BinaryOp::Less
+            | BinaryOp::LessEq
+            | BinaryOp::Greater
+            | BinaryOp::GreaterEq
+            | BinaryOp::Eq
+            | BinaryOp::NotEq
+                if arithmetic =>
+            {
+                let (lhs, rhs, _) = self.arithmetic(lhs, rhs);
+                return binary(lhs, rhs, int);
+            }
+            BinaryOp::Less
+            | BinaryOp::LessEq
+            | BinaryOp::Greater
+            | BinaryOp::GreaterEq
+            | BinaryOp::Eq
+            | BinaryOp::NotEq
+                if lhs_pointee.is_some() && lhs_pointee == rhs_pointee =>
+            {
+                return binary(lhs, rhs, int);
+            }
+            BinaryOp::Eq | BinaryOp::NotEq => {
+                // Pointers also compare with null, and with `void*`
+                let void = Some(self.void);
+                if lhs_pointee.is_some() && (self.is_null(&rhs) || rhs_pointee == void) {
+                    let ty = lhs.ty;
+                    let rhs = self.convert(rhs, ty);
+                    return binary(lhs, rhs, int);
+                }
+                if rhs_pointee.is_some() && (self.is_null(&lhs) || lhs_pointee == void) {
+                    let ty = rhs.ty;
+                    let lhs = self.convert(lhs, ty);
+                    return binary(lhs, rhs, int);
+                }
+            }
+            BinaryOp::And | BinaryOp::Or if self.is_scalar(lhs.ty) && self.is_scalar(rhs.ty) => {
+                return binary(lhs, rhs, int);
+            }
+            _ => {}
+        }
+        self.invalid_operands(op, lhs, rhs, span)
+    }
+
+    /// Report that a binary operator can't apply to its operands
+    fn invalid_operands(
+        &mut self,
+        op: BinaryOp,
+        lhs: TypedExpr,
+        rhs: TypedExpr,
+        span: Span,
+    ) -> TypedExpr {
+        let (lhs_name, rhs_name) = (self.name(lhs.ty), self.name(rhs.ty));
+        self.error(
+            span,
+            TypeErrorKind::InvalidOperands {
+                op: op.symbol(),
+                lhs: lhs_name,
+                rhs: rhs_name,
+            },
+        );
+        Self::poisoned(
+            TypedExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+            span,
+        )
+    }
+
+    /// Check `+` or `-` with a pointer operand
+    ///
+    /// Pointer arithmetic moves by whole elements, so they must be complete. Integer
+    /// offsets are converted to `long`, and the difference of two pointers is a `long`.
+    fn pointer_arithmetic(
+        &mut self,
+        op: BinaryOp,
+        lhs: TypedExpr,
+        rhs: TypedExpr,
+        span: Span,
+    ) -> TypedExpr {
+        let long = self.primitive(Primitive::Long);
+        let (lhs_pointee, rhs_pointee) = (self.pointee(lhs.ty), self.pointee(rhs.ty));
+        let (lhs_int, rhs_int) = (
+            self.int_info(lhs.ty).is_some(),
+            self.int_info(rhs.ty).is_some(),
+        );
+        let (lhs, rhs, address, ty) = if lhs_pointee.is_some() && rhs_int {
+            let ty = lhs.ty;
+            (lhs, Self::wrap(Conversion::Arithmetic, rhs, long), ty, ty)
+        } else if op == BinaryOp::Add && rhs_pointee.is_some() && lhs_int {
+            let ty = rhs.ty;
+            (Self::wrap(Conversion::Arithmetic, lhs, long), rhs, ty, ty)
+        } else if op == BinaryOp::Sub && lhs_pointee.is_some() && lhs_pointee == rhs_pointee {
+            let address = lhs.ty;
+            (lhs, rhs, address, long)
+        } else {
+            return self.invalid_operands(op, lhs, rhs, span);
+        };
+        let element = self.pointee(address).expect("one operand is a pointer");
+        let ty = if self.size_align(element).is_some() {
+            ty
+        } else {
+            let element = self.name(element);
+            self.error(span, TypeErrorKind::Incomplete { ty: element });
+            TypeId::BOTTOM
+        };
+        TypedExpr {
+            kind: TypedExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+            ty,
+            lvalue: false,
+            span,
+        }
+    }
+
+    /// Check an assignment or compound assignment
+    fn assign(&mut self, op: Option<BinaryOp>, lhs: &Expr, rhs: &Expr, span: Span) -> TypedExpr {
+        let lhs = self.expr(lhs);
+        let rhs = self.value(rhs);
+        let symbol = op.map_or("=", |op| match op {
+            BinaryOp::Mul => "*=",
+            BinaryOp::Div => "/=",
+            BinaryOp::Rem => "%=",
+            BinaryOp::Add => "+=",
+            BinaryOp::Sub => "-=",
+            BinaryOp::Shl => "<<=",
+            BinaryOp::Shr => ">>=",
+            BinaryOp::BitAnd => "&=",
+            BinaryOp::BitXor => "^=",
+            BinaryOp::BitOr => "|=",
+            _ => op.symbol(),
+        });
+        let ty = lhs.ty;
+        let assign = |lhs, rhs, ty| TypedExpr {
+            kind: TypedExprKind::Assign(op, Box::new(lhs), Box::new(rhs)),
+            ty,
+            lvalue: false,
+            span,
+        };
+        if !self.modifiable(&lhs, symbol) || rhs.ty == TypeId::BOTTOM {
+            return assign(lhs, rhs, TypeId::BOTTOM);
+        }
+        let Some(op) = op else {
+            let rhs = self.convert(rhs, ty);
+            return assign(lhs, rhs, ty);
+        };
+        // `lhs op= rhs` computes `lhs op rhs`, then converts it back to `lhs`'s type
+        let value = self.decay(lhs.clone());
+        let result = self.binary(op, value, rhs, span);
+        let TypedExprKind::Binary(_, _, rhs) = result.kind else {
+            unreachable!("binary operators check to binary expressions");
+        };
+        if result.ty == TypeId::BOTTOM {
+            return assign(lhs, *rhs, TypeId::BOTTOM);
+        }
+        let converted = TypedExpr {
+            ty: result.ty,
+            ..lhs.clone()
+        };
+        let converted = self.convert(converted, ty);
+        assign(lhs, *rhs, converted.ty)
+    }
+
+    /// Check a conditional expression
+    fn conditional(&mut self, cond: &Expr, then: &Expr, otherwise: &Expr, span: Span) -> TypedExpr {
+        let cond = self.condition(cond);
+        let (then, otherwise) = (self.value(then), self.value(otherwise));
+        let conditional = |then, otherwise, ty| TypedExpr {
+            kind: TypedExprKind::Conditional(Box::new(cond), Box::new(then), Box::new(otherwise)),
+            ty,
+            lvalue: false,
+            span,
+        };
+        if then.ty == TypeId::BOTTOM || otherwise.ty == TypeId::BOTTOM {
+            return conditional(then, otherwise, TypeId::BOTTOM);
+        }
+        if self.is_arithmetic(then.ty) && self.is_arithmetic(otherwise.ty) {
+            let (then, otherwise, ty) = self.arithmetic(then, otherwise);
+            return conditional(then, otherwise, ty);
+        }
+        if then.ty == otherwise.ty {
+            let ty = then.ty;
+            return conditional(then, otherwise, ty);
+        }
+        let (then_pointee, otherwise_pointee) = (self.pointee(then.ty), self.pointee(otherwise.ty));
+        let void = Some(self.void);
+        if then_pointee.is_some() && (self.is_null(&otherwise) || otherwise_pointee == void) {
+            let ty = then.ty;
+            let otherwise = self.convert(otherwise, ty);
+            return conditional(then, otherwise, ty);
+        }
+        if otherwise_pointee.is_some() && (self.is_null(&then) || then_pointee == void) {
+            let ty = otherwise.ty;
+            let then = self.convert(then, ty);
+            return conditional(then, otherwise, ty);
+        }
+        let (lhs, rhs) = (self.name(then.ty), self.name(otherwise.ty));
+        self.error(span, TypeErrorKind::InvalidOperands { op: "?:", lhs, rhs });
+        conditional(then, otherwise, TypeId::BOTTOM)
+    }
+
+    /// Check a function call
+    fn call(&mut self, callee: &Expr, args: &[Expr], span: Span) -> TypedExpr {
+        let callee = self.value(callee);
+        let args: Vec<_> = args.iter().map(|arg| self.value(arg)).collect();
+        let call = |callee, args, ty| TypedExpr {
+            kind: TypedExprKind::Call(Box::new(callee), args),
+            ty,
+            lvalue: false,
+            span,
+        };
+        if callee.ty == TypeId::BOTTOM {
+            return call(callee, args, TypeId::BOTTOM);
+        }
+        let function = self
+            .pointee(callee.ty)
+            .map(|pointee| self.types.get(pointee).clone());
+        let Some(Type::Function { params, ret }) = function else {
+            let ty = self.name(callee.ty);
+            self.error(callee.span, TypeErrorKind::NotCallable { ty });
+            return call(callee, args, TypeId::BOTTOM);
+        };
+        if params.len() != args.len() {
+            self.error(
+                span,
+                TypeErrorKind::ArgumentCount {
+                    expected: params.len(),
+                    found: args.len(),
+                },
+            );
+        }
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(index, arg)| match params.get(index) {
+                Some(&param) => self.convert(arg, param),
+                None => arg,
+            })
+            .collect();
+        call(callee, args, ret)
+    }
+}
+
+/// A type error in a SeaC program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeError {
+    /// Where the error is
+    pub span: Span,
+    /// What the error is
+    pub kind: TypeErrorKind,
+}
+
+/// The kinds of type errors, with types named as they're written in SeaC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeErrorKind {
+    /// A value has a type that can't be converted to the one needed
+    Mismatch {
+        /// The type needed
+        expected: String,
+        /// The value's type
+        found: String,
+    },
+    /// A binary operator can't apply to its operands' types
+    InvalidOperands {
+        /// The operator
+        op: &'static str,
+        /// The left operand's type
+        lhs: String,
+        /// The right operand's type
+        rhs: String,
+    },
+    /// A prefix or postfix operator can't apply to its operand's type
+    InvalidOperand {
+        /// The operator
+        op: &'static str,
+        /// The operand's type
+        operand: String,
+    },
+    /// An operator needs an lvalue, but its operand is an rvalue
+    NotAnLvalue {
+        /// The operator
+        op: &'static str,
+    },
+    /// A struct doesn't have the field accessed
+    NoField {
+        /// The struct type
+        ty: String,
+        /// The field's name
+        field: String,
+    },
+    /// A value that isn't a function is called
+    NotCallable {
+        /// The value's type
+        ty: String,
+    },
+    /// A function is called with the wrong number of arguments
+    ArgumentCount {
+        /// The number of parameters
+        expected: usize,
+        /// The number of arguments
+        found: usize,
+    },
+    /// A type's size is needed, but it's `void`, a function, or undefined
+    Incomplete {
+        /// The type
+        ty: String,
+    },
+    /// A condition isn't a number or pointer
+    NotScalar {
+        /// The condition's type
+        ty: String,
+    },
+    /// A cast between types that don't convert
+    InvalidCast {
+        /// The operand's type
+        from: String,
+        /// The type cast to
+        to: String,
+    },
+    /// A parameter has type `void`, other than in a lone `(void)`
+    VoidParameter,
+    /// `break` or `continue` isn't inside a loop
+    OutsideLoop {
+        /// The statement's keyword
+        keyword: &'static str,
+    },
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl fmt::Display for TypeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mismatch { expected, found } => {
+                write!(f, "Expected `{expected}`, found `{found}`")
+            }
+            Self::InvalidOperands { op, lhs, rhs } => {
+                write!(f, "Can't apply `{op}` to `{lhs}` and `{rhs}`")
+            }
+            Self::InvalidOperand { op, operand } => write!(f, "Can't apply `{op}` to `{operand}`"),
+            Self::NotAnLvalue { op } => write!(f, "The operand of `{op}` must be an lvalue"),
+            Self::NoField { ty, field } => write!(f, "`{ty}` has no field `{field}`"),
+            Self::NotCallable { ty } => write!(f, "Can't call a value of type `{ty}`"),
+            Self::ArgumentCount { expected, found } => write!(
+                f,
+                "Expected {expected} argument{}, found {found}",
+                if *expected == 1 { "" } else { "s" }
+            ),
+            Self::Incomplete { ty } => write!(f, "Can't use incomplete type `{ty}` here"),
+            Self::NotScalar { ty } => {
+                write!(f, "Expected a number or pointer condition, found `{ty}`")
+            }
+            Self::InvalidCast { from, to } => write!(f, "Can't cast `{from}` to `{to}`"),
+            Self::VoidParameter => {
+                f.write_str("A parameter can't be `void`, except as the lone `(void)`")
+            }
+            Self::OutsideLoop { keyword } => write!(f, "`{keyword}` outside of a loop"),
+        }
+    }
+}
+
+impl Error for TypeError {}
@@ -0,0 +1,564 @@
+//! SeaC's syntax as a [`Grammar`], for generating test programs
+//!
+//! The grammar describes the same language as the parser, with C's precedences and
+//! associativities as filters. It's used to generate random programs for fuzzing: every
+//! program it derives must parse.
+
+use crate::Token;
+use sea_parse::{nonterm, term, Assoc, Choices, Grammar, Symbol};
+
+/// A terminal for any identifier
+const fn ident() -> Symbol<Token> {
+    term(Token::Ident(String::new()))
+}
+
+/// The binary operator rules, from the loosest to the tightest, with their operator
+/// nonterminals and operators
+const BINARY: [(&str, &str, &[Token]); 10] = [
+    ("or", "OrOp", &[Token::PipePipe]),
+    ("and", "AndOp", &[Token::AmpAmp]),
+    ("bitor", "BitOrOp", &[Token::Pipe]),
+    ("bitxor", "BitXorOp", &[Token::Caret]),
+    ("bitand", "BitAndOp", &[Token::Amp]),
+    ("equality", "EqualityOp", &[Token::EqEq, Token::NotEq]),
+    (
+        "relational",
+        "RelationalOp",
+        &[Token::Less, Token::LessEq, Token::Greater, Token::GreaterEq],
+    ),
+    ("shift", "ShiftOp", &[Token::Shl, Token::Shr]),
+    ("additive", "AdditiveOp", &[Token::Plus, Token::Minus]),
+    (
+        "multiplicative",
+        "MultiplicativeOp",
+        &[Token::Star, Token::Slash, Token::Percent],
+    ),
+];
+
+/// The assignment operators
+const ASSIGN: [Token; 11] = [
+    Token::Eq,
+    Token::PlusEq,
+    Token::MinusEq,
+    Token::StarEq,
+    Token::SlashEq,
+    Token::PercentEq,
+    Token::AmpEq,
+    Token::PipeEq,
+    Token::CaretEq,
+    Token::ShlEq,
+    Token::ShrEq,
+];
+
+/// The prefix operators
+const PREFIX: [Token; 8] = [
+    Token::Minus,
+    Token::Plus,
+    Token::Bang,
+    Token::Tilde,
+    Token::Star,
+    Token::Amp,
+    Token::PlusPlus,
+    Token::MinusMinus,
+];
+
+/// SeaC's syntax, starting from `Program`
+///
+/// Rules for expressions are named after their operators' precedence levels
+/// (`"comma"`, `"assign"`, `"ternary"`, `"or"`, ..., `"multiplicative"`), and the rest
+/// after the construct they derive (`"if"`, `"cast"`, `"call"`, ...), for
+/// [`Generator::weight`](sea_parse::Generator::weight).
+#[must_use]
+pub fn grammar() -> Grammar<Token> {
+    let grammar = Grammar::new("Program")
+        .rule("Program", [])
+        .rule("Program", [nonterm("Program"), nonterm("Item")]);
+    let grammar = items(grammar);
+    let grammar = types(grammar);
+    let grammar = statements(grammar);
+    expressions(grammar)
+}
+
+/// Add the rules for top-level items
+fn items(grammar: Grammar<Token>) -> Grammar<Token> {
+    grammar
+        .named_rule(
+            "struct",
+            "Item",
+            [
+                term(Token::Struct),
+                ident(),
+                term(Token::LBrace),
+                nonterm("Fields"),
+                term(Token::RBrace),
+                term(Token::Semi),
+            ],
+        )
+        .named_rule(
+            "function",
+            "Item",
+            [
+                nonterm("Type"),
+                ident(),
+                term(Token::LParen),
+                nonterm("Params"),
+                term(Token::RParen),
+                nonterm("Block"),
+            ],
+        )
+        .named_rule(
+            "prototype",
+            "Item",
+            [
+                nonterm("Type"),
+                ident(),
+                term(Token::LParen),
+                nonterm("Params"),
+                term(Token::RParen),
+                term(Token::Semi),
+            ],
+        )
+        .named_rule("global", "Item", [nonterm("VarDecl"), term(Token::Semi)])
+        .rule("Fields", [])
+        .rule(
+            "Fields",
+            [
+                nonterm("Fields"),
+                nonterm("BaseType"),
+                nonterm("FieldDeclarators"),
+                term(Token::Semi),
+            ],
+        )
+        .rule("FieldDeclarators", [nonterm("FieldDeclarator")])
+        .rule(
+            "FieldDeclarators",
+            [
+                nonterm("FieldDeclarators"),
+                term(Token::Comma),
+                nonterm("FieldDeclarator"),
+            ],
+        )
+        .rule(
+            "FieldDeclarator",
+            [nonterm("Stars"), ident(), nonterm("Dims")],
+        )
+        .rule("Params", [])
+        .rule("Params", [nonterm("ParamList")])
+        .rule("ParamList", [nonterm("Param")])
+        .rule(
+            "ParamList",
+            [nonterm("ParamList"), term(Token::Comma), nonterm("Param")],
+        )
+        .rule("Param", [nonterm("Type"), ident(), nonterm("Dims")])
+        .rule("Param", [nonterm("Type")])
+        .rule("VarDecl", [nonterm("BaseType"), nonterm("Declarators")])
+        .rule("Declarators", [nonterm("Declarator")])
+        .rule(
+            "Declarators",
+            [
+                nonterm("Declarators"),
+                term(Token::Comma),
+                nonterm("Declarator"),
+            ],
+        )
+        .rule(
+            "Declarator",
+            [nonterm("Stars"), ident(), nonterm("Dims"), nonterm("Init")],
+        )
+        .rule("Init", [])
+        .rule("Init", [term(Token::Eq), nonterm("Expr")])
+}
+
+/// Add the rules for types and array dimensions
+fn types(grammar: Grammar<Token>) -> Grammar<Token> {
+    let primitives = [
+        Token::Void,
+        Token::Bool,
+        Token::Char,
+        Token::Short,
+        Token::Int,
+        Token::Long,
+        Token::Float,
+        Token::Double,
+        Token::Unsigned,
+    ];
+    let grammar = primitives.into_iter().fold(grammar, |grammar, kind| {
+        grammar.rule("BaseType", [term(kind)])
+    });
+    let grammar = [Token::Char, Token::Short, Token::Int, Token::Long]
+        .into_iter()
+        .fold(grammar, |grammar, kind| {
+            grammar.rule("BaseType", [term(Token::Unsigned), term(kind)])
+        });
+    grammar
+        .rule("BaseType", [term(Token::Struct), ident()])
+        .rule("Type", [nonterm("BaseType"), nonterm("Stars")])
+        .rule("Stars", [])
+        .rule("Stars", [nonterm("Stars"), term(Token::Star)])
+        .rule("Dims", [])
+        .rule(
+            "Dims",
+            [
+                nonterm("Dims"),
+                term(Token::LBracket),
+                term(Token::RBracket),
+            ],
+        )
+        .rule(
+            "Dims",
+            [
+                nonterm("Dims"),
+                term(Token::LBracket),
+                term(Token::IntLit(0)),
+                term(Token::RBracket),
+            ],
+        )
+}
+
+/// Add the rules for blocks and statements
+fn statements(grammar: Grammar<Token>) -> Grammar<Token> {
+    grammar
+        .rule(
+            "Block",
+            [term(Token::LBrace), nonterm("Stmts"), term(Token::RBrace)],
+        )
+        .rule("Stmts", [])
+        .rule("Stmts", [nonterm("Stmts"), nonterm("Stmt")])
+        .named_rule("block", "Stmt", [nonterm("Block")])
+        .named_rule("decl", "Stmt", [nonterm("VarDecl"), term(Token::Semi)])
+        .named_rule(
+            "if",
+            "Stmt",
+            [term(Token::If), nonterm("Condition"), nonterm("Stmt")],
+        )
+        .named_rule(
+            "if-else",
+            "Stmt",
+            [
+                term(Token::If),
+                nonterm("Condition"),
+                nonterm("Stmt"),
+                term(Token::Else),
+                nonterm("Stmt"),
+            ],
+        )
+        .named_rule(
+            "while",
+            "Stmt",
+            [term(Token::While), nonterm("Condition"), nonterm("Stmt")],
+        )
+        .named_rule(
+            "do-while",
+            "Stmt",
+            [
+                term(Token::Do),
+                nonterm("Stmt"),
+                term(Token::While),
+                nonterm("Condition"),
+                term(Token::Semi),
+            ],
+        )
+        .named_rule(
+            "for",
+            "Stmt",
+            [
+                term(Token::For),
+                term(Token::LParen),
+                nonterm("ForInit"),
+                term(Token::Semi),
+                nonterm("OptExpr"),
+                term(Token::Semi),
+                nonterm("OptExpr"),
+                term(Token::RParen),
+                nonterm("Stmt"),
+            ],
+        )
+        .named_rule(
+            "return",
+            "Stmt",
+            [term(Token::Return), nonterm("OptExpr"), term(Token::Semi)],
+        )
+        .named_rule("break", "Stmt", [term(Token::Break), term(Token::Semi)])
+        .named_rule(
+            "continue",
+            "Stmt",
+            [term(Token::Continue), term(Token::Semi)],
+        )
+        .named_rule("empty", "Stmt", [term(Token::Semi)])
+        .named_rule("expr", "Stmt", [nonterm("CommaExpr"), term(Token::Semi)])
+        .rule("ForInit", [])
+        .rule("ForInit", [nonterm("VarDecl")])
+        .rule("ForInit", [nonterm("CommaExpr")])
+        .rule("OptExpr", [])
+        .rule("OptExpr", [nonterm("CommaExpr")])
+        .rule(
+            "Condition",
+            [
+                term(Token::LParen),
+                nonterm("CommaExpr"),
+                term(Token::RParen),
+            ],
+        )
+}
+
+/// Add the rules for expressions, with their precedences as filters
+///
+/// `Expr` is an expression without the comma operator, as in arguments and initializers,
+/// and `CommaExpr` one with it.
+fn expressions(grammar: Grammar<Token>) -> Grammar<Token> {
+    let grammar = grammar.rule("CommaExpr", [nonterm("Expr")]).named_rule(
+        "comma",
+        "CommaExpr",
+        [nonterm("CommaExpr"), term(Token::Comma), nonterm("Expr")],
+    );
+    let grammar = ASSIGN
+        .into_iter()
+        .fold(grammar, |grammar, kind| {
+            grammar.rule("AssignOp", [term(kind)])
+        })
+        .named_rule(
+            "assign",
+            "Expr",
+            [nonterm("Expr"), nonterm("AssignOp"), nonterm("Expr")],
+        )
+        .named_rule(
+            "ternary",
+            "Expr",
+            [
+                nonterm("Expr"),
+                term(Token::Question),
+                nonterm("Expr"),
+                term(Token::Colon),
+                nonterm("Expr"),
+            ],
+        );
+    let grammar = BINARY
+        .into_iter()
+        .fold(grammar, |grammar, (name, operator, kinds)| {
+            let grammar = kinds.iter().fold(grammar, |grammar, kind| {
+                grammar.rule(operator, [term(kind.clone())])
+            });
+            grammar
+                .named_rule(
+                    name,
+                    "Expr",
+                    [nonterm("Expr"), nonterm(operator), nonterm("Expr")],
+                )
+                .assoc(Assoc::Left, [name])
+        });
+
+    // Each level binds tighter than the one before it
+    let levels: Vec<&'static str> = ["assign", "ternary"]
+        .into_iter()
+        .chain(BINARY.iter().map(|&(name, _, _)| name))
+        .collect();
+    let grammar = levels
+        .windows(2)
+        .fold(grammar, |grammar, pair| {
+            grammar.priority([pair[1]], [pair[0]])
+        })
+        .assoc(Assoc::Right, ["assign"])
+        .assoc(Assoc::Right, ["ternary"]);
+
+    operands(grammar)
+}
+
+/// Add the rules for the operands of binary operators: prefix and postfix expressions,
+/// casts, and primary expressions
+fn operands(grammar: Grammar<Token>) -> Grammar<Token> {
+    let grammar = PREFIX.into_iter().fold(grammar, |grammar, kind| {
+        grammar.rule("PrefixOp", [term(kind)])
+    });
+    let literals = [
+        Token::IntLit(0),
+        Token::FloatLit(0.0),
+        Token::CharLit(0),
+        Token::StringLit(String::new()),
+        Token::True,
+        Token::False,
+    ];
+    literals
+        .into_iter()
+        .fold(grammar, |grammar, kind| {
+            grammar.rule("Primary", [term(kind)])
+        })
+        .rule("Expr", [nonterm("Unary")])
+        .named_rule("prefix", "Unary", [nonterm("PrefixOp"), nonterm("Unary")])
+        .named_rule(
+            "cast",
+            "Unary",
+            [
+                term(Token::LParen),
+                nonterm("Type"),
+                term(Token::RParen),
+                nonterm("Unary"),
+            ],
+        )
+        .named_rule(
+            "sizeof-type",
+            "Unary",
+            [
+                term(Token::Sizeof),
+                term(Token::LParen),
+                nonterm("Type"),
+                term(Token::RParen),
+            ],
+        )
+        // An operand starting with a cast would be taken for `sizeof(type)`
+        .named_rule("sizeof", "Unary", [term(Token::Sizeof), nonterm("Postfix")])
+        .rule("Unary", [nonterm("Postfix")])
+        .rule("Postfix", [nonterm("Primary")])
+        .named_rule(
+            "call",
+            "Postfix",
+            [
+                nonterm("Postfix"),
+                term(Token::LParen),
+                nonterm("Args"),
+                term(Token::RParen),
+            ],
+        )
+        .named_rule(
+            "index",
+            "Postfix",
+            [
+                nonterm("Postfix"),
+                term(Token::LBracket),
+                nonterm("CommaExpr"),
+                term(Token::RBracket),
+            ],
+        )
+        .named_rule(
+            "member",
+            "Postfix",
+            [nonterm("Postfix"), term(Token::Dot), ident()],
+        )
+        .named_rule(
+            "ptr-member",
+            "Postfix",
+            [nonterm("Postfix"), term(Token::Arrow), ident()],
+        )
+        .named_rule(
+            "increment",
+            "Postfix",
+            [nonterm("Postfix"), term(Token::PlusPlus)],
+        )
+        .named_rule(
+            "decrement",
+            "Postfix",
+            [nonterm("Postfix"), term(Token::MinusMinus)],
+        )
+        .rule("Args", [])
+        .rule("Args", [nonterm("ArgList")])
+        .rule("ArgList", [nonterm("Expr")])
+        .rule(
+            "ArgList",
+            [nonterm("ArgList"), term(Token::Comma), nonterm("Expr")],
+        )
+        .rule("Primary", [ident()])
+        .named_rule(
+            "paren",
+            "Primary",
+            [
+                term(Token::LParen),
+                nonterm("CommaExpr"),
+                term(Token::RParen),
+            ],
+        )
+}
+
+/// Spell a token of a generated program, choosing names and literal values
+pub fn spell(token: &Token, choices: &mut impl Choices) -> String {
+    /// The names identifiers are chosen from
+    const NAMES: [&str; 6] = ["x", "y", "n", "p", "foo", "Node"];
+    let pick = |choices: &mut dyn FnMut(u32) -> u32, options: &[&str]| {
+        let count = u32::try_from(options.len()).unwrap_or(u32::MAX);
+        options[choices(count) as usize].to_string()
+    };
+    let mut below = |bound| choices.below(bound);
+    match token {
+        Token::Ident(_) => pick(&mut below, &NAMES),
+        Token::IntLit(_) => pick(&mut below, &["0", "1", "42", "0x7f", "4294967296"]),
+        Token::FloatLit(_) => pick(&mut below, &["0.5", "1.", "2e10", "3.25e-3"]),
+        Token::CharLit(_) => pick(&mut below, &["'a'", "'\\n'", "'\\0'", "'\\''"]),
+        Token::StringLit(_) => pick(&mut below, &["\"\"", "\"hello\"", "\"a\\tb\\\"\""]),
+        other => fixed_spelling(other).to_string(),
+    }
+}
+
+/// Get the text of a token kind that's always spelled the same way
+const fn fixed_spelling(token: &Token) -> &'static str {
+    match token {
+        Token::Struct => "struct",
+        Token::If => "if",
+        Token::Else => "else",
+        Token::While => "while",
+        Token::Do => "do",
+        Token::For => "for",
+        Token::Return => "return",
+        Token::Break => "break",
+        Token::Continue => "continue",
+        Token::Sizeof => "sizeof",
+        Token::True => "true",
+        Token::False => "false",
+        Token::Void => "void",
+        Token::Bool => "bool",
+        Token::Char => "char",
+        Token::Short => "short",
+        Token::Int => "int",
+        Token::Long => "long",
+        Token::Unsigned => "unsigned",
+        Token::Float => "float",
+        Token::Double => "double",
+        Token::Ident(_)
+        | Token::FloatLit(_)
+        | Token::IntLit(_)
+        | Token::CharLit(_)
+        | Token::StringLit(_) => "",
+        Token::Arrow => "->",
+        Token::PlusPlus => "++",
+        Token::MinusMinus => "--",
+        Token::PlusEq => "+=",
+        Token::MinusEq => "-=",
+        Token::StarEq => "*=",
+        Token::SlashEq => "/=",
+        Token::PercentEq => "%=",
+        Token::AmpEq => "&=",
+        Token::PipeEq => "|=",
+        Token::CaretEq => "^=",
+        Token::ShlEq => "<<=",
+        Token::ShrEq => ">>=",
+        Token::Shl => "<<",
+        Token::Shr => ">>",
+        Token::LessEq => "<=",
+        Token::GreaterEq => ">=",
+        Token::EqEq => "==",
+        Token::NotEq => "!=",
+        Token::AmpAmp => "&&",
+        Token::PipePipe => "||",
+        Token::Plus => "+",
+        Token::Minus => "-",
+        Token::Star => "*",
+        Token::Slash => "/",
+        Token::Percent => "%",
+        Token::Amp => "&",
+        Token::Pipe => "|",
+        Token::Caret => "^",
+        Token::Tilde => "~",
+        Token::Bang => "!",
+        Token::Eq => "=",
+        Token::Less => "<",
+        Token::Greater => ">",
+        Token::Question => "?",
+        Token::Colon => ":",
+        Token::Comma => ",",
+        Token::Semi => ";",
+        Token::Dot => ".",
+        Token::LParen => "(",
+        Token::RParen => ")",
+        Token::LBrace => "{",
+        Token::RBrace => "}",
+        Token::LBracket => "[",
+        Token::RBracket => "]",
+    }
+}
@@ -0,0 +1,64 @@
+//! sea-c - SeaFlow reference C-like language frontend
+//!
+//! SeaC is a small C-like language: functions, structs, pointers, arrays, loops,
+//! `if`/`else`, `return`, and C's expression operators, without the preprocessor,
+//! typedefs, or unions. [`parse`] turns a source file into a [`Program`], [`resolve`] links
+//! its names to their declarations, [`check`] type-checks it for a target into a
+//! [`TypedProgram`](typed::TypedProgram), and [`grammar`] describes the same syntax for
+//! generating test programs.
+
+#![warn(
+    clippy::all,
+    clippy::cargo,
+    clippy::missing_docs_in_private_items,
+    clippy::nursery,
+    clippy::pedantic,
+    missing_docs,
+    rustdoc::all
+)]
+
+pub mod ast;
+mod check;
+mod error;
+mod grammar;
+mod parser;
+mod pretty;
+mod resolve;
+mod token;
+pub mod typed;
+
+pub use ast::Program;
+pub use check::*;
+pub use error::*;
+pub use grammar::*;
+pub use resolve::*;
+pub use token::*;
+
+use sea_lex::FileId;
+use sea_parse::Parser;
+
+/// Parse the SeaC source `source` of `file`, reporting every error found
+///
+/// # Errors
+///
+/// Returns every lexing or parsing error, in source order
+pub fn parse(file: FileId, source: &str) -> Result<Program, Vec<Error>> {
+    match parse_recovering(file, source) {
+        (Some(program), errors) if errors.is_empty() => Ok(program),
+        (_, errors) => Err(errors),
+    }
+}
+
+/// Parse the SeaC source `source` of `file`, recovering from errors
+///
+/// Returns the program, with [`Item::Error`](ast::Item::Error) and
+/// [`StmtKind::Error`](ast::StmtKind::Error) nodes where parsing failed, along with every
+/// error found. The program is `None` only if the source couldn't be lexed.
+pub fn parse_recovering(file: FileId, source: &str) -> (Option<Program>, Vec<Error>) {
+    let tokens = match Token::lexer(source).collect() {
+        Ok(tokens) => tokens,
+        Err(error) => return (None, vec![error.into()]),
+    };
+    let (program, errors) = parser::program().parse_recovering(file, tokens);
+    (program, errors.into_iter().map(Error::from).collect())
+}
@@ -0,0 +1,618 @@
+//! The SeaC parser, built from sea-parse combinators
+
+use crate::ast::{
+    BinaryOp, Block, Expr, ExprKind, Field, Function, Ident, Item, Param, Primitive, Program, Stmt,
+    StmtKind, StructDef, Type, TypeKind, UnaryOp, VarDecl,
+};
+use crate::Token;
+use sea_lex::{Span, TokenInfo};
+use sea_parse::{
+    choice, empty, expect, many, optional, recursive, select, sep_by, seq, skip_to, token, Assoc,
+    BoxedParser, Input, ParseResult, Parser, Pratt, Recursive,
+};
+use std::iter;
+use std::mem::discriminant;
+
+/// A boxed SeaC parser
+type Boxed<O> = BoxedParser<'static, Token, O>;
+
+/// A parser for a whole source file
+pub fn program() -> Boxed<Program> {
+    let expr = expr();
+    let stmt = stmt(expr.clone());
+    many(item(expr, stmt))
+        .map(|items| Program { items })
+        .boxed()
+}
+
+/// Parse `then` if the next token is of the same kind as `kind`, and `otherwise` if it isn't
+///
+/// Unlike [`choice`], this never backtracks into `otherwise` once `kind` has been seen.
+/// Parsers using [`expect`] can repair almost any input into a match, so backtracking
+/// would hide the real error behind a bogus "insert `;`".
+fn branch<O>(
+    kind: Token,
+    then: impl Parser<Token, Output = O>,
+    otherwise: impl Parser<Token, Output = O>,
+) -> impl Parser<Token, Output = O> {
+    move |input: &mut Input<Token>| -> ParseResult<Token, O> {
+        if input
+            .peek()
+            .is_some_and(|token| discriminant(&token.kind) == discriminant(&kind))
+        {
+            then.parse(input)
+        } else {
+            otherwise.parse(input)
+        }
+    }
+}
+
+/// A parser for an optional `= value` initializer
+fn initializer(expr: Recursive<'static, Token, Expr>) -> Boxed<Option<Expr>> {
+    branch(
+        Token::Eq,
+        token(Token::Eq).ignore_then(expr).map(Some),
+        empty(None),
+    )
+    .boxed()
+}
+
+/// A parser for an identifier
+fn ident() -> Boxed<Ident> {
+    select(|token: &TokenInfo<Token>| match &token.kind {
+        Token::Ident(name) => Some(name.clone()),
+        _ => None,
+    })
+    .map_with_span(|name, span| Ident { name, span })
+    .labelled("identifier")
+    .boxed()
+}
+
+/// A parser for a type, without array dimensions
+fn ty() -> Boxed<Type> {
+    seq((base_type(), stars()))
+        .map(|(base, stars)| pointers(base, stars))
+        .boxed()
+}
+
+/// A parser for a type without pointers or array dimensions, which a declaration starts
+/// with
+fn base_type() -> Boxed<Type> {
+    let unsigned = token(Token::Unsigned)
+        .ignore_then(optional(select(|token: &TokenInfo<Token>| {
+            match token.kind {
+                Token::Char => Some(Primitive::UChar),
+                Token::Short => Some(Primitive::UShort),
+                Token::Int => Some(Primitive::UInt),
+                Token::Long => Some(Primitive::ULong),
+                _ => None,
+            }
+        })))
+        .map(|primitive| primitive.unwrap_or(Primitive::UInt));
+    let primitive = select(|token: &TokenInfo<Token>| match token.kind {
+        Token::Void => Some(Primitive::Void),
+        Token::Bool => Some(Primitive::Bool),
+        Token::Char => Some(Primitive::Char),
+        Token::Short => Some(Primitive::Short),
+        Token::Int => Some(Primitive::Int),
+        Token::Long => Some(Primitive::Long),
+        Token::Float => Some(Primitive::Float),
+        Token::Double => Some(Primitive::Double),
+        _ => None,
+    });
+    choice((
+        choice((unsigned, primitive)).map(TypeKind::Primitive),
+        token(Token::Struct)
+            .ignore_then(ident())
+            .map(TypeKind::Struct),
+    ))
+    .map_with_span(|kind, span| Type { kind, span })
+    .labelled("type")
+    .boxed()
+}
+
+/// A parser for the `*`s making a type a pointer, giving their locations
+fn stars() -> Boxed<Vec<Span>> {
+    many(token(Token::Star).map_with_span(|_, span| span)).boxed()
+}
+
+/// Wrap `base` in a pointer for each `*` after it
+fn pointers(base: Type, stars: Vec<Span>) -> Type {
+    stars.into_iter().fold(base, |pointee, star| Type {
+        span: pointee.span.to(star),
+        kind: TypeKind::Pointer(Box::new(pointee)),
+    })
+}
+
+/// A parser for the array dimensions after a declared name, like `[4][]`
+fn dims() -> Boxed<Vec<(Option<u64>, Span)>> {
+    let length = select(|token: &TokenInfo<Token>| match token.kind {
+        Token::IntLit(length) => Some(length),
+        _ => None,
+    })
+    .labelled("array length");
+    many(
+        seq((
+            token(Token::LBracket),
+            optional(length),
+            token(Token::RBracket),
+        ))
+        .map_with_span(|(_, length, _), span| (length, span)),
+    )
+    .boxed()
+}
+
+/// Wrap `ty` in the array dimensions declared after a name
+///
+/// The first dimension is the outermost: `int a[2][3]` is two arrays of three `int`s.
+fn with_dims(ty: Type, dims: Vec<(Option<u64>, Span)>) -> Type {
+    let span = dims.last().map_or(ty.span, |&(_, last)| ty.span.to(last));
+    dims.into_iter()
+        .rev()
+        .fold(ty, |element, (length, _)| Type {
+            kind: TypeKind::Array(Box::new(element), length),
+            span,
+        })
+}
+
+/// A parser for an expression without the comma operator, as C allows in arguments and
+/// initializers
+fn expr() -> Recursive<'static, Token, Expr> {
+    recursive(|expr| {
+        let literal = select(|token: &TokenInfo<Token>| match &token.kind {
+            Token::IntLit(value) => Some(ExprKind::Int(*value)),
+            Token::FloatLit(value) => Some(ExprKind::Float(*value)),
+            Token::CharLit(value) => Some(ExprKind::Char(*value)),
+            Token::StringLit(value) => Some(ExprKind::String(value.clone())),
+            Token::True => Some(ExprKind::Bool(true)),
+            Token::False => Some(ExprKind::Bool(false)),
+            _ => None,
+        })
+        .map_with_span(|kind, span| Expr { kind, span });
+        let var = ident().map(|name| Expr {
+            span: name.span,
+            kind: ExprKind::Var(name),
+        });
+        let paren = comma(expr.clone()).delimited_by(token(Token::LParen), token(Token::RParen));
+        let primary = choice((literal, var, paren));
+
+        let postfix_expr = seq((primary, many(postfix_op(expr.clone())))).map(|(base, ops)| {
+            ops.into_iter().fold(base, |base, (op, span)| Expr {
+                span: base.span.to(span),
+                kind: op(base),
+            })
+        });
+
+        let unary = recursive(|unary| {
+            let prefix_op = select(|token: &TokenInfo<Token>| match token.kind {
+                Token::Minus => Some(UnaryOp::Neg),
+                Token::Plus => Some(UnaryOp::Plus),
+                Token::Bang => Some(UnaryOp::Not),
+                Token::Tilde => Some(UnaryOp::BitNot),
+                Token::Star => Some(UnaryOp::Deref),
+                Token::Amp => Some(UnaryOp::AddrOf),
+                Token::PlusPlus => Some(UnaryOp::PreInc),
+                Token::MinusMinus => Some(UnaryOp::PreDec),
+                _ => None,
+            });
+            let prefix = seq((prefix_op, unary.clone()))
+                .map(|(op, operand)| ExprKind::Unary(op, Box::new(operand)));
+            let cast = seq((
+                ty().delimited_by(token(Token::LParen), token(Token::RParen)),
+                unary.clone(),
+            ))
+            .map(|(ty, operand)| ExprKind::Cast(ty, Box::new(operand)));
+            let sizeof_type = token(Token::Sizeof)
+                .ignore_then(ty().delimited_by(token(Token::LParen), token(Token::RParen)))
+                .map(ExprKind::SizeofType);
+            let sizeof_expr = token(Token::Sizeof)
+                .ignore_then(unary)
+                .map(|operand| ExprKind::SizeofExpr(Box::new(operand)));
+
+            choice((
+                choice((prefix, cast, sizeof_type, sizeof_expr))
+                    .map_with_span(|kind, span| Expr { kind, span }),
+                postfix_expr,
+            ))
+            .labelled("expression")
+        });
+
+        binary_operators(Pratt::new(unary)).labelled("expression")
+    })
+}
+
+/// The postfix operators, as functions building the expression from the operand
+type PostfixOp = Box<dyn FnOnce(Expr) -> ExprKind>;
+
+/// A parser for a postfix operator: a call, index, member access, or increment
+fn postfix_op(expr: Recursive<'static, Token, Expr>) -> Boxed<(PostfixOp, Span)> {
+    let call = sep_by(expr.clone(), token(Token::Comma))
+        .delimited_by(token(Token::LParen), token(Token::RParen))
+        .map(|args| -> PostfixOp { Box::new(|callee| ExprKind::Call(Box::new(callee), args)) });
+    let index = comma(expr)
+        .delimited_by(token(Token::LBracket), token(Token::RBracket))
+        .map(|index| -> PostfixOp {
+            Box::new(|base| ExprKind::Index(Box::new(base), Box::new(index)))
+        });
+    let member = token(Token::Dot)
+        .ignore_then(ident())
+        .map(|field| -> PostfixOp { Box::new(|base| ExprKind::Member(Box::new(base), field)) });
+    let ptr_member = token(Token::Arrow)
+        .ignore_then(ident())
+        .map(|field| -> PostfixOp { Box::new(|base| ExprKind::PtrMember(Box::new(base), field)) });
+    let increment = select(|token: &TokenInfo<Token>| match token.kind {
+        Token::PlusPlus => Some(UnaryOp::PostInc),
+        Token::MinusMinus => Some(UnaryOp::PostDec),
+        _ => None,
+    })
+    .map(|op| -> PostfixOp { Box::new(move |operand| ExprKind::Unary(op, Box::new(operand))) });
+
+    choice((call, index, member, ptr_member, increment))
+        .map_with_span(|op, span| (op, span))
+        .boxed()
+}
+
+/// Add C's binary, conditional, and assignment operators to an operator parser
+fn binary_operators<P: Parser<Token, Output = Expr>>(
+    pratt: Pratt<'static, Token, Expr, P>,
+) -> Pratt<'static, Token, Expr, P> {
+    /// Build a binary expression
+    fn binary(op: BinaryOp) -> impl Fn(Expr, TokenInfo<Token>, Expr, Span) -> Expr {
+        move |lhs, _, rhs, span| Expr {
+            kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+            span,
+        }
+    }
+
+    /// Build an assignment, with an optional compound operator
+    fn assign(op: Option<BinaryOp>) -> impl Fn(Expr, TokenInfo<Token>, Expr, Span) -> Expr {
+        move |lhs, _, rhs, span| Expr {
+            kind: ExprKind::Assign(op, Box::new(lhs), Box::new(rhs)),
+            span,
+        }
+    }
+
+    let assignments = [
+        (Token::Eq, None),
+        (Token::PlusEq, Some(BinaryOp::Add)),
+        (Token::MinusEq, Some(BinaryOp::Sub)),
+        (Token::StarEq, Some(BinaryOp::Mul)),
+        (Token::SlashEq, Some(BinaryOp::Div)),
+        (Token::PercentEq, Some(BinaryOp::Rem)),
+        (Token::AmpEq, Some(BinaryOp::BitAnd)),
+        (Token::PipeEq, Some(BinaryOp::BitOr)),
+        (Token::CaretEq, Some(BinaryOp::BitXor)),
+        (Token::ShlEq, Some(BinaryOp::Shl)),
+        (Token::ShrEq, Some(BinaryOp::Shr)),
+    ];
+    let binaries = [
+        (Token::PipePipe, BinaryOp::Or, 3),
+        (Token::AmpAmp, BinaryOp::And, 4),
+        (Token::Pipe, BinaryOp::BitOr, 5),
+        (Token::Caret, BinaryOp::BitXor, 6),
+        (Token::Amp, BinaryOp::BitAnd, 7),
+        (Token::EqEq, BinaryOp::Eq, 8),
+        (Token::NotEq, BinaryOp::NotEq, 8),
+        (Token::Less, BinaryOp::Less, 9),
+        (Token::LessEq, BinaryOp::LessEq, 9),
+        (Token::Greater, BinaryOp::Greater, 9),
+        (Token::GreaterEq, BinaryOp::GreaterEq, 9),
+        (Token::Shl, BinaryOp::Shl, 10),
+        (Token::Shr, BinaryOp::Shr, 10),
+        (Token::Plus, BinaryOp::Add, 11),
+        (Token::Minus, BinaryOp::Sub, 11),
+        (Token::Star, BinaryOp::Mul, 12),
+        (Token::Slash, BinaryOp::Div, 12),
+        (Token::Percent, BinaryOp::Rem, 12),
+    ];
+
+    let pratt = assignments
+        .into_iter()
+        .fold(pratt, |pratt, (kind, op)| {
+            pratt.infix(kind, 1, Assoc::Right, assign(op))
+        })
+        .ternary(
+            Token::Question,
+            Token::Colon,
+            2,
+            |cond, then, otherwise, span| Expr {
+                kind: ExprKind::Conditional(Box::new(cond), Box::new(then), Box::new(otherwise)),
+                span,
+            },
+        );
+    binaries
+        .into_iter()
+        .fold(pratt, |pratt, (kind, op, precedence)| {
+            pratt.infix(kind, precedence, Assoc::Left, binary(op))
+        })
+}
+
+/// A parser for a comma expression, `a, b`, of the expressions parsed by `expr`
+fn comma(expr: Recursive<'static, Token, Expr>) -> Boxed<Expr> {
+    seq((expr.clone(), many(token(Token::Comma).ignore_then(expr))))
+        .map(|(first, rest)| {
+            rest.into_iter().fold(first, |lhs, rhs| Expr {
+                span: lhs.span.to(rhs.span),
+                kind: ExprKind::Comma(Box::new(lhs), Box::new(rhs)),
+            })
+        })
+        .boxed()
+}
+
+/// One variable declared after a declaration's base type: `*name[4] = init`
+struct Declarator {
+    /// The location of each `*` making the variable a pointer
+    stars: Vec<Span>,
+    /// The variable's name
+    name: Ident,
+    /// The array dimensions after the name
+    dims: Vec<(Option<u64>, Span)>,
+    /// The initial value
+    init: Option<Expr>,
+}
+
+impl Declarator {
+    /// The location of the declarator, from its first `*` to the end of its initializer
+    fn span(&self) -> Span {
+        let start = self.stars.first().map_or(self.name.span, |&star| star);
+        let end = self.init.as_ref().map_or_else(
+            || self.dims.last().map_or(self.name.span, |&(_, span)| span),
+            |init| init.span,
+        );
+        start.to(end)
+    }
+}
+
+/// A parser for a declarator, with an initializer parsed by `init`
+fn declarator(init: Boxed<Option<Expr>>) -> Boxed<Declarator> {
+    seq((stars(), ident(), dims(), init))
+        .map(|(stars, name, dims, init)| Declarator {
+            stars,
+            name,
+            dims,
+            init,
+        })
+        .boxed()
+}
+
+/// Declare the variables of a declaration starting with the type `base`
+///
+/// The first variable's declaration includes the base type.
+fn declare(base: &Type, declarators: impl IntoIterator<Item = Declarator>) -> Vec<VarDecl> {
+    declarators
+        .into_iter()
+        .enumerate()
+        .map(|(index, declarator)| {
+            let span = declarator.span();
+            VarDecl {
+                ty: with_dims(pointers(base.clone(), declarator.stars), declarator.dims),
+                name: declarator.name,
+                init: declarator.init,
+                span: if index == 0 { base.span.to(span) } else { span },
+            }
+        })
+        .collect()
+}
+
+/// A parser for a declaration of one or more variables, without the trailing `;`
+fn var_decls(init: Boxed<Option<Expr>>) -> Boxed<Vec<VarDecl>> {
+    seq((
+        base_type(),
+        declarator(init.clone()),
+        many(token(Token::Comma).ignore_then(declarator(init))),
+    ))
+    .map(|(base, first, rest)| declare(&base, iter::once(first).chain(rest)))
+    .boxed()
+}
+
+/// A parser for a block
+fn block(stmt: Recursive<'static, Token, Stmt>) -> Boxed<Block> {
+    seq((token(Token::LBrace), many(stmt), expect(Token::RBrace)))
+        .map_with_span(|(_, stmts, _), span| Block { stmts, span })
+        .boxed()
+}
+
+/// A parser for a statement
+///
+/// A statement that can't be parsed is skipped up to the next `;` (outside of any
+/// braces), or the end of the enclosing block, becoming a [`StmtKind::Error`].
+fn stmt(assign: Recursive<'static, Token, Expr>) -> Recursive<'static, Token, Stmt> {
+    recursive(move |stmt| {
+        let expr = comma(assign.clone());
+        let semi = || expect(Token::Semi);
+        let condition = || {
+            expr.clone()
+                .delimited_by(token(Token::LParen), token(Token::RParen))
+        };
+        let body = || stmt.clone().map(Box::new);
+
+        let decl = var_decls(initializer(assign.clone()))
+            .then_ignore(semi())
+            .map(StmtKind::Decl);
+        let if_stmt = seq((
+            token(Token::If),
+            condition(),
+            body(),
+            optional(token(Token::Else).ignore_then(body())),
+        ))
+        .map(|(_, cond, then, otherwise)| StmtKind::If {
+            cond,
+            then,
+            otherwise,
+        });
+        let while_stmt = seq((token(Token::While), condition(), body()))
+            .map(|(_, cond, body)| StmtKind::While { cond, body });
+        let do_while = seq((
+            token(Token::Do),
+            body(),
+            token(Token::While),
+            condition(),
+            semi(),
+        ))
+        .map(|(_, body, _, cond, _)| StmtKind::DoWhile { body, cond });
+
+        let for_init = choice((
+            var_decls(initializer(assign.clone())).map(StmtKind::Decl),
+            expr.clone().map(StmtKind::Expr),
+        ))
+        .map_with_span(|kind, span| Box::new(Stmt { kind, span }));
+        let for_stmt = seq((
+            token(Token::For),
+            token(Token::LParen),
+            optional(for_init),
+            token(Token::Semi),
+            optional(expr.clone()),
+            token(Token::Semi),
+            optional(expr.clone()),
+            token(Token::RParen),
+            body(),
+        ))
+        .map(|(_, _, init, _, cond, _, step, _, body)| StmtKind::For {
+            init,
+            cond,
+            step,
+            body,
+        });
+
+        let return_stmt = seq((token(Token::Return), optional(expr.clone()), semi()))
+            .map(|(_, value, _)| StmtKind::Return(value));
+        let break_stmt = token(Token::Break).then_ignore(semi()).to(StmtKind::Break);
+        let continue_stmt = token(Token::Continue)
+            .then_ignore(semi())
+            .to(StmtKind::Continue);
+        let empty = token(Token::Semi).to(StmtKind::Empty);
+        let expr_stmt = expr.then_ignore(semi()).map(StmtKind::Expr);
+
+        choice((
+            block(stmt.clone()).map(StmtKind::Block),
+            decl,
+            if_stmt,
+            while_stmt,
+            do_while,
+            for_stmt,
+            return_stmt,
+            break_stmt,
+            continue_stmt,
+            empty,
+            expr_stmt,
+        ))
+        .map_with_span(|kind, span| Stmt { kind, span })
+        .labelled("statement")
+        .recover_with(
+            skip_to(|span| Stmt {
+                kind: StmtKind::Error,
+                span,
+            })
+            .after([Token::Semi])
+            .before([Token::RBrace])
+            .balance(Token::LBrace, Token::RBrace),
+        )
+    })
+}
+
+/// The part of a declaration after the type and name
+enum ItemRest {
+    /// A function's parameters and body
+    Function(Vec<Param>, Option<Block>),
+    /// The rest of the first global's declarator, and the declarators after it
+    Global(Vec<(Option<u64>, Span)>, Option<Expr>, Vec<Declarator>),
+}
+
+/// A parser for a top-level item
+///
+/// An item that can't be parsed is skipped up to the next `;` or braced body, becoming
+/// an [`Item::Error`].
+fn item(
+    expr: Recursive<'static, Token, Expr>,
+    stmt: Recursive<'static, Token, Stmt>,
+) -> Boxed<Item> {
+    let fields = var_decls(empty(None).boxed())
+        .then_ignore(expect(Token::Semi))
+        .map(|decls| {
+            decls.into_iter().map(|decl| Field {
+                ty: decl.ty,
+                name: decl.name,
+                span: decl.span,
+            })
+        });
+    let struct_def = seq((
+        token(Token::Struct),
+        ident(),
+        token(Token::LBrace),
+        many(fields),
+        expect(Token::RBrace),
+        expect(Token::Semi),
+    ))
+    .map_with_span(|(_, name, _, fields, _, _), span| {
+        let fields = fields.into_iter().flatten().collect();
+        Item::Struct(StructDef { name, fields, span })
+    });
+
+    let param =
+        seq((ty(), optional(ident()), dims())).map_with_span(|(ty, name, dims), span| Param {
+            ty: with_dims(ty, dims),
+            name,
+            span,
+        });
+    let params = sep_by(param, token(Token::Comma))
+        .delimited_by(token(Token::LParen), token(Token::RParen))
+        .map(|params| match params.as_slice() {
+            // `(void)` declares that there are no parameters
+            [Param {
+                ty:
+                    Type {
+                        kind: TypeKind::Primitive(Primitive::Void),
+                        ..
+                    },
+                name: None,
+                ..
+            }] => Vec::new(),
+            _ => params,
+        });
+    let function_rest = seq((
+        params,
+        choice((block(stmt).map(Some), token(Token::Semi).to(None))),
+    ))
+    .map(|(params, body)| ItemRest::Function(params, body));
+    let global_rest = seq((
+        dims(),
+        initializer(expr.clone()),
+        many(token(Token::Comma).ignore_then(declarator(initializer(expr)))),
+        expect(Token::Semi),
+    ))
+    .map(|(dims, init, rest, _)| ItemRest::Global(dims, init, rest));
+
+    let declaration = seq((
+        base_type(),
+        stars(),
+        ident(),
+        branch(Token::LParen, function_rest, global_rest),
+    ))
+    .map_with_span(|(base, stars, name, rest), span| match rest {
+        ItemRest::Function(params, body) => Item::Function(Function {
+            return_type: pointers(base, stars),
+            name,
+            params,
+            body,
+            span,
+        }),
+        ItemRest::Global(dims, init, rest) => {
+            let first = Declarator {
+                stars,
+                name,
+                dims,
+                init,
+            };
+            Item::Global(declare(&base, iter::once(first).chain(rest)))
+        }
+    });
+
+    choice((struct_def, declaration))
+        .labelled("declaration")
+        .recover_with(
+            skip_to(Item::Error)
+                .after([Token::Semi, Token::RBrace])
+                .balance(Token::LBrace, Token::RBrace),
+        )
+        .boxed()
+}
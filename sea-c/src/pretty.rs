@@ -0,0 +1,406 @@
+//! Printing SeaC syntax trees back to source
+//!
+//! The printer writes the fewest parentheses the parser needs to rebuild the same tree,
+//! and breaks long expressions and argument lists to fit the width. Reparsing printed
+//! source gives back the tree it was printed from, apart from spans, and apart from
+//! braces added around an `if` without an `else` that would otherwise take the `else` of
+//! an enclosing `if`.
+
+use crate::ast::{
+    BinaryOp, Block, Expr, ExprKind, Function, Item, Program, Stmt, StmtKind, StructDef, Type,
+    TypeKind, VarDecl,
+};
+use sea_parse::{Doc, Pretty};
+
+/// The number of columns each nested block is indented by
+const INDENT: usize = 4;
+
+/// The precedence of the comma operator
+const COMMA: u8 = 0;
+/// The precedence of assignments
+const ASSIGN: u8 = 1;
+/// The precedence of `? :`
+const CONDITIONAL: u8 = 2;
+/// The precedence of prefix operators, casts, and `sizeof`
+const PREFIX: u8 = 13;
+/// The precedence of postfix operators, calls, indexing, and member accesses
+const POSTFIX: u8 = 14;
+/// The precedence of literals, names, and parenthesized expressions
+const PRIMARY: u8 = 15;
+
+impl Pretty for Program {
+    fn to_doc(&self) -> Doc {
+        let items = self.items.iter().map(Pretty::to_doc);
+        Doc::join(items, Doc::hardline() + Doc::hardline()) + Doc::hardline()
+    }
+}
+
+impl Pretty for Item {
+    fn to_doc(&self) -> Doc {
+        match self {
+            Self::Struct(def) => struct_def(def),
+            Self::Function(function) => function_doc(function),
+            Self::Global(decls) => var_decls(decls) + Doc::text(";"),
+            Self::Error(_) => Doc::text("/* error */"),
+        }
+    }
+}
+
+impl Pretty for Stmt {
+    fn to_doc(&self) -> Doc {
+        match &self.kind {
+            StmtKind::Block(block) => block_doc(block),
+            StmtKind::Decl(decls) => var_decls(decls) + Doc::text(";"),
+            StmtKind::Expr(expr) => expr_doc(expr, 0) + Doc::text(";"),
+            StmtKind::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                let head = Doc::text("if (") + expr_doc(cond, 0) + Doc::text(")");
+                let Some(otherwise) = otherwise else {
+                    return head + body(then);
+                };
+                // Braces keep a nested `if` from taking this one's `else`
+                let (then, separator) = if dangles(then) {
+                    let braced = Doc::text(" {")
+                        + (Doc::hardline() + then.to_doc()).nest(INDENT)
+                        + Doc::hardline()
+                        + Doc::text("}");
+                    (braced, Doc::text(" "))
+                } else if matches!(then.kind, StmtKind::Block(_)) {
+                    (body(then), Doc::text(" "))
+                } else {
+                    (body(then), Doc::hardline())
+                };
+                let otherwise = if matches!(otherwise.kind, StmtKind::If { .. }) {
+                    Doc::text(" ") + otherwise.to_doc()
+                } else {
+                    body(otherwise)
+                };
+                head + then + separator + Doc::text("else") + otherwise
+            }
+            StmtKind::While { cond, body: inner } => {
+                Doc::text("while (") + expr_doc(cond, 0) + Doc::text(")") + body(inner)
+            }
+            StmtKind::DoWhile { body: inner, cond } => {
+                let separator = if matches!(inner.kind, StmtKind::Block(_)) {
+                    Doc::text(" ")
+                } else {
+                    Doc::hardline()
+                };
+                Doc::text("do")
+                    + body(inner)
+                    + separator
+                    + Doc::text("while (")
+                    + expr_doc(cond, 0)
+                    + Doc::text(");")
+            }
+            StmtKind::For {
+                init,
+                cond,
+                step,
+                body: inner,
+            } => {
+                let init = init
+                    .as_deref()
+                    .map_or_else(Doc::nil, |init| match &init.kind {
+                        StmtKind::Decl(decls) => var_decls(decls),
+                        StmtKind::Expr(expr) => expr_doc(expr, 0),
+                        _ => init.to_doc(),
+                    });
+                let optional = |expr: &Option<Expr>| {
+                    expr.as_ref()
+                        .map_or_else(Doc::nil, |expr| Doc::text(" ") + expr_doc(expr, 0))
+                };
+                let header = Doc::text("for (")
+                    + init
+                    + Doc::text(";")
+                    + optional(cond)
+                    + Doc::text(";")
+                    + optional(step)
+                    + Doc::text(")");
+                header.nest(INDENT).group() + body(inner)
+            }
+            StmtKind::Return(None) => Doc::text("return;"),
+            StmtKind::Return(Some(value)) => {
+                Doc::text("return ") + expr_doc(value, 0) + Doc::text(";")
+            }
+            StmtKind::Break => Doc::text("break;"),
+            StmtKind::Continue => Doc::text("continue;"),
+            StmtKind::Empty => Doc::text(";"),
+            StmtKind::Error => Doc::text("/* error */;"),
+        }
+    }
+}
+
+impl Pretty for Expr {
+    fn to_doc(&self) -> Doc {
+        expr_doc(self, 0)
+    }
+}
+
+/// A struct definition
+fn struct_def(def: &StructDef) -> Doc {
+    let fields = def.fields.iter().map(|field| {
+        Doc::hardline() + declaration(&field.ty, Some(&field.name.name)) + Doc::text(";")
+    });
+    Doc::text(format!("struct {} {{", def.name.name))
+        + Doc::concat(fields).nest(INDENT)
+        + Doc::hardline()
+        + Doc::text("};")
+}
+
+/// A function definition or declaration
+fn function_doc(function: &Function) -> Doc {
+    let params = function.params.iter().map(|param| {
+        declaration(
+            &param.ty,
+            param.name.as_ref().map(|name| name.name.as_str()),
+        )
+    });
+    let signature =
+        declaration(&function.return_type, Some(&function.name.name)) + list("(", params, ")");
+    match &function.body {
+        Some(body) => signature + Doc::text(" ") + block_doc(body),
+        None => signature + Doc::text(";"),
+    }
+}
+
+/// A declaration of one or more variables, without the `;`
+///
+/// The variables after the first are written as just their declarators, so they're
+/// declared with the first one's base type.
+fn var_decls(decls: &[VarDecl]) -> Doc {
+    let decls = decls.iter().enumerate().map(|(index, decl)| {
+        let declaration = if index == 0 {
+            declaration(&decl.ty, Some(&decl.name.name))
+        } else {
+            declarator(&decl.ty, &decl.name.name)
+        };
+        match &decl.init {
+            Some(init) => declaration + Doc::text(" = ") + expr_doc(init, ASSIGN),
+            None => declaration,
+        }
+    });
+    Doc::join(decls, Doc::text(",") + Doc::line())
+        .nest(INDENT)
+        .group()
+}
+
+/// A declaration of `name` with type `ty`, with any array dimensions after the name
+fn declaration(ty: &Type, name: Option<&str>) -> Doc {
+    let (base, dims) = array_dims(ty);
+    let name = name.map_or_else(String::new, |name| format!(" {name}"));
+    Doc::text(format!("{base}{name}{dims}"))
+}
+
+/// A declarator of `name` with type `ty`, without the base type: `*name[4]`
+fn declarator(ty: &Type, name: &str) -> Doc {
+    let (mut base, dims) = array_dims(ty);
+    let mut stars = String::new();
+    while let TypeKind::Pointer(pointee) = &base.kind {
+        stars.push('*');
+        base = pointee;
+    }
+    Doc::text(format!("{stars}{name}{dims}"))
+}
+
+/// Split the array dimensions off `ty`, giving the element type and the dimensions as
+/// written after a name, like `[2][3]`
+fn array_dims(ty: &Type) -> (&Type, String) {
+    let mut dims = String::new();
+    let mut base = ty;
+    while let TypeKind::Array(element, length) = &base.kind {
+        dims.push('[');
+        if let Some(length) = length {
+            dims.push_str(&length.to_string());
+        }
+        dims.push(']');
+        base = element;
+    }
+    (base, dims)
+}
+
+/// A block, with each statement on its own line
+fn block_doc(block: &Block) -> Doc {
+    if block.stmts.is_empty() {
+        return Doc::text("{}");
+    }
+    let stmts = block
+        .stmts
+        .iter()
+        .map(|stmt| Doc::hardline() + stmt.to_doc());
+    Doc::text("{") + Doc::concat(stmts).nest(INDENT) + Doc::hardline() + Doc::text("}")
+}
+
+/// The body of a control statement: a block on the same line, or another statement
+/// indented on the next
+fn body(stmt: &Stmt) -> Doc {
+    match &stmt.kind {
+        StmtKind::Block(block) => Doc::text(" ") + block_doc(block),
+        _ => (Doc::hardline() + stmt.to_doc()).nest(INDENT),
+    }
+}
+
+/// Reports whether `stmt` ends with an `if` without an `else`, which would take the
+/// `else` of an enclosing `if`
+fn dangles(stmt: &Stmt) -> bool {
+    match &stmt.kind {
+        StmtKind::If {
+            otherwise: None, ..
+        } => true,
+        StmtKind::If {
+            otherwise: Some(body),
+            ..
+        }
+        | StmtKind::While { body, .. }
+        | StmtKind::For { body, .. } => dangles(body),
+        _ => false,
+    }
+}
+
+/// An expression, parenthesized if its precedence is below `min`
+fn expr_doc(expr: &Expr, min: u8) -> Doc {
+    let precedence = precedence(expr);
+    let doc = match &expr.kind {
+        ExprKind::Int(value) => Doc::text(value.to_string()),
+        ExprKind::Float(value) => Doc::text(format!("{value:?}")),
+        ExprKind::Char(value) => Doc::text(format!("'{}'", escape(char::from(*value), '\''))),
+        ExprKind::String(value) => {
+            let escaped: String = value.chars().map(|c| escape(c, '"')).collect();
+            Doc::text(format!("\"{escaped}\""))
+        }
+        ExprKind::Bool(value) => Doc::text(value.to_string()),
+        ExprKind::Var(name) => Doc::text(name.name.clone()),
+        ExprKind::Unary(op, operand) if op.is_postfix() => {
+            expr_doc(operand, POSTFIX) + Doc::text(op.symbol())
+        }
+        ExprKind::Unary(op, operand) => {
+            // `- -x` and `& &x` would lex as `--x` and `&&x` without the space
+            let collides = match &operand.kind {
+                ExprKind::Unary(inner, _) if !inner.is_postfix() => {
+                    let last = op.symbol().chars().last();
+                    matches!(last, Some('-' | '+' | '&'))
+                        && last.is_some_and(|last| inner.symbol().starts_with(last))
+                }
+                _ => false,
+            };
+            let space = if collides { " " } else { "" };
+            Doc::text(op.symbol()) + Doc::text(space) + expr_doc(operand, PREFIX)
+        }
+        ExprKind::Binary(op, lhs, rhs) => {
+            let precedence = binary_precedence(*op);
+            (expr_doc(lhs, precedence)
+                + Doc::text(format!(" {}", op.symbol()))
+                + (Doc::line() + expr_doc(rhs, precedence + 1)).nest(INDENT))
+            .group()
+        }
+        ExprKind::Assign(op, lhs, rhs) => {
+            let op = op.map_or("", BinaryOp::symbol);
+            (expr_doc(lhs, CONDITIONAL)
+                + Doc::text(format!(" {op}="))
+                + (Doc::line() + expr_doc(rhs, ASSIGN)).nest(INDENT))
+            .group()
+        }
+        ExprKind::Conditional(cond, then, otherwise) => (expr_doc(cond, CONDITIONAL + 1)
+            + (Doc::line()
+                + Doc::text("? ")
+                + expr_doc(then, ASSIGN)
+                + Doc::line()
+                + Doc::text(": ")
+                + expr_doc(otherwise, CONDITIONAL))
+            .nest(INDENT))
+        .group(),
+        ExprKind::Comma(lhs, rhs) => (expr_doc(lhs, COMMA)
+            + Doc::text(",")
+            + (Doc::line() + expr_doc(rhs, ASSIGN)).nest(INDENT))
+        .group(),
+        ExprKind::Call(callee, args) => {
+            let args = args.iter().map(|arg| expr_doc(arg, ASSIGN));
+            expr_doc(callee, POSTFIX) + list("(", args, ")")
+        }
+        ExprKind::Index(base, index) => {
+            expr_doc(base, POSTFIX) + Doc::text("[") + expr_doc(index, 0) + Doc::text("]")
+        }
+        ExprKind::Member(base, field) => {
+            expr_doc(base, POSTFIX) + Doc::text(format!(".{}", field.name))
+        }
+        ExprKind::PtrMember(base, field) => {
+            expr_doc(base, POSTFIX) + Doc::text(format!("->{}", field.name))
+        }
+        ExprKind::Cast(ty, operand) => {
+            Doc::text("(") + declaration(ty, None) + Doc::text(")") + expr_doc(operand, PREFIX)
+        }
+        ExprKind::SizeofType(ty) => Doc::text("sizeof(") + declaration(ty, None) + Doc::text(")"),
+        // An operand starting with a cast would be taken for `sizeof(type)`
+        ExprKind::SizeofExpr(operand) => Doc::text("sizeof ") + expr_doc(operand, POSTFIX),
+    };
+    if precedence < min {
+        Doc::text("(") + doc + Doc::text(")")
+    } else {
+        doc
+    }
+}
+
+/// A comma-separated list between `open` and `close`, broken one item per line if it
+/// doesn't fit
+fn list(open: &'static str, items: impl IntoIterator<Item = Doc>, close: &'static str) -> Doc {
+    let items = Doc::join(items, Doc::text(",") + Doc::line());
+    (Doc::text(open) + (Doc::softline() + items).nest(INDENT) + Doc::softline() + Doc::text(close))
+        .group()
+}
+
+/// The precedence of an expression's outermost operator
+const fn precedence(expr: &Expr) -> u8 {
+    match &expr.kind {
+        ExprKind::Comma(..) => COMMA,
+        ExprKind::Assign(..) => ASSIGN,
+        ExprKind::Conditional(..) => CONDITIONAL,
+        ExprKind::Binary(op, ..) => binary_precedence(*op),
+        ExprKind::Unary(op, _) if op.is_postfix() => POSTFIX,
+        ExprKind::Unary(..)
+        | ExprKind::Cast(..)
+        | ExprKind::SizeofType(_)
+        | ExprKind::SizeofExpr(_) => PREFIX,
+        ExprKind::Call(..)
+        | ExprKind::Index(..)
+        | ExprKind::Member(..)
+        | ExprKind::PtrMember(..) => POSTFIX,
+        ExprKind::Int(_)
+        | ExprKind::Float(_)
+        | ExprKind::Char(_)
+        | ExprKind::String(_)
+        | ExprKind::Bool(_)
+        | ExprKind::Var(_) => PRIMARY,
+    }
+}
+
+/// The precedence of a binary operator, as the parser gives it
+const fn binary_precedence(op: BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Or => 3,
+        BinaryOp::And => 4,
+        BinaryOp::BitOr => 5,
+        BinaryOp::BitXor => 6,
+        BinaryOp::BitAnd => 7,
+        BinaryOp::Eq | BinaryOp::NotEq => 8,
+        BinaryOp::Less | BinaryOp::LessEq | BinaryOp::Greater | BinaryOp::GreaterEq => 9,
+        BinaryOp::Shl | BinaryOp::Shr => 10,
+        BinaryOp::Add | BinaryOp::Sub => 11,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 12,
+    }
+}
+
+/// Escape a character in a literal delimited by `quote`
+fn escape(c: char, quote: char) -> String {
+    match c {
+        '\n' => "\\n".to_string(),
+        '\t' => "\\t".to_string(),
+        '\r' => "\\r".to_string(),
+        '\0' => "\\0".to_string(),
+        '\\' => "\\\\".to_string(),
+        c if c == quote => format!("\\{c}"),
+        c => c.to_string(),
+    }
+}
@@ -0,0 +1,281 @@
+//! Name resolution for SeaC
+//!
+//! Items are visible throughout the file, so functions can call functions defined after
+//! them, and a function may be declared any number of times before it's defined.
+//! Parameters and locals are visible after their declarations, in the block that
+//! declares them and the blocks nested in it.
+
+use crate::ast::{
+    Block, Expr, ExprKind, Function, Item, Program, Stmt, StmtKind, StructDef, Type, TypeKind,
+    VarDecl,
+};
+use crate::Error;
+use sea_lex::Span;
+use sea_parse::{
+    downcast_ref, ResolveError, ScopeGraph, ScopeId, ScopeKind, SymbolId, Visit, Visitor,
+};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The namespaces of SeaC names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    /// Functions, globals, parameters, and locals
+    Value,
+    /// Struct names, as in `struct Name`
+    Struct,
+    /// The fields of one struct
+    Field,
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Value => "value",
+            Self::Struct => "struct",
+            Self::Field => "field",
+        })
+    }
+}
+
+/// Resolve every variable and struct name in `program`
+///
+/// Returns the scope graph, where [`ScopeGraph::resolution`] maps the span of each
+/// resolved [`Ident`](crate::ast::Ident) to its declaration, along with every
+/// resolution error in source order. Field names in member expressions depend on types,
+/// so they're left to the type checker.
+pub fn resolve(program: &Program) -> (ScopeGraph<Namespace>, Vec<Error>) {
+    let mut resolver = Resolver {
+        graph: ScopeGraph::new(ScopeKind::Module),
+        defined: HashMap::new(),
+        errors: Vec::new(),
+    };
+    let root = resolver.graph.root();
+    for item in &program.items {
+        resolver.declare_item(item, root);
+    }
+    for item in &program.items {
+        resolver.item(item, root);
+    }
+    resolver.errors.sort_by_key(|error| error.span);
+    let errors = resolver.errors.into_iter().map(Error::from).collect();
+    (resolver.graph, errors)
+}
+
+/// The state of name resolution
+struct Resolver {
+    /// The scopes and symbols so far
+    graph: ScopeGraph<Namespace>,
+    /// Whether each function declared so far has been defined, with a body
+    defined: HashMap<SymbolId, bool>,
+    /// The errors so far
+    errors: Vec<ResolveError<Namespace>>,
+}
+
+impl Resolver {
+    /// Declare an item's name in the file scope
+    fn declare_item(&mut self, item: &Item, scope: ScopeId) {
+        match item {
+            Item::Struct(def) => {
+                self.declare(scope, Namespace::Struct, &def.name.name, def.name.span);
+            }
+            Item::Function(function) => self.declare_function(function, scope),
+            Item::Global(decls) => {
+                for decl in decls {
+                    self.declare(scope, Namespace::Value, &decl.name.name, decl.name.span);
+                }
+            }
+            Item::Error(_) => {}
+        }
+    }
+
+    /// Declare a function, merging it with earlier declarations of the same function
+    fn declare_function(&mut self, function: &Function, scope: ScopeId) {
+        let name = &function.name;
+        let defines = function.body.is_some();
+        let previous = self
+            .graph
+            .lookup_local(scope, Namespace::Value, &name.name)
+            .filter(|symbol| self.defined.contains_key(symbol));
+        match previous {
+            Some(symbol) if !(defines && self.defined[&symbol]) => {
+                self.defined
+                    .insert(symbol, defines || self.defined[&symbol]);
+            }
+            _ => {
+                if let Some(symbol) = self.declare(scope, Namespace::Value, &name.name, name.span) {
+                    self.defined.insert(symbol, defines);
+                }
+            }
+        }
+    }
+
+    /// Resolve the names in an item
+    fn item(&mut self, item: &Item, scope: ScopeId) {
+        match item {
+            Item::Struct(def) => self.struct_def(def, scope),
+            Item::Function(function) => self.function(function, scope),
+            Item::Global(decls) => {
+                for decl in decls {
+                    self.references(&decl.ty, scope);
+                    self.references(&decl.init, scope);
+                }
+            }
+            Item::Error(_) => {}
+        }
+    }
+
+    /// Resolve the field types of a struct, and check its field names are distinct
+    fn struct_def(&mut self, def: &StructDef, scope: ScopeId) {
+        let fields = self.graph.add_scope(scope, ScopeKind::Module);
+        for field in &def.fields {
+            self.references(&field.ty, scope);
+            self.declare(fields, Namespace::Field, &field.name.name, field.name.span);
+        }
+    }
+
+    /// Resolve the names in a function's signature and body
+    fn function(&mut self, function: &Function, scope: ScopeId) {
+        self.references(&function.return_type, scope);
+        // The parameters share a scope with the outermost statements of the body
+        let body = self.graph.add_scope(scope, ScopeKind::Block);
+        for param in &function.params {
+            self.references(&param.ty, scope);
+            if let (Some(name), Some(_)) = (&param.name, &function.body) {
+                self.declare(body, Namespace::Value, &name.name, name.span);
+            }
+        }
+        if let Some(block) = &function.body {
+            for stmt in &block.stmts {
+                self.stmt(stmt, body);
+            }
+        }
+    }
+
+    /// Resolve the names in a block, in a new scope
+    fn block(&mut self, block: &Block, scope: ScopeId) {
+        let scope = self.graph.add_scope(scope, ScopeKind::Block);
+        for stmt in &block.stmts {
+            self.stmt(stmt, scope);
+        }
+    }
+
+    /// Resolve the names in the body of an `if` or loop, whose declarations don't
+    /// outlive it
+    fn nested(&mut self, stmt: &Stmt, scope: ScopeId) {
+        if matches!(stmt.kind, StmtKind::Block(_)) {
+            self.stmt(stmt, scope);
+        } else {
+            let scope = self.graph.add_scope(scope, ScopeKind::Block);
+            self.stmt(stmt, scope);
+        }
+    }
+
+    /// Resolve the names in a statement, declaring any local it declares
+    fn stmt(&mut self, stmt: &Stmt, scope: ScopeId) {
+        match &stmt.kind {
+            StmtKind::Block(block) => self.block(block, scope),
+            StmtKind::Decl(decls) => {
+                for decl in decls {
+                    self.local(decl, scope);
+                }
+            }
+            StmtKind::Expr(expr) => self.references(expr, scope),
+            StmtKind::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                self.references(cond, scope);
+                self.nested(then, scope);
+                if let Some(otherwise) = otherwise {
+                    self.nested(otherwise, scope);
+                }
+            }
+            StmtKind::While { cond, body } | StmtKind::DoWhile { body, cond } => {
+                self.references(cond, scope);
+                self.nested(body, scope);
+            }
+            StmtKind::For {
+                init,
+                cond,
+                step,
+                body,
+            } => {
+                let scope = self.graph.add_scope(scope, ScopeKind::Block);
+                if let Some(init) = init {
+                    self.stmt(init, scope);
+                }
+                self.references(cond, scope);
+                self.references(step, scope);
+                self.nested(body, scope);
+            }
+            StmtKind::Return(value) => self.references(value, scope),
+            StmtKind::Break | StmtKind::Continue | StmtKind::Empty | StmtKind::Error => {}
+        }
+    }
+
+    /// Declare a local variable, which is visible in its own initializer as in C
+    fn local(&mut self, decl: &VarDecl, scope: ScopeId) {
+        self.references(&decl.ty, scope);
+        self.declare(scope, Namespace::Value, &decl.name.name, decl.name.span);
+        self.references(&decl.init, scope);
+    }
+
+    /// Resolve every variable and struct name in a type or expression
+    fn references(&mut self, node: &impl Visit, scope: ScopeId) {
+        References {
+            resolver: self,
+            scope,
+        }
+        .visit(node);
+    }
+
+    /// Declare a name, recording the error if it's a duplicate
+    fn declare(
+        &mut self,
+        scope: ScopeId,
+        namespace: Namespace,
+        name: &str,
+        span: Span,
+    ) -> Option<SymbolId> {
+        self.graph
+            .declare(scope, namespace, name, span)
+            .map_err(|error| self.errors.push(error))
+            .ok()
+    }
+}
+
+/// A visitor resolving the names in types and expressions, which declare nothing
+struct References<'a> {
+    /// The resolver to record resolutions and errors in
+    resolver: &'a mut Resolver,
+    /// The scope the names are in
+    scope: ScopeId,
+}
+
+impl Visitor for References<'_> {
+    fn visit<N: Visit>(&mut self, node: &N) {
+        let reference = downcast_ref::<Expr>(node)
+            .and_then(|expr| match &expr.kind {
+                ExprKind::Var(name) => Some((Namespace::Value, name)),
+                _ => None,
+            })
+            .or_else(|| {
+                downcast_ref::<Type>(node).and_then(|ty| match &ty.kind {
+                    TypeKind::Struct(name) => Some((Namespace::Struct, name)),
+                    _ => None,
+                })
+            });
+        if let Some((namespace, name)) = reference {
+            let resolved = self
+                .resolver
+                .graph
+                .resolve(self.scope, namespace, &name.name, name.span);
+            if let Err(error) = resolved {
+                self.resolver.errors.push(error);
+            }
+        }
+        node.visit_children(self);
+    }
+}
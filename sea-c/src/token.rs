@@ -0,0 +1,302 @@
+//! SeaC tokens
+
+use thiserror::Error;
+
+/// A SeaC token
+///
+/// Keywords come before identifiers, and longer operators before their prefixes,
+/// since the lexer takes the first pattern that matches.
+#[derive(Debug, Clone, PartialEq, sea_lex::Token)]
+#[skip(r"\s+")]
+#[skip(r"//[^\n]*")]
+#[skip(r"/\*([^*]|\*+[^*/])*\*+/")]
+pub enum Token {
+    /// `struct`
+    #[token(r"struct\b")]
+    Struct,
+    /// `if`
+    #[token(r"if\b")]
+    If,
+    /// `else`
+    #[token(r"else\b")]
+    Else,
+    /// `while`
+    #[token(r"while\b")]
+    While,
+    /// `do`
+    #[token(r"do\b")]
+    Do,
+    /// `for`
+    #[token(r"for\b")]
+    For,
+    /// `return`
+    #[token(r"return\b")]
+    Return,
+    /// `break`
+    #[token(r"break\b")]
+    Break,
+    /// `continue`
+    #[token(r"continue\b")]
+    Continue,
+    /// `sizeof`
+    #[token(r"sizeof\b")]
+    Sizeof,
+    /// `true`
+    #[token(r"true\b")]
+    True,
+    /// `false`
+    #[token(r"false\b")]
+    False,
+    /// `void`
+    #[token(r"void\b")]
+    Void,
+    /// `bool`
+    #[token(r"bool\b")]
+    Bool,
+    /// `char`
+    #[token(r"char\b")]
+    Char,
+    /// `short`
+    #[token(r"short\b")]
+    Short,
+    /// `int`
+    #[token(r"int\b")]
+    Int,
+    /// `long`
+    #[token(r"long\b")]
+    Long,
+    /// `unsigned`
+    #[token(r"unsigned\b")]
+    Unsigned,
+    /// `float`
+    #[token(r"float\b")]
+    Float,
+    /// `double`
+    #[token(r"double\b")]
+    Double,
+
+    /// An identifier
+    #[token(r"[a-zA-Z_][a-zA-Z0-9_]*", String::from)]
+    Ident(String),
+    /// A floating-point literal
+    #[token(r"[0-9]+\.[0-9]*([eE][+-]?[0-9]+)?|[0-9]+[eE][+-]?[0-9]+", str::parse)]
+    FloatLit(f64),
+    /// An integer literal, in decimal, octal, or hexadecimal
+    #[token(r"0[xX][0-9a-fA-F]+|[0-9]+", parse_int)]
+    IntLit(u64),
+    /// A character literal
+    #[token(r"'([^'\\\n]|\\.)+'", parse_char)]
+    CharLit(u8),
+    /// A string literal
+    #[token(r#""([^"\\\n]|\\.)*""#, parse_string)]
+    StringLit(String),
+
+    /// `->`
+    #[token("->")]
+    Arrow,
+    /// `++`
+    #[token("++")]
+    PlusPlus,
+    /// `--`
+    #[token("--")]
+    MinusMinus,
+    /// `+=`
+    #[token("+=")]
+    PlusEq,
+    /// `-=`
+    #[token("-=")]
+    MinusEq,
+    /// `*=`
+    #[token("*=")]
+    StarEq,
+    /// `/=`
+    #[token("/=")]
+    SlashEq,
+    /// `%=`
+    #[token("%=")]
+    PercentEq,
+    /// `&=`
+    #[token("&=")]
+    AmpEq,
+    /// `|=`
+    #[token("|=")]
+    PipeEq,
+    /// `^=`
+    #[token("^=")]
+    CaretEq,
+    /// `<<=`
+    #[token("<<=")]
+    ShlEq,
+    /// `>>=`
+    #[token(">>=")]
+    ShrEq,
+    /// `<<`
+    #[token("<<")]
+    Shl,
+    /// `>>`
+    #[token(">>")]
+    Shr,
+    /// `<=`
+    #[token("<=")]
+    LessEq,
+    /// `>=`
+    #[token(">=")]
+    GreaterEq,
+    /// `==`
+    #[token("==")]
+    EqEq,
+    /// `!=`
+    #[token("!=")]
+    NotEq,
+    /// `&&`
+    #[token("&&")]
+    AmpAmp,
+    /// `||`
+    #[token("||")]
+    PipePipe,
+    /// `+`
+    #[token("+")]
+    Plus,
+    /// `-`
+    #[token("-")]
+    Minus,
+    /// `*`
+    #[token("*")]
+    Star,
+    /// `/`
+    #[token("/")]
+    Slash,
+    /// `%`
+    #[token("%")]
+    Percent,
+    /// `&`
+    #[token("&")]
+    Amp,
+    /// `|`
+    #[token("|")]
+    Pipe,
+    /// `^`
+    #[token("^")]
+    Caret,
+    /// `~`
+    #[token("~")]
+    Tilde,
+    /// `!`
+    #[token("!")]
+    Bang,
+    /// `=`
+    #[token("=")]
+    Eq,
+    /// `<`
+    #[token("<")]
+    Less,
+    /// `>`
+    #[token(">")]
+    Greater,
+    /// `?`
+    #[token("?")]
+    Question,
+    /// `:`
+    #[token(":")]
+    Colon,
+    /// `,`
+    #[token(",")]
+    Comma,
+    /// `;`
+    #[token(";")]
+    Semi,
+    /// `.`
+    #[token(".")]
+    Dot,
+    /// `(`
+    #[token("(")]
+    LParen,
+    /// `)`
+    #[token(")")]
+    RParen,
+    /// `{`
+    #[token("{")]
+    LBrace,
+    /// `}`
+    #[token("}")]
+    RBrace,
+    /// `[`
+    #[token("[")]
+    LBracket,
+    /// `]`
+    #[token("]")]
+    RBracket,
+}
+
+/// An error in the value of a literal token
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LiteralError {
+    /// An integer literal doesn't fit in 64 bits
+    #[error("integer literal '{0}' is too large")]
+    IntegerTooLarge(String),
+    /// An octal integer literal has an `8` or a `9`
+    #[error("invalid digit in octal literal '{0}'")]
+    InvalidOctal(String),
+    /// An escape sequence isn't recognized
+    #[error("unknown escape sequence '\\{0}'")]
+    UnknownEscape(char),
+    /// A character literal doesn't hold exactly one byte
+    #[error("character literal {0} must contain exactly one character")]
+    CharLength(String),
+}
+
+/// Parse a decimal, octal, or hexadecimal integer literal
+///
+/// As in C, a literal with a leading `0` is octal: `017` is 15.
+fn parse_int(text: &str) -> Result<u64, LiteralError> {
+    let hex = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X"));
+    let octal = text.strip_prefix('0').filter(|octal| !octal.is_empty());
+    let (digits, radix) = match (hex, octal) {
+        (Some(hex), _) => (hex, 16),
+        (None, Some(octal)) if octal.contains(['8', '9']) => {
+            return Err(LiteralError::InvalidOctal(text.to_string()));
+        }
+        (None, Some(octal)) => (octal, 8),
+        (None, None) => (text, 10),
+    };
+    u64::from_str_radix(digits, radix).map_err(|_| LiteralError::IntegerTooLarge(text.to_string()))
+}
+
+/// Parse a quoted character literal
+fn parse_char(text: &str) -> Result<u8, LiteralError> {
+    match unescape(&text[1..text.len() - 1])?.as_slice() {
+        [byte] => Ok(*byte),
+        _ => Err(LiteralError::CharLength(text.to_string())),
+    }
+}
+
+/// Parse a quoted string literal
+fn parse_string(text: &str) -> Result<String, LiteralError> {
+    let bytes = unescape(&text[1..text.len() - 1])?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Replace the escape sequences in the contents of a literal
+fn unescape(text: &str) -> Result<Vec<u8>, LiteralError> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        let escape = chars.next().unwrap_or('\\');
+        bytes.push(match escape {
+            'n' => b'\n',
+            't' => b'\t',
+            'r' => b'\r',
+            '0' => b'\0',
+            '\\' => b'\\',
+            '\'' => b'\'',
+            '"' => b'"',
+            other => return Err(LiteralError::UnknownEscape(other)),
+        });
+    }
+    Ok(bytes)
+}
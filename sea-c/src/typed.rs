@@ -0,0 +1,202 @@
+//! The typed SeaC syntax tree, produced by [`check`](crate::check)
+//!
+//! The typed tree mirrors [`ast`](crate::ast), with every expression annotated with its
+//! [`TypeId`] and value category, and C's implicit conversions made explicit. Some
+//! syntax is reduced to what it's defined as: `a[i]` becomes `*(a + i)`, `p->f` becomes
+//! `(*p).f`, and `sizeof` becomes the constant size.
+
+use crate::ast::{BinaryOp, Ident, UnaryOp};
+use sea_lex::Span;
+use sea_type::{StructLayout, TypeId};
+use std::collections::HashMap;
+
+/// A type-checked source file
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedProgram {
+    /// The functions and globals, in order
+    pub items: Vec<TypedItem>,
+    /// The layout of each defined struct, by name
+    pub layouts: HashMap<String, StructLayout>,
+}
+
+/// A type-checked top-level item
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedItem {
+    /// A function definition or declaration
+    Function(TypedFunction),
+    /// Global variables, declared together
+    Global(Vec<TypedVar>),
+}
+
+/// A type-checked function
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedFunction {
+    /// The function's name
+    pub name: Ident,
+    /// The function's type: a [`Type::Function`](sea_type::Type::Function)
+    pub ty: TypeId,
+    /// The parameters, with array parameters adjusted to pointers
+    pub params: Vec<TypedParam>,
+    /// The body, or `None` for a declaration
+    pub body: Option<TypedBlock>,
+    /// The location of the function
+    pub span: Span,
+}
+
+/// A type-checked function parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedParam {
+    /// The parameter's name, which declarations may omit
+    pub name: Option<Ident>,
+    /// The parameter's type
+    pub ty: TypeId,
+    /// The location of the parameter
+    pub span: Span,
+}
+
+/// A type-checked variable declaration
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedVar {
+    /// The variable's name
+    pub name: Ident,
+    /// The variable's type
+    pub ty: TypeId,
+    /// The initial value, converted to the variable's type
+    pub init: Option<TypedExpr>,
+    /// The location of the declaration
+    pub span: Span,
+}
+
+/// A type-checked block
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedBlock {
+    /// The statements, in order
+    pub stmts: Vec<TypedStmt>,
+    /// The location of the block, including its braces
+    pub span: Span,
+}
+
+/// A type-checked statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedStmt {
+    /// The kind of statement
+    pub kind: TypedStmtKind,
+    /// The location of the statement
+    pub span: Span,
+}
+
+/// The kinds of type-checked statements, as in [`StmtKind`](crate::ast::StmtKind)
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedStmtKind {
+    /// A nested block
+    Block(TypedBlock),
+    /// A local variable declaration, of one or more variables
+    Decl(Vec<TypedVar>),
+    /// An expression evaluated for its effects
+    Expr(TypedExpr),
+    /// `if (cond) then else otherwise`
+    If {
+        /// The condition, which is a scalar
+        cond: TypedExpr,
+        /// The statement run if the condition holds
+        then: Box<TypedStmt>,
+        /// The statement run otherwise, if any
+        otherwise: Option<Box<TypedStmt>>,
+    },
+    /// `while (cond) body`
+    While {
+        /// The condition, which is a scalar
+        cond: TypedExpr,
+        /// The loop body
+        body: Box<TypedStmt>,
+    },
+    /// `do body while (cond);`
+    DoWhile {
+        /// The loop body
+        body: Box<TypedStmt>,
+        /// The condition, which is a scalar
+        cond: TypedExpr,
+    },
+    /// `for (init; cond; step) body`
+    For {
+        /// The initializer, if any
+        init: Option<Box<TypedStmt>>,
+        /// The condition, which is a scalar, if any
+        cond: Option<TypedExpr>,
+        /// The expression evaluated after each iteration, if any
+        step: Option<TypedExpr>,
+        /// The loop body
+        body: Box<TypedStmt>,
+    },
+    /// `return value;`, with the value converted to the return type
+    Return(Option<TypedExpr>),
+    /// `break;`
+    Break,
+    /// `continue;`
+    Continue,
+    /// `;`
+    Empty,
+    /// A statement that couldn't be parsed
+    Error,
+}
+
+/// A type-checked expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedExpr {
+    /// The kind of expression
+    pub kind: TypedExprKind,
+    /// The expression's type, or [`TypeId::BOTTOM`] if it had an error
+    pub ty: TypeId,
+    /// Whether the expression designates an object, which can have its address taken
+    pub lvalue: bool,
+    /// The location of the expression
+    pub span: Span,
+}
+
+/// The kinds of type-checked expressions
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedExprKind {
+    /// An integer constant, including characters, booleans, and `sizeof`
+    Int(u64),
+    /// A floating-point constant
+    Float(f64),
+    /// A string literal, whose type is an array of `char` including the terminator
+    String(String),
+    /// A variable or function name
+    Var(Ident),
+    /// A prefix or postfix operator
+    Unary(UnaryOp, Box<TypedExpr>),
+    /// A binary operator, with both operands converted as C requires
+    Binary(BinaryOp, Box<TypedExpr>, Box<TypedExpr>),
+    /// `lhs = rhs`, or a compound assignment like `lhs += rhs`
+    Assign(Option<BinaryOp>, Box<TypedExpr>, Box<TypedExpr>),
+    /// `cond ? then : otherwise`
+    Conditional(Box<TypedExpr>, Box<TypedExpr>, Box<TypedExpr>),
+    /// `lhs, rhs`, with the type of `rhs`
+    Comma(Box<TypedExpr>, Box<TypedExpr>),
+    /// `callee(args)`, with the callee a function pointer
+    Call(Box<TypedExpr>, Vec<TypedExpr>),
+    /// `base.field`
+    Member(Box<TypedExpr>, Ident),
+    /// `(type) expr`
+    Cast(Box<TypedExpr>),
+    /// An implicit conversion to the expression's type
+    Convert(Conversion, Box<TypedExpr>),
+}
+
+/// The implicit conversions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Conversion {
+    /// Between arithmetic types, including integer promotion and the usual arithmetic
+    /// conversions
+    Arithmetic,
+    /// From a scalar to `bool`, comparing it with zero
+    ToBool,
+    /// From the null pointer constant `0` to a pointer
+    NullPointer,
+    /// Between object pointers, where one points to `void`
+    Pointer,
+    /// From an array to a pointer to its first element, or from a function to a pointer
+    /// to it
+    Decay,
+}
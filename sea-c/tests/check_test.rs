@@ -0,0 +1,361 @@
+use sea_c::typed::{Conversion, TypedExpr, TypedExprKind, TypedItem, TypedProgram, TypedStmtKind};
+use sea_c::TypeError;
+use sea_lex::SourceDb;
+use sea_type::{DataLayout, TypeInterner};
+
+/// Type-check a SeaC source file, which must parse
+fn check(source: &str) -> (TypeInterner, TypedProgram, Vec<TypeError>) {
+    let file = SourceDb::new().add("test.c", source);
+    let program = sea_c::parse(file, source).expect("the test program parses");
+    let mut types = TypeInterner::new();
+    let (typed, errors) = sea_c::check(&mut types, &DataLayout::X86_64_SYSV, &program);
+    (types, typed, errors)
+}
+
+/// Get the expression statements at the top of a function's body
+fn exprs<'a>(typed: &'a TypedProgram, function: &str) -> Vec<&'a TypedExpr> {
+    let body = typed
+        .items
+        .iter()
+        .find_map(|item| match item {
+            TypedItem::Function(f) if f.name.name == function => f.body.as_ref(),
+            _ => None,
+        })
+        .expect("the function is defined");
+    body.stmts
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            TypedStmtKind::Expr(expr) => Some(expr),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Get each error's message and the source text it points to
+fn messages<'a>(source: &'a str, errors: &[TypeError]) -> Vec<(String, &'a str)> {
+    errors
+        .iter()
+        .map(|error| (error.to_string(), &source[error.span.start..error.span.end]))
+        .collect()
+}
+
+#[test]
+fn test_arithmetic_conversions() {
+    let source = "
+        void f(char c, unsigned char uc, short s, int i, unsigned u, long l, unsigned long ul,
+               float x, double d, bool b) {
+            c + c; uc * s; i + u; l + u; u - ul; i / x; x * d; -c; ~b; c << l; i < d; b && x;
+        }
+    ";
+    let (mut types, typed, errors) = check(source);
+    assert!(errors.is_empty(), "{errors:?}");
+    let int = types.int(32, true);
+    let unsigned = types.int(32, false);
+    let long = types.int(64, true);
+    let ulong = types.int(64, false);
+    let float = types.float(sea_type::FloatKind::F32);
+    let double = types.float(sea_type::FloatKind::F64);
+    let found: Vec<_> = exprs(&typed, "f").iter().map(|expr| expr.ty).collect();
+    assert_eq!(
+        found,
+        [int, int, unsigned, long, ulong, float, double, int, int, int, int, int]
+    );
+
+    // Both operands of `c + c` are promoted to `int`
+    let TypedExprKind::Binary(_, lhs, _) = &exprs(&typed, "f")[0].kind else {
+        panic!("expected a binary operator");
+    };
+    assert!(matches!(
+        lhs.kind,
+        TypedExprKind::Convert(Conversion::Arithmetic, _)
+    ));
+    assert_eq!(lhs.ty, int);
+}
+
+#[test]
+fn test_pointers_and_lvalues() {
+    let source = "
+        struct node { int value; struct node *next; };
+        int g(int);
+        void f(int *p, int a[4], struct node n, void *v) {
+            p + 1; 2 + p; p - p; p[1]; *p; &p[1]; n.next->value; a; v == p; p == 0; g; &g;
+            *p = 1; p[0] += 2; n.value++; p = v; p = 0;
+        }
+    ";
+    let (mut types, typed, errors) = check(source);
+    assert!(errors.is_empty(), "{errors:?}");
+    let int = types.int(32, true);
+    let long = types.int(64, true);
+    let pointer = types.pointer(int, true);
+    let function = types.function([int], int);
+    let function_pointer = types.pointer(function, true);
+    let exprs = exprs(&typed, "f");
+    let found: Vec<_> = exprs.iter().map(|expr| (expr.ty, expr.lvalue)).collect();
+    assert_eq!(
+        found,
+        [
+            (pointer, false),
+            (pointer, false),
+            (long, false),
+            (int, true),
+            (int, true),
+            (pointer, false),
+            (int, true),
+            (pointer, true),
+            (int, false),
+            (int, false),
+            (function_pointer, false),
+            (function_pointer, false),
+            (int, false),
+            (int, false),
+            (int, false),
+            (pointer, false),
+            (pointer, false),
+        ]
+    );
+    // `p[1]` is `*(p + 1)`, with the offset converted to `long`
+    let TypedExprKind::Unary(_, address) = &exprs[3].kind else {
+        panic!("expected a dereference");
+    };
+    let TypedExprKind::Binary(_, _, offset) = &address.kind else {
+        panic!("expected an addition");
+    };
+    assert_eq!(offset.ty, long);
+    assert!(matches!(
+        exprs[16].kind,
+        TypedExprKind::Assign(None, _, ref rhs)
+            if matches!(rhs.kind, TypedExprKind::Convert(Conversion::NullPointer, _))
+    ));
+}
+
+#[test]
+fn test_commas_and_declarators() {
+    let source = "
+        double d, *q = &d;
+        void f(void) { int i, *p = &i, a[3]; (d, p); (i = 1, d); (a, 0); }
+    ";
+    let (mut types, typed, errors) = check(source);
+    assert!(errors.is_empty(), "{errors:?}");
+    let int = types.int(32, true);
+    let double = types.float(sea_type::FloatKind::F64);
+    let int_pointer = types.pointer(int, true);
+    let double_pointer = types.pointer(double, true);
+    let array = types.array(int, Some(3));
+
+    let TypedItem::Global(globals) = &typed.items[0] else {
+        panic!("expected globals");
+    };
+    let found: Vec<_> = globals.iter().map(|global| global.ty).collect();
+    assert_eq!(found, [double, double_pointer]);
+    let TypedItem::Function(f) = &typed.items[1] else {
+        panic!("expected a function");
+    };
+    let TypedStmtKind::Decl(locals) = &f.body.as_ref().unwrap().stmts[0].kind else {
+        panic!("expected a declaration");
+    };
+    let found: Vec<_> = locals.iter().map(|local| local.ty).collect();
+    assert_eq!(found, [int, int_pointer, array]);
+
+    // A comma expression has the value of its right operand, and isn't an lvalue
+    let found: Vec<_> = exprs(&typed, "f")
+        .iter()
+        .map(|expr| (expr.ty, expr.lvalue))
+        .collect();
+    assert_eq!(found, [(int_pointer, false), (double, false), (int, false)]);
+}
+
+#[test]
+fn test_struct_layout() {
+    let source = "
+        struct a { char c; int i; char d; long l; };
+        struct b { char c; struct a a; short s[3]; };
+        struct empty { };
+        void f(void) { int array[5]; sizeof(struct b); sizeof array; sizeof(array + 1); }
+    ";
+    let (_, typed, errors) = check(source);
+    assert!(errors.is_empty(), "{errors:?}");
+    let sizes: Vec<_> = exprs(&typed, "f")
+        .iter()
+        .map(|expr| expr.kind.clone())
+        .collect();
+    assert_eq!(
+        sizes,
+        [
+            TypedExprKind::Int(40),
+            TypedExprKind::Int(20),
+            TypedExprKind::Int(8)
+        ]
+    );
+    let a = &typed.layouts["a"];
+    assert_eq!(
+        (a.size, a.align, a.offsets.as_slice()),
+        (24, 8, &[0, 4, 8, 16][..])
+    );
+    let b = &typed.layouts["b"];
+    assert_eq!(
+        (b.size, b.align, b.offsets.as_slice()),
+        (40, 8, &[0, 8, 32][..])
+    );
+    assert_eq!(typed.layouts["empty"].size, 0);
+}
+
+#[test]
+fn test_type_errors() {
+    let source = "
+struct s { int x; struct t inner; };
+int g(int a, int b);
+void f(int *p, struct s v, void *q) {
+    int *r = 1.5;
+    1 = 2;
+    &3;
+    p + p;
+    q + 1;
+    g(1);
+    v.y;
+    v.x();
+    if (v) {}
+    *q;
+    (int*) 1.0;
+    sizeof(void);
+    return 3;
+}
+int h(void) { return; }
+    ";
+    let (_, _, errors) = check(source);
+    assert_eq!(
+        messages(source, &errors),
+        [
+            (
+                "Can't use incomplete type `struct t` here".to_string(),
+                "struct t"
+            ),
+            ("Expected `int*`, found `double`".to_string(), "1.5"),
+            ("The operand of `=` must be an lvalue".to_string(), "1"),
+            ("The operand of `&` must be an lvalue".to_string(), "3"),
+            ("Can't apply `+` to `int*` and `int*`".to_string(), "p + p"),
+            ("Can't use incomplete type `void` here".to_string(), "q + 1"),
+            ("Expected 2 arguments, found 1".to_string(), "g(1)"),
+            ("`struct s` has no field `y`".to_string(), "y"),
+            ("Can't call a value of type `int`".to_string(), "v.x"),
+            (
+                "Expected a number or pointer condition, found `struct s`".to_string(),
+                "v"
+            ),
+            ("Can't apply `*` to `void*`".to_string(), "*q"),
+            ("Can't cast `double` to `int*`".to_string(), "(int*) 1.0"),
+            (
+                "Can't use incomplete type `void` here".to_string(),
+                "sizeof(void)"
+            ),
+            ("Expected `void`, found `int`".to_string(), "3"),
+            ("Expected `int`, found `void`".to_string(), "return;"),
+        ]
+    );
+}
+
+#[test]
+fn test_void_parameters() {
+    let source = "
+void f(void x) {}
+int g(int, void);
+int h(void);
+int k(void) { return h(); }
+    ";
+    let (_, _, errors) = check(source);
+    let message = "A parameter can't be `void`, except as the lone `(void)`";
+    assert_eq!(
+        messages(source, &errors),
+        [(message.to_string(), "void"), (message.to_string(), "void")]
+    );
+    assert_eq!(errors[0].span.start, source.find("void x").unwrap());
+    assert_eq!(errors[1].span.start, source.find("void)").unwrap());
+}
+
+#[test]
+fn test_break_and_continue_outside_loops() {
+    let source = "
+void f(int n) {
+    break;
+    while (n) { if (n > 2) break; continue; }
+    do { for (;;) { break; } continue; } while (n);
+    if (n) continue;
+    { break; }
+}
+    ";
+    let (_, _, errors) = check(source);
+    assert_eq!(
+        messages(source, &errors),
+        [
+            ("`break` outside of a loop".to_string(), "break;"),
+            ("`continue` outside of a loop".to_string(), "continue;"),
+            ("`break` outside of a loop".to_string(), "break;"),
+        ]
+    );
+}
+
+#[test]
+fn test_checking_for_targets() {
+    let source = "
+        struct s { char c; long l; int *p; };
+        void f(void) { sizeof(long); sizeof(struct s); sizeof(char*); (char) 1; }
+    ";
+    let file = SourceDb::new().add("test.c", source);
+    let program = sea_c::parse(file, source).expect("the test program parses");
+    let check = |target: &DataLayout| {
+        let mut types = TypeInterner::new();
+        let (typed, errors) = sea_c::check(&mut types, target, &program);
+        assert!(errors.is_empty(), "{errors:?}");
+        let TypedItem::Function(f) = &typed.items[0] else {
+            panic!("expected a function");
+        };
+        let found: Vec<_> = f
+            .body
+            .as_ref()
+            .expect("the function is defined")
+            .stmts
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                TypedStmtKind::Expr(expr) => {
+                    (types.display(expr.ty).to_string(), expr.kind.clone())
+                }
+                _ => panic!("expected an expression"),
+            })
+            .collect();
+        (typed.layouts["s"].offsets.clone(), found)
+    };
+    let (offsets, found) = check(&DataLayout::X86_64_SYSV);
+    assert_eq!(offsets, [0, 8, 16]);
+    let sizes: Vec<_> = found
+        .iter()
+        .take(3)
+        .map(|(ty, kind)| (ty.as_str(), kind))
+        .collect();
+    assert_eq!(
+        sizes,
+        [
+            ("u64", &TypedExprKind::Int(8)),
+            ("u64", &TypedExprKind::Int(24)),
+            ("u64", &TypedExprKind::Int(8)),
+        ]
+    );
+    assert_eq!(found[3].0, "i8");
+
+    let (offsets, found) = check(&DataLayout::WASM32);
+    assert_eq!(offsets, [0, 4, 8]);
+    let sizes: Vec<_> = found
+        .iter()
+        .take(3)
+        .map(|(ty, kind)| (ty.as_str(), kind))
+        .collect();
+    assert_eq!(
+        sizes,
+        [
+            ("u32", &TypedExprKind::Int(4)),
+            ("u32", &TypedExprKind::Int(12)),
+            ("u32", &TypedExprKind::Int(4)),
+        ]
+    );
+
+    let (_, found) = check(&DataLayout::AARCH64_AAPCS64);
+    assert_eq!(found[3].0, "u8");
+}
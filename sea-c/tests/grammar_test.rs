@@ -0,0 +1,59 @@
+use sea_c::{grammar, parse, spell, Token};
+use sea_lex::SourceDb;
+use sea_parse::{ByteChoices, Generator, SeededChoices};
+use std::collections::HashMap;
+
+#[test]
+fn test_generated_programs_parse() {
+    let generator = Generator::new(grammar())
+        .unwrap()
+        .max_depth(12)
+        .max_tokens(200);
+    let mut db = SourceDb::new();
+    for seed in 0..200 {
+        let source = generator.generate_source(&mut SeededChoices::new(seed), spell);
+        let file = db.add("generated.c", &source);
+        if let Err(errors) = parse(file, &source) {
+            panic!("seed {seed} failed to parse: {errors:?}\n{source}");
+        }
+    }
+}
+
+#[test]
+fn test_fuzzer_bytes_parse() {
+    let generator = Generator::new(grammar()).unwrap();
+    let mut db = SourceDb::new();
+    for length in 0..64_u8 {
+        let bytes: Vec<u8> = (0..length)
+            .map(|byte| byte.wrapping_mul(37) ^ length)
+            .collect();
+        let source = generator.generate_source(&mut ByteChoices::new(&bytes), spell);
+        let file = db.add("generated.c", &source);
+        assert!(parse(file, &source).is_ok(), "{source}");
+    }
+}
+
+#[test]
+fn test_token_scopes() {
+    let scopes: HashMap<_, _> = Token::grammar()
+        .tokens
+        .iter()
+        .map(|rule| (rule.kind, rule.default_scope()))
+        .collect();
+    let scope = |kind| scopes[kind];
+
+    for keyword in [
+        "If", "While", "Return", "Struct", "Sizeof", "Int", "Char", "Bool",
+    ] {
+        assert_eq!(scope(keyword), Some("keyword.control"), "{keyword}");
+    }
+    assert_eq!(scope("True"), Some("constant.language"));
+    assert_eq!(scope("Ident"), Some("variable.other"));
+    assert_eq!(scope("IntLit"), Some("constant.numeric"));
+    assert_eq!(scope("FloatLit"), Some("constant.numeric"));
+    assert_eq!(scope("CharLit"), Some("string.quoted"));
+    assert_eq!(scope("StringLit"), Some("string.quoted"));
+    assert_eq!(scope("Arrow"), Some("keyword.operator"));
+    assert_eq!(scope("Semi"), Some("punctuation"));
+    assert_eq!(scope("LParen"), Some("punctuation"));
+}
@@ -0,0 +1,284 @@
+mod common;
+
+use common::{parse_expr, parse_program};
+use sea_c::ast::{ExprKind, Item, StmtKind, TypeKind};
+use sea_c::{parse, parse_recovering, Error};
+use sea_lex::SourceDb;
+
+#[test]
+fn test_precedence() {
+    assert_eq!(parse_expr("a + b * c - d"), "((a + (b * c)) - d)");
+    assert_eq!(parse_expr("a = b += c ? d : e"), "(a = (b += (c ? d : e)))");
+    assert_eq!(
+        parse_expr("a || b && c | d ^ e & f"),
+        "(a || (b && (c | (d ^ (e & f)))))"
+    );
+    assert_eq!(parse_expr("a == b < c << d"), "(a == (b < (c << d)))");
+    assert_eq!(parse_expr("-*p++ + !x"), "((-(*(p++))) + (!x))");
+    assert_eq!(parse_expr("(long) x * 2"), "(((long) x) * 2)");
+    assert_eq!(
+        parse_expr("sizeof(int*) + sizeof x"),
+        "(sizeof(int*) + (sizeof x))"
+    );
+    assert_eq!(
+        parse_expr("f(a, b)[i].next->value"),
+        "f(a, b)[i].next->value"
+    );
+    assert_eq!(parse_expr("&a[0] != (p + 1)"), "((&a[0]) != (p + 1))");
+}
+
+#[test]
+fn test_declarations() {
+    let program = parse_program(
+        "struct Node { int value; struct Node* next; char name[16]; };
+         unsigned long count = 0;
+         int table[4][8];
+         int add(int a, int b);
+         void reset(void) {}",
+    );
+    let types: Vec<_> = program
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::Struct(def) => {
+                let fields: Vec<_> = def
+                    .fields
+                    .iter()
+                    .map(|field| field.ty.to_string())
+                    .collect();
+                format!("struct {} {{ {} }}", def.name.name, fields.join(", "))
+            }
+            Item::Global(globals) => {
+                let globals: Vec<_> = globals
+                    .iter()
+                    .map(|global| format!("{} {}", global.ty, global.name.name))
+                    .collect();
+                globals.join(", ")
+            }
+            Item::Function(function) => format!(
+                "{} {}({}){}",
+                function.return_type,
+                function.name.name,
+                function.params.len(),
+                if function.body.is_some() { " {}" } else { ";" }
+            ),
+            Item::Error(_) => "error".to_string(),
+        })
+        .collect();
+    assert_eq!(
+        types,
+        [
+            "struct Node { int, struct Node*, char[16] }",
+            "unsigned long count",
+            "int[8][4] table",
+            "int add(2);",
+            "void reset(0) {}",
+        ]
+    );
+}
+
+#[test]
+fn test_comma_operator() {
+    assert_eq!(parse_expr("a = 1, b = 2, c"), "(((a = 1), (b = 2)), c)");
+    assert_eq!(parse_expr("f((a, b), c)[i, j]"), "f((a, b), c)[(i, j)]");
+    assert_eq!(parse_expr("a ? b : c, d"), "((a ? b : c), d)");
+
+    let program = parse_program("void f() { for (i = 0, j = 1; i < j; i++, j--) ; }");
+    let Item::Function(function) = &program.items[0] else {
+        panic!("expected a function");
+    };
+    let StmtKind::For { init, step, .. } = &function.body.as_ref().unwrap().stmts[0].kind else {
+        panic!("expected a for loop");
+    };
+    let StmtKind::Expr(init) = &init.as_deref().unwrap().kind else {
+        panic!("expected an expression");
+    };
+    assert_eq!(init.to_string(), "((i = 0), (j = 1))");
+    assert_eq!(step.as_ref().unwrap().to_string(), "((i++), (j--))");
+}
+
+#[test]
+fn test_multiple_declarators() {
+    let source = "struct Pair { int x, *y; };
+         int a = 1, *b, c[2] = f(1, 2);
+         void g() { for (int i = 0, j = i; i < j; i++) ; }";
+    let mut db = SourceDb::new();
+    let file = db.add("main.c", source);
+    let program = parse(file, source).unwrap();
+
+    let Item::Struct(def) = &program.items[0] else {
+        panic!("expected a struct");
+    };
+    let fields: Vec<_> = def
+        .fields
+        .iter()
+        .map(|field| format!("{} {}", field.ty, field.name.name))
+        .collect();
+    assert_eq!(fields, ["int x", "int* y"]);
+
+    // Each variable gets the base type, with its own declarator's stars and dimensions
+    let Item::Global(globals) = &program.items[1] else {
+        panic!("expected globals");
+    };
+    let globals: Vec<_> = globals
+        .iter()
+        .map(|global| {
+            let init = global.init.as_ref().map(ToString::to_string);
+            (global.ty.to_string(), db.text(global.span), init)
+        })
+        .collect();
+    assert_eq!(
+        globals,
+        [
+            ("int".to_string(), "int a = 1", Some("1".to_string())),
+            ("int*".to_string(), "*b", None),
+            (
+                "int[2]".to_string(),
+                "c[2] = f(1, 2)",
+                Some("f(1, 2)".to_string())
+            ),
+        ]
+    );
+
+    let Item::Function(function) = &program.items[2] else {
+        panic!("expected a function");
+    };
+    let StmtKind::For { init, .. } = &function.body.as_ref().unwrap().stmts[0].kind else {
+        panic!("expected a for loop");
+    };
+    let StmtKind::Decl(decls) = &init.as_deref().unwrap().kind else {
+        panic!("expected a declaration");
+    };
+    let names: Vec<_> = decls.iter().map(|decl| decl.name.name.as_str()).collect();
+    assert_eq!(names, ["i", "j"]);
+}
+
+#[test]
+fn test_statements() {
+    let program = parse_program(
+        "int sum(int* values, int length) {
+             int total = 0;
+             for (int i = 0; i < length; i++) {
+                 if (values[i] < 0) continue;
+                 else if (values[i] == 0) break;
+                 total += values[i];
+             }
+             while (total > 100) total /= 2;
+             do { total--; } while (total % 2);
+             ;
+             return total;
+         }",
+    );
+    let Item::Function(function) = &program.items[0] else {
+        panic!("expected a function");
+    };
+    let stmts = &function.body.as_ref().unwrap().stmts;
+    assert!(matches!(stmts[0].kind, StmtKind::Decl(_)));
+    let StmtKind::For {
+        init,
+        cond,
+        step,
+        body,
+    } = &stmts[1].kind
+    else {
+        panic!("expected a for loop");
+    };
+    assert!(matches!(init.as_deref().unwrap().kind, StmtKind::Decl(_)));
+    assert_eq!(cond.as_ref().unwrap().to_string(), "(i < length)");
+    assert_eq!(step.as_ref().unwrap().to_string(), "(i++)");
+    let StmtKind::Block(block) = &body.kind else {
+        panic!("expected a block");
+    };
+    assert!(matches!(
+        &block.stmts[0].kind,
+        StmtKind::If { otherwise: Some(otherwise), .. } if matches!(otherwise.kind, StmtKind::If { .. })
+    ));
+    assert!(matches!(stmts[2].kind, StmtKind::While { .. }));
+    assert!(matches!(stmts[3].kind, StmtKind::DoWhile { .. }));
+    assert!(matches!(stmts[4].kind, StmtKind::Empty));
+    assert!(matches!(
+        &stmts[5].kind,
+        StmtKind::Return(Some(value)) if matches!(value.kind, ExprKind::Var(_))
+    ));
+    assert!(matches!(function.params[0].ty.kind, TypeKind::Pointer(_)));
+}
+
+#[test]
+fn test_recovery() {
+    let source = "int f() {
+             int x = 1 +;
+             x = 2
+             return x;
+         }
+         int g( { }
+         int y;";
+    let mut db = SourceDb::new();
+    let file = db.add("main.c", source);
+    let (program, errors) = parse_recovering(file, source);
+    let program = program.unwrap();
+
+    let messages: Vec<_> = errors
+        .iter()
+        .map(|error| (db.text(error_span(error)).to_string(), error.to_string()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            (
+                ";".to_string(),
+                "Expected expression, found `;`".to_string()
+            ),
+            (
+                "return".to_string(),
+                "Expected `;`, found `return`".to_string()
+            ),
+            (
+                "{".to_string(),
+                "Expected one of type, `)`, found `{`".to_string()
+            ),
+        ]
+    );
+
+    assert!(matches!(program.items[0], Item::Function(_)));
+    assert!(matches!(program.items[1], Item::Error(_)));
+    assert!(matches!(program.items[2], Item::Global(_)));
+    let Item::Function(function) = &program.items[0] else {
+        unreachable!();
+    };
+    let stmts = &function.body.as_ref().unwrap().stmts;
+    assert!(matches!(stmts[0].kind, StmtKind::Error));
+    assert!(matches!(stmts[1].kind, StmtKind::Expr(_)));
+    assert!(matches!(stmts[2].kind, StmtKind::Return(_)));
+}
+
+#[test]
+fn test_lex_error() {
+    let mut db = SourceDb::new();
+    let file = db.add("main.c", "int x = 'ab';");
+    let errors = parse(file, "int x = 'ab';").unwrap_err();
+    assert!(matches!(errors.as_slice(), [Error::Lex(_)]));
+}
+
+#[test]
+fn test_integer_literals() {
+    assert_eq!(parse_expr("017 + 0x1f + 10 + 0"), "(((15 + 31) + 10) + 0)");
+
+    // A leading `0` makes a literal octal, which can't have an `8` or a `9`
+    let mut db = SourceDb::new();
+    let file = db.add("main.c", "int x = 08;");
+    let errors = parse(file, "int x = 08;").unwrap_err();
+    let [Error::Lex(error)] = errors.as_slice() else {
+        panic!("expected a lex error, found {errors:?}");
+    };
+    assert!(error
+        .to_string()
+        .contains("invalid digit in octal literal '08'"));
+}
+
+fn error_span(error: &Error) -> sea_lex::Span {
+    match error {
+        Error::Parse(error) => error.span,
+        Error::Resolve(error) => error.span,
+        Error::Lex(error) => panic!("unexpected lex error: {error}"),
+    }
+}
@@ -0,0 +1,127 @@
+use sea_c::{grammar, parse, spell, Program};
+use sea_lex::{FileId, SourceDb, Span};
+use sea_parse::{downcast_mut, Generator, Pretty, SeededChoices, Visit, VisitorMut};
+
+/// Moves every span to the start of one file, so that trees can be compared by shape
+struct ClearSpans(FileId);
+
+impl VisitorMut for ClearSpans {
+    fn visit_mut<N: Visit>(&mut self, node: &mut N) {
+        if let Some(span) = downcast_mut::<Span>(node) {
+            *span = Span::new(self.0, 0, 0);
+        }
+        node.visit_children_mut(self);
+    }
+}
+
+/// Parse `source`, panicking with the source if it's invalid
+fn parse_cleared(db: &mut SourceDb, source: &str) -> Program {
+    let file = db.add("test.c", source);
+    let mut program = parse(file, source)
+        .unwrap_or_else(|errors| panic!("failed to parse: {errors:?}\n{source}"));
+    ClearSpans(db.iter().next().unwrap().0).visit_mut(&mut program);
+    program
+}
+
+/// Check that printing `source`'s tree at `width` and reparsing it gives the same tree
+fn round_trip(db: &mut SourceDb, source: &str, width: usize) -> String {
+    let program = parse_cleared(db, source);
+    let printed = program.to_pretty(width);
+    assert_eq!(parse_cleared(db, &printed), program, "{printed}");
+    printed
+}
+
+#[test]
+fn test_prints_source() {
+    let source = "
+        struct Node { int value; struct Node* next; char name[16]; };
+        unsigned long count = 0;
+        int sum(struct Node* list, int scale) {
+            int total = 0;
+            for (; list; list = list->next) total += list->value * (scale + 1);
+            if (total > 100) { return -(-total); } else if (!total) return 0;
+            do count++; while (count < 10 && (count & 1) == 0);
+            return total ? sizeof (total + 1) : (int) 'a' + sizeof(long);
+        }
+    ";
+    let mut db = SourceDb::new();
+    assert_eq!(
+        round_trip(&mut db, source, 80),
+        "\
+struct Node {
+    int value;
+    struct Node* next;
+    char name[16];
+};
+
+unsigned long count = 0;
+
+int sum(struct Node* list, int scale) {
+    int total = 0;
+    for (; list; list = list->next)
+        total += list->value * (scale + 1);
+    if (total > 100) {
+        return - -total;
+    } else if (!total)
+        return 0;
+    do
+        count++;
+    while (count < 10 && (count & 1) == 0);
+    return total ? sizeof (total + 1) : (int)'a' + sizeof(long);
+}
+"
+    );
+    assert_eq!(
+        round_trip(
+            &mut db,
+            "void f() { g(first_argument, second_argument * 2); }",
+            30
+        ),
+        "\
+void f() {
+    g(
+        first_argument,
+        second_argument * 2
+    );
+}
+"
+    );
+}
+
+#[test]
+fn test_prints_commas_and_declarators() {
+    let source = "
+        int a = 1, *b, c[2] = (x, y);
+        void f() {
+            int i = g((1, 2), 3), *p;
+            for (i = 0, j = 1; i < j; i++, j--) x = y ? (1, 2) : (3, 4);
+        }
+    ";
+    let mut db = SourceDb::new();
+    assert_eq!(
+        round_trip(&mut db, source, 80),
+        "\
+int a = 1, *b, c[2] = (x, y);
+
+void f() {
+    int i = g((1, 2), 3), *p;
+    for (i = 0, j = 1; i < j; i++, j--)
+        x = y ? (1, 2) : (3, 4);
+}
+"
+    );
+}
+
+#[test]
+fn test_generated_programs_round_trip() {
+    let generator = Generator::new(grammar())
+        .unwrap()
+        .max_depth(12)
+        .max_tokens(200);
+    let mut db = SourceDb::new();
+    for seed in 0..200 {
+        let source = generator.generate_source(&mut SeededChoices::new(seed), spell);
+        round_trip(&mut db, &source, 80);
+        round_trip(&mut db, &source, 20);
+    }
+}
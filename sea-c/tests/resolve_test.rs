@@ -0,0 +1,83 @@
+mod common;
+
+use common::{nth, resolved};
+
+#[test]
+fn test_resolves_forward_references_and_shadowing() {
+    let source = "
+        int twice(int n);
+        struct point { int x; int y; };
+        int main(void) {
+            int n = twice(1);
+            struct point p;
+            { int n = n + 1; p.x = n; }
+            for (int i = 0; i < n; i++) { n = i; }
+            return count + twice(n);
+        }
+        int twice(int n) { return n * 2; }
+        int count = 0;
+    ";
+    let (graph, errors, file) = resolved(source);
+    assert_eq!(errors, Vec::<String>::new());
+    let at = |name, index| nth(source, file, name, index);
+
+    // The prototype and definition are one function, called before its definition
+    let twice = graph.resolution(at("twice", 1)).unwrap();
+    assert_eq!(graph.symbol(twice).span, at("twice", 0));
+    assert_eq!(graph.resolution(at("twice", 2)), Some(twice));
+    assert_eq!(
+        graph
+            .resolution(at("count", 0))
+            .map(|s| graph.symbol(s).span),
+        Some(at("count", 1))
+    );
+    assert_eq!(
+        graph
+            .resolution(at("point", 1))
+            .map(|s| graph.symbol(s).span),
+        Some(at("point", 0))
+    );
+
+    // `n` in the nested block's initializer is the inner `n`, as in C
+    let inner = graph.resolution(at("n", 3)).unwrap();
+    assert_eq!(graph.symbol(inner).span, at("n", 2));
+    assert_eq!(graph.references(inner), vec![at("n", 3), at("n", 4)]);
+    let outer = graph.resolution(at("n", 5)).unwrap();
+    assert_eq!(graph.symbol(outer).span, at("n", 1));
+    assert_eq!(
+        graph.references(outer),
+        vec![at("n", 5), at("n", 6), at("n", 7)]
+    );
+
+    // Parameters are visible in the body
+    let param = graph.resolution(at("n", 9)).unwrap();
+    assert_eq!(graph.symbol(param).span, at("n", 8));
+}
+
+#[test]
+fn test_reports_unresolved_and_duplicate_names() {
+    let source = "
+        struct pair { int first; int first; };
+        int total(int count) {
+            int count = 1;
+            for (int i = 0; i < 4; i++) {}
+            return i + totl(cout) + missing;
+        }
+        int total(int n) { return n; }
+        struct pari *p;
+    ";
+    let (_, errors, _) = resolved(source);
+    assert_eq!(
+        errors,
+        [
+            "Duplicate field `first` in the same scope",
+            "Duplicate value `count` in the same scope",
+            "Can't find value `i`",
+            "Can't find value `totl`, did you mean `total`?",
+            "Can't find value `cout`, did you mean `count`?",
+            "Can't find value `missing`",
+            "Duplicate value `total` in the same scope",
+            "Can't find struct `pari`, did you mean `pair`?",
+        ]
+    );
+}
@@ -0,0 +1,222 @@
+//! Throughput benchmarks for derived lexers over realistic grammars
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use sea_lex::Token;
+
+/// A C-like language with keywords, literals, comments, and operators
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+#[skip(r"//[^\n]*")]
+#[skip(r"/\*([^*]|\*[^/])*\*/")]
+enum CToken {
+    #[token(r"int\b")]
+    Int,
+    #[token(r"char\b")]
+    Char,
+    #[token(r"struct\b")]
+    Struct,
+    #[token(r"if\b")]
+    If,
+    #[token(r"else\b")]
+    Else,
+    #[token(r"while\b")]
+    While,
+    #[token(r"for\b")]
+    For,
+    #[token(r"return\b")]
+    Return,
+    #[token(r"[a-zA-Z_][a-zA-Z0-9_]*", String::from)]
+    Identifier(String),
+    #[token(r"0x[0-9a-fA-F]+|[0-9]+", String::from)]
+    Integer(String),
+    #[token(r#""([^"\\]|\\.)*""#, String::from)]
+    StringLit(String),
+    #[token(r"'([^'\\]|\\.)'", String::from)]
+    CharLit(String),
+    #[token("==")]
+    EqEq,
+    #[token("!=")]
+    NotEq,
+    #[token("<=")]
+    LessEq,
+    #[token(">=")]
+    GreaterEq,
+    #[token("&&")]
+    AndAnd,
+    #[token("||")]
+    OrOr,
+    #[token("->")]
+    Arrow,
+    #[token("++")]
+    PlusPlus,
+    #[token("+")]
+    Plus,
+    #[token("-")]
+    Minus,
+    #[token("*")]
+    Star,
+    #[token("/")]
+    Slash,
+    #[token("%")]
+    Percent,
+    #[token("=")]
+    Assign,
+    #[token("<")]
+    Less,
+    #[token(">")]
+    Greater,
+    #[token("!")]
+    Bang,
+    #[token("&")]
+    Amp,
+    #[token(".")]
+    Dot,
+    #[token(",")]
+    Comma,
+    #[token(";")]
+    Semicolon,
+    #[token("(")]
+    LeftParen,
+    #[token(")")]
+    RightParen,
+    #[token("{")]
+    LeftBrace,
+    #[token("}")]
+    RightBrace,
+    #[token("[")]
+    LeftBracket,
+    #[token("]")]
+    RightBracket,
+}
+
+/// JSON, with numbers and strings parsed into their values' text
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"[ \t\r\n]+")]
+enum JsonToken {
+    #[token("{")]
+    LeftBrace,
+    #[token("}")]
+    RightBrace,
+    #[token("[")]
+    LeftBracket,
+    #[token("]")]
+    RightBracket,
+    #[token(":")]
+    Colon,
+    #[token(",")]
+    Comma,
+    #[token("true")]
+    True,
+    #[token("false")]
+    False,
+    #[token("null")]
+    Null,
+    #[token(r"-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?", str::parse)]
+    Number(f64),
+    #[token(r#""([^"\\]|\\.)*""#, String::from)]
+    String(String),
+}
+
+/// A Lisp with symbols, numbers, strings, and quoting
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+#[skip(r";[^\n]*")]
+enum LispToken {
+    #[token("(")]
+    LeftParen,
+    #[token(")")]
+    RightParen,
+    #[token("'")]
+    Quote,
+    #[token("`")]
+    Quasiquote,
+    #[token(",@")]
+    UnquoteSplicing,
+    #[token(",")]
+    Unquote,
+    #[token(r"-?[0-9]+", str::parse)]
+    Integer(i64),
+    #[token(r#""([^"\\]|\\.)*""#, String::from)]
+    String(String),
+    #[token(r"[^\s()'`,;]+", String::from)]
+    Symbol(String),
+}
+
+/// A representative chunk of C-like source
+const C_SOURCE: &str = r#"
+/* Compute the nth Fibonacci number iteratively. */
+struct pair { int a; int b; };
+
+int fib(int n) {
+    struct pair p;
+    p.a = 0; p.b = 1;
+    for (int i = 0; i < n; i++) {
+        int t = p.a + p.b; // advance
+        p.a = p.b;
+        p.b = t;
+    }
+    if (n >= 0x10 && n != 42 || !n) {
+        return p.a % 1000000007;
+    } else {
+        char *msg = "done\n";
+        char c = '\'';
+        return p.a;
+    }
+}
+"#;
+
+/// A representative chunk of JSON
+const JSON_SOURCE: &str = r#"
+{"id": 1024, "name": "sea-lex", "tags": ["lexer", "regex", "derive"],
+ "weights": [0.5, -1.25e3, 3.0E-2, 0],
+ "nested": {"ok": true, "missing": null, "escaped": "a \"quoted\" word"},
+ "list": [{"x": 1, "y": 2}, {"x": 3, "y": 4}, {"x": 5, "y": 6}]},
+"#;
+
+/// A representative chunk of Lisp
+const LISP_SOURCE: &str = r#"
+; Map a function over a list.
+(define (map f xs)
+  (if (null? xs)
+      '()
+      (cons (f (car xs)) (map f (cdr xs)))))
+(define-syntax swap!
+  (syntax-rules ()
+    ((_ a b) `(let ((tmp ,a)) (set! ,a ,b) (set! ,b tmp)))))
+(display (map (lambda (x) (* x -42)) (list 1 2 3 ,@rest "str\"ing")))
+"#;
+
+/// Repeats `chunk` until the result is at least `len` bytes long
+fn repeat_to(chunk: &str, len: usize) -> String {
+    chunk.repeat(len.div_ceil(chunk.len()))
+}
+
+/// Benchmarks one grammar's lexer over several input sizes
+macro_rules! bench_grammar {
+    ($criterion:expr, $name:literal, $token:ty, $source:expr) => {{
+        let mut group = $criterion.benchmark_group($name);
+        for size in [1 << 10, 1 << 14, 1 << 18] {
+            let input = repeat_to($source, size);
+            group.throughput(Throughput::Bytes(input.len() as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(size), &input, |b, input| {
+                b.iter(|| {
+                    let tokens = <$token>::tokenize(black_box(input.as_str()))
+                        .collect()
+                        .expect("benchmark input should lex");
+                    black_box(tokens.len())
+                });
+            });
+        }
+        group.finish();
+    }};
+}
+
+/// Benchmarks every grammar
+fn lexer_benchmarks(c: &mut Criterion) {
+    bench_grammar!(c, "c_like", CToken, C_SOURCE);
+    bench_grammar!(c, "json", JsonToken, JSON_SOURCE);
+    bench_grammar!(c, "lisp", LispToken, LISP_SOURCE);
+}
+
+criterion_group!(benches, lexer_benchmarks);
+criterion_main!(benches);
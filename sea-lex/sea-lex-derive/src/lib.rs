@@ -1,6 +1,9 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Ident, Meta, Variant};
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprPath, Fields, Ident, LitStr, Variant,
+};
 
 #[proc_macro_derive(Token, attributes(token, skip))]
 pub fn derive_token(input: TokenStream) -> TokenStream {
@@ -35,38 +38,20 @@ pub fn derive_token(input: TokenStream) -> TokenStream {
                     (::sea_lex::TokenCreator::Unit(Self::#variant_name), #pattern, #is_regex)
                 }
             }
-            TokenCreatorType::Function(variant_name, func_name) => {
-                // Try to parse as a path first, if that fails, parse as an expression
-                // Handle special case for String::from
-                if func_name == "String::from" {
+            TokenCreatorType::Function(variant_name, parser) => {
+                if is_conversion(parser) {
+                    // `From::from` and `Into::into` are generic over their input, so they
+                    // can't be used as a `TokenParser`; call them on the text instead
                     quote! {
                         (::sea_lex::TokenCreator::Parser(std::sync::Arc::new(move |text, _position| {
-                            Ok(Self::#variant_name(String::from(text)))
-                        })), #pattern, #is_regex)
-                    }
-                } else if let Ok(func_path) = syn::parse_str::<syn::Path>(func_name) {
-                    quote! {
-                        (::sea_lex::TokenCreator::Parser(std::sync::Arc::new(move |text, position| {
-                            use ::sea_lex::TokenParser;
-                            let parser = #func_path;
-                            parser.parse(text, position).map(Self::#variant_name)
-                        })), #pattern, #is_regex)
-                    }
-                } else if let Ok(func_expr) = syn::parse_str::<syn::Expr>(func_name) {
-                    quote! {
-                        (::sea_lex::TokenCreator::Parser(std::sync::Arc::new(move |text, position| {
-                            use ::sea_lex::TokenParser;
-                            let parser = #func_expr;
-                            parser.parse(text, position).map(Self::#variant_name)
+                            Ok(Self::#variant_name(#parser(text)))
                         })), #pattern, #is_regex)
                     }
                 } else {
-                    // Fallback: treat as raw tokens
-                    let func_tokens: proc_macro2::TokenStream = func_name.parse().unwrap();
                     quote! {
                         (::sea_lex::TokenCreator::Parser(std::sync::Arc::new(move |text, position| {
                             use ::sea_lex::TokenParser;
-                            let parser = #func_tokens;
+                            let parser = #parser;
                             parser.parse(text, position).map(Self::#variant_name)
                         })), #pattern, #is_regex)
                     }
@@ -79,55 +64,193 @@ pub fn derive_token(input: TokenStream) -> TokenStream {
         .iter()
         .map(|(pattern, is_regex)| quote! { (#pattern, #is_regex) });
 
+    let enum_name_str = enum_name.to_string();
+    let token_rules = token_matchers.iter().map(|matcher| {
+        let pattern = &matcher.pattern;
+        let is_regex = matcher.is_regex;
+        let (variant_name, has_value) = match &matcher.creator {
+            TokenCreatorType::Unit(variant_name) => (variant_name, false),
+            TokenCreatorType::Function(variant_name, _) => (variant_name, true),
+        };
+        let kind = variant_name.to_string();
+        quote! {
+            ::sea_lex::TokenRule {
+                kind: #kind,
+                pattern: ::sea_lex::Pattern { source: #pattern, is_regex: #is_regex },
+                has_value: #has_value,
+            }
+        }
+    });
+    let skip_rules = skip_patterns.iter().map(|(pattern, is_regex)| {
+        quote! { ::sea_lex::Pattern { source: #pattern, is_regex: #is_regex } }
+    });
+    let kind_arms = data_enum.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        let kind = variant_name.to_string();
+        quote! { Self::#variant_name { .. } => #kind }
+    });
+
+    let build_table = quote! {
+        let matchers = vec![
+            #(#matcher_implementations),*
+        ];
+        let skip_patterns = vec![
+            #(#skip_pattern_strs),*
+        ];
+        ::sea_lex::Table::new(matchers, skip_patterns)
+    };
+
+    // Statics can't depend on generic parameters, so only non-generic token types get a
+    // cached table; generic ones compile a fresh table for every lexer
+    let is_generic = !input.generics.params.is_empty();
+    let table_constructors = if !is_generic {
+        quote! {
+            /// Get the compiled token table for this token type,
+            /// compiling it on first use
+            ///
+            /// # Errors
+            ///
+            /// Returns a `LexError` if any of the token patterns are invalid regexes
+            pub fn table() -> ::std::result::Result<&'static ::sea_lex::Table<Self>, ::sea_lex::LexError> {
+                static TABLE: ::std::sync::OnceLock<::sea_lex::Table<#enum_name>> =
+                    ::std::sync::OnceLock::new();
+                if let Some(table) = TABLE.get() {
+                    return Ok(table);
+                }
+                let table = { #build_table }?;
+                Ok(TABLE.get_or_init(|| table))
+            }
+
+            /// Create a new lexer for this token type
+            ///
+            /// # Errors
+            ///
+            /// Returns a `LexError` if any of the token patterns are invalid regexes
+            pub fn try_lexer(
+                input: impl Into<String>,
+            ) -> ::std::result::Result<::sea_lex::Lexer<Self>, ::sea_lex::LexError> {
+                Ok(::sea_lex::Lexer::from_table(Self::table()?, input))
+            }
+        }
+    } else {
+        quote! {
+            /// Create a new lexer for this token type
+            ///
+            /// # Errors
+            ///
+            /// Returns a `LexError` if any of the token patterns are invalid regexes
+            pub fn try_lexer(
+                input: impl Into<String>,
+            ) -> ::std::result::Result<::sea_lex::Lexer<Self>, ::sea_lex::LexError> {
+                let table = { #build_table }?;
+                Ok(::sea_lex::Lexer::with_table(table, input))
+            }
+        }
+    };
+
     let expanded = quote! {
         impl #impl_generics #enum_name #ty_generics #where_clause {
+            #table_constructors
+
             /// Create a new lexer for this token type
+            ///
+            /// # Panics
+            ///
+            /// Panics if any of the token patterns are invalid regexes;
+            /// use `try_lexer` to handle that case
             pub fn lexer(input: impl Into<String>) -> ::sea_lex::Lexer<Self> {
-                let matchers = vec![
-                    #(#matcher_implementations),*
-                ];
-                let skip_patterns = vec![
-                    #(#skip_pattern_strs),*
-                ];
-                ::sea_lex::Lexer::new(input, matchers, skip_patterns).unwrap()
+                match Self::try_lexer(input) {
+                    Ok(lexer) => lexer,
+                    Err(error) => panic!("invalid token pattern in {}: {error}", #enum_name_str),
+                }
             }
-            
+
             /// Create a tokenizing iterator for this token type
+            ///
+            /// # Panics
+            ///
+            /// Panics if any of the token patterns are invalid regexes
             pub fn tokenize(input: impl Into<String>) -> ::sea_lex::Lexer<Self> {
                 Self::lexer(input)
             }
+
+            /// Describe every pattern and skip pattern recognized by this token type
+            pub fn grammar() -> &'static ::sea_lex::Grammar {
+                static GRAMMAR: ::sea_lex::Grammar = ::sea_lex::Grammar {
+                    name: #enum_name_str,
+                    tokens: &[#(#token_rules),*],
+                    skips: &[#(#skip_rules),*],
+                };
+                &GRAMMAR
+            }
+        }
+
+        impl #impl_generics ::sea_lex::TokenGrammar for #enum_name #ty_generics #where_clause {
+            fn grammar() -> &'static ::sea_lex::Grammar {
+                <#enum_name #ty_generics>::grammar()
+            }
+
+            fn kind(&self) -> &'static str {
+                match self {
+                    #(#kind_arms),*
+                }
+            }
         }
     };
 
     TokenStream::from(expanded)
 }
 
-#[derive(Debug)]
 enum TokenCreatorType {
     Unit(Ident),
-    Function(Ident, String),
+    Function(Ident, Expr),
 }
 
-#[derive(Debug)]
 struct TokenMatcherInfo {
     pattern: String,
     creator: TokenCreatorType,
     is_regex: bool,
 }
 
+/// The arguments of a `#[token(pattern)]`, `#[token(pattern, parser)]`, or `#[skip(pattern)]`
+struct PatternArgs {
+    pattern: String,
+    is_regex: bool,
+    parser: Option<Expr>,
+}
+
+impl Parse for PatternArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lit: LitStr = input.parse()?;
+        // Raw string literals (`r"..."`) are regexes; regular ones are literals
+        let is_regex = lit.token().to_string().starts_with('r');
+        let parser = if input.peek(syn::Token![,]) {
+            input.parse::<syn::Token![,]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self {
+            pattern: lit.value(),
+            is_regex,
+            parser,
+        })
+    }
+}
+
 fn extract_skip_patterns(attrs: &[Attribute]) -> Vec<(String, bool)> {
     let mut skip_patterns = Vec::new();
 
     for attr in attrs {
         // Handle #[skip(pattern)] syntax only
         if attr.path().is_ident("skip") {
-            if let Meta::List(meta_list) = &attr.meta {
-                let tokens_str = meta_list.tokens.to_string();
-                let pattern_with_quotes = tokens_str.trim();
-                
-                if let Some((pattern, is_regex)) = parse_pattern_string(pattern_with_quotes) {
-                    skip_patterns.push((pattern, is_regex));
-                }
+            if let Ok(PatternArgs {
+                pattern,
+                is_regex,
+                parser: None,
+            }) = attr.parse_args()
+            {
+                skip_patterns.push((pattern, is_regex));
             }
         }
     }
@@ -135,27 +258,6 @@ fn extract_skip_patterns(attrs: &[Attribute]) -> Vec<(String, bool)> {
     skip_patterns
 }
 
-fn parse_pattern_string(pattern_with_quotes: &str) -> Option<(String, bool)> {
-    if pattern_with_quotes.starts_with("r\"")
-        && pattern_with_quotes.ends_with('"')
-    {
-        // Raw string literal: r"pattern" - this is a regex
-        let pattern = &pattern_with_quotes[2..pattern_with_quotes.len() - 1];
-        Some((pattern.to_string(), true))
-    } else if pattern_with_quotes.starts_with('"')
-        && pattern_with_quotes.ends_with('"')
-    {
-        // Regular string literal: "pattern" - this is a literal
-        if let Ok(lit) = syn::parse_str::<syn::LitStr>(pattern_with_quotes) {
-            Some((lit.value(), false))
-        } else {
-            None
-        }
-    } else {
-        None
-    }
-}
-
 fn extract_token_matcher(variant: &Variant) -> Option<TokenMatcherInfo> {
     for attr in &variant.attrs {
         if attr.path().is_ident("token") {
@@ -166,96 +268,53 @@ fn extract_token_matcher(variant: &Variant) -> Option<TokenMatcherInfo> {
 }
 
 fn parse_token_attribute(attr: &Attribute, variant: &Variant) -> Option<TokenMatcherInfo> {
-    if let Meta::List(meta_list) = &attr.meta {
-        // Simple string parsing approach
-        let tokens_str = meta_list.tokens.to_string();
-        let parts: Vec<&str> = tokens_str.split(',').map(|s| s.trim()).collect();
-
-        match parts.len() {
-            1 => {
-                // #[token("pattern")] or #[token(r"pattern")]
-                let pattern_with_quotes = parts[0].trim();
-                if pattern_with_quotes.starts_with("r\"") && pattern_with_quotes.ends_with('"') {
-                    // Raw string literal: r"pattern" - this is a regex
-                    let pattern = &pattern_with_quotes[2..pattern_with_quotes.len() - 1];
-                    let creator = match &variant.fields {
-                        Fields::Unit => TokenCreatorType::Unit(variant.ident.clone()),
-                        _ => return None,
-                    };
-                    return Some(TokenMatcherInfo {
-                        pattern: pattern.to_string(),
-                        creator,
-                        is_regex: true,
-                    });
-                } else if pattern_with_quotes.starts_with('"') && pattern_with_quotes.ends_with('"')
-                {
-                    // Regular string literal: "pattern" - this is a literal
-                    // Need to parse as a string literal to handle escapes properly
-                    if let Ok(lit) = syn::parse_str::<syn::LitStr>(pattern_with_quotes) {
-                        let creator = match &variant.fields {
-                            Fields::Unit => TokenCreatorType::Unit(variant.ident.clone()),
-                            _ => return None,
-                        };
-                        return Some(TokenMatcherInfo {
-                            pattern: lit.value(),
-                            creator,
-                            is_regex: false,
-                        });
-                    } else {
-                        return None;
-                    }
-                } else {
-                    return None;
-                }
-            }
-            _ if parts.len() >= 2 => {
-                // #[token("pattern", function)] or #[token(r"pattern", function)]
-                // Handle both simple functions and closures
-                let pattern_with_quotes = parts[0].trim();
-                // Join all parts after the first comma to handle closures with commas
-                let func_parts: Vec<&str> = parts[1..].iter().map(|s| s.trim()).collect();
-                let func_name = func_parts.join(", ");
+    let PatternArgs {
+        pattern,
+        is_regex,
+        parser,
+    } = attr.parse_args().ok()?;
 
-                if pattern_with_quotes.starts_with("r\"") && pattern_with_quotes.ends_with('"') {
-                    // Raw string literal: r"pattern" - this is a regex
-                    let pattern = &pattern_with_quotes[2..pattern_with_quotes.len() - 1];
-                    let creator = match &variant.fields {
-                        Fields::Unnamed(_) => {
-                            TokenCreatorType::Function(variant.ident.clone(), func_name)
-                        }
-                        _ => return None,
-                    };
-                    return Some(TokenMatcherInfo {
-                        pattern: pattern.to_string(),
-                        creator,
-                        is_regex: true,
-                    });
-                } else if pattern_with_quotes.starts_with('"') && pattern_with_quotes.ends_with('"')
-                {
-                    // Regular string literal: "pattern" - this is a literal
-                    // Need to parse as a string literal to handle escapes properly
-                    if let Ok(lit) = syn::parse_str::<syn::LitStr>(pattern_with_quotes) {
-                        let creator = match &variant.fields {
-                            Fields::Unnamed(_) => {
-                                TokenCreatorType::Function(variant.ident.clone(), func_name)
-                            }
-                            _ => return None,
-                        };
-                        return Some(TokenMatcherInfo {
-                            pattern: lit.value(),
-                            creator,
-                            is_regex: false,
-                        });
-                    } else {
-                        return None;
-                    }
-                } else {
-                    return None;
-                }
-            }
-            _ => return None,
+    let creator = match (parser, &variant.fields) {
+        // #[token("pattern")] or #[token(r"pattern")]
+        (None, Fields::Unit) => TokenCreatorType::Unit(variant.ident.clone()),
+        // #[token("pattern", function)] or #[token(r"pattern", function)]
+        (Some(parser), Fields::Unnamed(_)) => {
+            TokenCreatorType::Function(variant.ident.clone(), parser)
         }
-    }
+        _ => return None,
+    };
 
-    None
+    Some(TokenMatcherInfo {
+        pattern,
+        creator,
+        is_regex,
+    })
+}
+
+/// Reports whether a parser is a path to a conversion trait's method: `From::from`,
+/// `Into::into`, `<T>::from`, or a type's `from` or `into`, like `String::from`
+///
+/// By Rust's naming conventions, the segment before `from` is a type when it's in
+/// `UpperCamelCase`; a function named `from` in a module, like `parsers::from`, is an
+/// ordinary parser that goes through `TokenParser`.
+fn is_conversion(parser: &Expr) -> bool {
+    let Expr::Path(ExprPath { qself, path, .. }) = parser else {
+        return false;
+    };
+    let mut segments = path.segments.iter().rev();
+    let Some(method) = segments.next() else {
+        return false;
+    };
+    if method.ident != "from" && method.ident != "into" {
+        return false;
+    }
+    match (qself, segments.next()) {
+        // `<T>::from` or `<T as From<_>>::from`
+        (Some(_), _) => true,
+        (None, Some(owner)) => owner
+            .ident
+            .to_string()
+            .starts_with(|c: char| c.is_ascii_uppercase()),
+        (None, None) => false,
+    }
 }
@@ -1,5 +1,6 @@
 //! Error types for sea-lex
 
+use crate::{FileId, Span};
 use regex::Error as RegexError;
 use thiserror::Error;
 
@@ -8,26 +9,155 @@ use thiserror::Error;
 pub enum LexError {
     /// An unexpected character was encountered during lexing
     #[error("Unexpected character at position {position}: '{character}'")]
-    UnexpectedChar { 
+    UnexpectedChar {
         /// The position in the input where the error occurred
-        position: usize, 
+        position: usize,
         /// The unexpected character
-        character: char 
+        character: char,
     },
     /// An invalid regular expression pattern was provided
     #[error("Invalid regex pattern '{pattern}': '{error}'")]
-    InvalidRegex { 
+    InvalidRegex {
         /// The invalid regex pattern
-        pattern: String, 
+        pattern: String,
         /// The underlying regex error
-        error: RegexError 
+        error: RegexError,
     },
     /// An error occurred while parsing a token field value
     #[error("Error parsing token at position {position}: {error}")]
-    TokenParseError { 
+    TokenParseError {
         /// The position in the input where the error occurred
-        position: usize, 
+        position: usize,
         /// The underlying parsing error
-        error: Box<dyn std::error::Error> 
+        error: Box<dyn std::error::Error>,
     },
 }
+
+/// Error that can occur during preprocessing
+#[derive(Debug, Error)]
+pub enum PreprocessError {
+    /// A file couldn't be lexed
+    #[error("{error}")]
+    Lex {
+        /// The file being lexed
+        file: FileId,
+        /// The underlying lexing error
+        error: LexError,
+    },
+    /// An unknown directive was encountered
+    #[error("Unknown preprocessor directive '#{name}'")]
+    UnknownDirective {
+        /// The name of the directive
+        name: String,
+        /// The location of the directive
+        span: Span,
+    },
+    /// A macro definition was malformed
+    #[error("Invalid macro definition: {message}")]
+    InvalidDefinition {
+        /// A description of the problem
+        message: String,
+        /// The location of the problem
+        span: Span,
+    },
+    /// A function-like macro invocation was missing its closing parenthesis
+    #[error("Unterminated invocation of macro '{name}'")]
+    UnterminatedInvocation {
+        /// The name of the macro
+        name: String,
+        /// The location of the macro name
+        span: Span,
+    },
+    /// A function-like macro was invoked with the wrong number of arguments
+    #[error("Macro '{name}' expects {expected} argument(s), but {found} were given")]
+    ArgumentCount {
+        /// The name of the macro
+        name: String,
+        /// The number of parameters the macro declares
+        expected: usize,
+        /// The number of arguments that were given
+        found: usize,
+        /// The location of the macro name
+        span: Span,
+    },
+    /// Token pasting or stringizing produced text that isn't exactly one token
+    #[error("'{text}' is not a valid token")]
+    InvalidToken {
+        /// The text that was produced
+        text: String,
+        /// The location of the `#` or `##` operator
+        span: Span,
+    },
+    /// A conditional directive appeared without a matching `#if`, or after `#else`
+    #[error("'#{directive}' without a matching '#if'")]
+    UnmatchedConditional {
+        /// The name of the directive
+        directive: String,
+        /// The location of the directive
+        span: Span,
+    },
+    /// A file ended inside a conditional group
+    #[error("Unterminated conditional directive")]
+    UnterminatedConditional {
+        /// The location of the opening directive
+        span: Span,
+    },
+    /// An `#include` directive was malformed
+    #[error("Invalid #include directive")]
+    InvalidInclude {
+        /// The location of the directive
+        span: Span,
+    },
+    /// An included file couldn't be found by the resolver
+    #[error("Included file '{path}' not found")]
+    IncludeNotFound {
+        /// The requested path
+        path: String,
+        /// The location of the directive
+        span: Span,
+    },
+    /// Includes were nested too deeply (usually due to a recursive include)
+    #[error("#include nested too deeply")]
+    IncludeTooDeep {
+        /// The location of the directive
+        span: Span,
+    },
+    /// An `#if` or `#elif` expression couldn't be evaluated
+    #[error("Invalid preprocessor expression: {message}")]
+    InvalidExpression {
+        /// A description of the problem
+        message: String,
+        /// The location of the problem
+        span: Span,
+    },
+    /// An `#error` directive was encountered
+    #[error("#error {message}")]
+    ErrorDirective {
+        /// The message following the directive
+        message: String,
+        /// The location of the directive
+        span: Span,
+    },
+}
+
+impl PreprocessError {
+    /// Get the location of this error, if known
+    #[must_use]
+    pub const fn span(&self) -> Option<Span> {
+        match self {
+            Self::Lex { .. } => None,
+            Self::UnknownDirective { span, .. }
+            | Self::InvalidDefinition { span, .. }
+            | Self::UnterminatedInvocation { span, .. }
+            | Self::ArgumentCount { span, .. }
+            | Self::InvalidToken { span, .. }
+            | Self::UnmatchedConditional { span, .. }
+            | Self::UnterminatedConditional { span }
+            | Self::InvalidInclude { span }
+            | Self::IncludeNotFound { span, .. }
+            | Self::IncludeTooDeep { span }
+            | Self::InvalidExpression { span, .. }
+            | Self::ErrorDirective { span, .. } => Some(*span),
+        }
+    }
+}
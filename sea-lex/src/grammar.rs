@@ -0,0 +1,328 @@
+//! Grammar descriptions of token types, and exporters for editor tooling
+
+use std::fmt::Write;
+
+/// A description of every pattern recognized by a token type
+///
+/// Generated by `#[derive(Token)]` as `MyToken::grammar()`.
+/// Rules appear in declaration order, which is also their matching priority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    /// The name of the token type
+    pub name: &'static str,
+    /// The token rules, in priority order
+    pub tokens: &'static [TokenRule],
+    /// The skip patterns, in priority order
+    pub skips: &'static [Pattern],
+}
+
+/// A token type with a [`Grammar`] describing its patterns
+///
+/// Implemented by `#[derive(Token)]`, so that code generic over tokens, like parsers'
+/// error messages, can name them the way they're written.
+pub trait TokenGrammar {
+    /// Describe every pattern and skip pattern recognized by this token type
+    fn grammar() -> &'static Grammar;
+
+    /// Get the name of this token's variant
+    fn kind(&self) -> &'static str;
+
+    /// Describe this token's kind for diagnostics, as [`Grammar::describe`] does
+    fn describe(&self) -> String {
+        Self::grammar().describe(self.kind())
+    }
+}
+
+/// A pattern that produces one kind of token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenRule {
+    /// The name of the token variant
+    pub kind: &'static str,
+    /// The pattern that matches this token
+    pub pattern: Pattern,
+    /// Whether the token carries a value parsed from its text
+    pub has_value: bool,
+}
+
+/// A literal or regular expression pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    /// The pattern source, as written in the attribute
+    pub source: &'static str,
+    /// Whether the source is a regular expression (as opposed to a literal string)
+    pub is_regex: bool,
+}
+
+impl Pattern {
+    /// Render this pattern as an unanchored regular expression
+    ///
+    /// Literals are escaped, and the leading `^` of anchored regexes is removed.
+    #[must_use]
+    pub fn to_regex(&self) -> String {
+        if self.is_regex {
+            self.source
+                .strip_prefix('^')
+                .unwrap_or(self.source)
+                .to_string()
+        } else {
+            regex::escape(self.source)
+        }
+    }
+}
+
+impl TokenRule {
+    /// Guess a TextMate scope for this token from its kind and pattern
+    ///
+    /// Patterns for a single word are keywords, whether literal, like `"if"`, or a regex
+    /// ending at a word boundary, like `r"if\b"`. Other tokens are classified by the
+    /// first word of their kind, like `Int` in `IntLit`, and punctuation by any word of
+    /// it, like `Paren` in `LeftParen`.
+    ///
+    /// Returns `None` for tokens that don't warrant highlighting.
+    #[must_use]
+    pub fn default_scope(&self) -> Option<&'static str> {
+        let words = kind_words(self.kind);
+        let first = words.first().map_or("", String::as_str);
+        let starts_with_any = |names: &[&str]| names.contains(&first);
+        let has_any = |names: &[&str]| words.iter().any(|word| names.contains(&word.as_str()));
+
+        let keyword = self.pattern.keyword();
+
+        if keyword.is_some_and(|keyword| ["true", "false", "null", "nil"].contains(&keyword)) {
+            Some("constant.language")
+        } else if keyword.is_some() {
+            Some("keyword.control")
+        } else if starts_with_any(&["comment"]) {
+            Some("comment.line")
+        } else if starts_with_any(&["string", "char"]) && self.pattern.is_regex {
+            Some("string.quoted")
+        } else if starts_with_any(&["number", "integer", "int", "float", "digit"]) {
+            Some("constant.numeric")
+        } else if starts_with_any(&["true", "false", "bool", "null", "nil"]) {
+            Some("constant.language")
+        } else if starts_with_any(&["ident", "identifier", "name", "symbol"]) {
+            Some("variable.other")
+        } else if !self.pattern.is_regex
+            && self.pattern.source.chars().all(|c| c.is_ascii_punctuation())
+        {
+            if has_any(&[
+                "paren",
+                "brace",
+                "bracket",
+                "comma",
+                "semi",
+                "semicolon",
+                "colon",
+                "dot",
+            ]) {
+                Some("punctuation")
+            } else {
+                Some("keyword.operator")
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl Pattern {
+    /// Get the only text this pattern matches, if it's a literal or a keyword
+    #[must_use]
+    pub fn text(&self) -> Option<&'static str> {
+        if self.is_regex {
+            self.keyword()
+        } else {
+            Some(self.source)
+        }
+    }
+
+    /// Get the word this pattern matches if it's a keyword: a literal word, or a regex
+    /// for a word followed by a word boundary
+    fn keyword(&self) -> Option<&'static str> {
+        let word = if self.is_regex {
+            let source = self.source.strip_prefix('^').unwrap_or(self.source);
+            let source = source.strip_prefix(r"\b").unwrap_or(source);
+            source.strip_suffix(r"\b")?
+        } else {
+            self.source
+        };
+        let is_word = word.starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && word.chars().all(|c| c.is_alphanumeric() || c == '_');
+        is_word.then_some(word)
+    }
+}
+
+/// Split a token kind into lowercase words, at underscores and capitals that start a
+/// word, so `IntLit` is `int lit`, `LParen` is `l paren`, and `EOF` is `eof`
+fn kind_words(kind: &str) -> Vec<String> {
+    let chars: Vec<char> = kind.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    for (index, &c) in chars.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+        let next = chars.get(index + 1);
+        let starts_word = c.is_uppercase()
+            && previous.is_some_and(|previous| {
+                !previous.is_uppercase() || next.is_some_and(|next| next.is_lowercase())
+            });
+        if words.is_empty() || c == '_' || starts_word {
+            words.push(String::new());
+        }
+        if c != '_' {
+            words.last_mut().unwrap().extend(c.to_lowercase());
+        }
+    }
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+impl Grammar {
+    /// Describe a kind of token for diagnostics: the text it's always spelled with, in
+    /// backticks like `` `;` ``, or the kind's name if its spelling varies, like `Ident`
+    #[must_use]
+    pub fn describe(&self, kind: &str) -> String {
+        self.tokens
+            .iter()
+            .filter(|rule| rule.kind == kind)
+            .find_map(|rule| rule.pattern.text())
+            .map_or_else(|| kind.to_string(), |text| format!("`{text}`"))
+    }
+
+    /// Export this grammar as JSON
+    ///
+    /// The output has the form
+    /// `{"name": ..., "tokens": [{"kind": ..., "pattern": ..., "regex": ..., "value": ...}],
+    /// "skips": [{"pattern": ..., "regex": ...}]}`.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let tokens = self
+            .tokens
+            .iter()
+            .map(|rule| {
+                format!(
+                    r#"    {{"kind": {}, "pattern": {}, "regex": {}, "value": {}}}"#,
+                    json_string(rule.kind),
+                    json_string(rule.pattern.source),
+                    rule.pattern.is_regex,
+                    rule.has_value,
+                )
+            })
+            .collect::<Vec<_>>();
+        let skips = self
+            .skips
+            .iter()
+            .map(|pattern| {
+                format!(
+                    r#"    {{"pattern": {}, "regex": {}}}"#,
+                    json_string(pattern.source),
+                    pattern.is_regex,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        format!(
+            "{{\n  \"name\": {},\n  \"tokens\": {},\n  \"skips\": {}\n}}\n",
+            json_string(self.name),
+            json_array(&tokens),
+            json_array(&skips),
+        )
+    }
+
+    /// Export this grammar as a TextMate `.tmLanguage.json` grammar,
+    /// using [`TokenRule::default_scope`] to assign scopes
+    ///
+    /// `scope_name` is the grammar's root scope (e.g., `source.seac`).
+    #[must_use]
+    pub fn to_textmate(&self, scope_name: &str) -> String {
+        self.to_textmate_with(scope_name, TokenRule::default_scope)
+    }
+
+    /// Export this grammar as a TextMate `.tmLanguage.json` grammar,
+    /// using `scope_for` to assign scopes
+    ///
+    /// Scopes are suffixed with the language part of `scope_name`
+    /// (e.g., `keyword.control.seac`).
+    /// Tokens without a scope are still emitted, so that their text is consumed
+    /// exactly as the lexer would consume it.
+    /// Skip patterns that match something other than whitespace are scoped as comments.
+    pub fn to_textmate_with<'s>(
+        &self,
+        scope_name: &str,
+        scope_for: impl Fn(&TokenRule) -> Option<&'s str>,
+    ) -> String {
+        let language = scope_name.rsplit('.').next().unwrap_or(scope_name);
+
+        let skips = self
+            .skips
+            .iter()
+            .filter(|pattern| !is_whitespace_pattern(pattern))
+            .map(|pattern| {
+                format!(
+                    r#"    {{"name": {}, "match": {}}}"#,
+                    json_string(&format!("comment.{language}")),
+                    json_string(&pattern.to_regex()),
+                )
+            });
+        let tokens = self.tokens.iter().map(|rule| {
+            let name = scope_for(rule).map_or_else(String::new, |scope| {
+                format!(r#""name": {}, "#, json_string(&format!("{scope}.{language}")))
+            });
+            format!(
+                r#"    {{{name}"match": {}}}"#,
+                json_string(&rule.pattern.to_regex())
+            )
+        });
+        let patterns = skips.chain(tokens).collect::<Vec<_>>();
+
+        format!(
+            "{{\n  \"$schema\": {},\n  \"name\": {},\n  \"scopeName\": {},\n  \"patterns\": {}\n}}\n",
+            json_string(
+                "https://raw.githubusercontent.com/martinring/tmlanguage/master/tmlanguage.json"
+            ),
+            json_string(self.name),
+            json_string(scope_name),
+            json_array(&patterns),
+        )
+    }
+}
+
+/// Reports whether a skip pattern only ever matches whitespace
+fn is_whitespace_pattern(pattern: &Pattern) -> bool {
+    if pattern.is_regex {
+        matches!(
+            pattern.source,
+            r"\s+" | r"\s*" | r"\s" | r"^\s+" | r"[ \t\r\n]+" | r"[ \t\n\r]+" | r"[ \t]+" | r"\n"
+        )
+    } else {
+        pattern.source.chars().all(char::is_whitespace)
+    }
+}
+
+/// Format a JSON array from already-formatted, indented elements
+fn json_array(elements: &[String]) -> String {
+    if elements.is_empty() {
+        "[]".into()
+    } else {
+        format!("[\n{}\n  ]", elements.join(",\n"))
+    }
+}
+
+/// Quote and escape a string as a JSON string literal
+fn json_string(text: &str) -> String {
+    let mut output = String::with_capacity(text.len() + 2);
+    output.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(output, "\\u{:04x}", u32::from(c));
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
@@ -1,8 +1,6 @@
 //! Lexer implementation for sea-lex
 
-use crate::{LexError, TokenInfo};
-use regex::Regex;
-use std::sync::Arc;
+use crate::{LexError, Table, TokenCreator, TokenInfo};
 
 /// A compiled lexer for a specific token type
 pub struct Lexer<T> {
@@ -10,39 +8,16 @@ pub struct Lexer<T> {
     input: String,
     /// The current position in the input
     position: usize,
-    /// The compiled token matchers
-    matchers: Vec<(TokenMatcher, TokenCreator<T>)>,
-    /// The compiled skip patterns
-    skip_patterns: Vec<TokenMatcher>,
-}
-
-/// A compiled token matcher
-enum TokenMatcher {
-    /// A regular expression matcher
-    RegexMatcher {
-        /// The regex pattern to match
-        pattern: Regex,
-    },
-    /// A literal string matcher
-    LiteralMatcher {
-        /// The literal string to match
-        pattern: String,
-    },
-}
-
-/// Function to create a token from matched text
-pub enum TokenCreator<T> {
-    /// Create a unit variant (no data)
-    Unit(T),
-    /// Create a variant by calling a parser on the matched text
-    Parser(std::sync::Arc<dyn Fn(&str, usize) -> Result<T, crate::LexError> + Send + Sync>),
-    /// Skip this match (don't emit a token)
-    Skip,
+    /// The compiled token table, which may be shared with other lexers
+    table: Table<T>,
 }
 
 impl<T: Clone> Lexer<T> {
     /// Create a new lexer with the given input and matchers
     ///
+    /// This compiles a new [`Table`]; to lex many inputs with the same patterns,
+    /// compile the table once and use [`Lexer::from_table`] instead.
+    ///
     /// # Errors
     ///
     /// Returns a `LexError` if any of the provided regex patterns are invalid
@@ -51,78 +26,30 @@ impl<T: Clone> Lexer<T> {
         matchers: Vec<(TokenCreator<T>, &str, bool)>, // bool indicates if regex
         skip_patterns: Vec<(&str, bool)>,             // bool indicates if regex
     ) -> Result<Self, LexError> {
-        let input = input.into();
-
-        let compiled_matchers = matchers
-            .into_iter()
-            .map(|(creator, pattern, is_regex)| {
-                TokenMatcher::try_new(pattern, is_regex).map(|matcher| (matcher, creator))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::with_table(Table::new(matchers, skip_patterns)?, input))
+    }
 
-        let compiled_skip_patterns = skip_patterns
-            .into_iter()
-            .map(|(pattern, is_regex)| TokenMatcher::try_new(pattern, is_regex))
-            .collect::<Result<Vec<_>, _>>()?;
+    /// Create a new lexer with the given input, taking ownership of a compiled table
+    pub fn with_table(table: Table<T>, input: impl Into<String>) -> Self {
+        Self {
+            input: input.into(),
+            position: 0,
+            table,
+        }
+    }
 
-        Ok(Self {
-            input,
+    /// Create a new lexer with the given input, using a shared compiled table
+    pub fn from_table(table: &'static Table<T>, input: impl Into<String>) -> Self {
+        Self {
+            input: input.into(),
             position: 0,
-            matchers: compiled_matchers,
-            skip_patterns: compiled_skip_patterns,
-        })
+            table: table.clone(),
+        }
     }
 
     /// Get the next token from the input
     pub fn next_token(&mut self) -> Option<Result<TokenInfo<T>, LexError>> {
-        'retry_skip: loop {
-            if self.position >= self.input.len() {
-                return None;
-            }
-
-            let remaining = &self.input[self.position..];
-
-            // Try skip patterns first
-            for skip_pattern in &self.skip_patterns {
-                if let Some(len) = skip_pattern.try_match(remaining) {
-                    self.position += len;
-                    continue 'retry_skip;
-                }
-            }
-            break;
-        }
-
-        let remaining = &self.input[self.position..];
-
-        // Try token matchers
-        for (matcher, creator) in &self.matchers {
-            if let Some(match_len) = matcher.try_match(remaining) {
-                let start = self.position;
-                let end = self.position + match_len;
-                let text = &self.input[start..end];
-                self.position = end;
-
-                match creator {
-                    TokenCreator::Unit(token) => {
-                        return Some(Ok(TokenInfo::new(token.clone(), text, start, end)));
-                    }
-                    TokenCreator::Parser(parser) => {
-                        return Some(
-                            parser(text, start).map(|token| TokenInfo::new(token, text, start, end)),
-                        );
-                    }
-                    TokenCreator::Skip => {
-                        break; // Continue to next iteration to skip this match
-                    }
-                }
-            }
-        }
-
-        // No pattern matched
-        Some(Err(LexError::UnexpectedChar {
-            position: self.position,
-            character: remaining.chars().next().unwrap_or_default(),
-        }))
+        self.table.next_token(&self.input, &mut self.position)
     }
 
     /// Collect all tokens into a vector
@@ -146,49 +73,3 @@ impl<T: Clone> Iterator for Lexer<T> {
         self.next_token()
     }
 }
-
-impl TokenMatcher {
-    /// Tries to create a new [`TokenMatcher`] from the given pattern
-    ///
-    /// # Errors
-    ///
-    /// Returns a `LexError` if any of the provided regex patterns are invalid
-    pub fn try_new(pattern: &str, is_regex: bool) -> Result<Self, LexError> {
-        if is_regex {
-            // Add `^` if not present
-            if pattern.starts_with('^') {
-                Regex::new(pattern)
-            } else {
-                Regex::new(&format!("^{pattern}"))
-            }
-            .map(|pattern| Self::RegexMatcher { pattern })
-            .map_err(|error| LexError::InvalidRegex {
-                pattern: pattern.into(),
-                error,
-            })
-        } else {
-            Ok(Self::LiteralMatcher {
-                pattern: pattern.into(),
-            })
-        }
-    }
-
-    /// Reports whether this pattern matches the given text,
-    /// and returns the length of the match if successful
-    pub fn try_match(&self, text: &str) -> Option<usize> {
-        match self {
-            Self::RegexMatcher { pattern } => pattern.find(text).map(|m| m.len()),
-            Self::LiteralMatcher { pattern } => text.starts_with(pattern).then_some(pattern.len()),
-        }
-    }
-}
-
-impl<T: Clone> Clone for TokenCreator<T> {
-    fn clone(&self) -> Self {
-        match self {
-            Self::Unit(token) => Self::Unit(token.clone()),
-            Self::Parser(parser) => Self::Parser(Arc::clone(parser)),
-            Self::Skip => Self::Skip,
-        }
-    }
-}
@@ -11,12 +11,20 @@
 )]
 
 mod error;
+mod grammar;
 mod lexer;
+mod preprocessor;
+mod source;
+mod table;
 mod token;
 mod token_parser;
 
 pub use error::*;
+pub use grammar::*;
 pub use lexer::*;
+pub use preprocessor::*;
+pub use source::*;
+pub use table::*;
 pub use token::*;
 pub use token_parser::*;
 
@@ -0,0 +1,969 @@
+//! A C-style preprocessor operating on sea-lex token streams
+//!
+//! The preprocessor works on the text of tokens, so it can be used with any token type
+//! whose lexer produces separate tokens for `#`, `##`, `(`, `)`, `,`, and identifiers.
+//! Pasted (`##`) and stringized (`#`) tokens are re-lexed with the token type's [`Table`].
+//!
+//! Directives are recognized by their line structure: a token begins a new line if the
+//! skipped text before it contains a newline that isn't escaped with a backslash.
+
+mod expr;
+mod resolver;
+
+pub use resolver::*;
+
+use crate::{FileId, LexError, Lexer, PreprocessError, SourceDb, Span, Table, TokenInfo};
+use expr::ExprItem;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// The maximum depth of nested `#include`s
+const MAX_INCLUDE_DEPTH: usize = 200;
+
+/// A token produced by the preprocessor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PpToken<T> {
+    /// The token variant
+    pub kind: T,
+    /// The text of the token
+    pub text: String,
+    /// The location of the token's text: in the source for ordinary tokens and macro
+    /// arguments, in the macro definition for tokens from a macro body, and at the
+    /// `#` or `##` operator for stringized and pasted tokens
+    pub span: Span,
+    /// The macro expansion that produced this token, if any
+    pub expansion: Option<Arc<Expansion>>,
+}
+
+/// A record of one macro expansion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+    /// The name of the expanded macro
+    pub name: String,
+    /// The location of the macro name at the invocation
+    pub call_site: Span,
+    /// The location of the macro name in its definition
+    pub definition: Span,
+    /// The expansion that produced the invocation, if it came from another macro
+    pub parent: Option<Arc<Self>>,
+}
+
+impl<T> PpToken<T> {
+    /// Iterate over the expansions that produced this token, innermost first
+    pub fn expansions(&self) -> impl Iterator<Item = &Expansion> {
+        std::iter::successors(self.expansion.as_deref(), |expansion| {
+            expansion.parent.as_deref()
+        })
+    }
+
+    /// Get the location in the source that produced this token:
+    /// the outermost macro invocation, or the token itself
+    #[must_use]
+    pub fn source_span(&self) -> Span {
+        self.expansions()
+            .last()
+            .map_or(self.span, |expansion| expansion.call_site)
+    }
+}
+
+/// A set of macro names that may not be expanded again (see Prosser's algorithm)
+type HideSet = BTreeSet<Rc<str>>;
+
+/// A token being preprocessed
+#[derive(Debug, Clone)]
+struct Tok<T> {
+    /// The output token
+    token: PpToken<T>,
+    /// Macros whose expansion produced this token
+    hide: HideSet,
+    /// Whether whitespace preceded this token
+    space_before: bool,
+}
+
+impl<T> Tok<T> {
+    /// Get the text of this token
+    fn text(&self) -> &str {
+        &self.token.text
+    }
+}
+
+/// A macro definition
+#[derive(Debug)]
+struct Macro<T> {
+    /// The parameters of a function-like macro, or `None` for an object-like macro
+    ///
+    /// A variadic macro's last parameter is `__VA_ARGS__`.
+    params: Option<Vec<String>>,
+    /// Whether the macro takes a variable number of arguments
+    variadic: bool,
+    /// The replacement list
+    body: Vec<Tok<T>>,
+    /// The location of the macro name in the definition
+    definition: Span,
+}
+
+/// The state of an `#if`/`#elif`/`#else`/`#endif` group
+#[derive(Debug)]
+struct Conditional {
+    /// The location of the opening directive
+    span: Span,
+    /// Whether the current branch is active
+    active: bool,
+    /// Whether some branch of this group has been taken, or the enclosing group is inactive
+    taken: bool,
+    /// Whether `#else` has been seen
+    seen_else: bool,
+}
+
+/// A C-style preprocessor
+///
+/// Supports object-like and function-like (including variadic) macros with `#` and `##`,
+/// `#include`, `#if`/`#ifdef`/`#ifndef`/`#elif`/`#else`/`#endif`, `#undef`, `#error`,
+/// `#pragma once`, and the built-in macros `__FILE__` and `__LINE__`.
+/// Other `#pragma`s, `#warning`, and `#line` are ignored.
+pub struct Preprocessor<'db, T: 'static, R> {
+    /// The table used to lex included files and re-lex pasted tokens
+    table: &'static Table<T>,
+    /// The database of source files, to which included files are added
+    db: &'db mut SourceDb,
+    /// The resolver for `#include` directives
+    resolver: R,
+    /// The defined macros
+    macros: HashMap<String, Rc<Macro<T>>>,
+    /// Files loaded by `#include`, by resolved name
+    loaded: HashMap<String, FileId>,
+    /// Files marked with `#pragma once`
+    once: HashSet<FileId>,
+    /// The first `#include` directive that loaded each file
+    include_sites: HashMap<FileId, Span>,
+}
+
+impl<'db, T: Clone + 'static, R: IncludeResolver> Preprocessor<'db, T, R> {
+    /// Create a preprocessor that lexes with `table`, adds included files to `db`,
+    /// and finds them with `resolver`
+    pub fn new(table: &'static Table<T>, db: &'db mut SourceDb, resolver: R) -> Self {
+        Self {
+            table,
+            db,
+            resolver,
+            macros: HashMap::new(),
+            loaded: HashMap::new(),
+            once: HashSet::new(),
+            include_sites: HashMap::new(),
+        }
+    }
+
+    /// Get the database of source files, including any files added by `#include`
+    #[must_use]
+    pub const fn db(&self) -> &SourceDb {
+        self.db
+    }
+
+    /// Get the location of the first `#include` directive that loaded `file`
+    #[must_use]
+    pub fn include_site(&self, file: FileId) -> Option<Span> {
+        self.include_sites.get(&file).copied()
+    }
+
+    /// Define a macro, in the style of a `-D` command-line option:
+    /// `NAME` (defined as `1`), `NAME=body`, or `NAME(params)=body`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PreprocessError` if the definition is malformed
+    pub fn define(&mut self, definition: &str) -> Result<(), PreprocessError> {
+        let source = match definition.split_once('=') {
+            Some((name, body)) => format!("{name} {body}"),
+            None => format!("{definition} 1"),
+        };
+        let span_len = source.len();
+        let file = self.db.add("<command line>", source.clone());
+        let tokens = self.read_lines(file, Lexer::from_table(self.table, source))?;
+        let tokens: Vec<_> = tokens.into_iter().flatten().collect();
+        self.define_macro(&tokens, Span::new(file, 0, span_len))
+    }
+
+    /// Remove a macro definition, reporting whether it was defined
+    pub fn undefine(&mut self, name: &str) -> bool {
+        self.macros.remove(name).is_some()
+    }
+
+    /// Reports whether a macro is defined
+    #[must_use]
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.macros.contains_key(name)
+    }
+
+    /// Preprocess a file from the source database
+    ///
+    /// Macros defined while preprocessing remain defined afterward.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PreprocessError` if the file (or a file it includes) can't be lexed or
+    /// contains an invalid directive or macro invocation
+    pub fn preprocess(&mut self, file: FileId) -> Result<Vec<PpToken<T>>, PreprocessError> {
+        let contents = self.db.file(file).contents().to_string();
+        self.preprocess_tokens(file, Lexer::from_table(self.table, contents))
+    }
+
+    /// Preprocess a stream of tokens lexed from a file in the source database
+    ///
+    /// The tokens' positions must refer to the file's contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PreprocessError` if lexing fails, or if the tokens contain an invalid
+    /// directive or macro invocation
+    pub fn preprocess_tokens(
+        &mut self,
+        file: FileId,
+        tokens: impl IntoIterator<Item = Result<TokenInfo<T>, LexError>>,
+    ) -> Result<Vec<PpToken<T>>, PreprocessError> {
+        let mut output = Vec::new();
+        self.process_file(file, tokens, 0, &mut output)?;
+        Ok(output)
+    }
+
+    /// Preprocess the tokens of a file, appending the results to `output`
+    fn process_file(
+        &mut self,
+        file: FileId,
+        tokens: impl IntoIterator<Item = Result<TokenInfo<T>, LexError>>,
+        depth: usize,
+        output: &mut Vec<PpToken<T>>,
+    ) -> Result<(), PreprocessError> {
+        let mut conditionals = Vec::new();
+        let mut pending = Vec::new();
+
+        for line in self.read_lines(file, tokens)? {
+            let active = conditionals.last().is_none_or(|c: &Conditional| c.active);
+            if line[0].text() == "#" {
+                if active {
+                    self.flush(&mut pending, output)?;
+                }
+                self.directive(&line, &mut conditionals, depth, output)?;
+            } else if active {
+                pending.extend(line);
+            }
+        }
+        self.flush(&mut pending, output)?;
+
+        conditionals.first().map_or(Ok(()), |conditional| {
+            Err(PreprocessError::UnterminatedConditional {
+                span: conditional.span,
+            })
+        })
+    }
+
+    /// Group a file's tokens into lines
+    fn read_lines(
+        &self,
+        file: FileId,
+        tokens: impl IntoIterator<Item = Result<TokenInfo<T>, LexError>>,
+    ) -> Result<Vec<Vec<Tok<T>>>, PreprocessError> {
+        let contents = self.db.file(file).contents();
+        let mut lines: Vec<Vec<Tok<T>>> = Vec::new();
+        let mut previous_end = 0;
+
+        for token in tokens {
+            let token = token.map_err(|error| PreprocessError::Lex { file, error })?;
+            let gap = contents.get(previous_end..token.start).unwrap_or_default();
+            previous_end = token.end;
+
+            let tok = Tok {
+                token: PpToken {
+                    span: token.span(file),
+                    kind: token.kind,
+                    text: token.text,
+                    expansion: None,
+                },
+                hide: HideSet::new(),
+                space_before: !gap.is_empty(),
+            };
+            match lines.last_mut() {
+                Some(line) if !starts_new_line(gap) => line.push(tok),
+                _ => lines.push(vec![tok]),
+            }
+        }
+
+        Ok(lines)
+    }
+
+    /// Macro-expand the pending text lines and append them to `output`
+    fn flush(
+        &mut self,
+        pending: &mut Vec<Tok<T>>,
+        output: &mut Vec<PpToken<T>>,
+    ) -> Result<(), PreprocessError> {
+        let expanded = self.expand(std::mem::take(pending))?;
+        output.extend(expanded.into_iter().map(|tok| tok.token));
+        Ok(())
+    }
+
+    /// Process a directive line
+    fn directive(
+        &mut self,
+        line: &[Tok<T>],
+        conditionals: &mut Vec<Conditional>,
+        depth: usize,
+        output: &mut Vec<PpToken<T>>,
+    ) -> Result<(), PreprocessError> {
+        // A lone `#` is the null directive
+        let Some(name) = line.get(1) else {
+            return Ok(());
+        };
+        let span = line[0].token.span.to(name.token.span);
+        let args = &line[2..];
+        let active = conditionals.last().is_none_or(|c| c.active);
+        let unmatched = || PreprocessError::UnmatchedConditional {
+            directive: name.text().to_string(),
+            span,
+        };
+
+        match name.text() {
+            "if" | "ifdef" | "ifndef" => {
+                let value = active
+                    && match name.text() {
+                        "if" => self.condition(args, span)?,
+                        "ifdef" => self.is_defined(macro_name(args, span)?),
+                        _ => !self.is_defined(macro_name(args, span)?),
+                    };
+                conditionals.push(Conditional {
+                    span,
+                    active: value,
+                    taken: value || !active,
+                    seen_else: false,
+                });
+            }
+            "elif" => {
+                let conditional = conditionals
+                    .last_mut()
+                    .filter(|c| !c.seen_else)
+                    .ok_or_else(unmatched)?;
+                conditional.active = !conditional.taken && self.condition(args, span)?;
+                conditional.taken |= conditional.active;
+            }
+            "else" => {
+                let conditional = conditionals
+                    .last_mut()
+                    .filter(|c| !c.seen_else)
+                    .ok_or_else(unmatched)?;
+                conditional.active = !conditional.taken;
+                conditional.taken = true;
+                conditional.seen_else = true;
+            }
+            "endif" => {
+                conditionals.pop().ok_or_else(unmatched)?;
+            }
+            _ if !active => {}
+            "define" => self.define_macro(args, span)?,
+            "undef" => {
+                let name = macro_name(args, span)?.to_string();
+                self.undefine(&name);
+            }
+            "include" => self.include(args, span, depth, output)?,
+            "error" => {
+                return Err(PreprocessError::ErrorDirective {
+                    message: spell(args),
+                    span,
+                })
+            }
+            "pragma" => {
+                if args.first().is_some_and(|arg| arg.text() == "once") {
+                    self.once.insert(span.file);
+                }
+            }
+            "warning" | "line" => {}
+            directive => {
+                return Err(PreprocessError::UnknownDirective {
+                    name: directive.to_string(),
+                    span,
+                })
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse and record a macro definition (the tokens after `#define`)
+    fn define_macro(&mut self, args: &[Tok<T>], span: Span) -> Result<(), PreprocessError> {
+        let invalid = |message: &str, span: Span| PreprocessError::InvalidDefinition {
+            message: message.into(),
+            span,
+        };
+
+        let name = macro_name(args, span)?.to_string();
+        let mut rest = &args[1..];
+
+        // A function-like macro's `(` immediately follows its name
+        let mut params = None;
+        let mut variadic = false;
+        if rest
+            .first()
+            .is_some_and(|t| t.text() == "(" && !t.space_before)
+        {
+            let mut names = Vec::new();
+            rest = &rest[1..];
+            loop {
+                let ellipsis_len = match rest {
+                    [dot, ..] if dot.text() == "..." => 1,
+                    [a, b, c, ..] if [a, b, c].iter().all(|t| t.text() == ".") => 3,
+                    _ => 0,
+                };
+                match rest {
+                    [close, ..] if close.text() == ")" && names.is_empty() => {
+                        rest = &rest[1..];
+                        break;
+                    }
+                    _ if ellipsis_len > 0 => {
+                        variadic = true;
+                        names.push("__VA_ARGS__".to_string());
+                        rest = &rest[ellipsis_len..];
+                    }
+                    [param, ..] if is_identifier(param.text()) => {
+                        if names.iter().any(|name| name == param.text()) {
+                            return Err(invalid("duplicate macro parameter", param.token.span));
+                        }
+                        names.push(param.text().to_string());
+                        rest = &rest[1..];
+                    }
+                    [other, ..] => {
+                        return Err(invalid("expected a parameter name", other.token.span))
+                    }
+                    [] => return Err(invalid("unterminated parameter list", span)),
+                }
+                match rest {
+                    [close, ..] if close.text() == ")" => {
+                        rest = &rest[1..];
+                        break;
+                    }
+                    [comma, ..] if comma.text() == "," && !variadic => rest = &rest[1..],
+                    [other, ..] => return Err(invalid("expected ',' or ')'", other.token.span)),
+                    [] => return Err(invalid("unterminated parameter list", span)),
+                }
+            }
+            params = Some(names);
+        }
+
+        let body = rest.to_vec();
+        if let Some(first) = body.first().filter(|t| t.text() == "##") {
+            return Err(invalid("'##' cannot start a macro body", first.token.span));
+        }
+        if let Some(last) = body.last().filter(|t| t.text() == "##") {
+            return Err(invalid("'##' cannot end a macro body", last.token.span));
+        }
+        if let Some(names) = &params {
+            for pair in body.windows(2) {
+                if pair[0].text() == "#" && !names.iter().any(|name| name == pair[1].text()) {
+                    return Err(invalid(
+                        "'#' is not followed by a macro parameter",
+                        pair[0].token.span,
+                    ));
+                }
+            }
+            if body.last().is_some_and(|t| t.text() == "#") {
+                return Err(invalid(
+                    "'#' is not followed by a macro parameter",
+                    body[body.len() - 1].token.span,
+                ));
+            }
+        }
+
+        self.macros.insert(
+            name,
+            Rc::new(Macro {
+                params,
+                variadic,
+                body,
+                definition: args[0].token.span,
+            }),
+        );
+        Ok(())
+    }
+
+    /// Evaluate the condition of an `#if` or `#elif` directive
+    fn condition(&mut self, args: &[Tok<T>], span: Span) -> Result<bool, PreprocessError> {
+        let mut items = Vec::new();
+        let mut run = Vec::new();
+        let mut index = 0;
+
+        while index < args.len() {
+            if args[index].text() != "defined" {
+                run.push(args[index].clone());
+                index += 1;
+                continue;
+            }
+
+            // `defined NAME` and `defined ( NAME )` are evaluated before macro expansion
+            items.extend(self.expand_to_items(std::mem::take(&mut run))?);
+            let (name, consumed) = match &args[index + 1..] {
+                [open, name, close, ..] if open.text() == "(" && close.text() == ")" => (name, 4),
+                [name, ..] if name.text() != "(" => (name, 2),
+                _ => {
+                    return Err(PreprocessError::InvalidExpression {
+                        message: "expected a macro name after 'defined'".into(),
+                        span: args[index].token.span,
+                    })
+                }
+            };
+            items.push(ExprItem::Value(
+                i64::from(self.is_defined(name.text())),
+                args[index].token.span,
+            ));
+            index += consumed;
+        }
+        items.extend(self.expand_to_items(run)?);
+
+        expr::evaluate(&items, span)
+    }
+
+    /// Macro-expand tokens for use in an expression
+    fn expand_to_items(&mut self, tokens: Vec<Tok<T>>) -> Result<Vec<ExprItem>, PreprocessError> {
+        Ok(self
+            .expand(tokens)?
+            .into_iter()
+            .map(|tok| ExprItem::Text(tok.token.text, tok.token.span))
+            .collect())
+    }
+
+    /// Process an `#include` directive
+    fn include(
+        &mut self,
+        args: &[Tok<T>],
+        span: Span,
+        depth: usize,
+        output: &mut Vec<PpToken<T>>,
+    ) -> Result<(), PreprocessError> {
+        if depth >= MAX_INCLUDE_DEPTH {
+            return Err(PreprocessError::IncludeTooDeep { span });
+        }
+
+        // Computed includes are macro-expanded before being interpreted
+        let (path, system) = match include_path(args) {
+            Some(include) => include,
+            None => include_path(&self.expand(args.to_vec())?)
+                .ok_or(PreprocessError::InvalidInclude { span })?,
+        };
+
+        let includer = self.db.file(span.file).name().to_string();
+        let request = IncludeRequest {
+            path: &path,
+            system,
+            includer: &includer,
+        };
+        let resolved =
+            self.resolver
+                .resolve(&request)
+                .ok_or_else(|| PreprocessError::IncludeNotFound {
+                    path: path.clone(),
+                    span,
+                })?;
+
+        let file = if let Some(&file) = self.loaded.get(&resolved.name) {
+            file
+        } else {
+            let file = self.db.add(resolved.name.clone(), resolved.contents);
+            self.loaded.insert(resolved.name, file);
+            file
+        };
+        if self.once.contains(&file) {
+            return Ok(());
+        }
+        self.include_sites.entry(file).or_insert(span);
+
+        let contents = self.db.file(file).contents().to_string();
+        self.process_file(
+            file,
+            Lexer::from_table(self.table, contents),
+            depth + 1,
+            output,
+        )
+    }
+
+    /// Fully macro-expand a sequence of tokens
+    fn expand(&mut self, input: Vec<Tok<T>>) -> Result<Vec<Tok<T>>, PreprocessError> {
+        let mut input: VecDeque<_> = input.into();
+        let mut output = Vec::new();
+
+        while let Some(tok) = input.pop_front() {
+            if tok.hide.contains(tok.text()) {
+                output.push(tok);
+                continue;
+            }
+            let Some(mac) = self.macros.get(tok.text()).cloned() else {
+                let builtin = self.builtin(&tok);
+                output.push(builtin.unwrap_or(tok));
+                continue;
+            };
+
+            let name: Rc<str> = tok.text().into();
+            let (args, mut hide) = match &mac.params {
+                None => (Vec::new(), tok.hide.clone()),
+                Some(params) => {
+                    // A function-like macro name not followed by `(` isn't an invocation
+                    if input.front().is_none_or(|next| next.text() != "(") {
+                        output.push(tok);
+                        continue;
+                    }
+                    let (args, close) = collect_args(&mut input, &tok, params.len(), mac.variadic)?;
+                    let hide = tok.hide.intersection(&close.hide).cloned().collect();
+                    (args, hide)
+                }
+            };
+            hide.insert(name);
+
+            let expansion = Arc::new(Expansion {
+                name: tok.text().to_string(),
+                call_site: tok.token.span,
+                definition: mac.definition,
+                parent: tok.token.expansion.clone(),
+            });
+            let mut body = self.substitute(&mac, &args, &hide, &expansion)?;
+            if let Some(first) = body.first_mut() {
+                first.space_before = tok.space_before;
+            }
+            for tok in body.into_iter().rev() {
+                input.push_front(tok);
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Expand the built-in macros `__FILE__` and `__LINE__`
+    ///
+    /// Both describe the outermost macro invocation, like [`PpToken::source_span`], so a
+    /// macro whose body uses `__LINE__` gets the line it's used on, not its definition's.
+    fn builtin(&self, tok: &Tok<T>) -> Option<Tok<T>> {
+        let span = tok.token.span;
+        let source = tok.token.source_span();
+        let text = match tok.text() {
+            "__FILE__" => {
+                let name = self.db.file(source.file).name();
+                format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            "__LINE__" => self
+                .db
+                .file(source.file)
+                .line_col(source.start)
+                .line
+                .to_string(),
+            _ => return None,
+        };
+        let mut builtin = self.relex(text, span, tok.token.expansion.clone()).ok()?;
+        builtin.space_before = tok.space_before;
+        Some(builtin)
+    }
+
+    /// Substitute arguments into a macro's replacement list
+    fn substitute(
+        &mut self,
+        mac: &Macro<T>,
+        args: &[Vec<Tok<T>>],
+        hide: &HideSet,
+        expansion: &Arc<Expansion>,
+    ) -> Result<Vec<Tok<T>>, PreprocessError> {
+        let params = mac.params.as_deref().unwrap_or_default();
+        let param_index = |tok: &Tok<T>| params.iter().position(|param| param == tok.text());
+        let from_body = |tok: &Tok<T>| {
+            let mut tok = tok.clone();
+            tok.token.expansion = Some(Arc::clone(expansion));
+            tok
+        };
+
+        let body = &mac.body;
+        let mut output: Vec<Tok<T>> = Vec::new();
+        // Whether `output` ends with an empty argument, which `##` pastes onto as a no-op
+        let mut placemarker = false;
+        let mut index = 0;
+
+        while index < body.len() {
+            let tok = &body[index];
+            let next = body.get(index + 1);
+
+            // `# param`: stringize the unexpanded argument
+            if mac.params.is_some() && tok.text() == "#" {
+                if let Some(param) = next.and_then(param_index) {
+                    let mut stringized = self.stringize(&args[param], tok, expansion)?;
+                    stringized.space_before = tok.space_before;
+                    output.push(stringized);
+                    placemarker = false;
+                    index += 2;
+                    continue;
+                }
+            }
+
+            // `lhs ## rhs`: paste the last output token onto the first token of `rhs`
+            if tok.text() == "##" {
+                let Some(rhs) = next else { break };
+                let rhs_tokens = param_index(rhs)
+                    .map_or_else(|| vec![from_body(rhs)], |param| args[param].clone());
+                index += 2;
+
+                let Some(first) = rhs_tokens.first() else {
+                    // GNU extension: `, ## __VA_ARGS__` drops the comma when no variadic
+                    // arguments are given
+                    if mac.variadic
+                        && rhs.text() == "__VA_ARGS__"
+                        && !placemarker
+                        && output.last().is_some_and(|last| last.text() == ",")
+                    {
+                        output.pop();
+                    }
+                    continue;
+                };
+                // `, ## __VA_ARGS__` keeps the comma when variadic arguments are given
+                let gnu_comma = mac.variadic
+                    && rhs.text() == "__VA_ARGS__"
+                    && output.last().is_some_and(|last| last.text() == ",");
+                if placemarker || gnu_comma {
+                    placemarker = false;
+                    output.extend(rhs_tokens);
+                    continue;
+                }
+                let lhs = output.pop().expect("'##' cannot start a macro body");
+                let pasted = self.paste(&lhs, first, tok, expansion)?;
+                output.push(pasted);
+                output.extend(rhs_tokens.into_iter().skip(1));
+                continue;
+            }
+
+            // A parameter: substitute the argument, expanded unless it's an operand of `##`
+            if let Some(param) = param_index(tok) {
+                let mut arg = if next.is_some_and(|next| next.text() == "##") {
+                    args[param].clone()
+                } else {
+                    self.expand(args[param].clone())?
+                };
+                if let Some(first) = arg.first_mut() {
+                    first.space_before = tok.space_before;
+                }
+                placemarker = arg.is_empty();
+                output.extend(arg);
+                index += 1;
+                continue;
+            }
+
+            output.push(from_body(tok));
+            placemarker = false;
+            index += 1;
+        }
+
+        for tok in &mut output {
+            tok.hide.extend(hide.iter().cloned());
+        }
+        Ok(output)
+    }
+
+    /// Turn a macro argument into a string literal token
+    fn stringize(
+        &self,
+        arg: &[Tok<T>],
+        operator: &Tok<T>,
+        expansion: &Arc<Expansion>,
+    ) -> Result<Tok<T>, PreprocessError> {
+        let mut text = String::from("\"");
+        for (index, tok) in arg.iter().enumerate() {
+            if index > 0 && tok.space_before {
+                text.push(' ');
+            }
+            if tok.text().starts_with(['"', '\'']) {
+                text.push_str(&tok.text().replace('\\', "\\\\").replace('"', "\\\""));
+            } else {
+                text.push_str(tok.text());
+            }
+        }
+        text.push('"');
+        self.relex(text, operator.token.span, Some(Arc::clone(expansion)))
+    }
+
+    /// Paste two tokens together with `##`
+    fn paste(
+        &self,
+        lhs: &Tok<T>,
+        rhs: &Tok<T>,
+        operator: &Tok<T>,
+        expansion: &Arc<Expansion>,
+    ) -> Result<Tok<T>, PreprocessError> {
+        let mut pasted = self.relex(
+            format!("{}{}", lhs.text(), rhs.text()),
+            operator.token.span,
+            Some(Arc::clone(expansion)),
+        )?;
+        pasted.hide = lhs.hide.intersection(&rhs.hide).cloned().collect();
+        pasted.space_before = lhs.space_before;
+        Ok(pasted)
+    }
+
+    /// Lex text that must form exactly one token
+    fn relex(
+        &self,
+        text: String,
+        span: Span,
+        expansion: Option<Arc<Expansion>>,
+    ) -> Result<Tok<T>, PreprocessError> {
+        let mut tokens = Lexer::from_table(self.table, text.as_str())
+            .collect()
+            .unwrap_or_default();
+        match tokens.pop() {
+            Some(token) if tokens.is_empty() && token.text == text => Ok(Tok {
+                token: PpToken {
+                    kind: token.kind,
+                    text,
+                    span,
+                    expansion,
+                },
+                hide: HideSet::new(),
+                space_before: false,
+            }),
+            _ => Err(PreprocessError::InvalidToken { text, span }),
+        }
+    }
+}
+
+/// The arguments of a function-like macro invocation
+type Args<T> = Vec<Vec<Tok<T>>>;
+
+/// Collect the arguments of a function-like macro invocation, starting at its `(`
+///
+/// Returns the arguments and the closing `)`.
+fn collect_args<T>(
+    input: &mut VecDeque<Tok<T>>,
+    name: &Tok<T>,
+    param_count: usize,
+    variadic: bool,
+) -> Result<(Args<T>, Tok<T>), PreprocessError> {
+    input.pop_front();
+    let mut args = vec![Vec::new()];
+    let mut depth = 0_usize;
+
+    let close = loop {
+        let Some(tok) = input.pop_front() else {
+            return Err(PreprocessError::UnterminatedInvocation {
+                name: name.text().to_string(),
+                span: name.token.span,
+            });
+        };
+        match tok.text() {
+            "(" => depth += 1,
+            ")" if depth == 0 => break tok,
+            ")" => depth -= 1,
+            // Commas in variadic arguments belong to `__VA_ARGS__`
+            "," if depth == 0 && !(variadic && args.len() == param_count) => {
+                args.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        args.last_mut()
+            .expect("there is always an argument")
+            .push(tok);
+    };
+
+    // `F()` passes no arguments to a macro without parameters
+    if param_count == 0 && args.len() == 1 && args[0].is_empty() {
+        args.clear();
+    }
+    // Variadic arguments may be omitted entirely
+    if variadic && args.len() + 1 == param_count {
+        args.push(Vec::new());
+    }
+    if args.len() != param_count {
+        return Err(PreprocessError::ArgumentCount {
+            name: name.text().to_string(),
+            expected: param_count,
+            found: args.len(),
+            span: name.token.span,
+        });
+    }
+
+    Ok((args, close))
+}
+
+/// Get the macro name that a directive operates on
+fn macro_name<T>(args: &[Tok<T>], span: Span) -> Result<&str, PreprocessError> {
+    match args.first() {
+        Some(name) if is_identifier(name.text()) => Ok(name.text()),
+        Some(other) => Err(PreprocessError::InvalidDefinition {
+            message: "macro names must be identifiers".into(),
+            span: other.token.span,
+        }),
+        None => Err(PreprocessError::InvalidDefinition {
+            message: "missing macro name".into(),
+            span,
+        }),
+    }
+}
+
+/// Interpret the tokens of an `#include` directive as `"path"` or `<path>`
+fn include_path<T>(args: &[Tok<T>]) -> Option<(String, bool)> {
+    match args {
+        [path]
+            if path.text().len() >= 2
+                && path.text().starts_with('"')
+                && path.text().ends_with('"') =>
+        {
+            Some((path.text()[1..path.text().len() - 1].to_string(), false))
+        }
+        [open, path @ .., close] if open.text() == "<" && close.text() == ">" => {
+            Some((spell(path), true))
+        }
+        _ => None,
+    }
+}
+
+/// Spell out a sequence of tokens, preserving whether they were separated by whitespace
+fn spell<T>(tokens: &[Tok<T>]) -> String {
+    let mut text = String::new();
+    for (index, tok) in tokens.iter().enumerate() {
+        if index > 0 && tok.space_before {
+            text.push(' ');
+        }
+        text.push_str(tok.text());
+    }
+    text
+}
+
+/// Reports whether text is a C identifier
+fn is_identifier(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Reports whether skipped text between tokens contains an unescaped newline outside a
+/// block comment
+///
+/// Comments are replaced by a space before directives are processed, so a block comment
+/// spanning several lines doesn't end a directive.
+fn starts_new_line(gap: &str) -> bool {
+    let mut chars = gap.chars().peekable();
+    let mut in_block_comment = false;
+    // A line comment runs to the newline, even if it contains `/*`
+    let mut in_line_comment = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if !in_block_comment && !in_line_comment && chars.peek() == Some(&'*') => {
+                chars.next();
+                in_block_comment = true;
+            }
+            '/' if !in_block_comment && chars.peek() == Some(&'/') => {
+                chars.next();
+                in_line_comment = true;
+            }
+            '*' if in_block_comment && chars.peek() == Some(&'/') => {
+                chars.next();
+                in_block_comment = false;
+            }
+            '\\' => {
+                // A line continuation
+                chars.next_if_eq(&'\r');
+                chars.next_if_eq(&'\n');
+            }
+            '\n' if !in_block_comment => return true,
+            _ => {}
+        }
+    }
+    false
+}
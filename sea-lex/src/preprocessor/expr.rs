@@ -0,0 +1,263 @@
+//! Evaluation of `#if` and `#elif` expressions
+
+use crate::{PreprocessError, Span};
+
+/// An item in a preprocessor expression, after macro expansion
+#[derive(Debug, Clone)]
+pub(super) enum ExprItem {
+    /// The text of a token
+    Text(String, Span),
+    /// An already-evaluated value (from `defined`)
+    Value(i64, Span),
+}
+
+impl ExprItem {
+    /// Get the location of this item
+    const fn span(&self) -> Span {
+        match self {
+            Self::Text(_, span) | Self::Value(_, span) => *span,
+        }
+    }
+
+    /// Get the text of this item, if it's a token
+    fn text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text, _) => Some(text),
+            Self::Value(..) => None,
+        }
+    }
+}
+
+/// Evaluate a preprocessor expression, reporting whether it's nonzero
+///
+/// Identifiers remaining after macro expansion evaluate to 0.
+pub(super) fn evaluate(items: &[ExprItem], directive: Span) -> Result<bool, PreprocessError> {
+    let mut parser = ExprParser {
+        items,
+        position: 0,
+        directive,
+        evaluated: true,
+    };
+    let value = parser.ternary()?;
+    parser.peek().map_or(Ok(value != 0), |item| {
+        Err(expr_error(item.span(), "unexpected token"))
+    })
+}
+
+/// A precedence-climbing parser that evaluates as it goes
+struct ExprParser<'a> {
+    /// The items being parsed
+    items: &'a [ExprItem],
+    /// The index of the next item
+    position: usize,
+    /// The location of the directive, for errors at the end of the expression
+    directive: Span,
+    /// Whether the operand being parsed is evaluated, rather than skipped by `&&`, `||`,
+    /// or `?:`, where dividing by zero isn't an error
+    evaluated: bool,
+}
+
+/// Binary operators, from loosest to tightest binding
+const BINARY_LEVELS: &[&[&str]] = &[
+    &["||"],
+    &["&&"],
+    &["|"],
+    &["^"],
+    &["&"],
+    &["==", "!="],
+    &["<", ">", "<=", ">="],
+    &["<<", ">>"],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+impl ExprParser<'_> {
+    /// Peek at the next item
+    fn peek(&self) -> Option<&ExprItem> {
+        self.items.get(self.position)
+    }
+
+    /// Consume the next item if it's the given operator
+    fn eat(&mut self, operator: &str) -> bool {
+        let found = self.peek().and_then(ExprItem::text) == Some(operator);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    /// Get the location of the next item, or of the directive at the end of input
+    fn here(&self) -> Span {
+        self.peek().map_or(self.directive, ExprItem::span)
+    }
+
+    /// Parse an operand, without evaluating it if `skipped`
+    fn operand(
+        &mut self,
+        skipped: bool,
+        parse: impl FnOnce(&mut Self) -> Result<i64, PreprocessError>,
+    ) -> Result<i64, PreprocessError> {
+        let evaluated = self.evaluated;
+        self.evaluated = evaluated && !skipped;
+        let value = parse(self);
+        self.evaluated = evaluated;
+        value
+    }
+
+    /// Parse `cond ? a : b`, evaluating only the branch the condition selects
+    fn ternary(&mut self) -> Result<i64, PreprocessError> {
+        let condition = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let if_true = self.operand(condition == 0, Self::ternary)?;
+        if !self.eat(":") {
+            return Err(expr_error(self.here(), "expected ':'"));
+        }
+        let if_false = self.operand(condition != 0, Self::ternary)?;
+        Ok(if condition != 0 { if_true } else { if_false })
+    }
+
+    /// Parse binary operators at `level` and tighter
+    ///
+    /// The right operand of `&&` and `||` isn't evaluated when the left one decides the
+    /// result.
+    fn binary(&mut self, level: usize) -> Result<i64, PreprocessError> {
+        let Some(operators) = BINARY_LEVELS.get(level) else {
+            return self.unary();
+        };
+
+        let mut lhs = self.binary(level + 1)?;
+        loop {
+            let span = self.here();
+            let Some(&operator) = operators
+                .iter()
+                .find(|&&op| self.peek().and_then(ExprItem::text) == Some(op))
+            else {
+                return Ok(lhs);
+            };
+            self.position += 1;
+            let skipped = match operator {
+                "||" => lhs != 0,
+                "&&" => lhs == 0,
+                _ => false,
+            };
+            let rhs = self.operand(skipped, |parser| parser.binary(level + 1))?;
+            lhs = match operator {
+                "||" => i64::from(lhs != 0 || rhs != 0),
+                "&&" => i64::from(lhs != 0 && rhs != 0),
+                "|" => lhs | rhs,
+                "^" => lhs ^ rhs,
+                "&" => lhs & rhs,
+                "==" => i64::from(lhs == rhs),
+                "!=" => i64::from(lhs != rhs),
+                "<" => i64::from(lhs < rhs),
+                ">" => i64::from(lhs > rhs),
+                "<=" => i64::from(lhs <= rhs),
+                ">=" => i64::from(lhs >= rhs),
+                "<<" => lhs.wrapping_shl(u32::try_from(rhs).unwrap_or(u32::MAX)),
+                ">>" => lhs.wrapping_shr(u32::try_from(rhs).unwrap_or(u32::MAX)),
+                "+" => lhs.wrapping_add(rhs),
+                "-" => lhs.wrapping_sub(rhs),
+                "*" => lhs.wrapping_mul(rhs),
+                "/" | "%" if rhs == 0 && self.evaluated => {
+                    return Err(expr_error(span, "division by zero"));
+                }
+                "/" | "%" if rhs == 0 => 0,
+                "/" => lhs.wrapping_div(rhs),
+                "%" => lhs.wrapping_rem(rhs),
+                _ => unreachable!("every binary operator is handled"),
+            };
+        }
+    }
+
+    /// Parse unary operators and primary expressions
+    fn unary(&mut self) -> Result<i64, PreprocessError> {
+        let span = self.here();
+        if self.eat("+") {
+            return self.unary();
+        }
+        if self.eat("-") {
+            return self.unary().map(i64::wrapping_neg);
+        }
+        if self.eat("~") {
+            return self.unary().map(|value| !value);
+        }
+        if self.eat("!") {
+            return self.unary().map(|value| i64::from(value == 0));
+        }
+        if self.eat("(") {
+            let value = self.ternary()?;
+            if !self.eat(")") {
+                return Err(expr_error(self.here(), "expected ')'"));
+            }
+            return Ok(value);
+        }
+
+        let item = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| expr_error(span, "expected an expression"))?;
+        self.position += 1;
+        match item {
+            ExprItem::Value(value, _) => Ok(value),
+            ExprItem::Text(text, span) => {
+                if text.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+                    // Identifiers that aren't macros evaluate to 0
+                    Ok(0)
+                } else if text.starts_with(|c: char| c.is_ascii_digit()) {
+                    parse_integer(&text).ok_or_else(|| expr_error(span, "invalid integer"))
+                } else if text.starts_with('\'') {
+                    parse_char(&text).ok_or_else(|| expr_error(span, "invalid character"))
+                } else {
+                    Err(expr_error(span, "expected an expression"))
+                }
+            }
+        }
+    }
+}
+
+/// Build an expression error
+fn expr_error(span: Span, message: &str) -> PreprocessError {
+    PreprocessError::InvalidExpression {
+        message: message.into(),
+        span,
+    }
+}
+
+/// Parse a C integer literal, ignoring any suffix
+fn parse_integer(text: &str) -> Option<i64> {
+    let digits = text.trim_end_matches(['u', 'U', 'l', 'L']);
+    let lower = digits.to_ascii_lowercase();
+    let (digits, radix) = if lower.starts_with("0x") {
+        (&digits[2..], 16)
+    } else if lower.starts_with("0b") {
+        (&digits[2..], 2)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        (&digits[1..], 8)
+    } else {
+        (digits, 10)
+    };
+    // Values are computed with unsigned wraparound, as in `uintmax_t`
+    u64::from_str_radix(digits, radix)
+        .ok()
+        .map(u64::cast_signed)
+}
+
+/// Parse a C character literal
+fn parse_char(text: &str) -> Option<i64> {
+    let inner = text.strip_prefix('\'')?.strip_suffix('\'')?;
+    let mut chars = inner.chars();
+    let value = match chars.next()? {
+        '\\' => match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            c @ ('\\' | '\'' | '"' | '?') => c,
+            _ => return None,
+        },
+        c => c,
+    };
+    chars.next().is_none().then(|| i64::from(u32::from(value)))
+}
@@ -0,0 +1,129 @@
+//! Include file resolution for the preprocessor
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A request to resolve an `#include` directive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncludeRequest<'a> {
+    /// The path as written in the directive
+    pub path: &'a str,
+    /// Whether the path was written as `<path>` (as opposed to `"path"`)
+    pub system: bool,
+    /// The name of the file containing the directive
+    pub includer: &'a str,
+}
+
+/// A file found by an [`IncludeResolver`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedInclude {
+    /// The canonical name of the file; files with the same name are only loaded once
+    pub name: String,
+    /// The contents of the file
+    pub contents: String,
+}
+
+/// Finds the files named by `#include` directives
+pub trait IncludeResolver {
+    /// Resolve an include request, returning `None` if the file can't be found
+    fn resolve(&mut self, request: &IncludeRequest<'_>) -> Option<ResolvedInclude>;
+}
+
+/// Implement [`IncludeResolver`] for closures
+impl<F> IncludeResolver for F
+where
+    F: FnMut(&IncludeRequest<'_>) -> Option<ResolvedInclude>,
+{
+    fn resolve(&mut self, request: &IncludeRequest<'_>) -> Option<ResolvedInclude> {
+        self(request)
+    }
+}
+
+/// Resolves includes from an in-memory set of files
+///
+/// Quoted includes are first looked up relative to the including file's directory,
+/// then by their path as written; system includes are only looked up as written.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryResolver {
+    /// The files, by name
+    files: HashMap<String, String>,
+}
+
+impl MemoryResolver {
+    /// Create a resolver with no files
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file to the resolver
+    #[must_use]
+    pub fn with_file(mut self, name: impl Into<String>, contents: impl Into<String>) -> Self {
+        self.files.insert(name.into(), contents.into());
+        self
+    }
+
+    /// Add a file to the resolver
+    pub fn insert(&mut self, name: impl Into<String>, contents: impl Into<String>) {
+        self.files.insert(name.into(), contents.into());
+    }
+}
+
+impl IncludeResolver for MemoryResolver {
+    fn resolve(&mut self, request: &IncludeRequest<'_>) -> Option<ResolvedInclude> {
+        let relative = (!request.system)
+            .then(|| Path::new(request.includer).parent())
+            .flatten()
+            .map(|dir| dir.join(request.path).to_string_lossy().into_owned());
+
+        relative
+            .into_iter()
+            .chain(std::iter::once(request.path.to_string()))
+            .find_map(|name| {
+                self.files.get(&name).map(|contents| ResolvedInclude {
+                    contents: contents.clone(),
+                    name,
+                })
+            })
+    }
+}
+
+/// Resolves includes from the file system
+///
+/// Quoted includes are first looked up relative to the including file's directory;
+/// then both kinds of include are looked up in each include directory in order.
+#[derive(Debug, Clone, Default)]
+pub struct FsResolver {
+    /// The directories to search, in order
+    include_dirs: Vec<PathBuf>,
+}
+
+impl FsResolver {
+    /// Create a resolver that searches the given directories
+    pub fn new(include_dirs: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            include_dirs: include_dirs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl IncludeResolver for FsResolver {
+    fn resolve(&mut self, request: &IncludeRequest<'_>) -> Option<ResolvedInclude> {
+        let relative = (!request.system)
+            .then(|| Path::new(request.includer).parent())
+            .flatten()
+            .map(Path::to_path_buf);
+
+        relative
+            .iter()
+            .chain(&self.include_dirs)
+            .map(|dir| dir.join(request.path))
+            .find_map(|path| {
+                let contents = std::fs::read_to_string(&path).ok()?;
+                Some(ResolvedInclude {
+                    name: path.to_string_lossy().into_owned(),
+                    contents,
+                })
+            })
+    }
+}
@@ -0,0 +1,279 @@
+//! Source files, spans, and line tables shared by all SeaFlow crates
+
+use crate::TokenInfo;
+use std::fmt;
+
+/// An identifier for a file in a [`SourceDb`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(u32);
+
+/// A range of bytes within a single source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    /// The file that contains this span
+    pub file: FileId,
+    /// The start position in the file (in bytes)
+    pub start: usize,
+    /// The end position in the file (in bytes, exclusive)
+    pub end: usize,
+}
+
+/// A 1-based line and column position in a source file
+///
+/// Columns count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    /// The line number, starting at 1
+    pub line: usize,
+    /// The column number, starting at 1
+    pub column: usize,
+}
+
+/// A single source file
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// The name of the file (usually its path)
+    name: String,
+    /// The contents of the file
+    contents: String,
+    /// The byte offset at which each line starts
+    line_starts: Vec<usize>,
+}
+
+/// A database of all source files in a compilation
+#[derive(Debug, Clone, Default)]
+pub struct SourceDb {
+    /// The files, indexed by [`FileId`]
+    files: Vec<SourceFile>,
+}
+
+impl FileId {
+    /// Get the index of this file in its [`SourceDb`]
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Span {
+    /// Create a new span
+    #[must_use]
+    pub const fn new(file: FileId, start: usize, end: usize) -> Self {
+        Self { file, start, end }
+    }
+
+    /// Get the length of this span in bytes
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Reports whether this span is empty
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Get the smallest span covering both this span and `other`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the spans are in different files
+    #[must_use]
+    pub fn to(self, other: Self) -> Self {
+        assert_eq!(self.file, other.file, "cannot join spans from different files");
+        Self {
+            file: self.file,
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Reports whether this span contains `other`
+    #[must_use]
+    pub fn contains(&self, other: Self) -> bool {
+        self.file == other.file && self.start <= other.start && other.end <= self.end
+    }
+}
+
+impl<T> TokenInfo<T> {
+    /// Get this token's span, given the file it was lexed from
+    #[must_use]
+    pub const fn span(&self, file: FileId) -> Span {
+        Span::new(file, self.start, self.end)
+    }
+}
+
+impl SourceFile {
+    /// Create a new source file, computing its line table
+    fn new(name: String, contents: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(contents.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            name,
+            contents,
+            line_starts,
+        }
+    }
+
+    /// Get the name of this file
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the contents of this file
+    #[must_use]
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+
+    /// Get the number of lines in this file
+    #[must_use]
+    pub const fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Get the line and column of a byte offset in this file
+    ///
+    /// Offsets past the end of the file are clamped to the end.
+    #[must_use]
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let offset = offset.min(self.contents.len());
+        let line_index = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line_index];
+        let column = self
+            .contents
+            .get(line_start..offset)
+            .map_or(offset - line_start, |prefix| prefix.chars().count());
+        LineCol {
+            line: line_index + 1,
+            column: column + 1,
+        }
+    }
+
+    /// Get the byte offset of a 1-based line and column in this file, if it exists
+    #[must_use]
+    pub fn offset(&self, position: LineCol) -> Option<usize> {
+        let line_start = *self.line_starts.get(position.line.checked_sub(1)?)?;
+        let line = self.line(position.line)?;
+        let column = position.column.checked_sub(1)?;
+        if column == line.chars().count() {
+            return Some(line_start + line.len());
+        }
+        line.char_indices()
+            .nth(column)
+            .map(|(index, _)| line_start + index)
+    }
+
+    /// Get the text of a 1-based line, without its line terminator
+    #[must_use]
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.contents.len(), |&next| next - 1);
+        let text = &self.contents[start..end];
+        Some(text.strip_suffix('\r').unwrap_or(text))
+    }
+}
+
+impl SourceDb {
+    /// Create an empty source database
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file to the database, returning its ID
+    ///
+    /// # Panics
+    ///
+    /// Panics if the database already holds `u32::MAX` files
+    pub fn add(&mut self, name: impl Into<String>, contents: impl Into<String>) -> FileId {
+        let id = FileId(u32::try_from(self.files.len()).expect("too many source files"));
+        self.files.push(SourceFile::new(name.into(), contents.into()));
+        id
+    }
+
+    /// Get a file by its ID
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ID doesn't belong to this database
+    #[must_use]
+    pub fn file(&self, file: FileId) -> &SourceFile {
+        &self.files[file.index()]
+    }
+
+    /// Find a file by name
+    #[must_use]
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.iter().find(|(_, file)| file.name == name).map(|(id, _)| id)
+    }
+
+    /// Iterate over every file in the database
+    pub fn iter(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        (0..).map(FileId).zip(&self.files)
+    }
+
+    /// Get the number of files in the database
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Reports whether the database has no files
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Get the text covered by a span
+    ///
+    /// # Panics
+    ///
+    /// Panics if the span is out of bounds for its file
+    #[must_use]
+    pub fn text(&self, span: Span) -> &str {
+        &self.file(span.file).contents[span.start..span.end]
+    }
+
+    /// Get the line and column at which a span starts
+    #[must_use]
+    pub fn line_col(&self, span: Span) -> LineCol {
+        self.file(span.file).line_col(span.start)
+    }
+
+    /// Get a displayable `name:line:column` location for a span
+    #[must_use]
+    pub fn location(&self, span: Span) -> Location<'_> {
+        Location {
+            name: &self.file(span.file).name,
+            position: self.line_col(span),
+        }
+    }
+}
+
+/// A displayable `name:line:column` source location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location<'db> {
+    /// The name of the file
+    pub name: &'db str,
+    /// The line and column in the file
+    pub position: LineCol,
+}
+
+impl fmt::Display for LineCol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl fmt::Display for Location<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.name, self.position)
+    }
+}
@@ -0,0 +1,184 @@
+//! Compiled token tables for sea-lex
+
+use crate::{LexError, TokenInfo};
+use regex::Regex;
+use std::sync::Arc;
+
+/// A compiled set of token matchers and skip patterns
+///
+/// Compiling a table is the expensive part of creating a lexer, since every regex is built.
+/// A table can be shared by any number of lexers, either by building one `'static` table
+/// (as `#[derive(Token)]` does via `MyToken::table()`) and passing it to
+/// [`Lexer::from_table`](crate::Lexer::from_table), or implicitly through
+/// [`Lexer::new`](crate::Lexer::new). Cloning a table is cheap, since the clones share
+/// the compiled patterns.
+pub struct Table<T> {
+    /// The compiled token matchers
+    matchers: Arc<[(TokenMatcher, TokenCreator<T>)]>,
+    /// The compiled skip patterns
+    skip_patterns: Arc<[TokenMatcher]>,
+}
+
+/// A compiled token matcher
+enum TokenMatcher {
+    /// A regular expression matcher
+    RegexMatcher {
+        /// The regex pattern to match
+        pattern: Regex,
+    },
+    /// A literal string matcher
+    LiteralMatcher {
+        /// The literal string to match
+        pattern: String,
+    },
+}
+
+/// A shared parser that builds a token from matched text and its position
+pub type TokenParserFn<T> = Arc<dyn Fn(&str, usize) -> Result<T, LexError> + Send + Sync>;
+
+/// Function to create a token from matched text
+pub enum TokenCreator<T> {
+    /// Create a unit variant (no data)
+    Unit(T),
+    /// Create a variant by calling a parser on the matched text
+    Parser(TokenParserFn<T>),
+    /// Skip this match (don't emit a token)
+    Skip,
+}
+
+impl<T: Clone> Table<T> {
+    /// Compile a new table from the given matchers and skip patterns
+    ///
+    /// # Errors
+    ///
+    /// Returns a `LexError` if any of the provided regex patterns are invalid
+    pub fn new(
+        matchers: Vec<(TokenCreator<T>, &str, bool)>, // bool indicates if regex
+        skip_patterns: Vec<(&str, bool)>,             // bool indicates if regex
+    ) -> Result<Self, LexError> {
+        let matchers = matchers
+            .into_iter()
+            .map(|(creator, pattern, is_regex)| {
+                TokenMatcher::try_new(pattern, is_regex).map(|matcher| (matcher, creator))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let skip_patterns = skip_patterns
+            .into_iter()
+            .map(|(pattern, is_regex)| TokenMatcher::try_new(pattern, is_regex))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            matchers: matchers.into(),
+            skip_patterns: skip_patterns.into(),
+        })
+    }
+
+    /// Lex the next token from `input`, starting at and advancing `position`
+    pub(crate) fn next_token(
+        &self,
+        input: &str,
+        position: &mut usize,
+    ) -> Option<Result<TokenInfo<T>, LexError>> {
+        'retry_skip: loop {
+            if *position >= input.len() {
+                return None;
+            }
+
+            let remaining = &input[*position..];
+
+            // Try skip patterns first
+            for skip_pattern in self.skip_patterns.iter() {
+                if let Some(len) = skip_pattern.try_match(remaining) {
+                    *position += len;
+                    continue 'retry_skip;
+                }
+            }
+            break;
+        }
+
+        let remaining = &input[*position..];
+
+        // Try token matchers
+        for (matcher, creator) in self.matchers.iter() {
+            if let Some(match_len) = matcher.try_match(remaining) {
+                let start = *position;
+                let end = *position + match_len;
+                let text = &input[start..end];
+                *position = end;
+
+                match creator {
+                    TokenCreator::Unit(token) => {
+                        return Some(Ok(TokenInfo::new(token.clone(), text, start, end)));
+                    }
+                    TokenCreator::Parser(parser) => {
+                        return Some(
+                            parser(text, start).map(|token| TokenInfo::new(token, text, start, end)),
+                        );
+                    }
+                    TokenCreator::Skip => {
+                        break; // Continue to next iteration to skip this match
+                    }
+                }
+            }
+        }
+
+        // No pattern matched
+        Some(Err(LexError::UnexpectedChar {
+            position: *position,
+            character: remaining.chars().next().unwrap_or_default(),
+        }))
+    }
+}
+
+impl<T> Clone for Table<T> {
+    fn clone(&self) -> Self {
+        Self {
+            matchers: Arc::clone(&self.matchers),
+            skip_patterns: Arc::clone(&self.skip_patterns),
+        }
+    }
+}
+
+impl TokenMatcher {
+    /// Tries to create a new [`TokenMatcher`] from the given pattern
+    ///
+    /// # Errors
+    ///
+    /// Returns a `LexError` if any of the provided regex patterns are invalid
+    pub fn try_new(pattern: &str, is_regex: bool) -> Result<Self, LexError> {
+        if is_regex {
+            // Anchor the whole pattern, so that every alternative of `a|b` only matches at
+            // the start
+            Regex::new(&format!("^(?:{pattern})"))
+                .map(|pattern| Self::RegexMatcher { pattern })
+                .map_err(|error| LexError::InvalidRegex {
+                    pattern: pattern.into(),
+                    error,
+                })
+        } else {
+            Ok(Self::LiteralMatcher {
+                pattern: pattern.into(),
+            })
+        }
+    }
+
+    /// Reports whether this pattern matches the given text,
+    /// and returns the length of the match if successful
+    pub fn try_match(&self, text: &str) -> Option<usize> {
+        match self {
+            Self::RegexMatcher { pattern } => pattern.find(text).map(|m| m.len()),
+            Self::LiteralMatcher { pattern } => text.starts_with(pattern).then_some(pattern.len()),
+        }
+    }
+}
+
+impl<T: Clone> Clone for TokenCreator<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Unit(token) => Self::Unit(token.clone()),
+            Self::Parser(parser) => Self::Parser(Arc::clone(parser)),
+            Self::Skip => Self::Skip,
+        }
+    }
+}
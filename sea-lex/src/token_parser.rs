@@ -15,6 +15,10 @@ pub trait TokenParser<T> {
 /// Helper trait to distinguish between different return types
 pub trait IntoTokenResult<T> {
     /// Convert the result into a `Result<T, LexError>`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `LexError::TokenParseError` if the value represents a failure
     fn into_token_result(self, position: usize) -> Result<T, LexError>;
 }
 
@@ -0,0 +1,368 @@
+//! Differential tests cross-checking `Lexer` against a naive reference implementation
+//!
+//! The reference follows the documented semantics directly:
+//! 1. At each position, skip patterns are tried in declaration order; the first one
+//!    that matches is consumed and the process restarts.
+//! 2. Otherwise, token patterns are tried in declaration order and the first one that
+//!    matches produces a token (first match wins, not longest match).
+//! 3. If nothing matches, lexing stops with an unexpected-character error.
+//!
+//! Regex patterns are drawn from a small subset of regex syntax (alternations of
+//! single characters and character classes with `?`, `*`, and `+`) that the reference
+//! can match with a backtracking matcher using the same leftmost-first, greedy semantics
+//! as `regex`. Pattern sets include patterns that are prefixes of each other, where
+//! first-match and longest-match disagree.
+
+use proptest::prelude::*;
+use sea_lex::{LexError, Lexer, TokenCreator};
+
+/// Characters that patterns are built from
+const PATTERN_ALPHABET: &[char] = &['a', 'b', '1', '+', '.', ' '];
+
+/// Characters that inputs are built from (includes one that no pattern mentions)
+const INPUT_ALPHABET: &[char] = &['a', 'b', '1', '+', '.', ' ', 'z'];
+
+/// A single-character regex atom
+#[derive(Debug, Clone)]
+enum Atom {
+    /// A single character, matched exactly
+    Char(char),
+    /// A character class like `[ab+]`
+    Class(Vec<char>),
+}
+
+/// A regex repetition operator
+#[derive(Debug, Clone, Copy)]
+enum Repeat {
+    /// Exactly once
+    One,
+    /// `?`
+    Optional,
+    /// `*`
+    Star,
+    /// `+`
+    Plus,
+}
+
+/// A pattern as it would appear in a `#[token]` or `#[skip]` attribute
+#[derive(Debug, Clone)]
+enum Pattern {
+    /// A literal string
+    Literal(String),
+    /// A regex made of alternatives, each a sequence of repeated atoms
+    Regex(Vec<Vec<(Atom, Repeat)>>),
+}
+
+impl Atom {
+    /// Reports whether this atom matches the given character
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Char(expected) => *expected == c,
+            Self::Class(chars) => chars.contains(&c),
+        }
+    }
+
+    /// Renders this atom in `regex` syntax
+    fn source(&self) -> String {
+        match self {
+            Self::Char(c) => regex::escape(&c.to_string()),
+            Self::Class(chars) => {
+                let escaped: String = chars.iter().map(|c| regex::escape(&c.to_string())).collect();
+                format!("[{escaped}]")
+            }
+        }
+    }
+}
+
+impl Pattern {
+    /// Renders this pattern as the string passed to `Lexer::new`
+    ///
+    /// Alternatives are wrapped in capture groups. Without them, `regex` factors a shared
+    /// prefix out of `a+ab|a+1?` into `a+(?:ab|1?)`, which is not leftmost-first equivalent
+    /// (it matches `aa` rather than `aab`), so the reference would disagree with `regex`
+    /// rather than with the lexer.
+    fn source(&self) -> String {
+        match self {
+            Self::Literal(literal) => literal.clone(),
+            Self::Regex(alternatives) => alternatives
+                .iter()
+                .map(|pieces| {
+                    let sequence: String = pieces
+                        .iter()
+                        .map(|(atom, repeat)| {
+                            let suffix = match repeat {
+                                Repeat::One => "",
+                                Repeat::Optional => "?",
+                                Repeat::Star => "*",
+                                Repeat::Plus => "+",
+                            };
+                            format!("{}{suffix}", atom.source())
+                        })
+                        .collect();
+                    format!("({sequence})")
+                })
+                .collect::<Vec<_>>()
+                .join("|"),
+        }
+    }
+
+    /// Reports whether this is a regex pattern
+    const fn is_regex(&self) -> bool {
+        matches!(self, Self::Regex(_))
+    }
+
+    /// Returns the length of this pattern's match at the start of `input`, if any
+    fn match_len(&self, input: &[char]) -> Option<usize> {
+        match self {
+            Self::Literal(literal) => {
+                let literal: Vec<char> = literal.chars().collect();
+                input.starts_with(&literal).then_some(literal.len())
+            }
+            // The first alternative that matches wins, even if a later one is longer
+            Self::Regex(alternatives) => alternatives
+                .iter()
+                .find_map(|pieces| match_pieces(pieces, input, 0)),
+        }
+    }
+}
+
+/// Backtracking matcher: returns the end of the first match in leftmost-first order
+fn match_pieces(pieces: &[(Atom, Repeat)], input: &[char], position: usize) -> Option<usize> {
+    let Some(((atom, repeat), rest)) = pieces.split_first() else {
+        return Some(position);
+    };
+
+    let (min, max) = match repeat {
+        Repeat::One => (1, 1),
+        Repeat::Optional => (0, 1),
+        Repeat::Star => (0, usize::MAX),
+        Repeat::Plus => (1, usize::MAX),
+    };
+    let available = input[position..]
+        .iter()
+        .take_while(|&&c| atom.matches(c))
+        .count()
+        .min(max);
+
+    // Greedy: prefer as many repetitions as possible, then back off
+    (min..=available)
+        .rev()
+        .find_map(|count| match_pieces(rest, input, position + count))
+}
+
+/// The reference lexer's output: `(token index, start, end)` triples, or the error position
+/// and character
+type ReferenceResult = Result<Vec<(usize, usize, usize)>, (usize, char)>;
+
+/// Lexes `input` according to the documented semantics
+fn reference_lex(tokens: &[Pattern], skips: &[Pattern], input: &str) -> ReferenceResult {
+    let chars: Vec<char> = input.chars().collect();
+    let mut position = 0;
+    let mut output = Vec::new();
+
+    'outer: while position < chars.len() {
+        for skip in skips {
+            if let Some(len) = skip.match_len(&chars[position..]) {
+                position += len;
+                continue 'outer;
+            }
+        }
+
+        let matched = tokens
+            .iter()
+            .enumerate()
+            .find_map(|(index, token)| token.match_len(&chars[position..]).map(|len| (index, len)));
+        match matched {
+            Some((index, len)) => {
+                output.push((index, position, position + len));
+                position += len;
+            }
+            None => return Err((position, chars[position])),
+        }
+    }
+
+    Ok(output)
+}
+
+/// Lexes `input` with the real `Lexer`, in the same shape as [`reference_lex`]
+fn real_lex(tokens: &[Pattern], skips: &[Pattern], input: &str) -> ReferenceResult {
+    let token_sources: Vec<String> = tokens.iter().map(Pattern::source).collect();
+    let skip_sources: Vec<String> = skips.iter().map(Pattern::source).collect();
+
+    let matchers = tokens
+        .iter()
+        .zip(&token_sources)
+        .enumerate()
+        .map(|(index, (token, source))| (TokenCreator::Unit(index), source.as_str(), token.is_regex()))
+        .collect();
+    let skip_patterns = skips
+        .iter()
+        .zip(&skip_sources)
+        .map(|(skip, source)| (source.as_str(), skip.is_regex()))
+        .collect();
+
+    let lexer = Lexer::new(input, matchers, skip_patterns).expect("generated patterns are valid");
+    match lexer.collect() {
+        Ok(tokens) => {
+            for token in &tokens {
+                assert_eq!(token.text, input[token.start..token.end]);
+            }
+            Ok(tokens.into_iter().map(|t| (t.kind, t.start, t.end)).collect())
+        }
+        Err(LexError::UnexpectedChar {
+            position,
+            character,
+        }) => Err((position, character)),
+        Err(error) => panic!("unexpected lexer error: {error}"),
+    }
+}
+
+/// Generates a character from `alphabet`
+fn char_from(alphabet: &'static [char]) -> impl Strategy<Value = char> {
+    prop::sample::select(alphabet)
+}
+
+/// Generates a regex atom
+fn atom() -> impl Strategy<Value = Atom> {
+    prop_oneof![
+        char_from(PATTERN_ALPHABET).prop_map(Atom::Char),
+        prop::sample::subsequence(PATTERN_ALPHABET, 1..=3).prop_map(Atom::Class),
+    ]
+}
+
+/// Generates a repetition operator
+fn repeat() -> impl Strategy<Value = Repeat> {
+    prop_oneof![
+        Just(Repeat::One),
+        Just(Repeat::Optional),
+        Just(Repeat::Star),
+        Just(Repeat::Plus),
+    ]
+}
+
+/// Generates a sequence of repeated atoms that never matches the empty string
+fn sequence() -> impl Strategy<Value = Vec<(Atom, Repeat)>> {
+    (
+        atom(),
+        prop_oneof![Just(Repeat::One), Just(Repeat::Plus)],
+        prop::collection::vec((atom(), repeat()), 0..=2),
+    )
+        .prop_map(|(first, first_repeat, rest)| {
+            let mut pieces = vec![(first, first_repeat)];
+            pieces.extend(rest);
+            pieces
+        })
+}
+
+/// Generates a literal that isn't empty
+fn literal() -> impl Strategy<Value = String> {
+    prop::collection::vec(char_from(PATTERN_ALPHABET), 1..=3)
+        .prop_map(|chars| chars.into_iter().collect())
+}
+
+/// Generates a pattern that never matches the empty string
+fn pattern() -> impl Strategy<Value = Pattern> {
+    prop_oneof![
+        literal().prop_map(Pattern::Literal),
+        prop::collection::vec(sequence(), 1..=3).prop_map(Pattern::Regex),
+    ]
+}
+
+/// Generates two patterns where the first matches a prefix of what the second matches,
+/// so the one declared first wins even when the other match is longer
+fn overlapping() -> impl Strategy<Value = Vec<Pattern>> {
+    prop_oneof![
+        (literal(), literal()).prop_map(|(prefix, rest)| {
+            let longer = format!("{prefix}{rest}");
+            vec![Pattern::Literal(prefix), Pattern::Literal(longer)]
+        }),
+        (sequence(), sequence()).prop_map(|(prefix, rest)| {
+            let longer = prefix.iter().chain(&rest).cloned().collect();
+            vec![Pattern::Regex(vec![prefix]), Pattern::Regex(vec![longer])]
+        }),
+    ]
+}
+
+/// Generates up to `count` token patterns, plus a pair of overlapping ones, in any order
+fn tokens(count: usize) -> impl Strategy<Value = Vec<Pattern>> {
+    (prop::collection::vec(pattern(), 0..count), overlapping())
+        .prop_map(|(mut tokens, overlapping)| {
+            tokens.extend(overlapping);
+            tokens
+        })
+        .prop_shuffle()
+}
+
+/// Generates an input string
+fn input() -> impl Strategy<Value = String> {
+    prop::collection::vec(char_from(INPUT_ALPHABET), 0..=24).prop_map(|chars| chars.into_iter().collect())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2048))]
+
+    /// The lexer agrees with the reference on random pattern sets and inputs
+    #[test]
+    fn test_lexer_matches_reference(
+        tokens in tokens(4),
+        skips in prop::collection::vec(pattern(), 0..=2),
+        input in input(),
+    ) {
+        prop_assert_eq!(real_lex(&tokens, &skips, &input), reference_lex(&tokens, &skips, &input));
+    }
+
+    /// Appending a lowest-priority catch-all token never changes how already-lexable
+    /// inputs are tokenized
+    #[test]
+    fn test_catch_all_only_fills_gaps(
+        tokens in tokens(2),
+        input in input(),
+    ) {
+        let mut with_catch_all = tokens.clone();
+        with_catch_all.push(Pattern::Regex(vec![vec![(
+            Atom::Class(INPUT_ALPHABET.to_vec()),
+            Repeat::One,
+        )]]));
+
+        let lexed = real_lex(&with_catch_all, &[], &input)
+            .expect("the catch-all token matches every input character");
+        prop_assert_eq!(&lexed, &reference_lex(&with_catch_all, &[], &input).unwrap());
+
+        // Every token from the original set keeps its position in the stream
+        let catch_all = tokens.len();
+        if let Ok(without) = real_lex(&tokens, &[], &input) {
+            prop_assert!(lexed.iter().all(|&(kind, _, _)| kind != catch_all));
+            prop_assert_eq!(lexed, without);
+        }
+    }
+}
+
+/// Literal patterns are never interpreted as regex syntax
+#[test]
+fn test_literal_metacharacters_are_not_regex() {
+    let tokens = [
+        Pattern::Literal("a+".into()),
+        Pattern::Regex(vec![vec![(Atom::Char('a'), Repeat::Plus)]]),
+    ];
+    let expected = Ok(vec![(0, 0, 2), (1, 2, 4)]);
+    assert_eq!(real_lex(&tokens, &[], "a+aa"), expected);
+    assert_eq!(reference_lex(&tokens, &[], "a+aa"), expected);
+}
+
+/// The first pattern or alternative that matches wins, not the longest
+#[test]
+fn test_first_match_wins_over_longest_match() {
+    let a = (Atom::Char('a'), Repeat::One);
+    let b = (Atom::Char('b'), Repeat::One);
+    let cases = [
+        vec![Pattern::Literal("a".into()), Pattern::Literal("ab".into())],
+        vec![Pattern::Regex(vec![vec![a.clone()], vec![a, b]])],
+    ];
+    for mut tokens in cases {
+        tokens.push(Pattern::Literal("b".into()));
+        let b = tokens.len() - 1;
+        let expected = Ok(vec![(0, 0, 1), (b, 1, 2)]);
+        assert_eq!(real_lex(&tokens, &[], "ab"), expected);
+        assert_eq!(reference_lex(&tokens, &[], "ab"), expected);
+    }
+}
@@ -0,0 +1,246 @@
+use sea_lex::{Grammar, Pattern, Token, TokenRule};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+#[skip(r"//[^\n]*")]
+enum LangToken {
+    #[token("if")]
+    If,
+
+    #[token(r"\d+", str::parse)]
+    Number(i64),
+
+    #[token(r#""([^"\\]|\\.)*""#, String::from)]
+    String(String),
+
+    #[token(r"[a-zA-Z_]\w*", String::from)]
+    Identifier(String),
+
+    #[token("+")]
+    Plus,
+
+    #[token(",")]
+    Comma,
+
+    #[token("(")]
+    LeftParen,
+}
+
+#[test]
+fn test_grammar_lists_rules_in_order() {
+    let grammar = LangToken::grammar();
+
+    assert_eq!(grammar.name, "LangToken");
+    assert_eq!(
+        grammar.tokens.iter().map(|rule| rule.kind).collect::<Vec<_>>(),
+        ["If", "Number", "String", "Identifier", "Plus", "Comma", "LeftParen"]
+    );
+    assert_eq!(
+        grammar.tokens[1],
+        TokenRule {
+            kind: "Number",
+            pattern: Pattern {
+                source: r"\d+",
+                is_regex: true,
+            },
+            has_value: true,
+        }
+    );
+    assert_eq!(
+        grammar.tokens[5].pattern,
+        Pattern {
+            source: ",",
+            is_regex: false,
+        }
+    );
+    assert!(!grammar.tokens[5].has_value);
+    assert_eq!(
+        grammar.skips,
+        [
+            Pattern {
+                source: r"\s+",
+                is_regex: true,
+            },
+            Pattern {
+                source: r"//[^\n]*",
+                is_regex: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_grammar_matches_lexer() {
+    use LangToken::*;
+
+    let kinds: Vec<_> = LangToken::lexer(r#"if (x + 1, "s" // done"#)
+        .collect()
+        .unwrap()
+        .into_iter()
+        .map(|token| token.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [If, LeftParen, Identifier("x".into()), Plus, Number(1), Comma, String("\"s\"".into())]
+    );
+
+    let grammar = LangToken::grammar();
+    let rule_kinds: Vec<_> = grammar.tokens.iter().map(|rule| rule.kind).collect();
+    for kind in &kinds {
+        let name = format!("{kind:?}");
+        let name = name.split('(').next().unwrap();
+        assert!(rule_kinds.contains(&name), "{name} has no grammar rule");
+    }
+}
+
+#[test]
+fn test_json_export() {
+    let json: Value = serde_json::from_str(&LangToken::grammar().to_json()).unwrap();
+
+    assert_eq!(json["name"], "LangToken");
+    assert_eq!(json["tokens"].as_array().unwrap().len(), 7);
+    assert_eq!(json["tokens"][2]["kind"], "String");
+    assert_eq!(json["tokens"][2]["pattern"], r#""([^"\\]|\\.)*""#);
+    assert_eq!(json["tokens"][2]["regex"], true);
+    assert_eq!(json["tokens"][2]["value"], true);
+    assert_eq!(json["tokens"][4]["regex"], false);
+    assert_eq!(json["skips"][1]["pattern"], r"//[^\n]*");
+}
+
+#[test]
+fn test_textmate_export() {
+    let json: Value =
+        serde_json::from_str(&LangToken::grammar().to_textmate("source.lang")).unwrap();
+
+    assert_eq!(json["scopeName"], "source.lang");
+    assert_eq!(json["name"], "LangToken");
+
+    // Whitespace skips are dropped; the comment skip comes first
+    let patterns = json["patterns"].as_array().unwrap();
+    assert_eq!(patterns.len(), 8);
+    assert_eq!(patterns[0]["name"], "comment.lang");
+    assert_eq!(patterns[0]["match"], r"//[^\n]*");
+
+    let scopes: Vec<_> = patterns[1..].iter().map(|p| p["name"].clone()).collect();
+    assert_eq!(
+        scopes,
+        [
+            "keyword.control.lang",
+            "constant.numeric.lang",
+            "string.quoted.lang",
+            "variable.other.lang",
+            "keyword.operator.lang",
+            "punctuation.lang",
+            "punctuation.lang",
+        ]
+    );
+
+    // Literals are escaped for use as regexes
+    assert_eq!(patterns[5]["match"], r"\+");
+    assert_eq!(patterns[7]["match"], r"\(");
+}
+
+#[test]
+fn test_textmate_custom_scopes() {
+    const GRAMMAR: Grammar = Grammar {
+        name: "Tiny",
+        tokens: &[
+            TokenRule {
+                kind: "Word",
+                pattern: Pattern {
+                    source: r"^[a-z]+",
+                    is_regex: true,
+                },
+                has_value: false,
+            },
+            TokenRule {
+                kind: "Bang",
+                pattern: Pattern {
+                    source: "!",
+                    is_regex: false,
+                },
+                has_value: false,
+            },
+        ],
+        skips: &[],
+    };
+
+    let json: Value = serde_json::from_str(
+        &GRAMMAR.to_textmate_with("source.tiny", |rule| (rule.kind == "Word").then_some("entity.name")),
+    )
+    .unwrap();
+
+    let patterns = json["patterns"].as_array().unwrap();
+    assert_eq!(patterns[0]["name"], "entity.name.tiny");
+    // Anchors are stripped, since `^` means start-of-line in TextMate
+    assert_eq!(patterns[0]["match"], "[a-z]+");
+    assert!(patterns[1].get("name").is_none());
+    assert_eq!(patterns[1]["match"], "!");
+}
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum ScopeToken {
+    #[token(r"print\b")]
+    Print,
+
+    #[token(r"\d+", str::parse)]
+    IntLit(i64),
+
+    #[token(r"[A-Z]\w*", String::from)]
+    Point(String),
+
+    #[token("*")]
+    Pointer,
+
+    #[token(";")]
+    Semicolon,
+
+    #[token("(")]
+    LParen,
+}
+
+#[test]
+fn test_default_scopes_match_whole_words() {
+    let tokens: Vec<_> = ScopeToken::tokenize("print 1 P * ; (")
+        .collect()
+        .expect("unable to tokenize input");
+    let kinds: Vec<_> = tokens.into_iter().map(|token| token.kind).collect();
+    use ScopeToken::*;
+    assert_eq!(
+        kinds,
+        [Print, IntLit(1), Point("P".into()), Pointer, Semicolon, LParen]
+    );
+
+    let scopes: Vec<_> = ScopeToken::grammar()
+        .tokens
+        .iter()
+        .map(|rule| (rule.kind, rule.default_scope()))
+        .collect();
+
+    assert_eq!(
+        scopes,
+        [
+            ("Print", Some("keyword.control")),
+            ("IntLit", Some("constant.numeric")),
+            ("Point", None),
+            ("Pointer", Some("keyword.operator")),
+            ("Semicolon", Some("punctuation")),
+            ("LParen", Some("punctuation")),
+        ]
+    );
+}
+
+#[test]
+fn test_describe_tokens() {
+    use sea_lex::TokenGrammar;
+    use ScopeToken::*;
+    let tokens = [Print, IntLit(1), Point("P".into()), Pointer, Semicolon];
+    let kinds: Vec<_> = tokens.iter().map(TokenGrammar::kind).collect();
+    assert_eq!(kinds, ["Print", "IntLit", "Point", "Pointer", "Semicolon"]);
+
+    // Literal and keyword patterns are described by their text, and others by their kind
+    let described: Vec<_> = tokens.iter().map(TokenGrammar::describe).collect();
+    assert_eq!(described, ["`print`", "IntLit", "Point", "`*`", "`;`"]);
+}
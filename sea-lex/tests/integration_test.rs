@@ -1,4 +1,6 @@
 use sea_lex::Token;
+// An alias, to check that conversions are recognized through `use`
+use std::string::String as Text;
 
 #[derive(Debug, Clone, PartialEq, Token)]
 #[skip(r"\s+")]
@@ -75,3 +77,75 @@ fn test_token_positions() {
     assert_eq!(tokens[2].end, 5);
     assert_eq!(tokens[2].text, "34");
 }
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"[\s,]+")]
+enum ListToken {
+    #[token(r"[a-z]+", |text: &str| usize::max(text.len(), 2))]
+    Word(usize),
+
+    #[token(";")]
+    Semicolon,
+
+    #[token(r"\{[^,}]*\}")]
+    Block,
+}
+
+#[test]
+fn test_patterns_with_commas() {
+    // Commas in patterns and parsers are part of the pattern or parser, not separators
+    let tokens: Vec<_> = ListToken::tokenize("abc, d; {e}")
+        .collect()
+        .expect("unable to tokenize input");
+    let token_kinds: Vec<_> = tokens.iter().map(|t| t.kind.clone()).collect();
+
+    use ListToken::*;
+    assert_eq!(token_kinds, [Word(3), Word(2), Semicolon, Block]);
+}
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum ConversionToken {
+    #[token(r"[a-z]+", std::string::String::from)]
+    Word(String),
+
+    #[token(r"[A-Z]+", Into::into)]
+    Upper(String),
+
+    #[token(r"[0-9]+", Text::from)]
+    Digits(Text),
+
+    #[token(r"#[0-9]+", <String>::from)]
+    Tag(String),
+
+    #[token(r"\$[0-9]+", parsers::from)]
+    Amount(u32),
+}
+
+/// Parsers named like conversions
+mod parsers {
+    /// Parse an amount, skipping its `$`
+    pub fn from(text: &str) -> Result<u32, std::num::ParseIntError> {
+        text[1..].parse()
+    }
+}
+
+#[test]
+fn test_conversion_parsers() {
+    let tokens: Vec<_> = ConversionToken::tokenize("ab CD 12 #3 $45")
+        .collect()
+        .expect("unable to tokenize input");
+    let token_kinds: Vec<_> = tokens.iter().map(|t| t.kind.clone()).collect();
+
+    use ConversionToken::*;
+    assert_eq!(
+        token_kinds,
+        [
+            Word("ab".into()),
+            Upper("CD".into()),
+            Digits("12".into()),
+            Tag("#3".into()),
+            Amount(45),
+        ]
+    );
+}
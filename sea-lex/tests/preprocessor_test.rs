@@ -0,0 +1,377 @@
+use sea_lex::{
+    IncludeRequest, MemoryResolver, PpToken, PreprocessError, Preprocessor, ResolvedInclude,
+    SourceDb, Span, Token,
+};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+#[skip(r"\\\r?\n")]
+#[skip(r"//[^\n]*")]
+#[skip(r"/\*([^*]|\*[^/])*\*/")]
+enum CToken {
+    #[token(r"[a-zA-Z_][a-zA-Z0-9_]*")]
+    Identifier,
+    #[token(r"[0-9][0-9a-zA-Z]*")]
+    Number,
+    #[token(r#""([^"\\]|\\.)*""#)]
+    String,
+    #[token(r"'([^'\\]|\\.)'")]
+    Char,
+    #[token("...")]
+    Ellipsis,
+    #[token("##")]
+    HashHash,
+    #[token("#")]
+    Hash,
+    #[token(r"<<|>>|<=|>=|==|!=|&&|\|\||->|\+\+|--")]
+    Operator2,
+    #[token(r"[-+*/%<>=!&|^~?:;,.(){}\[\]]")]
+    Punct,
+}
+
+/// Preprocess `source` as `main.c`, with the given headers available
+fn preprocess_with(
+    source: &str,
+    headers: &[(&str, &str)],
+) -> Result<(Vec<PpToken<CToken>>, SourceDb), PreprocessError> {
+    let mut db = SourceDb::new();
+    let file = db.add("main.c", source);
+    let resolver = headers
+        .iter()
+        .fold(MemoryResolver::new(), |resolver, (name, contents)| {
+            resolver.with_file(*name, *contents)
+        });
+
+    let mut pp = Preprocessor::new(CToken::table().unwrap(), &mut db, resolver);
+    let tokens = pp.preprocess(file)?;
+    Ok((tokens, db))
+}
+
+/// Preprocess `source` and join the resulting token text with spaces
+fn expand(source: &str) -> String {
+    let (tokens, _) = preprocess_with(source, &[]).unwrap();
+    spell(&tokens)
+}
+
+/// Join token text with spaces
+fn spell(tokens: &[PpToken<CToken>]) -> String {
+    tokens
+        .iter()
+        .map(|token| token.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn test_object_like_macros() {
+    assert_eq!(
+        expand("#define N 10\n#define M (N * 2)\nint a[M];"),
+        "int a [ ( 10 * 2 ) ] ;"
+    );
+    assert_eq!(expand("#define EMPTY\nx EMPTY y"), "x y");
+    assert_eq!(expand("#define N 1\n#undef N\nN"), "N");
+}
+
+#[test]
+fn test_function_like_macros() {
+    assert_eq!(
+        expand("#define MAX(a, b) ((a) > (b) ? (a) : (b))\nMAX(x + 1, f(y, z))"),
+        "( ( x + 1 ) > ( f ( y , z ) ) ? ( x + 1 ) : ( f ( y , z ) ) )"
+    );
+    // Without `(`, a function-like macro name is left alone
+    assert_eq!(expand("#define F(x) x\nF + F"), "F + F");
+    // A space before `(` makes the macro object-like
+    assert_eq!(expand("#define G (x) x\nG"), "( x ) x");
+    assert_eq!(expand("#define NONE() 42\nNONE()"), "42");
+    // Invocations may span lines
+    assert_eq!(expand("#define ADD(a, b) a + b\nADD(1,\n2)"), "1 + 2");
+}
+
+#[test]
+fn test_recursion_is_blocked() {
+    assert_eq!(expand("#define foo foo + 1\nfoo"), "foo + 1");
+    assert_eq!(expand("#define a b\n#define b a\na b"), "a b");
+    // The classic example from the C standard
+    assert_eq!(
+        expand("#define f(a) a*g\n#define g(a) f(a)\nf(2)(9)"),
+        "2 * 9 * g"
+    );
+}
+
+#[test]
+fn test_arguments_are_expanded_before_substitution() {
+    assert_eq!(
+        expand("#define ONE 1\n#define ID(x) x\n#define STR(x) #x\nID(ONE) STR(ONE)"),
+        "1 \"ONE\""
+    );
+    assert_eq!(
+        expand("#define STR(x) #x\n#define XSTR(x) STR(x)\n#define V 3\nXSTR(V)"),
+        "\"3\""
+    );
+}
+
+#[test]
+fn test_stringizing() {
+    assert_eq!(
+        expand(
+            r#"#define S(x) #x
+S(a  +   b) S( "q\n" ) S('"')"#
+        ),
+        r#""a + b" "\"q\\n\"" "'\"'""#
+    );
+}
+
+#[test]
+fn test_token_pasting() {
+    assert_eq!(
+        expand("#define CAT(a, b) a ## b\nCAT(foo, bar) CAT(x, 1) CAT(, y) CAT(z, )"),
+        "foobar x1 y z"
+    );
+    assert_eq!(
+        expand("#define CAT(a, b) a ## b\n#define foobar done\nCAT(foo, bar)"),
+        "done"
+    );
+    // Operands of `##` are not expanded first
+    assert_eq!(
+        expand("#define CAT(a, b) a ## b\n#define X 1\nCAT(X, Y)"),
+        "XY"
+    );
+
+    let error = preprocess_with("#define CAT(a, b) a ## b\nCAT(+, /)", &[]).unwrap_err();
+    assert!(matches!(error, PreprocessError::InvalidToken { text, .. } if text == "+/"));
+}
+
+#[test]
+fn test_variadic_macros() {
+    assert_eq!(
+        expand("#define LOG(fmt, ...) printf(fmt, __VA_ARGS__)\nLOG(\"%d %d\", a, b)"),
+        "printf ( \"%d %d\" , a , b )"
+    );
+    assert_eq!(
+        expand("#define LOG(fmt, ...) printf(fmt , ## __VA_ARGS__)\nLOG(\"hi\") LOG(\"%d\", 1)"),
+        "printf ( \"hi\" ) printf ( \"%d\" , 1 )"
+    );
+    assert_eq!(
+        expand("#define ALL(...) [__VA_ARGS__]\nALL() ALL(1, 2)"),
+        "[ ] [ 1 , 2 ]"
+    );
+}
+
+#[test]
+fn test_argument_count_errors() {
+    let error = preprocess_with("#define F(a, b) a\nF(1)", &[]).unwrap_err();
+    assert!(matches!(
+        error,
+        PreprocessError::ArgumentCount {
+            expected: 2,
+            found: 1,
+            ..
+        }
+    ));
+
+    let error = preprocess_with("#define F(a) a\nF(1", &[]).unwrap_err();
+    assert!(matches!(error, PreprocessError::UnterminatedInvocation { name, .. } if name == "F"));
+}
+
+#[test]
+fn test_conditionals() {
+    let source = "
+#define A 2
+#if A > 1 && defined(A) && !defined B
+yes1
+#else
+no1
+#endif
+#ifdef B
+no2
+#elif A == 2
+yes2
+#elif 1
+no3
+#endif
+#ifndef A
+no4
+#else
+# if 0
+no5
+# elif (A << 2) == 8 ? 1 : 0
+yes3
+# endif
+#endif
+#if 0
+#error not reached
+#bogus directives are fine in skipped groups
+#endif
+";
+    assert_eq!(expand(source), "yes1 yes2 yes3");
+}
+
+#[test]
+fn test_conditional_errors() {
+    assert!(matches!(
+        preprocess_with("#if 1\nx", &[]).unwrap_err(),
+        PreprocessError::UnterminatedConditional { .. }
+    ));
+    assert!(matches!(
+        preprocess_with("#endif", &[]).unwrap_err(),
+        PreprocessError::UnmatchedConditional { directive, .. } if directive == "endif"
+    ));
+    assert!(matches!(
+        preprocess_with("#if 1\n#else\n#else\n#endif", &[]).unwrap_err(),
+        PreprocessError::UnmatchedConditional { directive, .. } if directive == "else"
+    ));
+    assert!(matches!(
+        preprocess_with("#if 1 / 0\n#endif", &[]).unwrap_err(),
+        PreprocessError::InvalidExpression { .. }
+    ));
+    assert!(matches!(
+        preprocess_with("#error stop here", &[]).unwrap_err(),
+        PreprocessError::ErrorDirective { message, .. } if message == "stop here"
+    ));
+}
+
+#[test]
+fn test_short_circuit_evaluation() {
+    // The operand that isn't evaluated may divide by zero
+    assert_eq!(expand("#if 0 && 1 / 0\nno\n#else\nyes\n#endif"), "yes");
+    assert_eq!(expand("#if 1 || 1 % 0\nyes\n#endif"), "yes");
+    assert_eq!(expand("#if 1 ? 1 : 1 / 0\nyes\n#endif"), "yes");
+    assert_eq!(expand("#if 0 ? 1 / 0 : 1\nyes\n#endif"), "yes");
+    assert_eq!(expand("#if 0 && (1 || 1 / 0)\nno\n#endif"), "");
+
+    // The operand that is evaluated may not
+    assert!(matches!(
+        preprocess_with("#if 1 && 1 / 0\n#endif", &[]).unwrap_err(),
+        PreprocessError::InvalidExpression { .. }
+    ));
+    assert!(matches!(
+        preprocess_with("#if 0 ? 1 : 1 / 0\n#endif", &[]).unwrap_err(),
+        PreprocessError::InvalidExpression { .. }
+    ));
+}
+
+#[test]
+fn test_line_continuations() {
+    assert_eq!(expand("#define LONG 1 + \\\n 2\nLONG"), "1 + 2");
+}
+
+#[test]
+fn test_comments_in_directives() {
+    // A comment is a single space, even if it spans several lines
+    assert_eq!(expand("#define X 1 /* multi\nline */ + 2\nX"), "1 + 2");
+    assert_eq!(expand("#if 0 /* a\nb */ || 1\nyes\n#endif"), "yes");
+    // But a line comment still ends at its newline
+    assert_eq!(expand("#define Y 3 // not /* a block\nY"), "3");
+}
+
+#[test]
+fn test_includes() {
+    let headers = [
+        ("include/config.h", "#pragma once\n#define SIZE 4\n"),
+        (
+            "include/types.h",
+            "#include \"config.h\"\ntypedef int arr[SIZE];\n",
+        ),
+        ("stdio.h", "int printf();\n"),
+    ];
+    let source =
+        "#include \"include/types.h\"\n#include \"include/config.h\"\n#include <stdio.h>\narr x;";
+    let (tokens, db) = preprocess_with(source, &headers).unwrap();
+
+    assert_eq!(
+        spell(&tokens),
+        "typedef int arr [ 4 ] ; int printf ( ) ; arr x ;"
+    );
+
+    // Tokens keep the file they came from
+    let types = db.find("include/types.h").unwrap();
+    assert_eq!(tokens[0].span.file, types);
+    assert_eq!(
+        db.location(tokens[0].span).to_string(),
+        "include/types.h:2:1"
+    );
+    assert_eq!(
+        db.location(tokens.last().unwrap().span).to_string(),
+        "main.c:4:6"
+    );
+
+    let error = preprocess_with("#include \"missing.h\"", &[]).unwrap_err();
+    assert!(matches!(error, PreprocessError::IncludeNotFound { path, .. } if path == "missing.h"));
+
+    let error =
+        preprocess_with("#include \"self.h\"", &[("self.h", "#include \"self.h\"")]).unwrap_err();
+    assert!(matches!(error, PreprocessError::IncludeTooDeep { .. }));
+}
+
+#[test]
+fn test_custom_resolver() {
+    let mut db = SourceDb::new();
+    let file = db.add("main.c", "#include <generated>\nVALUE");
+    let resolver = |request: &IncludeRequest<'_>| {
+        (request.system && request.path == "generated").then(|| ResolvedInclude {
+            name: "<generated>".into(),
+            contents: "#define VALUE 7".into(),
+        })
+    };
+
+    let mut pp = Preprocessor::new(CToken::table().unwrap(), &mut db, resolver);
+    pp.define("DEBUG").unwrap();
+    pp.define("SQUARE(x)=((x)*(x))").unwrap();
+    let tokens = pp.preprocess(file).unwrap();
+    assert_eq!(spell(&tokens), "7");
+    assert!(pp.is_defined("DEBUG"));
+    assert!(pp.is_defined("SQUARE"));
+    assert!(pp
+        .include_site(pp.db().find("<generated>").unwrap())
+        .is_some());
+}
+
+#[test]
+fn test_expansion_provenance() {
+    let source = "#define INNER(x) x + 1\n#define OUTER(y) INNER(y) * 2\nint z = OUTER(v);";
+    let (tokens, db) = preprocess_with(source, &[]).unwrap();
+    let texts: Vec<_> = tokens.iter().map(|token| token.text.as_str()).collect();
+    assert_eq!(texts, ["int", "z", "=", "v", "+", "1", "*", "2", ";"]);
+
+    let main = db.find("main.c").unwrap();
+    let at = |text: &str| {
+        let start = source.find(text).unwrap();
+        Span::new(main, start, start + text.len())
+    };
+
+    // `v` is an argument: it keeps its own location and has no expansion of its own
+    assert_eq!(tokens[3].span, at("v"));
+    assert!(tokens[3].expansion.is_none());
+
+    // `+` came from INNER's body, expanded from OUTER's body
+    let plus = &tokens[4];
+    assert_eq!(db.text(plus.span), "+");
+    let chain: Vec<_> = plus.expansions().map(|e| e.name.as_str()).collect();
+    assert_eq!(chain, ["INNER", "OUTER"]);
+    assert_eq!(plus.expansions().next().unwrap().definition, at("INNER"));
+    assert_eq!(plus.source_span().start, at("OUTER(v").start);
+    assert_eq!(db.text(plus.source_span()), "OUTER");
+
+    // `*` came directly from OUTER's body
+    let star = &tokens[6];
+    assert_eq!(star.expansions().count(), 1);
+    assert_eq!(star.source_span().start, source.rfind("OUTER").unwrap());
+
+    // Ordinary tokens have no expansion
+    assert!(tokens[0].expansion.is_none());
+    assert_eq!(tokens[0].source_span(), tokens[0].span);
+}
+
+#[test]
+fn test_builtin_macros() {
+    assert_eq!(expand("__FILE__\n\n__LINE__"), "\"main.c\" 3");
+
+    // Inside a macro, they describe where the macro is used, not where it's defined
+    let header = ("where.h", "\n#define WHERE __FILE__ __LINE__\n");
+    let (tokens, db) = preprocess_with("#include \"where.h\"\n\nWHERE", &[header]).unwrap();
+    assert_eq!(spell(&tokens), "\"main.c\" 3");
+    assert_eq!(
+        db.location(tokens[1].source_span()).to_string(),
+        "main.c:3:1"
+    );
+}
@@ -70,4 +70,35 @@ fn test_literal_pattern_parsing() {
     assert_eq!(tokens.len(), 2);
     assert_eq!(tokens[0].kind, TestToken::Plus);
     assert_eq!(tokens[1].kind, TestToken::Plus);
-}
\ No newline at end of file
+}
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(" ")]
+enum AlternationToken {
+    #[token(r"<<|>>|&&")]
+    Operator,
+
+    #[token(r"[^ ]")]
+    Other,
+}
+
+#[test]
+fn test_alternations_are_anchored() {
+    // Only the first alternative used to be anchored, so `&&` matched later in the
+    // input, and the operator token swallowed the text before it
+    let lexer = AlternationToken::lexer("> 1 && 2");
+    let tokens: Vec<_> = lexer.collect().unwrap();
+    let tokens: Vec<_> = tokens
+        .iter()
+        .map(|token| (&token.kind, token.text.as_str()))
+        .collect();
+
+    assert_eq!(
+        tokens,
+        [
+            (&AlternationToken::Other, ">"),
+            (&AlternationToken::Other, "1"),
+            (&AlternationToken::Operator, "&&"),
+            (&AlternationToken::Other, "2"),
+        ]
+    );
+}
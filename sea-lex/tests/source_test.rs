@@ -0,0 +1,86 @@
+use sea_lex::{LineCol, SourceDb, Span, Token};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum WordToken {
+    #[token(r"[a-zé]+", String::from)]
+    Word(String),
+
+    #[token(";")]
+    Semicolon,
+}
+
+#[test]
+fn test_files_get_distinct_ids() {
+    let mut db = SourceDb::new();
+    let main = db.add("main.c", "int main;");
+    let header = db.add("lib.h", "int lib;");
+
+    assert_ne!(main, header);
+    assert_eq!(db.len(), 2);
+    assert_eq!(db.file(main).name(), "main.c");
+    assert_eq!(db.file(header).contents(), "int lib;");
+    assert_eq!(db.find("lib.h"), Some(header));
+    assert_eq!(db.find("missing.h"), None);
+    assert_eq!(
+        db.iter().map(|(_, file)| file.name()).collect::<Vec<_>>(),
+        ["main.c", "lib.h"]
+    );
+}
+
+#[test]
+fn test_line_table() {
+    let mut db = SourceDb::new();
+    let file = db.add("test", "ab\ncdé\r\n\nlast");
+    let source = db.file(file);
+
+    assert_eq!(source.line_count(), 4);
+    assert_eq!(source.line(1), Some("ab"));
+    assert_eq!(source.line(2), Some("cdé"));
+    assert_eq!(source.line(3), Some(""));
+    assert_eq!(source.line(4), Some("last"));
+    assert_eq!(source.line(5), None);
+    assert_eq!(source.line(0), None);
+
+    assert_eq!(source.line_col(0), LineCol { line: 1, column: 1 });
+    assert_eq!(source.line_col(2), LineCol { line: 1, column: 3 });
+    assert_eq!(source.line_col(3), LineCol { line: 2, column: 1 });
+    // Columns count characters: `é` is two bytes
+    assert_eq!(source.line_col(7), LineCol { line: 2, column: 4 });
+    assert_eq!(source.line_col(10), LineCol { line: 4, column: 1 });
+    assert_eq!(source.line_col(100), LineCol { line: 4, column: 5 });
+
+    for offset in [0, 2, 3, 4, 5, 10, 14] {
+        assert_eq!(source.offset(source.line_col(offset)), Some(offset));
+    }
+    assert_eq!(source.offset(LineCol { line: 1, column: 9 }), None);
+}
+
+#[test]
+fn test_token_spans() {
+    let mut db = SourceDb::new();
+    let file = db.add("words.txt", "alpha;\n  beta gamma;");
+
+    let tokens = WordToken::lexer(db.file(file).contents()).collect().unwrap();
+    let spans: Vec<Span> = tokens.iter().map(|token| token.span(file)).collect();
+
+    assert_eq!(spans[2], Span::new(file, 9, 13));
+    assert_eq!(db.text(spans[2]), "beta");
+    assert_eq!(db.location(spans[2]).to_string(), "words.txt:2:3");
+    assert_eq!(db.location(spans[4]).to_string(), "words.txt:2:13");
+
+    let statement = spans[2].to(spans[4]);
+    assert_eq!(db.text(statement), "beta gamma;");
+    assert!(statement.contains(spans[3]));
+    assert!(!statement.contains(spans[0]));
+    assert_eq!(statement.len(), 11);
+}
+
+#[test]
+#[should_panic(expected = "different files")]
+fn test_spans_from_different_files_do_not_join() {
+    let mut db = SourceDb::new();
+    let a = db.add("a", "x");
+    let b = db.add("b", "x");
+    let _ = Span::new(a, 0, 1).to(Span::new(b, 0, 1));
+}
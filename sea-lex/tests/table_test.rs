@@ -0,0 +1,120 @@
+use sea_lex::{LexError, Lexer, Table, Token, TokenCreator};
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum WordToken {
+    #[token(r"\d+", str::parse)]
+    Number(u32),
+
+    #[token(r"[a-z]+", String::from)]
+    Word(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Token)]
+enum BrokenToken {
+    #[token(r"(unclosed")]
+    Broken,
+}
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum GenericToken<N: Clone + std::str::FromStr>
+where
+    N::Err: std::error::Error + 'static,
+{
+    #[token(r"\d+", str::parse::<N>)]
+    Number(N),
+}
+
+#[test]
+fn test_table_is_compiled_once() {
+    let first = WordToken::table().unwrap();
+    let second = WordToken::table().unwrap();
+    assert!(std::ptr::eq(first, second));
+}
+
+#[test]
+fn test_many_lexers_share_a_table() {
+    for i in 0..1000 {
+        let input = format!("item {i}");
+        let tokens = WordToken::try_lexer(input).unwrap().collect().unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].kind, WordToken::Word("item".into()));
+        assert_eq!(tokens[1].kind, WordToken::Number(i));
+    }
+}
+
+#[test]
+fn test_from_table() {
+    let lexer = Lexer::from_table(WordToken::table().unwrap(), "abc 12");
+    let kinds: Vec<_> = lexer.map(|token| token.unwrap().kind).collect();
+    assert_eq!(kinds, [WordToken::Word("abc".into()), WordToken::Number(12)]);
+}
+
+#[test]
+fn test_manual_static_table() {
+    static TABLE: OnceLock<Table<char>> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        Table::new(
+            vec![(TokenCreator::Unit('x'), "x", false), (TokenCreator::Unit('y'), "y+", true)],
+            vec![(" ", false)],
+        )
+        .unwrap()
+    });
+
+    let kinds: Vec<_> = Lexer::from_table(table, "x yy x")
+        .collect()
+        .unwrap()
+        .into_iter()
+        .map(|token| token.kind)
+        .collect();
+    assert_eq!(kinds, ['x', 'y', 'x']);
+}
+
+#[test]
+fn test_invalid_pattern_is_an_error() {
+    assert!(matches!(
+        BrokenToken::table(),
+        Err(LexError::InvalidRegex { pattern, .. }) if pattern == "(unclosed"
+    ));
+    assert!(matches!(
+        BrokenToken::try_lexer("x"),
+        Err(LexError::InvalidRegex { .. })
+    ));
+}
+
+#[test]
+#[should_panic(expected = "invalid token pattern in BrokenToken")]
+fn test_lexer_panics_on_invalid_pattern() {
+    let _ = BrokenToken::lexer("x");
+}
+
+#[test]
+fn test_generic_token_type() {
+    let tokens = GenericToken::<u8>::try_lexer("1 2 3").unwrap().collect().unwrap();
+    let kinds: Vec<_> = tokens.into_iter().map(|token| token.kind).collect();
+    assert_eq!(
+        kinds,
+        [GenericToken::Number(1), GenericToken::Number(2), GenericToken::Number(3)]
+    );
+
+    assert!(matches!(
+        GenericToken::<u8>::lexer("300").collect(),
+        Err(LexError::TokenParseError { position: 0, .. })
+    ));
+}
+
+#[test]
+fn test_tokens_can_borrow() {
+    // Lexers don't require their tokens to be `'static`
+    let keyword = String::from("let");
+    let table = Table::new(
+        vec![(TokenCreator::Unit(keyword.as_str()), "let", false)],
+        vec![(" ", false)],
+    )
+    .unwrap();
+    let lexer: Lexer<&str> = Lexer::with_table(table, "let let");
+    let kinds: Vec<_> = lexer.map(|token| token.unwrap().kind).collect();
+    assert_eq!(kinds, ["let", "let"]);
+}
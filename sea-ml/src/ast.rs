@@ -0,0 +1,538 @@
+//! The ML abstract syntax tree
+//!
+//! Every node carries the [`Span`] it was parsed from. Syntactic sugar is kept as
+//! written: `let f x = e` is a binding with parameters rather than a lambda, so
+//! inference can report errors against the source.
+
+use sea_lex::Span;
+use sea_parse::{Fold, Visit};
+use std::fmt;
+
+/// A whole source file
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Program {
+    /// The top-level items, in order
+    pub items: Vec<Item>,
+}
+
+/// A top-level item
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub enum Item {
+    /// A group of type declarations: `type t = ... and u = ...`
+    Type(Vec<TypeDecl>),
+    /// A group of global bindings: `let [rec] x = ... and y = ...`
+    Let(LetGroup),
+    /// An expression to evaluate, written `let ... in ...` at the top level
+    Expr(Expr),
+    /// An item that couldn't be parsed
+    Error(Span),
+}
+
+/// A name, with its location
+#[derive(Debug, Clone, PartialEq, Eq, Visit, Fold)]
+pub struct Ident {
+    /// The name
+    pub name: String,
+    /// The location of the name
+    pub span: Span,
+}
+
+/// A type declaration
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct TypeDecl {
+    /// The type parameters, like `'a` (without their quotes)
+    pub params: Vec<Ident>,
+    /// The type's name
+    pub name: Ident,
+    /// The type's definition
+    pub body: TypeBody,
+    /// The location of the declaration
+    pub span: Span,
+}
+
+/// The definition of a declared type
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub enum TypeBody {
+    /// An algebraic data type: `A | B of t`
+    Variant(Vec<Constructor>),
+    /// A record: `{ x : t; y : u }`
+    Record(Vec<FieldDecl>),
+    /// Another name for a type
+    Alias(Type),
+}
+
+/// One constructor of an algebraic data type
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Constructor {
+    /// The constructor's name
+    pub name: Ident,
+    /// The type of the constructor's argument, if it takes one
+    pub arg: Option<Type>,
+    /// The location of the constructor
+    pub span: Span,
+}
+
+/// One field of a record type
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct FieldDecl {
+    /// The field's name
+    pub name: Ident,
+    /// The field's type
+    pub ty: Type,
+    /// The location of the field
+    pub span: Span,
+}
+
+/// A type, as written
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Type {
+    /// The kind of type
+    pub kind: TypeKind,
+    /// The location of the type
+    pub span: Span,
+}
+
+/// The kinds of types
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub enum TypeKind {
+    /// A type variable: `'a`
+    Var(Ident),
+    /// A named type, applied to its arguments: `int`, `'a list`, `('a, 'b) result`
+    Con(Ident, Vec<Type>),
+    /// A tuple type: `t * u`
+    Tuple(Vec<Type>),
+    /// A function type: `t -> u`
+    Function(Box<Type>, Box<Type>),
+}
+
+/// A group of bindings, defined together
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct LetGroup {
+    /// Whether the bindings are `rec`, so they can refer to each other
+    pub recursive: bool,
+    /// The bindings, in order
+    pub bindings: Vec<Binding>,
+    /// The location of the group
+    pub span: Span,
+}
+
+/// One binding: `x = e`, or `f x y = e` for a function
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Binding {
+    /// The bound pattern: the function's name, if there are parameters
+    pub pattern: Pattern,
+    /// The function's parameters, which are empty for a plain binding
+    pub params: Vec<Pattern>,
+    /// The annotated type of the body: `f x : t = e`
+    pub annotation: Option<Type>,
+    /// The bound value
+    pub body: Expr,
+    /// The location of the binding
+    pub span: Span,
+}
+
+/// An expression
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Expr {
+    /// The kind of expression
+    pub kind: ExprKind,
+    /// The location of the expression
+    pub span: Span,
+}
+
+/// The kinds of expressions
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub enum ExprKind {
+    /// An integer literal
+    Int(i64),
+    /// A floating-point literal
+    Float(f64),
+    /// A string literal
+    String(String),
+    /// `true` or `false`
+    Bool(bool),
+    /// `()`
+    Unit,
+    /// A variable
+    Var(Ident),
+    /// A constructor, applied to its argument if it takes one: `None`, `Some x`
+    Constructor(Ident, Option<Box<Expr>>),
+    /// A tuple: `(a, b)`
+    Tuple(Vec<Expr>),
+    /// A list: `[a; b]`
+    List(Vec<Expr>),
+    /// A record: `{ x = a; y = b }`
+    Record(Vec<(Ident, Expr)>),
+    /// A copy of a record with some fields replaced: `{ r with x = a }`
+    RecordUpdate(Box<Expr>, Vec<(Ident, Expr)>),
+    /// A record field: `r.x`
+    Field(Box<Expr>, Ident),
+    /// A function application, of one argument at a time: `f x`
+    App(Box<Expr>, Box<Expr>),
+    /// A lambda: `fun x y -> e`
+    Lambda(Vec<Pattern>, Box<Expr>),
+    /// Local bindings: `let x = a in e`
+    Let(LetGroup, Box<Expr>),
+    /// A conditional, where a missing `else` means `()`
+    If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    /// Pattern matching: `match e with p -> a | q -> b`
+    Match(Box<Expr>, Vec<Arm>),
+    /// A unary operation
+    Unary(UnaryOp, Box<Expr>),
+    /// A binary operation
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// A sequence, evaluating the first expression for its effects: `a; b`
+    Seq(Box<Expr>, Box<Expr>),
+    /// A type annotation: `(e : t)`
+    Annot(Box<Expr>, Type),
+}
+
+/// One arm of a `match`
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Arm {
+    /// The pattern to match
+    pub pattern: Pattern,
+    /// The condition that must also hold: `p when cond -> e`
+    pub guard: Option<Expr>,
+    /// The arm's value
+    pub body: Expr,
+    /// The location of the arm
+    pub span: Span,
+}
+
+/// A pattern
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub struct Pattern {
+    /// The kind of pattern
+    pub kind: PatternKind,
+    /// The location of the pattern
+    pub span: Span,
+}
+
+/// The kinds of patterns
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+pub enum PatternKind {
+    /// `_`
+    Wildcard,
+    /// A variable, which binds the matched value
+    Var(Ident),
+    /// An integer literal
+    Int(i64),
+    /// A string literal
+    String(String),
+    /// `true` or `false`
+    Bool(bool),
+    /// `()`
+    Unit,
+    /// A tuple: `(p, q)`
+    Tuple(Vec<Pattern>),
+    /// A list of a fixed length: `[p; q]`
+    List(Vec<Pattern>),
+    /// A list's head and tail: `p :: q`
+    Cons(Box<Pattern>, Box<Pattern>),
+    /// A constructor, with a pattern for its argument if it takes one
+    Constructor(Ident, Option<Box<Pattern>>),
+    /// Some fields of a record: `{ x = p; y }`, where `y` is short for `y = y`
+    Record(Vec<(Ident, Pattern)>),
+    /// A type annotation: `(p : t)`
+    Annot(Box<Pattern>, Type),
+}
+
+/// Unary operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Visit, Fold)]
+pub enum UnaryOp {
+    /// Integer negation: `-`
+    Neg,
+    /// Floating-point negation: `-.`
+    FNeg,
+}
+
+/// Binary operators
+///
+/// Integer and floating-point arithmetic use different operators, so no operator is
+/// overloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Visit, Fold)]
+pub enum BinaryOp {
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `mod`
+    Mod,
+    /// `*.`
+    FMul,
+    /// `/.`
+    FDiv,
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `+.`
+    FAdd,
+    /// `-.`
+    FSub,
+    /// `::`
+    Cons,
+    /// `^`, string concatenation
+    Concat,
+    /// `=`, structural equality
+    Eq,
+    /// `<>`
+    NotEq,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEq,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEq,
+    /// `&&`
+    And,
+    /// `||`
+    Or,
+}
+
+impl UnaryOp {
+    /// Get the operator's source symbol
+    #[must_use]
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Neg => "-",
+            Self::FNeg => "-.",
+        }
+    }
+}
+
+impl BinaryOp {
+    /// Get the operator's source symbol
+    #[must_use]
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Mod => "mod",
+            Self::FMul => "*.",
+            Self::FDiv => "/.",
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::FAdd => "+.",
+            Self::FSub => "-.",
+            Self::Cons => "::",
+            Self::Concat => "^",
+            Self::Eq => "=",
+            Self::NotEq => "<>",
+            Self::Less => "<",
+            Self::LessEq => "<=",
+            Self::Greater => ">",
+            Self::GreaterEq => ">=",
+            Self::And => "&&",
+            Self::Or => "||",
+        }
+    }
+}
+
+/// Write `items` separated by `separator`
+fn write_list<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    items: &[T],
+    separator: &str,
+) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            f.write_str(separator)?;
+        }
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
+
+/// Write record fields: `x = a; y = b`
+fn write_fields<T: fmt::Display>(f: &mut fmt::Formatter<'_>, fields: &[(Ident, T)]) -> fmt::Result {
+    for (index, (name, value)) in fields.iter().enumerate() {
+        if index > 0 {
+            f.write_str("; ")?;
+        }
+        write!(f, "{} = {value}", name.name)?;
+    }
+    Ok(())
+}
+
+impl Type {
+    /// Write the type, parenthesized if it binds looser than `precedence`
+    ///
+    /// Function types have precedence 0, tuples 1, and everything else 2.
+    fn fmt_precedence(&self, f: &mut fmt::Formatter<'_>, precedence: u8) -> fmt::Result {
+        let own = match self.kind {
+            TypeKind::Function(..) => 0,
+            TypeKind::Tuple(_) => 1,
+            TypeKind::Var(_) | TypeKind::Con(..) => 2,
+        };
+        if own < precedence {
+            f.write_str("(")?;
+        }
+        match &self.kind {
+            TypeKind::Var(name) => write!(f, "'{}", name.name)?,
+            TypeKind::Con(name, args) => {
+                match args.as_slice() {
+                    [] => {}
+                    [arg] => {
+                        arg.fmt_precedence(f, 2)?;
+                        f.write_str(" ")?;
+                    }
+                    args => {
+                        f.write_str("(")?;
+                        write_list(f, args, ", ")?;
+                        f.write_str(") ")?;
+                    }
+                }
+                f.write_str(&name.name)?;
+            }
+            TypeKind::Tuple(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" * ")?;
+                    }
+                    element.fmt_precedence(f, 2)?;
+                }
+            }
+            TypeKind::Function(param, result) => {
+                param.fmt_precedence(f, 1)?;
+                f.write_str(" -> ")?;
+                result.fmt_precedence(f, 0)?;
+            }
+        }
+        if own < precedence {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Types are written with as few parentheses as possible: `('a -> 'b) -> 'a list -> 'b list`
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_precedence(f, 0)
+    }
+}
+
+impl fmt::Display for LetGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.recursive { "let rec " } else { "let " })?;
+        write_list(f, &self.bindings, " and ")
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)?;
+        for param in &self.params {
+            write!(f, " {param}")?;
+        }
+        if let Some(annotation) = &self.annotation {
+            write!(f, " : {annotation}")?;
+        }
+        write!(f, " = {}", self.body)
+    }
+}
+
+/// Expressions are written with every compound expression parenthesized, to show their
+/// structure
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ExprKind::Int(value) => write!(f, "{value}"),
+            ExprKind::Float(value) => write!(f, "{value:?}"),
+            ExprKind::String(value) => write!(f, "{value:?}"),
+            ExprKind::Bool(value) => write!(f, "{value}"),
+            ExprKind::Unit => f.write_str("()"),
+            ExprKind::Var(name) | ExprKind::Constructor(name, None) => f.write_str(&name.name),
+            ExprKind::Constructor(name, Some(arg)) => write!(f, "({} {arg})", name.name),
+            ExprKind::Tuple(elements) => {
+                f.write_str("(")?;
+                write_list(f, elements, ", ")?;
+                f.write_str(")")
+            }
+            ExprKind::List(elements) => {
+                f.write_str("[")?;
+                write_list(f, elements, "; ")?;
+                f.write_str("]")
+            }
+            ExprKind::Record(fields) => {
+                f.write_str("{ ")?;
+                write_fields(f, fields)?;
+                f.write_str(" }")
+            }
+            ExprKind::RecordUpdate(base, fields) => {
+                write!(f, "{{ {base} with ")?;
+                write_fields(f, fields)?;
+                f.write_str(" }")
+            }
+            ExprKind::Field(base, field) => write!(f, "{base}.{}", field.name),
+            ExprKind::App(callee, arg) => write!(f, "({callee} {arg})"),
+            ExprKind::Lambda(params, body) => {
+                f.write_str("(fun ")?;
+                write_list(f, params, " ")?;
+                write!(f, " -> {body})")
+            }
+            ExprKind::Let(group, body) => write!(f, "({group} in {body})"),
+            ExprKind::If(cond, then, None) => write!(f, "(if {cond} then {then})"),
+            ExprKind::If(cond, then, Some(otherwise)) => {
+                write!(f, "(if {cond} then {then} else {otherwise})")
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                write!(f, "(match {scrutinee} with ")?;
+                write_list(f, arms, " | ")?;
+                f.write_str(")")
+            }
+            ExprKind::Unary(op, operand) => write!(f, "({}{operand})", op.symbol()),
+            ExprKind::Binary(op, lhs, rhs) => write!(f, "({lhs} {} {rhs})", op.symbol()),
+            ExprKind::Seq(first, second) => write!(f, "({first}; {second})"),
+            ExprKind::Annot(expr, ty) => write!(f, "({expr} : {ty})"),
+        }
+    }
+}
+
+impl fmt::Display for Arm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " when {guard}")?;
+        }
+        write!(f, " -> {}", self.body)
+    }
+}
+
+/// Patterns are written like expressions, with every compound pattern parenthesized
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PatternKind::Wildcard => f.write_str("_"),
+            PatternKind::Var(name) | PatternKind::Constructor(name, None) => {
+                f.write_str(&name.name)
+            }
+            PatternKind::Int(value) => write!(f, "{value}"),
+            PatternKind::String(value) => write!(f, "{value:?}"),
+            PatternKind::Bool(value) => write!(f, "{value}"),
+            PatternKind::Unit => f.write_str("()"),
+            PatternKind::Tuple(elements) => {
+                f.write_str("(")?;
+                write_list(f, elements, ", ")?;
+                f.write_str(")")
+            }
+            PatternKind::List(elements) => {
+                f.write_str("[")?;
+                write_list(f, elements, "; ")?;
+                f.write_str("]")
+            }
+            PatternKind::Cons(head, tail) => write!(f, "({head} :: {tail})"),
+            PatternKind::Constructor(name, Some(arg)) => write!(f, "({} {arg})", name.name),
+            PatternKind::Record(fields) => {
+                f.write_str("{ ")?;
+                write_fields(f, fields)?;
+                f.write_str(" }")
+            }
+            PatternKind::Annot(pattern, ty) => write!(f, "({pattern} : {ty})"),
+        }
+    }
+}
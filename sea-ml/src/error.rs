@@ -0,0 +1,20 @@
+//! Error types for the ML frontend
+
+use crate::{Namespace, Token};
+use sea_lex::LexError;
+use sea_parse::{ParseError, ResolveError};
+use thiserror::Error;
+
+/// An error in an ML source file
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The source couldn't be lexed
+    #[error(transparent)]
+    Lex(#[from] LexError),
+    /// The source couldn't be parsed
+    #[error(transparent)]
+    Parse(#[from] ParseError<Token>),
+    /// A name couldn't be resolved, or was declared twice
+    #[error(transparent)]
+    Resolve(#[from] ResolveError<Namespace>),
+}
@@ -0,0 +1,57 @@
+//! sea-ml - SeaFlow ML-style functional language frontend
+//!
+//! The language is a small, layout-free dialect of ML in the style of OCaml: `let` and
+//! `let rec` bindings, lambdas, algebraic data types and records, tuples and lists,
+//! pattern matching, and curried application, without modules or objects. [`parse`]
+//! turns a source file into a [`Program`], and [`resolve`] links its names to their
+//! declarations.
+
+#![warn(
+    clippy::all,
+    clippy::cargo,
+    clippy::missing_docs_in_private_items,
+    clippy::nursery,
+    clippy::pedantic,
+    missing_docs,
+    rustdoc::all
+)]
+
+pub mod ast;
+mod error;
+mod parser;
+mod resolve;
+mod token;
+
+pub use ast::Program;
+pub use error::*;
+pub use resolve::*;
+pub use token::*;
+
+use sea_lex::FileId;
+use sea_parse::Parser;
+
+/// Parse the ML source `source` of `file`, reporting every error found
+///
+/// # Errors
+///
+/// Returns every lexing or parsing error, in source order
+pub fn parse(file: FileId, source: &str) -> Result<Program, Vec<Error>> {
+    match parse_recovering(file, source) {
+        (Some(program), errors) if errors.is_empty() => Ok(program),
+        (_, errors) => Err(errors),
+    }
+}
+
+/// Parse the ML source `source` of `file`, recovering from errors
+///
+/// Returns the program, with an [`Item::Error`](ast::Item::Error) for each item that
+/// couldn't be parsed, along with every error found. The program is `None` only if the
+/// source couldn't be lexed.
+pub fn parse_recovering(file: FileId, source: &str) -> (Option<Program>, Vec<Error>) {
+    let tokens = match Token::lexer(source).collect() {
+        Ok(tokens) => tokens,
+        Err(error) => return (None, vec![error.into()]),
+    };
+    let (program, errors) = parser::program().parse_recovering(file, tokens);
+    (program, errors.into_iter().map(Error::from).collect())
+}
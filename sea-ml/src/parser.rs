@@ -0,0 +1,612 @@
+//! The ML parser, built from sea-parse combinators
+
+use crate::ast::{
+    Arm, BinaryOp, Binding, Constructor, Expr, ExprKind, FieldDecl, Ident, Item, LetGroup, Pattern,
+    PatternKind, Program, Type, TypeBody, TypeDecl, TypeKind, UnaryOp,
+};
+use crate::Token;
+use sea_lex::{Span, TokenInfo};
+use sea_parse::{
+    choice, empty, many, optional, recursive, select, sep_by, seq, skip_to, token, Assoc,
+    BoxedParser, Parser, Pratt, Recursive,
+};
+
+/// A boxed ML parser
+type Boxed<O> = BoxedParser<'static, Token, O>;
+
+/// A parser for a whole source file
+pub fn program() -> Boxed<Program> {
+    let expr = expr();
+    many(item(expr).then_ignore(optional(token(Token::DoubleSemi))))
+        .map(|items| Program { items })
+        .boxed()
+}
+
+/// A parser for a lowercase identifier
+fn ident() -> Boxed<Ident> {
+    select(|token: &TokenInfo<Token>| match &token.kind {
+        Token::Ident(name) => Some(name.clone()),
+        _ => None,
+    })
+    .map_with_span(|name, span| Ident { name, span })
+    .labelled("identifier")
+    .boxed()
+}
+
+/// A parser for a capitalized identifier, naming a constructor
+fn upper_ident() -> Boxed<Ident> {
+    select(|token: &TokenInfo<Token>| match &token.kind {
+        Token::UpperIdent(name) => Some(name.clone()),
+        _ => None,
+    })
+    .map_with_span(|name, span| Ident { name, span })
+    .labelled("constructor")
+    .boxed()
+}
+
+/// A parser for a type variable
+fn type_var() -> Boxed<Ident> {
+    select(|token: &TokenInfo<Token>| match &token.kind {
+        Token::TypeVar(name) => Some(name.clone()),
+        _ => None,
+    })
+    .map_with_span(|name, span| Ident { name, span })
+    .labelled("type variable")
+    .boxed()
+}
+
+/// A parser for the span of a single token of the same kind as `kind`
+fn keyword(kind: Token) -> Boxed<Span> {
+    token(kind).map_with_span(|_, span| span).boxed()
+}
+
+/// Build a node from the items of a comma-separated list, where a single item stands
+/// for itself
+fn tuple<T>(mut items: Vec<T>, span: Span, tuple: impl FnOnce(Vec<T>, Span) -> T) -> T {
+    if items.len() == 1 {
+        items.pop().expect("the list has one item")
+    } else {
+        tuple(items, span)
+    }
+}
+
+/// A parser for a type
+fn ty() -> Recursive<'static, Token, Type> {
+    recursive(|ty| {
+        let var = type_var().map(|name| Type {
+            span: name.span,
+            kind: TypeKind::Var(name),
+        });
+        let named = ident().map(|name| Type {
+            span: name.span,
+            kind: TypeKind::Con(name, Vec::new()),
+        });
+        let multi_applied = seq((
+            sep_by(ty.clone(), token(Token::Comma))
+                .at_least(2)
+                .delimited_by(token(Token::LParen), token(Token::RParen)),
+            ident(),
+        ))
+        .map_with_span(|(args, name), span| Type {
+            kind: TypeKind::Con(name, args),
+            span,
+        });
+        let paren = ty
+            .clone()
+            .delimited_by(token(Token::LParen), token(Token::RParen))
+            .map_with_span(|ty, span| Type { span, ..ty });
+        let atom = choice((var, named, multi_applied, paren)).labelled("type");
+
+        // Type constructors are applied postfix: `int list option`
+        let applied = seq((atom, many(ident()))).map(|(arg, names)| {
+            names.into_iter().fold(arg, |arg, name| Type {
+                span: arg.span.to(name.span),
+                kind: TypeKind::Con(name, vec![arg]),
+            })
+        });
+        let product = sep_by(applied, token(Token::Star))
+            .at_least(1)
+            .map_with_span(|elements, span| {
+                tuple(elements, span, |elements, span| Type {
+                    kind: TypeKind::Tuple(elements),
+                    span,
+                })
+            });
+
+        seq((product, optional(token(Token::Arrow).ignore_then(ty)))).map_with_span(
+            |(param, result), span| match result {
+                Some(result) => Type {
+                    kind: TypeKind::Function(Box::new(param), Box::new(result)),
+                    span,
+                },
+                None => param,
+            },
+        )
+    })
+}
+
+/// A parser for a pattern that needs no parentheses as an argument, such as a
+/// function parameter
+fn atom_pattern(pattern: Recursive<'static, Token, Pattern>) -> Boxed<Pattern> {
+    let literal = select(|token: &TokenInfo<Token>| match &token.kind {
+        Token::Underscore => Some(PatternKind::Wildcard),
+        Token::IntLit(value) => Some(PatternKind::Int(*value)),
+        Token::StringLit(value) => Some(PatternKind::String(value.clone())),
+        Token::True => Some(PatternKind::Bool(true)),
+        Token::False => Some(PatternKind::Bool(false)),
+        _ => None,
+    });
+    let negative =
+        token(Token::Minus).ignore_then(select(|token: &TokenInfo<Token>| match token.kind {
+            Token::IntLit(value) => Some(PatternKind::Int(-value)),
+            _ => None,
+        }));
+    let unit = seq((token(Token::LParen), token(Token::RParen))).to(PatternKind::Unit);
+    let var = ident().map(PatternKind::Var);
+    let constructor = upper_ident().map(|name| PatternKind::Constructor(name, None));
+    let simple = choice((literal, negative, unit, var, constructor))
+        .map_with_span(|kind, span| Pattern { kind, span });
+
+    let paren = seq((
+        token(Token::LParen),
+        pattern.clone(),
+        optional(token(Token::Colon).ignore_then(ty())),
+        token(Token::RParen),
+    ))
+    .map_with_span(|(_, pattern, annotation, _), span| match annotation {
+        Some(ty) => Pattern {
+            kind: PatternKind::Annot(Box::new(pattern), ty),
+            span,
+        },
+        None => Pattern { span, ..pattern },
+    });
+    let list = sep_by(pattern.clone(), token(Token::Semi))
+        .allow_trailing()
+        .delimited_by(token(Token::LBracket), token(Token::RBracket))
+        .map_with_span(|elements, span| Pattern {
+            kind: PatternKind::List(elements),
+            span,
+        });
+    let field =
+        seq((ident(), optional(token(Token::Eq).ignore_then(pattern)))).map(|(name, pattern)| {
+            let pattern = pattern.unwrap_or_else(|| Pattern {
+                span: name.span,
+                kind: PatternKind::Var(name.clone()),
+            });
+            (name, pattern)
+        });
+    let record = sep_by(field, token(Token::Semi))
+        .at_least(1)
+        .allow_trailing()
+        .delimited_by(token(Token::LBrace), token(Token::RBrace))
+        .map_with_span(|fields, span| Pattern {
+            kind: PatternKind::Record(fields),
+            span,
+        });
+
+    choice((simple, paren, list, record))
+        .labelled("pattern")
+        .boxed()
+}
+
+/// A parser for a pattern
+fn pattern() -> Recursive<'static, Token, Pattern> {
+    recursive(|pattern| {
+        let atom = atom_pattern(pattern);
+        let constructor =
+            seq((upper_ident(), atom.clone())).map_with_span(|(name, arg), span| Pattern {
+                kind: PatternKind::Constructor(name, Some(Box::new(arg))),
+                span,
+            });
+        let applied = choice((constructor, atom));
+
+        // `::` is right-associative
+        let cons = seq((
+            applied.clone(),
+            many(token(Token::ColonColon).ignore_then(applied)),
+        ))
+        .map(|(head, tails)| {
+            let mut elements = tails;
+            elements.insert(0, head);
+            let last = elements.pop().expect("there is at least one element");
+            elements.into_iter().rfold(last, |tail, head| Pattern {
+                span: head.span.to(tail.span),
+                kind: PatternKind::Cons(Box::new(head), Box::new(tail)),
+            })
+        });
+
+        sep_by(cons, token(Token::Comma))
+            .at_least(1)
+            .map_with_span(|elements, span| {
+                tuple(elements, span, |elements, span| Pattern {
+                    kind: PatternKind::Tuple(elements),
+                    span,
+                })
+            })
+    })
+}
+
+/// A parser for an expression
+fn expr() -> Recursive<'static, Token, Expr> {
+    recursive(|expr| {
+        let operation = recursive(|operation| {
+            binary_operators(Pratt::new(operand(expr.clone(), operation))).labelled("expression")
+        });
+
+        seq((
+            tuple_expr(operation),
+            optional(token(Token::Semi).ignore_then(expr)),
+        ))
+        .map_with_span(|(first, second), span| match second {
+            Some(second) => Expr {
+                kind: ExprKind::Seq(Box::new(first), Box::new(second)),
+                span,
+            },
+            None => first,
+        })
+        .labelled("expression")
+    })
+}
+
+/// Add the binary operators to an operator parser
+///
+/// Precedences follow OCaml: `||` and `&&` bind loosest, then comparisons, `^`, `::`,
+/// and arithmetic.
+fn binary_operators<P: Parser<Token, Output = Expr>>(
+    pratt: Pratt<'static, Token, Expr, P>,
+) -> Pratt<'static, Token, Expr, P> {
+    let operators = [
+        (Token::PipePipe, BinaryOp::Or, 3, Assoc::Right),
+        (Token::AmpAmp, BinaryOp::And, 4, Assoc::Right),
+        (Token::Eq, BinaryOp::Eq, 5, Assoc::Left),
+        (Token::NotEq, BinaryOp::NotEq, 5, Assoc::Left),
+        (Token::Less, BinaryOp::Less, 5, Assoc::Left),
+        (Token::LessEq, BinaryOp::LessEq, 5, Assoc::Left),
+        (Token::Greater, BinaryOp::Greater, 5, Assoc::Left),
+        (Token::GreaterEq, BinaryOp::GreaterEq, 5, Assoc::Left),
+        (Token::Caret, BinaryOp::Concat, 6, Assoc::Right),
+        (Token::ColonColon, BinaryOp::Cons, 7, Assoc::Right),
+        (Token::Plus, BinaryOp::Add, 8, Assoc::Left),
+        (Token::Minus, BinaryOp::Sub, 8, Assoc::Left),
+        (Token::PlusDot, BinaryOp::FAdd, 8, Assoc::Left),
+        (Token::MinusDot, BinaryOp::FSub, 8, Assoc::Left),
+        (Token::Star, BinaryOp::Mul, 9, Assoc::Left),
+        (Token::Slash, BinaryOp::Div, 9, Assoc::Left),
+        (Token::Mod, BinaryOp::Mod, 9, Assoc::Left),
+        (Token::StarDot, BinaryOp::FMul, 9, Assoc::Left),
+        (Token::SlashDot, BinaryOp::FDiv, 9, Assoc::Left),
+    ];
+    operators
+        .into_iter()
+        .fold(pratt, |pratt, (kind, op, precedence, assoc)| {
+            pratt.infix(kind, precedence, assoc, move |lhs, _, rhs, span| Expr {
+                kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+                span,
+            })
+        })
+}
+
+/// A parser for a comma-separated tuple of operations, or a single operation
+fn tuple_expr(operation: Recursive<'static, Token, Expr>) -> Boxed<Expr> {
+    sep_by(operation, token(Token::Comma))
+        .at_least(1)
+        .map_with_span(|elements, span| {
+            tuple(elements, span, |elements, span| Expr {
+                kind: ExprKind::Tuple(elements),
+                span,
+            })
+        })
+        .boxed()
+}
+
+/// A parser for an operand of the binary operators: a negation, an application, or one
+/// of the keyword expressions, which extend as far to the right as possible
+fn operand(
+    expr: Recursive<'static, Token, Expr>,
+    operation: Recursive<'static, Token, Expr>,
+) -> Boxed<Expr> {
+    let pattern = pattern();
+    let params = many(atom_pattern(pattern.clone())).at_least(1);
+
+    let let_expr = seq((
+        let_group(expr.clone(), pattern.clone()),
+        token(Token::In),
+        expr.clone(),
+    ))
+    .map(|(group, _, body)| ExprKind::Let(group, Box::new(body)));
+    let lambda = seq((token(Token::Fun), params, token(Token::Arrow), expr.clone()))
+        .map(|(_, params, _, body)| ExprKind::Lambda(params, Box::new(body)));
+    let if_expr = seq((
+        token(Token::If),
+        expr.clone(),
+        token(Token::Then),
+        operation.clone(),
+        optional(token(Token::Else).ignore_then(operation.clone())),
+    ))
+    .map(|(_, cond, _, then, otherwise)| {
+        ExprKind::If(Box::new(cond), Box::new(then), otherwise.map(Box::new))
+    });
+
+    let arm = seq((
+        pattern,
+        optional(token(Token::When).ignore_then(expr.clone())),
+        token(Token::Arrow),
+        expr.clone(),
+    ))
+    .map_with_span(|(pattern, guard, _, body), span| Arm {
+        pattern,
+        guard,
+        body,
+        span,
+    });
+    let match_expr = seq((
+        token(Token::Match),
+        expr.clone(),
+        token(Token::With),
+        optional(token(Token::Pipe)),
+        sep_by(arm, token(Token::Pipe)).at_least(1),
+    ))
+    .map(|(_, scrutinee, _, _, arms)| ExprKind::Match(Box::new(scrutinee), arms));
+
+    let keyword_expr = choice((let_expr, lambda, if_expr, match_expr))
+        .map_with_span(|kind, span| Expr { kind, span });
+
+    let primary = choice((keyword_expr, application(expr, operation)));
+
+    // Negation binds tighter than any binary operator, but looser than application
+    recursive(|unary| {
+        let op = select(|token: &TokenInfo<Token>| match token.kind {
+            Token::Minus => Some(UnaryOp::Neg),
+            Token::MinusDot => Some(UnaryOp::FNeg),
+            _ => None,
+        });
+        choice((
+            seq((op, unary)).map_with_span(|(op, operand), span| Expr {
+                kind: ExprKind::Unary(op, Box::new(operand)),
+                span,
+            }),
+            primary,
+        ))
+        .labelled("expression")
+    })
+    .boxed()
+}
+
+/// A parser for a function or constructor application, or a single argument
+fn application(
+    expr: Recursive<'static, Token, Expr>,
+    operation: Recursive<'static, Token, Expr>,
+) -> Boxed<Expr> {
+    let argument = argument(expr, operation);
+
+    // A constructor takes exactly one argument: `Some (1, 2)`
+    let constructor =
+        seq((upper_ident(), argument.clone())).map_with_span(|(name, arg), span| Expr {
+            kind: ExprKind::Constructor(name, Some(Box::new(arg))),
+            span,
+        });
+    let curried = seq((argument.clone(), many(argument))).map(|(callee, args)| {
+        args.into_iter().fold(callee, |callee, arg| Expr {
+            span: callee.span.to(arg.span),
+            kind: ExprKind::App(Box::new(callee), Box::new(arg)),
+        })
+    });
+
+    choice((constructor, curried)).boxed()
+}
+
+/// A parser for an expression that needs no parentheses as an argument
+fn argument(
+    expr: Recursive<'static, Token, Expr>,
+    operation: Recursive<'static, Token, Expr>,
+) -> Boxed<Expr> {
+    let literal = select(|token: &TokenInfo<Token>| match &token.kind {
+        Token::IntLit(value) => Some(ExprKind::Int(*value)),
+        Token::FloatLit(value) => Some(ExprKind::Float(*value)),
+        Token::StringLit(value) => Some(ExprKind::String(value.clone())),
+        Token::True => Some(ExprKind::Bool(true)),
+        Token::False => Some(ExprKind::Bool(false)),
+        _ => None,
+    });
+    let unit = seq((token(Token::LParen), token(Token::RParen))).to(ExprKind::Unit);
+    let var = ident().map(ExprKind::Var);
+    let constructor = upper_ident().map(|name| ExprKind::Constructor(name, None));
+    let simple =
+        choice((literal, unit, var, constructor)).map_with_span(|kind, span| Expr { kind, span });
+
+    let paren = seq((
+        token(Token::LParen),
+        expr,
+        optional(token(Token::Colon).ignore_then(ty())),
+        token(Token::RParen),
+    ))
+    .map_with_span(|(_, expr, annotation, _), span| match annotation {
+        Some(ty) => Expr {
+            kind: ExprKind::Annot(Box::new(expr), ty),
+            span,
+        },
+        None => Expr { span, ..expr },
+    });
+
+    // List elements and field values are separated by `;`, so they can't be sequences
+    let element = tuple_expr(operation);
+    let list = sep_by(element.clone(), token(Token::Semi))
+        .allow_trailing()
+        .delimited_by(token(Token::LBracket), token(Token::RBracket))
+        .map(ExprKind::List);
+    let fields = sep_by(
+        seq((ident(), token(Token::Eq), element)).map(|(name, _, value)| (name, value)),
+        token(Token::Semi),
+    )
+    .at_least(1)
+    .allow_trailing();
+    let record = fields
+        .clone()
+        .delimited_by(token(Token::LBrace), token(Token::RBrace))
+        .map(ExprKind::Record);
+    let update = seq((
+        token(Token::LBrace),
+        choice((simple.clone(), paren.clone())),
+        token(Token::With),
+        fields,
+        token(Token::RBrace),
+    ))
+    .map(|(_, base, _, fields, _)| ExprKind::RecordUpdate(Box::new(base), fields));
+    let compound = choice((list, update, record)).map_with_span(|kind, span| Expr { kind, span });
+
+    seq((
+        choice((simple, paren, compound)),
+        many(token(Token::Dot).ignore_then(ident())),
+    ))
+    .map(|(base, fields)| {
+        fields.into_iter().fold(base, |base, field| Expr {
+            span: base.span.to(field.span),
+            kind: ExprKind::Field(Box::new(base), field),
+        })
+    })
+    .boxed()
+}
+
+/// A parser for a group of bindings: `let [rec] x = a and y = b`
+fn let_group(
+    expr: Recursive<'static, Token, Expr>,
+    pattern: Recursive<'static, Token, Pattern>,
+) -> Boxed<LetGroup> {
+    seq((keyword(Token::Let), bindings(expr, pattern)))
+        .map(|(start, (recursive, bindings))| group(start, recursive, bindings))
+        .boxed()
+}
+
+/// Build a group of bindings that starts at the `let` at `start`
+fn group(start: Span, recursive: bool, bindings: Vec<Binding>) -> LetGroup {
+    let end = bindings.last().map_or(start, |binding| binding.span);
+    LetGroup {
+        recursive,
+        bindings,
+        span: start.to(end),
+    }
+}
+
+/// A parser for the bindings after a `let`, with whether they're `rec`
+fn bindings(
+    expr: Recursive<'static, Token, Expr>,
+    pattern: Recursive<'static, Token, Pattern>,
+) -> Boxed<(bool, Vec<Binding>)> {
+    let annotation = || optional(token(Token::Colon).ignore_then(ty()));
+
+    // `f x y = e` binds a function; anything else not starting with a name is a pattern
+    let function = seq((
+        ident(),
+        many(atom_pattern(pattern.clone())),
+        annotation(),
+        token(Token::Eq),
+        expr.clone(),
+    ))
+    .map_with_span(|(name, params, annotation, _, body), span| Binding {
+        pattern: Pattern {
+            span: name.span,
+            kind: PatternKind::Var(name),
+        },
+        params,
+        annotation,
+        body,
+        span,
+    });
+    let destructure = seq((pattern, annotation(), token(Token::Eq), expr)).map_with_span(
+        |(pattern, annotation, _, body), span| Binding {
+            pattern,
+            params: Vec::new(),
+            annotation,
+            body,
+            span,
+        },
+    );
+
+    seq((
+        optional(token(Token::Rec)).map(|rec| rec.is_some()),
+        sep_by(choice((function, destructure)), token(Token::And)).at_least(1),
+    ))
+    .boxed()
+}
+
+/// A parser for a group of type declarations, after the `type`
+fn type_decls() -> Boxed<Vec<TypeDecl>> {
+    let params = choice((
+        type_var().map(|param| vec![param]),
+        sep_by(type_var(), token(Token::Comma))
+            .at_least(1)
+            .delimited_by(token(Token::LParen), token(Token::RParen)),
+        empty(Vec::new()),
+    ));
+
+    let field = seq((ident(), token(Token::Colon), ty()))
+        .map_with_span(|(name, _, ty), span| FieldDecl { name, ty, span });
+    let record = sep_by(field, token(Token::Semi))
+        .at_least(1)
+        .allow_trailing()
+        .delimited_by(token(Token::LBrace), token(Token::RBrace))
+        .map(TypeBody::Record);
+    let constructor = seq((upper_ident(), optional(token(Token::Of).ignore_then(ty()))))
+        .map_with_span(|(name, arg), span| Constructor { name, arg, span });
+    let variant = seq((
+        optional(token(Token::Pipe)),
+        sep_by(constructor, token(Token::Pipe)).at_least(1),
+    ))
+    .map(|(_, constructors)| TypeBody::Variant(constructors));
+    let alias = ty().map(TypeBody::Alias);
+
+    let decl = seq((
+        params,
+        ident(),
+        token(Token::Eq),
+        choice((record, variant, alias)),
+    ))
+    .map_with_span(|(params, name, _, body), span| TypeDecl {
+        params,
+        name,
+        body,
+        span,
+    });
+    sep_by(decl, token(Token::And)).at_least(1).boxed()
+}
+
+/// A parser for a top-level item
+///
+/// An item that can't be parsed is skipped up to the next `let`, `type`, or `;;`,
+/// becoming an [`Item::Error`]. Top-level bindings have no terminator, so skipping
+/// can't tell them from local ones: a local `let ... in` after the error is parsed as a
+/// top-level expression.
+fn item(expr: Recursive<'static, Token, Expr>) -> Boxed<Item> {
+    /// Recover from an error after an item's keyword
+    fn recovering<O: 'static>(
+        parser: impl Parser<Token, Output = O> + 'static,
+    ) -> Boxed<Option<O>> {
+        parser
+            .map(Some)
+            .recover_with(skip_to(|_| None).before([Token::Let, Token::Type, Token::DoubleSemi]))
+            .boxed()
+    }
+
+    let pattern = pattern();
+    let let_item = seq((
+        keyword(Token::Let),
+        recovering(seq((
+            bindings(expr.clone(), pattern),
+            optional(token(Token::In).ignore_then(expr)),
+        ))),
+    ))
+    .map_with_span(|(start, rest), span| match rest {
+        Some(((recursive, bindings), None)) => Item::Let(group(start, recursive, bindings)),
+        Some(((recursive, bindings), Some(body))) => Item::Expr(Expr {
+            kind: ExprKind::Let(group(start, recursive, bindings), Box::new(body)),
+            span,
+        }),
+        None => Item::Error(span),
+    });
+    let type_item = seq((token(Token::Type), recovering(type_decls())))
+        .map_with_span(|(_, decls), span| decls.map_or(Item::Error(span), Item::Type));
+
+    choice((let_item, type_item))
+        .labelled("declaration")
+        .boxed()
+}
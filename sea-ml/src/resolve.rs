@@ -0,0 +1,314 @@
+//! Name resolution for ML
+//!
+//! Each binding group opens a scope that everything after it is nested in, so a later
+//! `let` shadows an earlier one and a plain `let` can't see its own names. A `let rec`
+//! group, or a group of types, can refer to its own names anywhere in the group.
+//! Type variables must be parameters of the type declaring them, except in annotations,
+//! where they stand for any type.
+
+use crate::ast::{
+    Binding, Expr, ExprKind, Ident, Item, LetGroup, Pattern, PatternKind, Program, Type, TypeBody,
+    TypeDecl, TypeKind,
+};
+use crate::Error;
+use sea_lex::Span;
+use sea_parse::{
+    downcast_ref, suggest, ResolveError, ResolveErrorKind, ScopeGraph, ScopeId, ScopeKind, Visit,
+    Visitor,
+};
+use std::fmt;
+
+/// The namespaces of ML names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    /// Variables and functions
+    Value,
+    /// The constructors of algebraic data types
+    Constructor,
+    /// Type names
+    Type,
+    /// The fields of record types
+    Field,
+    /// Type variables, like `'a`
+    TypeVar,
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Value => "value",
+            Self::Constructor => "constructor",
+            Self::Type => "type",
+            Self::Field => "field",
+            Self::TypeVar => "type variable",
+        })
+    }
+}
+
+/// The types every program can use without declaring them
+pub const BUILTIN_TYPES: [&str; 6] = ["int", "float", "bool", "string", "unit", "list"];
+
+/// Resolve every name in `program`
+///
+/// Returns the scope graph, where [`ScopeGraph::resolution`] maps the span of each
+/// resolved [`Ident`] to its declaration, along with every
+/// resolution error in source order. References to [`BUILTIN_TYPES`] resolve to
+/// nothing, unless the program declares a type of the same name.
+pub fn resolve(program: &Program) -> (ScopeGraph<Namespace>, Vec<Error>) {
+    let mut resolver = Resolver {
+        graph: ScopeGraph::new(ScopeKind::Module),
+        errors: Vec::new(),
+    };
+    let mut scope = resolver.graph.root();
+    for item in &program.items {
+        scope = match item {
+            Item::Type(decls) => resolver.type_group(decls, scope),
+            Item::Let(group) => resolver.let_group(group, scope),
+            Item::Expr(expr) => {
+                resolver.expr(expr, scope);
+                scope
+            }
+            Item::Error(_) => scope,
+        };
+    }
+    resolver.errors.sort_by_key(|error| error.span);
+    let errors = resolver.errors.into_iter().map(Error::from).collect();
+    (resolver.graph, errors)
+}
+
+/// The state of name resolution
+struct Resolver {
+    /// The scopes and symbols so far
+    graph: ScopeGraph<Namespace>,
+    /// The errors so far
+    errors: Vec<ResolveError<Namespace>>,
+}
+
+impl Resolver {
+    /// Declare a group of types, returning the scope of the code after it
+    fn type_group(&mut self, decls: &[TypeDecl], scope: ScopeId) -> ScopeId {
+        let group = self.graph.add_scope(scope, ScopeKind::Module);
+        for decl in decls {
+            self.declare(group, Namespace::Type, &decl.name.name, decl.name.span);
+        }
+        for decl in decls {
+            let params = self.graph.add_scope(group, ScopeKind::Block);
+            for param in &decl.params {
+                self.declare(params, Namespace::TypeVar, &param.name, param.span);
+            }
+            match &decl.body {
+                TypeBody::Variant(constructors) => {
+                    for constructor in constructors {
+                        let name = &constructor.name;
+                        self.declare(group, Namespace::Constructor, &name.name, name.span);
+                        self.names(&constructor.arg, params, true);
+                    }
+                }
+                TypeBody::Record(fields) => {
+                    for field in fields {
+                        let name = &field.name;
+                        self.declare(group, Namespace::Field, &name.name, name.span);
+                        self.names(&field.ty, params, true);
+                    }
+                }
+                TypeBody::Alias(ty) => self.names(ty, params, true),
+            }
+        }
+        group
+    }
+
+    /// Declare a group of bindings, returning the scope of the code after it
+    fn let_group(&mut self, group: &LetGroup, scope: ScopeId) -> ScopeId {
+        if group.recursive {
+            let inner = self.graph.add_scope(scope, ScopeKind::Module);
+            for binding in &group.bindings {
+                self.pattern(&binding.pattern, scope, inner);
+            }
+            for binding in &group.bindings {
+                self.binding(binding, inner);
+            }
+            inner
+        } else {
+            for binding in &group.bindings {
+                self.binding(binding, scope);
+            }
+            let inner = self.graph.add_scope(scope, ScopeKind::Block);
+            for binding in &group.bindings {
+                self.pattern(&binding.pattern, scope, inner);
+            }
+            inner
+        }
+    }
+
+    /// Resolve the names in a binding's parameters and body
+    fn binding(&mut self, binding: &Binding, scope: ScopeId) {
+        let params = self.graph.add_scope(scope, ScopeKind::Block);
+        for param in &binding.params {
+            self.pattern(param, scope, params);
+        }
+        self.names(&binding.annotation, params, false);
+        self.expr(&binding.body, params);
+    }
+
+    /// Declare the variables in a pattern in `binds`, resolving its other names in
+    /// `scope`
+    fn pattern(&mut self, pattern: &Pattern, scope: ScopeId, binds: ScopeId) {
+        Names {
+            resolver: self,
+            scope,
+            binds: Some(binds),
+            type_vars: false,
+        }
+        .visit(pattern);
+    }
+
+    /// Resolve the names in an expression
+    fn expr(&mut self, expr: &Expr, scope: ScopeId) {
+        self.names(expr, scope, false);
+    }
+
+    /// Resolve the names in an expression or type, where type variables must be
+    /// declared if `type_vars` is set
+    fn names(&mut self, node: &impl Visit, scope: ScopeId, type_vars: bool) {
+        Names {
+            resolver: self,
+            scope,
+            binds: None,
+            type_vars,
+        }
+        .visit(node);
+    }
+
+    /// Declare a name, recording the error if it's a duplicate
+    fn declare(&mut self, scope: ScopeId, namespace: Namespace, name: &str, span: Span) {
+        if let Err(error) = self.graph.declare(scope, namespace, name, span) {
+            self.errors.push(error);
+        }
+    }
+
+    /// Resolve a name, recording the error if it's unresolved
+    fn resolve(&mut self, scope: ScopeId, namespace: Namespace, ident: &Ident) {
+        let Err(mut error) = self
+            .graph
+            .resolve(scope, namespace, &ident.name, ident.span)
+        else {
+            return;
+        };
+        if namespace == Namespace::Type {
+            if BUILTIN_TYPES.contains(&&*ident.name) {
+                return;
+            }
+            if let ResolveErrorKind::Unresolved { suggestion, .. } = &mut error.kind {
+                let builtin = suggest(&ident.name, BUILTIN_TYPES).map(ToString::to_string);
+                *suggestion = suggestion.take().or(builtin);
+            }
+        }
+        self.errors.push(error);
+    }
+}
+
+/// A visitor resolving the names in expressions, patterns, and types
+struct Names<'a> {
+    /// The resolver to record resolutions and errors in
+    resolver: &'a mut Resolver,
+    /// The scope the names are in
+    scope: ScopeId,
+    /// The scope to declare pattern variables in, when visiting a pattern
+    binds: Option<ScopeId>,
+    /// Whether type variables must be declared
+    type_vars: bool,
+}
+
+impl Names<'_> {
+    /// Resolve the names in an expression, handling the expressions that bind names
+    ///
+    /// Returns whether the expression's children still need to be visited.
+    fn expr(&mut self, expr: &Expr) -> bool {
+        let scope = self.scope;
+        let resolver = &mut *self.resolver;
+        match &expr.kind {
+            ExprKind::Var(name) => resolver.resolve(scope, Namespace::Value, name),
+            ExprKind::Constructor(name, _) => resolver.resolve(scope, Namespace::Constructor, name),
+            ExprKind::Field(_, name) => resolver.resolve(scope, Namespace::Field, name),
+            ExprKind::Record(fields) | ExprKind::RecordUpdate(_, fields) => {
+                for (name, _) in fields {
+                    resolver.resolve(scope, Namespace::Field, name);
+                }
+            }
+            ExprKind::Let(group, body) => {
+                let inner = resolver.let_group(group, scope);
+                resolver.expr(body, inner);
+                return false;
+            }
+            ExprKind::Lambda(params, body) => {
+                let inner = resolver.graph.add_scope(scope, ScopeKind::Block);
+                for param in params {
+                    resolver.pattern(param, scope, inner);
+                }
+                resolver.expr(body, inner);
+                return false;
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                resolver.expr(scrutinee, scope);
+                for arm in arms {
+                    let inner = resolver.graph.add_scope(scope, ScopeKind::Block);
+                    resolver.pattern(&arm.pattern, scope, inner);
+                    if let Some(guard) = &arm.guard {
+                        resolver.expr(guard, inner);
+                    }
+                    resolver.expr(&arm.body, inner);
+                }
+                return false;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Resolve the names in a pattern, declaring its variables
+    fn pattern(&mut self, pattern: &Pattern) {
+        let resolver = &mut *self.resolver;
+        match &pattern.kind {
+            PatternKind::Var(name) => {
+                if let Some(binds) = self.binds {
+                    resolver.declare(binds, Namespace::Value, &name.name, name.span);
+                }
+            }
+            PatternKind::Constructor(name, _) => {
+                resolver.resolve(self.scope, Namespace::Constructor, name);
+            }
+            PatternKind::Record(fields) => {
+                for (name, _) in fields {
+                    resolver.resolve(self.scope, Namespace::Field, name);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Resolve the names in a type
+    fn ty(&mut self, ty: &Type) {
+        match &ty.kind {
+            TypeKind::Con(name, _) => self.resolver.resolve(self.scope, Namespace::Type, name),
+            TypeKind::Var(name) if self.type_vars => {
+                self.resolver.resolve(self.scope, Namespace::TypeVar, name);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visitor for Names<'_> {
+    fn visit<N: Visit>(&mut self, node: &N) {
+        if let Some(expr) = downcast_ref::<Expr>(node) {
+            if !self.expr(expr) {
+                return;
+            }
+        } else if let Some(pattern) = downcast_ref::<Pattern>(node) {
+            self.pattern(pattern);
+        } else if let Some(ty) = downcast_ref::<Type>(node) {
+            self.ty(ty);
+        }
+        node.visit_children(self);
+    }
+}
@@ -0,0 +1,222 @@
+//! ML tokens
+
+use thiserror::Error;
+
+/// An ML token
+///
+/// The language is layout-free: whitespace and `(* comments *)` are skipped, and
+/// constructs are delimited by keywords rather than indentation. Keywords come before
+/// identifiers, and longer operators before their prefixes, since the lexer takes the
+/// first pattern that matches.
+#[derive(Debug, Clone, PartialEq, sea_lex::Token)]
+#[skip(r"\s+")]
+#[skip(r"\(\*([^*]|\*+[^*)])*\*+\)")]
+pub enum Token {
+    /// `let`
+    #[token(r"let\b")]
+    Let,
+    /// `rec`
+    #[token(r"rec\b")]
+    Rec,
+    /// `and`
+    #[token(r"and\b")]
+    And,
+    /// `in`
+    #[token(r"in\b")]
+    In,
+    /// `fun`
+    #[token(r"fun\b")]
+    Fun,
+    /// `match`
+    #[token(r"match\b")]
+    Match,
+    /// `with`
+    #[token(r"with\b")]
+    With,
+    /// `when`
+    #[token(r"when\b")]
+    When,
+    /// `if`
+    #[token(r"if\b")]
+    If,
+    /// `then`
+    #[token(r"then\b")]
+    Then,
+    /// `else`
+    #[token(r"else\b")]
+    Else,
+    /// `type`
+    #[token(r"type\b")]
+    Type,
+    /// `of`
+    #[token(r"of\b")]
+    Of,
+    /// `true`
+    #[token(r"true\b")]
+    True,
+    /// `false`
+    #[token(r"false\b")]
+    False,
+    /// `mod`
+    #[token(r"mod\b")]
+    Mod,
+    /// `_`
+    #[token(r"_\b")]
+    Underscore,
+
+    /// A lowercase identifier, naming a variable, type, or record field
+    #[token(r"[a-z_][a-zA-Z0-9_']*", String::from)]
+    Ident(String),
+    /// A capitalized identifier, naming a constructor
+    #[token(r"[A-Z][a-zA-Z0-9_']*", String::from)]
+    UpperIdent(String),
+    /// A type variable, like `'a` (without the quote)
+    #[token(r"'[a-z_][a-zA-Z0-9_']*", parse_type_var)]
+    TypeVar(String),
+    /// A floating-point literal
+    #[token(r"[0-9]+\.[0-9]*([eE][+-]?[0-9]+)?|[0-9]+[eE][+-]?[0-9]+", str::parse)]
+    FloatLit(f64),
+    /// An integer literal
+    #[token(r"[0-9]+", parse_int)]
+    IntLit(i64),
+    /// A string literal
+    #[token(r#""([^"\\\n]|\\.)*""#, parse_string)]
+    StringLit(String),
+
+    /// `;;`
+    #[token(";;")]
+    DoubleSemi,
+    /// `->`
+    #[token("->")]
+    Arrow,
+    /// `::`
+    #[token("::")]
+    ColonColon,
+    /// `<>`
+    #[token("<>")]
+    NotEq,
+    /// `<=`
+    #[token("<=")]
+    LessEq,
+    /// `>=`
+    #[token(">=")]
+    GreaterEq,
+    /// `&&`
+    #[token("&&")]
+    AmpAmp,
+    /// `||`
+    #[token("||")]
+    PipePipe,
+    /// `+.`
+    #[token("+.")]
+    PlusDot,
+    /// `-.`
+    #[token("-.")]
+    MinusDot,
+    /// `*.`
+    #[token("*.")]
+    StarDot,
+    /// `/.`
+    #[token("/.")]
+    SlashDot,
+    /// `+`
+    #[token("+")]
+    Plus,
+    /// `-`
+    #[token("-")]
+    Minus,
+    /// `*`
+    #[token("*")]
+    Star,
+    /// `/`
+    #[token("/")]
+    Slash,
+    /// `^`
+    #[token("^")]
+    Caret,
+    /// `=`
+    #[token("=")]
+    Eq,
+    /// `<`
+    #[token("<")]
+    Less,
+    /// `>`
+    #[token(">")]
+    Greater,
+    /// `|`
+    #[token("|")]
+    Pipe,
+    /// `:`
+    #[token(":")]
+    Colon,
+    /// `;`
+    #[token(";")]
+    Semi,
+    /// `,`
+    #[token(",")]
+    Comma,
+    /// `.`
+    #[token(".")]
+    Dot,
+    /// `(`
+    #[token("(")]
+    LParen,
+    /// `)`
+    #[token(")")]
+    RParen,
+    /// `[`
+    #[token("[")]
+    LBracket,
+    /// `]`
+    #[token("]")]
+    RBracket,
+    /// `{`
+    #[token("{")]
+    LBrace,
+    /// `}`
+    #[token("}")]
+    RBrace,
+}
+
+/// An error in the value of a literal token
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LiteralError {
+    /// An integer literal doesn't fit in a 64-bit signed integer
+    #[error("integer literal '{0}' is too large")]
+    IntegerTooLarge(String),
+    /// An escape sequence isn't recognized
+    #[error("unknown escape sequence '\\{0}'")]
+    UnknownEscape(char),
+}
+
+/// Parse a decimal integer literal
+fn parse_int(text: &str) -> Result<i64, LiteralError> {
+    text.parse()
+        .map_err(|_| LiteralError::IntegerTooLarge(text.to_string()))
+}
+
+/// Parse a type variable, dropping its quote
+fn parse_type_var(text: &str) -> String {
+    text[1..].to_string()
+}
+
+/// Parse a quoted string literal, replacing its escape sequences
+fn parse_string(text: &str) -> Result<String, LiteralError> {
+    let mut string = String::with_capacity(text.len());
+    let mut chars = text[1..text.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue;
+        }
+        string.push(match chars.next().unwrap_or('\\') {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '\\' => '\\',
+            '"' => '"',
+            other => return Err(LiteralError::UnknownEscape(other)),
+        });
+    }
+    Ok(string)
+}
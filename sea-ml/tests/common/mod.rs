@@ -0,0 +1,57 @@
+//! Helpers shared by the SeaML integration tests
+//!
+//! Each test binary compiles this module separately and uses only some of it.
+#![allow(dead_code)]
+
+use sea_lex::{FileId, SourceDb, Span};
+use sea_ml::ast::Item;
+use sea_ml::{parse, resolve, Namespace, Program};
+use sea_parse::ScopeGraph;
+
+/// Parse `source` as a file of its own, panicking if it's invalid
+pub fn parse_program(source: &str) -> Program {
+    parse_file(source).1
+}
+
+/// Parse `source` as the body of a binding and print the resulting tree
+pub fn parse_expr(source: &str) -> String {
+    let program = parse_program(&format!("let it = {source}"));
+    let Item::Let(group) = &program.items[0] else {
+        panic!("expected a binding");
+    };
+    group.bindings[0].body.to_string()
+}
+
+/// Parse and resolve `source`, returning the graph, the error messages, and the file
+pub fn resolved(source: &str) -> (ScopeGraph<Namespace>, Vec<String>, FileId) {
+    let (file, program) = parse_file(source);
+    let (graph, errors) = resolve(&program);
+    (
+        graph,
+        errors.iter().map(ToString::to_string).collect(),
+        file,
+    )
+}
+
+/// The span of the `nth` occurrence of the identifier `name` in `source`
+pub fn nth(source: &str, file: FileId, name: &str, nth: usize) -> Span {
+    let ident = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    let start = source
+        .match_indices(name)
+        .map(|(start, _)| start)
+        .filter(|&start| {
+            !source[..start].ends_with(ident) && !source[start + name.len()..].starts_with(ident)
+        })
+        .nth(nth)
+        .unwrap_or_else(|| panic!("no occurrence {nth} of {name}"));
+    Span::new(file, start, start + name.len())
+}
+
+/// Parse `source` as a new file, returning the file and its tree
+fn parse_file(source: &str) -> (FileId, Program) {
+    let mut db = SourceDb::new();
+    let file = db.add("main.ml", source);
+    let program =
+        parse(file, source).unwrap_or_else(|errors| panic!("failed to parse: {errors:?}"));
+    (file, program)
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use common::{parse_expr, parse_program};
+use sea_lex::SourceDb;
+use sea_ml::ast::{ExprKind, Item, PatternKind, TypeBody};
+use sea_ml::{parse_recovering, Error};
+
+#[test]
+fn test_operators() {
+    assert_eq!(parse_expr("1 + 2 * 3 - 4"), "((1 + (2 * 3)) - 4)");
+    assert_eq!(parse_expr("a || b && c = d"), "(a || (b && (c = d)))");
+    assert_eq!(parse_expr("x :: y :: [] = l"), "((x :: (y :: [])) = l)");
+    assert_eq!(parse_expr("-f x *. 2.0 +. y"), "(((-(f x)) *. 2.0) +. y)");
+    assert_eq!(parse_expr("\"a\" ^ \"b\" ^ s"), "(\"a\" ^ (\"b\" ^ s))");
+    assert_eq!(parse_expr("n mod 2 <> 0"), "((n mod 2) <> 0)");
+}
+
+#[test]
+fn test_application() {
+    assert_eq!(parse_expr("f x y + g (h z)"), "(((f x) y) + (g (h z)))");
+    assert_eq!(parse_expr("Some (1, 2)"), "(Some (1, 2))");
+    assert_eq!(parse_expr("f None p.x"), "((f None) p.x)");
+    assert_eq!(parse_expr("fun x y -> x + y"), "(fun x y -> (x + y))");
+    assert_eq!(parse_expr("(fun x -> x) 1, ()"), "(((fun x -> x) 1), ())");
+    assert_eq!(parse_expr("(x : int list)"), "(x : int list)");
+}
+
+#[test]
+fn test_keyword_expressions() {
+    assert_eq!(
+        parse_expr("let rec f n = if n = 0 then 1 else n * f (n - 1) in f 5"),
+        "(let rec f n = (if (n = 0) then 1 else (n * (f (n - 1)))) in (f 5))"
+    );
+    assert_eq!(
+        parse_expr("let (a, b) = p and c = 1 in a; b"),
+        "(let (a, b) = p and c = 1 in (a; b))"
+    );
+    assert_eq!(
+        parse_expr("match l with [] -> 0 | x :: _ when x > 0 -> x | Some { x; y = 2 } -> -1"),
+        "(match l with [] -> 0 | (x :: _) when (x > 0) -> x | (Some { x = x; y = 2 }) -> (-1))"
+    );
+    assert_eq!(
+        parse_expr("{ p with x = 1; y = [1; 2] }.x"),
+        "{ p with x = 1; y = [1; 2] }.x"
+    );
+    assert_eq!(
+        parse_expr("1 + if c then 2 else 3 + 4"),
+        "(1 + (if c then 2 else (3 + 4)))"
+    );
+}
+
+#[test]
+fn test_declarations() {
+    let program = parse_program(
+        "(* A binary tree *)
+         type 'a tree = Leaf | Node of 'a tree * 'a * 'a tree
+         and ('k, 'v) map = ('k * 'v) tree
+         type point = { x : float; y : float; }
+
+         let rec insert x = function_body
+         and size (t : 'a tree) : int = match t with
+           | Leaf -> 0
+           | Node (l, _, r) -> size l + 1 + size r
+         ;;
+         let origin = { x = 0.0; y = 0.0 }
+         let map f l = let rec go = fun l -> f l in go l",
+    );
+
+    let Item::Type(decls) = &program.items[0] else {
+        panic!("expected type declarations");
+    };
+    let TypeBody::Variant(constructors) = &decls[0].body else {
+        panic!("expected a variant");
+    };
+    let constructors: Vec<_> = constructors
+        .iter()
+        .map(|constructor| match &constructor.arg {
+            Some(arg) => format!("{} of {arg}", constructor.name.name),
+            None => constructor.name.name.clone(),
+        })
+        .collect();
+    assert_eq!(constructors, ["Leaf", "Node of 'a tree * 'a * 'a tree"]);
+    assert_eq!(decls[1].params.len(), 2);
+    assert!(matches!(&decls[1].body, TypeBody::Alias(ty) if ty.to_string() == "('k * 'v) tree"));
+
+    let Item::Type(decls) = &program.items[1] else {
+        panic!("expected type declarations");
+    };
+    assert!(matches!(&decls[0].body, TypeBody::Record(fields) if fields.len() == 2));
+
+    let Item::Let(group) = &program.items[2] else {
+        panic!("expected bindings");
+    };
+    assert!(group.recursive);
+    assert_eq!(group.bindings.len(), 2);
+    let size = &group.bindings[1];
+    assert!(matches!(&size.pattern.kind, PatternKind::Var(name) if name.name == "size"));
+    assert!(matches!(size.params[0].kind, PatternKind::Annot(..)));
+    assert_eq!(size.annotation.as_ref().unwrap().to_string(), "int");
+    assert!(matches!(&size.body.kind, ExprKind::Match(_, arms) if arms.len() == 2));
+
+    assert!(matches!(&program.items[3], Item::Let(group) if !group.recursive));
+    assert!(matches!(&program.items[4], Item::Let(group) if group.bindings[0].params.len() == 2));
+}
+
+#[test]
+fn test_types() {
+    let program =
+        parse_program("let f (g : ('a -> 'b) -> 'a list -> 'b list) (p : int * (int -> int)) = ()");
+    let Item::Let(group) = &program.items[0] else {
+        panic!("expected bindings");
+    };
+    let types: Vec<_> = group.bindings[0]
+        .params
+        .iter()
+        .map(|param| match &param.kind {
+            PatternKind::Annot(_, ty) => ty.to_string(),
+            _ => panic!("expected an annotation"),
+        })
+        .collect();
+    assert_eq!(
+        types,
+        ["('a -> 'b) -> 'a list -> 'b list", "int * (int -> int)"]
+    );
+}
+
+#[test]
+fn test_recovery() {
+    let source = "let x = 1 + * 2
+                  let y = (fun -> 0)
+                  type t = A | B
+                  let w = 2 in w";
+    let mut db = SourceDb::new();
+    let file = db.add("main.ml", source);
+    let (program, errors) = parse_recovering(file, source);
+    let program = program.unwrap();
+
+    let messages: Vec<_> = errors
+        .iter()
+        .map(|error| match error {
+            Error::Parse(error) => (db.text(error.span).to_string(), error.to_string()),
+            Error::Resolve(error) => panic!("unexpected resolve error: {error}"),
+            Error::Lex(error) => panic!("unexpected lex error: {error}"),
+        })
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            (
+                "*".to_string(),
+                "Expected expression, found `*`".to_string()
+            ),
+            ("->".to_string(), "Expected pattern, found `->`".to_string()),
+        ]
+    );
+    assert!(matches!(program.items[0], Item::Error(_)));
+    assert!(matches!(program.items[1], Item::Error(_)));
+    assert!(matches!(program.items[2], Item::Type(_)));
+    assert!(matches!(program.items[3], Item::Expr(_)));
+}
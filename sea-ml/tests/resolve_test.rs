@@ -0,0 +1,67 @@
+mod common;
+
+use common::{nth, resolved};
+
+#[test]
+fn test_resolves_shadowing_and_recursion() {
+    let source = "
+        type 'a tree = Leaf | Node of 'a tree * 'a * 'a tree
+        let x = 1
+        let x = x + 1
+        let rec even n = if n = 0 then true else odd (n - 1)
+        and odd n = if n = 0 then false else even (n - 1)
+        let rec size t = match t with
+          | Leaf -> 0
+          | Node (l, x, r) -> size l + x + size r
+        let total = (fun (x : int list) -> x) [x]
+    ";
+    let (graph, errors, file) = resolved(source);
+    assert_eq!(errors, Vec::<String>::new());
+    let at = |name, index| nth(source, file, name, index);
+    let declaration = |span| {
+        graph
+            .resolution(span)
+            .map(|symbol| graph.symbol(symbol).span)
+    };
+
+    // Each `let` sees the `let`s before it, but not itself
+    assert_eq!(declaration(at("x", 2)), Some(at("x", 0)));
+    assert_eq!(declaration(at("x", 7)), Some(at("x", 1)));
+    // `let rec` groups see themselves
+    assert_eq!(declaration(at("odd", 0)), Some(at("odd", 1)));
+    assert_eq!(declaration(at("even", 1)), Some(at("even", 0)));
+    assert_eq!(declaration(at("size", 1)), Some(at("size", 0)));
+    // Pattern variables shadow in their arm, and lambda parameters in their body
+    assert_eq!(declaration(at("x", 4)), Some(at("x", 3)));
+    assert_eq!(declaration(at("x", 6)), Some(at("x", 5)));
+    assert_eq!(declaration(at("Node", 1)), Some(at("Node", 0)));
+    assert_eq!(declaration(at("'a", 2)), Some(at("'a", 0)));
+    // Built-in types resolve to nothing
+    assert_eq!(graph.resolution(at("int", 0)), None);
+}
+
+#[test]
+fn test_reports_unresolved_and_duplicate_names() {
+    let source = "
+        type shape = Circle of float | Square of flaot | Circle
+        type 'a box = { contents : 'b }
+        let area s = match s with Circl r -> r | Square (w, w) -> w
+        let describe s = lenght s
+        let rec twice x = x and twice y = y
+        let z = { content = 1 }
+    ";
+    let (_, errors, _) = resolved(source);
+    assert_eq!(
+        errors,
+        [
+            "Can't find type `flaot`, did you mean `float`?",
+            "Duplicate constructor `Circle` in the same scope",
+            "Can't find type variable `b`",
+            "Can't find constructor `Circl`, did you mean `Circle`?",
+            "Duplicate value `w` in the same scope",
+            "Can't find value `lenght`",
+            "Duplicate value `twice` in the same scope",
+            "Can't find field `content`, did you mean `contents`?",
+        ]
+    );
+}
@@ -0,0 +1,160 @@
+//! `#[derive(AstNode)]`: typed views of concrete syntax tree nodes
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields, Path};
+
+/// Attributes on the type being derived
+struct AstAttrs {
+    /// The token enum
+    tokens: Path,
+    /// The node kind a struct views
+    kind: Option<Path>,
+    /// The node kind type of an enum
+    nodes: Option<Path>,
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let attrs = AstAttrs::parse(&input.attrs, input)?;
+    let tokens = &attrs.tokens;
+
+    let (nodes, body) = match &input.data {
+        Data::Struct(data) => {
+            let kind = attrs.kind.ok_or_else(|| {
+                syn::Error::new_spanned(name, "missing `#[ast(kind = Kind::Variant)]` attribute")
+            })?;
+            let mut nodes = kind.clone();
+            if nodes.segments.pop().is_none() || nodes.segments.is_empty() {
+                return Err(syn::Error::new_spanned(kind, "expected `Kind::Variant`"));
+            }
+            nodes.segments.pop_punct();
+
+            let (construct, field) = match &data.fields {
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    (quote!(Self(node)), quote!(0))
+                }
+                Fields::Named(fields) if fields.named.len() == 1 => {
+                    let field = &fields.named[0].ident;
+                    (quote!(Self { #field: node }), quote!(#field))
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        "AstNode structs must have exactly one field, the `SyntaxNode`",
+                    ))
+                }
+            };
+            let body = quote! {
+                fn can_cast(kind: #nodes) -> bool {
+                    ::core::matches!(kind, #kind)
+                }
+
+                fn cast(node: ::sea_parse::SyntaxNode<#tokens, #nodes>) -> ::core::option::Option<Self> {
+                    if Self::can_cast(node.kind()) {
+                        ::core::option::Option::Some(#construct)
+                    } else {
+                        ::core::option::Option::None
+                    }
+                }
+
+                fn syntax(&self) -> &::sea_parse::SyntaxNode<#tokens, #nodes> {
+                    &self.#field
+                }
+            };
+            (nodes, body)
+        }
+        Data::Enum(data) => {
+            let nodes = attrs.nodes.ok_or_else(|| {
+                syn::Error::new_spanned(name, "missing `#[ast(nodes = NodeKind)]` attribute")
+            })?;
+            let mut names = Vec::new();
+            let mut views = Vec::new();
+            for variant in &data.variants {
+                match &variant.fields {
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                        names.push(&variant.ident);
+                        views.push(&fields.unnamed[0].ty);
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            variant,
+                            "AstNode enum variants must wrap exactly one AstNode",
+                        ))
+                    }
+                }
+            }
+            let body = quote! {
+                fn can_cast(kind: #nodes) -> bool {
+                    false #(|| <#views as ::sea_parse::AstNode<#tokens, #nodes>>::can_cast(kind))*
+                }
+
+                fn cast(node: ::sea_parse::SyntaxNode<#tokens, #nodes>) -> ::core::option::Option<Self> {
+                    let kind = node.kind();
+                    #(
+                        if <#views as ::sea_parse::AstNode<#tokens, #nodes>>::can_cast(kind) {
+                            return <#views as ::sea_parse::AstNode<#tokens, #nodes>>::cast(node)
+                                .map(Self::#names);
+                        }
+                    )*
+                    ::core::option::Option::None
+                }
+
+                fn syntax(&self) -> &::sea_parse::SyntaxNode<#tokens, #nodes> {
+                    match self {
+                        #(Self::#names(view) => ::sea_parse::AstNode::syntax(view),)*
+                    }
+                }
+            };
+            (nodes, body)
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "AstNode can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::sea_parse::AstNode<#tokens, #nodes> for #name #ty_generics #where_clause {
+            #body
+        }
+    })
+}
+
+impl AstAttrs {
+    /// Parse the `#[ast(...)]` attributes on the derived type
+    fn parse(attrs: &[Attribute], input: &DeriveInput) -> syn::Result<Self> {
+        let mut tokens = None;
+        let mut kind = None;
+        let mut nodes = None;
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("ast")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tokens") {
+                    tokens = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("kind") {
+                    kind = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("nodes") {
+                    nodes = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `tokens`, `kind`, or `nodes`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let tokens = tokens.ok_or_else(|| {
+            syn::Error::new_spanned(
+                &input.ident,
+                "missing `#[ast(tokens = TokenEnum)]` attribute",
+            )
+        })?;
+        Ok(Self {
+            tokens,
+            kind,
+            nodes,
+        })
+    }
+}
@@ -0,0 +1,804 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Field, Fields, GenericArgument, Ident, LitInt,
+    LitStr, Pat, Path, PathArguments, Type, Variant,
+};
+
+mod ast_node;
+mod visit;
+
+#[proc_macro_derive(AstNode, attributes(ast))]
+pub fn derive_ast_node(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    ast_node::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(Visit, attributes(visit))]
+pub fn derive_visit(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    visit::expand(&input, visit::Traversal::Visit)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(Fold, attributes(visit))]
+pub fn derive_fold(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    visit::expand(&input, visit::Traversal::Fold)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(Parse, attributes(parse))]
+pub fn derive_parse(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Attributes on the type being derived
+struct ContainerAttrs {
+    /// The token enum
+    tokens: Path,
+    /// The name of this node in error messages
+    label: Option<LitStr>,
+    /// How to recover when parsing this node fails
+    recover: Option<Recovery>,
+}
+
+/// A `recover(...)` attribute: skip to synchronization tokens and produce the error variant
+#[derive(Default)]
+struct Recovery {
+    /// Stop skipping before these tokens
+    before: Vec<Ident>,
+    /// Stop skipping after these tokens
+    after: Vec<Ident>,
+    /// Delimiters whose contents are skipped as a whole
+    balance: Vec<(Ident, Ident)>,
+}
+
+/// A token kind written in a `token = ...` attribute
+enum TokenPattern {
+    /// `Kind`: a unit variant
+    Unit(Ident),
+    /// `Kind(_)`: a variant whose single value becomes the field
+    Value(Ident),
+    /// `Kind(..)`: any variant of that name
+    Any(Ident),
+}
+
+/// Attributes on a field
+#[derive(Default)]
+struct FieldAttrs {
+    /// The field is a single token
+    token: Option<TokenPattern>,
+    /// The field is a list separated by this token
+    sep_by: Option<Ident>,
+    /// The separated list may end with a separator
+    trailing: bool,
+    /// A missing or extra token is repaired instead of failing
+    repair: bool,
+    /// The field holds the node's span
+    span: bool,
+}
+
+/// The associativity of an infix operator variant
+enum Assoc {
+    /// Left-associative
+    Left,
+    /// Right-associative
+    Right,
+    /// Non-associative
+    None,
+}
+
+/// The position of an operator variant's token
+enum Fixity {
+    /// Between the two operands
+    Infix(Assoc),
+    /// Before the operand
+    Prefix,
+    /// After the operand
+    Postfix,
+}
+
+/// An operator variant
+struct Operator {
+    /// Where the operator token goes
+    fixity: Fixity,
+    /// The operator token
+    kind: Ident,
+    /// The operator's precedence
+    precedence: LitInt,
+}
+
+/// Attributes on an enum variant
+#[derive(Default)]
+struct VariantAttrs {
+    /// A token that begins the variant
+    token: Option<TokenPattern>,
+    /// The operator this variant represents
+    operator: Option<Operator>,
+    /// The variant is the error node produced by recovery
+    error: bool,
+}
+
+/// The generated parts of a derived parser for one constructor
+struct Sequence {
+    /// Statements that parse the fields into `__field_N` variables
+    body: TokenStream2,
+    /// The expression that builds the node
+    constructor: TokenStream2,
+    /// The `LEFT_DEPTH`s of the types that can begin this sequence
+    left_depths: Vec<TokenStream2>,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let container = ContainerAttrs::parse(&input.attrs, input)?;
+    let tokens = &container.tokens;
+
+    let (body, left_depths, error_node) = match &input.data {
+        Data::Struct(data) => {
+            let sequence = sequence(name, &quote!(Self), &data.fields, tokens, true)?;
+            let Sequence {
+                body, constructor, ..
+            } = &sequence;
+            (
+                quote! {
+                    let __start = input.position();
+                    let __file = input.file();
+                    #body
+                    Ok(#constructor)
+                },
+                sequence.left_depths,
+                None,
+            )
+        }
+        Data::Enum(data) => enum_body(name, data.variants.iter(), tokens)?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "Parse can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    let parser = container.label.as_ref().map_or_else(
+        || quote!(__parse),
+        |label| quote!(::sea_parse::Parser::labelled(__parse, #label)),
+    );
+    let parser = match &container.recover {
+        Some(recovery) => {
+            let Some(error_node) = error_node else {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`recover` needs an enum with a `#[parse(error)]` variant",
+                ));
+            };
+            let before = &recovery.before;
+            let after = &recovery.after;
+            let balance = recovery
+                .balance
+                .iter()
+                .map(|(open, close)| quote!(.balance(#tokens::#open, #tokens::#close)));
+            quote! {
+                ::sea_parse::Parser::recover_with(
+                    #parser,
+                    ::sea_parse::skip_to(|__span| #error_node)
+                        .before([#(#tokens::#before),*])
+                        .after([#(#tokens::#after),*])
+                        #(#balance)*,
+                )
+            }
+        }
+        None => parser,
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // Force `LEFT_DEPTH` to be evaluated, so that indirect left recursion is a cycle error
+    let left_recursion_check = input.generics.params.is_empty().then(|| {
+        quote! {
+            const _: () = ::core::assert!(
+                <#name as ::sea_parse::Parse<#tokens>>::LEFT_DEPTH > 0,
+                "left-recursive grammar",
+            );
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::sea_parse::Parse<#tokens> for #name #ty_generics #where_clause {
+            const LEFT_DEPTH: usize = ::sea_parse::__private::left_depth(&[#(#left_depths),*]);
+
+            fn parse(
+                input: &mut ::sea_parse::Input<#tokens>,
+            ) -> ::sea_parse::ParseResult<#tokens, Self> {
+                let __parse = |input: &mut ::sea_parse::Input<#tokens>|
+                    -> ::sea_parse::ParseResult<#tokens, Self> { #body };
+                ::sea_parse::Parser::parse(&#parser, input)
+            }
+        }
+
+        #left_recursion_check
+    })
+}
+
+/// Generate the body of an enum's parser, the `LEFT_DEPTH`s of the types that can begin it,
+/// and the constructor of its error variant (from `__span`), if it has one
+fn enum_body<'a>(
+    name: &Ident,
+    variants: impl Iterator<Item = &'a Variant>,
+    tokens: &Path,
+) -> syn::Result<(TokenStream2, Vec<TokenStream2>, Option<TokenStream2>)> {
+    let mut alternatives = Vec::new();
+    let mut operators = Vec::new();
+    let mut left_depths = Vec::new();
+    let mut error_node = None;
+
+    for variant in variants {
+        let attrs = VariantAttrs::parse(&variant.attrs)?;
+        let variant_name = &variant.ident;
+
+        if attrs.error {
+            if error_node.is_some() {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "only one variant can be `#[parse(error)]`",
+                ));
+            }
+            let mut values = Vec::new();
+            for field in &variant.fields {
+                if !FieldAttrs::parse(&field.attrs)?.span {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "error variants can only have `#[parse(span)]` fields",
+                    ));
+                }
+                values.push(quote!(__span));
+            }
+            error_node = Some(construct(
+                &quote!(Self::#variant_name),
+                &variant.fields,
+                &values,
+            ));
+            continue;
+        }
+
+        if let Some(operator) = attrs.operator {
+            operators.push(operator_builder(variant, &operator, tokens)?);
+            continue;
+        }
+
+        let leading = attrs.token.map(|token| {
+            // The token's value is discarded, so any payload is ignored
+            let token = match token {
+                TokenPattern::Value(kind) => TokenPattern::Any(kind),
+                other => other,
+            };
+            let parser = token_parser(&token, tokens);
+            quote!(let _: () = ::sea_parse::Parser::parse(&#parser, input)?;)
+        });
+        let sequence = sequence(
+            name,
+            &quote!(Self::#variant_name),
+            &variant.fields,
+            tokens,
+            leading.is_none(),
+        )?;
+        left_depths.extend(sequence.left_depths);
+
+        let Sequence {
+            body, constructor, ..
+        } = sequence;
+        alternatives.push(quote! {
+            |input| {
+                let __start = input.position();
+                let __file = input.file();
+                #leading
+                #body
+                Ok(#constructor)
+            }
+        });
+    }
+
+    if alternatives.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "Parse needs at least one variant that isn't an operator",
+        ));
+    }
+
+    let count = alternatives.len();
+    let choice = quote! {
+        let __start = input.save();
+        let __alternatives: [
+            fn(&mut ::sea_parse::Input<#tokens>) -> ::sea_parse::ParseResult<#tokens, Self>;
+            #count
+        ] = [#(#alternatives),*];
+        let mut __error: ::core::option::Option<::sea_parse::ParseError<#tokens>> = None;
+        for __alternative in __alternatives {
+            match __alternative(input) {
+                Ok(node) => return Ok(node),
+                Err(error) => {
+                    input.restore(__start);
+                    __error = Some(match __error {
+                        Some(previous) => previous.merge(error),
+                        None => error,
+                    });
+                }
+            }
+        }
+        Err(__error.expect("an enum has at least one alternative"))
+    };
+
+    if operators.is_empty() {
+        return Ok((choice, left_depths, error_node));
+    }
+
+    Ok((
+        quote! {
+            let __atom = |input: &mut ::sea_parse::Input<#tokens>|
+                -> ::sea_parse::ParseResult<#tokens, Self> { #choice };
+            let __pratt = ::sea_parse::Pratt::new(__atom) #(#operators)*;
+            ::sea_parse::Parser::parse(&__pratt, input)
+        },
+        left_depths,
+        error_node,
+    ))
+}
+
+/// Generate the `Pratt` builder call for an operator variant
+fn operator_builder(
+    variant: &Variant,
+    operator: &Operator,
+    tokens: &Path,
+) -> syn::Result<TokenStream2> {
+    let variant_name = &variant.ident;
+    let kind = &operator.kind;
+    let precedence = &operator.precedence;
+
+    let (operand_names, expected): (&[&str], _) = match operator.fixity {
+        Fixity::Infix(_) => (&["__lhs", "__rhs"], "two operand fields"),
+        Fixity::Prefix | Fixity::Postfix => (&["__operand"], "one operand field"),
+    };
+
+    // Operands fill the non-span fields in order, and span fields get the node's span
+    let mut operands = operand_names.iter();
+    let mut values = Vec::new();
+    for field in &variant.fields {
+        if FieldAttrs::parse(&field.attrs)?.span {
+            values.push(quote!(__span));
+        } else if let Some(operand) = operands.next() {
+            let operand = format_ident!("{operand}");
+            values.push(quote!(::core::convert::Into::into(#operand)));
+        } else {
+            return Err(syn::Error::new_spanned(
+                variant,
+                format!("operator variants need exactly {expected}"),
+            ));
+        }
+    }
+    if operands.next().is_some() {
+        return Err(syn::Error::new_spanned(
+            variant,
+            format!("operator variants need exactly {expected}"),
+        ));
+    }
+
+    let constructor = construct(&quote!(Self::#variant_name), &variant.fields, &values);
+    Ok(match &operator.fixity {
+        Fixity::Infix(assoc) => {
+            let assoc = match assoc {
+                Assoc::Left => quote!(Left),
+                Assoc::Right => quote!(Right),
+                Assoc::None => quote!(None),
+            };
+            quote! {
+                .infix(#tokens::#kind, #precedence, ::sea_parse::Assoc::#assoc,
+                    |__lhs, _, __rhs, __span| #constructor)
+            }
+        }
+        Fixity::Prefix => quote! {
+            .prefix(#tokens::#kind, #precedence, |_, __operand, __span| #constructor)
+        },
+        Fixity::Postfix => quote! {
+            .postfix(#tokens::#kind, #precedence, |__operand, _, __span| #constructor)
+        },
+    })
+}
+
+/// Generate the parser for a sequence of fields
+///
+/// `at_left` is whether the sequence can begin with its first field,
+/// rather than with a token parsed before it.
+fn sequence(
+    name: &Ident,
+    path: &TokenStream2,
+    fields: &Fields,
+    tokens: &Path,
+    mut at_left: bool,
+) -> syn::Result<Sequence> {
+    let mut body = Vec::new();
+    let mut spans = Vec::new();
+    let mut values = Vec::new();
+    let mut left_depths = Vec::new();
+
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        let var = format_ident!("__field_{index}");
+        values.push(quote!(#var));
+
+        if attrs.span {
+            spans.push(quote!(let #var = input.span_since(__start);));
+            continue;
+        }
+
+        let ty = &field.ty;
+        let nullable = wrapper(ty, "Option").is_some() || wrapper(ty, "Vec").is_some();
+        let parser = if let Some(token) = &attrs.token {
+            let parser = if attrs.repair {
+                let TokenPattern::Unit(kind) = token else {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "`repair` only works with `token = Kind`",
+                    ));
+                };
+                quote! {
+                    ::sea_parse::Parser::map(
+                        ::sea_parse::expect(#tokens::#kind),
+                        move |token| ::sea_parse::FromToken::from_token(token, __file),
+                    )
+                }
+            } else {
+                token_parser(token, tokens)
+            };
+            if wrapper(ty, "Option").is_some() {
+                quote!(::sea_parse::optional(#parser))
+            } else {
+                parser
+            }
+        } else {
+            if at_left {
+                check_left_recursion(name, field)?;
+                left_depths.push(quote!(<#ty as ::sea_parse::Parse<#tokens>>::LEFT_DEPTH));
+            }
+            match &attrs.sep_by {
+                Some(separator) => {
+                    let Some(item) = wrapper(ty, "Vec") else {
+                        return Err(syn::Error::new_spanned(ty, "sep_by fields must be a Vec"));
+                    };
+                    let list = quote! {
+                        ::sea_parse::sep_by(
+                            <#item as ::sea_parse::Parse<#tokens>>::parse,
+                            ::sea_parse::token(#tokens::#separator),
+                        )
+                    };
+                    if attrs.trailing {
+                        quote!(#list.allow_trailing())
+                    } else {
+                        list
+                    }
+                }
+                None => quote!(<#ty as ::sea_parse::Parse<#tokens>>::parse),
+            }
+        };
+        at_left &= nullable;
+
+        let ty = &field.ty;
+        body.push(quote_spanned! {ty.span()=>
+            let #var: #ty = ::sea_parse::Parser::parse(&#parser, input)?;
+        });
+    }
+
+    Ok(Sequence {
+        body: quote!(#(#body)* #(#spans)*),
+        constructor: construct(path, fields, &values),
+        left_depths,
+    })
+}
+
+/// Generate an expression building `path` from the given field values
+fn construct(path: &TokenStream2, fields: &Fields, values: &[TokenStream2]) -> TokenStream2 {
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#values),*)),
+        Fields::Unit => quote!(#path),
+    }
+}
+
+/// Generate a parser for a single token
+fn token_parser(token: &TokenPattern, tokens: &Path) -> TokenStream2 {
+    match token {
+        TokenPattern::Unit(kind) => quote! {
+            ::sea_parse::Parser::map(
+                ::sea_parse::token(#tokens::#kind),
+                move |token| ::sea_parse::FromToken::from_token(token, __file),
+            )
+        },
+        TokenPattern::Value(kind) => {
+            let label = kind.to_string();
+            quote! {
+                ::sea_parse::Parser::labelled(
+                    ::sea_parse::select(
+                        |token: &::sea_parse::__private::TokenInfo<#tokens>| match &token.kind {
+                            #tokens::#kind(value) => Some(::core::clone::Clone::clone(value)),
+                            _ => None,
+                        },
+                    ),
+                    #label,
+                )
+            }
+        }
+        TokenPattern::Any(kind) => {
+            let label = kind.to_string();
+            quote! {
+                ::sea_parse::Parser::labelled(
+                    ::sea_parse::select(
+                        move |token: &::sea_parse::__private::TokenInfo<#tokens>| match &token.kind {
+                            #tokens::#kind(..) => Some(::sea_parse::FromToken::from_token(
+                                ::core::clone::Clone::clone(token),
+                                __file,
+                            )),
+                            _ => None,
+                        },
+                    ),
+                    #label,
+                )
+            }
+        }
+    }
+}
+
+/// Report an error if `field` can begin with the type being derived
+fn check_left_recursion(name: &Ident, field: &Field) -> syn::Result<()> {
+    let mut ty = &field.ty;
+    while let Some(inner) = ["Box", "Option", "Vec", "Spanned"]
+        .iter()
+        .find_map(|wrapper_name| wrapper(ty, wrapper_name))
+    {
+        ty = inner;
+    }
+
+    let Type::Path(path) = ty else {
+        return Ok(());
+    };
+    if path.qself.is_none() && (path.path.is_ident("Self") || path.path.is_ident(name)) {
+        return Err(syn::Error::new_spanned(
+            &field.ty,
+            format!(
+                "left recursion: `{name}` can begin with itself, so its parser would never \
+                 terminate; start with a token or use an operator variant \
+                 (`#[parse(infix = ...)]`)"
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Get the type argument of `ty` if it's `wrapper_name<T>`
+fn wrapper<'a>(ty: &'a Type, wrapper_name: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper_name {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+impl ContainerAttrs {
+    /// Parse the `#[parse(...)]` attributes on the derived type
+    fn parse(attrs: &[Attribute], input: &DeriveInput) -> syn::Result<Self> {
+        let mut tokens = None;
+        let mut label = None;
+        let mut recover = None;
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("parse")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tokens") {
+                    tokens = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("label") {
+                    label = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("recover") {
+                    recover = Some(Recovery::parse(&meta)?);
+                } else {
+                    return Err(meta.error("expected `tokens`, `label`, or `recover`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let tokens = tokens.ok_or_else(|| {
+            syn::Error::new_spanned(
+                &input.ident,
+                "missing `#[parse(tokens = TokenEnum)]` attribute",
+            )
+        })?;
+        Ok(Self {
+            tokens,
+            label,
+            recover,
+        })
+    }
+}
+
+impl Recovery {
+    /// Parse `recover(before(...), after(...), balance(Open, Close))`
+    fn parse(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        /// Parse a parenthesized list of token kinds
+        fn kinds(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Vec<Ident>> {
+            let mut kinds = Vec::new();
+            meta.parse_nested_meta(|kind| {
+                kinds.push(
+                    kind.path
+                        .get_ident()
+                        .cloned()
+                        .ok_or_else(|| kind.error("expected a token kind"))?,
+                );
+                Ok(())
+            })?;
+            Ok(kinds)
+        }
+
+        let mut result = Self::default();
+        meta.parse_nested_meta(|meta| {
+            if meta.path.is_ident("before") {
+                result.before.extend(kinds(&meta)?);
+            } else if meta.path.is_ident("after") {
+                result.after.extend(kinds(&meta)?);
+            } else if meta.path.is_ident("balance") {
+                let [open, close]: [Ident; 2] = kinds(&meta)?
+                    .try_into()
+                    .map_err(|_| meta.error("expected `balance(Open, Close)`"))?;
+                result.balance.push((open, close));
+            } else {
+                return Err(meta.error("expected `before`, `after`, or `balance`"));
+            }
+            Ok(())
+        })?;
+        Ok(result)
+    }
+}
+
+impl TokenPattern {
+    /// Parse a token pattern: `Kind`, `Kind(_)`, or `Kind(..)`
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let pattern = Pat::parse_single(input)?;
+        match &pattern {
+            Pat::Ident(ident) if ident.subpat.is_none() => Ok(Self::Unit(ident.ident.clone())),
+            Pat::TupleStruct(tuple) if tuple.path.get_ident().is_some() => {
+                let kind = tuple.path.get_ident().cloned().expect("checked above");
+                match tuple.elems.iter().collect::<Vec<_>>().as_slice() {
+                    [Pat::Wild(_)] => Ok(Self::Value(kind)),
+                    [Pat::Rest(_)] => Ok(Self::Any(kind)),
+                    _ => Err(syn::Error::new_spanned(
+                        &pattern,
+                        "expected `Kind`, `Kind(_)`, or `Kind(..)`",
+                    )),
+                }
+            }
+            _ => Err(syn::Error::new_spanned(
+                &pattern,
+                "expected `Kind`, `Kind(_)`, or `Kind(..)`",
+            )),
+        }
+    }
+}
+
+impl FieldAttrs {
+    /// Parse the `#[parse(...)]` attributes on a field
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("parse")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("token") {
+                    result.token = Some(TokenPattern::parse(meta.value()?)?);
+                } else if meta.path.is_ident("sep_by") {
+                    result.sep_by = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("trailing") {
+                    result.trailing = true;
+                } else if meta.path.is_ident("span") {
+                    result.span = true;
+                } else if meta.path.is_ident("repair") {
+                    result.repair = true;
+                } else {
+                    return Err(
+                        meta.error("expected `token`, `sep_by`, `trailing`, `repair`, or `span`")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        Ok(result)
+    }
+}
+
+impl VariantAttrs {
+    /// Parse the `#[parse(...)]` attributes on a variant
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut token = None;
+        let mut fixity = None;
+        let mut kind: Option<Ident> = None;
+        let mut precedence = None;
+        let mut assoc = Assoc::Left;
+        let mut span = None;
+        let mut error = false;
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("parse")) {
+            span.get_or_insert(attr.span());
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("token") {
+                    token = Some(TokenPattern::parse(meta.value()?)?);
+                } else if meta.path.is_ident("infix") {
+                    fixity = Some(Fixity::Infix(Assoc::Left));
+                    kind = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("prefix") {
+                    fixity = Some(Fixity::Prefix);
+                    kind = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("postfix") {
+                    fixity = Some(Fixity::Postfix);
+                    kind = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("precedence") {
+                    precedence = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("assoc") {
+                    let value: Ident = meta.value()?.parse()?;
+                    assoc = match value.to_string().as_str() {
+                        "left" => Assoc::Left,
+                        "right" => Assoc::Right,
+                        "none" => Assoc::None,
+                        _ => return Err(meta.error("expected `left`, `right`, or `none`")),
+                    };
+                } else if meta.path.is_ident("error") {
+                    error = true;
+                } else {
+                    return Err(meta.error(
+                        "expected `token`, `infix`, `prefix`, `postfix`, `precedence`, `assoc`, \
+                         or `error`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+
+        let operator = match (fixity, kind) {
+            (Some(fixity), Some(kind)) => {
+                let precedence = precedence.ok_or_else(|| {
+                    syn::Error::new(
+                        span.unwrap_or_else(proc_macro2::Span::call_site),
+                        "operator variants need a `precedence`",
+                    )
+                })?;
+                let fixity = match fixity {
+                    Fixity::Infix(_) => Fixity::Infix(assoc),
+                    other => other,
+                };
+                Some(Operator {
+                    fixity,
+                    kind,
+                    precedence,
+                })
+            }
+            _ => None,
+        };
+        Ok(Self {
+            token,
+            operator,
+            error,
+        })
+    }
+}
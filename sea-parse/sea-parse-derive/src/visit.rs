@@ -0,0 +1,190 @@
+//! `#[derive(Visit)]` and `#[derive(Fold)]`: traversals over syntax tree nodes
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Field, Fields, Generics, Ident, Path};
+
+/// Which traversal to derive
+#[derive(Clone, Copy)]
+pub enum Traversal {
+    /// `Visit`, for `Visitor` and `VisitorMut`
+    Visit,
+    /// `Fold`, for `Folder`
+    Fold,
+}
+
+/// A struct or variant's fields, as bound by a pattern
+struct Bindings {
+    /// The pattern binding every field, or `_` for skipped fields when visiting
+    pattern: TokenStream2,
+    /// The binding of each field that isn't skipped
+    visited: Vec<Ident>,
+    /// The expression rebuilding the fields, folding those that aren't skipped
+    folded: TokenStream2,
+}
+
+pub fn expand(input: &DeriveInput, traversal: Traversal) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let arms: Vec<(Path, Bindings)> = match &input.data {
+        Data::Struct(data) => vec![(parse_quote!(Self), bind(&data.fields, traversal)?)],
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                Ok((
+                    parse_quote!(Self::#ident),
+                    bind(&variant.fields, traversal)?,
+                ))
+            })
+            .collect::<syn::Result<_>>()?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "Visit and Fold can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    let generics = bounded(&input.generics, traversal);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match traversal {
+        Traversal::Visit => {
+            let visit = arms.iter().map(|(path, bindings)| {
+                let Bindings {
+                    pattern, visited, ..
+                } = bindings;
+                quote!(#path #pattern => { #(visitor.visit(#visited);)* })
+            });
+            let visit_mut = arms.iter().map(|(path, bindings)| {
+                let Bindings {
+                    pattern, visited, ..
+                } = bindings;
+                quote!(#path #pattern => { #(visitor.visit_mut(#visited);)* })
+            });
+            quote! {
+                impl #impl_generics ::sea_parse::Visit for #name #ty_generics #where_clause {
+                    #[allow(unused_variables)]
+                    fn visit_children<V: ::sea_parse::Visitor + ?::core::marker::Sized>(
+                        &self,
+                        visitor: &mut V,
+                    ) {
+                        match self {
+                            #(#visit)*
+                        }
+                    }
+
+                    #[allow(unused_variables)]
+                    fn visit_children_mut<V: ::sea_parse::VisitorMut + ?::core::marker::Sized>(
+                        &mut self,
+                        visitor: &mut V,
+                    ) {
+                        match self {
+                            #(#visit_mut)*
+                        }
+                    }
+                }
+            }
+        }
+        Traversal::Fold => {
+            let fold = arms.iter().map(|(path, bindings)| {
+                let Bindings {
+                    pattern, folded, ..
+                } = bindings;
+                quote!(#path #pattern => #path #folded,)
+            });
+            quote! {
+                impl #impl_generics ::sea_parse::Fold for #name #ty_generics #where_clause {
+                    #[allow(unused_variables)]
+                    fn fold_children<F: ::sea_parse::Folder + ?::core::marker::Sized>(
+                        self,
+                        folder: &mut F,
+                    ) -> Self {
+                        match self {
+                            #(#fold)*
+                        }
+                    }
+                }
+            }
+        }
+    };
+    Ok(body)
+}
+
+/// Bind the fields of a struct or variant
+fn bind(fields: &Fields, traversal: Traversal) -> syn::Result<Bindings> {
+    let mut patterns = Vec::new();
+    let mut visited = Vec::new();
+    let mut folded = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let binding = format_ident!("field_{index}");
+        let skip = skipped(field)?;
+        let bound = if skip && matches!(traversal, Traversal::Visit) {
+            quote!(_)
+        } else {
+            quote!(#binding)
+        };
+        let value = if skip {
+            quote!(#binding)
+        } else {
+            quote!(folder.fold(#binding))
+        };
+        match &field.ident {
+            Some(ident) => {
+                patterns.push(quote!(#ident: #bound));
+                folded.push(quote!(#ident: #value));
+            }
+            None => {
+                patterns.push(bound);
+                folded.push(value);
+            }
+        }
+        if !skip {
+            visited.push(binding);
+        }
+    }
+
+    let (pattern, folded) = match fields {
+        Fields::Named(_) => (quote!({ #(#patterns),* }), quote!({ #(#folded),* })),
+        Fields::Unnamed(_) => (quote!((#(#patterns),*)), quote!((#(#folded),*))),
+        Fields::Unit => (quote!(), quote!()),
+    };
+    Ok(Bindings {
+        pattern,
+        visited,
+        folded,
+    })
+}
+
+/// Reports whether a field is marked `#[visit(skip)]`
+fn skipped(field: &Field) -> syn::Result<bool> {
+    let mut skip = false;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("visit"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `skip`"))
+            }
+        })?;
+    }
+    Ok(skip)
+}
+
+/// Require every type parameter to implement the derived trait
+fn bounded(generics: &Generics, traversal: Traversal) -> Generics {
+    let mut generics = generics.clone();
+    let bound: Path = match traversal {
+        Traversal::Visit => parse_quote!(::sea_parse::Visit),
+        Traversal::Fold => parse_quote!(::sea_parse::Fold),
+    };
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
@@ -0,0 +1,362 @@
+//! Primitive parsers and combinators
+
+use crate::{Expected, Input, ParseResult, Parser};
+use sea_lex::TokenInfo;
+use std::mem::discriminant;
+
+/// A parser for a single token of a given kind. See [`token`].
+#[derive(Debug, Clone)]
+pub struct TokenParser<T> {
+    /// The kind to match
+    kind: T,
+}
+
+/// Parse a single token of the same variant as `kind`
+///
+/// Only the variant is compared, so `token(Tok::Number(0))` matches any number.
+/// Use [`select`] to extract the data carried by a token.
+pub const fn token<T>(kind: T) -> TokenParser<T> {
+    TokenParser { kind }
+}
+
+impl<T: Clone> Parser<T> for TokenParser<T> {
+    type Output = TokenInfo<T>;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, TokenInfo<T>> {
+        match input.peek() {
+            Some(token) if discriminant(&token.kind) == discriminant(&self.kind) => {
+                Ok(input.next_token().expect("the token was just peeked"))
+            }
+            _ => Err(input.unexpected(vec![Expected::Token(self.kind.clone())])),
+        }
+    }
+}
+
+/// A parser for a single token chosen by a function. See [`select`].
+#[derive(Debug, Clone, Copy)]
+pub struct Select<F> {
+    /// The selection function
+    f: F,
+}
+
+/// Parse a single token for which `f` returns `Some`, producing its result
+///
+/// Errors from this parser don't know what was expected, so it's usually
+/// [`labelled`](Parser::labelled).
+pub const fn select<T, O, F: Fn(&TokenInfo<T>) -> Option<O>>(f: F) -> Select<F> {
+    Select { f }
+}
+
+impl<T: Clone, O, F: Fn(&TokenInfo<T>) -> Option<O>> Parser<T> for Select<F> {
+    type Output = O;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, O> {
+        match input.peek().and_then(&self.f) {
+            Some(output) => {
+                input.next_token();
+                Ok(output)
+            }
+            None => Err(input.unexpected(Vec::new())),
+        }
+    }
+}
+
+/// A parser for any single token. See [`any`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Any;
+
+/// Parse any single token
+#[must_use]
+pub const fn any() -> Any {
+    Any
+}
+
+impl<T: Clone> Parser<T> for Any {
+    type Output = TokenInfo<T>;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, TokenInfo<T>> {
+        input
+            .next_token()
+            .ok_or_else(|| input.unexpected(Vec::new()))
+    }
+}
+
+/// A parser for the end of the input. See [`end`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct End;
+
+/// Succeed only at the end of the input
+#[must_use]
+pub const fn end() -> End {
+    End
+}
+
+impl<T: Clone> Parser<T> for End {
+    type Output = ();
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, ()> {
+        if input.is_at_end() {
+            Ok(())
+        } else {
+            Err(input.unexpected(vec![Expected::End]))
+        }
+    }
+}
+
+/// A parser that runs a tuple of parsers in sequence. See [`seq`].
+#[derive(Debug, Clone, Copy)]
+pub struct Seq<P> {
+    /// The parsers
+    parsers: P,
+}
+
+/// Run each parser in a tuple in order, producing a tuple of their outputs
+pub const fn seq<P>(parsers: P) -> Seq<P> {
+    Seq { parsers }
+}
+
+/// A parser that tries a tuple of alternatives in order. See [`choice`].
+#[derive(Debug, Clone, Copy)]
+pub struct Choice<P> {
+    /// The alternatives
+    parsers: P,
+}
+
+/// Try each parser in a tuple in order, producing the output of the first that succeeds
+///
+/// Every alternative starts from the same position. If all of them fail,
+/// the error is the one that got furthest, with the expectations of every
+/// alternative that failed there.
+pub const fn choice<P>(parsers: P) -> Choice<P> {
+    Choice { parsers }
+}
+
+/// Implement [`Parser`] for [`Seq`] and [`Choice`] over tuples of parsers
+macro_rules! impl_tuples {
+    ($($parser:ident),+) => {
+        impl<T, $($parser: Parser<T>),+> Parser<T> for Seq<($($parser,)+)> {
+            type Output = ($($parser::Output,)+);
+
+            #[allow(non_snake_case)]
+            fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+                let ($($parser,)+) = &self.parsers;
+                Ok(($($parser.parse(input)?,)+))
+            }
+        }
+
+        impl<T: Clone, O, $($parser: Parser<T, Output = O>),+> Parser<T> for Choice<($($parser,)+)> {
+            type Output = O;
+
+            #[allow(non_snake_case)]
+            fn parse(&self, input: &mut Input<T>) -> ParseResult<T, O> {
+                let ($($parser,)+) = &self.parsers;
+                let start = input.save();
+                let mut error: Option<crate::ParseError<T>> = None;
+                $(
+                    match $parser.parse(input) {
+                        Ok(output) => return Ok(output),
+                        Err(next) => {
+                            input.restore(start);
+                            error = Some(match error {
+                                Some(error) => error.merge(next),
+                                None => next,
+                            });
+                        }
+                    }
+                )+
+                Err(error.expect("a choice has at least one alternative"))
+            }
+        }
+    };
+}
+
+impl_tuples!(A);
+impl_tuples!(A, B);
+impl_tuples!(A, B, C);
+impl_tuples!(A, B, C, D);
+impl_tuples!(A, B, C, D, E);
+impl_tuples!(A, B, C, D, E, F);
+impl_tuples!(A, B, C, D, E, F, G);
+impl_tuples!(A, B, C, D, E, F, G, H);
+impl_tuples!(A, B, C, D, E, F, G, H, I);
+impl_tuples!(A, B, C, D, E, F, G, H, I, J);
+impl_tuples!(A, B, C, D, E, F, G, H, I, J, K);
+impl_tuples!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// A parser that repeats another. See [`many`].
+#[derive(Debug, Clone, Copy)]
+pub struct Many<P> {
+    /// The repeated parser
+    parser: P,
+    /// The minimum number of repetitions
+    at_least: usize,
+}
+
+/// Run `parser` as many times as possible, producing a list of its outputs
+///
+/// Repetition stops when `parser` fails or stops consuming tokens.
+pub const fn many<P>(parser: P) -> Many<P> {
+    Many {
+        parser,
+        at_least: 0,
+    }
+}
+
+impl<P> Many<P> {
+    /// Require at least `count` repetitions
+    #[must_use]
+    pub const fn at_least(mut self, count: usize) -> Self {
+        self.at_least = count;
+        self
+    }
+}
+
+impl<T: Clone, P: Parser<T>> Parser<T> for Many<P> {
+    type Output = Vec<P::Output>;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        let mut outputs = Vec::new();
+        loop {
+            let start = input.save();
+            match self.parser.parse(input) {
+                Ok(output) => {
+                    outputs.push(output);
+                    if input.position() == start.position() {
+                        break;
+                    }
+                }
+                Err(error) => {
+                    if outputs.len() < self.at_least {
+                        return Err(error);
+                    }
+                    input.restore(start);
+                    break;
+                }
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+/// A parser for a separated list. See [`sep_by`].
+#[derive(Debug, Clone, Copy)]
+pub struct SepBy<P, S> {
+    /// The item parser
+    parser: P,
+    /// The separator parser
+    separator: S,
+    /// The minimum number of items
+    at_least: usize,
+    /// Whether a separator may follow the last item
+    allow_trailing: bool,
+}
+
+/// Parse a list of `parser` separated by `separator`, producing the items
+pub const fn sep_by<P, S>(parser: P, separator: S) -> SepBy<P, S> {
+    SepBy {
+        parser,
+        separator,
+        at_least: 0,
+        allow_trailing: false,
+    }
+}
+
+impl<P, S> SepBy<P, S> {
+    /// Require at least `count` items
+    #[must_use]
+    pub const fn at_least(mut self, count: usize) -> Self {
+        self.at_least = count;
+        self
+    }
+
+    /// Allow a separator after the last item
+    #[must_use]
+    pub const fn allow_trailing(mut self) -> Self {
+        self.allow_trailing = true;
+        self
+    }
+}
+
+impl<T: Clone, P: Parser<T>, S: Parser<T>> Parser<T> for SepBy<P, S> {
+    type Output = Vec<P::Output>;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        let mut items = Vec::new();
+        loop {
+            let start = input.save();
+            match self.parser.parse(input) {
+                Ok(item) => items.push(item),
+                Err(error) => {
+                    // A missing item is fine for an empty list, or after a trailing separator
+                    let allowed = if items.is_empty() {
+                        self.at_least == 0
+                    } else {
+                        self.allow_trailing && items.len() >= self.at_least
+                    };
+                    if !allowed {
+                        return Err(error);
+                    }
+                    input.restore(start);
+                    break;
+                }
+            }
+
+            let before_separator = input.save();
+            if let Err(error) = self.separator.parse(input) {
+                if items.len() < self.at_least {
+                    return Err(error);
+                }
+                input.restore(before_separator);
+                break;
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// A parser that may be skipped. See [`optional`].
+#[derive(Debug, Clone, Copy)]
+pub struct Optional<P> {
+    /// The inner parser
+    parser: P,
+}
+
+/// Run `parser` if it matches, producing `None` (and consuming nothing) if it doesn't
+pub const fn optional<P>(parser: P) -> Optional<P> {
+    Optional { parser }
+}
+
+impl<T: Clone, P: Parser<T>> Parser<T> for Optional<P> {
+    type Output = Option<P::Output>;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        let start = input.save();
+        self.parser.parse(input).map_or_else(
+            |_| {
+                input.restore(start);
+                Ok(None)
+            },
+            |output| Ok(Some(output)),
+        )
+    }
+}
+
+/// A parser that consumes nothing and produces a value. See [`empty`].
+#[derive(Debug, Clone, Copy)]
+pub struct Empty<O> {
+    /// The value to produce
+    value: O,
+}
+
+/// Consume nothing, producing `value`
+pub const fn empty<O: Clone>(value: O) -> Empty<O> {
+    Empty { value }
+}
+
+impl<T, O: Clone> Parser<T> for Empty<O> {
+    type Output = O;
+
+    fn parse(&self, _input: &mut Input<T>) -> ParseResult<T, O> {
+        Ok(self.value.clone())
+    }
+}
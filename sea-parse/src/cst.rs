@@ -0,0 +1,21 @@
+//! Lossless concrete syntax trees
+//!
+//! Trees are stored as immutable green trees ([`GreenNode`]), which hold every token and
+//! all trivia but no positions, so that edited trees can share unchanged subtrees.
+//! Red trees ([`SyntaxNode`]) are created on demand while traversing, adding positions
+//! and parent links, and typed views ([`AstNode`]) give them a convenient API.
+//!
+//! Tokens keep the kinds produced by the sea-lex lexer, and node kinds are any `Copy`
+//! type, usually a fieldless enum.
+
+mod ast;
+mod builder;
+mod green;
+mod red;
+mod reparse;
+
+pub use ast::*;
+pub use builder::*;
+pub use green::*;
+pub use red::*;
+pub use reparse::*;
@@ -0,0 +1,47 @@
+//! Typed views of concrete syntax trees
+
+use super::{SyntaxNode, SyntaxToken};
+use crate::same_kind;
+
+/// A typed view of a [`SyntaxNode`] of particular kinds
+///
+/// AST views wrap syntax nodes without copying anything, so they always reflect the
+/// full concrete syntax tree underneath. Derive this trait for a struct wrapping a single
+/// `SyntaxNode` with `#[ast(tokens = Tok, kind = Kind::Variant)]`, or for an enum whose
+/// variants each wrap one view with `#[ast(tokens = Tok, nodes = Kind)]`.
+pub trait AstNode<T, N>: Sized {
+    /// Reports whether nodes of `kind` can be viewed as this type
+    fn can_cast(kind: N) -> bool;
+
+    /// View `node` as this type, if it has the right kind
+    fn cast(node: SyntaxNode<T, N>) -> Option<Self>;
+
+    /// The syntax node this views
+    fn syntax(&self) -> &SyntaxNode<T, N>;
+}
+
+impl<T, N: Copy> SyntaxNode<T, N> {
+    /// The first child node that can be viewed as `A`
+    #[must_use]
+    pub fn child<A: AstNode<T, N>>(&self) -> Option<A> {
+        self.children().find_map(A::cast)
+    }
+
+    /// Every child node that can be viewed as `A`
+    pub fn children_of<A: AstNode<T, N>>(&self) -> impl Iterator<Item = A> {
+        self.children().filter_map(A::cast)
+    }
+
+    /// The first child token of the same variant as `kind`
+    #[must_use]
+    pub fn child_token(&self, kind: &T) -> Option<SyntaxToken<T, N>> {
+        self.children_with_tokens()
+            .filter_map(super::SyntaxElement::into_token)
+            .find(|token| {
+                token
+                    .kind()
+                    .token()
+                    .is_some_and(|token| same_kind(token, kind))
+            })
+    }
+}
@@ -0,0 +1,301 @@
+//! Building green trees, directly or from a token stream
+
+use super::{GreenElement, GreenNode, GreenToken, TokenKind};
+use crate::same_kind;
+use sea_lex::{TokenGrammar, TokenInfo};
+use std::fmt;
+use std::ops::Range;
+
+/// Builds a green tree from a preorder traversal
+///
+/// Nodes are opened with [`start_node`](Self::start_node) and closed with
+/// [`finish_node`](Self::finish_node); tokens are added to the innermost open node.
+#[derive(Debug)]
+pub struct GreenBuilder<T, N> {
+    /// The open nodes, and the index in `children` where each one's children begin
+    parents: Vec<(N, usize)>,
+    /// The children of every open node, innermost last
+    children: Vec<GreenElement<T, N>>,
+}
+
+/// A position in a [`GreenBuilder`] where a node can be started later, once it's known
+/// that the children added since should be wrapped in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeCheckpoint(usize);
+
+impl<T, N> GreenBuilder<T, N> {
+    /// Create an empty builder
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            parents: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Open a node
+    pub fn start_node(&mut self, kind: N) {
+        self.parents.push((kind, self.children.len()));
+    }
+
+    /// Add a token to the innermost open node
+    pub fn token(&mut self, kind: TokenKind<T>, text: impl Into<Box<str>>) {
+        self.children
+            .push(GreenElement::Token(GreenToken::new(kind, text)));
+    }
+
+    /// Close the innermost open node
+    ///
+    /// # Panics
+    ///
+    /// Panics if no node is open
+    pub fn finish_node(&mut self) {
+        let (kind, first) = self.parents.pop().expect("no node to finish");
+        let children: Vec<_> = self.children.drain(first..).collect();
+        self.children
+            .push(GreenElement::Node(GreenNode::new(kind, children)));
+    }
+
+    /// The number of open nodes
+    #[must_use]
+    pub const fn depth(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Remember the current position, to start a node there later
+    #[must_use]
+    pub const fn checkpoint(&self) -> TreeCheckpoint {
+        TreeCheckpoint(self.children.len())
+    }
+
+    /// Open a node at `checkpoint`, so that it contains everything added since
+    ///
+    /// # Panics
+    ///
+    /// Panics if a node that was open at `checkpoint` has been closed since
+    pub fn start_node_at(&mut self, checkpoint: TreeCheckpoint, kind: N) {
+        let TreeCheckpoint(first) = checkpoint;
+        assert!(
+            first <= self.children.len()
+                && self.parents.last().is_none_or(|&(_, start)| start <= first),
+            "checkpoint is no longer valid",
+        );
+        self.parents.push((kind, first));
+    }
+
+    /// Finish building, producing the root node
+    ///
+    /// # Panics
+    ///
+    /// Panics unless exactly one root node was built, and every node was closed
+    #[must_use]
+    pub fn finish(mut self) -> GreenNode<T, N> {
+        assert!(self.parents.is_empty(), "unfinished nodes");
+        assert_eq!(self.children.len(), 1, "expected exactly one root node");
+        match self.children.pop() {
+            Some(GreenElement::Node(root)) => root,
+            _ => panic!("expected a root node, not a token"),
+        }
+    }
+}
+
+impl<T, N> Default for GreenBuilder<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An error found while building a concrete syntax tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// The byte range of the offending text
+    pub range: Range<usize>,
+    /// A description of the error
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Builds a lossless concrete syntax tree from the tokens lexed from a source text
+///
+/// A hand-written parser drives the builder: it inspects tokens with
+/// [`peek`](Self::peek) and [`at`](Self::at), consumes them with [`bump`](Self::bump),
+/// and groups them into nodes. The text the lexer skipped between tokens is added as
+/// trivia automatically, so the tree's text is always exactly the source text. Trivia
+/// before a node goes in the node's parent.
+#[derive(Debug)]
+pub struct TreeBuilder<'a, T, N> {
+    /// The source text the tokens were lexed from
+    source: &'a str,
+    /// The tokens
+    tokens: Vec<TokenInfo<T>>,
+    /// The index of the next token
+    position: usize,
+    /// The number of bytes of `source` added to the tree
+    offset: usize,
+    /// The tree being built
+    green: GreenBuilder<T, N>,
+    /// The errors reported so far
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a, T: Clone + TokenGrammar, N> TreeBuilder<'a, T, N> {
+    /// Create a builder for `tokens`, which were lexed from `source`
+    pub fn new(source: &'a str, tokens: impl IntoIterator<Item = TokenInfo<T>>) -> Self {
+        Self {
+            source,
+            tokens: tokens.into_iter().collect(),
+            position: 0,
+            offset: 0,
+            green: GreenBuilder::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// The next token, without consuming it
+    #[must_use]
+    pub fn peek(&self) -> Option<&T> {
+        self.peek_nth(0)
+    }
+
+    /// The token `n` tokens ahead, without consuming anything
+    #[must_use]
+    pub fn peek_nth(&self, n: usize) -> Option<&T> {
+        self.tokens.get(self.position + n).map(|token| &token.kind)
+    }
+
+    /// Reports whether the next token is the same variant as `kind`
+    #[must_use]
+    pub fn at(&self, kind: &T) -> bool {
+        self.peek().is_some_and(|next| same_kind(next, kind))
+    }
+
+    /// Reports whether every token has been consumed
+    #[must_use]
+    pub const fn at_end(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    /// Add the next token, and the trivia before it, to the current node
+    ///
+    /// Does nothing at the end of the input.
+    pub fn bump(&mut self) {
+        self.flush_trivia();
+        if let Some(token) = self.tokens.get(self.position) {
+            self.green
+                .token(TokenKind::Token(token.kind.clone()), token.text.as_str());
+            self.offset = token.end;
+            self.position += 1;
+        }
+    }
+
+    /// Consume the next token if it's the same variant as `kind`
+    pub fn eat(&mut self, kind: &T) -> bool {
+        let matches = self.at(kind);
+        if matches {
+            self.bump();
+        }
+        matches
+    }
+
+    /// Consume the next token if it's the same variant as `kind`, reporting an error if not
+    pub fn expect(&mut self, kind: &T) -> bool {
+        if self.eat(kind) {
+            return true;
+        }
+        let found = self
+            .peek()
+            .map_or_else(|| "end of input".to_string(), TokenGrammar::describe);
+        self.error(format!("Expected {}, found {found}", kind.describe()));
+        false
+    }
+
+    /// Report an error at the next token
+    pub fn error(&mut self, message: impl Into<String>) {
+        let range = self.tokens.get(self.position).map_or_else(
+            || self.source.len()..self.source.len(),
+            |token| token.start..token.end,
+        );
+        self.errors.push(SyntaxError {
+            range,
+            message: message.into(),
+        });
+    }
+
+    /// Open a node
+    pub fn start_node(&mut self, kind: N) {
+        if self.green.depth() > 0 {
+            self.flush_trivia();
+        }
+        self.green.start_node(kind);
+    }
+
+    /// Close the innermost open node
+    ///
+    /// Closing the root node consumes any remaining tokens, reporting an error if there
+    /// are any, and the trailing trivia.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no node is open
+    pub fn finish_node(&mut self) {
+        if self.green.depth() == 1 {
+            if let Some(next) = self.peek() {
+                let message = format!("Expected end of input, found {}", next.describe());
+                self.error(message);
+                while !self.at_end() {
+                    self.bump();
+                }
+            }
+            self.flush_trivia();
+        }
+        self.green.finish_node();
+    }
+
+    /// Remember the current position, to start a node there later
+    #[must_use]
+    pub fn checkpoint(&mut self) -> TreeCheckpoint {
+        if self.green.depth() > 0 {
+            self.flush_trivia();
+        }
+        self.green.checkpoint()
+    }
+
+    /// Open a node at `checkpoint`, so that it contains everything added since
+    ///
+    /// # Panics
+    ///
+    /// Panics if a node that was open at `checkpoint` has been closed since
+    pub fn start_node_at(&mut self, checkpoint: TreeCheckpoint, kind: N) {
+        self.green.start_node_at(checkpoint, kind);
+    }
+
+    /// Finish building, producing the root node and the errors reported
+    ///
+    /// # Panics
+    ///
+    /// Panics unless exactly one root node was built, and every node was closed
+    #[must_use]
+    pub fn finish(self) -> (GreenNode<T, N>, Vec<SyntaxError>) {
+        (self.green.finish(), self.errors)
+    }
+
+    /// Add the skipped text before the next token as trivia
+    fn flush_trivia(&mut self) {
+        let end = self
+            .tokens
+            .get(self.position)
+            .map_or(self.source.len(), |token| token.start);
+        if self.offset < end {
+            self.green
+                .token(TokenKind::Trivia, &self.source[self.offset..end]);
+            self.offset = end;
+        }
+    }
+}
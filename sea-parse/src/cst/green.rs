@@ -0,0 +1,209 @@
+//! Green trees: immutable, position-independent, and shared between versions of a tree
+
+use std::fmt;
+use std::sync::Arc;
+
+/// The kind of a token in a concrete syntax tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenKind<T> {
+    /// A token produced by the lexer
+    Token(T),
+    /// Text the lexer skipped, such as whitespace and comments
+    Trivia,
+}
+
+impl<T> TokenKind<T> {
+    /// The lexer token, if this isn't trivia
+    pub const fn token(&self) -> Option<&T> {
+        match self {
+            Self::Token(token) => Some(token),
+            Self::Trivia => None,
+        }
+    }
+
+    /// Reports whether this is trivia
+    pub const fn is_trivia(&self) -> bool {
+        matches!(self, Self::Trivia)
+    }
+}
+
+/// The contents of a [`GreenToken`]
+#[derive(Debug, PartialEq, Eq)]
+struct GreenTokenData<T> {
+    /// The token's kind
+    kind: TokenKind<T>,
+    /// The token's text
+    text: Box<str>,
+}
+
+/// A token in a green tree: its kind and text, but not its position
+///
+/// Cloning a green token is cheap.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenToken<T>(Arc<GreenTokenData<T>>);
+
+impl<T> GreenToken<T> {
+    /// Create a green token
+    pub fn new(kind: TokenKind<T>, text: impl Into<Box<str>>) -> Self {
+        Self(Arc::new(GreenTokenData {
+            kind,
+            text: text.into(),
+        }))
+    }
+
+    /// The token's kind
+    #[must_use]
+    pub fn kind(&self) -> &TokenKind<T> {
+        &self.0.kind
+    }
+
+    /// The token's text
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    /// The length of the token's text, in bytes
+    #[must_use]
+    pub fn text_len(&self) -> usize {
+        self.0.text.len()
+    }
+
+    /// Reports whether two green tokens are the same allocation
+    #[must_use]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> Clone for GreenToken<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> fmt::Display for GreenToken<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+/// The contents of a [`GreenNode`]
+#[derive(Debug, PartialEq, Eq)]
+struct GreenNodeData<T, N> {
+    /// The node's kind
+    kind: N,
+    /// The total length of the node's text, in bytes
+    text_len: usize,
+    /// The node's children, including every token and trivia
+    children: Vec<GreenElement<T, N>>,
+}
+
+/// A node in a green tree: its kind and children, but not its position or parent
+///
+/// Green nodes are immutable, and cloning one is cheap, so an edited tree shares every
+/// subtree that the edit didn't touch with the original.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenNode<T, N>(Arc<GreenNodeData<T, N>>);
+
+/// A child of a [`GreenNode`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum GreenElement<T, N> {
+    /// A child node
+    Node(GreenNode<T, N>),
+    /// A child token
+    Token(GreenToken<T>),
+}
+
+impl<T, N> GreenElement<T, N> {
+    /// The length of this element's text, in bytes
+    #[must_use]
+    pub fn text_len(&self) -> usize {
+        match self {
+            Self::Node(node) => node.text_len(),
+            Self::Token(token) => token.text_len(),
+        }
+    }
+}
+
+impl<T, N> Clone for GreenElement<T, N> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Node(node) => Self::Node(node.clone()),
+            Self::Token(token) => Self::Token(token.clone()),
+        }
+    }
+}
+
+impl<T, N> fmt::Display for GreenElement<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Node(node) => node.fmt(f),
+            Self::Token(token) => token.fmt(f),
+        }
+    }
+}
+
+impl<T, N> GreenNode<T, N> {
+    /// Create a green node
+    pub fn new(kind: N, children: impl IntoIterator<Item = GreenElement<T, N>>) -> Self {
+        let children: Vec<_> = children.into_iter().collect();
+        let text_len = children.iter().map(GreenElement::text_len).sum();
+        Self(Arc::new(GreenNodeData {
+            kind,
+            text_len,
+            children,
+        }))
+    }
+
+    /// The node's kind
+    #[must_use]
+    pub fn kind(&self) -> &N {
+        &self.0.kind
+    }
+
+    /// The total length of the node's text, in bytes
+    #[must_use]
+    pub fn text_len(&self) -> usize {
+        self.0.text_len
+    }
+
+    /// The node's children
+    #[must_use]
+    pub fn children(&self) -> &[GreenElement<T, N>] {
+        &self.0.children
+    }
+
+    /// Create a copy of this node with the child at `index` replaced
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds
+    #[must_use]
+    pub fn replace_child(&self, index: usize, child: GreenElement<T, N>) -> Self
+    where
+        N: Clone,
+    {
+        let mut children = self.0.children.clone();
+        children[index] = child;
+        Self::new(self.0.kind.clone(), children)
+    }
+
+    /// Reports whether two green nodes are the same allocation
+    #[must_use]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T, N> Clone for GreenNode<T, N> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T, N> fmt::Display for GreenNode<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.children().iter().try_for_each(|child| child.fmt(f))
+    }
+}
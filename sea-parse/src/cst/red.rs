@@ -0,0 +1,468 @@
+//! Red trees: positioned, parent-linked views of green trees, created on demand
+
+use super::{GreenElement, GreenNode, GreenToken, TokenKind};
+use std::fmt::{self, Write};
+use std::ops::Range;
+use std::rc::Rc;
+
+/// The contents of a [`SyntaxNode`]
+struct NodeData<T, N> {
+    /// The green node this views
+    green: GreenNode<T, N>,
+    /// The parent node, and this node's index among its children
+    parent: Option<(SyntaxNode<T, N>, usize)>,
+    /// The byte offset of the node's text
+    offset: usize,
+}
+
+/// A node in a concrete syntax tree, with its position and parent
+///
+/// Syntax nodes are cheap to clone, and are created as the tree is traversed, from the
+/// [`GreenNode`]s that store the tree. Use [`SyntaxNode::new_root`] to start traversing.
+pub struct SyntaxNode<T, N>(Rc<NodeData<T, N>>);
+
+/// A token in a concrete syntax tree, with its position and parent
+pub struct SyntaxToken<T, N> {
+    /// The green token this views
+    green: GreenToken<T>,
+    /// The parent node
+    parent: SyntaxNode<T, N>,
+    /// This token's index among its parent's children
+    index: usize,
+    /// The byte offset of the token's text
+    offset: usize,
+}
+
+/// A node or token in a concrete syntax tree
+pub enum SyntaxElement<T, N> {
+    /// A node
+    Node(SyntaxNode<T, N>),
+    /// A token
+    Token(SyntaxToken<T, N>),
+}
+
+impl<T, N> SyntaxNode<T, N> {
+    /// View a green node as the root of a tree, starting at offset 0
+    #[must_use]
+    pub fn new_root(green: GreenNode<T, N>) -> Self {
+        Self(Rc::new(NodeData {
+            green,
+            parent: None,
+            offset: 0,
+        }))
+    }
+
+    /// The green node this views
+    #[must_use]
+    pub fn green(&self) -> &GreenNode<T, N> {
+        &self.0.green
+    }
+
+    /// The node's kind
+    #[must_use]
+    pub fn kind(&self) -> N
+    where
+        N: Copy,
+    {
+        *self.0.green.kind()
+    }
+
+    /// The byte range of the node's text
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.text_len()
+    }
+
+    /// The node's text, including all of its tokens and trivia
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    /// The parent node, unless this is the root
+    #[must_use]
+    pub fn parent(&self) -> Option<Self> {
+        self.0.parent.as_ref().map(|(parent, _)| parent.clone())
+    }
+
+    /// This node and its ancestors, from the innermost outwards
+    pub fn ancestors(&self) -> impl Iterator<Item = Self> {
+        std::iter::successors(Some(self.clone()), Self::parent)
+    }
+
+    /// The root of the tree
+    #[must_use]
+    pub fn root(&self) -> Self {
+        let mut node = self.clone();
+        while let Some(parent) = node.parent() {
+            node = parent;
+        }
+        node
+    }
+
+    /// The node's children, including tokens and trivia
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement<T, N>> {
+        let parent = self.clone();
+        let mut offset = self.0.offset;
+        (0..self.0.green.children().len()).map(move |index| {
+            let child = &parent.0.green.children()[index];
+            let element = SyntaxElement::new(&parent, index, offset, child);
+            offset += child.text_len();
+            element
+        })
+    }
+
+    /// The node's child nodes
+    pub fn children(&self) -> impl Iterator<Item = Self> {
+        self.children_with_tokens()
+            .filter_map(SyntaxElement::into_node)
+    }
+
+    /// This node and all of its descendant nodes, in preorder
+    pub fn descendants(&self) -> impl Iterator<Item = Self> {
+        let mut stack = vec![self.clone()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            let start = stack.len();
+            stack.extend(node.children());
+            stack[start..].reverse();
+            Some(node)
+        })
+    }
+
+    /// Every token in this node, including trivia, in order
+    pub fn tokens(&self) -> impl Iterator<Item = SyntaxToken<T, N>> {
+        let mut stack = vec![SyntaxElement::Node(self.clone())];
+        std::iter::from_fn(move || loop {
+            match stack.pop()? {
+                SyntaxElement::Token(token) => return Some(token),
+                SyntaxElement::Node(node) => {
+                    let start = stack.len();
+                    stack.extend(node.children_with_tokens());
+                    stack[start..].reverse();
+                }
+            }
+        })
+    }
+
+    /// The first token in this node, if it has any
+    #[must_use]
+    pub fn first_token(&self) -> Option<SyntaxToken<T, N>> {
+        self.tokens().next()
+    }
+
+    /// The last token in this node, if it has any
+    #[must_use]
+    pub fn last_token(&self) -> Option<SyntaxToken<T, N>> {
+        let mut node = self.clone();
+        loop {
+            let last = node.children_with_tokens().last()?;
+            match last {
+                SyntaxElement::Token(token) => return Some(token),
+                SyntaxElement::Node(child) => node = child,
+            }
+        }
+    }
+
+    /// The token whose text contains `offset`, or the last token if `offset` is at the end
+    #[must_use]
+    pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken<T, N>> {
+        let mut node = self.clone();
+        loop {
+            let child = node
+                .children_with_tokens()
+                .find(|child| child.range().contains(&offset))
+                .or_else(|| {
+                    (offset == node.range().end)
+                        .then(|| node.children_with_tokens().last())
+                        .flatten()
+                })?;
+            match child {
+                SyntaxElement::Token(token) => return Some(token),
+                SyntaxElement::Node(child) => node = child,
+            }
+        }
+    }
+
+    /// The innermost node whose text contains all of `range`
+    ///
+    /// An empty range at the boundary of two nodes is covered by the first of them.
+    #[must_use]
+    pub fn covering_node(&self, range: Range<usize>) -> Self {
+        let mut node = self.clone();
+        while let Some(child) = node.children().find(|child| {
+            let child = child.range();
+            child.start <= range.start && range.end <= child.end
+        }) {
+            node = child;
+        }
+        node
+    }
+
+    /// Build the green root of a copy of this tree with this node replaced by `green`
+    #[must_use]
+    pub fn replace_with(&self, green: GreenNode<T, N>) -> GreenNode<T, N>
+    where
+        N: Clone,
+    {
+        match &self.0.parent {
+            Some((parent, index)) => parent.replace_with(
+                parent
+                    .green()
+                    .replace_child(*index, GreenElement::Node(green)),
+            ),
+            None => green,
+        }
+    }
+
+    /// Render the tree below this node, one element per line, for debugging and tests
+    #[must_use]
+    pub fn debug_tree(&self) -> String
+    where
+        T: fmt::Debug,
+        N: fmt::Debug,
+    {
+        /// Write `node` and its children at the given depth
+        fn write_node<T: fmt::Debug, N: fmt::Debug>(
+            out: &mut String,
+            node: &SyntaxNode<T, N>,
+            depth: usize,
+        ) {
+            let _ = writeln!(out, "{:indent$}{node:?}", "", indent = depth * 2);
+            for child in node.children_with_tokens() {
+                match child {
+                    SyntaxElement::Node(child) => write_node(out, &child, depth + 1),
+                    SyntaxElement::Token(token) => {
+                        let _ = writeln!(out, "{:indent$}{token:?}", "", indent = depth * 2 + 2);
+                    }
+                }
+            }
+        }
+
+        let mut out = String::new();
+        write_node(&mut out, self, 0);
+        out
+    }
+}
+
+impl<T, N> SyntaxToken<T, N> {
+    /// The green token this views
+    #[must_use]
+    pub const fn green(&self) -> &GreenToken<T> {
+        &self.green
+    }
+
+    /// The token's kind
+    #[must_use]
+    pub fn kind(&self) -> &TokenKind<T> {
+        self.green.kind()
+    }
+
+    /// Reports whether this token is trivia
+    #[must_use]
+    pub fn is_trivia(&self) -> bool {
+        self.green.kind().is_trivia()
+    }
+
+    /// The token's text
+    #[must_use]
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+
+    /// The byte range of the token's text
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.text_len()
+    }
+
+    /// The node containing this token
+    #[must_use]
+    pub const fn parent(&self) -> &SyntaxNode<T, N> {
+        &self.parent
+    }
+
+    /// The next token in the tree, including trivia
+    #[must_use]
+    pub fn next_token(&self) -> Option<Self> {
+        self.adjacent_token(true)
+    }
+
+    /// The previous token in the tree, including trivia
+    #[must_use]
+    pub fn prev_token(&self) -> Option<Self> {
+        self.adjacent_token(false)
+    }
+
+    /// The token after this one, if `forwards`, or before it otherwise
+    fn adjacent_token(&self, forwards: bool) -> Option<Self> {
+        let range = self.range();
+        let is_adjacent = |child: &SyntaxElement<T, N>| {
+            let child = child.range();
+            !child.is_empty()
+                && if forwards {
+                    child.start >= range.end
+                } else {
+                    child.end <= range.start
+                }
+        };
+        let element = self.parent.ancestors().find_map(|ancestor| {
+            let mut children = ancestor.children_with_tokens().filter(is_adjacent);
+            if forwards {
+                children.next()
+            } else {
+                children.last()
+            }
+        })?;
+        match element {
+            SyntaxElement::Token(token) => Some(token),
+            SyntaxElement::Node(node) if forwards => node.first_token(),
+            SyntaxElement::Node(node) => node.last_token(),
+        }
+    }
+
+    /// Build the green root of a copy of this tree with this token replaced by `green`
+    #[must_use]
+    pub fn replace_with(&self, green: GreenToken<T>) -> GreenNode<T, N>
+    where
+        N: Clone,
+    {
+        self.parent.replace_with(
+            self.parent
+                .green()
+                .replace_child(self.index, GreenElement::Token(green)),
+        )
+    }
+}
+
+impl<T, N> SyntaxElement<T, N> {
+    /// View the green child at `index` of `parent`, which starts at `offset`
+    fn new(
+        parent: &SyntaxNode<T, N>,
+        index: usize,
+        offset: usize,
+        green: &GreenElement<T, N>,
+    ) -> Self {
+        match green {
+            GreenElement::Node(green) => Self::Node(SyntaxNode(Rc::new(NodeData {
+                green: green.clone(),
+                parent: Some((parent.clone(), index)),
+                offset,
+            }))),
+            GreenElement::Token(green) => Self::Token(SyntaxToken {
+                green: green.clone(),
+                parent: parent.clone(),
+                index,
+                offset,
+            }),
+        }
+    }
+
+    /// The byte range of the element's text
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            Self::Node(node) => node.range(),
+            Self::Token(token) => token.range(),
+        }
+    }
+
+    /// The node, if this is one
+    #[must_use]
+    pub fn into_node(self) -> Option<SyntaxNode<T, N>> {
+        match self {
+            Self::Node(node) => Some(node),
+            Self::Token(_) => None,
+        }
+    }
+
+    /// The token, if this is one
+    #[must_use]
+    pub fn into_token(self) -> Option<SyntaxToken<T, N>> {
+        match self {
+            Self::Node(_) => None,
+            Self::Token(token) => Some(token),
+        }
+    }
+}
+
+impl<T, N> Clone for SyntaxNode<T, N> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<T, N> Clone for SyntaxToken<T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            green: self.green.clone(),
+            parent: self.parent.clone(),
+            index: self.index,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<T, N> Clone for SyntaxElement<T, N> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Node(node) => Self::Node(node.clone()),
+            Self::Token(token) => Self::Token(token.clone()),
+        }
+    }
+}
+
+/// Nodes are equal if they view the same green node at the same position
+impl<T, N> PartialEq for SyntaxNode<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.offset == other.0.offset && self.0.green.ptr_eq(&other.0.green)
+    }
+}
+
+impl<T, N> Eq for SyntaxNode<T, N> {}
+
+/// Tokens are equal if they view the same green token at the same position
+impl<T, N> PartialEq for SyntaxToken<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset && self.green.ptr_eq(&other.green)
+    }
+}
+
+impl<T, N> Eq for SyntaxToken<T, N> {}
+
+impl<T, N> fmt::Display for SyntaxNode<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.green.fmt(f)
+    }
+}
+
+impl<T, N> fmt::Display for SyntaxToken<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+impl<T, N: fmt::Debug> fmt::Debug for SyntaxNode<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?}", self.0.green.kind(), self.range())
+    }
+}
+
+impl<T: fmt::Debug, N> fmt::Debug for SyntaxToken<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind() {
+            TokenKind::Token(kind) => write!(f, "{kind:?}")?,
+            TokenKind::Trivia => write!(f, "Trivia")?,
+        }
+        write!(f, "@{:?} {:?}", self.range(), self.text())
+    }
+}
+
+impl<T: fmt::Debug, N: fmt::Debug> fmt::Debug for SyntaxElement<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Node(node) => node.fmt(f),
+            Self::Token(token) => token.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Parsing source text into concrete syntax trees, and reparsing them after edits
+
+use super::{GreenElement, GreenNode, GreenToken, SyntaxError, SyntaxNode, TokenKind, TreeBuilder};
+use crate::same_kind;
+use sea_lex::{LexError, TokenGrammar, TokenInfo};
+use std::ops::Range;
+
+/// A change to a source text: `range` is replaced with `text`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The byte range of the replaced text
+    pub range: Range<usize>,
+    /// The replacement text
+    pub text: String,
+}
+
+impl TextEdit {
+    /// Create an edit replacing `range` with `text`
+    pub fn new(range: Range<usize>, text: impl Into<String>) -> Self {
+        Self {
+            range,
+            text: text.into(),
+        }
+    }
+
+    /// Apply this edit to `text`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edit's range isn't within `text`, on character boundaries
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        let mut result = text.to_string();
+        result.replace_range(self.range.clone(), &self.text);
+        result
+    }
+
+    /// Map an offset in the text before this edit to the text after it
+    ///
+    /// Offsets within the replaced range map to its start.
+    #[must_use]
+    pub fn map_offset(&self, offset: usize) -> usize {
+        if offset >= self.range.end {
+            offset - self.range.len() + self.text.len()
+        } else {
+            offset.min(self.range.start)
+        }
+    }
+}
+
+/// A source text's concrete syntax tree, with its syntax errors
+#[derive(Debug)]
+pub struct SyntaxTree<T, N> {
+    /// The root of the tree
+    green: GreenNode<T, N>,
+    /// The syntax errors, in the order they were reported
+    errors: Vec<SyntaxError>,
+}
+
+impl<T, N> SyntaxTree<T, N> {
+    /// Create a syntax tree from its root and errors
+    #[must_use]
+    pub const fn new(green: GreenNode<T, N>, errors: Vec<SyntaxError>) -> Self {
+        Self { green, errors }
+    }
+
+    /// The green root of the tree
+    #[must_use]
+    pub const fn green(&self) -> &GreenNode<T, N> {
+        &self.green
+    }
+
+    /// The root of the tree, for traversal
+    #[must_use]
+    pub fn syntax(&self) -> SyntaxNode<T, N> {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    /// The syntax errors found while parsing
+    #[must_use]
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    /// The source text, reconstructed from the tree
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.green.to_string()
+    }
+}
+
+impl<T, N> Clone for SyntaxTree<T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            green: self.green.clone(),
+            errors: self.errors.clone(),
+        }
+    }
+}
+
+/// A function that lexes a source text
+pub type LexFn<T> = fn(&str) -> Result<Vec<TokenInfo<T>>, LexError>;
+
+/// A function that parses with a [`TreeBuilder`]
+pub type ParseFn<T, N> = for<'a> fn(&mut TreeBuilder<'a, T, N>);
+
+/// Parses source text into [`SyntaxTree`]s, and reparses them incrementally after edits
+///
+/// An edit within a single token is handled by relexing just that token. Otherwise, the
+/// innermost node around the edit with a reparser (see [`reparsable`](Self::reparsable))
+/// is reparsed on its own. The reparsed subtree is only used if it has the same kind, no
+/// errors, and covers exactly the node's new text; if no node qualifies, the whole text
+/// is parsed again. Either way, the result is the same tree a full parse would produce,
+/// sharing every green node the edit didn't touch.
+pub struct CstParser<T, N> {
+    /// Lexes text
+    lex: LexFn<T>,
+    /// Parses a whole source text
+    root: ParseFn<T, N>,
+    /// Parsers for node kinds that can be reparsed on their own
+    reparsers: Vec<(N, ParseFn<T, N>)>,
+}
+
+impl<T: Clone + TokenGrammar, N: Copy + PartialEq> CstParser<T, N> {
+    /// Create a parser that lexes with `lex` and parses a whole text with `root`
+    ///
+    /// `root` must build exactly one node, the root of the tree.
+    #[must_use]
+    pub const fn new(lex: LexFn<T>, root: ParseFn<T, N>) -> Self {
+        Self {
+            lex,
+            root,
+            reparsers: Vec::new(),
+        }
+    }
+
+    /// Allow nodes of `kind` to be reparsed on their own with `parse`
+    ///
+    /// `parse` must build exactly one node, of kind `kind`, in the same way as the
+    /// parser for the whole text builds nodes of that kind. Kinds that are always
+    /// delimited, such as blocks, make the best candidates.
+    #[must_use]
+    pub fn reparsable(mut self, kind: N, parse: ParseFn<T, N>) -> Self {
+        self.reparsers.push((kind, parse));
+        self
+    }
+
+    /// Parse a whole source text
+    ///
+    /// # Errors
+    ///
+    /// Returns a `LexError` if `text` can't be lexed
+    pub fn parse(&self, text: &str) -> Result<SyntaxTree<T, N>, LexError> {
+        let tokens = (self.lex)(text)?;
+        let mut builder = TreeBuilder::new(text, tokens);
+        (self.root)(&mut builder);
+        let (green, errors) = builder.finish();
+        Ok(SyntaxTree::new(green, errors))
+    }
+
+    /// Apply `edit` to the text of `tree`, and parse the result, reusing as much of
+    /// `tree` as possible
+    ///
+    /// # Errors
+    ///
+    /// Returns a `LexError` if the edited text can't be lexed
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edit's range isn't within the tree's text
+    pub fn reparse(
+        &self,
+        tree: &SyntaxTree<T, N>,
+        edit: &TextEdit,
+    ) -> Result<SyntaxTree<T, N>, LexError> {
+        let root = tree.syntax();
+        if let Some(green) = self.reparse_token(&root, edit) {
+            let errors = shift_errors(tree.errors(), edit, None);
+            return Ok(SyntaxTree::new(green, errors));
+        }
+
+        let text = edit.apply(&tree.text());
+        if let Some((old, green)) = self.reparse_node(&root, edit, &text) {
+            let errors = shift_errors(tree.errors(), edit, Some(&old.range()));
+            return Ok(SyntaxTree::new(old.replace_with(green), errors));
+        }
+        self.parse(&text)
+    }
+
+    /// Reparse an edit within a single token by relexing it, producing the new root
+    fn reparse_token(&self, root: &SyntaxNode<T, N>, edit: &TextEdit) -> Option<GreenNode<T, N>> {
+        let token = root.token_at_offset(edit.range.start)?;
+        let range = token.range();
+        if edit.range.end > range.end {
+            return None;
+        }
+        let mut text = token.text().to_string();
+        text.replace_range(
+            edit.range.start - range.start..edit.range.end - range.start,
+            &edit.text,
+        );
+        if text.is_empty() {
+            return None;
+        }
+
+        // Lex the new text along with any adjacent tokens, which might merge with it
+        let adjacent = |token: Option<_>| {
+            token
+                .filter(|token: &super::SyntaxToken<T, N>| !token.is_trivia())
+                .map(|token| token.text().to_string())
+                .unwrap_or_default()
+        };
+        let before = adjacent(token.prev_token());
+        let after = adjacent(token.next_token());
+        let mut tokens = (self.lex)(&format!("{before}{text}{after}")).ok()?;
+        let start = before.len();
+        let end = start + text.len();
+        // The adjacent tokens must come out unchanged
+        if !after.is_empty() {
+            let last = tokens.pop()?;
+            if (last.start, last.end) != (end, end + after.len()) {
+                return None;
+            }
+        }
+        let mut tokens = tokens.into_iter();
+        if !before.is_empty() {
+            let first = tokens.next()?;
+            if (first.start, first.end) != (0, start) {
+                return None;
+            }
+        }
+        let new_kind = match (tokens.next(), tokens.next()) {
+            (None, _) => TokenKind::Trivia,
+            (Some(new), None) if (new.start, new.end) == (start, end) => TokenKind::Token(new.kind),
+            _ => return None,
+        };
+
+        let same = match (token.kind(), &new_kind) {
+            (TokenKind::Token(old), TokenKind::Token(new)) => same_kind(old, new),
+            (TokenKind::Trivia, TokenKind::Trivia) => true,
+            _ => false,
+        };
+        same.then(|| token.replace_with(GreenToken::new(new_kind, text)))
+    }
+
+    /// Reparse the innermost reparsable node around an edit, producing the old node and
+    /// its replacement
+    fn reparse_node(
+        &self,
+        root: &SyntaxNode<T, N>,
+        edit: &TextEdit,
+        text: &str,
+    ) -> Option<(SyntaxNode<T, N>, GreenNode<T, N>)> {
+        root.covering_node(edit.range.clone())
+            .ancestors()
+            .find_map(|node| {
+                let kind = node.kind();
+                let (_, parse) = self.reparsers.iter().find(|(other, _)| *other == kind)?;
+                let range = node.range();
+                let text = &text[range.start..edit.map_offset(range.end)];
+
+                let tokens = (self.lex)(text).ok()?;
+                let mut builder = TreeBuilder::new(text, tokens);
+                parse(&mut builder);
+                let (green, errors) = builder.finish();
+
+                // Trivia at the edges would belong to the parent in a full parse
+                let is_trivia = |child: Option<&GreenElement<T, N>>| {
+                    matches!(child, Some(GreenElement::Token(token)) if token.kind().is_trivia())
+                };
+                let children = green.children();
+                let valid = *green.kind() == kind
+                    && errors.is_empty()
+                    && !is_trivia(children.first())
+                    && !is_trivia(children.last());
+                valid.then_some((node, green))
+            })
+    }
+}
+
+/// Map errors through an edit, dropping those in `replaced` (a range before the edit)
+fn shift_errors(
+    errors: &[SyntaxError],
+    edit: &TextEdit,
+    replaced: Option<&Range<usize>>,
+) -> Vec<SyntaxError> {
+    errors
+        .iter()
+        .filter(|error| {
+            replaced.is_none_or(|replaced| {
+                error.range.start < replaced.start || error.range.end > replaced.end
+            })
+        })
+        .map(|error| SyntaxError {
+            range: edit.map_offset(error.range.start)..edit.map_offset(error.range.end),
+            message: error.message.clone(),
+        })
+        .collect()
+}
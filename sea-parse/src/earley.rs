@@ -0,0 +1,18 @@
+//! Earley parsing for ambiguous grammars
+//!
+//! The combinators and [`Pratt`](crate::Pratt) parse deterministically, committing to
+//! the first alternative that succeeds. [`EarleyParser`] instead accepts any
+//! context-free [`Grammar`] over token kinds, including left-recursive and ambiguous
+//! ones, and returns every parse at once as a shared packed parse forest ([`Forest`]).
+//! Disambiguation filters (priorities, associativity, and reject rules) prune the
+//! forest, ideally down to a single tree.
+
+mod build;
+mod chart;
+mod forest;
+mod grammar;
+mod parser;
+
+pub use forest::*;
+pub use grammar::*;
+pub use parser::*;
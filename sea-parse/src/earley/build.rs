@@ -0,0 +1,241 @@
+//! Building parse forests from the recognizer's chart
+
+use super::chart::Chart;
+use super::forest::{Alternative, Forest, ForestNode, NodeId};
+use super::grammar::RuleId;
+use super::parser::EarleyParser;
+use crate::{same_kind, span_of, Edge, ParseError};
+use sea_lex::{FileId, TokenInfo};
+use std::collections::HashMap;
+
+/// Builds a [`Forest`] from a recognizer's chart, applying the disambiguation filters
+pub struct ForestBuilder<'p, 'c, T> {
+    /// The parser
+    parser: &'p EarleyParser<T>,
+    /// The chart
+    chart: &'c Chart,
+    /// The file the tokens are from
+    file: FileId,
+    /// The tokens
+    tokens: Vec<TokenInfo<T>>,
+    /// The nodes built so far
+    nodes: Vec<ForestNode<T>>,
+    /// The index of the first token of each node
+    starts: Vec<usize>,
+    /// The node for each token, by index
+    token_nodes: HashMap<usize, NodeId>,
+    /// The node for each nonterminal over each range of tokens, or `None` if it has no
+    /// parse (or is being built)
+    symbol_nodes: HashMap<(usize, usize, usize), Option<NodeId>>,
+    /// The copy of each node without the alternatives forbidden at an edge of a rule
+    restricted: HashMap<(NodeId, usize, Edge), Option<NodeId>>,
+}
+
+impl<'p, 'c, T: Clone> ForestBuilder<'p, 'c, T> {
+    /// Create a builder for the parse of `tokens` recorded in `chart`
+    pub fn new(
+        parser: &'p EarleyParser<T>,
+        chart: &'c Chart,
+        file: FileId,
+        tokens: Vec<TokenInfo<T>>,
+    ) -> Self {
+        Self {
+            parser,
+            chart,
+            file,
+            tokens,
+            nodes: Vec::new(),
+            starts: Vec::new(),
+            token_nodes: HashMap::new(),
+            symbol_nodes: HashMap::new(),
+            restricted: HashMap::new(),
+        }
+    }
+
+    /// Build the forest
+    pub fn build(mut self) -> Result<Forest<'p, T>, ParseError<T>> {
+        let end = self.tokens.len();
+        let Some(root) = self.symbol(self.parser.start, 0, end) else {
+            return Err(ParseError::custom(
+                0,
+                span_of(self.file, &self.tokens, 0, end),
+                "Every parse was removed by the disambiguation filters".to_string(),
+            ));
+        };
+        Ok(Forest::new(
+            self.parser.grammar(),
+            self.nodes,
+            self.starts,
+            root,
+        ))
+    }
+
+    /// Add a node
+    fn push(&mut self, node: ForestNode<T>, start: usize) -> NodeId {
+        self.nodes.push(node);
+        self.starts.push(start);
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Get the node for the token at `index`
+    fn token(&mut self, index: usize) -> NodeId {
+        if let Some(&id) = self.token_nodes.get(&index) {
+            return id;
+        }
+        let token = self.tokens[index].clone();
+        let span = span_of(self.file, &self.tokens, index, index + 1);
+        let id = self.push(ForestNode::Token { token, span }, index);
+        self.token_nodes.insert(index, id);
+        id
+    }
+
+    /// Get the node for `nonterminal` over the tokens from `start` up to `end`, or
+    /// `None` if no parse survives the filters
+    fn symbol(&mut self, nonterminal: usize, start: usize, end: usize) -> Option<NodeId> {
+        let key = (nonterminal, start, end);
+        if let Some(&id) = self.symbol_nodes.get(&key) {
+            return id;
+        }
+        // Until it's built, the node has no parse, which cuts derivation cycles
+        self.symbol_nodes.insert(key, None);
+
+        let rules = self.chart.completed(end, nonterminal, start);
+        if rules.iter().any(|&rule| self.parser.rejected[rule]) {
+            return None;
+        }
+
+        let mut alternatives = Vec::new();
+        for &rule in rules {
+            for spans in self.splits(rule, self.parser.rhs[rule].len(), start, end) {
+                if let Some(children) = self.children(rule, &spans) {
+                    alternatives.push(Alternative {
+                        rule: RuleId(rule),
+                        children,
+                    });
+                }
+            }
+        }
+        if alternatives.is_empty() {
+            return None;
+        }
+
+        let name = self.parser.grammar().rule_by_id(alternatives[0].rule).lhs;
+        let span = span_of(self.file, &self.tokens, start, end);
+        let node = ForestNode::Symbol {
+            name,
+            span,
+            alternatives,
+        };
+        let id = self.push(node, start);
+        self.symbol_nodes.insert(key, Some(id));
+        Some(id)
+    }
+
+    /// Find every way to split the tokens from `start` up to `end` between the first
+    /// `count` symbols of `rule`, as the range of each symbol
+    fn splits(
+        &self,
+        rule: usize,
+        count: usize,
+        start: usize,
+        end: usize,
+    ) -> Vec<Vec<(usize, usize)>> {
+        if count == 0 {
+            return if start == end {
+                vec![Vec::new()]
+            } else {
+                Vec::new()
+            };
+        }
+        let mut splits = Vec::new();
+        for middle in start..=end {
+            // The symbols before the last one must match up to `middle`
+            let prefix_matches = if count == 1 {
+                middle == start
+            } else {
+                self.chart.has_item(middle, rule, count - 1, start)
+            };
+            if !prefix_matches || !self.matches(rule, count - 1, middle, end) {
+                continue;
+            }
+            for mut split in self.splits(rule, count - 1, start, middle) {
+                split.push((middle, end));
+                splits.push(split);
+            }
+        }
+        splits
+    }
+
+    /// Reports whether symbol `position` of `rule` can match the tokens from `start` up
+    /// to `end`, before filtering
+    fn matches(&self, rule: usize, position: usize, start: usize, end: usize) -> bool {
+        self.parser.rhs[rule][position].map_or_else(
+            || {
+                end == start + 1
+                    && same_kind(&self.tokens[start].kind, self.parser.token(rule, position))
+            },
+            |nonterminal| !self.chart.completed(end, nonterminal, start).is_empty(),
+        )
+    }
+
+    /// Build the children of `rule` over `spans`, or `None` if any of them has no parse
+    /// that survives the filters
+    fn children(&mut self, rule: usize, spans: &[(usize, usize)]) -> Option<Vec<NodeId>> {
+        let last = spans.len().checked_sub(1);
+        let mut children = Vec::with_capacity(spans.len());
+        for (position, &(start, end)) in spans.iter().enumerate() {
+            let Some(nonterminal) = self.parser.rhs[rule][position] else {
+                children.push(self.token(start));
+                continue;
+            };
+            let mut child = self.symbol(nonterminal, start, end)?;
+            if position == 0 {
+                child = self.restrict(child, rule, Edge::Left)?;
+            }
+            if Some(position) == last {
+                child = self.restrict(child, rule, Edge::Right)?;
+            }
+            children.push(child);
+        }
+        Some(children)
+    }
+
+    /// Remove the alternatives of `child` that are forbidden at `edge` of `rule`, or
+    /// return `None` if none are left
+    fn restrict(&mut self, child: NodeId, rule: usize, edge: Edge) -> Option<NodeId> {
+        let Some(forbidden) = self.parser.conflicts.get(&(rule, edge)) else {
+            return Some(child);
+        };
+        if let Some(&restricted) = self.restricted.get(&(child, rule, edge)) {
+            return restricted;
+        }
+
+        let ForestNode::Symbol {
+            name,
+            span,
+            alternatives,
+        } = &self.nodes[child.0]
+        else {
+            return Some(child);
+        };
+        let allowed: Vec<Alternative> = alternatives
+            .iter()
+            .filter(|alternative| !forbidden.contains(&alternative.rule.index()))
+            .cloned()
+            .collect();
+        let restricted = if allowed.len() == alternatives.len() {
+            Some(child)
+        } else if allowed.is_empty() {
+            None
+        } else {
+            let node = ForestNode::Symbol {
+                name,
+                span: *span,
+                alternatives: allowed,
+            };
+            Some(self.push(node, self.starts[child.0]))
+        };
+        self.restricted.insert((child, rule, edge), restricted);
+        restricted
+    }
+}
@@ -0,0 +1,192 @@
+//! The Earley recognizer
+
+use super::parser::EarleyParser;
+use crate::{same_kind, span_of, Expected, ParseError};
+use sea_lex::{FileId, TokenInfo};
+use std::collections::{HashMap, HashSet};
+
+/// A partially matched rule: `rule` has matched its first `dot` symbols, starting at
+/// token `origin`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Item {
+    /// The rule
+    rule: usize,
+    /// The number of symbols matched
+    dot: usize,
+    /// The index of the first token matched
+    origin: usize,
+}
+
+impl Item {
+    /// Get the item after matching one more symbol
+    const fn advance(self) -> Self {
+        Self {
+            dot: self.dot + 1,
+            ..self
+        }
+    }
+}
+
+/// The items ending before one token
+#[derive(Debug, Default)]
+struct EarleySet {
+    /// The items, in the order they were added
+    items: Vec<Item>,
+    /// The items, for deduplication
+    seen: HashSet<Item>,
+    /// The items waiting for each nonterminal
+    waiting: HashMap<usize, Vec<Item>>,
+    /// The rules completed for each nonterminal, by origin
+    completed: HashMap<(usize, usize), Vec<usize>>,
+}
+
+/// The Earley sets for an input, one more than there are tokens
+#[derive(Debug)]
+pub struct Chart {
+    /// The sets
+    sets: Vec<EarleySet>,
+}
+
+impl Chart {
+    /// Run the recognizer over `tokens`
+    ///
+    /// Nullable nonterminals are handled as described by Aycock and Horspool ("Practical
+    /// Earley Parsing", 2002): predicting a nullable nonterminal also skips over it.
+    pub fn recognize<T: Clone>(
+        parser: &EarleyParser<T>,
+        file: FileId,
+        tokens: &[TokenInfo<T>],
+    ) -> Result<Self, ParseError<T>> {
+        let mut chart = Self {
+            sets: (0..=tokens.len()).map(|_| EarleySet::default()).collect(),
+        };
+        for &rule in &parser.rules_of[parser.start] {
+            chart.add(
+                parser,
+                0,
+                Item {
+                    rule,
+                    dot: 0,
+                    origin: 0,
+                },
+            );
+        }
+
+        for position in 0..=tokens.len() {
+            let mut next = 0;
+            while let Some(&item) = chart.sets[position].items.get(next) {
+                next += 1;
+                match parser.rhs[item.rule].get(item.dot) {
+                    None => {
+                        let nonterminal = parser.lhs[item.rule];
+                        chart.sets[position]
+                            .completed
+                            .entry((nonterminal, item.origin))
+                            .or_default()
+                            .push(item.rule);
+                        let waiting = chart.sets[item.origin]
+                            .waiting
+                            .get(&nonterminal)
+                            .cloned()
+                            .unwrap_or_default();
+                        for waiting in waiting {
+                            chart.add(parser, position, waiting.advance());
+                        }
+                    }
+                    Some(Some(nonterminal)) => {
+                        for &rule in &parser.rules_of[*nonterminal] {
+                            let predicted = Item {
+                                rule,
+                                dot: 0,
+                                origin: position,
+                            };
+                            chart.add(parser, position, predicted);
+                        }
+                        if parser.nullable[*nonterminal] {
+                            chart.add(parser, position, item.advance());
+                        }
+                    }
+                    Some(None) => {
+                        let kind = parser.token(item.rule, item.dot);
+                        if tokens
+                            .get(position)
+                            .is_some_and(|token| same_kind(&token.kind, kind))
+                        {
+                            chart.add(parser, position + 1, item.advance());
+                        }
+                    }
+                }
+            }
+
+            let stuck = position < tokens.len() && chart.sets[position + 1].items.is_empty();
+            if stuck || (position == tokens.len() && !chart.accepts(parser)) {
+                return Err(chart.error(parser, file, tokens, position));
+            }
+        }
+        Ok(chart)
+    }
+
+    /// Add an item to a set, if it isn't already there
+    fn add<T>(&mut self, parser: &EarleyParser<T>, position: usize, item: Item) {
+        let set = &mut self.sets[position];
+        if !set.seen.insert(item) {
+            return;
+        }
+        set.items.push(item);
+        if let Some(Some(nonterminal)) = parser.rhs[item.rule].get(item.dot) {
+            set.waiting.entry(*nonterminal).or_default().push(item);
+        }
+    }
+
+    /// Reports whether the start nonterminal matches the whole input
+    fn accepts<T>(&self, parser: &EarleyParser<T>) -> bool {
+        self.sets
+            .last()
+            .is_some_and(|set| set.completed.contains_key(&(parser.start, 0)))
+    }
+
+    /// Build the error for an input that can't continue past token `position`
+    fn error<T: Clone>(
+        &self,
+        parser: &EarleyParser<T>,
+        file: FileId,
+        tokens: &[TokenInfo<T>],
+        position: usize,
+    ) -> ParseError<T> {
+        let set = &self.sets[position];
+        let mut expected: Vec<Expected<T>> = Vec::new();
+        for item in &set.items {
+            if parser.rhs[item.rule].get(item.dot) == Some(&None) {
+                let kind = Expected::Token(parser.token(item.rule, item.dot).clone());
+                if !expected.contains(&kind) {
+                    expected.push(kind);
+                }
+            }
+        }
+        if set.completed.contains_key(&(parser.start, 0)) {
+            expected.push(Expected::End);
+        }
+        ParseError::unexpected(
+            position,
+            span_of(file, tokens, position, position + 1),
+            tokens.get(position).map(|token| token.kind.clone()),
+            expected,
+        )
+    }
+
+    /// Reports whether the set ending before token `position` holds `rule` with `dot`
+    /// symbols matched from `origin`
+    pub fn has_item(&self, position: usize, rule: usize, dot: usize, origin: usize) -> bool {
+        self.sets[position]
+            .seen
+            .contains(&Item { rule, dot, origin })
+    }
+
+    /// Get the rules of `nonterminal` that match the tokens from `origin` up to `end`
+    pub fn completed(&self, end: usize, nonterminal: usize, origin: usize) -> &[usize] {
+        self.sets[end]
+            .completed
+            .get(&(nonterminal, origin))
+            .map_or(&[], Vec::as_slice)
+    }
+}
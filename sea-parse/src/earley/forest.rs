@@ -0,0 +1,209 @@
+//! Shared packed parse forests
+
+use super::grammar::{Grammar, Rule, RuleId};
+use crate::ParseError;
+use sea_lex::{Span, TokenInfo};
+use std::collections::{HashMap, HashSet};
+
+/// The index of a node in a [`Forest`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub(crate) usize);
+
+/// One way to derive a symbol node: a rule, and a node for each of its symbols
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alternative {
+    /// The rule
+    pub rule: RuleId,
+    /// The nodes matching the rule's symbols, in order
+    pub children: Vec<NodeId>,
+}
+
+/// A node in a [`Forest`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForestNode<T> {
+    /// A token
+    Token {
+        /// The token
+        token: TokenInfo<T>,
+        /// The location of the token
+        span: Span,
+    },
+    /// A nonterminal matching some tokens, in one or more ways
+    Symbol {
+        /// The nonterminal
+        name: &'static str,
+        /// The location of the tokens
+        span: Span,
+        /// Every way to derive the nonterminal from the tokens
+        alternatives: Vec<Alternative>,
+    },
+}
+
+impl<T> ForestNode<T> {
+    /// Get the location of the node
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        match self {
+            Self::Token { span, .. } | Self::Symbol { span, .. } => *span,
+        }
+    }
+}
+
+/// A shared packed parse forest: every parse of an input, in one graph
+///
+/// Each symbol node stands for one nonterminal matching one range of tokens, and
+/// lists every [`Alternative`] way to derive it. Nodes are shared between the parses
+/// that contain them, so a forest of exponentially many trees takes polynomial space.
+///
+/// Derivations in which a nonterminal derives itself over the same tokens, which only
+/// cyclic grammars (like `A ::= A`) allow, are left out, so the forest has no cycles.
+#[derive(Debug, Clone)]
+pub struct Forest<'g, T> {
+    /// The grammar the forest was parsed with
+    grammar: &'g Grammar<T>,
+    /// The nodes
+    nodes: Vec<ForestNode<T>>,
+    /// The index of the first token of each node
+    starts: Vec<usize>,
+    /// The node for the start nonterminal over the whole input
+    root: NodeId,
+}
+
+impl<'g, T> Forest<'g, T> {
+    /// Create a forest from its nodes
+    pub(crate) const fn new(
+        grammar: &'g Grammar<T>,
+        nodes: Vec<ForestNode<T>>,
+        starts: Vec<usize>,
+        root: NodeId,
+    ) -> Self {
+        Self {
+            grammar,
+            nodes,
+            starts,
+            root,
+        }
+    }
+
+    /// Get the node for the start nonterminal over the whole input
+    #[must_use]
+    pub const fn root(&self) -> NodeId {
+        self.root
+    }
+
+    /// Get a node
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node isn't in this forest
+    #[must_use]
+    pub fn node(&self, id: NodeId) -> &ForestNode<T> {
+        &self.nodes[id.0]
+    }
+
+    /// Get a rule of the grammar the forest was parsed with
+    #[must_use]
+    pub fn rule(&self, id: RuleId) -> &'g Rule<T> {
+        self.grammar.rule_by_id(id)
+    }
+
+    /// Reports whether the input has more than one parse
+    #[must_use]
+    pub fn is_ambiguous(&self) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![self.root];
+        while let Some(id) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            if let ForestNode::Symbol { alternatives, .. } = self.node(id) {
+                if alternatives.len() > 1 {
+                    return true;
+                }
+                stack.extend(
+                    alternatives
+                        .iter()
+                        .flat_map(|alternative| &alternative.children),
+                );
+            }
+        }
+        false
+    }
+
+    /// Count the parse trees in the forest, saturating at `u128::MAX`
+    #[must_use]
+    pub fn tree_count(&self) -> u128 {
+        /// Count the trees of one node, memoizing in `counts`
+        fn count<T>(
+            forest: &Forest<'_, T>,
+            id: NodeId,
+            counts: &mut HashMap<NodeId, u128>,
+        ) -> u128 {
+            if let Some(&count) = counts.get(&id) {
+                return count;
+            }
+            let total = match forest.node(id) {
+                ForestNode::Token { .. } => 1,
+                ForestNode::Symbol { alternatives, .. } => {
+                    alternatives.iter().fold(0u128, |total, alternative| {
+                        let trees = alternative.children.iter().fold(1u128, |trees, &child| {
+                            trees.saturating_mul(count(forest, child, counts))
+                        });
+                        total.saturating_add(trees)
+                    })
+                }
+            };
+            counts.insert(id, total);
+            total
+        }
+
+        count(self, self.root, &mut HashMap::new())
+    }
+
+    /// Build a value from the forest's only parse tree, bottom-up
+    ///
+    /// `token` builds the value of a token, and `node` the value of a nonterminal from
+    /// the rule that derived it, its location, and the values of its children.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error at the first node found with more than one alternative
+    pub fn fold<O>(
+        &self,
+        mut token: impl FnMut(&TokenInfo<T>, Span) -> O,
+        mut node: impl FnMut(&Rule<T>, Span, Vec<O>) -> O,
+    ) -> Result<O, ParseError<T>> {
+        self.fold_node(self.root, &mut token, &mut node)
+    }
+
+    /// Build the value of one node. See [`fold`](Self::fold).
+    fn fold_node<O>(
+        &self,
+        id: NodeId,
+        token: &mut impl FnMut(&TokenInfo<T>, Span) -> O,
+        node: &mut impl FnMut(&Rule<T>, Span, Vec<O>) -> O,
+    ) -> Result<O, ParseError<T>> {
+        match self.node(id) {
+            ForestNode::Token { token: info, span } => Ok(token(info, *span)),
+            ForestNode::Symbol {
+                name,
+                span,
+                alternatives,
+            } => {
+                let [alternative] = alternatives.as_slice() else {
+                    return Err(ParseError::custom(
+                        self.starts[id.0],
+                        *span,
+                        format!("Ambiguous {name}: {} alternatives", alternatives.len()),
+                    ));
+                };
+                let children = alternative
+                    .children
+                    .iter()
+                    .map(|&child| self.fold_node(child, token, node))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(node(self.rule(alternative.rule), *span, children))
+            }
+        }
+    }
+}
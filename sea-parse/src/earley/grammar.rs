@@ -0,0 +1,220 @@
+//! Grammars for the Earley parser
+
+use crate::{kind_name, Assoc};
+use std::fmt;
+
+/// A symbol on the right-hand side of a [`Rule`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Symbol<T> {
+    /// A token of the same variant as the given kind
+    ///
+    /// Only the variant matters: `Number(0)` stands for any `Number`.
+    Token(T),
+    /// A nonterminal, by name
+    Rule(&'static str),
+}
+
+/// A symbol matching a token of the same variant as `kind`
+pub const fn term<T>(kind: T) -> Symbol<T> {
+    Symbol::Token(kind)
+}
+
+/// A symbol matching the nonterminal `name`
+#[must_use]
+pub const fn nonterm<T>(name: &'static str) -> Symbol<T> {
+    Symbol::Rule(name)
+}
+
+/// The index of a rule in its [`Grammar`], in the order the rules were added
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RuleId(pub(crate) usize);
+
+impl RuleId {
+    /// Get the index of the rule
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A grammar rule: `lhs ::= rhs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule<T> {
+    /// The rule's name, which disambiguation filters refer to it by
+    pub name: Option<&'static str>,
+    /// The nonterminal the rule defines
+    pub lhs: &'static str,
+    /// The symbols the nonterminal derives, in order (empty for an ε-rule)
+    pub rhs: Vec<Symbol<T>>,
+}
+
+impl<T: fmt::Debug> fmt::Display for Rule<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ::=", self.lhs)?;
+        if self.rhs.is_empty() {
+            return write!(f, " ε");
+        }
+        for symbol in &self.rhs {
+            match symbol {
+                Symbol::Token(kind) => write!(f, " {}", kind_name(kind))?,
+                Symbol::Rule(name) => write!(f, " {name}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A context-free grammar over token kinds, with disambiguation filters
+///
+/// Nonterminals are named by strings and defined by their rules; any grammar is
+/// accepted, including left-recursive, ambiguous, and ε-rules. Filters refer to rules
+/// by the names given in [`named_rule`](Self::named_rule):
+/// - [`priority`](Self::priority) forbids a lower-priority rule as the leftmost or
+///   rightmost child of a higher-priority one, so `a + b * c` can't be `(a + b) * c`;
+/// - [`assoc`](Self::assoc) forbids rules in a group as each other's rightmost child
+///   (for [`Assoc::Left`]), leftmost child ([`Assoc::Right`]), or either
+///   ([`Assoc::None`]);
+/// - [`reject`](Self::reject) removes every parse of a nonterminal over tokens that a
+///   reject rule also matches, like keywords that aren't identifiers.
+#[derive(Debug, Clone)]
+pub struct Grammar<T> {
+    /// The start nonterminal
+    start: &'static str,
+    /// The rules, indexed by [`RuleId`]
+    rules: Vec<Rule<T>>,
+    /// Pairs of rule groups, where the first has higher priority than the second
+    pub(crate) priorities: Vec<(Vec<&'static str>, Vec<&'static str>)>,
+    /// Groups of rules with an associativity
+    pub(crate) associativity: Vec<(Assoc, Vec<&'static str>)>,
+    /// The reject rules
+    pub(crate) rejects: Vec<&'static str>,
+}
+
+impl<T> Grammar<T> {
+    /// Create an empty grammar for the nonterminal `start`
+    #[must_use]
+    pub const fn new(start: &'static str) -> Self {
+        Self {
+            start,
+            rules: Vec::new(),
+            priorities: Vec::new(),
+            associativity: Vec::new(),
+            rejects: Vec::new(),
+        }
+    }
+
+    /// Add the rule `lhs ::= rhs`
+    #[must_use]
+    pub fn rule(mut self, lhs: &'static str, rhs: impl IntoIterator<Item = Symbol<T>>) -> Self {
+        self.rules.push(Rule {
+            name: None,
+            lhs,
+            rhs: rhs.into_iter().collect(),
+        });
+        self
+    }
+
+    /// Add the rule `lhs ::= rhs`, named `name` for disambiguation filters
+    #[must_use]
+    pub fn named_rule(
+        mut self,
+        name: &'static str,
+        lhs: &'static str,
+        rhs: impl IntoIterator<Item = Symbol<T>>,
+    ) -> Self {
+        self.rules.push(Rule {
+            name: Some(name),
+            lhs,
+            rhs: rhs.into_iter().collect(),
+        });
+        self
+    }
+
+    /// Give the rules named in `higher` priority over those named in `lower`
+    ///
+    /// Priorities are transitive: declaring `*` over `+` and `+` over `==` also gives `*`
+    /// priority over `==`.
+    #[must_use]
+    pub fn priority(
+        mut self,
+        higher: impl IntoIterator<Item = &'static str>,
+        lower: impl IntoIterator<Item = &'static str>,
+    ) -> Self {
+        self.priorities
+            .push((higher.into_iter().collect(), lower.into_iter().collect()));
+        self
+    }
+
+    /// Make the rules named in `rules` associative with each other
+    #[must_use]
+    pub fn assoc(mut self, assoc: Assoc, rules: impl IntoIterator<Item = &'static str>) -> Self {
+        self.associativity
+            .push((assoc, rules.into_iter().collect()));
+        self
+    }
+
+    /// Make the rule named `rule` a reject rule
+    ///
+    /// A reject rule's parses are never part of the forest, and its nonterminal has no
+    /// parse at all over tokens the reject rule matches.
+    #[must_use]
+    pub fn reject(mut self, rule: &'static str) -> Self {
+        self.rejects.push(rule);
+        self
+    }
+
+    /// Get the start nonterminal
+    #[must_use]
+    pub const fn start(&self) -> &'static str {
+        self.start
+    }
+
+    /// Get the rules, indexed by [`RuleId`]
+    #[must_use]
+    pub fn rules(&self) -> &[Rule<T>] {
+        &self.rules
+    }
+
+    /// Get a rule
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule isn't in this grammar
+    #[must_use]
+    pub fn rule_by_id(&self, id: RuleId) -> &Rule<T> {
+        &self.rules[id.0]
+    }
+}
+
+/// An error in a [`Grammar`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarError {
+    /// A nonterminal is used, or is the start, but has no rules
+    UndefinedNonterminal(&'static str),
+    /// A filter refers to a rule name that no rule has
+    UnknownRule(&'static str),
+    /// More than one rule has the same name
+    DuplicateRule(&'static str),
+    /// A reject rule is used with a parser that can't apply it
+    UnsupportedReject(&'static str),
+    /// A nonterminal can't derive any finite sequence of tokens
+    Unproductive(&'static str),
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UndefinedNonterminal(name) => write!(f, "Nonterminal {name} has no rules"),
+            Self::UnknownRule(name) => write!(f, "No rule is named {name}"),
+            Self::DuplicateRule(name) => write!(f, "More than one rule is named {name}"),
+            Self::UnsupportedReject(name) => {
+                write!(f, "Reject rule {name} needs an Earley parser")
+            }
+            Self::Unproductive(name) => {
+                write!(f, "Nonterminal {name} can't derive any sequence of tokens")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GrammarError {}
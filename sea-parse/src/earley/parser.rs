@@ -0,0 +1,261 @@
+//! Compiling grammars for the Earley parser
+
+use super::build::ForestBuilder;
+use super::chart::Chart;
+use super::forest::Forest;
+use super::grammar::{Grammar, GrammarError, Symbol};
+use crate::{Assoc, Edge, ParseError};
+use sea_lex::{FileId, TokenInfo};
+use std::collections::{HashMap, HashSet};
+
+/// A parser for any context-free [`Grammar`], producing a shared packed parse forest
+///
+/// Parsing takes cubic time in the number of tokens at worst, linear time for most
+/// unambiguous grammars, and quadratic for the rest.
+///
+/// ```
+/// # use sea_lex::{SourceDb, Token};
+/// # use sea_parse::{nonterm, term, Assoc, EarleyParser, Grammar};
+/// # #[derive(Debug, Clone, PartialEq, Token)]
+/// # #[skip(r"\s+")]
+/// # enum Tok {
+/// #     #[token(r"\d+", str::parse)]
+/// #     Number(i64),
+/// #     #[token("+")]
+/// #     Plus,
+/// # }
+/// let grammar = Grammar::new("Expr")
+///     .named_rule("add", "Expr", [nonterm("Expr"), term(Tok::Plus), nonterm("Expr")])
+///     .rule("Expr", [term(Tok::Number(0))])
+///     .assoc(Assoc::Left, ["add"]);
+/// let parser = EarleyParser::new(grammar)?;
+///
+/// let mut db = SourceDb::new();
+/// let file = db.add("main", "1 + 2 + 3");
+/// let forest = parser.parse(file, Tok::lexer(db.file(file).contents()).collect()?)?;
+/// let value = forest.fold(
+///     |token, _| match token.kind {
+///         Tok::Number(value) => value,
+///         _ => 0,
+///     },
+///     |_, _, children| children.iter().sum(),
+/// )?;
+/// assert_eq!(value, 6);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct EarleyParser<T> {
+    /// The grammar
+    grammar: Grammar<T>,
+    /// The start nonterminal
+    pub(crate) start: usize,
+    /// The nonterminal each rule defines
+    pub(crate) lhs: Vec<usize>,
+    /// Each rule's right-hand side, with the nonterminal of each nonterminal symbol
+    pub(crate) rhs: Vec<Vec<Option<usize>>>,
+    /// The rules of each nonterminal
+    pub(crate) rules_of: Vec<Vec<usize>>,
+    /// Whether each nonterminal can derive no tokens
+    pub(crate) nullable: Vec<bool>,
+    /// The rules forbidden as the child at each edge of each rule
+    pub(crate) conflicts: HashMap<(usize, Edge), HashSet<usize>>,
+    /// Whether each rule is a reject rule
+    pub(crate) rejected: Vec<bool>,
+}
+
+impl<T> EarleyParser<T> {
+    /// Create a parser for `grammar`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a nonterminal has no rules, or if a filter refers to a rule
+    /// name that isn't unique
+    pub fn new(grammar: Grammar<T>) -> Result<Self, GrammarError> {
+        let mut nonterminals: HashMap<&'static str, usize> = HashMap::new();
+        let mut rules_of: Vec<Vec<usize>> = Vec::new();
+        let mut lhs = Vec::with_capacity(grammar.rules().len());
+        for (index, rule) in grammar.rules().iter().enumerate() {
+            let next = nonterminals.len();
+            let nonterminal = *nonterminals.entry(rule.lhs).or_insert(next);
+            if nonterminal == rules_of.len() {
+                rules_of.push(Vec::new());
+            }
+            rules_of[nonterminal].push(index);
+            lhs.push(nonterminal);
+        }
+
+        let lookup = |name: &'static str| {
+            nonterminals
+                .get(name)
+                .copied()
+                .ok_or(GrammarError::UndefinedNonterminal(name))
+        };
+        let start = lookup(grammar.start())?;
+        let rhs = grammar
+            .rules()
+            .iter()
+            .map(|rule| {
+                rule.rhs
+                    .iter()
+                    .map(|symbol| match symbol {
+                        Symbol::Token(_) => Ok(None),
+                        Symbol::Rule(name) => lookup(name).map(Some),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut named: HashMap<&'static str, usize> = HashMap::new();
+        for (index, rule) in grammar.rules().iter().enumerate() {
+            if let Some(name) = rule.name {
+                if named.insert(name, index).is_some() {
+                    return Err(GrammarError::DuplicateRule(name));
+                }
+            }
+        }
+        let find = |name: &'static str| {
+            named
+                .get(name)
+                .copied()
+                .ok_or(GrammarError::UnknownRule(name))
+        };
+
+        let mut rejected = vec![false; grammar.rules().len()];
+        for &name in &grammar.rejects {
+            rejected[find(name)?] = true;
+        }
+
+        let mut conflicts: HashMap<(usize, Edge), HashSet<usize>> = HashMap::new();
+        let mut forbid = |parent: usize, edges: &[Edge], child: usize| {
+            for &edge in edges {
+                conflicts.entry((parent, edge)).or_default().insert(child);
+            }
+        };
+        for (higher, lower) in priority_closure(&grammar, &find)? {
+            forbid(higher, &[Edge::Left, Edge::Right], lower);
+        }
+        for (assoc, names) in &grammar.associativity {
+            let edges: &[Edge] = match assoc {
+                Assoc::Left => &[Edge::Right],
+                Assoc::Right => &[Edge::Left],
+                Assoc::None => &[Edge::Left, Edge::Right],
+            };
+            let group = names
+                .iter()
+                .map(|&name| find(name))
+                .collect::<Result<Vec<_>, _>>()?;
+            for &parent in &group {
+                for &child in &group {
+                    forbid(parent, edges, child);
+                }
+            }
+        }
+
+        let nullable = nullable(&rules_of, &rhs);
+        Ok(Self {
+            grammar,
+            start,
+            lhs,
+            rhs,
+            rules_of,
+            nullable,
+            conflicts,
+            rejected,
+        })
+    }
+
+    /// Get the grammar
+    #[must_use]
+    pub const fn grammar(&self) -> &Grammar<T> {
+        &self.grammar
+    }
+
+    /// Take back the grammar
+    pub(crate) fn into_grammar(self) -> Grammar<T> {
+        self.grammar
+    }
+
+    /// Get the token kind of a rule's token symbol
+    pub(crate) fn token(&self, rule: usize, position: usize) -> &T {
+        match &self.grammar.rules()[rule].rhs[position] {
+            Symbol::Token(kind) => kind,
+            Symbol::Rule(_) => unreachable!("the symbol is a nonterminal"),
+        }
+    }
+
+    /// Parse `tokens` from `file`, producing every parse
+    ///
+    /// # Errors
+    ///
+    /// Returns an error at the first token that can't continue any parse (or at the end
+    /// of the input, if it ends too soon), with every token that could have, or an
+    /// error covering the whole input if the disambiguation filters removed every parse
+    pub fn parse(
+        &self,
+        file: FileId,
+        tokens: impl IntoIterator<Item = TokenInfo<T>>,
+    ) -> Result<Forest<'_, T>, ParseError<T>>
+    where
+        T: Clone,
+    {
+        let tokens: Vec<TokenInfo<T>> = tokens.into_iter().collect();
+        let chart = Chart::recognize(self, file, &tokens)?;
+        ForestBuilder::new(self, &chart, file, tokens).build()
+    }
+}
+
+/// Resolve the priority declarations of `grammar` into a transitive relation between
+/// rules, as `(higher, lower)` pairs
+fn priority_closure<T>(
+    grammar: &Grammar<T>,
+    find: &impl Fn(&'static str) -> Result<usize, GrammarError>,
+) -> Result<HashSet<(usize, usize)>, GrammarError> {
+    let mut pairs = HashSet::new();
+    for (higher, lower) in &grammar.priorities {
+        for &higher in higher {
+            for &lower in lower {
+                pairs.insert((find(higher)?, find(lower)?));
+            }
+        }
+    }
+    loop {
+        let implied: Vec<_> = pairs
+            .iter()
+            .flat_map(|&(a, b)| {
+                pairs
+                    .iter()
+                    .filter(move |&&(c, _)| c == b)
+                    .map(move |&(_, d)| (a, d))
+            })
+            .filter(|pair| !pairs.contains(pair))
+            .collect();
+        if implied.is_empty() {
+            return Ok(pairs);
+        }
+        pairs.extend(implied);
+    }
+}
+
+/// Find the nonterminals that can derive no tokens
+fn nullable(rules_of: &[Vec<usize>], rhs: &[Vec<Option<usize>>]) -> Vec<bool> {
+    let mut nullable = vec![false; rules_of.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (nonterminal, rules) in rules_of.iter().enumerate() {
+            if nullable[nonterminal] {
+                continue;
+            }
+            let derives_empty = rules.iter().any(|&rule| {
+                rhs[rule]
+                    .iter()
+                    .all(|symbol| symbol.is_some_and(|symbol| nullable[symbol]))
+            });
+            if derives_empty {
+                nullable[nonterminal] = true;
+                changed = true;
+            }
+        }
+    }
+    nullable
+}
@@ -0,0 +1,170 @@
+//! Error types for sea-parse
+
+use sea_lex::{Span, TokenGrammar};
+use std::fmt;
+use std::mem::discriminant;
+
+/// Something a parser expected to find
+#[derive(Debug, Clone)]
+pub enum Expected<T> {
+    /// A token of the given kind
+    ///
+    /// Only the variant matters: `Number(0)` stands for any `Number`.
+    Token(T),
+    /// A named construct, such as "expression"
+    Label(&'static str),
+    /// The end of the input
+    End,
+}
+
+/// Error that can occur during parsing
+#[derive(Debug, Clone)]
+pub struct ParseError<T> {
+    /// The location of the error: the unexpected token, or the end of the input
+    pub span: Span,
+    /// The kind of the unexpected token, or `None` at the end of the input
+    pub found: Option<T>,
+    /// Everything that would have been accepted at this location
+    pub expected: Vec<Expected<T>>,
+    /// A custom message, which replaces the "expected ... found ..." description
+    pub message: Option<String>,
+    /// A suggested fix, if the parser recovered by repairing the input
+    pub repair: Option<Box<Repair<T>>>,
+    /// The index of the token at which the error occurred
+    index: usize,
+}
+
+/// A suggested fix for a syntax error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair<T> {
+    /// Insert a token of the given kind
+    Insert {
+        /// The kind of token to insert
+        kind: T,
+        /// Where to insert it (an empty span)
+        span: Span,
+    },
+    /// Delete the tokens in a span
+    Delete {
+        /// The tokens to delete
+        span: Span,
+    },
+}
+
+/// The result of running a parser
+pub type ParseResult<T, O> = Result<O, ParseError<T>>;
+
+impl<T> PartialEq for Expected<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Token(a), Self::Token(b)) => discriminant(a) == discriminant(b),
+            (Self::Label(a), Self::Label(b)) => a == b,
+            (Self::End, Self::End) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T> ParseError<T> {
+    /// Create an error for an unexpected token (or end of input)
+    pub(crate) const fn unexpected(
+        index: usize,
+        span: Span,
+        found: Option<T>,
+        expected: Vec<Expected<T>>,
+    ) -> Self {
+        Self {
+            span,
+            found,
+            expected,
+            message: None,
+            repair: None,
+            index,
+        }
+    }
+
+    /// Create an error with a custom message
+    pub(crate) const fn custom(index: usize, span: Span, message: String) -> Self {
+        Self {
+            span,
+            found: None,
+            expected: Vec::new(),
+            message: Some(message),
+            repair: None,
+            index,
+        }
+    }
+
+    /// Attach a suggested fix to this error
+    #[must_use]
+    pub(crate) fn with_repair(mut self, repair: Repair<T>) -> Self {
+        self.repair = Some(Box::new(repair));
+        self
+    }
+
+    /// Get the index of the token at which the error occurred
+    pub(crate) const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Combine two errors, keeping the one that got further into the input
+    ///
+    /// Errors at the same position have their expected sets merged.
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        if other.index > self.index {
+            return other;
+        }
+        if other.index == self.index {
+            for expected in other.expected {
+                if !self.expected.contains(&expected) {
+                    self.expected.push(expected);
+                }
+            }
+            self.message = self.message.or(other.message);
+        }
+        self
+    }
+}
+
+impl<T: TokenGrammar> fmt::Display for Expected<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Token(kind) => write!(f, "{}", kind.describe()),
+            Self::Label(label) => write!(f, "{label}"),
+            Self::End => write!(f, "end of input"),
+        }
+    }
+}
+
+impl<T: TokenGrammar> fmt::Display for ParseError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(message) = &self.message {
+            return write!(f, "{message}");
+        }
+
+        let found = self
+            .found
+            .as_ref()
+            .map_or_else(|| "end of input".to_string(), TokenGrammar::describe);
+        match self.expected.as_slice() {
+            [] => write!(f, "Unexpected {found}"),
+            [expected] => write!(f, "Expected {expected}, found {found}"),
+            expected => {
+                let expected: Vec<_> = expected.iter().map(ToString::to_string).collect();
+                write!(f, "Expected one of {}, found {found}", expected.join(", "))
+            }
+        }
+    }
+}
+
+impl<T: TokenGrammar> fmt::Display for Repair<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Insert { kind, .. } => write!(f, "insert {}", kind.describe()),
+            Self::Delete { .. } => write!(f, "remove the unexpected tokens"),
+        }
+    }
+}
+
+impl<T: fmt::Debug + TokenGrammar> std::error::Error for ParseError<T> {}
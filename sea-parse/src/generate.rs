@@ -0,0 +1,349 @@
+//! Random programs from grammars, for fuzzing
+
+use crate::{EarleyParser, Edge, Grammar, GrammarError};
+use std::collections::HashSet;
+
+/// A source of the decisions a [`Generator`] makes
+pub trait Choices {
+    /// Choose a number below `bound`, which is at least 1
+    fn below(&mut self, bound: u32) -> u32;
+}
+
+/// Pseudo-random [`Choices`] from a seed, for reproducible tests
+#[derive(Debug, Clone)]
+pub struct SeededChoices {
+    /// The generator's state
+    state: u64,
+}
+
+impl SeededChoices {
+    /// Create a source of choices from `seed`
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl Choices for SeededChoices {
+    fn below(&mut self, bound: u32) -> u32 {
+        // SplitMix64
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        u32::try_from(z % u64::from(bound)).unwrap_or(0)
+    }
+}
+
+/// [`Choices`] read from a fuzzer's input bytes
+///
+/// Each choice reads as few bytes as its bound needs. Once the bytes run out, every
+/// choice is 0, which the [`Generator`] uses to finish the program quickly.
+#[derive(Debug, Clone)]
+pub struct ByteChoices<'a> {
+    /// The bytes that haven't been read yet
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteChoices<'a> {
+    /// Create a source of choices from `bytes`
+    #[must_use]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+}
+
+impl Choices for ByteChoices<'_> {
+    fn below(&mut self, bound: u32) -> u32 {
+        if bound <= 1 {
+            return 0;
+        }
+        let needed = (u32::BITS - (bound - 1).leading_zeros()).div_ceil(8) as usize;
+        let (read, rest) = self.bytes.split_at(needed.min(self.bytes.len()));
+        self.bytes = rest;
+        let value = read
+            .iter()
+            .fold(0, |value: u32, &byte| (value << 8) | u32::from(byte));
+        value % bound
+    }
+}
+
+/// Generates random token sequences that a [`Grammar`] accepts
+///
+/// Each sequence is built from a random derivation tree. Derivations are kept within
+/// a maximum depth and, once a maximum number of tokens has been generated, finish
+/// with their shortest rules; rules can be weighted to make them more or less common.
+/// Derivations respect the grammar's priority and associativity filters, so every
+/// sequence has a parse that an [`EarleyParser`] keeps. Reject rules are never
+/// expanded, but a sequence may still happen to match one.
+///
+/// ```
+/// # use sea_parse::{nonterm, term, Generator, Grammar, SeededChoices};
+/// # #[derive(Debug, Clone, PartialEq)]
+/// # enum Tok {
+/// #     Number(i64),
+/// #     Plus,
+/// # }
+/// let grammar = Grammar::new("Expr")
+///     .named_rule("add", "Expr", [nonterm("Expr"), term(Tok::Plus), nonterm("Expr")])
+///     .rule("Expr", [term(Tok::Number(0))]);
+/// let generator = Generator::new(grammar)?.max_depth(4).weight("add", 3)?;
+/// let source = generator.generate_source(&mut SeededChoices::new(1), |token, _| match token {
+///     Tok::Number(_) => "1".to_string(),
+///     Tok::Plus => "+".to_string(),
+/// });
+/// assert!(source.starts_with('1') && source.ends_with('1'));
+/// # Ok::<(), sea_parse::GrammarError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Generator<T> {
+    /// The grammar, analyzed
+    parser: EarleyParser<T>,
+    /// The weight of each rule
+    weights: Vec<u32>,
+    /// The smallest depth of a derivation from each rule
+    depths: Vec<usize>,
+    /// The fewest tokens a derivation from each rule can have
+    sizes: Vec<usize>,
+    /// The maximum depth of a derivation
+    max_depth: usize,
+    /// The number of tokens after which derivations finish as soon as they can
+    max_tokens: usize,
+}
+
+impl<T> Generator<T> {
+    /// Create a generator for `grammar`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grammar isn't valid for an [`EarleyParser`], or if a
+    /// nonterminal can't derive any sequence of tokens
+    pub fn new(grammar: Grammar<T>) -> Result<Self, GrammarError> {
+        let parser = EarleyParser::new(grammar)?;
+        let depths = least(&parser, 0, |children| children.max().unwrap_or(0) + 1);
+        let sizes = least(&parser, 1, |children| children.sum());
+        for (rules, name) in parser.rules_of.iter().zip(nonterminal_names(&parser)) {
+            let productive = |&rule: &usize| !parser.rejected[rule] && depths[rule] != usize::MAX;
+            if !rules.iter().any(productive) {
+                return Err(GrammarError::Unproductive(name));
+            }
+        }
+        Ok(Self {
+            weights: vec![1; parser.lhs.len()],
+            parser,
+            depths,
+            sizes,
+            max_depth: 32,
+            max_tokens: 256,
+        })
+    }
+
+    /// Set the maximum depth of a derivation, 32 by default
+    ///
+    /// Derivations are allowed to go deeper when the grammar can't derive the start
+    /// symbol, or a filter can't be met, within this depth.
+    #[must_use]
+    pub const fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Set the number of tokens after which every derivation finishes with its shortest
+    /// rules, 256 by default
+    #[must_use]
+    pub const fn max_tokens(mut self, tokens: usize) -> Self {
+        self.max_tokens = tokens;
+        self
+    }
+
+    /// Set the weight of the rule named `rule`, relative to the default weight of 1
+    ///
+    /// A rule with weight 0 is only chosen when no other rule can be.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no rule is named `rule`
+    pub fn weight(mut self, rule: &'static str, weight: u32) -> Result<Self, GrammarError> {
+        let index = self
+            .parser
+            .grammar()
+            .rules()
+            .iter()
+            .position(|candidate| candidate.name == Some(rule))
+            .ok_or(GrammarError::UnknownRule(rule))?;
+        self.weights[index] = weight;
+        Ok(self)
+    }
+
+    /// Get the grammar
+    #[must_use]
+    pub const fn grammar(&self) -> &Grammar<T> {
+        self.parser.grammar()
+    }
+}
+
+impl<T: Clone> Generator<T> {
+    /// Generate a sequence of token kinds
+    pub fn generate(&self, choices: &mut impl Choices) -> Vec<T> {
+        let mut tokens = Vec::new();
+        let forbidden = HashSet::new();
+        self.expand(
+            self.parser.start,
+            &forbidden,
+            self.max_depth,
+            choices,
+            &mut tokens,
+        );
+        tokens
+    }
+
+    /// Generate source text, spelling each token with `spell` and separating them with
+    /// spaces
+    pub fn generate_source<C: Choices>(
+        &self,
+        choices: &mut C,
+        mut spell: impl FnMut(&T, &mut C) -> String,
+    ) -> String {
+        let tokens = self.generate(choices);
+        let words: Vec<String> = tokens.iter().map(|token| spell(token, choices)).collect();
+        words.join(" ")
+    }
+
+    /// Derive `nonterminal` with any rule but the `forbidden` ones, appending its tokens
+    fn expand(
+        &self,
+        nonterminal: usize,
+        forbidden: &HashSet<usize>,
+        depth: usize,
+        choices: &mut impl Choices,
+        tokens: &mut Vec<T>,
+    ) {
+        let rule = self.choose(nonterminal, forbidden, depth, tokens.len(), choices);
+        let symbols = &self.parser.rhs[rule];
+        let last = symbols.len().saturating_sub(1);
+        for (position, symbol) in symbols.iter().enumerate() {
+            let Some(child) = *symbol else {
+                tokens.push(self.parser.token(rule, position).clone());
+                continue;
+            };
+            let mut forbidden = HashSet::new();
+            for (edge, at) in [(Edge::Left, 0), (Edge::Right, last)] {
+                if position == at {
+                    if let Some(rules) = self.parser.conflicts.get(&(rule, edge)) {
+                        forbidden.extend(rules);
+                    }
+                }
+            }
+            self.expand(child, &forbidden, depth.saturating_sub(1), choices, tokens);
+        }
+    }
+
+    /// Choose a rule for `nonterminal`, given the remaining `depth` and the number of
+    /// tokens generated so far
+    fn choose(
+        &self,
+        nonterminal: usize,
+        forbidden: &HashSet<usize>,
+        depth: usize,
+        generated: usize,
+        choices: &mut impl Choices,
+    ) -> usize {
+        let rules = &self.parser.rules_of[nonterminal];
+        let mut candidates: Vec<usize> = rules
+            .iter()
+            .copied()
+            .filter(|&rule| {
+                !self.parser.rejected[rule]
+                    && !forbidden.contains(&rule)
+                    && self.depths[rule] != usize::MAX
+            })
+            .collect();
+        if candidates.is_empty() {
+            candidates = rules
+                .iter()
+                .copied()
+                .filter(|&rule| !self.parser.rejected[rule] && self.depths[rule] != usize::MAX)
+                .collect();
+        }
+
+        let shallowest = candidates
+            .iter()
+            .map(|&rule| self.depths[rule])
+            .min()
+            .unwrap_or(0);
+        candidates.retain(|&rule| self.depths[rule] <= depth.max(shallowest));
+        if generated >= self.max_tokens {
+            let smallest = candidates
+                .iter()
+                .map(|&rule| self.sizes[rule])
+                .min()
+                .unwrap_or(0);
+            candidates.retain(|&rule| self.sizes[rule] == smallest);
+        }
+
+        let weights: Vec<u32> = candidates.iter().map(|&rule| self.weights[rule]).collect();
+        let total: u32 = weights.iter().sum();
+        if total == 0 {
+            let count = u32::try_from(candidates.len()).unwrap_or(u32::MAX);
+            return candidates[choices.below(count) as usize];
+        }
+        let mut chosen = choices.below(total);
+        for (&rule, &weight) in candidates.iter().zip(&weights) {
+            if chosen < weight {
+                return rule;
+            }
+            chosen -= weight;
+        }
+        candidates[0]
+    }
+}
+
+/// Compute the least value of each rule's derivations, bottom-up, where a rule's value
+/// is `combine` of its symbols' values (`token` for each token) and a nonterminal's is
+/// the least of its non-reject rules'; rules with no finite derivation get `usize::MAX`
+fn least<T>(
+    parser: &EarleyParser<T>,
+    token: usize,
+    combine: impl Fn(&mut dyn Iterator<Item = usize>) -> usize,
+) -> Vec<usize> {
+    let mut rules = vec![usize::MAX; parser.lhs.len()];
+    let mut nonterminals = vec![usize::MAX; parser.rules_of.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (rule, symbols) in parser.rhs.iter().enumerate() {
+            let values: Option<Vec<usize>> = symbols
+                .iter()
+                .map(|symbol| {
+                    symbol.map_or(Some(token), |child| {
+                        Some(nonterminals[child]).filter(|&value| value != usize::MAX)
+                    })
+                })
+                .collect();
+            let Some(values) = values else {
+                continue;
+            };
+            let value = combine(&mut values.into_iter());
+            if value < rules[rule] {
+                rules[rule] = value;
+                changed = true;
+            }
+            let nonterminal = parser.lhs[rule];
+            if !parser.rejected[rule] && value < nonterminals[nonterminal] {
+                nonterminals[nonterminal] = value;
+                changed = true;
+            }
+        }
+    }
+    rules
+}
+
+/// Get the name of each nonterminal, by number
+fn nonterminal_names<T>(parser: &EarleyParser<T>) -> Vec<&'static str> {
+    parser
+        .rules_of
+        .iter()
+        .map(|rules| parser.grammar().rules()[rules[0]].lhs)
+        .collect()
+}
@@ -0,0 +1,223 @@
+//! Token input for parsers
+
+use crate::{Expected, ParseError};
+use sea_lex::{FileId, Span, TokenInfo};
+
+/// A buffered stream of tokens from one source file
+///
+/// Parsers read tokens from an `Input` and may restore it to a [`Checkpoint`] to backtrack.
+/// The input also remembers the error that got furthest into the tokens,
+/// which is usually the most useful one to report when parsing fails,
+/// and collects the errors that parsers recovered from.
+#[derive(Debug, Clone)]
+pub struct Input<T> {
+    /// The file the tokens came from
+    file: FileId,
+    /// The tokens
+    tokens: Vec<TokenInfo<T>>,
+    /// The index of the next token
+    position: usize,
+    /// The error that got furthest into the input
+    pub(crate) furthest: Option<ParseError<T>>,
+    /// The errors that parsers recovered from
+    errors: Vec<ParseError<T>>,
+}
+
+/// A saved state of an [`Input`], for backtracking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The index of the next token
+    position: usize,
+    /// The number of recovered errors
+    errors: usize,
+}
+
+/// A node together with the location of the tokens it was parsed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Spanned<N> {
+    /// The node
+    pub node: N,
+    /// The location of the node
+    pub span: Span,
+}
+
+impl<T: Clone> Input<T> {
+    /// Create an input from the tokens of `file`
+    pub fn new(file: FileId, tokens: impl IntoIterator<Item = TokenInfo<T>>) -> Self {
+        Self {
+            file,
+            tokens: tokens.into_iter().collect(),
+            position: 0,
+            furthest: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Get the file the tokens came from
+    #[must_use]
+    pub const fn file(&self) -> FileId {
+        self.file
+    }
+
+    /// Get all of the tokens
+    #[must_use]
+    pub fn tokens(&self) -> &[TokenInfo<T>] {
+        &self.tokens
+    }
+
+    /// Get the index of the next token
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Move back (or forward) to the token at `position`
+    ///
+    /// Unlike [`restore`](Self::restore), this keeps any errors recovered from since.
+    pub fn rewind(&mut self, position: usize) {
+        self.position = position.min(self.tokens.len());
+    }
+
+    /// Save the current state, to backtrack to later with [`restore`](Self::restore)
+    #[must_use]
+    pub const fn save(&self) -> Checkpoint {
+        Checkpoint {
+            position: self.position,
+            errors: self.errors.len(),
+        }
+    }
+
+    /// Backtrack to a saved state, forgetting any errors recovered from since
+    pub fn restore(&mut self, checkpoint: Checkpoint) {
+        self.position = checkpoint.position;
+        self.errors.truncate(checkpoint.errors);
+    }
+
+    /// Reports whether every token has been consumed
+    #[must_use]
+    pub const fn is_at_end(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    /// Get the next token without consuming it
+    #[must_use]
+    pub fn peek(&self) -> Option<&TokenInfo<T>> {
+        self.tokens.get(self.position)
+    }
+
+    /// Get the token `n` places after the next one without consuming anything
+    #[must_use]
+    pub fn peek_nth(&self, n: usize) -> Option<&TokenInfo<T>> {
+        self.tokens.get(self.position + n)
+    }
+
+    /// Consume and return the next token
+    pub fn next_token(&mut self) -> Option<TokenInfo<T>> {
+        let token = self.tokens.get(self.position).cloned();
+        if token.is_some() {
+            self.position += 1;
+        }
+        token
+    }
+
+    /// Get the location of the next token, or an empty span at the end of the input
+    #[must_use]
+    pub fn span(&self) -> Span {
+        self.span_at(self.position)
+    }
+
+    /// Get the location of the tokens consumed since `start`
+    ///
+    /// If nothing has been consumed, this is an empty span at the next token.
+    #[must_use]
+    pub fn span_since(&self, start: usize) -> Span {
+        if start >= self.position {
+            let span = self.span();
+            return Span::new(self.file, span.start, span.start);
+        }
+        Span::new(
+            self.file,
+            self.tokens[start].start,
+            self.tokens[self.position - 1].end,
+        )
+    }
+
+    /// Get the location of the token at `index`, or an empty span at the end of the input
+    fn span_at(&self, index: usize) -> Span {
+        self.tokens.get(index).map_or_else(
+            || {
+                let end = self.tokens.last().map_or(0, |token| token.end);
+                Span::new(self.file, end, end)
+            },
+            |token| Span::new(self.file, token.start, token.end),
+        )
+    }
+
+    /// Build an error for the next token, which didn't match any of `expected`
+    ///
+    /// The error is also recorded as a candidate for the furthest error.
+    pub fn unexpected(&mut self, expected: Vec<Expected<T>>) -> ParseError<T> {
+        let found = self.peek().map(|token| token.kind.clone());
+        let error = ParseError::unexpected(self.position, self.span(), found, expected);
+        self.record(error.clone());
+        error
+    }
+
+    /// Build an error with a custom message, covering the tokens consumed since `start`
+    ///
+    /// The error is also recorded as a candidate for the furthest error.
+    pub fn custom_error(&mut self, start: usize, message: impl Into<String>) -> ParseError<T> {
+        let error = ParseError::custom(start, self.span_since(start), message.into());
+        self.record(error.clone());
+        error
+    }
+
+    /// Record an error as a candidate for the furthest error
+    pub fn record(&mut self, error: ParseError<T>) {
+        self.furthest = Some(match self.furthest.take() {
+            Some(furthest) => furthest.merge(error),
+            None => error,
+        });
+    }
+
+    /// Take the error that got furthest into the input, if any
+    pub const fn take_furthest_error(&mut self) -> Option<ParseError<T>> {
+        self.furthest.take()
+    }
+
+    /// Report an error that a parser recovered from
+    pub fn report(&mut self, error: ParseError<T>) {
+        self.errors.push(error);
+    }
+
+    /// Get the errors that parsers have recovered from, in the order they were reported
+    #[must_use]
+    pub fn errors(&self) -> &[ParseError<T>] {
+        &self.errors
+    }
+
+    /// Take the errors that parsers have recovered from
+    pub fn take_errors(&mut self) -> Vec<ParseError<T>> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+impl<N> Spanned<N> {
+    /// Create a new spanned node
+    pub const fn new(node: N, span: Span) -> Self {
+        Self { node, span }
+    }
+
+    /// Transform the node, keeping its location
+    pub fn map<M>(self, f: impl FnOnce(N) -> M) -> Spanned<M> {
+        Spanned::new(f(self.node), self.span)
+    }
+}
+
+impl Checkpoint {
+    /// Get the index of the next token at this checkpoint
+    #[must_use]
+    pub const fn position(self) -> usize {
+        self.position
+    }
+}
@@ -9,3 +9,106 @@
     missing_docs,
     rustdoc::all
 )]
+
+mod combinator;
+mod cst;
+mod earley;
+mod error;
+mod generate;
+mod input;
+mod lr;
+mod parse;
+mod parser;
+mod pratt;
+mod pretty;
+mod recovery;
+mod recursive;
+mod scope;
+mod visit;
+
+pub use combinator::*;
+pub use cst::*;
+pub use earley::*;
+pub use error::*;
+pub use generate::*;
+pub use input::*;
+pub use lr::*;
+pub use parse::*;
+pub use parser::*;
+pub use pratt::*;
+pub use pretty::*;
+pub use recovery::*;
+pub use recursive::*;
+pub use scope::*;
+pub use visit::*;
+
+use sea_lex::{FileId, Span, TokenInfo};
+
+/// One end of a rule's right-hand side, where disambiguation filters apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Edge {
+    /// The first symbol
+    Left,
+    /// The last symbol
+    Right,
+}
+
+/// Get the name of a token kind's variant from its `Debug` representation
+///
+/// Grammars and generated code refer to tokens by these names; errors in parsed input
+/// describe them as written instead, with [`TokenGrammar::describe`](sea_lex::TokenGrammar::describe).
+fn kind_name(kind: &impl std::fmt::Debug) -> String {
+    let debug = format!("{kind:?}");
+    debug
+        .split(['(', ' ', '{'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Reports whether two token kinds are the same variant
+fn same_kind<T>(a: &T, b: &T) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+/// Get the span of the tokens from `start` up to `end`, or an empty span at `start`
+fn span_of<T>(file: FileId, tokens: &[TokenInfo<T>], start: usize, end: usize) -> Span {
+    let offset = |index: usize| {
+        tokens.get(index).map_or_else(
+            || tokens.last().map_or(0, |token| token.end),
+            |token| token.start,
+        )
+    };
+    let end = end.min(tokens.len());
+    if end > start {
+        Span::new(file, tokens[start].start, tokens[end - 1].end)
+    } else {
+        let at = offset(start);
+        Span::new(file, at, at)
+    }
+}
+
+#[cfg(feature = "derive")]
+pub use sea_parse_derive::{AstNode, Fold, Parse, Visit};
+
+/// Items used by code generated by `#[derive(Parse)]`
+#[doc(hidden)]
+pub mod __private {
+    pub use sea_lex::TokenInfo;
+
+    /// Get one more than the largest of `depths`, for derived [`Parse::LEFT_DEPTH`]s
+    ///
+    /// [`Parse::LEFT_DEPTH`]: crate::Parse::LEFT_DEPTH
+    #[must_use]
+    pub const fn left_depth(depths: &[usize]) -> usize {
+        let mut max = 0;
+        let mut i = 0;
+        while i < depths.len() {
+            if depths[i] > max {
+                max = depths[i];
+            }
+            i += 1;
+        }
+        max + 1
+    }
+}
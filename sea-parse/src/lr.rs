@@ -0,0 +1,13 @@
+//! Table-driven LR parsing
+//!
+//! [`LrTable`] builds LALR(1) or canonical LR(1) tables from a [`Grammar`](crate::Grammar),
+//! reporting the conflicts that make a grammar unsuitable, each with an example input.
+//! The tables parse directly through [`ParseTable`], or can be written out as Rust code
+//! by a build script with [`LrTable::generate`], so that parsing needs no grammar
+//! analysis at run time.
+
+mod automaton;
+mod codegen;
+mod table;
+
+pub use table::*;
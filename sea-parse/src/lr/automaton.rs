@@ -0,0 +1,264 @@
+//! LR(1) and LALR(1) automata
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+/// A grammar symbol, by number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sym {
+    /// A terminal
+    Terminal(usize),
+    /// A nonterminal
+    Nonterminal(usize),
+}
+
+/// A set of items, each a rule with a dot position and the terminals that may follow it
+pub type Items = BTreeMap<(usize, usize), BTreeSet<usize>>;
+
+/// The states of an LR automaton over a numbered grammar
+///
+/// The grammar is augmented with one more nonterminal and rule, `S' ::= start`, which
+/// come after the grammar's own; terminal `terminals` is the end of the input.
+pub struct Automaton {
+    /// The number of terminals, not counting the end of the input
+    pub terminals: usize,
+    /// The nonterminal each rule defines
+    pub lhs: Vec<usize>,
+    /// The symbols of each rule
+    pub rhs: Vec<Vec<Sym>>,
+    /// The rules of each nonterminal
+    rules_of: Vec<Vec<usize>>,
+    /// Whether each nonterminal can derive no tokens
+    nullable: Vec<bool>,
+    /// The terminals each nonterminal's tokens can start with
+    first: Vec<BTreeSet<usize>>,
+    /// The kernel items of each state
+    pub kernels: Vec<Items>,
+    /// The state after each symbol in each state
+    pub transitions: Vec<BTreeMap<Sym, usize>>,
+}
+
+impl Automaton {
+    /// Build the automaton for a grammar, from `start`
+    ///
+    /// With `merge`, states with the same items but different lookaheads are merged,
+    /// giving the LALR(1) automaton rather than the canonical LR(1) one.
+    pub fn new(
+        terminals: usize,
+        mut lhs: Vec<usize>,
+        mut rhs: Vec<Vec<Sym>>,
+        mut nullable: Vec<bool>,
+        start: usize,
+        merge: bool,
+    ) -> Self {
+        let augmented = nullable.len();
+        lhs.push(augmented);
+        rhs.push(vec![Sym::Nonterminal(start)]);
+        nullable.push(false);
+        let mut rules_of = vec![Vec::new(); augmented + 1];
+        for (rule, &nonterminal) in lhs.iter().enumerate() {
+            rules_of[nonterminal].push(rule);
+        }
+
+        let mut automaton = Self {
+            terminals,
+            lhs,
+            rhs,
+            rules_of,
+            nullable,
+            first: vec![BTreeSet::new(); augmented + 1],
+            kernels: Vec::new(),
+            transitions: Vec::new(),
+        };
+        automaton.first = automaton.first_sets();
+        automaton.build(merge);
+        automaton
+    }
+
+    /// Get the augmented start rule
+    pub const fn start_rule(&self) -> usize {
+        self.rhs.len() - 1
+    }
+
+    /// Compute the terminals each nonterminal's tokens can start with
+    fn first_sets(&self) -> Vec<BTreeSet<usize>> {
+        let mut first = vec![BTreeSet::new(); self.rules_of.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (rule, symbols) in self.rhs.iter().enumerate() {
+                let (terminals, _) = self.first_of(symbols, &first);
+                let set = &mut first[self.lhs[rule]];
+                let before = set.len();
+                set.extend(terminals);
+                changed |= set.len() > before;
+            }
+        }
+        first
+    }
+
+    /// Get the terminals `symbols` can start with, and whether they can derive no
+    /// tokens
+    fn first_of(&self, symbols: &[Sym], first: &[BTreeSet<usize>]) -> (BTreeSet<usize>, bool) {
+        let mut terminals = BTreeSet::new();
+        for &symbol in symbols {
+            match symbol {
+                Sym::Terminal(terminal) => {
+                    terminals.insert(terminal);
+                    return (terminals, false);
+                }
+                Sym::Nonterminal(nonterminal) => {
+                    terminals.extend(first[nonterminal].iter().copied());
+                    if !self.nullable[nonterminal] {
+                        return (terminals, false);
+                    }
+                }
+            }
+        }
+        (terminals, true)
+    }
+
+    /// Add the items predicted by `kernel`, with their lookaheads
+    pub fn closure(&self, kernel: &Items) -> Items {
+        let mut items = kernel.clone();
+        let mut work: Vec<(usize, usize)> = items.keys().copied().collect();
+        while let Some((rule, dot)) = work.pop() {
+            let Some(&Sym::Nonterminal(next)) = self.rhs[rule].get(dot) else {
+                continue;
+            };
+            let (mut lookahead, nullable) = self.first_of(&self.rhs[rule][dot + 1..], &self.first);
+            if nullable {
+                lookahead.extend(items[&(rule, dot)].iter().copied());
+            }
+            for &child in &self.rules_of[next] {
+                let added = !items.contains_key(&(child, 0));
+                let set = items.entry((child, 0)).or_default();
+                let before = set.len();
+                set.extend(lookahead.iter().copied());
+                if added || set.len() > before {
+                    work.push((child, 0));
+                }
+            }
+        }
+        items
+    }
+
+    /// Build the states, starting from the augmented start rule
+    fn build(&mut self, merge: bool) {
+        let key = |kernel: &Items| -> Vec<(usize, usize, Vec<usize>)> {
+            kernel
+                .iter()
+                .map(|(&(rule, dot), lookahead)| {
+                    let lookahead = if merge {
+                        Vec::new()
+                    } else {
+                        lookahead.iter().copied().collect()
+                    };
+                    (rule, dot, lookahead)
+                })
+                .collect()
+        };
+
+        let start = Items::from([((self.start_rule(), 0), BTreeSet::from([self.terminals]))]);
+        let mut states = HashMap::from([(key(&start), 0)]);
+        self.kernels.push(start);
+        self.transitions.push(BTreeMap::new());
+        let mut work = VecDeque::from([0]);
+        while let Some(state) = work.pop_front() {
+            let mut successors: BTreeMap<Sym, Items> = BTreeMap::new();
+            for ((rule, dot), lookahead) in self.closure(&self.kernels[state]) {
+                if let Some(&symbol) = self.rhs[rule].get(dot) {
+                    successors
+                        .entry(symbol)
+                        .or_default()
+                        .entry((rule, dot + 1))
+                        .or_default()
+                        .extend(lookahead);
+                }
+            }
+
+            for (symbol, kernel) in successors {
+                let target = if let Some(&target) = states.get(&key(&kernel)) {
+                    let mut grew = false;
+                    for (item, lookahead) in kernel {
+                        let set = self.kernels[target].entry(item).or_default();
+                        let before = set.len();
+                        set.extend(lookahead);
+                        grew |= set.len() > before;
+                    }
+                    if grew && !work.contains(&target) {
+                        work.push_back(target);
+                    }
+                    target
+                } else {
+                    let target = self.kernels.len();
+                    states.insert(key(&kernel), target);
+                    self.kernels.push(kernel);
+                    self.transitions.push(BTreeMap::new());
+                    work.push_back(target);
+                    target
+                };
+                self.transitions[state].insert(symbol, target);
+            }
+        }
+    }
+
+    /// Find a shortest string of terminals reaching each state from the start
+    pub fn examples(&self) -> Vec<Vec<usize>> {
+        let yields = self.shortest_yields();
+        let mut examples: Vec<Option<Vec<usize>>> = vec![None; self.kernels.len()];
+        examples[0] = Some(Vec::new());
+        let mut work = VecDeque::from([0]);
+        while let Some(state) = work.pop_front() {
+            for (&symbol, &target) in &self.transitions[state] {
+                if examples[target].is_some() {
+                    continue;
+                }
+                let mut example = examples[state].clone().unwrap_or_default();
+                match symbol {
+                    Sym::Terminal(terminal) => example.push(terminal),
+                    Sym::Nonterminal(nonterminal) => {
+                        example.extend(yields[nonterminal].iter().flatten().copied());
+                    }
+                }
+                examples[target] = Some(example);
+                work.push_back(target);
+            }
+        }
+        examples
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect()
+    }
+
+    /// Find a shortest string of terminals each nonterminal can derive, if it derives
+    /// any
+    fn shortest_yields(&self) -> Vec<Option<Vec<usize>>> {
+        let mut yields: Vec<Option<Vec<usize>>> = vec![None; self.rules_of.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (rule, symbols) in self.rhs.iter().enumerate() {
+                let derived: Option<Vec<usize>> = symbols
+                    .iter()
+                    .map(|&symbol| match symbol {
+                        Sym::Terminal(terminal) => Some(vec![terminal]),
+                        Sym::Nonterminal(nonterminal) => yields[nonterminal].clone(),
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(|parts| parts.concat());
+                let Some(derived) = derived else {
+                    continue;
+                };
+                let current = &mut yields[self.lhs[rule]];
+                if current
+                    .as_ref()
+                    .is_none_or(|current| derived.len() < current.len())
+                {
+                    *current = Some(derived);
+                    changed = true;
+                }
+            }
+        }
+        yields
+    }
+}
@@ -0,0 +1,193 @@
+//! Rust code generation for LR tables
+
+use super::table::{Action, LrTable};
+use crate::kind_name;
+use std::fmt::{self, Write};
+
+impl<T: fmt::Debug> LrTable<T> {
+    /// Write the tables as Rust code, for a build script
+    ///
+    /// The code defines a unit struct `name` that implements
+    /// [`ParseTable<token_type>`](super::ParseTable), where `token_type` is the path of
+    /// the token enum where the code is included. It refers to `sea_parse` by its
+    /// absolute path, and doesn't depend on the grammar at run time.
+    ///
+    /// ```ignore
+    /// // build.rs
+    /// let table = LrTable::lalr(grammar())?;
+    /// let out = std::path::Path::new(&std::env::var("OUT_DIR")?).join("parser.rs");
+    /// std::fs::write(out, table.generate("ExprParser", "crate::token::Token"))?;
+    ///
+    /// // src/parser.rs
+    /// include!(concat!(env!("OUT_DIR"), "/parser.rs"));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Never panics: writing to a string can't fail
+    #[must_use]
+    pub fn generate(&self, name: &str, token_type: &str) -> String {
+        let mut out = String::new();
+        self.write_tables(&mut out, name)
+            .and_then(|()| self.write_impl(&mut out, name, token_type))
+            .expect("writing to a string can't fail");
+        out
+    }
+
+    /// Write the struct `name` holding the tables to `out`
+    fn write_tables(&self, out: &mut String, name: &str) -> fmt::Result {
+        let algorithm = if self.lalr { "LALR(1)" } else { "LR(1)" };
+        writeln!(out, "// Generated by sea-parse. Do not edit.")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/// An {algorithm} parser for `{}`",
+            self.grammar().start()
+        )?;
+        writeln!(out, "///")?;
+        writeln!(out, "/// Its rules are numbered:")?;
+        for (index, rule) in self.grammar().rules().iter().enumerate() {
+            writeln!(out, "/// {index}. `{rule}`")?;
+        }
+        writeln!(out, "#[derive(Debug, Clone, Copy, Default)]")?;
+        writeln!(out, "pub struct {name};")?;
+        writeln!(out)?;
+
+        let states = self.actions.len();
+        let terminals = self.terminals.len();
+        let nonterminals = self.gotos.first().map_or(0, Vec::len);
+        writeln!(out, "impl {name} {{")?;
+        writeln!(
+            out,
+            "    /// The action in each state on each terminal, then on the end of the input"
+        )?;
+        writeln!(
+            out,
+            "    const ACTIONS: [[::sea_parse::Action; {}]; {states}] = [",
+            terminals + 1
+        )?;
+        for row in &self.actions {
+            let row: Vec<String> = row.iter().map(action).collect();
+            writeln!(out, "        [{}],", row.join(", "))?;
+        }
+        writeln!(out, "    ];")?;
+        writeln!(
+            out,
+            "    /// The state after each nonterminal in each state"
+        )?;
+        writeln!(
+            out,
+            "    const GOTOS: [[Option<usize>; {nonterminals}]; {states}] = ["
+        )?;
+        for row in &self.gotos {
+            let row: Vec<String> = row
+                .iter()
+                .map(|goto| goto.map_or_else(|| "None".to_string(), |goto| format!("Some({goto})")))
+                .collect();
+            writeln!(out, "        [{}],", row.join(", "))?;
+        }
+        writeln!(out, "    ];")?;
+        writeln!(out, "    /// The nonterminal and length of each rule")?;
+        writeln!(
+            out,
+            "    const RULES: [(usize, usize); {}] = [",
+            self.rules.len()
+        )?;
+        for (nonterminal, length) in &self.rules {
+            writeln!(out, "        ({nonterminal}, {length}),")?;
+        }
+        writeln!(out, "    ];")?;
+        writeln!(out, "    /// The name of each terminal")?;
+        let names: Vec<String> = self
+            .terminals
+            .iter()
+            .map(|kind| format!("{:?}", kind_name(kind)))
+            .collect();
+        writeln!(
+            out,
+            "    const TERMINALS: [&'static str; {terminals}] = [{}];",
+            names.join(", ")
+        )?;
+        writeln!(out, "}}")?;
+        writeln!(out)
+    }
+
+    /// Write the implementation of [`ParseTable`](super::ParseTable) for `name` to `out`
+    fn write_impl(&self, out: &mut String, name: &str, token_type: &str) -> fmt::Result {
+        let terminals = self.terminals.len();
+        writeln!(
+            out,
+            "impl ::sea_parse::ParseTable<{token_type}> for {name} {{"
+        )?;
+        writeln!(out, "    fn terminal_count(&self) -> usize {{")?;
+        writeln!(out, "        {terminals}")?;
+        writeln!(out, "    }}")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    fn terminal(&self, kind: &{token_type}) -> Option<usize> {{"
+        )?;
+        writeln!(out, "        match kind {{")?;
+        for (index, kind) in self.terminals.iter().enumerate() {
+            writeln!(
+                out,
+                "            {token_type}::{} => Some({index}),",
+                pattern(kind)
+            )?;
+        }
+        writeln!(out, "            #[allow(unreachable_patterns)]")?;
+        writeln!(out, "            _ => None,")?;
+        writeln!(out, "        }}")?;
+        writeln!(out, "    }}")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    fn expected(&self, terminal: usize) -> ::sea_parse::Expected<{token_type}> {{"
+        )?;
+        writeln!(
+            out,
+            "        ::sea_parse::Expected::Label(Self::TERMINALS[terminal])"
+        )?;
+        writeln!(out, "    }}")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    fn action(&self, state: usize, terminal: usize) -> ::sea_parse::Action {{"
+        )?;
+        writeln!(out, "        Self::ACTIONS[state][terminal]")?;
+        writeln!(out, "    }}")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    fn goto(&self, state: usize, nonterminal: usize) -> Option<usize> {{"
+        )?;
+        writeln!(out, "        Self::GOTOS[state][nonterminal]")?;
+        writeln!(out, "    }}")?;
+        writeln!(out)?;
+        writeln!(out, "    fn rule(&self, rule: usize) -> (usize, usize) {{")?;
+        writeln!(out, "        Self::RULES[rule]")?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "}}")
+    }
+}
+
+/// Write an action as a Rust expression
+fn action(action: &Action) -> String {
+    match action {
+        Action::Shift(state) => format!("::sea_parse::Action::Shift({state})"),
+        Action::Reduce(rule) => format!("::sea_parse::Action::Reduce({rule})"),
+        Action::Accept => "::sea_parse::Action::Accept".to_string(),
+        Action::Error => "::sea_parse::Action::Error".to_string(),
+    }
+}
+
+/// Write a pattern matching any token of the same variant as `kind`
+fn pattern(kind: &impl fmt::Debug) -> String {
+    let name = kind_name(kind);
+    let debug = format!("{kind:?}");
+    match debug[name.len()..].chars().next() {
+        None => name,
+        Some('(') => format!("{name}(..)"),
+        Some(_) => format!("{name} {{ .. }}"),
+    }
+}
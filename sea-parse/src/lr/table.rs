@@ -0,0 +1,511 @@
+//! LR parse tables
+
+use super::automaton::{Automaton, Sym};
+use crate::{kind_name, span_of, Edge};
+use crate::{EarleyParser, Expected, Grammar, GrammarError, ParseError, Rule, RuleId, Symbol};
+use sea_lex::{FileId, Span, TokenInfo};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem::{discriminant, Discriminant};
+
+/// What an LR parser does in a state on a terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Consume the token and go to the given state
+    Shift(usize),
+    /// Replace the symbols of the given rule with its nonterminal
+    Reduce(usize),
+    /// Finish parsing
+    Accept,
+    /// Report a syntax error
+    Error,
+}
+
+/// The tables that drive an LR parser
+///
+/// Terminals, nonterminals, and rules are numbered from 0, with the end of the input
+/// numbered after the last terminal. [`LrTable`] builds tables from a [`Grammar`], and
+/// [`LrTable::generate`] writes them out as Rust code that implements this trait.
+pub trait ParseTable<T> {
+    /// Get the number of terminals, not counting the end of the input
+    fn terminal_count(&self) -> usize;
+
+    /// Get the terminal for a token kind, or `None` if the grammar doesn't use it
+    fn terminal(&self, kind: &T) -> Option<usize>;
+
+    /// Describe a terminal in an error message
+    fn expected(&self, terminal: usize) -> Expected<T>;
+
+    /// Get the action in `state` on `terminal`
+    fn action(&self, state: usize, terminal: usize) -> Action;
+
+    /// Get the state to go to after reducing to `nonterminal` in `state`
+    fn goto(&self, state: usize, nonterminal: usize) -> Option<usize>;
+
+    /// Get the nonterminal that `rule` defines, and the number of symbols in it
+    fn rule(&self, rule: usize) -> (usize, usize);
+
+    /// Parse `tokens` from `file`, building a value bottom-up
+    ///
+    /// `token` builds the value of each token, and `reduce` the value of each
+    /// nonterminal from the rule that derived it, its location, and the values of its
+    /// symbols.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error at the first token that can't continue the parse (or at the end
+    /// of the input, if it ends too soon), with every token that could have
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tables are inconsistent, with no state to go to after a reduction
+    fn parse<O>(
+        &self,
+        file: FileId,
+        tokens: impl IntoIterator<Item = TokenInfo<T>>,
+        mut token: impl FnMut(&TokenInfo<T>, Span) -> O,
+        mut reduce: impl FnMut(RuleId, Span, Vec<O>) -> O,
+    ) -> Result<O, ParseError<T>>
+    where
+        T: Clone,
+    {
+        let tokens: Vec<TokenInfo<T>> = tokens.into_iter().collect();
+        let end = self.terminal_count();
+        let mut states = vec![0];
+        let mut values: Vec<(O, usize)> = Vec::new();
+        let mut position = 0;
+        loop {
+            let state = states[states.len() - 1];
+            let terminal = tokens
+                .get(position)
+                .map_or(Some(end), |token| self.terminal(&token.kind));
+            let action = terminal.map_or(Action::Error, |terminal| self.action(state, terminal));
+            match action {
+                Action::Shift(next) => {
+                    let span = span_of(file, &tokens, position, position + 1);
+                    values.push((token(&tokens[position], span), position));
+                    states.push(next);
+                    position += 1;
+                }
+                Action::Reduce(rule) => {
+                    let (nonterminal, length) = self.rule(rule);
+                    let children: Vec<(O, usize)> = values.split_off(values.len() - length);
+                    states.truncate(states.len() - length);
+                    let start = children.first().map_or(position, |&(_, start)| start);
+                    let span = span_of(file, &tokens, start, position);
+                    let children = children.into_iter().map(|(value, _)| value).collect();
+                    values.push((reduce(RuleId(rule), span, children), start));
+                    let Some(next) = self.goto(states[states.len() - 1], nonterminal) else {
+                        panic!("No state to go to after reducing by rule {rule}");
+                    };
+                    states.push(next);
+                }
+                Action::Accept => {
+                    let (value, _) = values.pop().expect("an accepted parse has a value");
+                    return Ok(value);
+                }
+                Action::Error => {
+                    let expected = (0..=end)
+                        .filter(|&terminal| self.action(state, terminal) != Action::Error)
+                        .map(|terminal| {
+                            if terminal == end {
+                                Expected::End
+                            } else {
+                                self.expected(terminal)
+                            }
+                        })
+                        .collect();
+                    return Err(ParseError::unexpected(
+                        position,
+                        span_of(file, &tokens, position, position + 1),
+                        tokens.get(position).map(|token| token.kind.clone()),
+                        expected,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// A conflict between actions in an LR table, which the table resolved by default
+///
+/// Conflicts are resolved as yacc does: by shifting rather than reducing, and otherwise
+/// by reducing by the rule that came first in the grammar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict<T> {
+    /// The state with the conflict
+    pub state: usize,
+    /// The token that can't decide between the actions, or `None` at the end of the
+    /// input
+    pub lookahead: Option<T>,
+    /// A shortest sequence of tokens that reaches the state, before the lookahead
+    ///
+    /// An LALR(1) state can merge several LR(1) states, so the lookahead may only
+    /// follow some of the inputs that reach it.
+    pub example: Vec<T>,
+    /// Whether the lookahead could be shifted
+    pub shift: bool,
+    /// Whether the input could be accepted
+    pub accept: bool,
+    /// The rules that could be reduced by
+    pub reduce: Vec<Rule<T>>,
+}
+
+impl<T: fmt::Debug> fmt::Display for Conflict<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.shift { "Shift" } else { "Reduce" };
+        write!(f, "{kind}/reduce conflict on ")?;
+        match &self.lookahead {
+            Some(kind) => write!(f, "{}", kind_name(kind))?,
+            None => write!(f, "end of input")?,
+        }
+        let example: Vec<String> = self.example.iter().map(kind_name).collect();
+        write!(f, " after `{}`: ", example.join(" "))?;
+
+        let mut actions = Vec::new();
+        if self.shift {
+            actions.push("shift".to_string());
+        }
+        if self.accept {
+            actions.push("accept".to_string());
+        }
+        for rule in &self.reduce {
+            actions.push(format!("reduce by `{rule}`"));
+        }
+        write!(f, "{}", actions.join(", or "))
+    }
+}
+
+/// LR parse tables built from a [`Grammar`]
+///
+/// Tables are built for LALR(1) parsing with [`lalr`](Self::lalr), or canonical LR(1)
+/// parsing with [`lr1`](Self::lr1), which accepts more grammars at the cost of more
+/// states. Both parse in linear time. Conflicts the grammar's filters don't resolve
+/// are listed by [`conflicts`](Self::conflicts).
+///
+/// The grammar's [`priority`](Grammar::priority) and [`assoc`](Grammar::assoc) filters
+/// resolve shift/reduce conflicts between a rule that ends with a nonterminal and one
+/// that starts with one, such as between binary operators. Reject rules aren't
+/// supported.
+///
+/// ```
+/// # use sea_lex::{SourceDb, Token};
+/// # use sea_parse::{nonterm, term, Assoc, Grammar, LrTable, ParseTable};
+/// # #[derive(Debug, Clone, PartialEq, Token)]
+/// # #[skip(r"\s+")]
+/// # enum Tok {
+/// #     #[token(r"\d+", str::parse)]
+/// #     Number(i64),
+/// #     #[token("+")]
+/// #     Plus,
+/// # }
+/// let grammar = Grammar::new("Expr")
+///     .named_rule("add", "Expr", [nonterm("Expr"), term(Tok::Plus), nonterm("Expr")])
+///     .rule("Expr", [term(Tok::Number(0))]);
+/// let table = LrTable::lalr(grammar.clone())?;
+/// assert_eq!(
+///     table.conflicts()[0].to_string(),
+///     "Shift/reduce conflict on Plus after `Number Plus Number`: shift, or reduce by `Expr ::= Expr Plus Expr`",
+/// );
+///
+/// let table = LrTable::lalr(grammar.assoc(Assoc::Left, ["add"]))?;
+/// assert!(table.conflicts().is_empty());
+/// let mut db = SourceDb::new();
+/// let file = db.add("main", "1 + 2 + 3");
+/// let value = table.parse(
+///     file,
+///     Tok::lexer(db.file(file).contents()).collect()?,
+///     |token, _| match token.kind {
+///         Tok::Number(value) => value,
+///         _ => 0,
+///     },
+///     |_, _, children| children.iter().sum(),
+/// )?;
+/// assert_eq!(value, 6);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct LrTable<T> {
+    /// The grammar
+    grammar: Grammar<T>,
+    /// A token kind for each terminal
+    pub(crate) terminals: Vec<T>,
+    /// The terminal for each token kind
+    terminal_of: HashMap<Discriminant<T>, usize>,
+    /// The nonterminal and length of each rule
+    pub(crate) rules: Vec<(usize, usize)>,
+    /// The action in each state on each terminal
+    pub(crate) actions: Vec<Vec<Action>>,
+    /// The state after each nonterminal in each state
+    pub(crate) gotos: Vec<Vec<Option<usize>>>,
+    /// The unresolved conflicts
+    conflicts: Vec<Conflict<T>>,
+    /// Whether states with the same items were merged
+    pub(crate) lalr: bool,
+}
+
+impl<T: Clone> LrTable<T> {
+    /// Build LALR(1) tables for `grammar`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a nonterminal has no rules, if a filter refers to a rule name
+    /// that isn't unique, or if the grammar has a reject rule
+    pub fn lalr(grammar: Grammar<T>) -> Result<Self, GrammarError> {
+        Self::build(grammar, true)
+    }
+
+    /// Build canonical LR(1) tables for `grammar`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a nonterminal has no rules, if a filter refers to a rule name
+    /// that isn't unique, or if the grammar has a reject rule
+    pub fn lr1(grammar: Grammar<T>) -> Result<Self, GrammarError> {
+        Self::build(grammar, false)
+    }
+
+    /// Build tables for `grammar`, merging states with the same items if `lalr`
+    fn build(grammar: Grammar<T>, lalr: bool) -> Result<Self, GrammarError> {
+        if let Some(&name) = grammar.rejects.first() {
+            return Err(GrammarError::UnsupportedReject(name));
+        }
+        let mut analysis = EarleyParser::new(grammar)?;
+        let forbidden = std::mem::take(&mut analysis.conflicts);
+
+        let mut terminals: Vec<T> = Vec::new();
+        let mut terminal_of = HashMap::new();
+        let mut rhs = Vec::with_capacity(analysis.rhs.len());
+        for rule in analysis.grammar().rules() {
+            let symbols = rule.rhs.iter().map(|symbol| match symbol {
+                Symbol::Token(kind) => {
+                    let next = terminals.len();
+                    let terminal = *terminal_of.entry(discriminant(kind)).or_insert(next);
+                    if terminal == next {
+                        terminals.push(kind.clone());
+                    }
+                    Sym::Terminal(terminal)
+                }
+                Symbol::Rule(_) => Sym::Nonterminal(0),
+            });
+            rhs.push(symbols.collect::<Vec<_>>());
+        }
+        for (symbols, nonterminals) in rhs.iter_mut().zip(&analysis.rhs) {
+            for (symbol, nonterminal) in symbols.iter_mut().zip(nonterminals) {
+                if let Some(nonterminal) = nonterminal {
+                    *symbol = Sym::Nonterminal(*nonterminal);
+                }
+            }
+        }
+
+        let automaton = Automaton::new(
+            terminals.len(),
+            analysis.lhs.clone(),
+            rhs,
+            analysis.nullable.clone(),
+            analysis.start,
+            lalr,
+        );
+        let rules = analysis
+            .lhs
+            .iter()
+            .zip(&analysis.rhs)
+            .map(|(&lhs, rhs)| (lhs, rhs.len()))
+            .collect();
+        let mut table = Self {
+            grammar: analysis.into_grammar(),
+            terminals,
+            terminal_of,
+            rules,
+            actions: Vec::new(),
+            gotos: Vec::new(),
+            conflicts: Vec::new(),
+            lalr,
+        };
+        table.fill(&automaton, &forbidden);
+        Ok(table)
+    }
+
+    /// Fill in the actions and gotos of each state of `automaton`, resolving conflicts
+    /// with the rules `forbidden` at each edge of each rule
+    fn fill(&mut self, automaton: &Automaton, forbidden: &HashMap<(usize, Edge), HashSet<usize>>) {
+        let end = self.terminals.len();
+        let nonterminals = automaton.lhs[automaton.start_rule()];
+        let examples = automaton.examples();
+        for (state, kernel) in automaton.kernels.iter().enumerate() {
+            let items = automaton.closure(kernel);
+            let mut reductions = vec![Vec::new(); end + 1];
+            for (&(rule, dot), lookahead) in &items {
+                if dot == automaton.rhs[rule].len() {
+                    for &terminal in lookahead {
+                        reductions[terminal].push(rule);
+                    }
+                }
+            }
+
+            let mut actions = vec![Action::Error; end + 1];
+            let mut gotos = vec![None; nonterminals];
+            for (&symbol, &target) in &automaton.transitions[state] {
+                match symbol {
+                    Sym::Terminal(terminal) => actions[terminal] = Action::Shift(target),
+                    Sym::Nonterminal(nonterminal) => gotos[nonterminal] = Some(target),
+                }
+            }
+
+            for (terminal, mut rules) in reductions.into_iter().enumerate() {
+                let accept = rules.contains(&automaton.start_rule());
+                rules.retain(|&rule| rule != automaton.start_rule());
+                let shift = matches!(actions[terminal], Action::Shift(_));
+                let candidates = usize::from(shift) + usize::from(accept) + rules.len();
+                if candidates == 0 {
+                    continue;
+                }
+                if candidates == 1 {
+                    if accept {
+                        actions[terminal] = Action::Accept;
+                    } else if let Some(&rule) = rules.first() {
+                        actions[terminal] = Action::Reduce(rule);
+                    }
+                    continue;
+                }
+
+                if shift && !accept && rules.len() == 1 {
+                    let shifting = items.keys().filter(|&&(rule, dot)| {
+                        automaton.rhs[rule].get(dot) == Some(&Sym::Terminal(terminal))
+                    });
+                    if let Some(resolution) = resolve(automaton, forbidden, rules[0], shifting) {
+                        match resolution {
+                            Resolution::Shift => {}
+                            Resolution::Reduce => actions[terminal] = Action::Reduce(rules[0]),
+                            Resolution::Error => actions[terminal] = Action::Error,
+                        }
+                        continue;
+                    }
+                }
+
+                if !shift {
+                    actions[terminal] = if accept {
+                        Action::Accept
+                    } else {
+                        Action::Reduce(rules[0])
+                    };
+                }
+                self.conflicts.push(Conflict {
+                    state,
+                    lookahead: self.terminals.get(terminal).cloned(),
+                    example: examples[state]
+                        .iter()
+                        .map(|&terminal| self.terminals[terminal].clone())
+                        .collect(),
+                    shift,
+                    accept,
+                    reduce: rules
+                        .iter()
+                        .map(|&rule| self.grammar.rules()[rule].clone())
+                        .collect(),
+                });
+            }
+            self.actions.push(actions);
+            self.gotos.push(gotos);
+        }
+    }
+}
+
+impl<T> LrTable<T> {
+    /// Get the grammar
+    #[must_use]
+    pub const fn grammar(&self) -> &Grammar<T> {
+        &self.grammar
+    }
+
+    /// Get the number of states
+    #[must_use]
+    pub const fn state_count(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Get the conflicts the grammar's filters didn't resolve
+    #[must_use]
+    pub fn conflicts(&self) -> &[Conflict<T>] {
+        &self.conflicts
+    }
+}
+
+/// How the grammar's filters resolve a shift/reduce conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resolution {
+    /// Shift the lookahead
+    Shift,
+    /// Reduce by the rule
+    Reduce,
+    /// Neither is allowed, as between non-associative rules
+    Error,
+}
+
+/// Decide between reducing by `rule` and shifting into the `shifting` items with the
+/// grammar's filters, or return `None` if the filters don't decide
+fn resolve<'a>(
+    automaton: &Automaton,
+    forbidden: &HashMap<(usize, Edge), HashSet<usize>>,
+    rule: usize,
+    mut shifting: impl Iterator<Item = &'a (usize, usize)>,
+) -> Option<Resolution> {
+    let forbids = |parent: usize, edge: Edge, child: usize| {
+        forbidden
+            .get(&(parent, edge))
+            .is_some_and(|rules| rules.contains(&child))
+    };
+    if !matches!(automaton.rhs[rule].last(), Some(Sym::Nonterminal(_))) {
+        return None;
+    }
+
+    let mut decision = None;
+    shifting.try_for_each(|&(other, dot)| {
+        if dot != 1 || !matches!(automaton.rhs[other][0], Sym::Nonterminal(_)) {
+            return None;
+        }
+        // Reducing makes `rule` the leftmost child of `other`; shifting makes `other`
+        // the rightmost child of `rule`
+        let action = match (
+            forbids(other, Edge::Left, rule),
+            forbids(rule, Edge::Right, other),
+        ) {
+            (false, true) => Resolution::Reduce,
+            (true, false) => Resolution::Shift,
+            (true, true) => Resolution::Error,
+            (false, false) => return None,
+        };
+        if decision.is_some_and(|decision| decision != action) {
+            return None;
+        }
+        decision = Some(action);
+        Some(())
+    })?;
+    decision
+}
+
+impl<T: Clone> ParseTable<T> for LrTable<T> {
+    fn terminal_count(&self) -> usize {
+        self.terminals.len()
+    }
+
+    fn terminal(&self, kind: &T) -> Option<usize> {
+        self.terminal_of.get(&discriminant(kind)).copied()
+    }
+
+    fn expected(&self, terminal: usize) -> Expected<T> {
+        Expected::Token(self.terminals[terminal].clone())
+    }
+
+    fn action(&self, state: usize, terminal: usize) -> Action {
+        self.actions[state][terminal]
+    }
+
+    fn goto(&self, state: usize, nonterminal: usize) -> Option<usize> {
+        self.gotos[state][nonterminal]
+    }
+
+    fn rule(&self, rule: usize) -> (usize, usize) {
+        self.rules[rule]
+    }
+}
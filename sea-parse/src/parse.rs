@@ -0,0 +1,145 @@
+//! Types that know how to parse themselves, usually through `#[derive(Parse)]`
+
+use crate::{many, optional, Input, ParseResult, Parser, Spanned};
+use sea_lex::{FileId, Span, TokenInfo};
+
+/// A syntax tree node that can be parsed from tokens of kind `T`
+///
+/// This is usually implemented with `#[derive(Parse)]`:
+///
+/// ```ignore
+/// #[derive(Parse)]
+/// #[parse(tokens = Tok)]
+/// struct Let {
+///     #[parse(token = Let)]
+///     _let: (),
+///     #[parse(token = Ident(_))]
+///     name: String,
+///     #[parse(token = Eq)]
+///     _eq: (),
+///     value: Expr,
+///     #[parse(span)]
+///     span: Span,
+/// }
+/// ```
+///
+/// The derive rejects grammars that are left-recursive, which a recursive-descent
+/// parser would loop on forever:
+///
+/// ```compile_fail
+/// # use sea_lex::Token;
+/// # use sea_parse::Parse;
+/// # #[derive(Debug, Clone, PartialEq, Token)]
+/// # enum Tok {
+/// #     #[token("+")]
+/// #     Plus,
+/// #     #[token("1")]
+/// #     One,
+/// # }
+/// #[derive(Parse)]
+/// #[parse(tokens = Tok)]
+/// enum Expr {
+///     Add(Box<Expr>, #[parse(token = Plus)] (), Box<Expr>),
+///     One(#[parse(token = One)] ()),
+/// }
+/// ```
+///
+/// Indirect left recursion through other derived types is a compile error too:
+///
+/// ```compile_fail
+/// # use sea_lex::Token;
+/// # use sea_parse::Parse;
+/// # #[derive(Debug, Clone, PartialEq, Token)]
+/// # enum Tok {
+/// #     #[token(";")]
+/// #     Semi,
+/// # }
+/// #[derive(Parse)]
+/// #[parse(tokens = Tok)]
+/// struct A(Option<Box<B>>, #[parse(token = Semi)] ());
+///
+/// #[derive(Parse)]
+/// #[parse(tokens = Tok)]
+/// struct B(Box<A>);
+/// ```
+pub trait Parse<T>: Sized {
+    /// How many nested types may be entered before this type's parser must consume a token
+    ///
+    /// Derived implementations compute this from the types that can begin them,
+    /// so that a left-recursive grammar fails to compile with a cycle error.
+    const LEFT_DEPTH: usize = 0;
+
+    /// Parse a node at the current position of `input`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if the tokens at the current position don't match
+    fn parse(input: &mut Input<T>) -> ParseResult<T, Self>;
+}
+
+/// A value that can be built from a single token, for `#[parse(token = ...)]` fields
+pub trait FromToken<T> {
+    /// Build a value from a token of `file`
+    fn from_token(token: TokenInfo<T>, file: FileId) -> Self;
+}
+
+impl<T> FromToken<T> for TokenInfo<T> {
+    fn from_token(token: Self, _file: FileId) -> Self {
+        token
+    }
+}
+
+impl<T> FromToken<T> for String {
+    fn from_token(token: TokenInfo<T>, _file: FileId) -> Self {
+        token.text
+    }
+}
+
+impl<T> FromToken<T> for () {
+    fn from_token(_token: TokenInfo<T>, _file: FileId) -> Self {}
+}
+
+impl<T> FromToken<T> for Span {
+    fn from_token(token: TokenInfo<T>, file: FileId) -> Self {
+        Self::new(file, token.start, token.end)
+    }
+}
+
+impl<T, N: FromToken<T>> FromToken<T> for Spanned<N> {
+    fn from_token(token: TokenInfo<T>, file: FileId) -> Self {
+        let span = Span::new(file, token.start, token.end);
+        Self::new(N::from_token(token, file), span)
+    }
+}
+
+impl<T, N: Parse<T>> Parse<T> for Box<N> {
+    const LEFT_DEPTH: usize = N::LEFT_DEPTH;
+
+    fn parse(input: &mut Input<T>) -> ParseResult<T, Self> {
+        N::parse(input).map(Self::new)
+    }
+}
+
+impl<T: Clone, N: Parse<T>> Parse<T> for Option<N> {
+    const LEFT_DEPTH: usize = N::LEFT_DEPTH;
+
+    fn parse(input: &mut Input<T>) -> ParseResult<T, Self> {
+        optional(N::parse).parse(input)
+    }
+}
+
+impl<T: Clone, N: Parse<T>> Parse<T> for Vec<N> {
+    const LEFT_DEPTH: usize = N::LEFT_DEPTH;
+
+    fn parse(input: &mut Input<T>) -> ParseResult<T, Self> {
+        many(N::parse).parse(input)
+    }
+}
+
+impl<T: Clone, N: Parse<T>> Parse<T> for Spanned<N> {
+    const LEFT_DEPTH: usize = N::LEFT_DEPTH;
+
+    fn parse(input: &mut Input<T>) -> ParseResult<T, Self> {
+        N::parse.spanned().parse(input)
+    }
+}
@@ -0,0 +1,542 @@
+//! The core parser trait and its method combinators
+
+use crate::{Expected, Input, ParseError, ParseResult, Spanned, Strategy};
+use sea_lex::{FileId, Span, TokenInfo};
+use std::rc::Rc;
+
+/// A parser over tokens of kind `T`
+///
+/// Any `Fn(&mut Input<T>) -> ParseResult<T, O>` is a parser, so custom parsers can be
+/// written as closures. A parser that fails may leave the input anywhere;
+/// combinators that backtrack restore it to a [`Checkpoint`](crate::Checkpoint) themselves.
+pub trait Parser<T> {
+    /// The value produced by this parser
+    type Output;
+
+    /// Run this parser at the current position of `input`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if the tokens at the current position don't match
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output>;
+
+    /// Parse all of the tokens of `file`
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that got furthest into the tokens if they don't match,
+    /// or if tokens are left over
+    fn parse_all(
+        &self,
+        file: FileId,
+        tokens: impl IntoIterator<Item = TokenInfo<T>>,
+    ) -> ParseResult<T, Self::Output>
+    where
+        T: Clone,
+        Self: Sized,
+    {
+        let mut input = Input::new(file, tokens);
+        let result = self.parse(&mut input).and_then(|output| {
+            if input.is_at_end() {
+                Ok(output)
+            } else {
+                Err(input.unexpected(vec![Expected::End]))
+            }
+        });
+        result.map_err(|error| input.take_furthest_error().unwrap_or(error))
+    }
+
+    /// Parse all of the tokens of `file`, recovering from as many errors as possible
+    ///
+    /// Returns the parsed node, if parsing succeeded (possibly by recovering from errors),
+    /// along with every error found, in source order. Recovery only happens in parsers
+    /// built with [`Parser::recover_with`] or [`expect`](crate::expect).
+    fn parse_recovering(
+        &self,
+        file: FileId,
+        tokens: impl IntoIterator<Item = TokenInfo<T>>,
+    ) -> (Option<Self::Output>, Vec<ParseError<T>>)
+    where
+        T: Clone,
+        Self: Sized,
+    {
+        let mut input = Input::new(file, tokens);
+        let result = self.parse(&mut input);
+        let mut errors = input.take_errors();
+        let output = match result {
+            Ok(output) => {
+                if !input.is_at_end() {
+                    let error = input.unexpected(vec![Expected::End]);
+                    errors.push(input.take_furthest_error().unwrap_or(error));
+                }
+                Some(output)
+            }
+            Err(error) => {
+                errors.push(input.take_furthest_error().unwrap_or(error));
+                None
+            }
+        };
+        errors.sort_by_key(|error| error.span.start);
+        (output, errors)
+    }
+
+    /// Transform the output of this parser
+    fn map<U, F>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Output) -> U,
+    {
+        Map { parser: self, f }
+    }
+
+    /// Transform the output of this parser, along with the location it was parsed from
+    fn map_with_span<U, F>(self, f: F) -> MapWithSpan<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Output, Span) -> U,
+    {
+        MapWithSpan { parser: self, f }
+    }
+
+    /// Transform the output of this parser, failing with a message if `f` returns an error
+    ///
+    /// The error covers the tokens this parser consumed.
+    fn try_map<U, F>(self, f: F) -> TryMap<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Output, Span) -> Result<U, String>,
+    {
+        TryMap { parser: self, f }
+    }
+
+    /// Attach the location this parser's output was parsed from
+    fn spanned(self) -> WithSpan<Self>
+    where
+        Self: Sized,
+    {
+        WithSpan { parser: self }
+    }
+
+    /// Replace the output of this parser with a fixed value
+    fn to<U: Clone>(self, value: U) -> To<Self, U>
+    where
+        Self: Sized,
+    {
+        To {
+            parser: self,
+            value,
+        }
+    }
+
+    /// Run this parser, then `next`, producing both outputs
+    fn then<P: Parser<T>>(self, next: P) -> Then<Self, P>
+    where
+        Self: Sized,
+    {
+        Then {
+            first: self,
+            second: next,
+        }
+    }
+
+    /// Run this parser, then `next`, keeping only this parser's output
+    fn then_ignore<P: Parser<T>>(self, next: P) -> ThenIgnore<Self, P>
+    where
+        Self: Sized,
+    {
+        ThenIgnore {
+            first: self,
+            second: next,
+        }
+    }
+
+    /// Run this parser, then `next`, keeping only the output of `next`
+    fn ignore_then<P: Parser<T>>(self, next: P) -> IgnoreThen<Self, P>
+    where
+        Self: Sized,
+    {
+        IgnoreThen {
+            first: self,
+            second: next,
+        }
+    }
+
+    /// Run this parser between `open` and `close`, keeping only this parser's output
+    fn delimited_by<L: Parser<T>, R: Parser<T>>(self, open: L, close: R) -> DelimitedBy<Self, L, R>
+    where
+        Self: Sized,
+    {
+        DelimitedBy {
+            parser: self,
+            open,
+            close,
+        }
+    }
+
+    /// Try this parser, and if it fails, try `other` from the same position
+    fn or<P: Parser<T, Output = Self::Output>>(self, other: P) -> Or<Self, P>
+    where
+        Self: Sized,
+    {
+        Or {
+            first: self,
+            second: other,
+        }
+    }
+
+    /// Name this parser in error messages
+    ///
+    /// If this parser fails without consuming any tokens, the error expects `label`
+    /// instead of whatever this parser would have accepted first.
+    fn labelled(self, label: &'static str) -> Labelled<Self>
+    where
+        Self: Sized,
+    {
+        Labelled {
+            parser: self,
+            label,
+        }
+    }
+
+    /// Recover from failures of this parser with `strategy`
+    ///
+    /// When this parser fails, the input is restored to where it started, and `strategy`
+    /// gets the error that got furthest. If the strategy succeeds, the error is reported
+    /// to the input and parsing continues; see [`Parser::parse_recovering`].
+    fn recover_with<S: Strategy<T, Self::Output>>(self, strategy: S) -> Recover<Self, S>
+    where
+        Self: Sized,
+    {
+        Recover {
+            parser: self,
+            strategy,
+        }
+    }
+
+    /// Erase the type of this parser, so that it can be stored or returned easily
+    fn boxed<'a>(self) -> BoxedParser<'a, T, Self::Output>
+    where
+        Self: Sized + 'a,
+    {
+        BoxedParser {
+            parser: Rc::new(self),
+        }
+    }
+}
+
+impl<T, O, F> Parser<T> for F
+where
+    F: Fn(&mut Input<T>) -> ParseResult<T, O>,
+{
+    type Output = O;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, O> {
+        self(input)
+    }
+}
+
+/// A parser that transforms the output of another. See [`Parser::map`].
+#[derive(Debug, Clone, Copy)]
+pub struct Map<P, F> {
+    /// The inner parser
+    parser: P,
+    /// The transformation
+    f: F,
+}
+
+impl<T, U, P: Parser<T>, F: Fn(P::Output) -> U> Parser<T> for Map<P, F> {
+    type Output = U;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, U> {
+        self.parser.parse(input).map(&self.f)
+    }
+}
+
+/// A parser that transforms the output of another with its location.
+/// See [`Parser::map_with_span`].
+#[derive(Debug, Clone, Copy)]
+pub struct MapWithSpan<P, F> {
+    /// The inner parser
+    parser: P,
+    /// The transformation
+    f: F,
+}
+
+impl<T: Clone, U, P: Parser<T>, F: Fn(P::Output, Span) -> U> Parser<T> for MapWithSpan<P, F> {
+    type Output = U;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, U> {
+        let start = input.position();
+        let output = self.parser.parse(input)?;
+        Ok((self.f)(output, input.span_since(start)))
+    }
+}
+
+/// A parser that fallibly transforms the output of another. See [`Parser::try_map`].
+#[derive(Debug, Clone, Copy)]
+pub struct TryMap<P, F> {
+    /// The inner parser
+    parser: P,
+    /// The transformation
+    f: F,
+}
+
+impl<T, U, P, F> Parser<T> for TryMap<P, F>
+where
+    T: Clone,
+    P: Parser<T>,
+    F: Fn(P::Output, Span) -> Result<U, String>,
+{
+    type Output = U;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, U> {
+        let start = input.position();
+        let output = self.parser.parse(input)?;
+        (self.f)(output, input.span_since(start))
+            .map_err(|message| input.custom_error(start, message))
+    }
+}
+
+/// A parser that attaches a location to the output of another. See [`Parser::spanned`].
+#[derive(Debug, Clone, Copy)]
+pub struct WithSpan<P> {
+    /// The inner parser
+    parser: P,
+}
+
+impl<T: Clone, P: Parser<T>> Parser<T> for WithSpan<P> {
+    type Output = Spanned<P::Output>;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        let start = input.position();
+        let output = self.parser.parse(input)?;
+        Ok(Spanned::new(output, input.span_since(start)))
+    }
+}
+
+/// A parser that replaces the output of another with a fixed value. See [`Parser::to`].
+#[derive(Debug, Clone, Copy)]
+pub struct To<P, U> {
+    /// The inner parser
+    parser: P,
+    /// The value to produce
+    value: U,
+}
+
+impl<T, U: Clone, P: Parser<T>> Parser<T> for To<P, U> {
+    type Output = U;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, U> {
+        self.parser.parse(input).map(|_| self.value.clone())
+    }
+}
+
+/// A parser that runs two parsers in sequence. See [`Parser::then`].
+#[derive(Debug, Clone, Copy)]
+pub struct Then<A, B> {
+    /// The first parser
+    first: A,
+    /// The second parser
+    second: B,
+}
+
+impl<T, A: Parser<T>, B: Parser<T>> Parser<T> for Then<A, B> {
+    type Output = (A::Output, B::Output);
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        let first = self.first.parse(input)?;
+        let second = self.second.parse(input)?;
+        Ok((first, second))
+    }
+}
+
+/// A parser that runs two parsers in sequence, keeping the first output.
+/// See [`Parser::then_ignore`].
+#[derive(Debug, Clone, Copy)]
+pub struct ThenIgnore<A, B> {
+    /// The first parser
+    first: A,
+    /// The second parser
+    second: B,
+}
+
+impl<T, A: Parser<T>, B: Parser<T>> Parser<T> for ThenIgnore<A, B> {
+    type Output = A::Output;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        let first = self.first.parse(input)?;
+        self.second.parse(input)?;
+        Ok(first)
+    }
+}
+
+/// A parser that runs two parsers in sequence, keeping the second output.
+/// See [`Parser::ignore_then`].
+#[derive(Debug, Clone, Copy)]
+pub struct IgnoreThen<A, B> {
+    /// The first parser
+    first: A,
+    /// The second parser
+    second: B,
+}
+
+impl<T, A: Parser<T>, B: Parser<T>> Parser<T> for IgnoreThen<A, B> {
+    type Output = B::Output;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        self.first.parse(input)?;
+        self.second.parse(input)
+    }
+}
+
+/// A parser surrounded by delimiters. See [`Parser::delimited_by`].
+#[derive(Debug, Clone, Copy)]
+pub struct DelimitedBy<P, L, R> {
+    /// The inner parser
+    parser: P,
+    /// The opening delimiter
+    open: L,
+    /// The closing delimiter
+    close: R,
+}
+
+impl<T, P: Parser<T>, L: Parser<T>, R: Parser<T>> Parser<T> for DelimitedBy<P, L, R> {
+    type Output = P::Output;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        self.open.parse(input)?;
+        let output = self.parser.parse(input)?;
+        self.close.parse(input)?;
+        Ok(output)
+    }
+}
+
+/// A parser that tries two alternatives. See [`Parser::or`].
+#[derive(Debug, Clone, Copy)]
+pub struct Or<A, B> {
+    /// The first alternative
+    first: A,
+    /// The second alternative
+    second: B,
+}
+
+impl<T: Clone, A: Parser<T>, B: Parser<T, Output = A::Output>> Parser<T> for Or<A, B> {
+    type Output = A::Output;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        let start = input.save();
+        match self.first.parse(input) {
+            Ok(output) => Ok(output),
+            Err(first) => {
+                input.restore(start);
+                self.second
+                    .parse(input)
+                    .map_err(|second| first.merge(second))
+            }
+        }
+    }
+}
+
+/// A parser with a name for error messages. See [`Parser::labelled`].
+#[derive(Debug, Clone, Copy)]
+pub struct Labelled<P> {
+    /// The inner parser
+    parser: P,
+    /// The name of the parser
+    label: &'static str,
+}
+
+impl<T: Clone, P: Parser<T>> Parser<T> for Labelled<P> {
+    type Output = P::Output;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        let start = input.position();
+        let saved = input.furthest.clone();
+        self.parser.parse(input).map_err(|error| {
+            if error.index() != start || error.message.is_some() {
+                return error;
+            }
+
+            // Replace whatever the inner parser expected at `start` with the label,
+            // unless it recorded an error further along
+            let deeper = input
+                .furthest
+                .as_ref()
+                .is_some_and(|furthest| furthest.index() > start);
+            let error = ParseError::unexpected(
+                start,
+                error.span,
+                error.found,
+                vec![Expected::Label(self.label)],
+            );
+            if !deeper {
+                input.furthest = saved;
+                input.record(error.clone());
+            }
+            error
+        })
+    }
+}
+
+/// A parser that recovers from its failures. See [`Parser::recover_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct Recover<P, S> {
+    /// The inner parser
+    parser: P,
+    /// The recovery strategy
+    strategy: S,
+}
+
+impl<T: Clone, P: Parser<T>, S: Strategy<T, P::Output>> Parser<T> for Recover<P, S> {
+    type Output = P::Output;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, Self::Output> {
+        let start = input.save();
+        // Track the furthest error within this parser separately from any before it
+        let outer = input.furthest.take();
+        let result = self.parser.parse(input);
+        let inner = input.furthest.take();
+        input.furthest = outer;
+
+        let error = match result {
+            Ok(output) => {
+                if let Some(inner) = inner {
+                    input.record(inner);
+                }
+                return Ok(output);
+            }
+            Err(error) => inner.unwrap_or(error),
+        };
+
+        input.restore(start);
+        self.strategy
+            .recover(&self.parser, input, error.clone())
+            .map_err(|_| {
+                input.restore(start);
+                input.record(error.clone());
+                error
+            })
+    }
+}
+
+/// A type-erased, cheaply cloneable parser. See [`Parser::boxed`].
+pub struct BoxedParser<'a, T, O> {
+    /// The inner parser
+    parser: Rc<dyn Parser<T, Output = O> + 'a>,
+}
+
+impl<T, O> Clone for BoxedParser<'_, T, O> {
+    fn clone(&self) -> Self {
+        Self {
+            parser: Rc::clone(&self.parser),
+        }
+    }
+}
+
+impl<T, O> Parser<T> for BoxedParser<'_, T, O> {
+    type Output = O;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, O> {
+        self.parser.parse(input)
+    }
+}
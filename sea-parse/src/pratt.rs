@@ -0,0 +1,514 @@
+//! Pratt (operator-precedence) expression parsing
+
+use crate::{same_kind, Expected, Input, ParseResult, Parser};
+use sea_lex::{Span, TokenInfo};
+use std::rc::Rc;
+
+/// The associativity of an infix operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a = b = c` is `a = (b = c)`
+    Right,
+    /// `a < b < c` is an error
+    None,
+}
+
+/// Builds a prefix operator node from the operator and its operand
+type PrefixFn<'a, T, E> = Rc<dyn Fn(TokenInfo<T>, E, Span) -> E + 'a>;
+/// Builds an infix operator node from its operands and the operator
+type InfixFn<'a, T, E> = Rc<dyn Fn(E, TokenInfo<T>, E, Span) -> E + 'a>;
+/// Builds a postfix operator node from its operand and the operator
+type PostfixFn<'a, T, E> = Rc<dyn Fn(E, TokenInfo<T>, Span) -> E + 'a>;
+/// Builds a ternary node from the condition and the two branches
+type TernaryFn<'a, E> = Rc<dyn Fn(E, E, E, Span) -> E + 'a>;
+/// Builds a call node from the callee and the arguments
+type CallFn<'a, E> = Rc<dyn Fn(E, Vec<E>, Span) -> E + 'a>;
+/// Builds an index node from the indexed expression and the index
+type IndexFn<'a, E> = Rc<dyn Fn(E, E, Span) -> E + 'a>;
+
+/// A prefix operator
+struct Prefix<'a, T, E> {
+    /// The operator token
+    kind: T,
+    /// The operator's precedence
+    precedence: u16,
+    /// Builds the node
+    build: PrefixFn<'a, T, E>,
+}
+
+/// An operator that follows an operand
+enum Operator<'a, T, E> {
+    /// `a op b`
+    Infix {
+        /// The operator token
+        kind: T,
+        /// The operator's precedence
+        precedence: u16,
+        /// The operator's associativity
+        assoc: Assoc,
+        /// Builds the node
+        build: InfixFn<'a, T, E>,
+    },
+    /// `a op`
+    Postfix {
+        /// The operator token
+        kind: T,
+        /// The operator's precedence
+        precedence: u16,
+        /// Builds the node
+        build: PostfixFn<'a, T, E>,
+    },
+    /// `a ? b : c`, which is right-associative
+    Ternary {
+        /// The token between the condition and the first branch
+        question: T,
+        /// The token between the branches
+        colon: T,
+        /// The operator's precedence
+        precedence: u16,
+        /// Builds the node
+        build: TernaryFn<'a, E>,
+    },
+    /// `a(b, c, ...)`
+    Call {
+        /// The token opening the arguments
+        open: T,
+        /// The token between arguments
+        separator: T,
+        /// The token closing the arguments
+        close: T,
+        /// The operator's precedence
+        precedence: u16,
+        /// Builds the node
+        build: CallFn<'a, E>,
+    },
+    /// `a[b]`
+    Index {
+        /// The token opening the index
+        open: T,
+        /// The token closing the index
+        close: T,
+        /// The operator's precedence
+        precedence: u16,
+        /// Builds the node
+        build: IndexFn<'a, E>,
+    },
+}
+
+/// A Pratt parser for expressions built from `atom`s and operators
+///
+/// Operators are keyed by token kind (only the variant is compared, as with
+/// [`token`](crate::token)) and have a precedence, where higher precedences bind
+/// tighter. Operands inside brackets (the middle of a ternary, call arguments,
+/// and indices) are parsed as full expressions.
+///
+/// ```ignore
+/// let expr = recursive(|expr| {
+///     let atom = choice((number, expr.delimited_by(token(LParen), token(RParen))));
+///     Pratt::new(atom)
+///         .infix(Plus, 1, Assoc::Left, |lhs, _, rhs, _| Expr::Add(lhs.into(), rhs.into()))
+///         .infix(Star, 2, Assoc::Left, |lhs, _, rhs, _| Expr::Mul(lhs.into(), rhs.into()))
+///         .prefix(Minus, 3, |_, operand, _| Expr::Neg(operand.into()))
+///         .call(LParen, Comma, RParen, 4, |callee, args, _| Expr::Call(callee.into(), args))
+/// });
+/// ```
+pub struct Pratt<'a, T, E, P> {
+    /// The parser for operands
+    atom: P,
+    /// The prefix operators
+    prefix: Vec<Prefix<'a, T, E>>,
+    /// The operators that follow an operand
+    operators: Vec<Operator<'a, T, E>>,
+}
+
+impl<'a, T, E, P> Pratt<'a, T, E, P> {
+    /// Create a Pratt parser with no operators
+    pub const fn new(atom: P) -> Self {
+        Self {
+            atom,
+            prefix: Vec::new(),
+            operators: Vec::new(),
+        }
+    }
+
+    /// Add a prefix operator, such as `-a`
+    #[must_use]
+    pub fn prefix(
+        mut self,
+        kind: T,
+        precedence: u16,
+        build: impl Fn(TokenInfo<T>, E, Span) -> E + 'a,
+    ) -> Self {
+        self.prefix.push(Prefix {
+            kind,
+            precedence,
+            build: Rc::new(build),
+        });
+        self
+    }
+
+    /// Add an infix operator, such as `a + b`
+    #[must_use]
+    pub fn infix(
+        mut self,
+        kind: T,
+        precedence: u16,
+        assoc: Assoc,
+        build: impl Fn(E, TokenInfo<T>, E, Span) -> E + 'a,
+    ) -> Self {
+        self.operators.push(Operator::Infix {
+            kind,
+            precedence,
+            assoc,
+            build: Rc::new(build),
+        });
+        self
+    }
+
+    /// Add a postfix operator, such as `a!`
+    #[must_use]
+    pub fn postfix(
+        mut self,
+        kind: T,
+        precedence: u16,
+        build: impl Fn(E, TokenInfo<T>, Span) -> E + 'a,
+    ) -> Self {
+        self.operators.push(Operator::Postfix {
+            kind,
+            precedence,
+            build: Rc::new(build),
+        });
+        self
+    }
+
+    /// Add a right-associative ternary operator, such as `a ? b : c`
+    #[must_use]
+    pub fn ternary(
+        mut self,
+        question: T,
+        colon: T,
+        precedence: u16,
+        build: impl Fn(E, E, E, Span) -> E + 'a,
+    ) -> Self {
+        self.operators.push(Operator::Ternary {
+            question,
+            colon,
+            precedence,
+            build: Rc::new(build),
+        });
+        self
+    }
+
+    /// Add a call operator, such as `f(a, b)`
+    #[must_use]
+    pub fn call(
+        mut self,
+        open: T,
+        separator: T,
+        close: T,
+        precedence: u16,
+        build: impl Fn(E, Vec<E>, Span) -> E + 'a,
+    ) -> Self {
+        self.operators.push(Operator::Call {
+            open,
+            separator,
+            close,
+            precedence,
+            build: Rc::new(build),
+        });
+        self
+    }
+
+    /// Add an index operator, such as `a[i]`
+    #[must_use]
+    pub fn index(
+        mut self,
+        open: T,
+        close: T,
+        precedence: u16,
+        build: impl Fn(E, E, Span) -> E + 'a,
+    ) -> Self {
+        self.operators.push(Operator::Index {
+            open,
+            close,
+            precedence,
+            build: Rc::new(build),
+        });
+        self
+    }
+}
+
+impl<T, E> Operator<'_, T, E> {
+    /// Get the token that introduces this operator
+    const fn trigger(&self) -> &T {
+        match self {
+            Self::Infix { kind, .. } | Self::Postfix { kind, .. } => kind,
+            Self::Ternary { question, .. } => question,
+            Self::Call { open, .. } | Self::Index { open, .. } => open,
+        }
+    }
+
+    /// Get the binding powers of this operator on its left and right
+    ///
+    /// Binding powers are derived from precedences so that operators of the same
+    /// precedence group according to their associativity.
+    fn binding_power(&self) -> (u32, u32) {
+        let (precedence, right_assoc) = match self {
+            Self::Infix {
+                precedence, assoc, ..
+            } => (*precedence, *assoc == Assoc::Right),
+            Self::Ternary { precedence, .. } => (*precedence, true),
+            Self::Postfix { precedence, .. }
+            | Self::Call { precedence, .. }
+            | Self::Index { precedence, .. } => (*precedence, false),
+        };
+        let power = u32::from(precedence) * 2;
+        if right_assoc {
+            (power + 2, power + 1)
+        } else {
+            (power + 1, power + 2)
+        }
+    }
+}
+
+impl<T: Clone, E, P: Parser<T, Output = E>> Pratt<'_, T, E, P> {
+    /// Parse an expression whose operators all bind at least as tightly as `min_power`
+    fn parse_power(&self, input: &mut Input<T>, min_power: u32) -> ParseResult<T, E> {
+        let start = input.position();
+        let mut lhs = self.parse_prefix(input)?;
+        // The precedence of the non-associative operator just applied, if any
+        let mut non_assoc = None;
+
+        loop {
+            let Some(operator) = input.peek().and_then(|next| {
+                self.operators
+                    .iter()
+                    .find(|operator| same_kind(operator.trigger(), &next.kind))
+            }) else {
+                // Record what could have continued the expression, for error messages
+                let expected = self
+                    .operators
+                    .iter()
+                    .map(|operator| Expected::Token(operator.trigger().clone()))
+                    .collect();
+                input.unexpected(expected);
+                break;
+            };
+
+            let (left_power, right_power) = operator.binding_power();
+            if left_power < min_power {
+                break;
+            }
+            if let Operator::Infix {
+                assoc: Assoc::None,
+                precedence,
+                ..
+            } = operator
+            {
+                if non_assoc == Some(*precedence) {
+                    let operator_start = input.position();
+                    input.next_token();
+                    return Err(input.custom_error(
+                        operator_start,
+                        "Non-associative operators can't be chained",
+                    ));
+                }
+                non_assoc = Some(*precedence);
+            } else {
+                non_assoc = None;
+            }
+
+            let op = input.next_token().expect("the operator was just peeked");
+            lhs = match operator {
+                Operator::Infix { build, .. } => {
+                    let rhs = self.parse_power(input, right_power)?;
+                    build(lhs, op, rhs, input.span_since(start))
+                }
+                Operator::Postfix { build, .. } => build(lhs, op, input.span_since(start)),
+                Operator::Ternary { colon, build, .. } => {
+                    let if_true = self.parse_power(input, 0)?;
+                    expect(input, colon)?;
+                    let if_false = self.parse_power(input, right_power)?;
+                    build(lhs, if_true, if_false, input.span_since(start))
+                }
+                Operator::Call {
+                    separator,
+                    close,
+                    build,
+                    ..
+                } => {
+                    let args = self.parse_args(input, separator, close)?;
+                    build(lhs, args, input.span_since(start))
+                }
+                Operator::Index { close, build, .. } => {
+                    let index = self.parse_power(input, 0)?;
+                    expect(input, close)?;
+                    build(lhs, index, input.span_since(start))
+                }
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parse an atom, or a prefix operator and its operand
+    fn parse_prefix(&self, input: &mut Input<T>) -> ParseResult<T, E> {
+        let start = input.position();
+        let prefix = input.peek().and_then(|next| {
+            self.prefix
+                .iter()
+                .find(|prefix| same_kind(&prefix.kind, &next.kind))
+        });
+
+        if let Some(prefix) = prefix {
+            let op = input.next_token().expect("the operator was just peeked");
+            let operand = self.parse_power(input, u32::from(prefix.precedence) * 2 + 1)?;
+            return Ok((prefix.build)(op, operand, input.span_since(start)));
+        }
+
+        self.atom.parse(input).map_err(|error| {
+            if self.prefix.is_empty() {
+                return error;
+            }
+            input.rewind(start);
+            let expected = self
+                .prefix
+                .iter()
+                .map(|prefix| Expected::Token(prefix.kind.clone()))
+                .collect();
+            input.unexpected(expected).merge(error)
+        })
+    }
+
+    /// Parse the arguments of a call, after the opening token
+    fn parse_args(&self, input: &mut Input<T>, separator: &T, close: &T) -> ParseResult<T, Vec<E>> {
+        let mut args = Vec::new();
+        if input
+            .peek()
+            .is_some_and(|next| same_kind(&next.kind, close))
+        {
+            input.next_token();
+            return Ok(args);
+        }
+        loop {
+            args.push(self.parse_power(input, 0)?);
+            match input.peek() {
+                Some(next) if same_kind(&next.kind, separator) => {
+                    input.next_token();
+                }
+                Some(next) if same_kind(&next.kind, close) => {
+                    input.next_token();
+                    return Ok(args);
+                }
+                _ => {
+                    return Err(input.unexpected(vec![
+                        Expected::Token(separator.clone()),
+                        Expected::Token(close.clone()),
+                    ]));
+                }
+            }
+        }
+    }
+}
+
+/// Consume a token of the given kind
+fn expect<T: Clone>(input: &mut Input<T>, kind: &T) -> ParseResult<T, ()> {
+    if input.peek().is_some_and(|next| same_kind(&next.kind, kind)) {
+        input.next_token();
+        Ok(())
+    } else {
+        Err(input.unexpected(vec![Expected::Token(kind.clone())]))
+    }
+}
+
+impl<T: Clone, E, P: Parser<T, Output = E>> Parser<T> for Pratt<'_, T, E, P> {
+    type Output = E;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, E> {
+        self.parse_power(input, 0)
+    }
+}
+
+impl<T: Clone, E> Clone for Prefix<'_, T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            kind: self.kind.clone(),
+            precedence: self.precedence,
+            build: Rc::clone(&self.build),
+        }
+    }
+}
+
+impl<T: Clone, E> Clone for Operator<'_, T, E> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Infix {
+                kind,
+                precedence,
+                assoc,
+                build,
+            } => Self::Infix {
+                kind: kind.clone(),
+                precedence: *precedence,
+                assoc: *assoc,
+                build: Rc::clone(build),
+            },
+            Self::Postfix {
+                kind,
+                precedence,
+                build,
+            } => Self::Postfix {
+                kind: kind.clone(),
+                precedence: *precedence,
+                build: Rc::clone(build),
+            },
+            Self::Ternary {
+                question,
+                colon,
+                precedence,
+                build,
+            } => Self::Ternary {
+                question: question.clone(),
+                colon: colon.clone(),
+                precedence: *precedence,
+                build: Rc::clone(build),
+            },
+            Self::Call {
+                open,
+                separator,
+                close,
+                precedence,
+                build,
+            } => Self::Call {
+                open: open.clone(),
+                separator: separator.clone(),
+                close: close.clone(),
+                precedence: *precedence,
+                build: Rc::clone(build),
+            },
+            Self::Index {
+                open,
+                close,
+                precedence,
+                build,
+            } => Self::Index {
+                open: open.clone(),
+                close: close.clone(),
+                precedence: *precedence,
+                build: Rc::clone(build),
+            },
+        }
+    }
+}
+
+impl<T: Clone, E, P: Clone> Clone for Pratt<'_, T, E, P> {
+    fn clone(&self) -> Self {
+        Self {
+            atom: self.atom.clone(),
+            prefix: self.prefix.clone(),
+            operators: self.operators.clone(),
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Wadler-style pretty printing
+//!
+//! A [`Doc`] describes text along with the places it may break across lines. Rendering
+//! lays each [group](Doc::group) out on one line if it fits in the remaining width, and
+//! otherwise breaks every [line](Doc::line) directly inside it, indented by its
+//! [nesting](Doc::nest).
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Add;
+use std::rc::Rc;
+
+/// A document to be pretty printed
+///
+/// Documents are immutable and cheap to clone.
+///
+/// ```
+/// # use sea_parse::Doc;
+/// let args = ["first", "second", "third"].map(Doc::text);
+/// let call = Doc::text("call(")
+///     + (Doc::softline() + Doc::join(args, Doc::text(",") + Doc::line())).nest(4)
+///     + Doc::softline()
+///     + Doc::text(")");
+/// let call = call.group();
+/// assert_eq!(call.render(80), "call(first, second, third)");
+/// assert_eq!(call.render(20), "call(\n    first,\n    second,\n    third\n)");
+/// ```
+#[derive(Clone)]
+pub struct Doc(Rc<Node>);
+
+/// The parts of a document
+enum Node {
+    /// Nothing
+    Nil,
+    /// Text without newlines
+    Text(Cow<'static, str>),
+    /// A newline, or `flat` when its group fits on one line
+    Line(&'static str),
+    /// A newline, which also breaks every enclosing group
+    HardLine,
+    /// Documents one after another
+    Concat(Doc, Doc),
+    /// A document with its line breaks indented further
+    Nest(usize, Doc),
+    /// A document laid out flat if it fits
+    Group(Doc),
+}
+
+impl Doc {
+    /// The empty document
+    #[must_use]
+    pub fn nil() -> Self {
+        Self(Rc::new(Node::Nil))
+    }
+
+    /// Text, which shouldn't contain newlines
+    #[must_use]
+    pub fn text(text: impl Into<Cow<'static, str>>) -> Self {
+        Self(Rc::new(Node::Text(text.into())))
+    }
+
+    /// A line break, or a space when its group is flat
+    #[must_use]
+    pub fn line() -> Self {
+        Self(Rc::new(Node::Line(" ")))
+    }
+
+    /// A line break, or nothing when its group is flat
+    #[must_use]
+    pub fn softline() -> Self {
+        Self(Rc::new(Node::Line("")))
+    }
+
+    /// A line break that's always taken, forcing every enclosing group to break
+    #[must_use]
+    pub fn hardline() -> Self {
+        Self(Rc::new(Node::HardLine))
+    }
+
+    /// This document followed by `other`
+    #[must_use]
+    pub fn append(self, other: Self) -> Self {
+        match (&*self.0, &*other.0) {
+            (Node::Nil, _) => other,
+            (_, Node::Nil) => self,
+            _ => Self(Rc::new(Node::Concat(self, other))),
+        }
+    }
+
+    /// Indent the lines after each line break in this document by `indent` more columns
+    #[must_use]
+    pub fn nest(self, indent: usize) -> Self {
+        Self(Rc::new(Node::Nest(indent, self)))
+    }
+
+    /// Lay this document out on one line if it fits, and break its lines otherwise
+    ///
+    /// Only the lines directly inside the group break: nested groups are laid out
+    /// separately.
+    #[must_use]
+    pub fn group(self) -> Self {
+        Self(Rc::new(Node::Group(self)))
+    }
+
+    /// The documents in `docs`, one after another
+    #[must_use]
+    pub fn concat(docs: impl IntoIterator<Item = Self>) -> Self {
+        docs.into_iter().fold(Self::nil(), Self::append)
+    }
+
+    /// The documents in `docs`, with `separator` between each pair
+    #[must_use]
+    pub fn join(docs: impl IntoIterator<Item = Self>, separator: Self) -> Self {
+        let mut docs = docs.into_iter();
+        let first = docs.next().unwrap_or_else(Self::nil);
+        docs.zip(std::iter::repeat(separator))
+            .fold(first, |joined, (doc, separator)| {
+                joined.append(separator).append(doc)
+            })
+    }
+
+    /// Render this document into lines of at most `width` columns, where it can
+    ///
+    /// Text too long for a line still overflows it, and lines have no trailing spaces.
+    #[must_use]
+    pub fn render(&self, width: usize) -> String {
+        let mut out = String::new();
+        let mut column = 0;
+        // Indentation waiting for the next text, so that blank lines stay empty
+        let mut pending = None;
+        let mut stack = vec![(0, Mode::Break, self)];
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match &*doc.0 {
+                Node::Nil => {}
+                Node::Text(text) => {
+                    if text.is_empty() {
+                        continue;
+                    }
+                    if let Some(indent) = pending.take() {
+                        out.extend(std::iter::repeat_n(' ', indent));
+                    }
+                    out.push_str(text);
+                    column += text.chars().count();
+                }
+                Node::Line(flat) if mode == Mode::Flat => {
+                    if let Some(indent) = pending.take() {
+                        out.extend(std::iter::repeat_n(' ', indent));
+                    }
+                    out.push_str(flat);
+                    column += flat.len();
+                }
+                Node::Line(_) | Node::HardLine => {
+                    out.push('\n');
+                    pending = Some(indent);
+                    column = indent;
+                }
+                Node::Concat(first, second) => {
+                    stack.push((indent, mode, second));
+                    stack.push((indent, mode, first));
+                }
+                Node::Nest(more, doc) => stack.push((indent + more, mode, doc)),
+                Node::Group(doc) => {
+                    let mode =
+                        if mode == Mode::Flat || fits(width.saturating_sub(column), doc, &stack) {
+                            Mode::Flat
+                        } else {
+                            Mode::Break
+                        };
+                    stack.push((indent, mode, doc));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// How a group's lines are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// On one line
+    Flat,
+    /// Broken across lines
+    Break,
+}
+
+/// Reports whether `doc`, laid out flat, and whatever follows it up to the next line
+/// break in `rest` fit in `width` columns
+fn fits(width: usize, doc: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut remaining = width;
+    let mut rest = rest.iter().rev();
+    let mut stack = vec![(Mode::Flat, doc)];
+    loop {
+        let Some((mode, doc)) = stack
+            .pop()
+            .or_else(|| rest.next().map(|&(_, mode, doc)| (mode, doc)))
+        else {
+            return true;
+        };
+        let used = match &*doc.0 {
+            Node::Nil => 0,
+            Node::Text(text) => text.chars().count(),
+            Node::Line(flat) if mode == Mode::Flat => flat.len(),
+            Node::Line(_) => return true,
+            Node::HardLine => return mode == Mode::Break,
+            Node::Concat(first, second) => {
+                stack.push((mode, second));
+                stack.push((mode, first));
+                0
+            }
+            Node::Nest(_, doc) | Node::Group(doc) => {
+                stack.push((mode, doc));
+                0
+            }
+        };
+        remaining = match remaining.checked_sub(used) {
+            Some(remaining) => remaining,
+            None => return false,
+        };
+    }
+}
+
+impl Add for Doc {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.append(other)
+    }
+}
+
+impl Default for Doc {
+    fn default() -> Self {
+        Self::nil()
+    }
+}
+
+impl From<&'static str> for Doc {
+    fn from(text: &'static str) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<String> for Doc {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+impl fmt::Debug for Doc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &*self.0 {
+            Node::Nil => f.write_str("Nil"),
+            Node::Text(text) => f.debug_tuple("Text").field(text).finish(),
+            Node::Line(flat) => f.debug_tuple("Line").field(flat).finish(),
+            Node::HardLine => f.write_str("HardLine"),
+            Node::Concat(first, second) => {
+                f.debug_tuple("Concat").field(first).field(second).finish()
+            }
+            Node::Nest(indent, doc) => f.debug_tuple("Nest").field(indent).field(doc).finish(),
+            Node::Group(doc) => f.debug_tuple("Group").field(doc).finish(),
+        }
+    }
+}
+
+/// Documents display at the formatter's width, or 80 columns: `format!("{doc:100}")`
+impl fmt::Display for Doc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(f.width().unwrap_or(80)))
+    }
+}
+
+/// A syntax tree node that can be printed as source
+pub trait Pretty {
+    /// Build the document for this node
+    fn to_doc(&self) -> Doc;
+
+    /// Print this node into lines of at most `width` columns, where it can
+    fn to_pretty(&self, width: usize) -> String {
+        self.to_doc().render(width)
+    }
+}
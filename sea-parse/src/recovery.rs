@@ -0,0 +1,231 @@
+//! Recovery from syntax errors
+
+use crate::{same_kind, Expected, Input, ParseError, ParseResult, Parser, Repair};
+use sea_lex::{Span, TokenInfo};
+
+/// A way to continue parsing after a parser fails
+///
+/// A strategy that succeeds reports the error to the [`Input`], so that parsing can go on
+/// and find more errors. See [`Parser::recover_with`].
+pub trait Strategy<T, O> {
+    /// Recover from `error`, which `parser` failed with at the current position
+    ///
+    /// # Errors
+    ///
+    /// Returns `error` if this strategy can't recover here
+    fn recover<P: Parser<T, Output = O>>(
+        &self,
+        parser: &P,
+        input: &mut Input<T>,
+        error: ParseError<T>,
+    ) -> ParseResult<T, O>;
+}
+
+/// Panic-mode recovery: skip to a synchronization token. See [`skip_to`].
+#[derive(Debug, Clone)]
+pub struct SkipTo<T, F> {
+    /// Stop before any of these tokens
+    before: Vec<T>,
+    /// Stop after any of these tokens
+    after: Vec<T>,
+    /// Pairs of delimiters whose contents are skipped as a whole
+    delimiters: Vec<(T, T)>,
+    /// Builds an error node covering the skipped tokens
+    fallback: F,
+}
+
+/// Recover by skipping tokens up to a synchronization token, producing `fallback(span)`
+/// for the skipped tokens
+///
+/// Configure the synchronization tokens with [`before`](SkipTo::before) and
+/// [`after`](SkipTo::after); skipping also stops at the end of the input, or before a
+/// closing delimiter that doesn't match an opening one that was skipped. A closing
+/// delimiter listed in `after` also stops skipping when it closes the outermost skipped
+/// group, so `after([RBrace])` skips a whole braced body. Recovery fails
+/// if no tokens would be skipped, so a parser that fails at a synchronization token
+/// (for example, a statement parser at a block's `}`) fails as usual.
+pub const fn skip_to<T, O, F: Fn(Span) -> O>(fallback: F) -> SkipTo<T, F> {
+    SkipTo {
+        before: Vec::new(),
+        after: Vec::new(),
+        delimiters: Vec::new(),
+        fallback,
+    }
+}
+
+impl<T, F> SkipTo<T, F> {
+    /// Stop skipping before any of `kinds`
+    #[must_use]
+    pub fn before(mut self, kinds: impl IntoIterator<Item = T>) -> Self {
+        self.before.extend(kinds);
+        self
+    }
+
+    /// Stop skipping after consuming any of `kinds`
+    #[must_use]
+    pub fn after(mut self, kinds: impl IntoIterator<Item = T>) -> Self {
+        self.after.extend(kinds);
+        self
+    }
+
+    /// Skip everything between `open` and a matching `close`, including synchronization tokens
+    #[must_use]
+    pub fn balance(mut self, open: T, close: T) -> Self {
+        self.delimiters.push((open, close));
+        self
+    }
+}
+
+impl<T: Clone, O, F: Fn(Span) -> O> Strategy<T, O> for SkipTo<T, F> {
+    fn recover<P: Parser<T, Output = O>>(
+        &self,
+        _parser: &P,
+        input: &mut Input<T>,
+        error: ParseError<T>,
+    ) -> ParseResult<T, O> {
+        let start = input.position();
+        let is_any = |kinds: &[T], kind: &T| kinds.iter().any(|other| same_kind(other, kind));
+
+        // The closing delimiters of the skipped delimiters that are still open
+        let mut open: Vec<&T> = Vec::new();
+        while let Some(token) = input.peek() {
+            let kind = &token.kind;
+            if open.is_empty() {
+                if is_any(&self.before, kind) {
+                    break;
+                }
+                if is_any(&self.after, kind) {
+                    input.next_token();
+                    break;
+                }
+            }
+
+            if let Some((_, close)) = self
+                .delimiters
+                .iter()
+                .find(|(open, _)| same_kind(open, kind))
+            {
+                open.push(close);
+            } else if open.last().is_some_and(|close| same_kind(*close, kind)) {
+                open.pop();
+                if open.is_empty() && is_any(&self.after, kind) {
+                    input.next_token();
+                    break;
+                }
+            } else if open.is_empty()
+                && self
+                    .delimiters
+                    .iter()
+                    .any(|(_, close)| same_kind(close, kind))
+            {
+                // This closes something the failed parser didn't open
+                break;
+            }
+            input.next_token();
+        }
+
+        if input.position() == start {
+            return Err(error);
+        }
+        input.report(error);
+        Ok((self.fallback)(input.span_since(start)))
+    }
+}
+
+/// Deletion recovery: skip tokens until the parser succeeds. See [`skip_then_retry`].
+#[derive(Debug, Clone, Copy)]
+pub struct SkipThenRetry {
+    /// The maximum number of tokens to skip
+    max_skips: usize,
+}
+
+/// Recover by deleting up to `max_skips` tokens, retrying the parser after each
+///
+/// The reported error suggests deleting the skipped tokens.
+#[must_use]
+pub const fn skip_then_retry(max_skips: usize) -> SkipThenRetry {
+    SkipThenRetry { max_skips }
+}
+
+impl<T: Clone, O> Strategy<T, O> for SkipThenRetry {
+    fn recover<P: Parser<T, Output = O>>(
+        &self,
+        parser: &P,
+        input: &mut Input<T>,
+        error: ParseError<T>,
+    ) -> ParseResult<T, O> {
+        let start = input.save();
+        for _ in 0..self.max_skips {
+            if input.next_token().is_none() {
+                break;
+            }
+            let retry = input.save();
+            let deleted = input.span_since(start.position());
+            if let Ok(output) = parser.parse(input) {
+                input.report(error.with_repair(Repair::Delete { span: deleted }));
+                return Ok(output);
+            }
+            input.restore(retry);
+        }
+        input.restore(start);
+        Err(error)
+    }
+}
+
+/// A parser for a single token that repairs a missing or extra token. See [`expect`].
+#[derive(Debug, Clone)]
+pub struct Expect<T> {
+    /// The kind to match
+    kind: T,
+}
+
+/// Parse a single token of the same variant as `kind`, repairing the input if it isn't there
+///
+/// If the next token is unexpected but the one after it matches, the unexpected token is
+/// deleted. Otherwise, the token is inserted: the parser succeeds with an empty token
+/// without consuming anything. Either way, the error is reported to the input with a
+/// [`Repair`]. Since this never fails, it belongs where the grammar has already
+/// committed to a construct, not at the start of an alternative.
+pub const fn expect<T>(kind: T) -> Expect<T> {
+    Expect { kind }
+}
+
+impl<T: Clone> Parser<T> for Expect<T> {
+    type Output = TokenInfo<T>;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, TokenInfo<T>> {
+        let matches = |token: Option<&TokenInfo<T>>| {
+            token.is_some_and(|token| same_kind(&token.kind, &self.kind))
+        };
+        if matches(input.peek()) {
+            return Ok(input.next_token().expect("the token was just peeked"));
+        }
+
+        let found = input.peek().map(|token| token.kind.clone());
+        let error = ParseError::unexpected(
+            input.position(),
+            input.span(),
+            found,
+            vec![Expected::Token(self.kind.clone())],
+        );
+
+        if matches(input.peek_nth(1)) {
+            let span = input.span();
+            input.report(error.with_repair(Repair::Delete { span }));
+            input.next_token();
+            return Ok(input.next_token().expect("the token was just peeked"));
+        }
+
+        // Insert the token right after the previous one
+        let at = input.position().checked_sub(1).map_or_else(
+            || input.span().start,
+            |previous| input.tokens()[previous].end,
+        );
+        let span = Span::new(input.file(), at, at);
+        input.report(error.with_repair(Repair::Insert {
+            kind: self.kind.clone(),
+            span,
+        }));
+        Ok(TokenInfo::new(self.kind.clone(), "", at, at))
+    }
+}
@@ -0,0 +1,80 @@
+//! Recursive parsers
+
+use crate::{Input, ParseResult, Parser};
+use std::cell::OnceCell;
+use std::rc::{Rc, Weak};
+
+/// The slot holding a recursive parser's definition
+type Slot<'a, T, O> = OnceCell<Box<dyn Parser<T, Output = O> + 'a>>;
+
+/// A parser that may refer to itself. See [`recursive`].
+pub struct Recursive<'a, T, O> {
+    /// The parser's definition
+    slot: SlotRef<'a, T, O>,
+}
+
+/// A reference to a recursive parser's definition
+///
+/// The handle passed to the defining closure is weak, so that the definition
+/// doesn't keep itself alive.
+enum SlotRef<'a, T, O> {
+    /// A handle returned by [`recursive`]
+    Strong(Rc<Slot<'a, T, O>>),
+    /// A handle used within the parser's own definition
+    Weak(Weak<Slot<'a, T, O>>),
+}
+
+/// Define a parser in terms of itself
+///
+/// `define` receives a handle to the parser being defined, which can be cloned
+/// and used anywhere within the definition:
+///
+/// ```ignore
+/// let expr = recursive(|expr| {
+///     let atom = choice((number, expr.delimited_by(token(LParen), token(RParen))));
+///     sep_by(atom, token(Plus)).at_least(1)
+/// });
+/// ```
+pub fn recursive<'a, T, O, P, F>(define: F) -> Recursive<'a, T, O>
+where
+    P: Parser<T, Output = O> + 'a,
+    F: FnOnce(Recursive<'a, T, O>) -> P,
+{
+    let slot = Rc::new(OnceCell::new());
+    let handle = Recursive {
+        slot: SlotRef::Weak(Rc::downgrade(&slot)),
+    };
+    let parser: Box<dyn Parser<T, Output = O> + 'a> = Box::new(define(handle));
+    if slot.set(parser).is_err() {
+        unreachable!("a recursive parser is only defined once");
+    }
+    Recursive {
+        slot: SlotRef::Strong(slot),
+    }
+}
+
+impl<T, O> Clone for Recursive<'_, T, O> {
+    fn clone(&self) -> Self {
+        let slot = match &self.slot {
+            SlotRef::Strong(slot) => SlotRef::Strong(Rc::clone(slot)),
+            SlotRef::Weak(slot) => SlotRef::Weak(Weak::clone(slot)),
+        };
+        Self { slot }
+    }
+}
+
+impl<T, O> Parser<T> for Recursive<'_, T, O> {
+    type Output = O;
+
+    fn parse(&self, input: &mut Input<T>) -> ParseResult<T, O> {
+        let slot = match &self.slot {
+            SlotRef::Strong(slot) => Rc::clone(slot),
+            SlotRef::Weak(slot) => slot
+                .upgrade()
+                .expect("recursive parser used after its definition was dropped"),
+        };
+        slot.get()
+            .expect("recursive parser used while it was being defined")
+            .parse(input)
+    }
+}
@@ -0,0 +1,427 @@
+//! Scope graphs, for resolving names between parsing and type checking
+//!
+//! A [`ScopeGraph`] is a tree of scopes, each declaring symbols in namespaces chosen by
+//! the frontend (values and types, say). A reference to a name is resolved by looking
+//! in its scope and then each enclosing one, and the graph remembers the symbol each
+//! reference's span resolved to. What a reference can see within a scope depends on the
+//! scope's [`ScopeKind`], so a frontend can declare everything first and resolve
+//! afterwards, or resolve as it goes.
+
+use sea_lex::Span;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A scope in a [`ScopeGraph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScopeId(u32);
+
+/// A symbol declared in a [`ScopeGraph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SymbolId(u32);
+
+/// How the names declared in a scope are visible
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScopeKind {
+    /// Each name is visible after its declaration, and can only be declared once, like
+    /// the variables in a C block
+    Block,
+    /// Each name is visible after its declaration, and redeclaring it shadows the
+    /// earlier declaration from then on, like top-level `let`s in ML
+    Sequence,
+    /// Every name is visible throughout the scope, even before its declaration, and can
+    /// only be declared once, like the items of a module or a `let rec` group
+    Module,
+}
+
+/// A declared name, identified by a [`SymbolId`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration<N> {
+    /// The name
+    pub name: String,
+    /// The namespace the name is declared in
+    pub namespace: N,
+    /// The location of the declaration's name
+    pub span: Span,
+    /// The scope the name is declared in
+    pub scope: ScopeId,
+}
+
+/// A scope's place in the graph and its declarations
+#[derive(Debug, Clone)]
+struct Scope {
+    /// The enclosing scope, or `None` for the root
+    parent: Option<ScopeId>,
+    /// How the scope's names are visible
+    kind: ScopeKind,
+    /// The symbols declared in the scope with each name, in declaration order
+    names: HashMap<String, Vec<SymbolId>>,
+}
+
+/// A tree of scopes and the symbols declared in them, with the resolution of each
+/// reference
+///
+/// `N` is the frontend's type of namespaces: names in different namespaces never clash
+/// or resolve to each other.
+///
+/// ```
+/// # use sea_lex::{SourceDb, Span};
+/// # use sea_parse::{ScopeGraph, ScopeKind};
+/// # let mut db = SourceDb::new();
+/// # let file = db.add("test", "");
+/// # let at = |offset| Span::new(file, offset, offset + 1);
+/// let mut graph = ScopeGraph::new(ScopeKind::Module);
+/// let counter = graph.declare(graph.root(), "value", "counter", at(0))?;
+/// let block = graph.add_scope(graph.root(), ScopeKind::Block);
+/// graph.declare(block, "value", "limit", at(10))?;
+///
+/// assert_eq!(graph.resolve(block, "value", "counter", at(20))?, counter);
+/// assert_eq!(graph.resolution(at(20)), Some(counter));
+/// let error = graph.resolve(graph.root(), "value", "limit", at(30)).unwrap_err();
+/// assert_eq!(error.to_string(), "Can't find value `limit`");
+/// let error = graph.resolve(block, "value", "countr", at(40)).unwrap_err();
+/// assert_eq!(error.to_string(), "Can't find value `countr`, did you mean `counter`?");
+/// # Ok::<(), sea_parse::ResolveError<&str>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScopeGraph<N> {
+    /// The scopes, indexed by [`ScopeId`]
+    scopes: Vec<Scope>,
+    /// The symbols, indexed by [`SymbolId`]
+    symbols: Vec<Declaration<N>>,
+    /// The symbol each resolved reference refers to
+    references: HashMap<Span, SymbolId>,
+}
+
+impl<N: Copy + Eq> ScopeGraph<N> {
+    /// Create a graph with a root scope of the given kind
+    #[must_use]
+    pub fn new(kind: ScopeKind) -> Self {
+        Self {
+            scopes: vec![Scope {
+                parent: None,
+                kind,
+                names: HashMap::new(),
+            }],
+            symbols: Vec::new(),
+            references: HashMap::new(),
+        }
+    }
+
+    /// Get the root scope
+    #[must_use]
+    pub const fn root(&self) -> ScopeId {
+        ScopeId(0)
+    }
+
+    /// Add a scope nested in `parent`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph has more than `u32::MAX` scopes
+    pub fn add_scope(&mut self, parent: ScopeId, kind: ScopeKind) -> ScopeId {
+        let id = ScopeId(u32::try_from(self.scopes.len()).expect("too many scopes"));
+        self.scopes.push(Scope {
+            parent: Some(parent),
+            kind,
+            names: HashMap::new(),
+        });
+        id
+    }
+
+    /// Get the scope enclosing `scope`, or `None` for the root
+    #[must_use]
+    pub fn parent(&self, scope: ScopeId) -> Option<ScopeId> {
+        self.scope(scope).parent
+    }
+
+    /// Get the kind of `scope`
+    #[must_use]
+    pub fn kind(&self, scope: ScopeId) -> ScopeKind {
+        self.scope(scope).kind
+    }
+
+    /// Declare `name` in `namespace` in `scope`, where `span` is the location of the name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without declaring anything, if the name is already declared in
+    /// the same namespace in a scope that isn't a [`ScopeKind::Sequence`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph has more than `u32::MAX` symbols
+    pub fn declare(
+        &mut self,
+        scope: ScopeId,
+        namespace: N,
+        name: impl Into<String>,
+        span: Span,
+    ) -> Result<SymbolId, ResolveError<N>> {
+        let name = name.into();
+        if self.kind(scope) != ScopeKind::Sequence {
+            if let Some(previous) = self.lookup_local(scope, namespace, &name) {
+                return Err(ResolveError {
+                    span,
+                    kind: ResolveErrorKind::Duplicate {
+                        namespace,
+                        name,
+                        previous: self.symbol(previous).span,
+                    },
+                });
+            }
+        }
+        let id = SymbolId(u32::try_from(self.symbols.len()).expect("too many symbols"));
+        self.scopes[scope.0 as usize]
+            .names
+            .entry(name.clone())
+            .or_default()
+            .push(id);
+        self.symbols.push(Declaration {
+            name,
+            namespace,
+            span,
+            scope,
+        });
+        Ok(id)
+    }
+
+    /// Find the latest declaration of `name` in `namespace` in `scope` itself, wherever
+    /// it is in the scope
+    #[must_use]
+    pub fn lookup_local(&self, scope: ScopeId, namespace: N, name: &str) -> Option<SymbolId> {
+        self.scope(scope)
+            .names
+            .get(name)?
+            .iter()
+            .rev()
+            .copied()
+            .find(|&symbol| self.symbol(symbol).namespace == namespace)
+    }
+
+    /// Find the symbol that `name` in `namespace` refers to at `at` in `scope`, without
+    /// recording the reference
+    #[must_use]
+    pub fn lookup(&self, scope: ScopeId, namespace: N, name: &str, at: Span) -> Option<SymbolId> {
+        self.scopes_from(scope).find_map(|scope| {
+            let symbols = self.scope(scope).names.get(name)?;
+            symbols
+                .iter()
+                .rev()
+                .copied()
+                .find(|&symbol| self.visible(symbol, namespace, at))
+        })
+    }
+
+    /// Resolve `name` in `namespace`, referred to at `span` in `scope`, and record what
+    /// the reference refers to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no declaration of the name is visible, suggesting a similar
+    /// name that is
+    pub fn resolve(
+        &mut self,
+        scope: ScopeId,
+        namespace: N,
+        name: &str,
+        span: Span,
+    ) -> Result<SymbolId, ResolveError<N>> {
+        if let Some(symbol) = self.lookup(scope, namespace, name, span) {
+            self.references.insert(span, symbol);
+            return Ok(symbol);
+        }
+        Err(ResolveError {
+            span,
+            kind: ResolveErrorKind::Unresolved {
+                namespace,
+                name: name.to_string(),
+                suggestion: self.suggest(scope, namespace, name, span),
+            },
+        })
+    }
+
+    /// Get every symbol in `namespace` visible at `at` in `scope`, innermost first,
+    /// leaving out shadowed ones
+    #[must_use]
+    pub fn visible_symbols(&self, scope: ScopeId, namespace: N, at: Span) -> Vec<SymbolId> {
+        let mut seen = std::collections::HashSet::new();
+        let mut visible = Vec::new();
+        for scope in self.scopes_from(scope) {
+            let mut names: Vec<_> = self.scope(scope).names.iter().collect();
+            names.sort_unstable_by_key(|&(name, _)| name);
+            for (name, symbols) in names {
+                let symbol = symbols
+                    .iter()
+                    .rev()
+                    .copied()
+                    .find(|&symbol| self.visible(symbol, namespace, at));
+                if let Some(symbol) = symbol {
+                    if seen.insert(name) {
+                        visible.push(symbol);
+                    }
+                }
+            }
+        }
+        visible
+    }
+
+    /// Get a symbol
+    ///
+    /// # Panics
+    ///
+    /// Panics if the symbol isn't in this graph
+    #[must_use]
+    pub fn symbol(&self, symbol: SymbolId) -> &Declaration<N> {
+        &self.symbols[symbol.0 as usize]
+    }
+
+    /// Get every symbol, in declaration order
+    pub fn symbols(&self) -> impl Iterator<Item = (SymbolId, &Declaration<N>)> {
+        (0..).map(SymbolId).zip(&self.symbols)
+    }
+
+    /// Get the symbol that the reference at `span` resolved to
+    #[must_use]
+    pub fn resolution(&self, span: Span) -> Option<SymbolId> {
+        self.references.get(&span).copied()
+    }
+
+    /// Get the location of every resolved reference to `symbol`, in source order
+    #[must_use]
+    pub fn references(&self, symbol: SymbolId) -> Vec<Span> {
+        let mut spans: Vec<Span> = self
+            .references
+            .iter()
+            .filter(|&(_, &target)| target == symbol)
+            .map(|(&span, _)| span)
+            .collect();
+        spans.sort_unstable();
+        spans
+    }
+
+    /// Get a scope's data
+    fn scope(&self, scope: ScopeId) -> &Scope {
+        &self.scopes[scope.0 as usize]
+    }
+
+    /// Iterate over `scope` and each scope enclosing it, innermost first
+    fn scopes_from(&self, scope: ScopeId) -> impl Iterator<Item = ScopeId> + '_ {
+        std::iter::successors(Some(scope), |&scope| self.parent(scope))
+    }
+
+    /// Reports whether `symbol` is in `namespace` and visible from a reference at `at`
+    /// within its scope
+    fn visible(&self, symbol: SymbolId, namespace: N, at: Span) -> bool {
+        let symbol = self.symbol(symbol);
+        symbol.namespace == namespace
+            && (self.kind(symbol.scope) == ScopeKind::Module
+                || symbol.span.file != at.file
+                || symbol.span.start <= at.start)
+    }
+
+    /// Find the visible name most similar to `name`, if any is similar enough to be a
+    /// likely typo
+    fn suggest(&self, scope: ScopeId, namespace: N, name: &str, at: Span) -> Option<String> {
+        let visible = self.visible_symbols(scope, namespace, at);
+        let candidates = visible.iter().map(|&symbol| &*self.symbol(symbol).name);
+        suggest(name, candidates).map(ToString::to_string)
+    }
+}
+
+/// Find the candidate most similar to `name`, if any is similar enough to be a likely
+/// typo, for suggesting names a [`ScopeGraph`] doesn't know about, like built-ins
+///
+/// A third of a name's characters can be wrong, so names shorter than three characters
+/// get no suggestions.
+///
+/// ```
+/// # use sea_parse::suggest;
+/// assert_eq!(suggest("lenght", ["length", "list"]), Some("length"));
+/// assert_eq!(suggest("size", ["length", "list"]), None);
+/// ```
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = name.chars().count() / 3;
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Count the single-character insertions, deletions, and substitutions, and swaps of
+/// adjacent characters, that turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // The distances from the prefixes of `a` up to the last two rows to each prefix of `b`
+    let mut previous: Vec<usize> = Vec::new();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut next = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitute = row[j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            next[j] = substitute.min(row[j] + 1).min(next[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                next[j] = next[j].min(previous[j - 2] + 1);
+            }
+        }
+        previous = std::mem::replace(&mut row, next);
+    }
+    row[b.len()]
+}
+
+/// An error resolving names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveError<N> {
+    /// The location of the reference or declaration
+    pub span: Span,
+    /// What went wrong
+    pub kind: ResolveErrorKind<N>,
+}
+
+/// The kinds of [`ResolveError`]s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveErrorKind<N> {
+    /// No declaration of a name is visible
+    Unresolved {
+        /// The namespace the name was looked up in
+        namespace: N,
+        /// The name
+        name: String,
+        /// A similar name that is visible
+        suggestion: Option<String>,
+    },
+    /// A name is declared twice in the same scope
+    Duplicate {
+        /// The namespace of the name
+        namespace: N,
+        /// The name
+        name: String,
+        /// The location of the first declaration
+        previous: Span,
+    },
+}
+
+impl<N: fmt::Display> fmt::Display for ResolveError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ResolveErrorKind::Unresolved {
+                namespace,
+                name,
+                suggestion,
+            } => {
+                write!(f, "Can't find {namespace} `{name}`")?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean `{suggestion}`?")?;
+                }
+                Ok(())
+            }
+            ResolveErrorKind::Duplicate {
+                namespace, name, ..
+            } => write!(f, "Duplicate {namespace} `{name}` in the same scope"),
+        }
+    }
+}
+
+impl<N: fmt::Debug + fmt::Display> std::error::Error for ResolveError<N> {}
@@ -0,0 +1,274 @@
+//! Traversals over syntax trees
+//!
+//! A node type implements [`Visit`] and [`Fold`], usually with `#[derive(Visit, Fold)]`,
+//! by handing each of its fields to a [`Visitor`], [`VisitorMut`], or [`Folder`]. The
+//! traversal traits have one generic method each, which implementations override to act
+//! on the node types they care about (found with [`downcast_ref`], [`downcast_mut`], or
+//! [`fold_as`]) and then continue into the node's children, or not.
+
+use sea_lex::{FileId, Span};
+use std::any::Any;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A syntax tree node whose children can be visited
+pub trait Visit: Any {
+    /// Pass each child of this node to `visitor`
+    fn visit_children<V: Visitor + ?Sized>(&self, visitor: &mut V);
+
+    /// Pass each child of this node to `visitor`, mutably
+    fn visit_children_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V);
+}
+
+/// A traversal over shared references to the nodes of a syntax tree
+///
+/// ```
+/// # use sea_parse::{downcast_ref, Visit, Visitor};
+/// #[derive(Visit)]
+/// enum Expr {
+///     Number(i64),
+///     Add(Box<Expr>, Box<Expr>),
+/// }
+///
+/// struct Sum(i64);
+///
+/// impl Visitor for Sum {
+///     fn visit<N: Visit>(&mut self, node: &N) {
+///         if let Some(&number) = downcast_ref::<i64>(node) {
+///             self.0 += number;
+///         }
+///         node.visit_children(self);
+///     }
+/// }
+///
+/// let expr = Expr::Add(Box::new(Expr::Number(1)), Box::new(Expr::Number(2)));
+/// let mut sum = Sum(0);
+/// sum.visit(&expr);
+/// assert_eq!(sum.0, 3);
+/// ```
+pub trait Visitor {
+    /// Visit `node`, by default visiting each of its children in turn
+    ///
+    /// Work done before calling [`Visit::visit_children`] happens in preorder, and work
+    /// done after it in postorder; not calling it skips the node's children.
+    fn visit<N: Visit>(&mut self, node: &N) {
+        node.visit_children(self);
+    }
+}
+
+/// A traversal over mutable references to the nodes of a syntax tree, for rewriting
+/// nodes in place
+pub trait VisitorMut {
+    /// Visit `node`, by default visiting each of its children in turn
+    fn visit_mut<N: Visit>(&mut self, node: &mut N) {
+        node.visit_children_mut(self);
+    }
+}
+
+/// A syntax tree node that can be rebuilt from folded children
+pub trait Fold: Any + Sized {
+    /// Rebuild this node, passing each of its children through `folder`
+    #[must_use]
+    fn fold_children<F: Folder + ?Sized>(self, folder: &mut F) -> Self;
+}
+
+/// A traversal that consumes a syntax tree and builds a new one
+///
+/// ```
+/// # use sea_parse::{fold_as, Fold, Folder};
+/// #[derive(Debug, PartialEq, Fold)]
+/// enum Expr {
+///     Number(i64),
+///     Add(Box<Expr>, Box<Expr>),
+/// }
+///
+/// /// Folds constant additions, bottom-up
+/// struct ConstantFolder;
+///
+/// impl Folder for ConstantFolder {
+///     fn fold<N: Fold>(&mut self, node: N) -> N {
+///         fold_as(node.fold_children(self), |expr| match expr {
+///             Expr::Add(lhs, rhs) => match (*lhs, *rhs) {
+///                 (Expr::Number(lhs), Expr::Number(rhs)) => Expr::Number(lhs + rhs),
+///                 (lhs, rhs) => Expr::Add(Box::new(lhs), Box::new(rhs)),
+///             },
+///             expr => expr,
+///         })
+///     }
+/// }
+///
+/// let expr = Expr::Add(Box::new(Expr::Number(1)), Box::new(Expr::Number(2)));
+/// assert_eq!(ConstantFolder.fold(expr), Expr::Number(3));
+/// ```
+pub trait Folder {
+    /// Fold `node`, by default rebuilding it from its folded children
+    fn fold<N: Fold>(&mut self, node: N) -> N {
+        node.fold_children(self)
+    }
+}
+
+/// Get `node` as an `A`, if that's its type
+pub fn downcast_ref<A: Any>(node: &impl Any) -> Option<&A> {
+    (node as &dyn Any).downcast_ref()
+}
+
+/// Get `node` as a mutable `A`, if that's its type
+pub fn downcast_mut<A: Any>(node: &mut impl Any) -> Option<&mut A> {
+    (node as &mut dyn Any).downcast_mut()
+}
+
+/// Replace `node` with `f(node)` if it's an `A`, or return it unchanged if it isn't
+pub fn fold_as<N: Any, A: Any>(node: N, f: impl FnOnce(A) -> A) -> N {
+    let mut slot = Some(node);
+    if let Some(node) = downcast_mut::<Option<A>>(&mut slot) {
+        *node = node.take().map(f);
+    }
+    slot.unwrap_or_else(|| unreachable!("the slot is always refilled"))
+}
+
+/// Implement [`Visit`] and [`Fold`] for types without children
+macro_rules! leaves {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Visit for $ty {
+                fn visit_children<V: Visitor + ?Sized>(&self, _: &mut V) {}
+
+                fn visit_children_mut<V: VisitorMut + ?Sized>(&mut self, _: &mut V) {}
+            }
+
+            impl Fold for $ty {
+                fn fold_children<F: Folder + ?Sized>(self, _: &mut F) -> Self {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+leaves!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    String,
+    &'static str,
+    FileId,
+    Span,
+);
+
+impl<N: Visit> Visit for Box<N> {
+    fn visit_children<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit(&**self);
+    }
+
+    fn visit_children_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_mut(&mut **self);
+    }
+}
+
+impl<N: Fold> Fold for Box<N> {
+    fn fold_children<F: Folder + ?Sized>(self, folder: &mut F) -> Self {
+        Self::new(folder.fold(*self))
+    }
+}
+
+impl<N: Visit> Visit for Option<N> {
+    fn visit_children<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        if let Some(node) = self {
+            visitor.visit(node);
+        }
+    }
+
+    fn visit_children_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        if let Some(node) = self {
+            visitor.visit_mut(node);
+        }
+    }
+}
+
+impl<N: Fold> Fold for Option<N> {
+    fn fold_children<F: Folder + ?Sized>(self, folder: &mut F) -> Self {
+        self.map(|node| folder.fold(node))
+    }
+}
+
+impl<N: Visit> Visit for Vec<N> {
+    fn visit_children<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        for node in self {
+            visitor.visit(node);
+        }
+    }
+
+    fn visit_children_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        for node in self {
+            visitor.visit_mut(node);
+        }
+    }
+}
+
+impl<N: Fold> Fold for Vec<N> {
+    fn fold_children<F: Folder + ?Sized>(self, folder: &mut F) -> Self {
+        self.into_iter().map(|node| folder.fold(node)).collect()
+    }
+}
+
+/// Shared nodes are visited, but can't be visited mutably or folded
+macro_rules! shared {
+    ($($pointer:ident),*) => {
+        $(
+            impl<N: Visit> Visit for $pointer<N> {
+                fn visit_children<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+                    visitor.visit(&**self);
+                }
+
+                fn visit_children_mut<V: VisitorMut + ?Sized>(&mut self, _: &mut V) {}
+            }
+        )*
+    };
+}
+
+shared!(Rc, Arc);
+
+/// Implement [`Visit`] and [`Fold`] for tuples, whose children are their elements
+macro_rules! tuples {
+    ($(($($name:ident),+)),* $(,)?) => {
+        $(
+            impl<$($name: Visit),+> Visit for ($($name,)+) {
+                #[allow(non_snake_case)]
+                fn visit_children<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+                    let ($($name,)+) = self;
+                    $(visitor.visit($name);)+
+                }
+
+                #[allow(non_snake_case)]
+                fn visit_children_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+                    let ($($name,)+) = self;
+                    $(visitor.visit_mut($name);)+
+                }
+            }
+
+            impl<$($name: Fold),+> Fold for ($($name,)+) {
+                #[allow(non_snake_case)]
+                fn fold_children<F: Folder + ?Sized>(self, folder: &mut F) -> Self {
+                    let ($($name,)+) = self;
+                    ($(folder.fold($name),)+)
+                }
+            }
+        )*
+    };
+}
+
+tuples!((A), (A, B), (A, B, C), (A, B, C, D));
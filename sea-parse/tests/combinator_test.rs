@@ -0,0 +1,234 @@
+use sea_lex::{SourceDb, Span, Token, TokenInfo};
+use sea_parse::{
+    choice, end, many, optional, recursive, select, sep_by, seq, token, Expected, Input,
+    ParseError, Parser, Spanned,
+};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum Tok {
+    #[token("let")]
+    Let,
+    #[token(r"\d+", str::parse)]
+    Number(i64),
+    #[token(r"[a-z_]+", String::from)]
+    Ident(String),
+    #[token("=")]
+    Eq,
+    #[token("+")]
+    Plus,
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+    #[token(",")]
+    Comma,
+    #[token(";")]
+    Semi,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(i64),
+    Var(String),
+    Call(String, Vec<Spanned<Expr>>),
+    Add(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Let {
+    name: String,
+    value: Spanned<Expr>,
+}
+
+/// Parse an identifier's name
+fn ident() -> impl Parser<Tok, Output = String> + Clone {
+    select(|token: &TokenInfo<Tok>| match &token.kind {
+        Tok::Ident(name) => Some(name.clone()),
+        _ => None,
+    })
+    .labelled("identifier")
+}
+
+/// Parse an expression
+fn expr<'a>() -> impl Parser<Tok, Output = Spanned<Expr>> + Clone + 'a {
+    recursive(|expr| {
+        let number = select(|token: &TokenInfo<Tok>| match token.kind {
+            Tok::Number(value) => Some(Expr::Number(value)),
+            _ => None,
+        });
+        let call = seq((
+            ident(),
+            sep_by(expr.clone(), token(Tok::Comma))
+                .allow_trailing()
+                .delimited_by(token(Tok::LParen), token(Tok::RParen)),
+        ))
+        .map(|(name, args)| Expr::Call(name, args));
+        let atom = choice((
+            number,
+            call,
+            ident().map(Expr::Var),
+            expr.clone()
+                .delimited_by(token(Tok::LParen), token(Tok::RParen))
+                .map(|inner: Spanned<Expr>| inner.node),
+        ))
+        .spanned()
+        .labelled("expression");
+
+        seq((atom.clone(), many(token(Tok::Plus).ignore_then(atom)))).map_with_span(
+            |(first, rest), span| {
+                let node = rest.into_iter().fold(first, |lhs, rhs| {
+                    let span = lhs.span.to(rhs.span);
+                    Spanned::new(Expr::Add(Box::new(lhs), Box::new(rhs)), span)
+                });
+                Spanned::new(node.node, span)
+            },
+        )
+    })
+}
+
+/// Parse a `let` statement
+fn let_stmt<'a>() -> impl Parser<Tok, Output = Let> + 'a {
+    seq((
+        token(Tok::Let),
+        ident(),
+        token(Tok::Eq),
+        expr(),
+        token(Tok::Semi),
+    ))
+    .map(|(_, name, _, value, _)| Let { name, value })
+}
+
+/// Lex `source` and run `parser` over all of it
+fn run<P: Parser<Tok>>(parser: P, source: &str) -> (Result<P::Output, ParseError<Tok>>, SourceDb) {
+    let mut db = SourceDb::new();
+    let file = db.add("test", source);
+    let tokens = Tok::lexer(source).collect().unwrap();
+    (parser.parse_all(file, tokens), db)
+}
+
+#[test]
+fn test_sequences_and_choices() {
+    let (result, db) = run(let_stmt(), "let x = f(1, y + 2) + (3);");
+    let stmt = result.unwrap();
+    assert_eq!(stmt.name, "x");
+    assert_eq!(db.text(stmt.value.span), "f(1, y + 2) + (3)");
+
+    let Expr::Add(call, three) = &stmt.value.node else {
+        panic!("expected an addition, got {:?}", stmt.value.node);
+    };
+    assert_eq!(db.text(call.span), "f(1, y + 2)");
+    assert_eq!(db.text(three.span), "(3)");
+    assert_eq!(three.node, Expr::Number(3));
+
+    let Expr::Call(name, args) = &call.node else {
+        panic!("expected a call, got {:?}", call.node);
+    };
+    assert_eq!(name, "f");
+    let args: Vec<_> = args.iter().map(|arg| db.text(arg.span)).collect();
+    assert_eq!(args, ["1", "y + 2"]);
+}
+
+#[test]
+fn test_repetition() {
+    let (result, _) = run(many(let_stmt()), "let a = 1; let b = a; let c = b + a;");
+    let names: Vec<_> = result.unwrap().into_iter().map(|stmt| stmt.name).collect();
+    assert_eq!(names, ["a", "b", "c"]);
+
+    let (result, _) = run(many(let_stmt()), "");
+    assert!(result.unwrap().is_empty());
+
+    let (result, _) = run(many(token(Tok::Semi)).at_least(2), ";");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_separated_lists() {
+    let list = || sep_by(token(Tok::Number(0)), token(Tok::Comma));
+    assert_eq!(run(list(), "1, 2, 3").0.unwrap().len(), 3);
+    assert_eq!(run(list(), "").0.unwrap().len(), 0);
+    assert!(run(list(), "1, 2,").0.is_err());
+    assert_eq!(run(list().allow_trailing(), "1, 2,").0.unwrap().len(), 2);
+    assert!(run(list().at_least(1), "").0.is_err());
+}
+
+#[test]
+fn test_optional() {
+    let parser = seq((optional(token(Tok::Let)), ident()));
+    let (result, _) = run(parser.clone(), "let x");
+    assert!(result.unwrap().0.is_some());
+    let (result, _) = run(parser, "x");
+    assert_eq!(result.unwrap(), (None, "x".to_string()));
+}
+
+#[test]
+fn test_errors_report_furthest_failure() {
+    // The failure inside the call's arguments is further than the
+    // alternatives that would have stopped at `f`
+    let (result, db) = run(let_stmt(), "let x = f(1, +);");
+    let error = result.unwrap_err();
+    assert_eq!(db.text(error.span), "+");
+    assert_eq!(error.found, Some(Tok::Plus));
+    // Arguments allow a trailing comma, so `)` would also be accepted
+    assert_eq!(
+        error.expected,
+        [Expected::Label("expression"), Expected::Token(Tok::RParen)]
+    );
+    assert_eq!(
+        error.to_string(),
+        "Expected one of expression, `)`, found `+`"
+    );
+
+    let (result, _) = run(let_stmt(), "let x = ;");
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Expected expression, found `;`"
+    );
+
+    let (result, _) = run(let_stmt(), "let x = 1");
+    let error = result.unwrap_err();
+    assert_eq!(error.found, None);
+    assert!(error.expected.contains(&Expected::Token(Tok::Semi)));
+    assert!(error.expected.contains(&Expected::Token(Tok::Plus)));
+
+    let (result, _) = run(let_stmt(), "let x = 1; ;");
+    let error = result.unwrap_err();
+    assert_eq!(error.expected, [Expected::End]);
+    assert_eq!(error.to_string(), "Expected end of input, found `;`");
+}
+
+#[test]
+fn test_choice_merges_expectations() {
+    let parser = choice((token(Tok::Let), token(Tok::Semi)));
+    let (result, _) = run(parser, "=");
+    let error = result.unwrap_err();
+    assert_eq!(
+        error.expected,
+        [Expected::Token(Tok::Let), Expected::Token(Tok::Semi)]
+    );
+    assert_eq!(error.to_string(), "Expected one of `let`, `;`, found `=`");
+}
+
+#[test]
+fn test_try_map_and_manual_input() {
+    let small = select(|token: &TokenInfo<Tok>| match token.kind {
+        Tok::Number(value) => Some(value),
+        _ => None,
+    })
+    .try_map(|value, _| {
+        if value < 100 {
+            Ok(value)
+        } else {
+            Err(format!("{value} is too large"))
+        }
+    });
+
+    let mut db = SourceDb::new();
+    let file = db.add("test", "7 700");
+    let mut input = Input::new(file, Tok::lexer("7 700").collect().unwrap());
+    assert_eq!(small.parse(&mut input).unwrap(), 7);
+    let error = small.parse(&mut input).unwrap_err();
+    assert_eq!(error.to_string(), "700 is too large");
+    assert_eq!(error.span, Span::new(file, 2, 5));
+    assert!(end().parse(&mut input).is_ok());
+}
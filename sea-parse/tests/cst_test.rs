@@ -0,0 +1,404 @@
+use sea_lex::{LexError, Token, TokenInfo};
+use sea_parse::{AstNode, CstParser, SyntaxNode, SyntaxTree, TextEdit, TokenKind, TreeBuilder};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+#[skip(r"//[^\n]*")]
+enum Tok {
+    #[token("let")]
+    Let,
+    #[token(r"\d+", str::parse)]
+    Number(i64),
+    #[token(r"[a-z_]+", String::from)]
+    Ident(String),
+    #[token("=")]
+    Eq,
+    #[token("+")]
+    Plus,
+    #[token("*")]
+    Star,
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+    #[token("{")]
+    LBrace,
+    #[token("}")]
+    RBrace,
+    #[token(";")]
+    Semi,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Root,
+    Let,
+    Block,
+    ExprStmt,
+    Binary,
+    Literal,
+    Name,
+    Paren,
+    Error,
+}
+
+type Builder<'a> = TreeBuilder<'a, Tok, Kind>;
+
+fn lex(text: &str) -> Result<Vec<TokenInfo<Tok>>, LexError> {
+    Tok::lexer(text).collect()
+}
+
+fn root(p: &mut Builder) {
+    p.start_node(Kind::Root);
+    while !p.at_end() {
+        if p.at(&Tok::RBrace) {
+            p.error("Unmatched RBrace");
+            p.start_node(Kind::Error);
+            p.bump();
+            p.finish_node();
+        } else {
+            stmt(p);
+        }
+    }
+    p.finish_node();
+}
+
+fn stmt(p: &mut Builder) {
+    if p.at(&Tok::Let) {
+        p.start_node(Kind::Let);
+        p.bump();
+        p.expect(&Tok::Ident(String::new()));
+        p.expect(&Tok::Eq);
+        expr(p, 0);
+        p.expect(&Tok::Semi);
+        p.finish_node();
+    } else if p.at(&Tok::LBrace) {
+        block(p);
+    } else {
+        p.start_node(Kind::ExprStmt);
+        expr(p, 0);
+        p.expect(&Tok::Semi);
+        p.finish_node();
+    }
+}
+
+fn block(p: &mut Builder) {
+    p.start_node(Kind::Block);
+    p.expect(&Tok::LBrace);
+    while !p.at_end() && !p.at(&Tok::RBrace) {
+        stmt(p);
+    }
+    p.expect(&Tok::RBrace);
+    p.finish_node();
+}
+
+fn expr(p: &mut Builder, min_precedence: u8) {
+    let start = p.checkpoint();
+    atom(p);
+    loop {
+        let precedence = match p.peek() {
+            Some(Tok::Plus) => 1,
+            Some(Tok::Star) => 2,
+            _ => break,
+        };
+        if precedence < min_precedence {
+            break;
+        }
+        p.start_node_at(start, Kind::Binary);
+        p.bump();
+        expr(p, precedence + 1);
+        p.finish_node();
+    }
+}
+
+fn atom(p: &mut Builder) {
+    let kind = match p.peek() {
+        Some(Tok::Number(_)) => Kind::Literal,
+        Some(Tok::Ident(_)) => Kind::Name,
+        Some(Tok::LParen) => {
+            p.start_node(Kind::Paren);
+            p.bump();
+            expr(p, 0);
+            p.expect(&Tok::RParen);
+            p.finish_node();
+            return;
+        }
+        _ => {
+            p.error("Expected expression");
+            if p.at_end() || p.at(&Tok::Semi) || p.at(&Tok::RBrace) {
+                return;
+            }
+            Kind::Error
+        }
+    };
+    p.start_node(kind);
+    p.bump();
+    p.finish_node();
+}
+
+fn parser() -> CstParser<Tok, Kind> {
+    CstParser::new(lex, root).reparsable(Kind::Block, block)
+}
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, kind = Kind::Root)]
+struct Root(SyntaxNode<Tok, Kind>);
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, nodes = Kind)]
+enum Stmt {
+    Let(LetStmt),
+    Block(Block),
+    Expr(ExprStmt),
+}
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, kind = Kind::Let)]
+struct LetStmt {
+    syntax: SyntaxNode<Tok, Kind>,
+}
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, kind = Kind::Block)]
+struct Block(SyntaxNode<Tok, Kind>);
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, kind = Kind::ExprStmt)]
+struct ExprStmt(SyntaxNode<Tok, Kind>);
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, nodes = Kind)]
+enum Expr {
+    Binary(Binary),
+    Literal(Literal),
+    Name(Name),
+    Paren(Paren),
+}
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, kind = Kind::Binary)]
+struct Binary(SyntaxNode<Tok, Kind>);
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, kind = Kind::Literal)]
+struct Literal(SyntaxNode<Tok, Kind>);
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, kind = Kind::Name)]
+struct Name(SyntaxNode<Tok, Kind>);
+
+#[derive(AstNode)]
+#[ast(tokens = Tok, kind = Kind::Paren)]
+struct Paren(SyntaxNode<Tok, Kind>);
+
+impl LetStmt {
+    fn name(&self) -> Option<String> {
+        let token = self.syntax.child_token(&Tok::Ident(String::new()))?;
+        Some(token.text().to_string())
+    }
+
+    fn value(&self) -> Option<Expr> {
+        self.syntax.child()
+    }
+}
+
+impl Expr {
+    /// Evaluate the expression, with every variable set to 10
+    fn eval(&self) -> Option<i64> {
+        match self {
+            Self::Binary(binary) => {
+                let mut operands = binary.0.children_of::<Self>();
+                let lhs = operands.next()?.eval()?;
+                let rhs = operands.next()?.eval()?;
+                if binary.0.child_token(&Tok::Plus).is_some() {
+                    Some(lhs + rhs)
+                } else {
+                    Some(lhs * rhs)
+                }
+            }
+            Self::Literal(literal) => match literal.0.first_token()?.kind() {
+                TokenKind::Token(Tok::Number(value)) => Some(*value),
+                _ => None,
+            },
+            Self::Name(_) => Some(10),
+            Self::Paren(paren) => paren.0.child::<Self>()?.eval(),
+        }
+    }
+}
+
+/// Parse `text`, checking that the tree is lossless
+fn parse(text: &str) -> SyntaxTree<Tok, Kind> {
+    let tree = parser().parse(text).unwrap();
+    assert_eq!(tree.text(), text);
+    tree
+}
+
+/// Render a tree and its errors, for comparison
+fn dump(tree: &SyntaxTree<Tok, Kind>) -> String {
+    let mut dump = tree.syntax().debug_tree();
+    for error in tree.errors() {
+        dump += &format!("{:?}: {error}\n", error.range);
+    }
+    dump
+}
+
+#[test]
+fn test_lossless_tree() {
+    let tree = parse("let x = 1 + y; // sum\n");
+    assert!(tree.errors().is_empty());
+    assert_eq!(
+        tree.syntax().debug_tree(),
+        r#"Root@0..22
+  Let@0..14
+    Let@0..3 "let"
+    Trivia@3..4 " "
+    Ident("x")@4..5 "x"
+    Trivia@5..6 " "
+    Eq@6..7 "="
+    Trivia@7..8 " "
+    Binary@8..13
+      Literal@8..9
+        Number(1)@8..9 "1"
+      Trivia@9..10 " "
+      Plus@10..11 "+"
+      Trivia@11..12 " "
+      Name@12..13
+        Ident("y")@12..13 "y"
+    Semi@13..14 ";"
+  Trivia@14..22 " // sum\n"
+"#
+    );
+
+    // Text with errors is preserved too
+    let tree = parse("  let = (2 ;\n} x");
+    let errors: Vec<_> = tree
+        .errors()
+        .iter()
+        .map(|error| (error.range.clone(), error.to_string()))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (6..7, "Expected Ident, found `=`".into()),
+            (11..12, "Expected `)`, found `;`".into()),
+            (13..14, "Unmatched RBrace".into()),
+            (16..16, "Expected `;`, found end of input".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_typed_views() {
+    let tree = parse("let a = 2 * (3 + x); { let b = 4; } a;");
+    let root = Root::cast(tree.syntax()).unwrap();
+    let stmts: Vec<Stmt> = root.0.children_of().collect();
+    assert_eq!(stmts.len(), 3);
+
+    let Stmt::Let(stmt) = &stmts[0] else {
+        panic!("expected a let statement");
+    };
+    assert_eq!(stmt.name().as_deref(), Some("a"));
+    assert_eq!(stmt.value().unwrap().eval(), Some(26));
+
+    let Stmt::Block(block) = &stmts[1] else {
+        panic!("expected a block");
+    };
+    assert_eq!(block.syntax().text(), "{ let b = 4; }");
+    assert!(matches!(stmts[2], Stmt::Expr(_)));
+    assert!(Expr::cast(block.syntax().clone()).is_none());
+}
+
+#[test]
+fn test_navigation() {
+    let tree = parse("let x = (1 + y);");
+    let root = tree.syntax();
+
+    let one = root.token_at_offset(9).unwrap();
+    assert_eq!(one.text(), "1");
+    let kinds: Vec<_> = one.parent().ancestors().map(|node| node.kind()).collect();
+    assert_eq!(
+        kinds,
+        [
+            Kind::Literal,
+            Kind::Binary,
+            Kind::Paren,
+            Kind::Let,
+            Kind::Root
+        ]
+    );
+
+    assert_eq!(one.prev_token().unwrap().text(), "(");
+    assert!(one.next_token().unwrap().is_trivia());
+    assert_eq!(root.last_token().unwrap().prev_token().unwrap().text(), ")");
+
+    assert_eq!(root.covering_node(9..13).kind(), Kind::Binary);
+    let names: Vec<_> = root.descendants().map(|node| node.kind()).collect();
+    assert_eq!(
+        names,
+        [
+            Kind::Root,
+            Kind::Let,
+            Kind::Paren,
+            Kind::Binary,
+            Kind::Literal,
+            Kind::Name
+        ]
+    );
+    let text: String = root
+        .tokens()
+        .map(|token| token.text().to_string())
+        .collect();
+    assert_eq!(text, "let x = (1 + y);");
+}
+
+#[test]
+fn test_incremental_reparse_shares_subtrees() {
+    let parser = parser();
+    let text = "let a = 1; { let b = 2; } let c = 3;";
+    let tree = parse(text);
+    let old: Vec<_> = tree.syntax().children().collect();
+
+    // Within a token: only the token's ancestors are rebuilt
+    let edit = TextEdit::new(8..9, "42");
+    let new = parser.reparse(&tree, &edit).unwrap();
+    assert_eq!(dump(&new), dump(&parse(&edit.apply(text))));
+    let children: Vec<_> = new.syntax().children().collect();
+    assert!(!children[0].green().ptr_eq(old[0].green()));
+    assert!(children[1].green().ptr_eq(old[1].green()));
+
+    // Within a block: only the block is reparsed
+    let edit = TextEdit::new(23..23, " b;");
+    let new = parser.reparse(&tree, &edit).unwrap();
+    assert_eq!(dump(&new), dump(&parse(&edit.apply(text))));
+    let children: Vec<_> = new.syntax().children().collect();
+    assert_eq!(children[1].text(), "{ let b = 2; b; }");
+    assert!(children[0].green().ptr_eq(old[0].green()));
+    assert!(children[2].green().ptr_eq(old[2].green()));
+
+    // An edit that changes the structure around the block falls back to a full parse
+    let edit = TextEdit::new(24..25, "");
+    let new = parser.reparse(&tree, &edit).unwrap();
+    assert_eq!(dump(&new), dump(&parse(&edit.apply(text))));
+}
+
+#[test]
+fn test_incremental_reparse_matches_full_parse() {
+    let parser = parser();
+    let text = "let a = 1 + b; { x * (2 + 3); { y; } } // done\nlet c = 4;";
+    let tree = parse(text);
+    let insertions = ["", "1", "x", " ", ";", "{", "}", "+", "(", "// c\n"];
+
+    for start in 0..=text.len() {
+        for end in start..=(start + 2).min(text.len()) {
+            for insertion in insertions {
+                let edit = TextEdit::new(start..end, insertion);
+                let edited = edit.apply(text);
+                let Ok(expected) = parser.parse(&edited) else {
+                    continue;
+                };
+                let actual = parser.reparse(&tree, &edit).unwrap();
+                assert_eq!(dump(&actual), dump(&expected), "after {edit:?}");
+            }
+        }
+    }
+}
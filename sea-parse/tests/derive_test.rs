@@ -0,0 +1,242 @@
+use sea_lex::{SourceDb, Span, Token, TokenInfo};
+use sea_parse::{Expected, Parse, ParseError, Parser, Spanned};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum Tok {
+    #[token("let")]
+    Let,
+    #[token("fn")]
+    Fn,
+    #[token(r"\d+", str::parse)]
+    Number(i64),
+    #[token(r"[a-z_]+", String::from)]
+    Ident(String),
+    #[token("=")]
+    Eq,
+    #[token("<")]
+    Less,
+    #[token("+")]
+    Plus,
+    #[token("-")]
+    Minus,
+    #[token("*")]
+    Star,
+    #[token("^")]
+    Caret,
+    #[token("!")]
+    Bang,
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+    #[token("{")]
+    LBrace,
+    #[token("}")]
+    RBrace,
+    #[token(",")]
+    Comma,
+    #[token(";")]
+    Semi,
+}
+
+#[derive(Debug, Clone, PartialEq, Parse)]
+#[parse(tokens = Tok, label = "expression")]
+enum Expr {
+    #[parse(infix = Less, precedence = 1, assoc = none)]
+    Less(Box<Expr>, Box<Expr>),
+    #[parse(infix = Plus, precedence = 2)]
+    Add(Box<Expr>, Box<Expr>),
+    #[parse(infix = Minus, precedence = 2)]
+    Sub(Box<Expr>, Box<Expr>),
+    #[parse(infix = Star, precedence = 3)]
+    Mul {
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+        #[parse(span)]
+        span: Span,
+    },
+    #[parse(prefix = Minus, precedence = 4)]
+    Neg(Box<Expr>),
+    #[parse(infix = Caret, precedence = 5, assoc = right)]
+    Pow(Box<Expr>, Box<Expr>),
+    #[parse(postfix = Bang, precedence = 6)]
+    Factorial(Box<Expr>),
+    Number(#[parse(token = Number(_))] i64),
+    Call(Call),
+    Var(#[parse(token = Ident(_))] String),
+    #[parse(token = LParen)]
+    Paren(Box<Expr>, #[parse(token = RParen)] ()),
+}
+
+#[derive(Debug, Clone, PartialEq, Parse)]
+#[parse(tokens = Tok)]
+struct Call {
+    #[parse(token = Ident(..))]
+    name: Spanned<String>,
+    #[parse(token = LParen)]
+    _open: (),
+    #[parse(sep_by = Comma, trailing)]
+    args: Vec<Expr>,
+    #[parse(token = RParen)]
+    _close: (),
+}
+
+#[derive(Debug, Clone, PartialEq, Parse)]
+#[parse(tokens = Tok, label = "statement")]
+enum Stmt {
+    Let {
+        #[parse(token = Let)]
+        keyword: TokenInfo<Tok>,
+        #[parse(token = Ident(_))]
+        name: String,
+        #[parse(token = Eq)]
+        _eq: (),
+        value: Expr,
+        #[parse(token = Semi)]
+        _semi: (),
+        #[parse(span)]
+        span: Span,
+    },
+    Block(Block),
+    Expr(Expr, #[parse(token = Semi)] Option<()>),
+}
+
+#[derive(Debug, Clone, PartialEq, Parse)]
+#[parse(tokens = Tok)]
+struct Block {
+    #[parse(token = LBrace)]
+    open: Span,
+    stmts: Vec<Stmt>,
+    #[parse(token = RBrace)]
+    close: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Parse)]
+#[parse(tokens = Tok)]
+struct Function {
+    #[parse(token = Fn)]
+    _fn: (),
+    #[parse(token = Ident(_))]
+    name: String,
+    #[parse(token = LParen)]
+    _open: (),
+    #[parse(sep_by = Comma)]
+    params: Vec<Param>,
+    #[parse(token = RParen)]
+    _close: (),
+    body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Parse)]
+#[parse(tokens = Tok)]
+struct Param(#[parse(token = Ident(_))] String);
+
+/// Parse all of `source` as an `N`
+fn parse<N: Parse<Tok>>(source: &str) -> (Result<N, ParseError<Tok>>, SourceDb) {
+    let mut db = SourceDb::new();
+    let file = db.add("test", source);
+    let tokens = Tok::lexer(source).collect().unwrap();
+    ((N::parse).parse_all(file, tokens), db)
+}
+
+/// Render an expression as an S-expression
+fn sexpr(expr: &Expr) -> String {
+    match expr {
+        Expr::Less(a, b) => format!("(< {} {})", sexpr(a), sexpr(b)),
+        Expr::Add(a, b) => format!("(+ {} {})", sexpr(a), sexpr(b)),
+        Expr::Sub(a, b) => format!("(- {} {})", sexpr(a), sexpr(b)),
+        Expr::Mul { lhs, rhs, .. } => format!("(* {} {})", sexpr(lhs), sexpr(rhs)),
+        Expr::Neg(a) => format!("(- {})", sexpr(a)),
+        Expr::Pow(a, b) => format!("(^ {} {})", sexpr(a), sexpr(b)),
+        Expr::Factorial(a) => format!("(! {})", sexpr(a)),
+        Expr::Number(n) => n.to_string(),
+        Expr::Call(call) => {
+            let args: Vec<_> = call.args.iter().map(sexpr).collect();
+            format!("({} {})", call.name.node, args.join(" "))
+        }
+        Expr::Var(name) => name.clone(),
+        Expr::Paren(inner, ()) => sexpr(inner),
+    }
+}
+
+#[test]
+fn test_operator_variants() {
+    let expr = |source| sexpr(&parse::<Expr>(source).0.unwrap());
+    assert_eq!(expr("1 + 2 * 3 - x"), "(- (+ 1 (* 2 3)) x)");
+    assert_eq!(expr("-2 ^ 3 ^ 4!"), "(- (^ 2 (^ 3 (! 4))))");
+    assert_eq!(expr("(1 + 2) * f(a, b + 1,)"), "(* (+ 1 2) (f a (+ b 1)))");
+    assert_eq!(expr("a + 1 < b"), "(< (+ a 1) b)");
+    assert!(parse::<Expr>("a < b < c").0.is_err());
+}
+
+#[test]
+fn test_spans() {
+    let (result, db) = parse::<Expr>("a + b * (c)");
+    let Expr::Add(_, mul) = result.unwrap() else {
+        panic!("expected an addition");
+    };
+    let Expr::Mul { span, .. } = *mul else {
+        panic!("expected a multiplication");
+    };
+    assert_eq!(db.text(span), "b * (c)");
+
+    let (result, db) = parse::<Expr>("print(1)");
+    let Expr::Call(call) = result.unwrap() else {
+        panic!("expected a call");
+    };
+    assert_eq!(db.text(call.name.span), "print");
+}
+
+#[test]
+fn test_sequences() {
+    let source = "fn main(a, b) { let x = a + 1; { f(x) } b; }";
+    let (result, db) = parse::<Function>(source);
+    let function = result.unwrap();
+    assert_eq!(function.name, "main");
+    assert_eq!(function.params, [Param("a".into()), Param("b".into())]);
+    assert_eq!(db.text(function.body.open), "{");
+    assert_eq!(function.body.close.start, source.len() - 1);
+
+    let stmts = &function.body.stmts;
+    assert_eq!(stmts.len(), 3);
+    let Stmt::Let {
+        keyword,
+        name,
+        span,
+        ..
+    } = &stmts[0]
+    else {
+        panic!("expected a let statement");
+    };
+    assert_eq!(keyword.kind, Tok::Let);
+    assert_eq!(name, "x");
+    assert_eq!(db.text(*span), "let x = a + 1;");
+
+    let Stmt::Block(block) = &stmts[1] else {
+        panic!("expected a block");
+    };
+    assert!(matches!(
+        &block.stmts[..],
+        [Stmt::Expr(Expr::Call(_), None)]
+    ));
+    assert!(matches!(&stmts[2], Stmt::Expr(Expr::Var(_), Some(()))));
+}
+
+#[test]
+fn test_errors() {
+    let (result, db) = parse::<Function>("fn f(a) { let = 1; }");
+    let error = result.unwrap_err();
+    assert_eq!(db.text(error.span), "=");
+    assert_eq!(error.expected, [Expected::Label("Ident")]);
+
+    let (result, _) = parse::<Function>("fn f(a,) {}");
+    let error = result.unwrap_err();
+    assert_eq!(error.to_string(), "Expected Ident, found `)`");
+
+    let (result, _) = parse::<Block>("{ let x = ; }");
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Expected expression, found `;`"
+    );
+}
@@ -0,0 +1,170 @@
+use sea_lex::{SourceDb, Token};
+use sea_parse::{
+    nonterm, term, Assoc, EarleyParser, Expected, Forest, Grammar, GrammarError, ParseError,
+};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum Tok {
+    #[token(r"[a-z0-9]+", String::from)]
+    Word(String),
+    #[token("+")]
+    Plus,
+    #[token("*")]
+    Star,
+    #[token("==")]
+    EqEq,
+    #[token(",")]
+    Comma,
+}
+
+/// Parse `source` with `parser`
+fn parse<'g>(
+    parser: &'g EarleyParser<Tok>,
+    source: &str,
+) -> Result<Forest<'g, Tok>, ParseError<Tok>> {
+    let mut db = SourceDb::new();
+    let file = db.add("test", source);
+    parser.parse(file, Tok::lexer(source).collect().unwrap())
+}
+
+/// Print the only tree in `forest` as an S-expression
+fn sexpr(forest: &Forest<'_, Tok>) -> Result<String, ParseError<Tok>> {
+    forest.fold(
+        |token, _| match &token.kind {
+            Tok::Word(word) => word.clone(),
+            _ => token.text.clone(),
+        },
+        |rule, _, children| match rule.name {
+            Some(name) => format!("({name} {})", children.join(" ")),
+            None => children.join(" "),
+        },
+    )
+}
+
+/// An ambiguous grammar of sums, products, and comparisons
+fn expressions() -> Grammar<Tok> {
+    Grammar::new("E")
+        .named_rule("+", "E", [nonterm("E"), term(Tok::Plus), nonterm("E")])
+        .named_rule("*", "E", [nonterm("E"), term(Tok::Star), nonterm("E")])
+        .named_rule("==", "E", [nonterm("E"), term(Tok::EqEq), nonterm("E")])
+        .rule("E", [term(Tok::Word(String::new()))])
+}
+
+#[test]
+fn test_forest_shares_every_parse() {
+    let parser = EarleyParser::new(expressions()).unwrap();
+    let forest = parse(&parser, "a + b + c + d + e").unwrap();
+    assert!(forest.is_ambiguous());
+    // The 4th Catalan number
+    assert_eq!(forest.tree_count(), 14);
+    let error = sexpr(&forest).unwrap_err();
+    assert_eq!(error.to_string(), "Ambiguous E: 4 alternatives");
+
+    let forest = parse(&parser, "a").unwrap();
+    assert!(!forest.is_ambiguous());
+    assert_eq!(forest.tree_count(), 1);
+}
+
+#[test]
+fn test_filters_pick_one_tree() {
+    let grammar = expressions()
+        .assoc(Assoc::Left, ["+"])
+        .assoc(Assoc::Right, ["*"])
+        .assoc(Assoc::None, ["=="])
+        .priority(["*"], ["+"])
+        .priority(["+"], ["=="]);
+    let parser = EarleyParser::new(grammar).unwrap();
+
+    let forest = parse(&parser, "a + b + c").unwrap();
+    assert_eq!(sexpr(&forest).unwrap(), "(+ (+ a + b) + c)");
+    let forest = parse(&parser, "a * b * c").unwrap();
+    assert_eq!(sexpr(&forest).unwrap(), "(* a * (* b * c))");
+    let forest = parse(&parser, "a + b * c == d * e + f").unwrap();
+    assert_eq!(
+        sexpr(&forest).unwrap(),
+        "(== (+ a + (* b * c)) == (+ (* d * e) + f))"
+    );
+
+    // Priorities are transitive, and non-associative rules can't nest
+    let forest = parse(&parser, "a * b == c").unwrap();
+    assert_eq!(sexpr(&forest).unwrap(), "(== (* a * b) == c)");
+    let error = parse(&parser, "a == b == c").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Every parse was removed by the disambiguation filters"
+    );
+}
+
+#[test]
+fn test_reject_rules_remove_parses() {
+    // Two words are a declaration, never an expression
+    let grammar = Grammar::new("Stmt")
+        .named_rule("decl", "Stmt", [nonterm("Decl")])
+        .named_rule("expr", "Stmt", [nonterm("Expr")])
+        .rule(
+            "Decl",
+            [
+                term(Tok::Word(String::new())),
+                term(Tok::Word(String::new())),
+            ],
+        )
+        .rule("Expr", [term(Tok::Word(String::new()))])
+        .rule("Expr", [nonterm("Expr"), term(Tok::Word(String::new()))])
+        .named_rule("pair", "Expr", [nonterm("Decl")])
+        .reject("pair");
+    let parser = EarleyParser::new(grammar).unwrap();
+
+    let forest = parse(&parser, "a b").unwrap();
+    assert!(!forest.is_ambiguous());
+    assert_eq!(sexpr(&forest).unwrap(), "(decl a b)");
+    let forest = parse(&parser, "a").unwrap();
+    assert_eq!(sexpr(&forest).unwrap(), "(expr a)");
+}
+
+#[test]
+fn test_nullable_and_left_recursive_rules() {
+    let grammar = Grammar::new("Args")
+        .rule("Args", [])
+        .rule("Args", [nonterm("Args"), nonterm("Opt"), term(Tok::Comma)])
+        .rule("Opt", [])
+        .named_rule("arg", "Opt", [term(Tok::Word(String::new()))]);
+    let parser = EarleyParser::new(grammar).unwrap();
+
+    let forest = parse(&parser, "").unwrap();
+    assert_eq!(sexpr(&forest).unwrap(), "");
+    let forest = parse(&parser, "a, , b,").unwrap();
+    assert_eq!(forest.tree_count(), 1);
+    assert_eq!(sexpr(&forest).unwrap(), " (arg a) ,  , (arg b) ,");
+}
+
+#[test]
+fn test_errors() {
+    let parser = EarleyParser::new(expressions()).unwrap();
+    let error = parse(&parser, "a + * b").unwrap_err();
+    assert_eq!(error.found, Some(Tok::Star));
+    assert_eq!(error.expected.len(), 1);
+    assert!(matches!(error.expected[0], Expected::Token(Tok::Word(_))));
+
+    let error = parse(&parser, "a +").unwrap_err();
+    assert_eq!(error.found, None);
+    let error = parse(&parser, "a b").unwrap_err();
+    assert_eq!(error.found, Some(Tok::Word("b".to_string())));
+    assert!(matches!(error.expected.last(), Some(Expected::End)));
+
+    let grammar = expressions().rule("E", [nonterm("F")]);
+    assert_eq!(
+        EarleyParser::new(grammar).unwrap_err(),
+        GrammarError::UndefinedNonterminal("F")
+    );
+    let grammar = expressions().priority(["*"], ["-"]);
+    assert_eq!(
+        EarleyParser::new(grammar).unwrap_err(),
+        GrammarError::UnknownRule("-")
+    );
+    let grammar = expressions().named_rule("+", "E", []);
+    assert_eq!(
+        EarleyParser::new(grammar).unwrap_err(),
+        GrammarError::DuplicateRule("+")
+    );
+}
@@ -0,0 +1,136 @@
+use sea_lex::{SourceDb, Token};
+use sea_parse::{
+    nonterm, term, Assoc, ByteChoices, Choices, EarleyParser, Generator, Grammar, GrammarError,
+    LrTable, ParseTable, SeededChoices,
+};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum Tok {
+    #[token(r"[a-z]+", String::from)]
+    Ident(String),
+    #[token("==")]
+    EqEq,
+    #[token("+")]
+    Plus,
+    #[token("*")]
+    Star,
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+    #[token(";")]
+    Semi,
+}
+
+/// Statements of arithmetic, with filters that leave one parse
+fn statements() -> Grammar<Tok> {
+    Grammar::new("Stmts")
+        .rule("Stmts", [])
+        .rule(
+            "Stmts",
+            [nonterm("Stmts"), nonterm("Expr"), term(Tok::Semi)],
+        )
+        .named_rule(
+            "==",
+            "Expr",
+            [nonterm("Expr"), term(Tok::EqEq), nonterm("Expr")],
+        )
+        .named_rule(
+            "+",
+            "Expr",
+            [nonterm("Expr"), term(Tok::Plus), nonterm("Expr")],
+        )
+        .named_rule(
+            "*",
+            "Expr",
+            [nonterm("Expr"), term(Tok::Star), nonterm("Expr")],
+        )
+        .named_rule(
+            "paren",
+            "Expr",
+            [term(Tok::LParen), nonterm("Expr"), term(Tok::RParen)],
+        )
+        .rule("Expr", [term(Tok::Ident(String::new()))])
+        .assoc(Assoc::None, ["=="])
+        .assoc(Assoc::Left, ["+", "*"])
+        .priority(["*"], ["+"])
+        .priority(["+"], ["=="])
+}
+
+/// Spell a token
+fn spell(token: &Tok, choices: &mut impl Choices) -> String {
+    match token {
+        Tok::Ident(_) => ["x", "y", "z"][choices.below(3) as usize].to_string(),
+        Tok::EqEq => "==".to_string(),
+        Tok::Plus => "+".to_string(),
+        Tok::Star => "*".to_string(),
+        Tok::LParen => "(".to_string(),
+        Tok::RParen => ")".to_string(),
+        Tok::Semi => ";".to_string(),
+    }
+}
+
+#[test]
+fn test_generated_programs_parse() {
+    let generator = Generator::new(statements()).unwrap().max_depth(8);
+    let earley = EarleyParser::new(statements()).unwrap();
+    let lalr = LrTable::lalr(statements()).unwrap();
+    assert!(lalr.conflicts().is_empty());
+
+    let mut db = SourceDb::new();
+    for seed in 0..100 {
+        let source = generator.generate_source(&mut SeededChoices::new(seed), spell);
+        let file = db.add("generated", &source);
+        let tokens: Vec<_> = Tok::lexer(&source).collect().unwrap();
+
+        let forest = earley.parse(file, tokens.clone()).unwrap();
+        assert!(!forest.is_ambiguous(), "{source}");
+        lalr.parse(file, tokens, |_, _| (), |_, _, _| ()).unwrap();
+    }
+}
+
+#[test]
+fn test_bounds_and_weights() {
+    let generator = Generator::new(statements())
+        .unwrap()
+        .max_tokens(20)
+        .weight("paren", 0)
+        .unwrap();
+    for seed in 0..100 {
+        let tokens = generator.generate(&mut SeededChoices::new(seed));
+        assert!(!tokens.contains(&Tok::LParen));
+        // Once past the limit, each open derivation finishes with its shortest rule
+        assert!(tokens.len() < 40, "{} tokens", tokens.len());
+    }
+
+    // Reproducible from the seed, and from the bytes
+    let generator = Generator::new(statements()).unwrap();
+    assert_eq!(
+        generator.generate(&mut SeededChoices::new(7)),
+        generator.generate(&mut SeededChoices::new(7))
+    );
+    let bytes = [3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9];
+    assert_eq!(
+        generator.generate(&mut ByteChoices::new(&bytes)),
+        generator.generate(&mut ByteChoices::new(&bytes))
+    );
+    // Without any bytes, the generator takes the first rule it can
+    assert!(generator.generate(&mut ByteChoices::new(&[])).is_empty());
+}
+
+#[test]
+fn test_invalid_grammars() {
+    let grammar = statements().rule("Loop", [nonterm("Loop"), term(Tok::Semi)]);
+    assert_eq!(
+        Generator::new(grammar).unwrap_err(),
+        GrammarError::Unproductive("Loop")
+    );
+    assert_eq!(
+        Generator::new(statements())
+            .unwrap()
+            .weight("-", 2)
+            .unwrap_err(),
+        GrammarError::UnknownRule("-")
+    );
+}
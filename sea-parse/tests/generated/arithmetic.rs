@@ -0,0 +1,97 @@
+// Generated by sea-parse. Do not edit.
+
+/// An LALR(1) parser for `Expr`
+///
+/// Its rules are numbered:
+/// 0. `Expr ::= Expr EqEq Expr`
+/// 1. `Expr ::= Expr Plus Expr`
+/// 2. `Expr ::= Expr Star Expr`
+/// 3. `Expr ::= Expr Caret Expr`
+/// 4. `Expr ::= LParen Expr RParen`
+/// 5. `Expr ::= Number`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Arithmetic;
+
+impl Arithmetic {
+    /// The action in each state on each terminal, then on the end of the input
+    const ACTIONS: [[::sea_parse::Action; 8]; 14] = [
+        [::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Shift(1), ::sea_parse::Action::Error, ::sea_parse::Action::Shift(2), ::sea_parse::Action::Error],
+        [::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Shift(1), ::sea_parse::Action::Error, ::sea_parse::Action::Shift(2), ::sea_parse::Action::Error],
+        [::sea_parse::Action::Reduce(5), ::sea_parse::Action::Reduce(5), ::sea_parse::Action::Reduce(5), ::sea_parse::Action::Reduce(5), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(5), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(5)],
+        [::sea_parse::Action::Shift(5), ::sea_parse::Action::Shift(6), ::sea_parse::Action::Shift(7), ::sea_parse::Action::Shift(8), ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Accept],
+        [::sea_parse::Action::Shift(5), ::sea_parse::Action::Shift(6), ::sea_parse::Action::Shift(7), ::sea_parse::Action::Shift(8), ::sea_parse::Action::Error, ::sea_parse::Action::Shift(9), ::sea_parse::Action::Error, ::sea_parse::Action::Error],
+        [::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Shift(1), ::sea_parse::Action::Error, ::sea_parse::Action::Shift(2), ::sea_parse::Action::Error],
+        [::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Shift(1), ::sea_parse::Action::Error, ::sea_parse::Action::Shift(2), ::sea_parse::Action::Error],
+        [::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Shift(1), ::sea_parse::Action::Error, ::sea_parse::Action::Shift(2), ::sea_parse::Action::Error],
+        [::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Error, ::sea_parse::Action::Shift(1), ::sea_parse::Action::Error, ::sea_parse::Action::Shift(2), ::sea_parse::Action::Error],
+        [::sea_parse::Action::Reduce(4), ::sea_parse::Action::Reduce(4), ::sea_parse::Action::Reduce(4), ::sea_parse::Action::Reduce(4), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(4), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(4)],
+        [::sea_parse::Action::Error, ::sea_parse::Action::Shift(6), ::sea_parse::Action::Shift(7), ::sea_parse::Action::Shift(8), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(0), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(0)],
+        [::sea_parse::Action::Reduce(1), ::sea_parse::Action::Reduce(1), ::sea_parse::Action::Shift(7), ::sea_parse::Action::Shift(8), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(1), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(1)],
+        [::sea_parse::Action::Reduce(2), ::sea_parse::Action::Reduce(2), ::sea_parse::Action::Reduce(2), ::sea_parse::Action::Shift(8), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(2), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(2)],
+        [::sea_parse::Action::Reduce(3), ::sea_parse::Action::Reduce(3), ::sea_parse::Action::Reduce(3), ::sea_parse::Action::Shift(8), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(3), ::sea_parse::Action::Error, ::sea_parse::Action::Reduce(3)],
+    ];
+    /// The state after each nonterminal in each state
+    const GOTOS: [[Option<usize>; 1]; 14] = [
+        [Some(3)],
+        [Some(4)],
+        [None],
+        [None],
+        [None],
+        [Some(10)],
+        [Some(11)],
+        [Some(12)],
+        [Some(13)],
+        [None],
+        [None],
+        [None],
+        [None],
+        [None],
+    ];
+    /// The nonterminal and length of each rule
+    const RULES: [(usize, usize); 6] = [
+        (0, 3),
+        (0, 3),
+        (0, 3),
+        (0, 3),
+        (0, 3),
+        (0, 1),
+    ];
+    /// The name of each terminal
+    const TERMINALS: [&'static str; 7] = ["EqEq", "Plus", "Star", "Caret", "LParen", "RParen", "Number"];
+}
+
+impl ::sea_parse::ParseTable<Tok> for Arithmetic {
+    fn terminal_count(&self) -> usize {
+        7
+    }
+
+    fn terminal(&self, kind: &Tok) -> Option<usize> {
+        match kind {
+            Tok::EqEq => Some(0),
+            Tok::Plus => Some(1),
+            Tok::Star => Some(2),
+            Tok::Caret => Some(3),
+            Tok::LParen => Some(4),
+            Tok::RParen => Some(5),
+            Tok::Number(..) => Some(6),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    fn expected(&self, terminal: usize) -> ::sea_parse::Expected<Tok> {
+        ::sea_parse::Expected::Label(Self::TERMINALS[terminal])
+    }
+
+    fn action(&self, state: usize, terminal: usize) -> ::sea_parse::Action {
+        Self::ACTIONS[state][terminal]
+    }
+
+    fn goto(&self, state: usize, nonterminal: usize) -> Option<usize> {
+        Self::GOTOS[state][nonterminal]
+    }
+
+    fn rule(&self, rule: usize) -> (usize, usize) {
+        Self::RULES[rule]
+    }
+}
@@ -0,0 +1,261 @@
+use sea_lex::{SourceDb, Token, TokenInfo};
+use sea_parse::{
+    nonterm, term, Assoc, Expected, Grammar, GrammarError, LrTable, ParseError, ParseTable,
+};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum Tok {
+    #[token(r"if\b")]
+    If,
+    #[token(r"then\b")]
+    Then,
+    #[token(r"else\b")]
+    Else,
+    #[token(r"a\b")]
+    A,
+    #[token(r"b\b")]
+    B,
+    #[token(r"c\b")]
+    C,
+    #[token(r"d\b")]
+    D,
+    #[token(r"e\b")]
+    E,
+    #[token(r"\d+", str::parse)]
+    Number(i64),
+    #[token("+")]
+    Plus,
+    #[token("*")]
+    Star,
+    #[token("^")]
+    Caret,
+    #[token("==")]
+    EqEq,
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+}
+
+/// Generated by `LrTable::generate` for `arithmetic()`
+mod generated {
+    use super::Tok;
+
+    include!("generated/arithmetic.rs");
+}
+
+/// A grammar of arithmetic with operator filters
+fn arithmetic() -> Grammar<Tok> {
+    Grammar::new("Expr")
+        .named_rule(
+            "==",
+            "Expr",
+            [nonterm("Expr"), term(Tok::EqEq), nonterm("Expr")],
+        )
+        .named_rule(
+            "+",
+            "Expr",
+            [nonterm("Expr"), term(Tok::Plus), nonterm("Expr")],
+        )
+        .named_rule(
+            "*",
+            "Expr",
+            [nonterm("Expr"), term(Tok::Star), nonterm("Expr")],
+        )
+        .named_rule(
+            "^",
+            "Expr",
+            [nonterm("Expr"), term(Tok::Caret), nonterm("Expr")],
+        )
+        .rule(
+            "Expr",
+            [term(Tok::LParen), nonterm("Expr"), term(Tok::RParen)],
+        )
+        .rule("Expr", [term(Tok::Number(0))])
+        .assoc(Assoc::None, ["=="])
+        .assoc(Assoc::Left, ["+"])
+        .assoc(Assoc::Left, ["*"])
+        .assoc(Assoc::Right, ["^"])
+        .priority(["^"], ["*"])
+        .priority(["*"], ["+"])
+        .priority(["+"], ["=="])
+}
+
+/// Parse `source` with `table` into an S-expression
+fn parse(table: &impl ParseTable<Tok>, source: &str) -> Result<String, ParseError<Tok>> {
+    let mut db = SourceDb::new();
+    let file = db.add("test", source);
+    table.parse(
+        file,
+        Tok::lexer(source).collect().unwrap(),
+        |token: &TokenInfo<Tok>, _| token.text.clone(),
+        |rule, _, children| match rule.index() {
+            0..=3 => format!("({} {} {})", children[1], children[0], children[2]),
+            4 => children[1].clone(),
+            _ => children[0].clone(),
+        },
+    )
+}
+
+#[test]
+fn test_filters_resolve_conflicts() {
+    let table = LrTable::lalr(arithmetic()).unwrap();
+    assert!(table.conflicts().is_empty());
+    assert_eq!(parse(&table, "1 + 2 + 3").unwrap(), "(+ (+ 1 2) 3)");
+    assert_eq!(parse(&table, "2 ^ 3 ^ 4").unwrap(), "(^ 2 (^ 3 4))");
+    assert_eq!(
+        parse(&table, "1 + 2 * 3 ^ 4 == (1 + 2) * 3").unwrap(),
+        "(== (+ 1 (* 2 (^ 3 4))) (* (+ 1 2) 3))"
+    );
+
+    let error = parse(&table, "1 == 2 == 3").unwrap_err();
+    assert_eq!(error.found, Some(Tok::EqEq));
+    let error = parse(&table, "(1 +").unwrap_err();
+    assert_eq!(error.found, None);
+    assert_eq!(error.expected.len(), 2);
+    assert!(error.expected.contains(&Expected::Token(Tok::Number(0))));
+    assert!(error.expected.contains(&Expected::Token(Tok::LParen)));
+}
+
+#[test]
+fn test_conflicts_have_examples() {
+    let grammar = Grammar::new("Stmt")
+        .rule(
+            "Stmt",
+            [
+                term(Tok::If),
+                nonterm("Expr"),
+                term(Tok::Then),
+                nonterm("Stmt"),
+            ],
+        )
+        .rule(
+            "Stmt",
+            [
+                term(Tok::If),
+                nonterm("Expr"),
+                term(Tok::Then),
+                nonterm("Stmt"),
+                term(Tok::Else),
+                nonterm("Stmt"),
+            ],
+        )
+        .rule("Stmt", [nonterm("Expr")])
+        .rule("Expr", [term(Tok::A)])
+        .rule("Expr", [term(Tok::B)])
+        .rule("Stmt", [term(Tok::B)]);
+    let table = LrTable::lalr(grammar).unwrap();
+    let conflicts: Vec<String> = table.conflicts().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        conflicts,
+        [
+            "Reduce/reduce conflict on Else after `B`: reduce by `Expr ::= B`, or reduce by \
+             `Stmt ::= B`",
+            "Reduce/reduce conflict on end of input after `B`: reduce by `Expr ::= B`, or \
+             reduce by `Stmt ::= B`",
+            "Shift/reduce conflict on Else after `If A Then B`: shift, or reduce by \
+             `Stmt ::= If Expr Then Stmt`",
+        ]
+    );
+
+    // The dangling else binds to the nearest `if`
+    let mut db = SourceDb::new();
+    let source = "if a then if a then a else a";
+    let file = db.add("test", source);
+    let depth = table.parse(
+        file,
+        Tok::lexer(source).collect().unwrap(),
+        |_, _| 0,
+        |rule, _, children| match rule.index() {
+            0 => children[3] + 1,
+            1 => children[3].max(children[5]) + 1,
+            _ => 0,
+        },
+    );
+    assert_eq!(depth.unwrap(), 2);
+}
+
+#[test]
+fn test_canonical_lr1_splits_states() {
+    let grammar = Grammar::new("S")
+        .rule("S", [term(Tok::A), nonterm("X"), term(Tok::D)])
+        .rule("S", [term(Tok::B), nonterm("Y"), term(Tok::D)])
+        .rule("S", [term(Tok::A), nonterm("Y"), term(Tok::E)])
+        .rule("S", [term(Tok::B), nonterm("X"), term(Tok::E)])
+        .rule("X", [term(Tok::C)])
+        .rule("Y", [term(Tok::C)]);
+    let lalr = LrTable::lalr(grammar.clone()).unwrap();
+    assert_eq!(lalr.conflicts().len(), 2);
+    let lr1 = LrTable::lr1(grammar).unwrap();
+    assert!(lr1.conflicts().is_empty());
+    assert!(lr1.state_count() > lalr.state_count());
+}
+
+#[test]
+fn test_nullable_rules() {
+    let grammar = Grammar::new("List")
+        .rule("List", [])
+        .rule("List", [nonterm("List"), nonterm("Item")])
+        .rule("Item", [term(Tok::Number(0))])
+        .rule(
+            "Item",
+            [term(Tok::LParen), nonterm("List"), term(Tok::RParen)],
+        );
+    let table = LrTable::lalr(grammar).unwrap();
+    assert!(table.conflicts().is_empty());
+
+    let mut db = SourceDb::new();
+    let source = "1 (2 ()) 3";
+    let file = db.add("test", source);
+    let spans = table.parse(
+        file,
+        Tok::lexer(source).collect().unwrap(),
+        |_, span| vec![(span.start, span.end)],
+        |_, span, children| {
+            let mut spans = vec![(span.start, span.end)];
+            spans.extend(children.into_iter().flatten());
+            spans
+        },
+    );
+    // The outermost list, then the empty list before the first item
+    assert_eq!(spans.unwrap()[..3], [(0, 10), (0, 8), (0, 1)]);
+}
+
+#[test]
+fn test_unsupported_grammars() {
+    let grammar = arithmetic()
+        .named_rule("zero", "Expr", [term(Tok::Number(0))])
+        .reject("zero");
+    assert_eq!(
+        LrTable::lalr(grammar).unwrap_err(),
+        GrammarError::UnsupportedReject("zero")
+    );
+    let grammar = arithmetic().rule("Expr", [nonterm("Missing")]);
+    assert_eq!(
+        LrTable::lalr(grammar).unwrap_err(),
+        GrammarError::UndefinedNonterminal("Missing")
+    );
+}
+
+#[test]
+fn test_generated_code() {
+    let table = LrTable::lalr(arithmetic()).unwrap();
+    assert_eq!(
+        table.generate("Arithmetic", "Tok"),
+        include_str!("generated/arithmetic.rs")
+    );
+
+    let parser = generated::Arithmetic;
+    for source in ["1 + 2 + 3", "2 ^ 3 ^ 4", "1 + 2 * 3 ^ 4 == (1 + 2) * 3"] {
+        assert_eq!(
+            parse(&parser, source).unwrap(),
+            parse(&table, source).unwrap()
+        );
+    }
+    let error = parse(&parser, "1 +").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Expected one of LParen, Number, found end of input"
+    );
+}
@@ -0,0 +1,148 @@
+use sea_lex::{SourceDb, Token, TokenInfo};
+use sea_parse::{choice, recursive, select, token, Assoc, ParseError, Parser, Pratt};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum Tok {
+    #[token(r"[a-z0-9]+", String::from)]
+    Atom(String),
+    #[token("==")]
+    EqEq,
+    #[token("=")]
+    Assign,
+    #[token("<")]
+    Less,
+    #[token("+")]
+    Plus,
+    #[token("-")]
+    Minus,
+    #[token("*")]
+    Star,
+    #[token("^")]
+    Caret,
+    #[token("!")]
+    Bang,
+    #[token("?")]
+    Question,
+    #[token(":")]
+    Colon,
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+    #[token("[")]
+    LBracket,
+    #[token("]")]
+    RBracket,
+    #[token(",")]
+    Comma,
+}
+
+/// Parse an expression into an S-expression string
+fn expr<'a>() -> impl Parser<Tok, Output = String> + 'a {
+    let binary =
+        |lhs: String, op: TokenInfo<Tok>, rhs: String, _| format!("({} {lhs} {rhs})", op.text);
+    recursive(move |expr| {
+        let atom = choice((
+            select(|token: &TokenInfo<Tok>| match &token.kind {
+                Tok::Atom(text) => Some(text.clone()),
+                _ => None,
+            }),
+            expr.delimited_by(token(Tok::LParen), token(Tok::RParen)),
+        ));
+        Pratt::new(atom)
+            .infix(Tok::Assign, 1, Assoc::Right, binary)
+            .ternary(Tok::Question, Tok::Colon, 2, |c, a, b, _| {
+                format!("(? {c} {a} {b})")
+            })
+            .infix(Tok::EqEq, 3, Assoc::None, binary)
+            .infix(Tok::Less, 4, Assoc::None, binary)
+            .infix(Tok::Plus, 5, Assoc::Left, binary)
+            .infix(Tok::Minus, 5, Assoc::Left, binary)
+            .infix(Tok::Star, 6, Assoc::Left, binary)
+            .prefix(Tok::Minus, 7, |op, operand, _| {
+                format!("({} {operand})", op.text)
+            })
+            .infix(Tok::Caret, 8, Assoc::Right, binary)
+            .postfix(Tok::Bang, 9, |operand, op, _| {
+                format!("({} {operand})", op.text)
+            })
+            .call(
+                Tok::LParen,
+                Tok::Comma,
+                Tok::RParen,
+                10,
+                |callee, args, _| format!("(call {callee} [{}])", args.join(" ")),
+            )
+            .index(Tok::LBracket, Tok::RBracket, 10, |base, index, _| {
+                format!("([] {base} {index})")
+            })
+    })
+}
+
+/// Parse `source` as an expression
+fn parse(source: &str) -> Result<String, ParseError<Tok>> {
+    let mut db = SourceDb::new();
+    let file = db.add("test", source);
+    expr().parse_all(file, Tok::lexer(source).collect().unwrap())
+}
+
+#[test]
+fn test_precedence_and_associativity() {
+    assert_eq!(parse("1 + 2 * 3").unwrap(), "(+ 1 (* 2 3))");
+    assert_eq!(parse("1 * 2 + 3").unwrap(), "(+ (* 1 2) 3)");
+    assert_eq!(parse("1 - 2 + 3").unwrap(), "(+ (- 1 2) 3)");
+    assert_eq!(parse("2 ^ 3 ^ 4").unwrap(), "(^ 2 (^ 3 4))");
+    assert_eq!(parse("a = b = c + 1").unwrap(), "(= a (= b (+ c 1)))");
+    assert_eq!(parse("(1 + 2) * 3").unwrap(), "(* (+ 1 2) 3)");
+}
+
+#[test]
+fn test_prefix_and_postfix() {
+    assert_eq!(parse("-a * b").unwrap(), "(* (- a) b)");
+    assert_eq!(parse("-a ^ b").unwrap(), "(- (^ a b))");
+    assert_eq!(parse("- - a").unwrap(), "(- (- a))");
+    assert_eq!(parse("-n!").unwrap(), "(- (! n))");
+    assert_eq!(parse("a - -b").unwrap(), "(- a (- b))");
+}
+
+#[test]
+fn test_mixfix_operators() {
+    assert_eq!(parse("a ? b : c ? d : e").unwrap(), "(? a b (? c d e))");
+    assert_eq!(parse("a = b ? c = 1 : d").unwrap(), "(= a (? b (= c 1) d))");
+    assert_eq!(
+        parse("f(1, g(x), y + 1)").unwrap(),
+        "(call f [1 (call g [x]) (+ y 1)])"
+    );
+    assert_eq!(parse("f()(2)").unwrap(), "(call (call f []) [2])");
+    assert_eq!(parse("a[i + 1][j]!").unwrap(), "(! ([] ([] a (+ i 1)) j))");
+    assert_eq!(parse("-a[0]").unwrap(), "(- ([] a 0))");
+}
+
+#[test]
+fn test_non_associative_operators() {
+    assert_eq!(parse("a < b == c < d").unwrap(), "(== (< a b) (< c d))");
+    let error = parse("a < b < c").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Non-associative operators can't be chained"
+    );
+    assert_eq!(error.span.start, 6);
+}
+
+#[test]
+fn test_errors() {
+    let error = parse("1 +").unwrap_err();
+    assert_eq!(error.found, None);
+    assert_eq!(
+        error.to_string(),
+        "Expected one of `(`, `-`, found end of input"
+    );
+
+    let error = parse("f(1 2)").unwrap_err();
+    assert_eq!(error.span.start, 4);
+    assert!(error.to_string().contains("`,`"));
+
+    let error = parse("a ? b").unwrap_err();
+    assert!(error.to_string().contains("`:`"));
+}
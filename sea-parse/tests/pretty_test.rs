@@ -0,0 +1,49 @@
+use sea_parse::Doc;
+
+/// `name(args)`, with the arguments broken one per line if they don't fit
+fn call(name: &'static str, args: Vec<Doc>) -> Doc {
+    (Doc::text(name)
+        + Doc::text("(")
+        + (Doc::softline() + Doc::join(args, Doc::text(",") + Doc::line())).nest(4)
+        + Doc::softline()
+        + Doc::text(")"))
+    .group()
+}
+
+#[test]
+fn test_groups_break_outermost_first() {
+    let inner = call("inner", vec!["alpha".into(), "beta".into()]);
+    let outer = call("outer", vec![inner, "gamma".into()]);
+    assert_eq!(outer.render(80), "outer(inner(alpha, beta), gamma)");
+    assert_eq!(
+        outer.render(30),
+        "outer(\n    inner(alpha, beta),\n    gamma\n)"
+    );
+    assert_eq!(
+        outer.render(15),
+        "outer(\n    inner(\n        alpha,\n        beta\n    ),\n    gamma\n)"
+    );
+    // Text that can't fit anywhere overflows
+    assert_eq!(Doc::text("overflowing").render(4), "overflowing");
+}
+
+#[test]
+fn test_lines_and_indentation() {
+    // What follows a group up to the next line break has to fit too
+    let doc = (Doc::text("a") + Doc::line() + Doc::text("b")).group() + Doc::text("cdef");
+    assert_eq!(doc.render(7), "a bcdef");
+    assert_eq!(doc.render(6), "a\nbcdef");
+
+    // Hard lines break their groups, and blank lines have no indentation
+    let block = Doc::text("{")
+        + (Doc::hardline() + "x;".into() + Doc::hardline() + Doc::hardline() + "y;".into()).nest(2)
+        + Doc::hardline()
+        + Doc::text("}");
+    assert_eq!(block.clone().group().render(80), "{\n  x;\n\n  y;\n}");
+    assert_eq!(format!("{block}"), "{\n  x;\n\n  y;\n}");
+
+    let words = Doc::join(["one", "two", "three"].map(Doc::text), Doc::line()).group();
+    assert_eq!(format!("{words}"), "one two three");
+    assert_eq!(format!("{words:8}"), "one\ntwo\nthree");
+    assert_eq!(Doc::concat([Doc::nil(), Doc::nil()]).render(80), "");
+}
@@ -0,0 +1,196 @@
+use sea_lex::{SourceDb, Span, Token, TokenInfo};
+use sea_parse::{sep_by, skip_then_retry, token, Parse, ParseError, Parser, Repair};
+
+#[derive(Debug, Clone, PartialEq, Token)]
+#[skip(r"\s+")]
+enum Tok {
+    #[token("let")]
+    Let,
+    #[token(r"\d+", str::parse)]
+    Number(i64),
+    #[token(r"[a-z_]+", String::from)]
+    Ident(String),
+    #[token("=")]
+    Eq,
+    #[token("+")]
+    Plus,
+    #[token(",")]
+    Comma,
+    #[token(")")]
+    RParen,
+    #[token("{")]
+    LBrace,
+    #[token("}")]
+    RBrace,
+    #[token(";")]
+    Semi,
+}
+
+#[derive(Debug, Clone, PartialEq, Parse)]
+#[parse(tokens = Tok, label = "expression")]
+enum Expr {
+    #[parse(infix = Plus, precedence = 1)]
+    Add(Box<Expr>, Box<Expr>),
+    Number(#[parse(token = Number(_))] i64),
+    Var(#[parse(token = Ident(_))] String),
+}
+
+#[derive(Debug, Clone, PartialEq, Parse)]
+#[parse(
+    tokens = Tok,
+    label = "statement",
+    recover(before(RBrace), after(Semi), balance(LBrace, RBrace)),
+)]
+enum Stmt {
+    Let {
+        #[parse(token = Let)]
+        _let: (),
+        #[parse(token = Ident(_))]
+        name: String,
+        #[parse(token = Eq)]
+        _eq: (),
+        value: Expr,
+        #[parse(token = Semi, repair)]
+        _semi: (),
+    },
+    Block(Block),
+    #[parse(error)]
+    Error(#[parse(span)] Span),
+}
+
+#[derive(Debug, Clone, PartialEq, Parse)]
+#[parse(tokens = Tok)]
+struct Block {
+    #[parse(token = LBrace)]
+    _open: (),
+    stmts: Vec<Stmt>,
+    #[parse(token = RBrace, repair)]
+    _close: (),
+}
+
+/// Lex `source` and run `parser` over all of it, recovering from errors
+fn run<P: Parser<Tok>>(
+    parser: P,
+    source: &str,
+) -> (Option<P::Output>, Vec<ParseError<Tok>>, SourceDb) {
+    let mut db = SourceDb::new();
+    let file = db.add("test", source);
+    let tokens: Vec<TokenInfo<Tok>> = Tok::lexer(source).collect().unwrap();
+    let (output, errors) = parser.parse_recovering(file, tokens);
+    (output, errors, db)
+}
+
+/// The names of the `let` statements in a block, with `!` for error nodes
+fn names(block: &Block) -> Vec<&str> {
+    block
+        .stmts
+        .iter()
+        .map(|stmt| match stmt {
+            Stmt::Let { name, .. } => name.as_str(),
+            Stmt::Block(_) => "{}",
+            Stmt::Error(_) => "!",
+        })
+        .collect()
+}
+
+#[test]
+fn test_synchronization() {
+    let source = "{ let x = 1; let = 2; let y = { let a = 1; }; let z = x + y; }";
+    let (block, errors, db) = run(Block::parse, source);
+    let block = block.unwrap();
+    assert_eq!(names(&block), ["x", "!", "!", "z"]);
+
+    let spans: Vec<_> = block
+        .stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Error(span) => Some(db.text(*span)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(spans, ["let = 2;", "let y = { let a = 1; };"]);
+
+    let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        [
+            "Expected Ident, found `=`",
+            "Expected expression, found `{`"
+        ]
+    );
+}
+
+#[test]
+fn test_no_recovery_at_synchronization_tokens() {
+    // Nothing can be skipped before `}`, so the statement list just ends there
+    let (block, errors, _) = run(Block::parse, "{ let x = 1; }");
+    assert_eq!(names(&block.unwrap()), ["x"]);
+    assert!(errors.is_empty());
+
+    let (block, errors, db) = run(Block::parse, "{ } }");
+    assert!(block.unwrap().stmts.is_empty());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "Expected end of input, found `}`");
+    assert_eq!(db.text(errors[0].span), "}");
+
+    let (block, errors, _) = run(Block::parse, "let x = 1;");
+    assert!(block.is_none());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "Expected `{`, found `let`");
+}
+
+#[test]
+fn test_insertion_and_deletion_repairs() {
+    let source = "{ let x = 1 let y = 2 ); let z = 3;";
+    let (block, errors, db) = run(Block::parse, source);
+    assert_eq!(names(&block.unwrap()), ["x", "y", "z"]);
+
+    let repairs: Vec<_> = errors
+        .iter()
+        .map(|error| {
+            let repair = error.repair.as_deref().unwrap();
+            let span = match repair {
+                Repair::Insert { span, .. } | Repair::Delete { span } => *span,
+            };
+            (error.to_string(), repair.to_string(), span.start)
+        })
+        .collect();
+    assert_eq!(
+        repairs,
+        [
+            ("Expected `;`, found `let`".into(), "insert `;`".into(), 11),
+            (
+                "Expected `;`, found `)`".into(),
+                "remove the unexpected tokens".into(),
+                22
+            ),
+            (
+                "Expected `}`, found end of input".into(),
+                "insert `}`".into(),
+                source.len()
+            ),
+        ]
+    );
+    assert_eq!(db.text(errors[1].span), ")");
+}
+
+#[test]
+fn test_skip_then_retry() {
+    let number = token(Tok::Number(0)).recover_with(skip_then_retry(2));
+    let (list, errors, db) = run(sep_by(number, token(Tok::Comma)), "1, + 2, 3");
+    let list: Vec<_> = list.unwrap().into_iter().map(|token| token.text).collect();
+    assert_eq!(list, ["1", "2", "3"]);
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "Expected Number, found `+`");
+    let Some(&Repair::Delete { span }) = errors[0].repair.as_deref() else {
+        panic!("expected a deletion, got {:?}", errors[0].repair);
+    };
+    assert_eq!(db.text(span), "+");
+
+    // Skipping too many tokens gives up
+    let number = token(Tok::Number(0)).recover_with(skip_then_retry(1));
+    let (list, errors, _) = run(sep_by(number, token(Tok::Comma)), "1, + + 2");
+    assert!(list.is_none());
+    assert_eq!(errors.len(), 1);
+}
@@ -0,0 +1,110 @@
+use sea_lex::{SourceDb, Span};
+use sea_parse::{ResolveErrorKind, ScopeGraph, ScopeKind};
+
+/// Namespaces for the tests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ns {
+    Value,
+    Type,
+}
+
+impl std::fmt::Display for Ns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Value => "value",
+            Self::Type => "type",
+        })
+    }
+}
+
+/// A one-byte span at `offset` in a test file
+fn at(offset: usize) -> Span {
+    let mut db = SourceDb::new();
+    let file = db.add("test", "");
+    Span::new(file, offset, offset + 1)
+}
+
+#[test]
+fn test_blocks_see_declarations_before_references() {
+    let mut graph = ScopeGraph::new(ScopeKind::Block);
+    let root = graph.root();
+    let x = graph.declare(root, Ns::Value, "x", at(10)).unwrap();
+
+    assert!(graph.resolve(root, Ns::Value, "x", at(5)).is_err());
+    assert_eq!(graph.resolve(root, Ns::Value, "x", at(15)), Ok(x));
+    // Namespaces are separate
+    assert!(graph.resolve(root, Ns::Type, "x", at(15)).is_err());
+    assert!(graph.declare(root, Ns::Type, "x", at(20)).is_ok());
+
+    let error = graph.declare(root, Ns::Value, "x", at(30)).unwrap_err();
+    assert_eq!(
+        error.kind,
+        ResolveErrorKind::Duplicate {
+            namespace: Ns::Value,
+            name: "x".to_string(),
+            previous: at(10),
+        }
+    );
+    assert_eq!(error.to_string(), "Duplicate value `x` in the same scope");
+}
+
+#[test]
+fn test_inner_scopes_shadow_outer_ones() {
+    let mut graph = ScopeGraph::new(ScopeKind::Block);
+    let root = graph.root();
+    let outer = graph.declare(root, Ns::Value, "x", at(0)).unwrap();
+    let block = graph.add_scope(root, ScopeKind::Block);
+    assert_eq!(graph.resolve(block, Ns::Value, "x", at(10)), Ok(outer));
+    let inner = graph.declare(block, Ns::Value, "x", at(20)).unwrap();
+    assert_eq!(graph.resolve(block, Ns::Value, "x", at(30)), Ok(inner));
+    assert_eq!(graph.resolve(root, Ns::Value, "x", at(40)), Ok(outer));
+
+    assert_eq!(graph.references(outer), vec![at(10), at(40)]);
+    assert_eq!(graph.references(inner), vec![at(30)]);
+    assert_eq!(graph.resolution(at(30)), Some(inner));
+    assert_eq!(graph.symbol(inner).scope, block);
+}
+
+#[test]
+fn test_sequences_shadow_and_modules_allow_forward_references() {
+    let mut graph = ScopeGraph::new(ScopeKind::Sequence);
+    let root = graph.root();
+    let first = graph.declare(root, Ns::Value, "f", at(0)).unwrap();
+    let second = graph.declare(root, Ns::Value, "f", at(20)).unwrap();
+    assert_eq!(graph.resolve(root, Ns::Value, "f", at(10)), Ok(first));
+    assert_eq!(graph.resolve(root, Ns::Value, "f", at(30)), Ok(second));
+
+    let group = graph.add_scope(root, ScopeKind::Module);
+    assert_eq!(graph.resolve(group, Ns::Value, "f", at(40)), Ok(second));
+    let even = graph.declare(group, Ns::Value, "even", at(50)).unwrap();
+    let odd = graph.declare(group, Ns::Value, "odd", at(60)).unwrap();
+    assert_eq!(graph.resolve(group, Ns::Value, "odd", at(55)), Ok(odd));
+    assert_eq!(graph.resolve(group, Ns::Value, "even", at(65)), Ok(even));
+    assert_eq!(graph.lookup_local(group, Ns::Value, "f"), None);
+}
+
+#[test]
+fn test_unresolved_names_suggest_similar_visible_names() {
+    let mut graph = ScopeGraph::new(ScopeKind::Block);
+    let root = graph.root();
+    graph.declare(root, Ns::Value, "length", at(0)).unwrap();
+    graph.declare(root, Ns::Value, "count", at(10)).unwrap();
+    graph.declare(root, Ns::Type, "lengths", at(20)).unwrap();
+    graph.declare(root, Ns::Value, "later", at(100)).unwrap();
+
+    let suggest = |graph: &mut ScopeGraph<Ns>, name| match graph
+        .resolve(root, Ns::Value, name, at(50))
+        .unwrap_err()
+        .kind
+    {
+        ResolveErrorKind::Unresolved { suggestion, .. } => suggestion,
+        ResolveErrorKind::Duplicate { .. } => panic!("expected an unresolved name"),
+    };
+    assert_eq!(suggest(&mut graph, "lenght").as_deref(), Some("length"));
+    assert_eq!(suggest(&mut graph, "cont").as_deref(), Some("count"));
+    // Too different, in another namespace, or not declared yet
+    assert_eq!(suggest(&mut graph, "total"), None);
+    assert_eq!(suggest(&mut graph, "lengthss").as_deref(), Some("length"));
+    assert_eq!(suggest(&mut graph, "latr"), None);
+    assert!(graph.resolution(at(50)).is_none());
+}
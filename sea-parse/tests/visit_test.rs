@@ -0,0 +1,134 @@
+use sea_lex::{SourceDb, Span};
+use sea_parse::{downcast_mut, downcast_ref, fold_as, Fold, Folder, Visit, Visitor, VisitorMut};
+
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+enum Expr {
+    Number(i64),
+    Var(String),
+    Binary(Box<Expr>, char, Box<Expr>),
+    Let {
+        bindings: Vec<(String, Expr)>,
+        body: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Visit, Fold)]
+struct Spanned<N> {
+    node: N,
+    span: Span,
+    #[visit(skip)]
+    note: NotANode,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct NotANode(u8);
+
+fn binary(lhs: Expr, op: char, rhs: Expr) -> Expr {
+    Expr::Binary(Box::new(lhs), op, Box::new(rhs))
+}
+
+/// `let x = 1 + 2 in x * (3 + y)`
+fn example() -> Expr {
+    Expr::Let {
+        bindings: vec![(
+            "x".to_string(),
+            binary(Expr::Number(1), '+', Expr::Number(2)),
+        )],
+        body: Box::new(binary(
+            Expr::Var("x".to_string()),
+            '*',
+            binary(Expr::Number(3), '+', Expr::Var("y".to_string())),
+        )),
+    }
+}
+
+/// Collects names, and the operators in preorder
+#[derive(Default)]
+struct Collect {
+    names: Vec<String>,
+    operators: Vec<char>,
+}
+
+impl Visitor for Collect {
+    fn visit<N: Visit>(&mut self, node: &N) {
+        if let Some(name) = downcast_ref::<String>(node) {
+            self.names.push(name.clone());
+        }
+        if let Some(Expr::Binary(_, op, _)) = downcast_ref(node) {
+            self.operators.push(*op);
+        }
+        node.visit_children(self);
+    }
+}
+
+#[test]
+fn test_visitors() {
+    let mut collect = Collect::default();
+    collect.visit(&example());
+    assert_eq!(collect.names, ["x", "x", "y"]);
+    assert_eq!(collect.operators, ['+', '*', '+']);
+
+    /// Renames `x` to `z`, without entering `let` bindings
+    struct Rename;
+    impl VisitorMut for Rename {
+        fn visit_mut<N: Visit>(&mut self, node: &mut N) {
+            if let Some(Expr::Let { body, .. }) = downcast_mut::<Expr>(node) {
+                self.visit_mut(body);
+                return;
+            }
+            if let Some(Expr::Var(name)) = downcast_mut(node) {
+                if name == "x" {
+                    *name = "z".to_string();
+                }
+            }
+            node.visit_children_mut(self);
+        }
+    }
+    let mut expr = example();
+    Rename.visit_mut(&mut expr);
+    let mut collect = Collect::default();
+    collect.visit(&expr);
+    assert_eq!(collect.names, ["x", "z", "y"]);
+}
+
+#[test]
+fn test_folders() {
+    /// Evaluates additions of numbers, bottom-up
+    struct Constants;
+    impl Folder for Constants {
+        fn fold<N: Fold>(&mut self, node: N) -> N {
+            fold_as(node.fold_children(self), |expr| match expr {
+                Expr::Binary(lhs, '+', rhs) => match (*lhs, *rhs) {
+                    (Expr::Number(lhs), Expr::Number(rhs)) => Expr::Number(lhs + rhs),
+                    (lhs, rhs) => binary(lhs, '+', rhs),
+                },
+                expr => expr,
+            })
+        }
+    }
+    let folded = Constants.fold(binary(
+        binary(Expr::Number(1), '+', Expr::Number(2)),
+        '+',
+        Expr::Number(3),
+    ));
+    assert_eq!(folded, Expr::Number(6));
+    let folded = Constants.fold(example());
+    let Expr::Let { bindings, .. } = &folded else {
+        panic!("expected a let");
+    };
+    assert_eq!(bindings[0].1, Expr::Number(3));
+
+    // Generic nodes are traversed through their type parameters, skipping marked fields
+    let file = SourceDb::new().add("test", "");
+    let spanned = Spanned {
+        node: example(),
+        span: Span::new(file, 0, 0),
+        note: NotANode(7),
+    };
+    let folded = Constants.fold(spanned.clone());
+    assert_eq!(folded.note, NotANode(7));
+    assert_eq!(folded.node, Constants.fold(example()));
+    let mut collect = Collect::default();
+    collect.visit(&spanned);
+    assert_eq!(collect.names.len(), 3);
+}
@@ -0,0 +1,279 @@
+//! Algebraic data types, and the patterns that match them
+
+use crate::{Scheme, Ty, TyVar};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// An algebraic data type: a sum of variants, each a product of fields, over type
+/// parameters
+///
+/// `C` is the language's type constructors, as in [`Ty`]. An ML declaration like
+/// `type 'a option = None | Some of 'a` becomes an `AdtDef` named `option` with one
+/// parameter, and variants `None`, with no fields, and `Some`, with a field of type
+/// `'a`. A constructor that takes a tuple has one field per element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdtDef<C> {
+    /// The type constructor
+    pub name: C,
+    /// The type parameters, created with [`Inference::fresh_var`](crate::Inference::fresh_var)
+    pub params: Vec<TyVar>,
+    /// The variants, in declaration order
+    pub variants: Vec<Variant<C>>,
+}
+
+/// A variant of an [`AdtDef`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant<C> {
+    /// The constructor's name
+    pub name: String,
+    /// The types of the constructor's fields, over the type's parameters
+    pub fields: Vec<Ty<C>>,
+}
+
+impl<C: Clone> AdtDef<C> {
+    /// Get the type the definition declares, applied to its own parameters: `'a option`
+    #[must_use]
+    pub fn ty(&self) -> Ty<C> {
+        Ty::Con(
+            self.name.clone(),
+            self.params.iter().map(|&param| Ty::Var(param)).collect(),
+        )
+    }
+
+    /// Get the type of a variant's constructor, quantified over the type's parameters
+    ///
+    /// As in ML, constructors without fields are constants (`None : 'a option`), and the
+    /// others are functions from their field, or the tuple of their fields
+    /// (`Some : 'a -> 'a option`).
+    #[must_use]
+    pub fn constructor_scheme(&self, variant: usize) -> Scheme<C> {
+        let ty = match self.variants[variant].fields.as_slice() {
+            [] => self.ty(),
+            [field] => Ty::fun(field.clone(), self.ty()),
+            fields => Ty::fun(Ty::Tuple(fields.to_vec()), self.ty()),
+        };
+        Scheme {
+            vars: self.params.clone(),
+            ty,
+        }
+    }
+
+    /// Get the types of a variant's fields, for the type applied to `args`
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer arguments than parameters
+    #[must_use]
+    pub fn field_types(&self, variant: usize, args: &[Ty<C>]) -> Vec<Ty<C>> {
+        assert!(
+            args.len() >= self.params.len(),
+            "expected {} type arguments, found {}",
+            self.params.len(),
+            args.len()
+        );
+        let substitution: HashMap<_, _> = self
+            .params
+            .iter()
+            .copied()
+            .zip(args.iter().cloned())
+            .collect();
+        self.variants[variant]
+            .fields
+            .iter()
+            .map(|field| field.substitute(&substitution))
+            .collect()
+    }
+
+    /// Get the index of the variant with the given constructor
+    #[must_use]
+    pub fn variant(&self, constructor: &str) -> Option<usize> {
+        self.variants
+            .iter()
+            .position(|variant| variant.name == constructor)
+    }
+}
+
+/// The algebraic data types in scope, and their constructors
+///
+/// Matches find the type a constructor belongs to here, so they know which other
+/// constructors they must cover. Like ML, a later definition shadows the constructors of
+/// an earlier one with the same names.
+#[derive(Debug, Clone)]
+pub struct Adts<C> {
+    /// The definitions, in the order they were defined
+    defs: Vec<AdtDef<C>>,
+    /// The index of each type constructor's definition
+    by_name: HashMap<C, usize>,
+    /// The definition and variant indices of each constructor
+    constructors: HashMap<String, (usize, usize)>,
+}
+
+impl<C: Clone + Eq + Hash> Adts<C> {
+    /// Start without any types
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            defs: Vec::new(),
+            by_name: HashMap::new(),
+            constructors: HashMap::new(),
+        }
+    }
+
+    /// Define a type and its constructors
+    pub fn define(&mut self, def: AdtDef<C>) {
+        let index = self.defs.len();
+        for (variant, constructor) in def.variants.iter().enumerate() {
+            self.constructors
+                .insert(constructor.name.clone(), (index, variant));
+        }
+        self.by_name.insert(def.name.clone(), index);
+        self.defs.push(def);
+    }
+
+    /// Get a type's definition
+    #[must_use]
+    pub fn get(&self, name: &C) -> Option<&AdtDef<C>> {
+        self.by_name.get(name).map(|&index| &self.defs[index])
+    }
+
+    /// Get the definition a constructor belongs to, and the index of its variant
+    #[must_use]
+    pub fn constructor(&self, name: &str) -> Option<(&AdtDef<C>, usize)> {
+        self.constructors
+            .get(name)
+            .map(|&(index, variant)| (&self.defs[index], variant))
+    }
+
+    /// Get the indices of a constructor's definition and variant
+    pub(crate) fn constructor_index(&self, name: &str) -> Option<(usize, usize)> {
+        self.constructors.get(name).copied()
+    }
+
+    /// Get a definition by its index
+    pub(crate) fn def(&self, index: usize) -> &AdtDef<C> {
+        &self.defs[index]
+    }
+}
+
+impl<C: Clone + Eq + Hash> Default for Adts<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A pattern, as matched by [`check_match`](crate::check_match) and
+/// [`compile_match`](crate::compile_match)
+///
+/// Patterns are untyped: constructors are found by name in [`Adts`], and the frontend
+/// has already checked the patterns against the scrutinee's type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Pat {
+    /// `_`, which matches anything
+    Wildcard,
+    /// A variable, which matches anything and binds it
+    Var(String),
+    /// A constant
+    Literal(Literal),
+    /// `(a, b)`
+    Tuple(Vec<Self>),
+    /// A constructor applied to a pattern for each of its fields: `Some x`
+    Constructor(String, Vec<Self>),
+    /// `a | b`, which matches what any alternative matches
+    Or(Vec<Self>),
+}
+
+/// A constant in a pattern
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Literal {
+    /// `true` or `false`
+    Bool(bool),
+    /// An integer
+    Int(i64),
+    /// A string
+    String(String),
+}
+
+impl Pat {
+    /// A constructor without fields
+    #[must_use]
+    pub fn constant(constructor: impl Into<String>) -> Self {
+        Self::Constructor(constructor.into(), Vec::new())
+    }
+
+    /// Write a pattern, parenthesized if it binds looser than `precedence`
+    ///
+    /// Or-patterns have precedence 0, constructors with fields 1, and everything else,
+    /// including tuples, which are always parenthesized, 2.
+    fn write(&self, f: &mut fmt::Formatter<'_>, precedence: u8) -> fmt::Result {
+        let own = match self {
+            Self::Or(_) => 0,
+            Self::Constructor(_, fields) if !fields.is_empty() => 1,
+            _ => 2,
+        };
+        if own < precedence {
+            f.write_str("(")?;
+        }
+        match self {
+            Self::Wildcard => f.write_str("_")?,
+            Self::Var(name) => f.write_str(name)?,
+            Self::Literal(literal) => write!(f, "{literal}")?,
+            Self::Tuple(elements) => write_tuple(f, elements)?,
+            Self::Constructor(name, fields) => {
+                f.write_str(name)?;
+                match fields.as_slice() {
+                    [] => {}
+                    [field] => {
+                        f.write_str(" ")?;
+                        field.write(f, 2)?;
+                    }
+                    fields => {
+                        f.write_str(" ")?;
+                        write_tuple(f, fields)?;
+                    }
+                }
+            }
+            Self::Or(alternatives) => {
+                for (index, alternative) in alternatives.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" | ")?;
+                    }
+                    alternative.write(f, 1)?;
+                }
+            }
+        }
+        if own < precedence {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Write patterns as a parenthesized tuple: `(a, b)`
+fn write_tuple(f: &mut fmt::Formatter<'_>, elements: &[Pat]) -> fmt::Result {
+    f.write_str("(")?;
+    for (index, element) in elements.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        element.write(f, 1)?;
+    }
+    f.write_str(")")
+}
+
+/// Patterns are written as in ML: `Some (x, _) | None`
+impl fmt::Display for Pat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::String(value) => write!(f, "{value:?}"),
+        }
+    }
+}
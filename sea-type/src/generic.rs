@@ -0,0 +1,403 @@
+//! Generic type parameters with trait bounds, and the implementations that satisfy them
+
+use crate::{Inference, Ty, TyVar};
+use sea_lex::Span;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// The deepest [`Traits::solve`] looks through implementations' bounds
+const MAX_SOLVE_DEPTH: usize = 64;
+
+/// A requirement that a type implement a trait: `'a: Show`, or `'a list: Show`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bound<C> {
+    /// The trait's name
+    pub trait_name: String,
+    /// The type that must implement it
+    pub ty: Ty<C>,
+}
+
+impl<C> Bound<C> {
+    /// Require that `ty` implement `trait_name`
+    pub fn new(trait_name: impl Into<String>, ty: Ty<C>) -> Self {
+        Self {
+            trait_name: trait_name.into(),
+            ty,
+        }
+    }
+}
+
+impl<C: Clone> Bound<C> {
+    /// Replace the variables in the bound's type that `substitution` maps
+    #[must_use]
+    pub fn substitute(&self, substitution: &HashMap<TyVar, Ty<C>>) -> Self {
+        Self {
+            trait_name: self.trait_name.clone(),
+            ty: self.ty.substitute(substitution),
+        }
+    }
+}
+
+/// A polymorphic type whose parameters must satisfy bounds: `'a -> string` where
+/// `'a: Show`
+///
+/// This is a [`Scheme`](crate::Scheme) with bounds on its variables, for generic
+/// functions. Each use [instantiates](Self::instantiate) it, and the frontend checks the
+/// instantiated bounds with [`Traits::solve`] once inference has solved their types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generic<C> {
+    /// The type parameters, created with [`Inference::fresh_var`]
+    pub params: Vec<TyVar>,
+    /// The bounds the parameters must satisfy
+    pub bounds: Vec<Bound<C>>,
+    /// The type, over the parameters
+    pub ty: Ty<C>,
+}
+
+impl<C: Clone + PartialEq> Generic<C> {
+    /// Replace the parameters with fresh variables, getting the type and the bounds the
+    /// variables must satisfy, along with the variables in parameter order
+    pub fn instantiate(&self, inference: &mut Inference<C>) -> Instantiated<C> {
+        let args: Vec<_> = self.params.iter().map(|_| inference.fresh()).collect();
+        let substitution = self.params.iter().copied().zip(args.clone()).collect();
+        Instantiated {
+            ty: self.ty.substitute(&substitution),
+            bounds: self
+                .bounds
+                .iter()
+                .map(|bound| bound.substitute(&substitution))
+                .collect(),
+            args,
+        }
+    }
+}
+
+/// A use of a [`Generic`], from [`Generic::instantiate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instantiated<C> {
+    /// The type, over fresh variables
+    pub ty: Ty<C>,
+    /// The bounds the fresh variables must satisfy
+    pub bounds: Vec<Bound<C>>,
+    /// The fresh variables, one for each parameter, which become the type arguments to
+    /// monomorphize with
+    pub args: Vec<Ty<C>>,
+}
+
+/// An implementation of a trait for the types matching `ty`: `impl Show for 'a list
+/// where 'a: Show`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Impl<C> {
+    /// The trait implemented
+    pub trait_name: String,
+    /// The type parameters, created with [`Inference::fresh_var`]
+    pub params: Vec<TyVar>,
+    /// The bounds the parameters must satisfy
+    pub bounds: Vec<Bound<C>>,
+    /// The types implementing the trait, over the parameters
+    pub ty: Ty<C>,
+    /// The location of the implementation
+    pub span: Span,
+}
+
+/// The index of an [`Impl`] in [`Traits`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ImplId(pub usize);
+
+/// The traits, or type classes, in scope, and their implementations
+///
+/// Traits are named, and may have supertraits: a type can only implement `Ord` if it
+/// implements `Eq`, so a bound `'a: Ord` also gives `'a: Eq`. Implementations can't
+/// overlap, so each bound on a concrete type is satisfied by at most one of them.
+///
+/// ```
+/// # use sea_lex::{SourceDb, Span};
+/// # use sea_type::{Bound, Impl, Inference, Traits, Ty};
+/// # let span = Span::new(SourceDb::new().add("example.ml", ""), 0, 0);
+/// let mut inference = Inference::<&str>::new();
+/// let mut traits = Traits::new();
+/// traits.define_trait("Show", &[]);
+/// traits.add_impl(Impl {
+///     trait_name: "Show".to_string(),
+///     params: vec![],
+///     bounds: vec![],
+///     ty: Ty::con("int"),
+///     span,
+/// })?;
+/// let a = inference.fresh_var();
+/// traits.add_impl(Impl {
+///     trait_name: "Show".to_string(),
+///     params: vec![a],
+///     bounds: vec![Bound::new("Show", Ty::Var(a))],
+///     ty: Ty::Con("list", vec![Ty::Var(a)]),
+///     span,
+/// })?;
+///
+/// let ints = Ty::Con("list", vec![Ty::con("int")]);
+/// assert!(traits.solve(&Bound::new("Show", ints), &[], span).is_ok());
+/// let bools = Ty::Con("list", vec![Ty::con("bool")]);
+/// let error = traits.solve(&Bound::new("Show", bools), &[], span).unwrap_err();
+/// assert_eq!(error.to_string(), "`bool` doesn't implement `Show`");
+/// # Ok::<(), sea_type::BoundError<&str>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Traits<C> {
+    /// The supertraits of each trait
+    supertraits: HashMap<String, Vec<String>>,
+    /// The implementations, in the order they were added
+    impls: Vec<Impl<C>>,
+}
+
+impl<C: Clone + PartialEq> Traits<C> {
+    /// Start without any traits
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            supertraits: HashMap::new(),
+            impls: Vec::new(),
+        }
+    }
+
+    /// Define a trait, which its implementors must also implement its supertraits for
+    pub fn define_trait(&mut self, name: impl Into<String>, supertraits: &[&str]) {
+        let supertraits = supertraits.iter().map(ToString::to_string).collect();
+        self.supertraits.insert(name.into(), supertraits);
+    }
+
+    /// Add an implementation
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the trait isn't defined, or if another implementation of it
+    /// covers some of the same types
+    pub fn add_impl(&mut self, implementation: Impl<C>) -> Result<ImplId, BoundError<C>> {
+        let bound = Bound::new(implementation.trait_name.clone(), implementation.ty.clone());
+        if !self.supertraits.contains_key(&bound.trait_name) {
+            return Err(BoundError {
+                span: implementation.span,
+                bound,
+                kind: BoundErrorKind::UnknownTrait,
+            });
+        }
+        let overlaps = self
+            .impls
+            .iter()
+            .any(|other| other.trait_name == bound.trait_name && overlap(other, &implementation));
+        if overlaps {
+            return Err(BoundError {
+                span: implementation.span,
+                bound,
+                kind: BoundErrorKind::Overlap,
+            });
+        }
+        self.impls.push(implementation);
+        Ok(ImplId(self.impls.len() - 1))
+    }
+
+    /// Get an implementation
+    #[must_use]
+    pub fn get(&self, id: ImplId) -> &Impl<C> {
+        &self.impls[id.0]
+    }
+
+    /// Find the implementation that covers a bound's type, and the types its parameters
+    /// take, without checking the implementation's own bounds
+    #[must_use]
+    pub fn select(&self, bound: &Bound<C>) -> Option<(ImplId, Vec<Ty<C>>)> {
+        self.impls
+            .iter()
+            .enumerate()
+            .find_map(|(index, implementation)| {
+                if implementation.trait_name != bound.trait_name {
+                    return None;
+                }
+                let mut substitution = HashMap::new();
+                matches(&implementation.ty, &bound.ty, &mut substitution).then(|| {
+                    let args = implementation
+                        .params
+                        .iter()
+                        .map(|param| substitution.get(param).cloned().unwrap_or(Ty::Var(*param)))
+                        .collect();
+                    (ImplId(index), args)
+                })
+            })
+    }
+
+    /// Check that a bound holds, given the `assumptions` that hold in the generic
+    /// function being checked, like its own parameters' bounds
+    ///
+    /// A bound holds if it or a subtrait of it is assumed, or if an implementation
+    /// covers its type and the implementation's bounds hold in turn. A bound on an
+    /// unsolved variable only holds by assumption.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error at `span` for the innermost bound that doesn't hold
+    pub fn solve(
+        &self,
+        bound: &Bound<C>,
+        assumptions: &[Bound<C>],
+        span: Span,
+    ) -> Result<(), BoundError<C>> {
+        self.solve_within(bound, assumptions, span, 0)
+    }
+
+    /// Check that a bound holds, having looked through `depth` implementations
+    fn solve_within(
+        &self,
+        bound: &Bound<C>,
+        assumptions: &[Bound<C>],
+        span: Span,
+        depth: usize,
+    ) -> Result<(), BoundError<C>> {
+        let error = |kind| BoundError {
+            span,
+            bound: bound.clone(),
+            kind,
+        };
+        if !self.supertraits.contains_key(&bound.trait_name) {
+            return Err(error(BoundErrorKind::UnknownTrait));
+        }
+        if assumptions.iter().any(|assumption| {
+            assumption.ty == bound.ty && self.implies(&assumption.trait_name, &bound.trait_name)
+        }) {
+            return Ok(());
+        }
+        if depth == MAX_SOLVE_DEPTH {
+            return Err(error(BoundErrorKind::Overflow));
+        }
+        let Some((id, args)) = self.select(bound) else {
+            return Err(error(BoundErrorKind::Unsatisfied));
+        };
+        let implementation = self.get(id);
+        let substitution = implementation.params.iter().copied().zip(args).collect();
+        for required in &implementation.bounds {
+            let required = required.substitute(&substitution);
+            self.solve_within(&required, assumptions, span, depth + 1)?;
+        }
+        for supertrait in &self.supertraits[&bound.trait_name] {
+            let required = Bound::new(supertrait.clone(), bound.ty.clone());
+            self.solve_within(&required, assumptions, span, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Reports whether implementing `subtrait` means implementing `supertrait`
+    fn implies(&self, subtrait: &str, supertrait: &str) -> bool {
+        subtrait == supertrait
+            || self.supertraits.get(subtrait).is_some_and(|supertraits| {
+                supertraits
+                    .iter()
+                    .any(|direct| self.implies(direct, supertrait))
+            })
+    }
+}
+
+impl<C: Clone + PartialEq> Default for Traits<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Match a term against a pattern whose variables are implementation parameters,
+/// extending `substitution` with what each parameter must be
+fn matches<C: Clone + PartialEq>(
+    pattern: &Ty<C>,
+    ty: &Ty<C>,
+    substitution: &mut HashMap<TyVar, Ty<C>>,
+) -> bool {
+    match (pattern, ty) {
+        (Ty::Var(var), ty) => substitution.entry(*var).or_insert_with(|| ty.clone()) == ty,
+        (Ty::Con(a, a_args), Ty::Con(b, b_args)) => {
+            a == b && a_args.len() == b_args.len() && all_match(a_args, b_args, substitution)
+        }
+        (Ty::Fun(a_param, a_ret), Ty::Fun(b_param, b_ret)) => {
+            matches(a_param, b_param, substitution) && matches(a_ret, b_ret, substitution)
+        }
+        (Ty::Tuple(a), Ty::Tuple(b)) => a.len() == b.len() && all_match(a, b, substitution),
+        _ => false,
+    }
+}
+
+/// Match terms against patterns pairwise
+fn all_match<C: Clone + PartialEq>(
+    patterns: &[Ty<C>],
+    tys: &[Ty<C>],
+    substitution: &mut HashMap<TyVar, Ty<C>>,
+) -> bool {
+    patterns
+        .iter()
+        .zip(tys)
+        .all(|(pattern, ty)| matches(pattern, ty, substitution))
+}
+
+/// Reports whether two implementations cover some of the same types
+///
+/// Each implementation's parameters are replaced with fresh variables, so they're
+/// distinct, and the types overlap if they unify.
+fn overlap<C: Clone + PartialEq>(a: &Impl<C>, b: &Impl<C>) -> bool {
+    let mut inference = Inference::new();
+    let mut fresh = |implementation: &Impl<C>| {
+        let substitution: HashMap<_, _> = implementation
+            .params
+            .iter()
+            .map(|&param| (param, inference.fresh()))
+            .collect();
+        implementation.ty.substitute(&substitution)
+    };
+    let (a_ty, b_ty) = (fresh(a), fresh(b));
+    inference.unify(&a_ty, &b_ty, b.span).is_ok()
+}
+
+/// An error from [`Traits`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundError<C> {
+    /// The location of the error
+    pub span: Span,
+    /// The bound that doesn't hold, or the implementation's trait and type
+    pub bound: Bound<C>,
+    /// The kind of error
+    pub kind: BoundErrorKind,
+}
+
+/// The kinds of [`BoundError`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoundErrorKind {
+    /// No implementation covers the type, and it isn't assumed
+    Unsatisfied,
+    /// The implementations needed were nested too deeply, as when an implementation's
+    /// bounds require ever larger types
+    Overflow,
+    /// An implementation covers some of the same types as an earlier one
+    Overlap,
+    /// The trait isn't defined
+    UnknownTrait,
+}
+
+impl<C: fmt::Display> fmt::Display for BoundError<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = RefCell::default();
+        let ty = self.bound.ty.named(&names);
+        let trait_name = &self.bound.trait_name;
+        match self.kind {
+            BoundErrorKind::Unsatisfied => {
+                write!(f, "`{ty}` doesn't implement `{trait_name}`")
+            }
+            BoundErrorKind::Overflow => write!(
+                f,
+                "Overflowed checking whether `{ty}` implements `{trait_name}`"
+            ),
+            BoundErrorKind::Overlap => {
+                write!(
+                    f,
+                    "Conflicting implementations of `{trait_name}` for `{ty}`"
+                )
+            }
+            BoundErrorKind::UnknownTrait => write!(f, "No trait named `{trait_name}`"),
+        }
+    }
+}
+
+impl<C: fmt::Debug + fmt::Display> Error for BoundError<C> {}
@@ -0,0 +1,351 @@
+//! Hindley–Milner type inference over generic type terms
+
+use crate::{Scheme, Ty, TyVar};
+use sea_lex::Span;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// A type variable's state in the union-find forest
+#[derive(Debug, Clone)]
+struct VarState<C> {
+    /// What the variable was unified with: another variable closer to the root of its
+    /// class, or a term, or nothing if it's unsolved
+    binding: Option<Ty<C>>,
+    /// The let-nesting depth of the outermost binding the variable may escape to
+    level: u32,
+}
+
+/// The state of Hindley–Milner inference: type variables, and the constraints solved so
+/// far
+///
+/// Inference isn't tied to a syntax tree. A frontend walks its own tree, creating
+/// variables with [`fresh`](Self::fresh) and equating terms with
+/// [`unify`](Self::unify). Variables are kept in a union-find forest, so unification is
+/// nearly linear.
+///
+/// Let-polymorphism uses levels, as in OCaml: the frontend
+/// [enters a level](Self::enter_level) to infer a `let`'s right-hand side, then
+/// [leaves it](Self::exit_level) and [generalizes](Self::generalize) over the variables
+/// that didn't escape into the enclosing scope. Each use of the binding
+/// [instantiates](Self::instantiate) the resulting [`Scheme`] with fresh variables.
+///
+/// ```
+/// # use sea_lex::{SourceDb, Span};
+/// # use sea_type::{Inference, Ty};
+/// # let span = Span::new(SourceDb::new().add("example.ml", ""), 0, 0);
+/// let mut inference = Inference::<&str>::new();
+/// // let id = fun x -> x
+/// inference.enter_level();
+/// let x = inference.fresh();
+/// let id = Ty::fun(x.clone(), x);
+/// inference.exit_level();
+/// let id = inference.generalize(&id);
+/// assert_eq!(id.to_string(), "'a -> 'a");
+///
+/// // id 1
+/// let (result, instance) = (inference.fresh(), inference.instantiate(&id));
+/// let applied = Ty::fun(Ty::con("int"), result.clone());
+/// inference.unify(&instance, &applied, span)?;
+/// assert_eq!(inference.resolve(&result), Ty::con("int"));
+/// # Ok::<(), sea_type::UnifyError<&str>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Inference<C> {
+    /// The type variables, indexed by [`TyVar`]
+    vars: Vec<VarState<C>>,
+    /// The current let-nesting depth
+    level: u32,
+}
+
+impl<C: Clone + PartialEq> Inference<C> {
+    /// Start inference, without any type variables
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            vars: Vec::new(),
+            level: 0,
+        }
+    }
+
+    /// Create an unsolved type variable at the current level
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than `u32::MAX` variables
+    pub fn fresh(&mut self) -> Ty<C> {
+        Ty::Var(self.fresh_var())
+    }
+
+    /// Create an unsolved type variable at the current level, such as a parameter of an
+    /// [`AdtDef`](crate::AdtDef)
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than `u32::MAX` variables
+    pub fn fresh_var(&mut self) -> TyVar {
+        let var = TyVar(u32::try_from(self.vars.len()).expect("too many type variables"));
+        self.vars.push(VarState {
+            binding: None,
+            level: self.level,
+        });
+        var
+    }
+
+    /// Enter a `let`'s right-hand side, whose variables may be generalized
+    pub const fn enter_level(&mut self) {
+        self.level += 1;
+    }
+
+    /// Leave a `let`'s right-hand side
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no level to leave
+    pub const fn exit_level(&mut self) {
+        self.level = self.level.checked_sub(1).expect("no level to exit");
+    }
+
+    /// Find the root of a variable's class, compressing the path to it
+    fn find(&mut self, var: TyVar) -> TyVar {
+        let mut root = var;
+        while let Some(Ty::Var(parent)) = &self.vars[root.0 as usize].binding {
+            root = *parent;
+        }
+        let mut current = var;
+        while current != root {
+            let state = &mut self.vars[current.0 as usize];
+            let Some(Ty::Var(parent)) = state.binding.replace(Ty::Var(root)) else {
+                unreachable!("the path to the root is made of variables");
+            };
+            current = parent;
+        }
+        root
+    }
+
+    /// Get a term with its outermost variable resolved: an unsolved root variable, or
+    /// the term it was solved to
+    fn shallow(&mut self, ty: &Ty<C>) -> Ty<C> {
+        match ty {
+            Ty::Var(var) => {
+                let root = self.find(*var);
+                self.vars[root.0 as usize]
+                    .binding
+                    .clone()
+                    .unwrap_or(Ty::Var(root))
+            }
+            ty => ty.clone(),
+        }
+    }
+
+    /// Substitute every solved variable in a term, leaving only unsolved root variables
+    pub fn resolve(&mut self, ty: &Ty<C>) -> Ty<C> {
+        match self.shallow(ty) {
+            Ty::Var(var) => Ty::Var(var),
+            Ty::Con(constructor, args) => Ty::Con(
+                constructor,
+                args.iter().map(|arg| self.resolve(arg)).collect(),
+            ),
+            Ty::Fun(param, ret) => Ty::fun(self.resolve(&param), self.resolve(&ret)),
+            Ty::Tuple(elements) => Ty::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.resolve(element))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Make `expected` and `found` equal, solving variables in either
+    ///
+    /// # Errors
+    ///
+    /// Returns an error at `span` if the terms have different constructors, or if a
+    /// variable would have to contain itself. The variables solved before the conflict
+    /// stay solved.
+    pub fn unify(
+        &mut self,
+        expected: &Ty<C>,
+        found: &Ty<C>,
+        span: Span,
+    ) -> Result<(), UnifyError<C>> {
+        self.unify_terms(expected, found).map_err(|conflict| {
+            let kind = match conflict {
+                Conflict::Mismatch(expected, found) => UnifyErrorKind::Mismatch {
+                    expected: self.resolve(&expected),
+                    found: self.resolve(&found),
+                },
+                Conflict::Occurs(var, ty) => UnifyErrorKind::Occurs {
+                    var,
+                    ty: self.resolve(&ty),
+                },
+            };
+            UnifyError {
+                span,
+                expected: self.resolve(expected),
+                found: self.resolve(found),
+                kind: Box::new(kind),
+            }
+        })
+    }
+
+    /// Unify two terms, returning the innermost conflict
+    fn unify_terms(&mut self, expected: &Ty<C>, found: &Ty<C>) -> Result<(), Conflict<C>> {
+        match (self.shallow(expected), self.shallow(found)) {
+            (Ty::Var(a), Ty::Var(b)) if a == b => Ok(()),
+            (Ty::Var(var), ty) | (ty, Ty::Var(var)) => self.bind(var, ty),
+            (Ty::Con(a, a_args), Ty::Con(b, b_args)) if a == b && a_args.len() == b_args.len() => {
+                for (a, b) in a_args.iter().zip(&b_args) {
+                    self.unify_terms(a, b)?;
+                }
+                Ok(())
+            }
+            (Ty::Fun(a_param, a_ret), Ty::Fun(b_param, b_ret)) => {
+                self.unify_terms(&a_param, &b_param)?;
+                self.unify_terms(&a_ret, &b_ret)
+            }
+            (Ty::Tuple(a), Ty::Tuple(b)) if a.len() == b.len() => {
+                for (a, b) in a.iter().zip(&b) {
+                    self.unify_terms(a, b)?;
+                }
+                Ok(())
+            }
+            (expected, found) => Err(Conflict::Mismatch(expected, found)),
+        }
+    }
+
+    /// Solve an unsolved root variable to a term
+    fn bind(&mut self, var: TyVar, ty: Ty<C>) -> Result<(), Conflict<C>> {
+        let level = self.vars[var.0 as usize].level;
+        if let Ty::Var(other) = ty {
+            // Keep the outer level, so the class doesn't generalize too early
+            let other_state = &mut self.vars[other.0 as usize];
+            other_state.level = other_state.level.min(level);
+        } else {
+            self.check_occurs(var, level, &ty)
+                .map_err(|()| Conflict::Occurs(var, ty.clone()))?;
+        }
+        self.vars[var.0 as usize].binding = Some(ty);
+        Ok(())
+    }
+
+    /// Check that `var` doesn't occur in `ty`, lowering the variables in `ty` to `level`
+    fn check_occurs(&mut self, var: TyVar, level: u32, ty: &Ty<C>) -> Result<(), ()> {
+        match self.shallow(ty) {
+            Ty::Var(other) if other == var => Err(()),
+            Ty::Var(other) => {
+                let state = &mut self.vars[other.0 as usize];
+                state.level = state.level.min(level);
+                Ok(())
+            }
+            Ty::Con(_, args) | Ty::Tuple(args) => args
+                .iter()
+                .try_for_each(|arg| self.check_occurs(var, level, arg)),
+            Ty::Fun(param, ret) => {
+                self.check_occurs(var, level, &param)?;
+                self.check_occurs(var, level, &ret)
+            }
+        }
+    }
+
+    /// Quantify a term over its unsolved variables that were created inside the level
+    /// just left, and didn't escape into an enclosing one
+    pub fn generalize(&mut self, ty: &Ty<C>) -> Scheme<C> {
+        let ty = self.resolve(ty);
+        let vars = ty
+            .vars()
+            .into_iter()
+            .filter(|var| self.vars[var.0 as usize].level > self.level)
+            .collect();
+        Scheme { vars, ty }
+    }
+
+    /// Replace a scheme's quantified variables with fresh ones
+    pub fn instantiate(&mut self, scheme: &Scheme<C>) -> Ty<C> {
+        let fresh: HashMap<_, _> = scheme.vars.iter().map(|&var| (var, self.fresh())).collect();
+        let ty = self.resolve(&scheme.ty);
+        ty.substitute(&fresh)
+    }
+}
+
+impl<C: Clone + PartialEq> Default for Inference<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The innermost reason two terms don't unify
+enum Conflict<C> {
+    /// The terms have different shapes
+    Mismatch(Ty<C>, Ty<C>),
+    /// The variable would have to contain the term it's in
+    Occurs(TyVar, Ty<C>),
+}
+
+/// An error from [`Inference::unify`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnifyError<C> {
+    /// Where the terms had to be equal
+    pub span: Span,
+    /// The expected term, with the variables solved before the error substituted
+    pub expected: Ty<C>,
+    /// The term that was found instead
+    pub found: Ty<C>,
+    /// The part of the terms that conflicted, boxed to keep results small
+    pub kind: Box<UnifyErrorKind<C>>,
+}
+
+/// The part of the terms that conflicted in a [`UnifyError`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnifyErrorKind<C> {
+    /// Two parts of the terms have different constructors
+    Mismatch {
+        /// The expected part
+        expected: Ty<C>,
+        /// The part found instead
+        found: Ty<C>,
+    },
+    /// A variable would be an infinite term, containing itself
+    Occurs {
+        /// The variable
+        var: TyVar,
+        /// The term containing the variable
+        ty: Ty<C>,
+    },
+}
+
+/// Errors share variable names between terms: "Expected `'a list`, found `'a`, which
+/// would make `'a` the infinite type `'a list`"
+impl<C: fmt::Display + PartialEq> fmt::Display for UnifyError<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = RefCell::default();
+        write!(
+            f,
+            "Expected `{}`, found `{}`",
+            self.expected.named(&names),
+            self.found.named(&names)
+        )?;
+        match &*self.kind {
+            UnifyErrorKind::Mismatch { expected, found } => {
+                if (expected, found) != (&self.expected, &self.found) {
+                    write!(
+                        f,
+                        " (`{}` isn't `{}`)",
+                        found.named(&names),
+                        expected.named(&names)
+                    )?;
+                }
+                Ok(())
+            }
+            UnifyErrorKind::Occurs { var, ty } => write!(
+                f,
+                ", which would make `{}` the infinite type `{}`",
+                Ty::<C>::Var(*var).named(&names),
+                ty.named(&names)
+            ),
+        }
+    }
+}
+
+impl<C: fmt::Debug + fmt::Display + PartialEq> Error for UnifyError<C> {}
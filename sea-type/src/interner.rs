@@ -0,0 +1,240 @@
+//! Hash-consing types into [`TypeId`]s
+
+use crate::{FloatKind, Type, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A table of interned types
+///
+/// Interning a type gives its [`TypeId`], which is the same for every equal type, so
+/// comparing and hashing types is as cheap as for integers. The lattice operations
+/// ([`meet`](Self::meet) and [`join`](Self::join)) work on ids and remember their
+/// results.
+///
+/// ```
+/// # use sea_type::{TypeId, TypeInterner};
+/// let mut types = TypeInterner::new();
+/// let int = types.int(32, true);
+/// let pointer = types.pointer(int, false);
+/// assert_eq!(types.pointer(int, false), pointer);
+/// assert_eq!(types.display(pointer).to_string(), "*i32");
+/// assert_eq!(types.meet(pointer, TypeId::NULL), types.pointer(int, true));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TypeInterner {
+    /// The interned types, indexed by [`TypeId`]
+    types: Vec<Type>,
+    /// The id of each interned type
+    ids: HashMap<Type, TypeId>,
+    /// The fields of each defined struct
+    fields: HashMap<TypeId, Vec<(String, TypeId)>>,
+    /// The meets computed so far, keyed with the smaller id first
+    pub(crate) meets: HashMap<(TypeId, TypeId), TypeId>,
+    /// The joins computed so far, keyed with the smaller id first
+    pub(crate) joins: HashMap<(TypeId, TypeId), TypeId>,
+}
+
+impl TypeInterner {
+    /// Create an interner holding just the types with constant [`TypeId`]s
+    #[must_use]
+    pub fn new() -> Self {
+        let mut types = Self {
+            types: Vec::new(),
+            ids: HashMap::new(),
+            fields: HashMap::new(),
+            meets: HashMap::new(),
+            joins: HashMap::new(),
+        };
+        for ty in TypeId::CONSTANTS {
+            types.intern(ty);
+        }
+        types
+    }
+
+    /// Get the id of `ty`, interning it if it's new
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` is an integer without 1 to 128 bits, or if the interner holds more
+    /// than `u32::MAX` types
+    pub fn intern(&mut self, ty: Type) -> TypeId {
+        if let Type::Int { bits, .. } = ty {
+            assert!(
+                (1..=128).contains(&bits),
+                "integers must have 1 to 128 bits, not {bits}"
+            );
+        }
+        if let Some(&id) = self.ids.get(&ty) {
+            return id;
+        }
+        let id = TypeId(u32::try_from(self.types.len()).expect("too many types"));
+        self.types.push(ty.clone());
+        self.ids.insert(ty, id);
+        id
+    }
+
+    /// Get an interned type
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type wasn't interned by this interner
+    #[must_use]
+    pub fn get(&self, id: TypeId) -> &Type {
+        &self.types[id.0 as usize]
+    }
+
+    /// Intern an integer type of `bits` bits
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `bits` is between 1 and 128
+    pub fn int(&mut self, bits: u8, signed: bool) -> TypeId {
+        self.intern(Type::Int { bits, signed })
+    }
+
+    /// Intern a floating-point type
+    pub fn float(&mut self, kind: FloatKind) -> TypeId {
+        self.intern(Type::Float(kind))
+    }
+
+    /// Intern a pointer type
+    pub fn pointer(&mut self, pointee: TypeId, nullable: bool) -> TypeId {
+        self.intern(Type::Pointer { pointee, nullable })
+    }
+
+    /// Intern an array type, of an unknown length if `length` is `None`
+    pub fn array(&mut self, element: TypeId, length: Option<u64>) -> TypeId {
+        self.intern(Type::Array { element, length })
+    }
+
+    /// Intern a tuple type
+    pub fn tuple(&mut self, elements: impl IntoIterator<Item = TypeId>) -> TypeId {
+        self.intern(Type::Tuple(elements.into_iter().collect()))
+    }
+
+    /// Intern a function type
+    pub fn function(&mut self, params: impl IntoIterator<Item = TypeId>, ret: TypeId) -> TypeId {
+        self.intern(Type::Function {
+            params: params.into_iter().collect(),
+            ret,
+        })
+    }
+
+    /// Intern the struct type named `name`, which may not be defined yet
+    ///
+    /// Structs are nominal, so a struct can contain pointers to itself.
+    pub fn struct_type(&mut self, name: impl Into<String>) -> TypeId {
+        self.intern(Type::Struct(name.into()))
+    }
+
+    /// Define the fields of the struct named `name`, replacing any earlier definition
+    pub fn define_struct(
+        &mut self,
+        name: impl Into<String>,
+        fields: impl IntoIterator<Item = (String, TypeId)>,
+    ) -> TypeId {
+        let id = self.struct_type(name);
+        self.fields.insert(id, fields.into_iter().collect());
+        id
+    }
+
+    /// Get the fields of a struct type, or `None` if it's not a defined struct
+    #[must_use]
+    pub fn fields(&self, id: TypeId) -> Option<&[(String, TypeId)]> {
+        self.fields.get(&id).map(Vec::as_slice)
+    }
+
+    /// Get the number of interned types
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Reports whether the interner is empty, which it never is
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Get a displayable form of a type: `i32`, `*struct node?`, or `fn(f64) -> ()`
+    #[must_use]
+    pub const fn display(&self, id: TypeId) -> DisplayType<'_> {
+        DisplayType { types: self, id }
+    }
+}
+
+impl Default for TypeInterner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A type that can be displayed, from [`TypeInterner::display`]
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayType<'a> {
+    /// The interner holding the type
+    types: &'a TypeInterner,
+    /// The type
+    id: TypeId,
+}
+
+impl DisplayType<'_> {
+    /// Display another type from the same interner
+    const fn of(&self, id: TypeId) -> Self {
+        Self { id, ..*self }
+    }
+
+    /// Write `ids` separated by commas
+    fn write_list(&self, f: &mut fmt::Formatter<'_>, ids: &[TypeId]) -> fmt::Result {
+        for (index, &id) in ids.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", self.of(id))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DisplayType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.types.get(self.id) {
+            Type::Top => f.write_str("top"),
+            Type::Bottom => f.write_str("bottom"),
+            Type::Control => f.write_str("ctrl"),
+            Type::XControl => f.write_str("~ctrl"),
+            Type::Memory => f.write_str("mem"),
+            Type::XMemory => f.write_str("~mem"),
+            Type::Int { bits, signed } => write!(f, "{}{bits}", if *signed { 'i' } else { 'u' }),
+            Type::Float(FloatKind::F32) => f.write_str("f32"),
+            Type::Float(FloatKind::F64) => f.write_str("f64"),
+            Type::Null => f.write_str("null"),
+            Type::Pointer { pointee, nullable } => {
+                write!(f, "*{}", self.of(*pointee))?;
+                if *nullable {
+                    f.write_str("?")?;
+                }
+                Ok(())
+            }
+            Type::Array {
+                element,
+                length: Some(length),
+            } => write!(f, "[{}; {length}]", self.of(*element)),
+            Type::Array {
+                element,
+                length: None,
+            } => write!(f, "[{}]", self.of(*element)),
+            Type::Tuple(elements) => {
+                f.write_str("(")?;
+                self.write_list(f, elements)?;
+                f.write_str(")")
+            }
+            Type::Struct(name) => write!(f, "struct {name}"),
+            Type::Function { params, ret } => {
+                f.write_str("fn(")?;
+                self.write_list(f, params)?;
+                write!(f, ") -> {}", self.of(*ret))
+            }
+        }
+    }
+}
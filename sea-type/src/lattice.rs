@@ -0,0 +1,236 @@
+//! The lattice operations on interned types
+
+use crate::{Type, TypeId, TypeInterner};
+
+/// Which way a lattice operation moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// Down, towards [`Type::Bottom`]
+    Meet,
+    /// Up, towards [`Type::Top`]
+    Join,
+}
+
+impl Direction {
+    /// The opposite direction, for contravariant parts of a type
+    const fn flip(self) -> Self {
+        match self {
+            Self::Meet => Self::Join,
+            Self::Join => Self::Meet,
+        }
+    }
+}
+
+impl TypeInterner {
+    /// Get the meet of two types: the highest type below both, which has every value
+    /// either has
+    ///
+    /// Mismatched types meet at [`Type::Bottom`].
+    ///
+    /// ```
+    /// # use sea_type::{FloatKind, TypeId, TypeInterner};
+    /// let mut types = TypeInterner::new();
+    /// let (i8, u8, i9) = (types.int(8, true), types.int(8, false), types.int(9, true));
+    /// assert_eq!(types.meet(i8, u8), i9);
+    /// assert_eq!(types.meet(i8, TypeId::TOP), i8);
+    /// let f64 = types.float(FloatKind::F64);
+    /// assert_eq!(types.meet(i8, f64), TypeId::BOTTOM);
+    /// ```
+    pub fn meet(&mut self, a: TypeId, b: TypeId) -> TypeId {
+        self.combine(a, b, Direction::Meet)
+    }
+
+    /// Get the join of two types: the lowest type above both, which has only values both
+    /// have
+    ///
+    /// Mismatched types join at [`Type::Top`].
+    ///
+    /// ```
+    /// # use sea_type::{TypeId, TypeInterner};
+    /// let mut types = TypeInterner::new();
+    /// let (i8, u8, u7) = (types.int(8, true), types.int(8, false), types.int(7, false));
+    /// assert_eq!(types.join(i8, u8), u7);
+    /// assert_eq!(types.join(i8, TypeId::BOTTOM), i8);
+    /// assert_eq!(types.join(i8, TypeId::CONTROL), TypeId::TOP);
+    /// ```
+    pub fn join(&mut self, a: TypeId, b: TypeId) -> TypeId {
+        self.combine(a, b, Direction::Join)
+    }
+
+    /// Reports whether `a` is at or above `b`, so every value of type `a` is also of
+    /// type `b`
+    pub fn isa(&mut self, a: TypeId, b: TypeId) -> bool {
+        self.meet(a, b) == b
+    }
+
+    /// Meet or join two types, remembering the result
+    fn combine(&mut self, a: TypeId, b: TypeId, direction: Direction) -> TypeId {
+        if a == b {
+            return a;
+        }
+        let key = (a.min(b), a.max(b));
+        let cache = match direction {
+            Direction::Meet => &self.meets,
+            Direction::Join => &self.joins,
+        };
+        if let Some(&result) = cache.get(&key) {
+            return result;
+        }
+        let result = self.combine_uncached(a, b, direction);
+        match direction {
+            Direction::Meet => self.meets.insert(key, result),
+            Direction::Join => self.joins.insert(key, result),
+        };
+        result
+    }
+
+    /// Meet or join two different types
+    fn combine_uncached(&mut self, a: TypeId, b: TypeId, direction: Direction) -> TypeId {
+        let (top, bottom) = match direction {
+            Direction::Meet => (b, TypeId::BOTTOM),
+            Direction::Join => (TypeId::TOP, b),
+        };
+        let meet = direction == Direction::Meet;
+        let ty = match (self.get(a).clone(), self.get(b).clone()) {
+            (Type::Top, _) => return top,
+            (_, Type::Top) => return self.combine_uncached(b, a, direction),
+            (Type::Bottom, _) => return bottom,
+            (_, Type::Bottom) => return self.combine_uncached(b, a, direction),
+            (Type::Control, Type::XControl) | (Type::XControl, Type::Control) => {
+                return if meet {
+                    TypeId::CONTROL
+                } else {
+                    TypeId::XCONTROL
+                };
+            }
+            (Type::Memory, Type::XMemory) | (Type::XMemory, Type::Memory) => {
+                return if meet {
+                    TypeId::MEMORY
+                } else {
+                    TypeId::XMEMORY
+                };
+            }
+            (
+                Type::Int {
+                    bits: a_bits,
+                    signed: a_signed,
+                },
+                Type::Int {
+                    bits: b_bits,
+                    signed: b_signed,
+                },
+            ) => int(direction, (a_bits, a_signed), (b_bits, b_signed)),
+            (Type::Float(a), Type::Float(b)) => {
+                Some(Type::Float(if meet { a.max(b) } else { a.min(b) }))
+            }
+            (Type::Null, Type::Pointer { pointee, nullable })
+            | (Type::Pointer { pointee, nullable }, Type::Null) => match (meet, nullable) {
+                (true, _) => Some(Type::Pointer {
+                    pointee,
+                    nullable: true,
+                }),
+                (false, true) => Some(Type::Null),
+                (false, false) => None,
+            },
+            (
+                Type::Pointer {
+                    pointee: a,
+                    nullable: a_nullable,
+                },
+                Type::Pointer {
+                    pointee: b,
+                    nullable: b_nullable,
+                },
+            ) => Some(Type::Pointer {
+                pointee: self.combine(a, b, direction),
+                nullable: if meet {
+                    a_nullable || b_nullable
+                } else {
+                    a_nullable && b_nullable
+                },
+            }),
+            (
+                Type::Array {
+                    element: a,
+                    length: a_length,
+                },
+                Type::Array {
+                    element: b,
+                    length: b_length,
+                },
+            ) if a_length == b_length => Some(Type::Array {
+                element: self.combine(a, b, direction),
+                length: a_length,
+            }),
+            (Type::Tuple(a), Type::Tuple(b)) if a.len() == b.len() => {
+                Some(Type::Tuple(self.combine_all(&a, &b, direction)))
+            }
+            (
+                Type::Function {
+                    params: a_params,
+                    ret: a_ret,
+                },
+                Type::Function {
+                    params: b_params,
+                    ret: b_ret,
+                },
+            ) if a_params.len() == b_params.len() => Some(Type::Function {
+                // Parameters are contravariant: a function taking any of either's
+                // arguments can stand in for both
+                params: self.combine_all(&a_params, &b_params, direction.flip()),
+                ret: self.combine(a_ret, b_ret, direction),
+            }),
+            _ => None,
+        };
+        match (ty, direction) {
+            (Some(ty), _) => self.intern(ty),
+            (None, Direction::Meet) => TypeId::BOTTOM,
+            (None, Direction::Join) => TypeId::TOP,
+        }
+    }
+
+    /// Meet or join two lists of types of the same length, element by element
+    fn combine_all(&mut self, a: &[TypeId], b: &[TypeId], direction: Direction) -> Vec<TypeId> {
+        a.iter()
+            .zip(b)
+            .map(|(&a, &b)| self.combine(a, b, direction))
+            .collect()
+    }
+}
+
+/// Meet or join two integer types, given as their bits and signedness, or get `None` if
+/// no integer type holds the result
+///
+/// The meet is the smallest type holding the values of both, and the join the largest
+/// type whose values both hold.
+fn int(direction: Direction, a: (u8, bool), b: (u8, bool)) -> Option<Type> {
+    let ((a_bits, a_signed), (b_bits, b_signed)) = (a, b);
+    if a_signed == b_signed {
+        let bits = match direction {
+            Direction::Meet => a_bits.max(b_bits),
+            Direction::Join => a_bits.min(b_bits),
+        };
+        return Some(Type::Int {
+            bits,
+            signed: a_signed,
+        });
+    }
+    let (signed, unsigned) = if a_signed {
+        (a_bits, b_bits)
+    } else {
+        (b_bits, a_bits)
+    };
+    match direction {
+        // A signed type needs a bit more to hold every unsigned value
+        Direction::Meet => Some(signed.max(unsigned + 1))
+            .filter(|&bits| bits <= 128)
+            .map(|bits| Type::Int { bits, signed: true }),
+        // The shared values are the unsigned ones below the signed type's maximum
+        Direction::Join => Some((signed - 1).min(unsigned))
+            .filter(|&bits| bits > 0)
+            .map(|bits| Type::Int {
+                bits,
+                signed: false,
+            }),
+    }
+}
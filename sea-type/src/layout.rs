@@ -0,0 +1,462 @@
+//! Target data layouts: sizes, alignments, struct layout, and argument passing
+
+use crate::{FloatKind, Type, TypeId, TypeInterner};
+
+/// The size and alignment of a type, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Layout {
+    /// The size, which is a multiple of the alignment
+    pub size: u64,
+    /// The alignment, which is a power of two
+    pub align: u64,
+}
+
+/// The layout of a struct in memory
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StructLayout {
+    /// The struct's size in bytes, including any padding at the end
+    pub size: u64,
+    /// The struct's alignment in bytes
+    pub align: u64,
+    /// The offset of each field in bytes, in declaration order
+    pub offsets: Vec<u64>,
+}
+
+impl StructLayout {
+    /// Get the struct's size and alignment
+    #[must_use]
+    pub const fn layout(&self) -> Layout {
+        Layout {
+            size: self.size,
+            align: self.align,
+        }
+    }
+}
+
+/// How a struct's fields are laid out
+///
+/// Fields are always laid out in declaration order, as in C and Rust's `repr(C)`, each at
+/// the next offset that's a multiple of its alignment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Repr {
+    /// The most alignment any field gets, as with `#pragma pack(n)` or
+    /// `repr(packed(n))`
+    pub pack: Option<u64>,
+    /// The least alignment the struct gets, as with `repr(align(n))`
+    pub align: Option<u64>,
+}
+
+impl Repr {
+    /// C's layout: every field at its natural alignment
+    pub const C: Self = Self {
+        pack: None,
+        align: None,
+    };
+
+    /// No padding at all, as with `__attribute__((packed))` or `repr(packed)`
+    pub const PACKED: Self = Self::packed(1);
+
+    /// Cap every field's alignment at `pack` bytes
+    #[must_use]
+    pub const fn packed(pack: u64) -> Self {
+        Self {
+            pack: Some(pack),
+            align: None,
+        }
+    }
+
+    /// Raise the struct's alignment to at least `align` bytes
+    #[must_use]
+    pub const fn aligned(self, align: u64) -> Self {
+        Self {
+            align: Some(align),
+            ..self
+        }
+    }
+}
+
+/// A target's procedure call standard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Abi {
+    /// The System V AMD64 ABI, used on x86-64 Linux and macOS
+    SysV,
+    /// The Arm 64-bit procedure call standard
+    Aapcs64,
+    /// The WebAssembly basic C ABI
+    Wasm,
+}
+
+/// A register, or part of the stack, that carries a piece of an argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reg {
+    /// A general-purpose register, holding `size` bytes
+    Int {
+        /// The number of bytes used
+        size: u64,
+    },
+    /// A floating-point or vector register, holding `size` bytes
+    Float {
+        /// The number of bytes used
+        size: u64,
+    },
+}
+
+/// How a value is passed to or returned from a function
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PassMode {
+    /// Not passed at all, since it has no bytes
+    Ignore,
+    /// In registers, one per piece, in order; when they run out, the value goes on the
+    /// stack
+    Direct(Vec<Reg>),
+    /// In memory: arguments are copied and passed by address or on the stack, and
+    /// results are written through a hidden pointer the caller passes
+    Indirect,
+}
+
+/// A scalar inside a value, found by flattening its aggregates
+#[derive(Debug, Clone, Copy)]
+struct Leaf {
+    /// The byte offset from the start of the value
+    offset: u64,
+    /// The scalar's layout
+    layout: Layout,
+    /// The scalar's floating-point format, or `None` for integers and pointers
+    float: Option<FloatKind>,
+}
+
+/// The sizes, alignments, and calling convention of a target
+///
+/// Interned types don't say how big they are: integers and floats have their bit widths,
+/// but pointers and alignment depend on the target. A `DataLayout` gives the
+/// [`Layout`] of any type, lays out structs, and classifies how values are passed
+/// between functions. It also says how wide C's `long` is, and whether `char` is signed.
+///
+/// ```
+/// # use sea_type::{DataLayout, Layout, Repr, TypeInterner};
+/// let mut types = TypeInterner::new();
+/// let (i8, i32) = (types.int(8, true), types.int(32, true));
+/// let pointer = types.pointer(i8, false);
+/// assert_eq!(DataLayout::X86_64_SYSV.layout(&types, pointer).map(|l| l.size), Some(8));
+/// assert_eq!(DataLayout::WASM32.layout(&types, pointer).map(|l| l.size), Some(4));
+///
+/// let fields = [i8, i32].map(|ty| DataLayout::WASM32.layout(&types, ty).unwrap());
+/// assert_eq!(DataLayout::WASM32.struct_layout(fields, Repr::C).offsets, [0, 4]);
+/// assert_eq!(DataLayout::WASM32.struct_layout(fields, Repr::PACKED).offsets, [0, 1]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DataLayout {
+    /// The target's name
+    pub name: &'static str,
+    /// The size and alignment of pointers, in bytes
+    pub pointer_size: u64,
+    /// The widest alignment any scalar gets, in bytes
+    pub max_scalar_align: u64,
+    /// The number of bits in C's `long`
+    pub long_bits: u8,
+    /// Whether C's plain `char` is signed
+    pub char_signed: bool,
+    /// The procedure call standard
+    pub abi: Abi,
+}
+
+impl DataLayout {
+    /// x86-64 with the System V ABI: LP64, with signed `char` and 16-byte aligned `i128`
+    pub const X86_64_SYSV: Self = Self {
+        name: "x86_64-sysv",
+        pointer_size: 8,
+        max_scalar_align: 16,
+        long_bits: 64,
+        char_signed: true,
+        abi: Abi::SysV,
+    };
+
+    /// 64-bit Arm with the AAPCS64: LP64, with unsigned `char` and 16-byte aligned `i128`
+    pub const AARCH64_AAPCS64: Self = Self {
+        name: "aarch64-aapcs64",
+        pointer_size: 8,
+        max_scalar_align: 16,
+        long_bits: 64,
+        char_signed: false,
+        abi: Abi::Aapcs64,
+    };
+
+    /// 32-bit WebAssembly with the basic C ABI: ILP32, with signed `char` and 16-byte
+    /// aligned `i128`
+    pub const WASM32: Self = Self {
+        name: "wasm32",
+        pointer_size: 4,
+        max_scalar_align: 16,
+        long_bits: 32,
+        char_signed: true,
+        abi: Abi::Wasm,
+    };
+
+    /// Get the layout of a scalar of `size` bytes, aligned to its size up to the
+    /// target's maximum
+    const fn scalar(&self, size: u64) -> Layout {
+        Layout {
+            size,
+            align: if size < self.max_scalar_align {
+                size
+            } else {
+                self.max_scalar_align
+            },
+        }
+    }
+
+    /// Get a type's size and alignment, or `None` if it has no size: `void`, functions,
+    /// undefined structs, arrays without lengths, and the types that only describe
+    /// control and memory
+    ///
+    /// Defined structs are laid out with [`Repr::C`]. Integers take the smallest power
+    /// of two bytes that holds their bits, so `bool`, an `i1`, takes a byte.
+    #[must_use]
+    pub fn layout(&self, types: &TypeInterner, ty: TypeId) -> Option<Layout> {
+        self.layout_within(types, ty, &mut Vec::new())
+    }
+
+    /// Get a type's layout, or `None` if it contains one of `enclosing` by value
+    fn layout_within(
+        &self,
+        types: &TypeInterner,
+        ty: TypeId,
+        enclosing: &mut Vec<TypeId>,
+    ) -> Option<Layout> {
+        match types.get(ty) {
+            Type::Int { bits, .. } => {
+                Some(self.scalar(u64::from(bits.div_ceil(8)).next_power_of_two()))
+            }
+            Type::Float(FloatKind::F32) => Some(self.scalar(4)),
+            Type::Float(FloatKind::F64) => Some(self.scalar(8)),
+            Type::Null | Type::Pointer { .. } => Some(self.scalar(self.pointer_size)),
+            Type::Array {
+                element,
+                length: Some(length),
+            } => {
+                let element = self.layout_within(types, *element, enclosing)?;
+                Some(Layout {
+                    size: element.size * length,
+                    align: element.align,
+                })
+            }
+            Type::Tuple(elements) if !elements.is_empty() => {
+                self.aggregate_layout(types, ty, elements, enclosing)
+            }
+            Type::Struct(_) => {
+                let fields: Vec<_> = types.fields(ty)?.iter().map(|&(_, field)| field).collect();
+                self.aggregate_layout(types, ty, &fields, enclosing)
+            }
+            _ => None,
+        }
+    }
+
+    /// Lay out a struct or tuple with [`Repr::C`], unless it contains itself
+    fn aggregate_layout(
+        &self,
+        types: &TypeInterner,
+        ty: TypeId,
+        fields: &[TypeId],
+        enclosing: &mut Vec<TypeId>,
+    ) -> Option<Layout> {
+        if enclosing.contains(&ty) {
+            return None;
+        }
+        enclosing.push(ty);
+        let fields: Option<Vec<_>> = fields
+            .iter()
+            .map(|&field| self.layout_within(types, field, enclosing))
+            .collect();
+        enclosing.pop();
+        Some(self.struct_layout(fields?, Repr::C).layout())
+    }
+
+    /// Lay out a struct with fields of the given layouts
+    ///
+    /// Each field goes at the next offset that's a multiple of its alignment, capped by
+    /// [`Repr::pack`]. The struct is as aligned as its most aligned field, or
+    /// [`Repr::align`] if that's more, and padded to a multiple of its alignment.
+    #[must_use]
+    pub fn struct_layout(
+        &self,
+        fields: impl IntoIterator<Item = Layout>,
+        repr: Repr,
+    ) -> StructLayout {
+        let (mut size, mut align) = (0_u64, 1);
+        let mut offsets = Vec::new();
+        for field in fields {
+            let field_align = repr.pack.map_or(field.align, |pack| field.align.min(pack));
+            let offset = size.next_multiple_of(field_align);
+            offsets.push(offset);
+            size = offset + field.size;
+            align = align.max(field_align);
+        }
+        if let Some(min_align) = repr.align {
+            align = align.max(min_align);
+        }
+        StructLayout {
+            size: size.next_multiple_of(align),
+            align,
+            offsets,
+        }
+    }
+
+    /// Classify how a value of type `ty` is passed as an argument or returned, or get
+    /// `None` if it has no layout
+    ///
+    /// This classifies one value on its own; when the registers run out, the caller
+    /// passes the rest on the stack.
+    ///
+    /// - **System V:** values of up to 16 bytes are split into eightbytes, each passed
+    ///   in a floating-point register if it holds only floats, and a general-purpose
+    ///   register otherwise, and skipped if it's only padding. Bigger values are passed
+    ///   in memory.
+    /// - **AAPCS64:** aggregates of up to four floats of the same format are passed in
+    ///   floating-point registers, other values of up to 16 bytes in general-purpose
+    ///   registers, and bigger values in memory.
+    /// - **WebAssembly:** scalars, and aggregates holding a single scalar, are passed
+    ///   directly, and other aggregates in memory.
+    ///
+    /// ```
+    /// # use sea_type::{DataLayout, FloatKind, PassMode, Reg, TypeInterner};
+    /// let mut types = TypeInterner::new();
+    /// let (i32, f64) = (types.int(32, true), types.float(FloatKind::F64));
+    /// let pair = types.tuple([i32, f64]);
+    /// assert_eq!(
+    ///     DataLayout::X86_64_SYSV.classify(&types, pair),
+    ///     Some(PassMode::Direct(vec![Reg::Int { size: 8 }, Reg::Float { size: 8 }])),
+    /// );
+    /// assert_eq!(DataLayout::WASM32.classify(&types, pair), Some(PassMode::Indirect));
+    /// ```
+    #[must_use]
+    pub fn classify(&self, types: &TypeInterner, ty: TypeId) -> Option<PassMode> {
+        let layout = self.layout(types, ty)?;
+        if layout.size == 0 {
+            return Some(PassMode::Ignore);
+        }
+        let mut leaves = Vec::new();
+        self.flatten(types, ty, 0, &mut leaves);
+        let aggregate = !matches!(
+            types.get(ty),
+            Type::Int { .. } | Type::Float(_) | Type::Null | Type::Pointer { .. }
+        );
+        // Integers wider than a general-purpose register, like `i128`, take a pair of them
+        let regs = |leaf: &Leaf| match leaf.float {
+            Some(_) => vec![Reg::Float {
+                size: leaf.layout.size,
+            }],
+            None => (0..leaf.layout.size.div_ceil(8))
+                .map(|index| Reg::Int {
+                    size: (leaf.layout.size - index * 8).min(8),
+                })
+                .collect(),
+        };
+        let mode = match self.abi {
+            // Scalars are split into eightbytes too, so an `i128` takes two registers
+            Abi::SysV => {
+                if layout.size > 16 {
+                    PassMode::Indirect
+                } else {
+                    PassMode::Direct(
+                        (0..layout.size.div_ceil(8))
+                            .filter_map(|eightbyte| {
+                                let start = eightbyte * 8;
+                                let size = (layout.size - start).min(8);
+                                let within: Vec<_> = leaves
+                                    .iter()
+                                    .filter(|leaf| {
+                                        leaf.offset < start + 8
+                                            && leaf.offset + leaf.layout.size > start
+                                    })
+                                    .collect();
+                                // Padding alone is NO_CLASS, which merges into any other class,
+                                // so an eightbyte of padding isn't passed at all
+                                if within.is_empty() {
+                                    return None;
+                                }
+                                // INTEGER wins over SSE when they merge
+                                Some(if within.iter().all(|leaf| leaf.float.is_some()) {
+                                    Reg::Float { size }
+                                } else {
+                                    Reg::Int { size }
+                                })
+                            })
+                            .collect(),
+                    )
+                }
+            }
+            _ if !aggregate => PassMode::Direct(leaves.iter().flat_map(regs).collect()),
+            Abi::Aapcs64 => {
+                let homogeneous =
+                    leaves
+                        .first()
+                        .and_then(|first| first.float)
+                        .is_some_and(|kind| {
+                            leaves.len() <= 4 && leaves.iter().all(|leaf| leaf.float == Some(kind))
+                        });
+                if homogeneous {
+                    PassMode::Direct(leaves.iter().flat_map(regs).collect())
+                } else if layout.size > 16 {
+                    PassMode::Indirect
+                } else {
+                    PassMode::Direct(
+                        (0..layout.size.div_ceil(8))
+                            .map(|index| Reg::Int {
+                                size: (layout.size - index * 8).min(8),
+                            })
+                            .collect(),
+                    )
+                }
+            }
+            Abi::Wasm => match leaves.as_slice() {
+                [leaf] => PassMode::Direct(regs(leaf)),
+                _ => PassMode::Indirect,
+            },
+        };
+        Some(mode)
+    }
+
+    /// Add the scalars in a value of type `ty` at `offset` to `leaves`
+    fn flatten(&self, types: &TypeInterner, ty: TypeId, offset: u64, leaves: &mut Vec<Leaf>) {
+        let Some(layout) = self.layout(types, ty) else {
+            return;
+        };
+        let fields: Vec<_> = match types.get(ty) {
+            Type::Float(kind) => {
+                leaves.push(Leaf {
+                    offset,
+                    layout,
+                    float: Some(*kind),
+                });
+                return;
+            }
+            Type::Array {
+                element,
+                length: Some(length),
+            } => (0..*length).map(|_| *element).collect(),
+            Type::Tuple(elements) => elements.clone(),
+            Type::Struct(_) => types
+                .fields(ty)
+                .unwrap_or_default()
+                .iter()
+                .map(|&(_, field)| field)
+                .collect(),
+            _ => {
+                leaves.push(Leaf {
+                    offset,
+                    layout,
+                    float: None,
+                });
+                return;
+            }
+        };
+        let layouts: Vec<_> = fields
+            .iter()
+            .filter_map(|&field| self.layout(types, field))
+            .collect();
+        let offsets = self.struct_layout(layouts, Repr::C).offsets;
+        for (&field, field_offset) in fields.iter().zip(offsets) {
+            self.flatten(types, field, offset + field_offset, leaves);
+        }
+    }
+}
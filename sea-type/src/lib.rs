@@ -1,4 +1,20 @@
 //! sea-type - SeaFlow type-checker component
+//!
+//! The types of sea-of-nodes values form a lattice, from [`Type::Top`] down to
+//! [`Type::Bottom`]. A [`TypeInterner`] hash-conses [`Type`]s into [`TypeId`]s, and
+//! computes their [`meet`](TypeInterner::meet) and [`join`](TypeInterner::join).
+//! [`IntRange`] refines integers into constants and ranges, for constant propagation.
+//!
+//! Frontends with type inference build [`Ty`] terms over their own type constructors,
+//! and solve them with an [`Inference`]: Hindley–Milner unification with
+//! let-polymorphism. Their algebraic data types are [`AdtDef`]s, and [`check_match`]
+//! checks matches on them for exhaustiveness and redundancy, while [`compile_match`]
+//! compiles them to [`Decision`] trees. [`Generic`] functions have type parameters
+//! with trait bounds, which [`Traits`] solves against their implementations, and a
+//! [`Monomorphizer`] instantiates them at the types they're used at.
+//!
+//! A [`DataLayout`] describes a target: the sizes and alignments of types, how structs
+//! are laid out, and how values are passed between functions.
 
 #![warn(
     clippy::all,
@@ -9,3 +25,26 @@
     missing_docs,
     rustdoc::all
 )]
+
+mod adt;
+mod generic;
+mod infer;
+mod interner;
+mod lattice;
+mod layout;
+mod matching;
+mod mono;
+mod range;
+mod term;
+mod ty;
+
+pub use adt::*;
+pub use generic::*;
+pub use infer::*;
+pub use interner::*;
+pub use layout::*;
+pub use matching::*;
+pub use mono::*;
+pub use range::*;
+pub use term::*;
+pub use ty::*;
@@ -0,0 +1,732 @@
+//! Checking and compiling pattern matches
+
+use crate::{Adts, Literal, Pat};
+use sea_lex::Span;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+/// An arm of a match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchArm {
+    /// The arm's pattern
+    pub pattern: Pat,
+    /// Whether the arm has a guard, like `when x > 0`, so that it may not be taken even
+    /// when its pattern matches
+    pub guarded: bool,
+    /// The location of the pattern
+    pub span: Span,
+}
+
+/// The outermost shape of a value, which patterns test for
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Head {
+    /// A variant, by the indices of its definition and of itself
+    Variant(usize, usize),
+    /// A tuple of some length, which is every tuple of that type
+    Tuple(usize),
+    /// A constant
+    Literal(Literal),
+}
+
+/// Compares patterns by the shapes of values they test for
+///
+/// The patterns in a match must be well-typed: every constructor known, with a pattern
+/// for each of its fields, and every pattern in a column testing values of one type.
+struct Heads<'a, C> {
+    /// The types the constructors belong to
+    adts: &'a Adts<C>,
+}
+
+impl<C: Clone + Eq + Hash> Heads<'_, C> {
+    /// Get the shape a pattern tests for, or `None` if it matches anything
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is an or-pattern, or has an unknown constructor
+    fn of(&self, pattern: &Pat) -> Option<Head> {
+        match pattern {
+            Pat::Wildcard | Pat::Var(_) => None,
+            Pat::Literal(literal) => Some(Head::Literal(literal.clone())),
+            Pat::Tuple(elements) => Some(Head::Tuple(elements.len())),
+            Pat::Constructor(name, _) => {
+                let (def, variant) = self
+                    .adts
+                    .constructor_index(name)
+                    .expect("the constructor is defined");
+                Some(Head::Variant(def, variant))
+            }
+            Pat::Or(_) => unreachable!("or-patterns are expanded first"),
+        }
+    }
+
+    /// Get the number of fields a shape has
+    fn arity(&self, head: &Head) -> usize {
+        match *head {
+            Head::Variant(def, variant) => self.adts.def(def).variants[variant].fields.len(),
+            Head::Tuple(arity) => arity,
+            Head::Literal(_) => 0,
+        }
+    }
+
+    /// Get every shape of the type of `heads`, or `None` if there are too many to list
+    fn signature(&self, heads: &[Head]) -> Option<Vec<Head>> {
+        match heads.first()? {
+            &Head::Tuple(arity) => Some(vec![Head::Tuple(arity)]),
+            &Head::Variant(def, _) => Some(
+                (0..self.adts.def(def).variants.len())
+                    .map(|variant| Head::Variant(def, variant))
+                    .collect(),
+            ),
+            Head::Literal(Literal::Bool(_)) => Some(vec![
+                Head::Literal(Literal::Bool(false)),
+                Head::Literal(Literal::Bool(true)),
+            ]),
+            Head::Literal(Literal::Int(_) | Literal::String(_)) => None,
+        }
+    }
+
+    /// Get every shape of the type of `heads`, if they cover it
+    fn complete(&self, heads: &[Head]) -> Option<Vec<Head>> {
+        self.signature(heads)
+            .filter(|all| all.iter().all(|head| heads.contains(head)))
+    }
+
+    /// Get the distinct shapes the first patterns of the rows test for, in order
+    fn first_column<'r>(&self, rows: impl IntoIterator<Item = &'r [Pat]>) -> Vec<Head> {
+        let mut heads = Vec::new();
+        for row in rows {
+            if let Some(head) = self.of(&row[0]) {
+                if !heads.contains(&head) {
+                    heads.push(head);
+                }
+            }
+        }
+        heads
+    }
+
+    /// Get a row's patterns for a value of shape `head`: the first pattern's fields,
+    /// followed by the rest, or `None` if the first pattern doesn't match the shape
+    fn specialize(&self, row: &[Pat], head: &Head) -> Option<Vec<Pat>> {
+        let fields = match &row[0] {
+            Pat::Wildcard | Pat::Var(_) => vec![Pat::Wildcard; self.arity(head)],
+            Pat::Tuple(elements) if *head == Head::Tuple(elements.len()) => elements.clone(),
+            pattern @ Pat::Constructor(_, fields) if self.of(pattern).as_ref() == Some(head) => {
+                fields.clone()
+            }
+            Pat::Literal(literal) if *head == Head::Literal(literal.clone()) => Vec::new(),
+            _ => return None,
+        };
+        Some(fields.into_iter().chain(row[1..].iter().cloned()).collect())
+    }
+
+    /// Build the pattern for a shape with the given fields
+    fn pattern(&self, head: &Head, fields: Vec<Pat>) -> Pat {
+        match *head {
+            Head::Variant(def, variant) => {
+                Pat::Constructor(self.adts.def(def).variants[variant].name.clone(), fields)
+            }
+            Head::Tuple(_) => Pat::Tuple(fields),
+            Head::Literal(ref literal) => Pat::Literal(literal.clone()),
+        }
+    }
+
+    /// Rebuild witnesses for a specialized matrix into witnesses for the original: the
+    /// first fields become a pattern of shape `head`
+    fn unspecialize(&self, head: &Head, witnesses: Vec<Vec<Pat>>) -> Vec<Vec<Pat>> {
+        let arity = self.arity(head);
+        witnesses
+            .into_iter()
+            .map(|mut witness| {
+                let rest = witness.split_off(arity);
+                let mut rebuilt = vec![self.pattern(head, witness)];
+                rebuilt.extend(rest);
+                rebuilt
+            })
+            .collect()
+    }
+
+    /// Find the values matched by `row` that no row of `rows` matches, as patterns
+    ///
+    /// This is Maranget's usefulness algorithm: `row` is useful, and its arm reachable,
+    /// if there are any such values. When `row` is all wildcards, the values are the
+    /// ones the match misses.
+    fn useful(&self, rows: &[Vec<Pat>], row: &[Pat]) -> Vec<Vec<Pat>> {
+        let Some((first, rest)) = row.split_first() else {
+            return if rows.is_empty() {
+                vec![Vec::new()]
+            } else {
+                Vec::new()
+            };
+        };
+        let rows = expand_or(rows.to_vec());
+        match first {
+            Pat::Or(alternatives) => alternatives
+                .iter()
+                .flat_map(|alternative| {
+                    let row: Vec<_> = std::iter::once(alternative.clone())
+                        .chain(rest.to_vec())
+                        .collect();
+                    self.useful(&rows, &row)
+                })
+                .collect(),
+            Pat::Wildcard | Pat::Var(_) => {
+                let heads = self.first_column(rows.iter().map(Vec::as_slice));
+                if let Some(all) = self.complete(&heads) {
+                    return all
+                        .iter()
+                        .flat_map(|head| {
+                            let specialized = self.specialize_all(&rows, head);
+                            let row = self.specialize(row, head).expect("wildcards match");
+                            self.unspecialize(head, self.useful(&specialized, &row))
+                        })
+                        .collect();
+                }
+                let default: Vec<_> = rows
+                    .iter()
+                    .filter(|row| matches!(row[0], Pat::Wildcard | Pat::Var(_)))
+                    .map(|row| row[1..].to_vec())
+                    .collect();
+                // The shapes no row tests for, or `_` if there are too many to list
+                let missing: Vec<_> = self.signature(&heads).map_or_else(
+                    || vec![Pat::Wildcard],
+                    |all| {
+                        all.iter()
+                            .filter(|head| !heads.contains(head))
+                            .map(|head| self.pattern(head, vec![Pat::Wildcard; self.arity(head)]))
+                            .collect()
+                    },
+                );
+                self.useful(&default, rest)
+                    .into_iter()
+                    .flat_map(|witness| {
+                        missing.iter().map(move |pattern| {
+                            std::iter::once(pattern.clone())
+                                .chain(witness.clone())
+                                .collect()
+                        })
+                    })
+                    .collect()
+            }
+            pattern => {
+                let head = self.of(pattern).expect("the pattern has a shape");
+                let specialized = self.specialize_all(&rows, &head);
+                let row = self
+                    .specialize(row, &head)
+                    .expect("the pattern has this shape");
+                self.unspecialize(&head, self.useful(&specialized, &row))
+            }
+        }
+    }
+
+    /// Specialize every row that matches `head`
+    fn specialize_all(&self, rows: &[Vec<Pat>], head: &Head) -> Vec<Vec<Pat>> {
+        rows.iter()
+            .filter_map(|row| self.specialize(row, head))
+            .collect()
+    }
+}
+
+/// Expand the or-patterns at the start of rows into a row for each alternative
+fn expand_or(rows: Vec<Vec<Pat>>) -> Vec<Vec<Pat>> {
+    let mut expanded = Vec::new();
+    let mut pending: Vec<_> = rows.into_iter().rev().collect();
+    while let Some(row) = pending.pop() {
+        match row.first() {
+            Some(Pat::Or(alternatives)) => {
+                for alternative in alternatives.iter().rev() {
+                    let mut row = row.clone();
+                    row[0] = alternative.clone();
+                    pending.push(row);
+                }
+            }
+            _ => expanded.push(row),
+        }
+    }
+    expanded
+}
+
+/// Check a match's arms for exhaustiveness and redundancy
+///
+/// Reports the values that no arm matches, as patterns, and the arms that can't be
+/// reached because the arms before them match everything they do. Guarded arms may not
+/// be taken, so they don't cover anything, but can still be unreachable themselves.
+///
+/// Constructors must be defined in `adts`, with a pattern for each field; the arms that
+/// break this are reported, and nothing else is checked.
+///
+/// ```
+/// # use sea_lex::{SourceDb, Span};
+/// # use sea_type::{check_match, AdtDef, Adts, Inference, MatchArm, Pat, Ty, Variant};
+/// # let span = Span::new(SourceDb::new().add("example.ml", ""), 0, 0);
+/// let mut inference = Inference::<&str>::new();
+/// let a = inference.fresh_var();
+/// let mut adts = Adts::new();
+/// adts.define(AdtDef {
+///     name: "option",
+///     params: vec![a],
+///     variants: vec![
+///         Variant { name: "None".to_string(), fields: vec![] },
+///         Variant { name: "Some".to_string(), fields: vec![Ty::Var(a)] },
+///     ],
+/// });
+/// let arm = |pattern| MatchArm { pattern, guarded: false, span };
+/// let some = |pattern| Pat::Constructor("Some".to_string(), vec![pattern]);
+/// let arms = [arm(some(Pat::Var("x".to_string()))), arm(some(Pat::Wildcard))];
+/// let errors = check_match(&adts, &arms, span);
+/// let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+/// assert_eq!(messages, ["This pattern is unreachable", "Pattern `None` isn't covered"]);
+/// ```
+#[must_use]
+pub fn check_match<C: Clone + Eq + Hash>(
+    adts: &Adts<C>,
+    arms: &[MatchArm],
+    span: Span,
+) -> Vec<MatchError> {
+    let mut errors = Vec::new();
+    for arm in arms {
+        validate(adts, &arm.pattern, arm.span, &mut errors);
+    }
+    if !errors.is_empty() {
+        return errors;
+    }
+    let heads = Heads { adts };
+    let mut rows = Vec::new();
+    for arm in arms {
+        let row = vec![arm.pattern.clone()];
+        if heads.useful(&rows, &row).is_empty() {
+            errors.push(MatchError {
+                span: arm.span,
+                kind: MatchErrorKind::Unreachable,
+            });
+        }
+        if !arm.guarded {
+            rows.push(row);
+        }
+    }
+    let missing: Vec<_> = heads
+        .useful(&rows, &[Pat::Wildcard])
+        .into_iter()
+        .map(|mut witness| witness.remove(0))
+        .collect();
+    if !missing.is_empty() {
+        errors.push(MatchError {
+            span,
+            kind: MatchErrorKind::NonExhaustive { missing },
+        });
+    }
+    errors
+}
+
+/// Report the constructors in a pattern that are undefined, or have the wrong number of
+/// fields
+fn validate<C: Clone + Eq + Hash>(
+    adts: &Adts<C>,
+    pattern: &Pat,
+    span: Span,
+    errors: &mut Vec<MatchError>,
+) {
+    match pattern {
+        Pat::Wildcard | Pat::Var(_) | Pat::Literal(_) => {}
+        Pat::Tuple(patterns) | Pat::Or(patterns) => {
+            for pattern in patterns {
+                validate(adts, pattern, span, errors);
+            }
+        }
+        Pat::Constructor(name, fields) => {
+            match adts.constructor(name) {
+                None => errors.push(MatchError {
+                    span,
+                    kind: MatchErrorKind::UnknownConstructor { name: name.clone() },
+                }),
+                Some((def, variant)) if def.variants[variant].fields.len() != fields.len() => {
+                    errors.push(MatchError {
+                        span,
+                        kind: MatchErrorKind::FieldCount {
+                            constructor: name.clone(),
+                            expected: def.variants[variant].fields.len(),
+                            found: fields.len(),
+                        },
+                    });
+                }
+                Some(_) => {}
+            }
+            for field in fields {
+                validate(adts, field, span, errors);
+            }
+        }
+    }
+}
+
+/// An error from [`check_match`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchError {
+    /// The location of the error: the match for missing patterns, and otherwise the arm
+    pub span: Span,
+    /// The kind of error
+    pub kind: MatchErrorKind,
+}
+
+/// The kinds of [`MatchError`]s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchErrorKind {
+    /// No arm matches these values
+    NonExhaustive {
+        /// Patterns for the values that aren't matched
+        missing: Vec<Pat>,
+    },
+    /// The arms before this one match every value it does
+    Unreachable,
+    /// A pattern uses a constructor that isn't defined
+    UnknownConstructor {
+        /// The constructor's name
+        name: String,
+    },
+    /// A constructor pattern has the wrong number of fields
+    FieldCount {
+        /// The constructor's name
+        constructor: String,
+        /// The number of fields the constructor has
+        expected: usize,
+        /// The number of patterns given
+        found: usize,
+    },
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            MatchErrorKind::NonExhaustive { missing } => match missing.as_slice() {
+                [pattern] => write!(f, "Pattern `{pattern}` isn't covered"),
+                patterns => {
+                    f.write_str("Patterns ")?;
+                    for (index, pattern) in patterns.iter().enumerate() {
+                        match index {
+                            0 => {}
+                            _ if index + 1 == patterns.len() => f.write_str(" and ")?,
+                            _ => f.write_str(", ")?,
+                        }
+                        write!(f, "`{pattern}`")?;
+                    }
+                    f.write_str(" aren't covered")
+                }
+            },
+            MatchErrorKind::Unreachable => f.write_str("This pattern is unreachable"),
+            MatchErrorKind::UnknownConstructor { name } => {
+                write!(f, "No constructor named `{name}`")
+            }
+            MatchErrorKind::FieldCount {
+                constructor,
+                expected,
+                found,
+            } => {
+                let plural = if *expected == 1 { "" } else { "s" };
+                write!(
+                    f,
+                    "`{constructor}` has {expected} field{plural}, found {found}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for MatchError {}
+
+/// A path to part of a match's scrutinee: the field or element index at each step, from
+/// the scrutinee down
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Occurrence(pub Vec<usize>);
+
+impl Occurrence {
+    /// Get the path to one of the fields here
+    #[must_use]
+    pub fn field(&self, index: usize) -> Self {
+        let mut path = self.0.clone();
+        path.push(index);
+        Self(path)
+    }
+}
+
+/// Occurrences are written as the scrutinee, `$`, followed by field indices: `$.1.0`
+impl fmt::Display for Occurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("$")?;
+        for index in &self.0 {
+            write!(f, ".{index}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What a [`Decision::Switch`] tests a value for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// A variant, whose fields are then at the value's field occurrences
+    Variant {
+        /// The variant's index in its definition, which codegen may use as its tag
+        index: usize,
+        /// The constructor's name
+        name: String,
+    },
+    /// A constant
+    Literal(Literal),
+}
+
+/// A decision tree, which matches a scrutinee testing each part of it at most once
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// No arm matches
+    Fail,
+    /// Take an arm
+    Leaf {
+        /// The arm's index
+        arm: usize,
+        /// The arm's variables, and the parts of the scrutinee they're bound to
+        bindings: Vec<(String, Occurrence)>,
+    },
+    /// Take an arm if its guard holds, with its variables bound, and otherwise continue
+    Guard {
+        /// The arm's index
+        arm: usize,
+        /// The arm's variables, and the parts of the scrutinee they're bound to
+        bindings: Vec<(String, Occurrence)>,
+        /// What to do if the guard doesn't hold
+        otherwise: Box<Self>,
+    },
+    /// Branch on the shape of part of the scrutinee
+    Switch {
+        /// The part of the scrutinee to test
+        occurrence: Occurrence,
+        /// The shapes to test for, and what to do for each
+        cases: Vec<(Case, Self)>,
+        /// What to do for the other shapes, or `None` if the cases cover them all
+        default: Option<Box<Self>>,
+    },
+}
+
+impl Decision {
+    /// Write a decision, with nested lines indented by `indent` levels
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        let write_arm = |f: &mut fmt::Formatter<'_>, arm, bindings: &[(String, Occurrence)]| {
+            write!(f, "arm {arm}")?;
+            for (index, (name, occurrence)) in bindings.iter().enumerate() {
+                let separator = if index == 0 { " with " } else { ", " };
+                write!(f, "{separator}{name} = {occurrence}")?;
+            }
+            Ok(())
+        };
+        match self {
+            Self::Fail => f.write_str("fail"),
+            Self::Leaf { arm, bindings } => write_arm(f, arm, bindings),
+            Self::Guard {
+                arm,
+                bindings,
+                otherwise,
+            } => {
+                write_arm(f, arm, bindings)?;
+                f.write_str(" if guard, else ")?;
+                otherwise.write(f, indent)
+            }
+            Self::Switch {
+                occurrence,
+                cases,
+                default,
+            } => {
+                writeln!(f, "switch {occurrence} {{")?;
+                let pad = "    ".repeat(indent + 1);
+                for (case, decision) in cases {
+                    match case {
+                        Case::Variant { name, .. } => write!(f, "{pad}{name} => ")?,
+                        Case::Literal(literal) => write!(f, "{pad}{literal} => ")?,
+                    }
+                    decision.write(f, indent + 1)?;
+                    writeln!(f)?;
+                }
+                if let Some(default) = default {
+                    write!(f, "{pad}_ => ")?;
+                    default.write(f, indent + 1)?;
+                    writeln!(f)?;
+                }
+                write!(f, "{}}}", "    ".repeat(indent))
+            }
+        }
+    }
+}
+
+/// Decision trees are written as nested switches:
+///
+/// ```text
+/// switch $ {
+///     None => arm 0
+///     Some => arm 1 with x = $.0
+/// }
+/// ```
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// A row of the pattern matrix being compiled
+#[derive(Debug, Clone)]
+struct Row {
+    /// A pattern for each occurrence being tested
+    patterns: Vec<Pat>,
+    /// The index of the arm the row came from
+    arm: usize,
+    /// Whether the arm has a guard
+    guarded: bool,
+    /// The variables bound so far
+    bindings: Vec<(String, Occurrence)>,
+}
+
+/// Compile a match into a decision tree, for lowering to branches
+///
+/// The tree tests each part of the scrutinee at most once on any path, and takes the
+/// first arm whose pattern matches and whose guard holds. Patterns are compiled column
+/// by column, as in Maranget's "Compiling Pattern Matching to Good Decision Trees",
+/// testing first the part of the scrutinee that the first remaining arm needs.
+/// Tuples aren't tested at all: their elements are tested directly. [`Decision::Fail`]
+/// is only reachable if the match isn't exhaustive.
+///
+/// # Panics
+///
+/// Panics if a pattern has a constructor that isn't defined in `adts`; check the match
+/// with [`check_match`] first.
+#[must_use]
+pub fn compile_match<C: Clone + Eq + Hash>(adts: &Adts<C>, arms: &[MatchArm]) -> Decision {
+    let rows = arms
+        .iter()
+        .enumerate()
+        .map(|(index, arm)| Row {
+            patterns: vec![arm.pattern.clone()],
+            arm: index,
+            guarded: arm.guarded,
+            bindings: Vec::new(),
+        })
+        .collect();
+    compile(&Heads { adts }, vec![Occurrence::default()], rows)
+}
+
+/// Compile a pattern matrix, whose columns test the given occurrences
+fn compile<C: Clone + Eq + Hash>(
+    heads: &Heads<'_, C>,
+    occurrences: Vec<Occurrence>,
+    rows: Vec<Row>,
+) -> Decision {
+    let rows = normalize(&occurrences, rows);
+    let Some(first) = rows.first() else {
+        return Decision::Fail;
+    };
+    let Some(column) = first
+        .patterns
+        .iter()
+        .position(|pattern| !matches!(pattern, Pat::Wildcard))
+    else {
+        let (arm, bindings) = (first.arm, first.bindings.clone());
+        return if first.guarded {
+            Decision::Guard {
+                arm,
+                bindings,
+                otherwise: Box::new(compile(heads, occurrences, rows[1..].to_vec())),
+            }
+        } else {
+            Decision::Leaf { arm, bindings }
+        };
+    };
+
+    // Move the column to the front, so it can be specialized
+    let mut occurrences = occurrences;
+    let occurrence = occurrences.remove(column);
+    let rows: Vec<_> = rows
+        .into_iter()
+        .map(|mut row| {
+            let pattern = row.patterns.remove(column);
+            row.patterns.insert(0, pattern);
+            row
+        })
+        .collect();
+    let specialize = |head: &Head| {
+        let fields = (0..heads.arity(head)).map(|index| occurrence.field(index));
+        let occurrences = fields.chain(occurrences.iter().cloned()).collect();
+        let rows = rows
+            .iter()
+            .filter_map(|row| {
+                let patterns = heads.specialize(&row.patterns, head)?;
+                Some(Row {
+                    patterns,
+                    ..row.clone()
+                })
+            })
+            .collect();
+        compile(heads, occurrences, rows)
+    };
+
+    let column_heads = heads.first_column(rows.iter().map(|row| row.patterns.as_slice()));
+    if let [head @ Head::Tuple(_)] = column_heads.as_slice() {
+        return specialize(head);
+    }
+    let cases = column_heads
+        .iter()
+        .map(|head| {
+            let case = match *head {
+                Head::Variant(def, index) => Case::Variant {
+                    index,
+                    name: heads.adts.def(def).variants[index].name.clone(),
+                },
+                Head::Literal(ref literal) => Case::Literal(literal.clone()),
+                Head::Tuple(_) => unreachable!("tuples are expanded without a switch"),
+            };
+            (case, specialize(head))
+        })
+        .collect();
+    let default = heads.complete(&column_heads).is_none().then(|| {
+        let rows = rows
+            .iter()
+            .filter(|row| matches!(row.patterns[0], Pat::Wildcard))
+            .map(|row| Row {
+                patterns: row.patterns[1..].to_vec(),
+                ..row.clone()
+            })
+            .collect();
+        Box::new(compile(heads, occurrences.clone(), rows))
+    });
+    Decision::Switch {
+        occurrence,
+        cases,
+        default,
+    }
+}
+
+/// Expand the or-patterns in rows into a row for each alternative, and bind the
+/// variables in them, leaving wildcards
+fn normalize(occurrences: &[Occurrence], rows: Vec<Row>) -> Vec<Row> {
+    let mut normalized = Vec::new();
+    let mut pending: Vec<_> = rows.into_iter().rev().collect();
+    while let Some(mut row) = pending.pop() {
+        let or = row
+            .patterns
+            .iter()
+            .position(|pattern| matches!(pattern, Pat::Or(_)));
+        if let Some(column) = or {
+            let Pat::Or(alternatives) = row.patterns[column].clone() else {
+                unreachable!("the pattern is an or-pattern");
+            };
+            for alternative in alternatives.into_iter().rev() {
+                let mut row = row.clone();
+                row.patterns[column] = alternative;
+                pending.push(row);
+            }
+            continue;
+        }
+        for (pattern, occurrence) in row.patterns.iter_mut().zip(occurrences) {
+            if let Pat::Var(name) = pattern {
+                row.bindings
+                    .push((std::mem::take(name), occurrence.clone()));
+                *pattern = Pat::Wildcard;
+            }
+        }
+        normalized.push(row);
+    }
+    normalized
+}
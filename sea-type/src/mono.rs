@@ -0,0 +1,318 @@
+//! Monomorphization: instantiating generic functions at the types they're used at
+
+use crate::{Ty, TyVar};
+use sea_lex::Span;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+/// The index of an instance, from [`Monomorphizer::request`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InstanceId(pub usize);
+
+/// A generic function at concrete type arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instance<K, C> {
+    /// The generic function
+    pub function: K,
+    /// The type arguments, which have no variables
+    pub args: Vec<Ty<C>>,
+    /// The instance whose body requested this one, or `None` for a root
+    pub parent: Option<InstanceId>,
+}
+
+/// A monomorphization driver: instantiates generic functions on demand, once for each
+/// list of type arguments
+///
+/// `K` identifies the frontend's generic functions, and `C` is its type constructors.
+/// The backend requests the roots, like `main`, then takes instances off the queue with
+/// [`next_instance`](Self::next_instance), lowering each function's body with its type
+/// parameters replaced by the instance's arguments. Each call it lowers requests its
+/// callee's instance, which is queued the first time and reused from the cache after
+/// that, so recursion at the same types terminates.
+///
+/// Polymorphic recursion, where a function calls itself at ever larger types, as in
+/// `f : 'a -> unit` calling `f [x]`, would need infinitely many instances, so it's
+/// reported instead once the same growth repeats: `f<int>` needing `f<int list>` is
+/// fine, but `f<int list>` then needing `f<int list list>` isn't.
+///
+/// The driver doesn't know what a lowered function is, so backends can lower instances
+/// to sea-ir functions, or anything else, and index them by [`InstanceId`]:
+///
+/// ```
+/// # use sea_lex::{SourceDb, Span};
+/// # use sea_type::{Monomorphizer, Ty};
+/// # let span = Span::new(SourceDb::new().add("example.ml", ""), 0, 0);
+/// let mut mono = Monomorphizer::<&str, &str>::new();
+/// mono.request("main", vec![], span)?;
+/// let mut lowered = Vec::new();
+/// while let Some(id) = mono.next_instance() {
+///     // `main` calls `id 1` and `id true`, and `id` calls nothing
+///     if mono.instance(id).function == "main" {
+///         mono.request("id", vec![Ty::con("int")], span)?;
+///         mono.request("id", vec![Ty::con("bool")], span)?;
+///         mono.request("id", vec![Ty::con("int")], span)?;
+///     }
+///     lowered.push(mono.symbol(id));
+/// }
+/// assert_eq!(lowered, ["main", "id<int>", "id<bool>"]);
+/// # Ok::<(), sea_type::MonoError<&str, &str>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Monomorphizer<K, C> {
+    /// The instances requested so far, in order
+    instances: Vec<Instance<K, C>>,
+    /// Each instance's index, by its function and type arguments
+    cache: HashMap<(K, Vec<Ty<C>>), InstanceId>,
+    /// The number of instances taken off the queue
+    lowered: usize,
+    /// The instance being lowered, which requests are made from
+    current: Option<InstanceId>,
+}
+
+impl<K: Clone + Eq + Hash, C: Clone + Eq + Hash> Monomorphizer<K, C> {
+    /// Start without any instances
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            instances: Vec::new(),
+            cache: HashMap::new(),
+            lowered: 0,
+            current: None,
+        }
+    }
+
+    /// Get the instance of `function` at `args`, queueing it to be lowered if it's new
+    ///
+    /// Requests made after [`next_instance`](Self::next_instance) returns an instance
+    /// are calls from that instance's body, which polymorphic recursion is checked along.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error at `span` if the arguments have unsolved variables, or if the
+    /// instance would grow its type arguments the same way one of its ancestors did
+    pub fn request(
+        &mut self,
+        function: K,
+        args: Vec<Ty<C>>,
+        span: Span,
+    ) -> Result<InstanceId, MonoError<K, C>> {
+        let key = (function, args);
+        if let Some(&id) = self.cache.get(&key) {
+            return Ok(id);
+        }
+        let (function, args) = key;
+        let error = |kind| MonoError {
+            span,
+            function: function.clone(),
+            args: args.clone(),
+            kind,
+        };
+        if let Some(var) = args.iter().find_map(|arg| arg.vars().first().copied()) {
+            return Err(error(MonoErrorKind::Unresolved(var)));
+        }
+        // The instances of the same function that led to this request, innermost first
+        let mut ancestors = Vec::new();
+        let mut ancestor = self.current;
+        while let Some(id) = ancestor {
+            let instance = &self.instances[id.0];
+            if instance.function == function {
+                ancestors.push(&instance.args);
+            }
+            ancestor = instance.parent;
+        }
+        for (index, &next) in ancestors.iter().enumerate() {
+            if !grows(next, &args) {
+                continue;
+            }
+            if let Some(&from) = ancestors[index + 1..]
+                .iter()
+                .find(|&&from| grows(from, next) && repeats(from, next, &args))
+            {
+                return Err(error(MonoErrorKind::PolymorphicRecursion {
+                    from: from.clone(),
+                    next: next.clone(),
+                }));
+            }
+        }
+        let id = InstanceId(self.instances.len());
+        self.cache.insert((function.clone(), args.clone()), id);
+        self.instances.push(Instance {
+            function,
+            args,
+            parent: self.current,
+        });
+        Ok(id)
+    }
+
+    /// Take the next instance to lower off the queue, in the order they were requested,
+    /// or `None` if every instance has been lowered
+    pub const fn next_instance(&mut self) -> Option<InstanceId> {
+        if self.lowered == self.instances.len() {
+            self.current = None;
+            return None;
+        }
+        let id = InstanceId(self.lowered);
+        self.lowered += 1;
+        self.current = Some(id);
+        Some(id)
+    }
+
+    /// Get an instance
+    #[must_use]
+    pub fn instance(&self, id: InstanceId) -> &Instance<K, C> {
+        &self.instances[id.0]
+    }
+
+    /// Get every instance requested so far, indexed by [`InstanceId`]
+    #[must_use]
+    pub fn instances(&self) -> &[Instance<K, C>] {
+        &self.instances
+    }
+
+    /// Get the substitution for a generic function's parameters in an instance
+    #[must_use]
+    pub fn substitution(&self, id: InstanceId, params: &[TyVar]) -> HashMap<TyVar, Ty<C>> {
+        params
+            .iter()
+            .copied()
+            .zip(self.instances[id.0].args.iter().cloned())
+            .collect()
+    }
+}
+
+impl<K: fmt::Display, C: fmt::Display> Monomorphizer<K, C> {
+    /// Get a unique name for an instance, for the lowered function: the function's name
+    /// followed by its type arguments, like `map<int, bool list>`
+    #[must_use]
+    pub fn symbol(&self, id: InstanceId) -> String {
+        let instance = &self.instances[id.0];
+        let mut symbol = instance.function.to_string();
+        if !instance.args.is_empty() {
+            let args: Vec<_> = instance.args.iter().map(ToString::to_string).collect();
+            symbol = format!("{symbol}<{}>", args.join(", "));
+        }
+        symbol
+    }
+}
+
+impl<K: Clone + Eq + Hash, C: Clone + Eq + Hash> Default for Monomorphizer<K, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reports whether type arguments are larger than earlier ones they contain: each
+/// earlier argument is part of the later one, and they aren't all the same
+fn grows<C: PartialEq>(earlier: &[Ty<C>], later: &[Ty<C>]) -> bool {
+    earlier.len() == later.len()
+        && earlier != later
+        && earlier
+            .iter()
+            .zip(later)
+            .all(|(earlier, later)| contains(later, earlier))
+}
+
+/// Reports whether `later` grows `next` the same way `next` grew `earlier`: replacing
+/// each earlier argument in the next one with the next one gives the later one
+fn repeats<C: Clone + PartialEq>(earlier: &[Ty<C>], next: &[Ty<C>], later: &[Ty<C>]) -> bool {
+    earlier
+        .iter()
+        .zip(next)
+        .zip(later)
+        .all(|((earlier, next), later)| replace(next, earlier, next) == *later)
+}
+
+/// Replace each occurrence of `part` in a term with `with`
+fn replace<C: Clone + PartialEq>(ty: &Ty<C>, part: &Ty<C>, with: &Ty<C>) -> Ty<C> {
+    if ty == part {
+        return with.clone();
+    }
+    match ty {
+        Ty::Var(var) => Ty::Var(*var),
+        Ty::Con(constructor, args) => Ty::Con(
+            constructor.clone(),
+            args.iter().map(|arg| replace(arg, part, with)).collect(),
+        ),
+        Ty::Fun(param, ret) => Ty::fun(replace(param, part, with), replace(ret, part, with)),
+        Ty::Tuple(elements) => Ty::Tuple(
+            elements
+                .iter()
+                .map(|element| replace(element, part, with))
+                .collect(),
+        ),
+    }
+}
+
+/// Reports whether a term is, or contains, another
+fn contains<C: PartialEq>(ty: &Ty<C>, part: &Ty<C>) -> bool {
+    ty == part
+        || match ty {
+            Ty::Var(_) => false,
+            Ty::Con(_, args) | Ty::Tuple(args) => args.iter().any(|arg| contains(arg, part)),
+            Ty::Fun(param, ret) => contains(param, part) || contains(ret, part),
+        }
+}
+
+/// An error from [`Monomorphizer::request`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonoError<K, C> {
+    /// The location of the request
+    pub span: Span,
+    /// The function requested
+    pub function: K,
+    /// The type arguments requested
+    pub args: Vec<Ty<C>>,
+    /// The kind of error
+    pub kind: MonoErrorKind<C>,
+}
+
+/// The kinds of [`MonoError`]s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonoErrorKind<C> {
+    /// A type argument has a variable that inference didn't solve, so the instance to
+    /// use is ambiguous
+    Unresolved(TyVar),
+    /// The function calls itself, through the instances being lowered, at larger types,
+    /// growing them the same way twice
+    PolymorphicRecursion {
+        /// The type arguments of the earliest instance in the growing chain
+        from: Vec<Ty<C>>,
+        /// The type arguments of the instance that grew from `from`, and led to this
+        /// request
+        next: Vec<Ty<C>>,
+    },
+}
+
+impl<K: fmt::Display, C: fmt::Display> fmt::Display for MonoError<K, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = RefCell::default();
+        let list = |args: &[Ty<C>]| {
+            let args: Vec<_> = args
+                .iter()
+                .map(|arg| arg.named(&names).to_string())
+                .collect();
+            args.join(", ")
+        };
+        let function = &self.function;
+        match &self.kind {
+            MonoErrorKind::Unresolved(_) => write!(
+                f,
+                "Can't instantiate `{function}` at `{}`, which isn't fully known",
+                list(&self.args)
+            ),
+            MonoErrorKind::PolymorphicRecursion { from, next } => write!(
+                f,
+                "Instantiating `{function}` at `{}` needs it at `{}`, then at `{}`, and so on \
+                 forever",
+                list(from),
+                list(next),
+                list(&self.args)
+            ),
+        }
+    }
+}
+
+impl<K: fmt::Debug + fmt::Display, C: fmt::Debug + fmt::Display> Error for MonoError<K, C> {}
@@ -0,0 +1,220 @@
+//! The constant-range integer lattice, for constant propagation and peepholes
+
+use std::fmt;
+
+/// A 64-bit integer type narrowed to a range of values, as in Cliff Click's Simple
+///
+/// A range is a pair of bounds ordered independently: lowering `min` or raising `max`
+/// moves the range down the lattice. A range with `min <= max` is low, written
+/// `int[min..max]`: a value somewhere in the range, which is a constant when the bounds
+/// are equal. A range with `min > max` is its high [dual](Self::dual), written
+/// `~int[max..min]`: whichever value in the range is convenient, so it sits above every
+/// constant in it. [`BOTTOM`](Self::BOTTOM), written `int`, is any integer, and
+/// [`TOP`](Self::TOP), written `~int`, is the type of code that's never reached.
+///
+/// ```
+/// # use sea_type::IntRange;
+/// let small = IntRange::new(0, 10);
+/// assert_eq!(small.to_string(), "int[0..10]");
+/// assert_eq!(small.meet(IntRange::constant(20)), IntRange::new(0, 20));
+/// assert_eq!(small.join(IntRange::new(5, 20)), IntRange::new(5, 10));
+/// assert_eq!(small.dual().to_string(), "~int[0..10]");
+/// assert_eq!(IntRange::TOP.meet(IntRange::constant(3)), IntRange::constant(3));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IntRange {
+    /// The lower bound, or the upper bound of a high range
+    min: i64,
+    /// The upper bound, or the lower bound of a high range
+    max: i64,
+}
+
+/// The lower bounds that [`IntRange::widen`] jumps to: the minimums of the integer types
+const WIDEN_MIN: [i64; 5] = [
+    0,
+    i8::MIN as i64,
+    i16::MIN as i64,
+    i32::MIN as i64,
+    i64::MIN,
+];
+
+/// The upper bounds that [`IntRange::widen`] jumps to: the maximums of the integer types
+const WIDEN_MAX: [i64; 8] = [
+    0,
+    i8::MAX as i64,
+    u8::MAX as i64,
+    i16::MAX as i64,
+    u16::MAX as i64,
+    i32::MAX as i64,
+    u32::MAX as i64,
+    i64::MAX,
+];
+
+impl IntRange {
+    /// Any integer: `int`
+    pub const BOTTOM: Self = Self {
+        min: i64::MIN,
+        max: i64::MAX,
+    };
+
+    /// No integer: `~int`
+    pub const TOP: Self = Self::BOTTOM.dual();
+
+    /// A low range, holding some value from `min` to `max` inclusive
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min > max`
+    #[must_use]
+    pub const fn new(min: i64, max: i64) -> Self {
+        assert!(min <= max, "a range's minimum can't exceed its maximum");
+        Self { min, max }
+    }
+
+    /// A constant
+    #[must_use]
+    pub const fn constant(value: i64) -> Self {
+        Self {
+            min: value,
+            max: value,
+        }
+    }
+
+    /// The high range above every constant from `min` to `max` inclusive
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min > max`
+    #[must_use]
+    pub const fn high(min: i64, max: i64) -> Self {
+        Self::new(min, max).dual()
+    }
+
+    /// Get the range's bounds, lowest first, whether it's low or high
+    #[must_use]
+    pub const fn bounds(self) -> (i64, i64) {
+        if self.is_high() {
+            (self.max, self.min)
+        } else {
+            (self.min, self.max)
+        }
+    }
+
+    /// Get the range's value, if it's a constant
+    #[must_use]
+    pub const fn as_constant(self) -> Option<i64> {
+        if self.min == self.max {
+            Some(self.min)
+        } else {
+            None
+        }
+    }
+
+    /// Reports whether the range is high: above the constants in it
+    #[must_use]
+    pub const fn is_high(self) -> bool {
+        self.min > self.max
+    }
+
+    /// Reports whether the range is low and holds `value`
+    #[must_use]
+    pub const fn contains(self, value: i64) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    /// Get the highest range below both: the smallest low range holding every value
+    /// either might be
+    #[must_use]
+    pub fn meet(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Get the lowest range above both
+    ///
+    /// This is the dual of the meet of the duals, so two low ranges join at their
+    /// intersection, or at a high range if they're disjoint.
+    #[must_use]
+    pub fn join(self, other: Self) -> Self {
+        Self {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        }
+    }
+
+    /// Get the range mirrored across the constants: low ranges become high and high
+    /// ranges become low, while constants stay the same
+    #[must_use]
+    pub const fn dual(self) -> Self {
+        Self {
+            min: self.max,
+            max: self.min,
+        }
+    }
+
+    /// Reports whether `self` is at or above `other`
+    #[must_use]
+    pub fn isa(self, other: Self) -> bool {
+        self.meet(other) == other
+    }
+
+    /// Meet `self`, a previous approximation, with `next`, jumping any bound of a low
+    /// approximation that moved down the lattice to the next integer type's bound
+    ///
+    /// The result is at or below the meet. A high approximation, like [`TOP`](Self::TOP)
+    /// on the first visit, just takes the meet, so constants aren't lost. Once it's low,
+    /// a bound can only jump a few times, so repeatedly widening an approximation with
+    /// the ranges a loop computes reaches a fixed point within a few iterations.
+    ///
+    /// ```
+    /// # use sea_type::IntRange;
+    /// let start = IntRange::TOP.widen(IntRange::constant(0));
+    /// assert_eq!(start, IntRange::constant(0));
+    /// let counter = IntRange::new(0, 1).widen(IntRange::new(0, 2));
+    /// assert_eq!(counter, IntRange::new(0, 127));
+    /// assert_eq!(counter.widen(IntRange::new(0, 128)), IntRange::new(0, 255));
+    /// ```
+    #[must_use]
+    pub fn widen(self, next: Self) -> Self {
+        let meet = self.meet(next);
+        if self.is_high() {
+            return meet;
+        }
+        let min = if meet.min < self.min {
+            WIDEN_MIN
+                .into_iter()
+                .find(|&bound| bound <= meet.min)
+                .unwrap_or(i64::MIN)
+        } else {
+            meet.min
+        };
+        let max = if meet.max > self.max {
+            WIDEN_MAX
+                .into_iter()
+                .find(|&bound| bound >= meet.max)
+                .unwrap_or(i64::MAX)
+        } else {
+            meet.max
+        };
+        Self { min, max }
+    }
+}
+
+impl fmt::Display for IntRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(value) = self.as_constant() {
+            return write!(f, "{value}");
+        }
+        if self.is_high() {
+            f.write_str("~")?;
+        }
+        let (min, max) = self.bounds();
+        if (min, max) == (i64::MIN, i64::MAX) {
+            f.write_str("int")
+        } else {
+            write!(f, "int[{min}..{max}]")
+        }
+    }
+}
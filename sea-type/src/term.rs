@@ -0,0 +1,228 @@
+//! Type terms for Hindley–Milner inference
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::BuildHasher;
+
+/// A type variable, created by an [`Inference`](crate::Inference)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TyVar(pub(crate) u32);
+
+/// A type term: a variable, or a constructor applied to other terms
+///
+/// `C` is the language's type constructors, like `int` and `list` for ML. Functions and
+/// tuples are built in, since every language with inference has them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Ty<C> {
+    /// A type variable, which unification may solve
+    Var(TyVar),
+    /// A type constructor applied to its arguments: `int`, or `'a list`
+    Con(C, Vec<Self>),
+    /// A function type: `a -> b`
+    Fun(Box<Self>, Box<Self>),
+    /// A tuple type: `a * b`
+    Tuple(Vec<Self>),
+}
+
+impl<C> Ty<C> {
+    /// A constructor without arguments
+    #[must_use]
+    pub const fn con(constructor: C) -> Self {
+        Self::Con(constructor, Vec::new())
+    }
+
+    /// A function type
+    #[must_use]
+    pub fn fun(param: Self, ret: Self) -> Self {
+        Self::Fun(Box::new(param), Box::new(ret))
+    }
+
+    /// Get the variables in the term, in order of first appearance
+    #[must_use]
+    pub fn vars(&self) -> Vec<TyVar> {
+        let mut vars = Vec::new();
+        self.collect_vars(&mut vars);
+        vars
+    }
+
+    /// Add the variables in the term to `vars`, if they aren't there already
+    fn collect_vars(&self, vars: &mut Vec<TyVar>) {
+        match self {
+            Self::Var(var) => {
+                if !vars.contains(var) {
+                    vars.push(*var);
+                }
+            }
+            Self::Con(_, args) | Self::Tuple(args) => {
+                for arg in args {
+                    arg.collect_vars(vars);
+                }
+            }
+            Self::Fun(param, ret) => {
+                param.collect_vars(vars);
+                ret.collect_vars(vars);
+            }
+        }
+    }
+}
+
+impl<C: Clone> Ty<C> {
+    /// Replace the variables in the term that `substitution` maps
+    #[must_use]
+    pub fn substitute<S: BuildHasher>(&self, substitution: &HashMap<TyVar, Self, S>) -> Self {
+        match self {
+            Self::Var(var) => substitution.get(var).cloned().unwrap_or(Self::Var(*var)),
+            Self::Con(constructor, args) => Self::Con(
+                constructor.clone(),
+                args.iter()
+                    .map(|arg| arg.substitute(substitution))
+                    .collect(),
+            ),
+            Self::Fun(param, ret) => {
+                Self::fun(param.substitute(substitution), ret.substitute(substitution))
+            }
+            Self::Tuple(elements) => Self::Tuple(
+                elements
+                    .iter()
+                    .map(|element| element.substitute(substitution))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A polymorphic type: a term that holds for any types substituted for its variables
+///
+/// Schemes are displayed as their terms, with the variables implicitly quantified as in
+/// ML: `'a -> 'a`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Scheme<C> {
+    /// The quantified variables
+    pub vars: Vec<TyVar>,
+    /// The term
+    pub ty: Ty<C>,
+}
+
+impl<C> Scheme<C> {
+    /// A scheme without quantified variables, for a type that isn't polymorphic
+    #[must_use]
+    pub const fn mono(ty: Ty<C>) -> Self {
+        Self {
+            vars: Vec::new(),
+            ty,
+        }
+    }
+}
+
+impl<C: fmt::Display> Ty<C> {
+    /// Get a displayable form of the term whose variables are named by `names`, so
+    /// that the terms in one message share names
+    pub(crate) const fn named<'a>(
+        &'a self,
+        names: &'a RefCell<Vec<TyVar>>,
+    ) -> impl fmt::Display + 'a {
+        Named { ty: self, names }
+    }
+}
+
+/// Get a variable's name, `'a` to `'z` and then `'a1` and so on, in the order the
+/// variables were named
+fn var_name(names: &mut Vec<TyVar>, var: TyVar) -> String {
+    let index = names
+        .iter()
+        .position(|&named| named == var)
+        .unwrap_or_else(|| {
+            names.push(var);
+            names.len() - 1
+        });
+    let letter = char::from(b'a' + u8::try_from(index % 26).unwrap_or_default());
+    match index / 26 {
+        0 => format!("'{letter}"),
+        round => format!("'{letter}{round}"),
+    }
+}
+
+/// A term to display with shared variable names, from [`Ty::named`]
+struct Named<'a, C> {
+    /// The term
+    ty: &'a Ty<C>,
+    /// The variables named so far
+    names: &'a RefCell<Vec<TyVar>>,
+}
+
+impl<C: fmt::Display> Named<'_, C> {
+    /// Write a term, parenthesized if it binds looser than `precedence`
+    ///
+    /// Function types have precedence 0, tuples 1, and everything else 2.
+    fn write(&self, f: &mut fmt::Formatter<'_>, ty: &Ty<C>, precedence: u8) -> fmt::Result {
+        let own = match ty {
+            Ty::Fun(..) => 0,
+            Ty::Tuple(_) => 1,
+            Ty::Var(_) | Ty::Con(..) => 2,
+        };
+        if own < precedence {
+            f.write_str("(")?;
+        }
+        match ty {
+            Ty::Var(var) => f.write_str(&var_name(&mut self.names.borrow_mut(), *var))?,
+            Ty::Con(constructor, args) => {
+                match args.as_slice() {
+                    [] => {}
+                    [arg] => {
+                        self.write(f, arg, 2)?;
+                        f.write_str(" ")?;
+                    }
+                    args => {
+                        f.write_str("(")?;
+                        for (index, arg) in args.iter().enumerate() {
+                            if index > 0 {
+                                f.write_str(", ")?;
+                            }
+                            self.write(f, arg, 0)?;
+                        }
+                        f.write_str(") ")?;
+                    }
+                }
+                write!(f, "{constructor}")?;
+            }
+            Ty::Fun(param, ret) => {
+                self.write(f, param, 1)?;
+                f.write_str(" -> ")?;
+                self.write(f, ret, 0)?;
+            }
+            Ty::Tuple(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" * ")?;
+                    }
+                    self.write(f, element, 2)?;
+                }
+            }
+        }
+        if own < precedence {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: fmt::Display> fmt::Display for Named<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, self.ty, 0)
+    }
+}
+
+/// Terms are written as in ML, with variables named in order of appearance:
+/// `('a -> 'b) -> 'a list -> 'b list`
+impl<C: fmt::Display> fmt::Display for Ty<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.named(&RefCell::default()).fmt(f)
+    }
+}
+
+impl<C: fmt::Display> fmt::Display for Scheme<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.ty.fmt(f)
+    }
+}
@@ -0,0 +1,103 @@
+//! The types of sea-of-nodes values
+
+/// A type interned in a [`TypeInterner`](crate::TypeInterner)
+///
+/// Interned types are hash-consed, so two ids from the same interner are equal exactly
+/// when their types are. The types every interner starts with have constant ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TypeId(pub(crate) u32);
+
+impl TypeId {
+    /// [`Type::Top`]
+    pub const TOP: Self = Self(0);
+    /// [`Type::Bottom`]
+    pub const BOTTOM: Self = Self(1);
+    /// [`Type::Control`]
+    pub const CONTROL: Self = Self(2);
+    /// [`Type::XControl`]
+    pub const XCONTROL: Self = Self(3);
+    /// [`Type::Memory`]
+    pub const MEMORY: Self = Self(4);
+    /// [`Type::XMemory`]
+    pub const XMEMORY: Self = Self(5);
+    /// [`Type::Null`]
+    pub const NULL: Self = Self(6);
+
+    /// The types with constant ids, in id order
+    pub(crate) const CONSTANTS: [Type; 7] = [
+        Type::Top,
+        Type::Bottom,
+        Type::Control,
+        Type::XControl,
+        Type::Memory,
+        Type::XMemory,
+        Type::Null,
+    ];
+}
+
+/// A type in the lattice
+///
+/// Types are ordered from [`Top`](Self::Top), which no value has, down to
+/// [`Bottom`](Self::Bottom), which every value has. Where a node's type is one of a few
+/// possibilities, its type is their [`meet`](crate::TypeInterner::meet): the highest
+/// type below them all.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    /// No value: the type of code that's never reached
+    Top,
+    /// Any value
+    Bottom,
+    /// Live control flow
+    Control,
+    /// Dead control flow, above [`Control`](Self::Control)
+    XControl,
+    /// The state of memory
+    Memory,
+    /// Unreachable memory state, above [`Memory`](Self::Memory)
+    XMemory,
+    /// An integer of `bits` bits, from 1 to 128
+    Int {
+        /// The number of bits
+        bits: u8,
+        /// Whether the integer is two's complement signed
+        signed: bool,
+    },
+    /// A floating-point number
+    Float(FloatKind),
+    /// The null pointer
+    Null,
+    /// A pointer to a value of type `pointee`
+    Pointer {
+        /// The type of the value pointed to
+        pointee: TypeId,
+        /// Whether the pointer may be null
+        nullable: bool,
+    },
+    /// A fixed-size array of `length` elements, or of an unknown length
+    Array {
+        /// The type of the elements
+        element: TypeId,
+        /// The number of elements, if known
+        length: Option<u64>,
+    },
+    /// A tuple of values, as from a node with several results
+    Tuple(Vec<TypeId>),
+    /// A struct, identified by its name, whose fields are defined in the interner
+    Struct(String),
+    /// A function
+    Function {
+        /// The parameter types, in order
+        params: Vec<TypeId>,
+        /// The return type
+        ret: TypeId,
+    },
+}
+
+/// The floating-point formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FloatKind {
+    /// IEEE 754 single precision
+    F32,
+    /// IEEE 754 double precision
+    F64,
+}
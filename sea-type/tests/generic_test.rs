@@ -0,0 +1,256 @@
+use sea_lex::{SourceDb, Span};
+use sea_type::{
+    Bound, BoundErrorKind, Generic, Impl, Inference, InstanceId, MonoErrorKind, Monomorphizer,
+    Traits, Ty,
+};
+
+/// A span for tests that don't look at spans
+fn span() -> Span {
+    Span::new(SourceDb::new().add("test.ml", ""), 0, 0)
+}
+
+/// The type of a list of `element`
+fn list(element: Ty<&'static str>) -> Ty<&'static str> {
+    Ty::Con("list", vec![element])
+}
+
+/// Define `Eq`, `Ord: Eq`, and `Show`, with `Show` and `Eq` for `int` and lists, and
+/// `Ord` for `int`
+fn prelude(inference: &mut Inference<&'static str>) -> Traits<&'static str> {
+    let mut traits = Traits::new();
+    traits.define_trait("Eq", &[]);
+    traits.define_trait("Ord", &["Eq"]);
+    traits.define_trait("Show", &[]);
+    for trait_name in ["Eq", "Ord", "Show"] {
+        let int = Impl {
+            trait_name: trait_name.to_string(),
+            params: vec![],
+            bounds: vec![],
+            ty: Ty::con("int"),
+            span: span(),
+        };
+        traits.add_impl(int).expect("the impls don't overlap");
+    }
+    for trait_name in ["Eq", "Show"] {
+        let a = inference.fresh_var();
+        let lists = Impl {
+            trait_name: trait_name.to_string(),
+            params: vec![a],
+            bounds: vec![Bound::new(trait_name, Ty::Var(a))],
+            ty: list(Ty::Var(a)),
+            span: span(),
+        };
+        traits.add_impl(lists).expect("the impls don't overlap");
+    }
+    traits
+}
+
+#[test]
+fn test_bounds() {
+    let mut inference = Inference::new();
+    let traits = prelude(&mut inference);
+    let solve = |trait_name, ty, assumptions: &[Bound<&'static str>]| {
+        traits
+            .solve(&Bound::new(trait_name, ty), assumptions, span())
+            .map_err(|error| (error.kind, error.to_string()))
+    };
+    assert_eq!(solve("Show", list(list(Ty::con("int"))), &[]), Ok(()));
+    assert_eq!(
+        solve("Show", list(Ty::con("bool")), &[]),
+        Err((
+            BoundErrorKind::Unsatisfied,
+            "`bool` doesn't implement `Show`".to_string()
+        ))
+    );
+    assert_eq!(
+        solve("Ord", list(Ty::con("int")), &[]),
+        Err((
+            BoundErrorKind::Unsatisfied,
+            "`int list` doesn't implement `Ord`".to_string()
+        ))
+    );
+    assert_eq!(
+        solve("Hash", Ty::con("int"), &[]),
+        Err((
+            BoundErrorKind::UnknownTrait,
+            "No trait named `Hash`".to_string()
+        ))
+    );
+
+    // Inside `fun (x : 'a) -> ...` where `'a: Ord`, `'a list: Eq` holds through `Ord`'s
+    // supertrait, but `'a: Show` doesn't
+    let a = inference.fresh();
+    let assumptions = [Bound::new("Ord", a.clone())];
+    assert_eq!(solve("Eq", list(a.clone()), &assumptions), Ok(()));
+    assert_eq!(
+        solve("Show", a.clone(), &assumptions),
+        Err((
+            BoundErrorKind::Unsatisfied,
+            "`'a` doesn't implement `Show`".to_string()
+        ))
+    );
+
+    let (id, args) = traits
+        .select(&Bound::new("Show", list(Ty::con("int"))))
+        .expect("an impl covers `int list`");
+    assert_eq!(args, [Ty::con("int")]);
+    assert_eq!(traits.get(id).ty.to_string(), "'a list");
+}
+
+#[test]
+fn test_impls_and_generics() {
+    let mut inference = Inference::new();
+    let mut traits = prelude(&mut inference);
+    let b = inference.fresh_var();
+    let error = traits
+        .add_impl(Impl {
+            trait_name: "Show".to_string(),
+            params: vec![b],
+            bounds: vec![],
+            ty: list(Ty::Tuple(vec![Ty::Var(b), Ty::Var(b)])),
+            span: span(),
+        })
+        .expect_err("`'a list` covers pairs");
+    assert_eq!(
+        error.to_string(),
+        "Conflicting implementations of `Show` for `('a * 'a) list`"
+    );
+
+    // `show : 'a -> string where 'a: Show`, applied to a list of ints
+    let a = inference.fresh_var();
+    let show = Generic {
+        params: vec![a],
+        bounds: vec![Bound::new("Show", Ty::Var(a))],
+        ty: Ty::fun(Ty::Var(a), Ty::con("string")),
+    };
+    let use_site = show.instantiate(&mut inference);
+    let result = inference.fresh();
+    let applied = Ty::fun(list(Ty::con("int")), result.clone());
+    inference
+        .unify(&use_site.ty, &applied, span())
+        .expect("`show` takes anything");
+    assert_eq!(inference.resolve(&result), Ty::con("string"));
+    let args: Vec<_> = use_site
+        .args
+        .iter()
+        .map(|arg| inference.resolve(arg))
+        .collect();
+    assert_eq!(args, [list(Ty::con("int"))]);
+    for bound in &use_site.bounds {
+        let bound = Bound::new(bound.trait_name.clone(), inference.resolve(&bound.ty));
+        assert_eq!(traits.solve(&bound, &[], span()), Ok(()));
+    }
+}
+
+/// The calls in each function's body, at its type parameter `'a`: each call is a
+/// function and a type argument over `'a`
+type Program = Vec<(&'static str, Vec<(&'static str, Ty<&'static str>)>)>;
+
+/// Monomorphize a program from `main`, getting each instance's symbol and its callees,
+/// or the first error
+fn monomorphize(program: &Program) -> Result<Vec<(String, Vec<InstanceId>)>, String> {
+    let mut inference = Inference::<&str>::new();
+    let a = inference.fresh_var();
+    let mut mono = Monomorphizer::new();
+    mono.request("main", vec![Ty::con("unit")], span())
+        .map_err(|error| error.to_string())?;
+    let mut lowered = Vec::new();
+    while let Some(id) = mono.next_instance() {
+        let function = mono.instance(id).function;
+        let substitution = mono.substitution(id, &[a]);
+        let calls = &program
+            .iter()
+            .find(|(name, _)| *name == function)
+            .expect("the function is defined")
+            .1;
+        let mut callees = Vec::new();
+        for (callee, arg) in calls {
+            let arg = arg.substitute(&substitution);
+            let callee = mono
+                .request(*callee, vec![arg], span())
+                .map_err(|error| error.to_string())?;
+            callees.push(callee);
+        }
+        lowered.push((mono.symbol(id), callees));
+    }
+    Ok(lowered)
+}
+
+#[test]
+fn test_monomorphization() {
+    // Every function's parameter is the first variable created, as in `monomorphize`
+    let a = Ty::Var(Inference::<&str>::new().fresh_var());
+    let int = Ty::con("int");
+    let program: Program = vec![
+        (
+            "main",
+            vec![
+                ("length", int.clone()),
+                ("length", Ty::con("bool")),
+                ("even", int.clone()),
+            ],
+        ),
+        // Recursion at the same type reuses the instance
+        ("length", vec![("length", a.clone())]),
+        // So does mutual recursion
+        ("even", vec![("odd", a.clone())]),
+        (
+            "odd",
+            vec![("even", a.clone()), ("length", list(a.clone()))],
+        ),
+    ];
+    let lowered = monomorphize(&program).expect("the program monomorphizes");
+    let symbols: Vec<_> = lowered.iter().map(|(symbol, _)| symbol.as_str()).collect();
+    assert_eq!(
+        symbols,
+        [
+            "main<unit>",
+            "length<int>",
+            "length<bool>",
+            "even<int>",
+            "odd<int>",
+            "length<int list>",
+        ]
+    );
+    assert_eq!(lowered[1].1, [InstanceId(1)]);
+    assert_eq!(lowered[4].1, [InstanceId(3), InstanceId(5)]);
+
+    // `nested` calls itself on a list of its argument, so it would need instances at
+    // `int`, `int list`, `int list list`, and so on
+    let program: Program = vec![
+        ("main", vec![("nested", int.clone())]),
+        ("nested", vec![("wrap", a.clone())]),
+        ("wrap", vec![("nested", list(a.clone()))]),
+    ];
+    assert_eq!(
+        monomorphize(&program),
+        Err(
+            "Instantiating `nested` at `int` needs it at `int list`, then at `int list list`, \
+             and so on forever"
+                .to_string()
+        )
+    );
+
+    // `concrete` calls itself at `int list` whatever its argument, which grows once and
+    // then reuses the instance
+    let program: Program = vec![
+        ("main", vec![("concrete", int.clone())]),
+        ("concrete", vec![("concrete", list(int.clone()))]),
+    ];
+    let lowered = monomorphize(&program).expect("the program monomorphizes");
+    let symbols: Vec<_> = lowered.iter().map(|(symbol, _)| symbol.as_str()).collect();
+    assert_eq!(
+        symbols,
+        ["main<unit>", "concrete<int>", "concrete<int list>"]
+    );
+
+    let mut mono = Monomorphizer::<&str, &str>::new();
+    let error = mono
+        .request("length", vec![list(a)], span())
+        .expect_err("`'a` isn't known");
+    assert!(matches!(error.kind, MonoErrorKind::Unresolved(_)));
+    assert_eq!(
+        error.to_string(),
+        "Can't instantiate `length` at `'a list`, which isn't fully known"
+    );
+}
@@ -0,0 +1,199 @@
+use sea_lex::{SourceDb, Span};
+use sea_type::{Inference, Scheme, Ty, UnifyError, UnifyErrorKind};
+use std::collections::HashMap;
+
+/// A tiny ML, to drive inference the way a frontend would
+enum Expr {
+    /// An integer literal
+    Int,
+    /// A boolean literal
+    Bool,
+    /// The empty list
+    Nil,
+    /// A variable
+    Var(&'static str),
+    /// `fun x -> e`
+    Lambda(&'static str, Box<Expr>),
+    /// `f x`, at a span
+    App(Box<Expr>, Box<Expr>, Span),
+    /// `let x = e in e`
+    Let(&'static str, Box<Expr>, Box<Expr>),
+    /// `(a, b)`
+    Pair(Box<Expr>, Box<Expr>),
+}
+
+use Expr::{App, Bool, Int, Lambda, Let, Nil, Pair, Var};
+
+/// The type of a list of `element`
+fn list(element: Ty<&'static str>) -> Ty<&'static str> {
+    Ty::Con("list", vec![element])
+}
+
+/// Infer the type of `expr` in `env`
+fn infer(
+    inference: &mut Inference<&'static str>,
+    env: &mut HashMap<&'static str, Scheme<&'static str>>,
+    expr: &Expr,
+) -> Result<Ty<&'static str>, UnifyError<&'static str>> {
+    Ok(match expr {
+        Int => Ty::con("int"),
+        Bool => Ty::con("bool"),
+        Nil => list(inference.fresh()),
+        Var(name) => inference.instantiate(&env[name]),
+        Lambda(param, body) => {
+            let param_ty = inference.fresh();
+            let shadowed = env.insert(param, Scheme::mono(param_ty.clone()));
+            let body = infer(inference, env, body);
+            match shadowed {
+                Some(scheme) => env.insert(param, scheme),
+                None => env.remove(param),
+            };
+            Ty::fun(param_ty, body?)
+        }
+        App(function, argument, span) => {
+            let function = infer(inference, env, function)?;
+            let argument = infer(inference, env, argument)?;
+            let result = inference.fresh();
+            inference.unify(&function, &Ty::fun(argument, result.clone()), *span)?;
+            result
+        }
+        Let(name, value, body) => {
+            inference.enter_level();
+            let value = infer(inference, env, value);
+            inference.exit_level();
+            let scheme = inference.generalize(&value?);
+            let shadowed = env.insert(name, scheme);
+            let body = infer(inference, env, body);
+            match shadowed {
+                Some(scheme) => env.insert(name, scheme),
+                None => env.remove(name),
+            };
+            body?
+        }
+        Pair(a, b) => Ty::Tuple(vec![infer(inference, env, a)?, infer(inference, env, b)?]),
+    })
+}
+
+/// An environment with `cons : 'a -> 'a list -> 'a list`
+fn prelude(inference: &mut Inference<&'static str>) -> HashMap<&'static str, Scheme<&'static str>> {
+    inference.enter_level();
+    let element = inference.fresh();
+    let cons = Ty::fun(
+        element.clone(),
+        Ty::fun(list(element.clone()), list(element)),
+    );
+    inference.exit_level();
+    HashMap::from([("cons", inference.generalize(&cons))])
+}
+
+/// Infer the type of `expr`, with its variables resolved
+fn type_of(expr: &Expr) -> Result<String, UnifyError<&'static str>> {
+    let mut inference = Inference::new();
+    let mut env = prelude(&mut inference);
+    let ty = infer(&mut inference, &mut env, expr)?;
+    Ok(inference.resolve(&ty).to_string())
+}
+
+/// A span for the `index`th application in a test
+fn span(index: usize) -> Span {
+    let file = SourceDb::new().add("test.ml", "");
+    Span::new(file, index, index + 1)
+}
+
+/// `f x`
+fn app(f: Expr, x: Expr, index: usize) -> Expr {
+    App(Box::new(f), Box::new(x), span(index))
+}
+
+/// `fun x -> body`
+fn lambda(x: &'static str, body: Expr) -> Expr {
+    Lambda(x, Box::new(body))
+}
+
+/// `let x = value in body`
+fn let_in(x: &'static str, value: Expr, body: Expr) -> Expr {
+    Let(x, Box::new(value), Box::new(body))
+}
+
+#[test]
+fn test_let_polymorphism() {
+    // let id = fun x -> x in (id 1, id true)
+    let expr = let_in(
+        "id",
+        lambda("x", Var("x")),
+        Pair(
+            Box::new(app(Var("id"), Int, 0)),
+            Box::new(app(Var("id"), Bool, 1)),
+        ),
+    );
+    assert_eq!(type_of(&expr).unwrap(), "int * bool");
+
+    // fun f -> fun x -> f (f x)
+    let twice = lambda(
+        "f",
+        lambda("x", app(Var("f"), app(Var("f"), Var("x"), 0), 1)),
+    );
+    assert_eq!(type_of(&twice).unwrap(), "('a -> 'a) -> 'a -> 'a");
+
+    // fun x -> cons x nil
+    let singleton = lambda("x", app(app(Var("cons"), Var("x"), 0), Nil, 1));
+    assert_eq!(type_of(&singleton).unwrap(), "'a -> 'a list");
+}
+
+#[test]
+fn test_lambda_bound_variables_stay_monomorphic() {
+    // fun x -> let y = x in y
+    let expr = lambda("x", let_in("y", Var("x"), Var("y")));
+    assert_eq!(type_of(&expr).unwrap(), "'a -> 'a");
+
+    // fun f -> (f 1, f true)
+    let expr = lambda(
+        "f",
+        Pair(
+            Box::new(app(Var("f"), Int, 0)),
+            Box::new(app(Var("f"), Bool, 1)),
+        ),
+    );
+    let error = type_of(&expr).unwrap_err();
+    assert_eq!(error.span, span(1));
+    assert_eq!(
+        error.to_string(),
+        "Expected `int -> 'a`, found `bool -> 'b` (`bool` isn't `int`)"
+    );
+    assert_eq!(
+        *error.kind,
+        UnifyErrorKind::Mismatch {
+            expected: Ty::con("int"),
+            found: Ty::con("bool"),
+        }
+    );
+}
+
+#[test]
+fn test_unification_errors() {
+    // cons 1 (cons true nil)
+    let expr = app(
+        app(Var("cons"), Int, 0),
+        app(app(Var("cons"), Bool, 1), Nil, 2),
+        3,
+    );
+    let error = type_of(&expr).unwrap_err();
+    assert_eq!(error.span, span(3));
+    assert_eq!(
+        error.to_string(),
+        "Expected `int list -> int list`, found `bool list -> 'a` (`bool` isn't `int`)"
+    );
+
+    // fun x -> x x
+    let expr = lambda("x", app(Var("x"), Var("x"), 0));
+    let error = type_of(&expr).unwrap_err();
+    assert!(matches!(*error.kind, UnifyErrorKind::Occurs { .. }));
+    assert_eq!(
+        error.to_string(),
+        "Expected `'a`, found `'a -> 'b`, which would make `'a` the infinite type `'a -> 'b`"
+    );
+
+    // 1 1
+    let error = type_of(&app(Int, Int, 0)).unwrap_err();
+    assert_eq!(error.to_string(), "Expected `int`, found `int -> 'a`");
+}
@@ -0,0 +1,143 @@
+use sea_type::{FloatKind, Type, TypeId, TypeInterner};
+
+/// An interner with a sample of types of every kind
+fn sample() -> (TypeInterner, Vec<TypeId>) {
+    let mut types = TypeInterner::new();
+    let mut sample = vec![
+        TypeId::TOP,
+        TypeId::BOTTOM,
+        TypeId::CONTROL,
+        TypeId::XCONTROL,
+        TypeId::MEMORY,
+        TypeId::XMEMORY,
+        TypeId::NULL,
+    ];
+    for bits in [1, 7, 8, 9, 32, 63, 64, 128] {
+        sample.push(types.int(bits, true));
+        sample.push(types.int(bits, false));
+    }
+    let (i8, u8) = (types.int(8, true), types.int(8, false));
+    let (f32, f64) = (types.float(FloatKind::F32), types.float(FloatKind::F64));
+    let node = types.struct_type("node");
+    let node_pointer = types.pointer(node, true);
+    types.define_struct("node", [("next".to_string(), node_pointer)]);
+    let list = types.struct_type("list");
+    let i8_pointer = types.pointer(i8, false);
+    sample.extend([
+        f32,
+        f64,
+        node,
+        list,
+        node_pointer,
+        i8_pointer,
+        types.pointer(i8, true),
+        types.pointer(u8, false),
+        types.pointer(i8_pointer, false),
+        types.tuple([i8, f32]),
+        types.tuple([u8, f64]),
+        types.tuple([i8]),
+        types.array(i8, Some(4)),
+        types.array(u8, Some(4)),
+        types.array(i8, None),
+        types.function([i8], u8),
+        types.function([u8], i8),
+        types.function([i8, f64], TypeId::CONTROL),
+    ]);
+    (types, sample)
+}
+
+#[test]
+fn test_interning_shares_ids() {
+    let mut types = TypeInterner::new();
+    assert_eq!(types.get(TypeId::TOP), &Type::Top);
+    assert_eq!(types.intern(Type::Null), TypeId::NULL);
+    let int = types.int(32, true);
+    let count = types.len();
+    let pair = types.tuple([int, int]);
+    assert_eq!(types.intern(Type::Tuple(vec![int, int])), pair);
+    assert_eq!(types.len(), count + 1);
+    assert_ne!(types.int(32, false), int);
+
+    let node = types.struct_type("node");
+    let next = types.pointer(node, true);
+    types.define_struct("node", [("next".to_string(), next)]);
+    assert_eq!(types.fields(node), Some(&[("next".to_string(), next)][..]));
+    assert_eq!(types.display(next).to_string(), "*struct node?");
+    let f64 = types.float(FloatKind::F64);
+    let function = types.function([pair, f64], TypeId::MEMORY);
+    assert_eq!(
+        types.display(function).to_string(),
+        "fn((i32, i32), f64) -> mem"
+    );
+}
+
+#[test]
+fn test_meets_and_joins() {
+    let mut types = TypeInterner::new();
+    let (i8, u8) = (types.int(8, true), types.int(8, false));
+    let (i9, u7, u128) = (
+        types.int(9, true),
+        types.int(7, false),
+        types.int(128, false),
+    );
+    assert_eq!(types.meet(i8, u8), i9);
+    assert_eq!(types.join(i8, u8), u7);
+    assert_eq!(types.meet(u128, i8), TypeId::BOTTOM);
+    assert_eq!(
+        types.meet(TypeId::CONTROL, TypeId::XCONTROL),
+        TypeId::CONTROL
+    );
+    assert_eq!(types.join(TypeId::MEMORY, TypeId::XMEMORY), TypeId::XMEMORY);
+    assert_eq!(types.meet(TypeId::CONTROL, TypeId::MEMORY), TypeId::BOTTOM);
+    assert!(types.isa(u7, i8));
+    assert!(!types.isa(i8, u8));
+
+    let pointer = types.pointer(i8, false);
+    let nullable = types.pointer(i9, true);
+    let other = types.pointer(u8, false);
+    assert_eq!(types.meet(pointer, TypeId::NULL), types.pointer(i8, true));
+    assert_eq!(types.meet(pointer, other), types.pointer(i9, false));
+    assert_eq!(types.join(nullable, TypeId::NULL), TypeId::NULL);
+    assert_eq!(types.join(pointer, TypeId::NULL), TypeId::TOP);
+    let (a, b) = (types.array(i8, Some(4)), types.array(u8, Some(4)));
+    let expected = types.array(i9, Some(4));
+    assert_eq!(types.meet(a, b), expected);
+    let unsized_array = types.array(i8, None);
+    assert_eq!(types.meet(a, unsized_array), TypeId::BOTTOM);
+    assert_eq!(types.display(expected).to_string(), "[i9; 4]");
+
+    // Functions taking either argument type can stand in for both
+    let (f, g) = (types.function([i8], u8), types.function([u8], u8));
+    let expected = types.function([u7], u8);
+    assert_eq!(types.meet(f, g), expected);
+    let expected = types.function([i9], u8);
+    assert_eq!(types.join(f, g), expected);
+}
+
+#[test]
+fn test_lattice_laws_hold() {
+    let (mut types, sample) = sample();
+    for &a in &sample {
+        assert_eq!(types.meet(a, TypeId::TOP), a);
+        assert_eq!(types.join(a, TypeId::BOTTOM), a);
+        assert_eq!(types.meet(a, TypeId::BOTTOM), TypeId::BOTTOM);
+        assert_eq!(types.join(a, TypeId::TOP), TypeId::TOP);
+        for &b in &sample {
+            let meet = types.meet(a, b);
+            let join = types.join(a, b);
+            assert_eq!(types.meet(b, a), meet);
+            assert_eq!(types.join(b, a), join);
+            assert_eq!(types.meet(a, join), a, "absorption");
+            assert_eq!(types.join(a, meet), a, "absorption");
+            assert!(types.isa(a, meet) && types.isa(join, a));
+            for &c in &sample {
+                let left = types.meet(meet, c);
+                let right = types.meet(b, c);
+                assert_eq!(left, types.meet(a, right), "meet associativity");
+                let left = types.join(join, c);
+                let right = types.join(b, c);
+                assert_eq!(left, types.join(a, right), "join associativity");
+            }
+        }
+    }
+}
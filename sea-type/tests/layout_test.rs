@@ -0,0 +1,237 @@
+use sea_type::{DataLayout, FloatKind, Layout, PassMode, Reg, Repr, TypeInterner};
+
+/// Make a layout
+const fn layout(size: u64, align: u64) -> Layout {
+    Layout { size, align }
+}
+
+#[test]
+fn test_scalars_and_arrays() {
+    let mut types = TypeInterner::new();
+    let bool = types.int(1, false);
+    let i16 = types.int(16, true);
+    let i64 = types.int(64, true);
+    let i128 = types.int(128, true);
+    let f32 = types.float(FloatKind::F32);
+    let pointer = types.pointer(i16, true);
+    let array = types.array(i16, Some(3));
+    let unsized_array = types.array(i16, None);
+    let void = types.tuple([]);
+    let function = types.function([i16], i16);
+    let undefined = types.struct_type("undefined");
+    let samples = [
+        bool,
+        i16,
+        i64,
+        f32,
+        pointer,
+        array,
+        unsized_array,
+        void,
+        function,
+        undefined,
+        i128,
+    ];
+    let layouts = |target: DataLayout| -> Vec<_> {
+        samples
+            .iter()
+            .map(|&ty| target.layout(&types, ty))
+            .collect()
+    };
+    let lp64 = [
+        Some(layout(1, 1)),
+        Some(layout(2, 2)),
+        Some(layout(8, 8)),
+        Some(layout(4, 4)),
+        Some(layout(8, 8)),
+        Some(layout(6, 2)),
+        None,
+        None,
+        None,
+        None,
+        Some(layout(16, 16)),
+    ];
+    assert_eq!(layouts(DataLayout::X86_64_SYSV), lp64);
+    assert_eq!(layouts(DataLayout::AARCH64_AAPCS64), lp64);
+    let mut ilp32 = lp64;
+    ilp32[4] = Some(layout(4, 4));
+    assert_eq!(layouts(DataLayout::WASM32), ilp32);
+}
+
+#[test]
+fn test_struct_reprs() {
+    let target = DataLayout::X86_64_SYSV;
+    let fields = [layout(1, 1), layout(4, 4), layout(2, 2), layout(8, 8)];
+    let offsets = |repr| {
+        let layout = target.struct_layout(fields, repr);
+        (layout.size, layout.align, layout.offsets)
+    };
+    assert_eq!(offsets(Repr::C), (24, 8, vec![0, 4, 8, 16]));
+    assert_eq!(offsets(Repr::PACKED), (15, 1, vec![0, 1, 5, 7]));
+    assert_eq!(offsets(Repr::packed(2)), (16, 2, vec![0, 2, 6, 8]));
+    assert_eq!(offsets(Repr::C.aligned(32)), (32, 32, vec![0, 4, 8, 16]));
+    assert_eq!(
+        target.struct_layout([], Repr::C.aligned(4)).layout(),
+        layout(0, 4)
+    );
+
+    // Structs nest, but can't contain themselves by value
+    let mut types = TypeInterner::new();
+    let (i8, i32) = (types.int(8, true), types.int(32, true));
+    let inner = types.struct_type("inner");
+    types.define_struct("inner", [("a".to_string(), i8), ("b".to_string(), i32)]);
+    let outer = types.struct_type("outer");
+    let next = types.pointer(outer, true);
+    let fields = [("c".to_string(), i8), ("inner".to_string(), inner)];
+    types.define_struct(
+        "outer",
+        fields.into_iter().chain([("next".to_string(), next)]),
+    );
+    assert_eq!(target.layout(&types, outer), Some(layout(24, 8)));
+    assert_eq!(
+        DataLayout::WASM32.layout(&types, outer),
+        Some(layout(16, 4))
+    );
+
+    // `i128` is 16-byte aligned on every target, so a struct holding one is too
+    let i128 = types.int(128, true);
+    let wide = types.tuple([i8, i128]);
+    for target in [
+        DataLayout::X86_64_SYSV,
+        DataLayout::AARCH64_AAPCS64,
+        DataLayout::WASM32,
+    ] {
+        assert_eq!(target.layout(&types, wide), Some(layout(32, 16)));
+    }
+    let cycle = types.struct_type("cycle");
+    types.define_struct("cycle", [("again".to_string(), cycle)]);
+    assert_eq!(target.layout(&types, cycle), None);
+}
+
+#[test]
+fn test_calling_conventions() {
+    let mut types = TypeInterner::new();
+    let i8 = types.int(8, true);
+    let i32 = types.int(32, true);
+    let i64 = types.int(64, true);
+    let i128 = types.int(128, true);
+    let f32 = types.float(FloatKind::F32);
+    let f64 = types.float(FloatKind::F64);
+    let int_pair = types.tuple([i32, i32]);
+    let mixed = types.tuple([i32, i32, f64]);
+    let floats = types.tuple([f32, f32, f32]);
+    let char_float = types.tuple([i8, f32]);
+    let doubles = types.array(f64, Some(4));
+    let big = types.tuple([i64, i64, i64]);
+    let wrapped = types.tuple([int_pair]);
+    let single = types.tuple([i32]);
+    let nested_single = types.tuple([single]);
+    let empty = types.struct_type("empty");
+    types.define_struct("empty", []);
+    let void = types.tuple([]);
+    // Aligning to the empty array pads these to 16 bytes
+    let no_wide = types.array(i128, Some(0));
+    let padded_float = types.struct_type("padded_float");
+    types.define_struct(
+        "padded_float",
+        [("value".to_string(), f64), ("align".to_string(), no_wide)],
+    );
+    let padded_int = types.tuple([i32, no_wide]);
+    let wrapped_wide = types.tuple([i128]);
+    assert_eq!(
+        DataLayout::X86_64_SYSV.layout(&types, padded_float),
+        Some(layout(16, 16))
+    );
+    let samples = [
+        i32,
+        f64,
+        int_pair,
+        mixed,
+        floats,
+        char_float,
+        doubles,
+        big,
+        wrapped,
+        nested_single,
+        empty,
+        void,
+        i128,
+        padded_float,
+        padded_int,
+        wrapped_wide,
+    ];
+    let classify = |target: DataLayout| -> Vec<_> {
+        samples
+            .iter()
+            .map(|&ty| target.classify(&types, ty))
+            .collect()
+    };
+    let int = |size| Reg::Int { size };
+    let float = |size| Reg::Float { size };
+    let direct = |regs: &[Reg]| Some(PassMode::Direct(regs.to_vec()));
+    let indirect = Some(PassMode::Indirect);
+    let ignore = Some(PassMode::Ignore);
+    assert_eq!(
+        classify(DataLayout::X86_64_SYSV),
+        [
+            direct(&[int(4)]),
+            direct(&[float(8)]),
+            direct(&[int(8)]),
+            direct(&[int(8), float(8)]),
+            direct(&[float(8), float(4)]),
+            direct(&[int(8)]),
+            indirect.clone(),
+            indirect.clone(),
+            direct(&[int(8)]),
+            direct(&[int(4)]),
+            ignore.clone(),
+            None,
+            direct(&[int(8), int(8)]),
+            direct(&[float(8)]),
+            direct(&[int(8)]),
+            direct(&[int(8), int(8)]),
+        ]
+    );
+    assert_eq!(
+        classify(DataLayout::AARCH64_AAPCS64),
+        [
+            direct(&[int(4)]),
+            direct(&[float(8)]),
+            direct(&[int(8)]),
+            direct(&[int(8), int(8)]),
+            direct(&[float(4), float(4), float(4)]),
+            direct(&[int(8)]),
+            direct(&[float(8), float(8), float(8), float(8)]),
+            indirect.clone(),
+            direct(&[int(8)]),
+            direct(&[int(4)]),
+            ignore.clone(),
+            None,
+            direct(&[int(8), int(8)]),
+            direct(&[float(8)]),
+            direct(&[int(8), int(8)]),
+            direct(&[int(8), int(8)]),
+        ]
+    );
+    assert_eq!(
+        classify(DataLayout::WASM32),
+        [
+            direct(&[int(4)]),
+            direct(&[float(8)]),
+            indirect.clone(),
+            indirect.clone(),
+            indirect.clone(),
+            indirect.clone(),
+            indirect.clone(),
+            indirect.clone(),
+            indirect,
+            direct(&[int(4)]),
+            ignore,
+            None,
+            direct(&[int(8), int(8)]),
+            direct(&[float(8)]),
+            direct(&[int(4)]),
+            direct(&[int(8), int(8)]),
+        ]
+    );
+}
@@ -0,0 +1,290 @@
+use sea_lex::{SourceDb, Span};
+use sea_type::{
+    check_match, compile_match, AdtDef, Adts, Case, Decision, Inference, Literal, MatchArm,
+    Occurrence, Pat, Ty, Variant,
+};
+
+/// Define `'a option` and `'a list`, with `Nil` and `Cons of 'a * 'a list`
+fn prelude() -> Adts<&'static str> {
+    let mut inference = Inference::<&str>::new();
+    let mut adts = Adts::new();
+    let a = inference.fresh_var();
+    adts.define(AdtDef {
+        name: "option",
+        params: vec![a],
+        variants: vec![
+            Variant {
+                name: "None".to_string(),
+                fields: vec![],
+            },
+            Variant {
+                name: "Some".to_string(),
+                fields: vec![Ty::Var(a)],
+            },
+        ],
+    });
+    let a = inference.fresh_var();
+    adts.define(AdtDef {
+        name: "list",
+        params: vec![a],
+        variants: vec![
+            Variant {
+                name: "Nil".to_string(),
+                fields: vec![],
+            },
+            Variant {
+                name: "Cons".to_string(),
+                fields: vec![Ty::Var(a), Ty::Con("list", vec![Ty::Var(a)])],
+            },
+        ],
+    });
+    adts
+}
+
+/// `Some pattern`
+fn some(pattern: Pat) -> Pat {
+    Pat::Constructor("Some".to_string(), vec![pattern])
+}
+
+/// `Cons (head, tail)`
+fn cons(head: Pat, tail: Pat) -> Pat {
+    Pat::Constructor("Cons".to_string(), vec![head, tail])
+}
+
+/// A variable pattern
+fn var(name: &str) -> Pat {
+    Pat::Var(name.to_string())
+}
+
+/// Make arms from patterns, each at a span made of its index, guarded if marked
+fn arms(patterns: impl IntoIterator<Item = (Pat, bool)>) -> Vec<MatchArm> {
+    let file = SourceDb::new().add("test.ml", "");
+    patterns
+        .into_iter()
+        .enumerate()
+        .map(|(index, (pattern, guarded))| MatchArm {
+            pattern,
+            guarded,
+            span: Span::new(file, index, index + 1),
+        })
+        .collect()
+}
+
+/// Check a match of unguarded arms, getting each error's message and the index of the
+/// arm it's on, or `None` for the whole match
+fn check(adts: &Adts<&'static str>, patterns: &[Pat]) -> Vec<(String, Option<usize>)> {
+    check_guarded(
+        adts,
+        patterns.iter().map(|pattern| (pattern.clone(), false)),
+    )
+}
+
+/// Check a match, getting each error's message and the index of the arm it's on
+fn check_guarded(
+    adts: &Adts<&'static str>,
+    patterns: impl IntoIterator<Item = (Pat, bool)>,
+) -> Vec<(String, Option<usize>)> {
+    let arms = arms(patterns);
+    let span = Span::new(SourceDb::new().add("test.ml", ""), 100, 100);
+    check_match(adts, &arms, span)
+        .into_iter()
+        .map(|error| {
+            let arm = (error.span != span).then_some(error.span.start);
+            (error.to_string(), arm)
+        })
+        .collect()
+}
+
+#[test]
+fn test_generic_definitions() {
+    let adts = prelude();
+    let list = adts.get(&"list").expect("`list` is defined");
+    let (def, cons) = adts.constructor("Cons").expect("`Cons` is defined");
+    assert_eq!(def, list);
+    assert_eq!(list.ty().to_string(), "'a list");
+    assert_eq!(
+        list.constructor_scheme(cons).to_string(),
+        "'a * 'a list -> 'a list"
+    );
+    assert_eq!(list.constructor_scheme(0).to_string(), "'a list");
+    let fields = list.field_types(cons, &[Ty::con("int")]);
+    let fields: Vec<_> = fields.iter().map(ToString::to_string).collect();
+    assert_eq!(fields, ["int", "int list"]);
+    assert_eq!(list.variant("Nil"), Some(0));
+}
+
+#[test]
+fn test_exhaustiveness() {
+    let adts = prelude();
+    let none = Pat::constant("None");
+    let nil = Pat::constant("Nil");
+    let pair = |a, b| Pat::Tuple(vec![a, b]);
+    let bool = |value| Pat::Literal(Literal::Bool(value));
+
+    assert_eq!(check(&adts, &[none.clone(), some(var("x"))]), []);
+    assert_eq!(
+        check(&adts, &[some(var("x"))]),
+        [("Pattern `None` isn't covered".to_string(), None)]
+    );
+    assert_eq!(
+        check(&adts, &[nil.clone(), cons(var("x"), nil.clone())]),
+        [(
+            "Pattern `Cons (_, Cons (_, _))` isn't covered".to_string(),
+            None
+        )]
+    );
+    assert_eq!(
+        check(
+            &adts,
+            &[
+                pair(none.clone(), none.clone()),
+                pair(some(bool(true)), Pat::Wildcard),
+            ]
+        ),
+        [(
+            "Patterns `(None, Some _)` and `(Some false, _)` aren't covered".to_string(),
+            None
+        )]
+    );
+    assert_eq!(
+        check(
+            &adts,
+            &[
+                Pat::Or(vec![none.clone(), some(bool(false))]),
+                some(bool(true))
+            ]
+        ),
+        []
+    );
+    assert_eq!(
+        check(
+            &adts,
+            &[some(Pat::Literal(Literal::Int(0))), some(Pat::Wildcard)]
+        ),
+        [("Pattern `None` isn't covered".to_string(), None)]
+    );
+    assert_eq!(
+        check(&adts, &[Pat::Literal(Literal::Int(0))]),
+        [("Pattern `_` isn't covered".to_string(), None)]
+    );
+}
+
+#[test]
+fn test_redundancy_and_guards() {
+    let adts = prelude();
+    let none = Pat::constant("None");
+    assert_eq!(
+        check(&adts, &[Pat::Wildcard, none.clone()]),
+        [("This pattern is unreachable".to_string(), Some(1))]
+    );
+    assert_eq!(
+        check(
+            &adts,
+            &[
+                some(Pat::Or(vec![
+                    Pat::Literal(Literal::Int(1)),
+                    Pat::Literal(Literal::Int(2))
+                ])),
+                some(Pat::Literal(Literal::Int(2))),
+                var("x"),
+            ]
+        ),
+        [("This pattern is unreachable".to_string(), Some(1))]
+    );
+
+    // A guarded arm doesn't cover anything, so the match isn't exhaustive without the
+    // last arm, and the last arm isn't redundant
+    let guarded = [(some(var("x")), true), (none.clone(), false)];
+    assert_eq!(
+        check_guarded(&adts, guarded.clone()),
+        [("Pattern `Some _` isn't covered".to_string(), None)]
+    );
+    assert_eq!(
+        check_guarded(&adts, guarded.into_iter().chain([(var("y"), false)])),
+        []
+    );
+
+    assert_eq!(
+        check(
+            &adts,
+            &[
+                Pat::constant("Nothing"),
+                Pat::Constructor("Some".to_string(), vec![]),
+            ]
+        ),
+        [
+            ("No constructor named `Nothing`".to_string(), Some(0)),
+            ("`Some` has 1 field, found 0".to_string(), Some(1)),
+        ]
+    );
+}
+
+#[test]
+fn test_decision_trees() {
+    let adts = prelude();
+    let nil = Pat::constant("Nil");
+    // match (xs, ys) with
+    // | (Nil, _) -> 0
+    // | (_, Nil) -> 1
+    // | (Cons (x, _), Cons (y, _)) when x = y -> 2
+    // | (Cons (x, _), _) -> 3
+    let pairs = arms([
+        (Pat::Tuple(vec![nil.clone(), Pat::Wildcard]), false),
+        (Pat::Tuple(vec![Pat::Wildcard, nil]), false),
+        (
+            Pat::Tuple(vec![
+                cons(var("x"), Pat::Wildcard),
+                cons(var("y"), Pat::Wildcard),
+            ]),
+            true,
+        ),
+        (
+            Pat::Tuple(vec![cons(var("x"), Pat::Wildcard), Pat::Wildcard]),
+            false,
+        ),
+    ]);
+    let decision = compile_match(&adts, &pairs);
+    assert_eq!(
+        decision.to_string(),
+        "\
+switch $.0 {
+    Nil => arm 0
+    Cons => switch $.1 {
+        Nil => arm 1
+        Cons => arm 2 with x = $.0.0, y = $.1.0 if guard, else arm 3 with x = $.0.0
+    }
+}"
+    );
+    let Decision::Switch {
+        occurrence, cases, ..
+    } = &decision
+    else {
+        panic!("expected a switch");
+    };
+    assert_eq!(*occurrence, Occurrence(vec![0]));
+    assert_eq!(
+        cases[1].0,
+        Case::Variant {
+            index: 1,
+            name: "Cons".to_string()
+        }
+    );
+
+    // Literals can't be covered, so they get a default case
+    let literals = arms([
+        (some(Pat::Literal(Literal::Int(1))), false),
+        (some(Pat::Literal(Literal::Int(2))), false),
+    ]);
+    assert_eq!(
+        compile_match(&adts, &literals).to_string(),
+        "\
+switch $ {
+    Some => switch $.0 {
+        1 => arm 0
+        2 => arm 1
+        _ => fail
+    }
+    _ => fail
+}"
+    );
+}
@@ -0,0 +1,177 @@
+use proptest::prelude::*;
+use sea_type::IntRange;
+
+/// Bounds worth testing: the extremes, the widening thresholds, and small numbers
+const BOUNDS: [i64; 9] = [
+    i64::MIN,
+    i32::MIN as i64,
+    -128,
+    -1,
+    0,
+    1,
+    127,
+    255,
+    i64::MAX,
+];
+
+/// Every low and high range between the bounds, from `BOTTOM` to `TOP`
+fn every_range() -> Vec<IntRange> {
+    let mut ranges = Vec::new();
+    for (index, &min) in BOUNDS.iter().enumerate() {
+        for &max in &BOUNDS[index..] {
+            ranges.push(IntRange::new(min, max));
+            if min < max {
+                ranges.push(IntRange::high(min, max));
+            }
+        }
+    }
+    ranges
+}
+
+/// Any range, biased towards interesting bounds
+fn range() -> impl Strategy<Value = IntRange> {
+    let bound = prop_oneof![prop::sample::select(&BOUNDS[..]), -8_i64..8, any::<i64>()];
+    (bound.clone(), bound, any::<bool>()).prop_map(|(a, b, high)| {
+        let range = IntRange::new(a.min(b), a.max(b));
+        if high {
+            range.dual()
+        } else {
+            range
+        }
+    })
+}
+
+/// Check the lattice laws that involve `a`, `b`, and `c`
+fn check_laws(a: IntRange, b: IntRange, c: IntRange) {
+    assert_eq!(a.meet(b), b.meet(a), "meet commutes");
+    assert_eq!(a.join(b), b.join(a), "join commutes");
+    assert_eq!(a.meet(b).meet(c), a.meet(b.meet(c)), "meet associates");
+    assert_eq!(a.join(b).join(c), a.join(b.join(c)), "join associates");
+    assert_eq!(a.meet(a), a, "meet is idempotent");
+    assert_eq!(a.join(a), a, "join is idempotent");
+    assert_eq!(a.meet(a.join(b)), a, "absorption");
+    assert_eq!(a.join(a.meet(b)), a, "absorption");
+    assert_eq!(a.meet(IntRange::TOP), a);
+    assert_eq!(a.meet(IntRange::BOTTOM), IntRange::BOTTOM);
+    assert_eq!(a.join(IntRange::BOTTOM), a);
+    assert_eq!(a.join(IntRange::TOP), IntRange::TOP);
+    assert_eq!(a.dual().dual(), a, "dual is an involution");
+    assert_eq!(
+        a.meet(b).dual(),
+        a.dual().join(b.dual()),
+        "dual swaps meet and join"
+    );
+    assert!(a.isa(a.meet(b)) && a.join(b).isa(a));
+    assert_eq!(a.isa(b), b.dual().isa(a.dual()), "dual reverses the order");
+    let widened = a.widen(b);
+    assert!(
+        a.meet(b).isa(widened),
+        "widening goes at least as low as the meet"
+    );
+}
+
+#[test]
+fn test_lattice_laws_hold_exhaustively() {
+    let ranges = every_range();
+    assert!(ranges.contains(&IntRange::TOP) && ranges.contains(&IntRange::BOTTOM));
+    for &a in &ranges {
+        for &b in &ranges {
+            for &c in &ranges {
+                check_laws(a, b, c);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_constants_and_ranges() {
+    let five = IntRange::constant(5);
+    assert_eq!(five.as_constant(), Some(5));
+    assert_eq!(five.dual(), five);
+    assert_eq!(five.meet(IntRange::constant(7)), IntRange::new(5, 7));
+    assert_eq!(five.join(IntRange::constant(7)), IntRange::high(5, 7));
+    assert!(five.isa(IntRange::new(0, 10)));
+    assert!(IntRange::high(0, 10).isa(five));
+    assert!(!IntRange::high(0, 4).isa(five));
+    // Disjoint high ranges meet at the range between them
+    assert_eq!(
+        IntRange::high(0, 1).meet(IntRange::high(5, 6)),
+        IntRange::new(1, 5)
+    );
+    assert_eq!(IntRange::BOTTOM.to_string(), "int");
+    assert_eq!(IntRange::TOP.to_string(), "~int");
+    assert_eq!(IntRange::high(-1, 3).to_string(), "~int[-1..3]");
+    assert_eq!(IntRange::new(-1, 3).bounds(), (-1, 3));
+    assert_eq!(IntRange::high(-1, 3).bounds(), (-1, 3));
+}
+
+#[test]
+fn test_widening_keeps_constants() {
+    assert_eq!(
+        IntRange::TOP.widen(IntRange::constant(5)),
+        IntRange::constant(5)
+    );
+    assert_eq!(
+        IntRange::TOP.widen(IntRange::constant(-5)),
+        IntRange::constant(-5)
+    );
+    assert_eq!(
+        IntRange::high(0, 10).widen(IntRange::constant(5)),
+        IntRange::constant(5)
+    );
+}
+
+#[test]
+fn test_widening_reaches_a_fixed_point() {
+    // `i = 0; while (...) i = i + 1;` grows by one each iteration
+    let mut approximation = IntRange::constant(0);
+    let mut iterations = 0;
+    loop {
+        let (_, max) = approximation.bounds();
+        let next = approximation.meet(IntRange::new(0, max.saturating_add(1)));
+        let widened = approximation.widen(next);
+        if widened == approximation {
+            break;
+        }
+        approximation = widened;
+        iterations += 1;
+    }
+    assert_eq!(approximation, IntRange::new(0, i64::MAX));
+    assert!(iterations <= 8, "took {iterations} iterations");
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(4096))]
+
+    /// The lattice laws hold for arbitrary ranges
+    #[test]
+    fn test_lattice_laws_hold(a in range(), b in range(), c in range()) {
+        check_laws(a, b, c);
+    }
+
+    /// Widening a high approximation takes the meet
+    #[test]
+    fn test_widening_a_high_range_meets(a in range(), b in range()) {
+        let high = if a.is_high() { a } else { a.dual() };
+        prop_assume!(high.is_high());
+        prop_assert_eq!(high.widen(b), high.meet(b));
+    }
+
+    /// Once an approximation is low, any sequence of widenings stops descending within a
+    /// bounded number of steps
+    #[test]
+    fn test_widening_terminates(start in range(), steps in prop::collection::vec(range(), 1..64)) {
+        let mut approximation = start;
+        let mut changes = 0;
+        for step in steps {
+            let widened = approximation.widen(step);
+            prop_assert!(approximation.isa(widened));
+            if widened != approximation && !approximation.is_high() {
+                changes += 1;
+            }
+            approximation = widened;
+        }
+        // Each bound moves once to a threshold, then only between thresholds
+        prop_assert!(changes <= 2 * (1 + 8), "{changes} changes");
+    }
+}